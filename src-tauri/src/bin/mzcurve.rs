@@ -0,0 +1,265 @@
+use clap::{Parser, Subcommand};
+use std::path::{Path, PathBuf};
+use mz_curve_gui_lib::core::data::ProcessingRequest;
+use mz_curve_gui_lib::core::exporters::ExportManager;
+use mz_curve_gui_lib::core::loaders::mzdata_loader::DataLoader;
+use mz_curve_gui_lib::core::processors::overlay_extractor::process_request;
+use mz_curve_gui_lib::core::utils::batch_checkpoint::{run_batch, BatchCheckpoint};
+use mz_curve_gui_lib::core::utils::stdio_server::RequestServer;
+use mz_curve_gui_lib::core::utils::thread_pool;
+
+/// mzcurve - 质谱数据处理工具
+#[derive(Parser)]
+#[command(name = "mzcurve")]
+#[command(about = "质谱数据处理工具，支持DT曲线提取和峰值分析")]
+#[command(version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+
+    /// 启用详细日志输出
+    #[arg(short, long)]
+    verbose: bool,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// 处理单个文件
+    Process {
+        /// 输入文件路径
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// m/z范围 (格式: min-max)
+        #[arg(short = 'z', long)]
+        mz_range: String,
+
+        /// 保留时间范围 (格式: min-max)
+        #[arg(short = 't', long)]
+        rt_range: String,
+
+        /// MS级别
+        #[arg(short = 'l', long, default_value = "1")]
+        ms_level: u8,
+
+        /// 处理模式 (dt, tic, xic, peak)
+        #[arg(short, long, default_value = "dt")]
+        mode: String,
+
+        /// 输出文件路径 (.tsv 或 .json)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// 批量处理目录中的 mzML 文件，每个文件的结果导出为输出目录中的 JSON
+    Batch {
+        /// 输入目录
+        #[arg(short, long)]
+        input_dir: PathBuf,
+
+        /// 输出目录（检查点也保存在这里）
+        #[arg(short, long)]
+        output_dir: PathBuf,
+
+        /// m/z范围 (格式: min-max)
+        #[arg(short = 'z', long)]
+        mz_range: String,
+
+        /// 保留时间范围 (格式: min-max)
+        #[arg(short = 't', long)]
+        rt_range: String,
+
+        /// MS级别
+        #[arg(short = 'l', long, default_value = "1")]
+        ms_level: u8,
+
+        /// 处理模式 (dt, tic, xic, peak)
+        #[arg(short, long, default_value = "dt")]
+        mode: String,
+
+        /// 从输出目录中的检查点恢复，跳过已完成的文件
+        #[arg(long)]
+        resume: bool,
+
+        /// 同时处理的文件数，默认为可用的并行度
+        #[arg(short, long)]
+        jobs: Option<usize>,
+    },
+
+    /// 验证文件格式
+    Validate {
+        /// 要验证的文件路径
+        #[arg(short, long)]
+        file: PathBuf,
+    },
+
+    /// 转换已导出的结果文件格式 (TSV/JSON)，输出格式由扩展名决定
+    Convert {
+        /// 输入文件路径 (.tsv 或 .json)
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// 输出文件路径 (.tsv 或 .json)
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+
+    /// 常驻服务：从标准输入逐行读取 JSON 请求，向标准输出逐行写出 JSON 响应
+    Serve,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+
+    // 初始化日志（写到标准错误）
+    let level = if cli.verbose { log::LevelFilter::Debug } else { log::LevelFilter::Info };
+    env_logger::Builder::from_default_env()
+        .filter_level(level)
+        .init();
+
+    match cli.command {
+        Commands::Process { input, mz_range, rt_range, ms_level, mode, output } => {
+            let request = ProcessingRequest { file_path: input.to_string_lossy().to_string(), mz_range, rt_range, ms_level, mode };
+            process_single_file(request, output).await?;
+        }
+        Commands::Batch { input_dir, output_dir, mz_range, rt_range, ms_level, mode, resume, jobs } => {
+            let template = ProcessingRequest { file_path: String::new(), mz_range, rt_range, ms_level, mode };
+            let jobs = jobs.unwrap_or_else(thread_pool::default_pool_size);
+            process_batch_files(input_dir, output_dir, template, resume, jobs).await?;
+        }
+        Commands::Validate { file } => {
+            validate_file(file).await?;
+        }
+        Commands::Convert { input, output } => {
+            convert_file(input, output).await?;
+        }
+        Commands::Serve => {
+            serve_stdio().await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn process_single_file(
+    request: ProcessingRequest,
+    output: Option<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    log::info!("开始处理文件: {}", request.file_path);
+
+    let container = DataLoader::load_from_file(&request.file_path)?;
+    let result = process_request(container, &request).await?;
+
+    log::info!("处理完成: {} 条曲线, {} 个峰值", result.curve_count(), result.total_peak_count());
+
+    if let Some(output_path) = output {
+        let exporter_name = ExportManager::exporter_for_path(&output_path)?;
+        let exported = ExportManager::new()
+            .export(exporter_name, &result, serde_json::json!({}))
+            .await?;
+        std::fs::write(&output_path, &exported.data)?;
+        log::info!("导出结果到: {:?} ({} 字节)", output_path, exported.data.len());
+    }
+
+    Ok(())
+}
+
+async fn process_batch_files(
+    input_dir: PathBuf,
+    output_dir: PathBuf,
+    template: ProcessingRequest,
+    resume: bool,
+    jobs: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    log::info!("批量处理目录: {:?} -> {:?}（并发 {}）", input_dir, output_dir, jobs);
+
+    std::fs::create_dir_all(&output_dir)?;
+
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(&input_dir)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("mzML") {
+            files.push(path.to_string_lossy().to_string());
+        }
+    }
+    files.sort();
+
+    // 检查点保存在输出目录中，文件处理并导出成功后才记为完成
+    let checkpoint_path = BatchCheckpoint::path_in(&output_dir);
+    let mut checkpoint = if resume {
+        BatchCheckpoint::load(&checkpoint_path)?
+    } else {
+        BatchCheckpoint::new(&checkpoint_path)
+    };
+
+    let export_manager = std::sync::Arc::new(ExportManager::new());
+    let run = run_batch(&files, &mut checkpoint, jobs, || false, |file| {
+        let request = ProcessingRequest { file_path: file, ..template.clone() };
+        let export_manager = export_manager.clone();
+        let output_dir = output_dir.clone();
+        async move {
+            log::info!("处理文件: {}", request.file_path);
+            let container = DataLoader::load_from_file(&request.file_path).map_err(|e| e.to_string())?;
+            let result = process_request(container, &request).await.map_err(|e| e.to_string())?;
+            let exported = export_manager
+                .export("json", &result, serde_json::json!({}))
+                .await
+                .map_err(|e| e.to_string())?;
+
+            let stem = Path::new(&request.file_path).file_stem().unwrap_or_default().to_string_lossy().to_string();
+            std::fs::write(output_dir.join(format!("{}.json", stem)), &exported.data).map_err(|e| e.to_string())?;
+            Ok(result.total_peak_count())
+        }
+    }).await;
+
+    log::info!("批量处理完成: 成功 {} 个，跳过 {} 个，失败 {} 个",
+        run.processed.len(), run.skipped.len(), run.failed.len());
+    for (file, e) in &run.failed {
+        log::error!("  - {}: {}", file, e);
+    }
+    if !run.failed.is_empty() {
+        return Err(format!("{} 个文件处理失败，可使用 --resume 重试", run.failed.len()).into());
+    }
+
+    Ok(())
+}
+
+async fn validate_file(file: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    log::info!("验证文件: {:?}", file);
+
+    // 尝试加载文件来验证格式
+    let container = DataLoader::load_from_file(&file.to_string_lossy())?;
+
+    log::info!("文件验证成功:");
+    log::info!("  - 光谱数量: {}", container.spectrum_count());
+    log::info!("  - 曲线数量: {}", container.curve_count());
+    log::info!("  - 峰值数量: {}", container.total_peak_count());
+
+    Ok(())
+}
+
+async fn convert_file(input: PathBuf, output: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    log::info!("转换文件: {:?} -> {:?}", input, output);
+
+    let export_manager = ExportManager::new();
+    let result = export_manager.convert_file(&input, &output).await?;
+
+    log::info!("转换完成: {} ({} 字节)", result.filename, result.data.len());
+    log::info!("  - 曲线数量: {}", result.metadata.get("curve_count").cloned().unwrap_or_default());
+    log::info!("  - 峰值数量: {}", result.metadata.get("peak_count").cloned().unwrap_or_default());
+
+    Ok(())
+}
+
+async fn serve_stdio() -> Result<(), Box<dyn std::error::Error>> {
+    // 日志写到标准错误，标准输出只用于响应
+    log::info!("服务模式启动，等待标准输入中的请求");
+
+    let reader = tokio::io::BufReader::new(tokio::io::stdin());
+    let handled = RequestServer::new().serve(reader, tokio::io::stdout()).await?;
+
+    log::info!("输入结束，共处理 {} 个请求", handled);
+
+    Ok(())
+}
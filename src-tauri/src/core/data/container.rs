@@ -2,8 +2,10 @@ use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
 use mzdata::prelude::SpectrumLike;
 
-use super::curve::Curve;
+use super::curve::{AreaMethod, Curve};
 use super::peak::Peak;
+use super::processing::ProcessingError;
+use crate::core::processors::projection::{project_spectra, ProjectionAxis, Reducer, SpectrumFilter};
 
 /// Universal data container - does not directly serialize mzdata types
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +17,17 @@ pub struct DataContainer {
     // 注意：peaks 现在嵌套在 curves 中，实现树状结构
 }
 
+/// Summary of a `DataContainer::merge`
+/// 合并结果：新增/去重的曲线数、新增的峰数，以及值冲突的元数据键
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MergeReport {
+    pub curves_added: usize,
+    pub curves_deduplicated: usize,
+    pub peaks_added: usize,
+    /// 两侧都存在但值不同的元数据键，合并后保留原容器的值
+    pub metadata_conflicts: Vec<String>,
+}
+
 /// 用于序列化的数据容器，不包含复杂的 mzdata 类型
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SerializableDataContainer {
@@ -102,6 +115,11 @@ impl SerializableDataContainer {
         self.curves.iter().map(|curve| curve.peak_count()).sum()
     }
     
+    /// Sum of all peak areas across all curves
+    pub fn total_integrated_area(&self) -> f64 {
+        self.curves.iter().map(|curve| curve.total_integrated_area()).sum()
+    }
+    
     /// Add metadata
     pub fn add_metadata(&mut self, key: String, value: serde_json::Value) {
         self.metadata.insert(key, value);
@@ -166,6 +184,11 @@ impl DataContainer {
         self.curves.iter().map(|curve| curve.peak_count()).sum()
     }
     
+    /// Sum of all peak areas across all curves
+    pub fn total_integrated_area(&self) -> f64 {
+        self.curves.iter().map(|curve| curve.total_integrated_area()).sum()
+    }
+    
     /// Get the number of spectra
     pub fn spectrum_count(&self) -> usize {
         self.spectra.len()
@@ -178,6 +201,53 @@ impl DataContainer {
         self.curves.clear();
     }
     
+    /// Merge another container into this one
+    /// 追加光谱和曲线；ID相同的曲线只保留一条，并入对方曲线中ID不重复的峰；
+    /// 元数据键冲突时保留本容器的值并在报告中列出
+    pub fn merge(&mut self, other: DataContainer) -> MergeReport {
+        let mut report = MergeReport::default();
+        
+        self.spectra.extend(other.spectra);
+        
+        for curve in other.curves {
+            if let Some(existing) = self.curves.iter_mut().find(|c| c.id == curve.id) {
+                report.curves_deduplicated += 1;
+                for peak in curve.peaks {
+                    if !existing.peaks.iter().any(|p| p.id == peak.id) {
+                        existing.add_peak(peak);
+                        report.peaks_added += 1;
+                    }
+                }
+            } else {
+                report.peaks_added += curve.peak_count();
+                report.curves_added += 1;
+                self.curves.push(curve);
+            }
+        }
+        
+        for (key, value) in other.metadata {
+            match self.metadata.get(&key) {
+                Some(existing) if *existing != value => report.metadata_conflicts.push(key),
+                Some(_) => {}
+                None => {
+                    self.metadata.insert(key, value);
+                }
+            }
+        }
+        report.metadata_conflicts.sort();
+        
+        report
+    }
+    
+    /// Recompute all peak areas with one method; area percentages are per curve
+    /// 返回重新计算的峰数量
+    pub fn normalize_peak_areas(&mut self, method: AreaMethod) -> usize {
+        for curve in &mut self.curves {
+            curve.normalize_peak_areas(method);
+        }
+        self.total_peak_count()
+    }
+    
     /// Get curves by type
     pub fn get_curves_by_type(&self, curve_type: &str) -> Vec<&Curve> {
         self.curves.iter()
@@ -204,6 +274,74 @@ impl DataContainer {
         self.metadata.get(key)
     }
     
+    /// MS levels present in the data, sorted ascending
+    /// 优先使用加载时记录的 `ms_levels` 元数据，没有时从光谱统计
+    pub fn available_ms_levels(&self) -> Vec<u8> {
+        if let Some(levels) = self.metadata.get("ms_levels").and_then(|v| v.as_array()) {
+            return levels.iter().filter_map(|v| v.as_u64()).map(|v| v as u8).collect();
+        }
+        
+        let mut levels: Vec<u8> = self.spectra.iter().map(|s| s.ms_level()).collect();
+        levels.sort_unstable();
+        levels.dedup();
+        levels
+    }
+    
+    /// Check that the data contains spectra at the requested MS level
+    /// 请求的MS级别不存在时返回列出可用级别的错误，而不是静默得到空曲线
+    pub fn ensure_ms_level(&self, ms_level: u8) -> Result<(), ProcessingError> {
+        let levels = self.available_ms_levels();
+        if levels.is_empty() || levels.contains(&ms_level) {
+            return Ok(());
+        }
+        
+        let available: Vec<String> = levels.iter().map(|level| format!("MS{}", level)).collect();
+        Err(ProcessingError::DataError(format!(
+            "文件中没有 MS{} 光谱，可用的MS级别: {}", ms_level, available.join(", ")
+        )))
+    }
+    
+    /// Compute the total ion current (TIC) overview curve from the loaded spectra
+    /// 从原始光谱计算整次运行的TIC概览曲线
+    pub fn compute_tic(&self, ms_level: Option<u8>) -> Option<Curve> {
+        self.compute_overview_curve("TIC", ms_level, Reducer::Sum)
+    }
+    
+    /// Compute the base peak chromatogram (BPC) overview curve from the loaded spectra
+    /// 从原始光谱计算整次运行的BPC概览曲线
+    pub fn compute_bpc(&self, ms_level: Option<u8>) -> Option<Curve> {
+        self.compute_overview_curve("BPC", ms_level, Reducer::Max)
+    }
+    
+    /// 按保留时间归约每张光谱的强度（不分箱），生成概览曲线
+    fn compute_overview_curve(&self, curve_type: &str, ms_level: Option<u8>, reducer: Reducer) -> Option<Curve> {
+        let spectra: Vec<&mzdata::spectrum::Spectrum> = self.spectra.iter().collect();
+        let filter = SpectrumFilter { ms_level, ..SpectrumFilter::default() };
+        let points = project_spectra(&spectra, &filter, ProjectionAxis::retention_time().unbinned(), reducer);
+        
+        if points.is_empty() {
+            return None;
+        }
+        
+        let mut curve = Curve::new(
+            format!("overview_{}", curve_type.to_lowercase()),
+            curve_type.to_string(),
+            points.iter().map(|(x, _)| *x).collect(),
+            points.iter().map(|(_, y)| *y).collect(),
+            "Retention Time".to_string(),
+            "Intensity".to_string(),
+            "min".to_string(),
+            "counts".to_string(),
+        );
+        curve.ms_level = ms_level;
+        if let (Some(first), Some(last)) = (points.first(), points.last()) {
+            curve.set_rt_range(first.0, last.0);
+        }
+        curve.add_metadata("overview".to_string(), serde_json::json!(true));
+        
+        Some(curve)
+    }
+    
     // === Memory management optimization methods ===
     
     /// Remove processed spectra to free memory
@@ -323,3 +461,95 @@ impl DataContainer {
         recommendations
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::data::PeakType;
+
+    fn curve_with_peaks(id: &str, curve_type: &str, peak_ids: &[&str]) -> Curve {
+        let mut curve = Curve::new(
+            id.to_string(),
+            curve_type.to_string(),
+            vec![0.0, 1.0, 2.0],
+            vec![0.0, 10.0, 0.0],
+            "X".to_string(),
+            "Intensity".to_string(),
+            "ms".to_string(),
+            "counts".to_string(),
+        );
+        for peak_id in peak_ids {
+            curve.add_peak(Peak::new(peak_id.to_string(), id.to_string(), 1.0, 10.0, PeakType::Gaussian));
+        }
+        curve
+    }
+
+    #[test]
+    fn test_merge_combines_curves_peaks_and_metadata() {
+        let mut dt = DataContainer::new();
+        dt.add_curve(curve_with_peaks("dt_1", "DT", &["p1", "p2"]));
+        dt.add_curve(curve_with_peaks("shared", "DT", &["s1"]));
+        dt.add_metadata("dt_source".to_string(), serde_json::json!("a.mzML"));
+        dt.add_metadata("ms_level".to_string(), serde_json::json!(1));
+
+        let mut xic = DataContainer::new();
+        xic.add_curve(curve_with_peaks("xic_1", "XIC", &["p3"]));
+        xic.add_curve(curve_with_peaks("shared", "DT", &["s1", "s2"]));
+        xic.add_metadata("xic_source".to_string(), serde_json::json!("b.mzML"));
+        xic.add_metadata("ms_level".to_string(), serde_json::json!(2));
+
+        let report = dt.merge(xic);
+
+        assert_eq!(dt.curve_count(), 3);
+        assert_eq!(dt.total_peak_count(), 5);
+        assert_eq!(report.curves_added, 1);
+        assert_eq!(report.curves_deduplicated, 1);
+        assert_eq!(report.peaks_added, 2);
+        assert!(dt.get_metadata("dt_source").is_some());
+        assert!(dt.get_metadata("xic_source").is_some());
+        assert_eq!(report.metadata_conflicts, vec!["ms_level".to_string()]);
+        assert_eq!(dt.get_metadata("ms_level"), Some(&serde_json::json!(1)));
+    }
+
+    #[test]
+    fn test_normalize_peak_areas_uses_one_method() {
+        let x_values: Vec<f64> = (0..400).map(|i| i as f64 * 0.05).collect();
+        let y_values: Vec<f64> = x_values.iter()
+            .map(|&x| 5.0 + 100.0 * (-0.5 * ((x - 6.0) / 0.4).powi(2)).exp() + 40.0 * (-0.5 * ((x - 13.0) / 0.6).powi(2)).exp())
+            .collect();
+        let mut curve = Curve::new(
+            "curve".to_string(), "DT".to_string(), x_values, y_values,
+            "Drift Time".to_string(), "Intensity".to_string(), "ms".to_string(), "counts".to_string(),
+        );
+        // 两个峰的面积来自不同路径：一个有拟合参数，一个只有检测结果
+        let mut fitted = Peak::new("fitted".to_string(), "curve".to_string(), 6.0, 100.0, PeakType::Gaussian);
+        fitted.fwhm = 0.4 * 2.355;
+        fitted.set_fit_parameters(vec![100.0, 6.0, 0.4], vec![1.0, 0.01, 0.01], None);
+        fitted.calculate_area_from_fit();
+        let mut detected = Peak::new("detected".to_string(), "curve".to_string(), 13.0, 40.0, PeakType::Gaussian);
+        detected.fwhm = 0.6 * 2.355;
+        detected.left_boundary = 11.0;
+        detected.right_boundary = 15.0;
+        detected.area = 40.0 * 1.4;
+        curve.add_peak(fitted);
+        curve.add_peak(detected);
+        let mut container = DataContainer::new();
+        container.add_curve(curve);
+
+        for name in ["analytic", "trapezoid", "local_baseline"] {
+            let method = AreaMethod::from_name(name).unwrap();
+            assert_eq!(container.normalize_peak_areas(method), 2);
+
+            let peaks = &container.curves[0].peaks;
+            assert!(peaks.iter().all(|p| p.metadata["area_method"] == name), "{}", name);
+            let total: f64 = peaks.iter().map(|p| p.area_percentage).sum();
+            assert!((total - 100.0).abs() < 1e-6, "{}: {}", name, total);
+        }
+
+        // 扣除局部基线后，检测峰的面积接近高斯解析值 A·σ·√(2π)
+        let detected = &container.curves[0].peaks[1];
+        let expected = 40.0 * 0.6 * (2.0 * std::f64::consts::PI).sqrt();
+        assert!((detected.area / expected - 1.0).abs() < 0.05, "area {}", detected.area);
+        assert!(AreaMethod::from_name("simpson").is_err());
+    }
+}
@@ -1,6 +1,82 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use super::peak::Peak;
+use super::processing::ProcessingError;
+
+/// Peak area integration method used by `Curve::normalize_peak_areas`
+/// 峰面积计算方法：拟合参数解析式、边界内梯形积分、扣除边界连线基线后的梯形积分
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AreaMethod {
+    Analytic,
+    Trapezoid,
+    LocalBaseline,
+}
+
+impl AreaMethod {
+    /// Parse "analytic" / "trapezoid" / "local_baseline"
+    pub fn from_name(name: &str) -> Result<Self, ProcessingError> {
+        match name {
+            "analytic" => Ok(Self::Analytic),
+            "trapezoid" => Ok(Self::Trapezoid),
+            "local_baseline" => Ok(Self::LocalBaseline),
+            _ => Err(ProcessingError::ConfigError(format!(
+                "不支持的峰面积方法: {}（可选 analytic、trapezoid、local_baseline）", name
+            ))),
+        }
+    }
+    
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Analytic => "analytic",
+            Self::Trapezoid => "trapezoid",
+            Self::LocalBaseline => "local_baseline",
+        }
+    }
+}
+
+/// Axis label / unit overrides applied to extracted curves
+/// 坐标轴标签与单位覆盖：漂移维实际为 bin、强度为任意单位等情况下替换提取器的默认值，未设置的项保持不变
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AxisLabels {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x_label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub y_label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x_unit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub y_unit: Option<String>,
+}
+
+impl AxisLabels {
+    /// Read overrides from a processor config; other config keys are ignored
+    pub fn from_config(config: &serde_json::Value) -> Self {
+        serde_json::from_value(config.clone()).unwrap_or_default()
+    }
+    
+    /// Write the set overrides into a processor config object
+    pub fn merge_into(&self, config: &mut serde_json::Value) {
+        if let (Some(target), Ok(serde_json::Value::Object(labels))) = (config.as_object_mut(), serde_json::to_value(self)) {
+            target.extend(labels);
+        }
+    }
+    
+    /// Replace the curve's labels and units with the set overrides
+    pub fn apply(&self, curve: &mut Curve) {
+        if let Some(x_label) = &self.x_label {
+            curve.x_label = x_label.clone();
+        }
+        if let Some(y_label) = &self.y_label {
+            curve.y_label = y_label.clone();
+        }
+        if let Some(x_unit) = &self.x_unit {
+            curve.x_unit = x_unit.clone();
+        }
+        if let Some(y_unit) = &self.y_unit {
+            curve.y_unit = y_unit.clone();
+        }
+    }
+}
 
 /// Curve data - contains complete scientific parameters
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -158,6 +234,14 @@ impl Curve {
         self.mz_range = Some((mz_min, mz_max));
     }
     
+    /// Record the MS level and acquisition polarity of the spectra the curve was extracted from
+    pub fn set_acquisition(&mut self, ms_level: u8, polarity: Option<&str>) {
+        self.ms_level = Some(ms_level);
+        if let Some(polarity) = polarity {
+            self.metadata.insert("polarity".to_string(), serde_json::Value::String(polarity.to_string()));
+        }
+    }
+    
     /// Set retention time range
     pub fn set_rt_range(&mut self, rt_min: f64, rt_max: f64) {
         self.rt_range = Some((rt_min, rt_max));
@@ -299,6 +383,88 @@ impl Curve {
             .collect()
     }
     
+    /// Recompute every peak's area with one method and refresh `area_percentage`
+    /// 积分区间为峰边界，边界缺失时取中心 ±1.5·FWHM；没有拟合参数的峰按高斯解析式由峰高与FWHM计算；
+    /// 面积百分比以本曲线所有峰面积之和为 100%，使用的方法记录在峰元数据 `area_method` 中
+    pub fn normalize_peak_areas(&mut self, method: AreaMethod) {
+        for i in 0..self.peaks.len() {
+            let area = match method {
+                AreaMethod::Analytic => {
+                    let peak = &mut self.peaks[i];
+                    if peak.fit_parameters.is_empty() {
+                        // 高斯：A·FWHM·√(π / (4 ln2))
+                        peak.area = peak.amplitude * peak.fwhm * (std::f64::consts::PI / (4.0 * std::f64::consts::LN_2)).sqrt();
+                    } else {
+                        peak.calculate_area_from_fit();
+                    }
+                    peak.area
+                }
+                AreaMethod::Trapezoid | AreaMethod::LocalBaseline => {
+                    let (left, right) = self.peak_integration_bounds(&self.peaks[i]);
+                    self.integrate_range(left, right, method == AreaMethod::LocalBaseline)
+                }
+            };
+            let peak = &mut self.peaks[i];
+            peak.area = area;
+            peak.add_metadata("area_method".to_string(), serde_json::json!(method.name()));
+        }
+        
+        let total_area: f64 = self.peaks.iter().map(|peak| peak.area).sum();
+        for peak in &mut self.peaks {
+            peak.area_percentage = if total_area > 0.0 { peak.area / total_area * 100.0 } else { 0.0 };
+        }
+    }
+    
+    /// Integration bounds of a peak: its boundaries, or center ± 1.5·FWHM when unset
+    fn peak_integration_bounds(&self, peak: &Peak) -> (f64, f64) {
+        if peak.right_boundary > peak.left_boundary {
+            (peak.left_boundary, peak.right_boundary)
+        } else {
+            (peak.center - 1.5 * peak.fwhm, peak.center + 1.5 * peak.fwhm)
+        }
+    }
+    
+    /// Trapezoidal area of the points within [left, right], optionally above the straight line joining the end points
+    fn integrate_range(&self, left: f64, right: f64, subtract_baseline: bool) -> f64 {
+        let points: Vec<(f64, f64)> = self.x_values.iter().zip(&self.y_values)
+            .filter(|&(&x, _)| x >= left && x <= right)
+            .map(|(&x, &y)| (x, y))
+            .collect();
+        if points.len() < 2 {
+            return 0.0;
+        }
+        
+        let (x0, y0) = points[0];
+        let (x1, y1) = points[points.len() - 1];
+        let baseline = |x: f64| if subtract_baseline && x1 > x0 { y0 + (y1 - y0) * (x - x0) / (x1 - x0) } else { 0.0 };
+        let area: f64 = points.windows(2)
+            .map(|w| (w[1].0 - w[0].0) * ((w[0].1 - baseline(w[0].0)) + (w[1].1 - baseline(w[1].0))) / 2.0)
+            .sum();
+        area.max(0.0)
+    }
+    
+    /// Unit of peak areas on this curve: intensity unit × X unit, omitting whichever is empty
+    pub fn area_unit(&self) -> String {
+        [self.y_unit.as_str(), self.x_unit.as_str()]
+            .into_iter()
+            .filter(|unit| !unit.is_empty())
+            .collect::<Vec<_>>()
+            .join("·")
+    }
+    
+    /// Stamp every peak's `area_unit` with this curve's area unit
+    pub fn stamp_area_units(&mut self) {
+        let unit = self.area_unit();
+        for peak in &mut self.peaks {
+            peak.area_unit = Some(unit.clone());
+        }
+    }
+    
+    /// Sum of all peak areas on this curve
+    pub fn total_integrated_area(&self) -> f64 {
+        self.peaks.iter().map(|peak| peak.area).sum()
+    }
+    
     /// Get peaks with quality score above threshold
     pub fn get_high_quality_peaks(&self, threshold: f64) -> Vec<&Peak> {
         self.peaks.iter()
@@ -7,17 +7,20 @@
 //! - `curve.rs`: Curve data structure with scientific parameters
 //! - `peak.rs`: Peak data structure with high-precision parameters
 //! - `processing.rs`: Processing results, errors, and configuration
+//! - `provenance.rs`: Processing provenance chain (file checksum, versions, stages)
 
 pub mod container;
 pub mod curve;
 pub mod peak;
 pub mod processing;
+pub mod provenance;
 
 // Re-export the main types for convenience
-pub use container::{DataContainer, SerializableDataContainer};
-pub use curve::Curve;
+pub use container::{DataContainer, SerializableDataContainer, MergeReport};
+pub use curve::{AreaMethod, AxisLabels, Curve};
 pub use peak::{Peak, PeakType, DetectionAlgorithm};
 pub use processing::{ProcessingResult, ProcessingError, ProcessingProgress, ProcessingConfig, ProcessingStatus};
+pub use provenance::{Provenance, ProvenanceRegistry, ProvenanceStage};
 
 /// 处理请求参数
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -16,6 +16,8 @@ pub enum PeakType {
     VoigtExponentialTail,
     /// Pearson-IV distribution - Pearson-IV分布峰
     PearsonIV,
+    /// Pearson-VII distribution - Pearson-VII重尾峰
+    PearsonVII,
     /// Non-Linear Curve - 非线性曲线峰
     NLC,
     /// GMG Bayesian - GMG贝叶斯峰
@@ -48,6 +50,12 @@ pub struct Peak {
     pub amplitude: f64,
     /// Peak area (intensity × time unit, precision: 1e-3)
     pub area: f64,
+    /// Peak area standard uncertainty, propagated from the fit parameter errors (0 if not fitted)
+    #[serde(default)]
+    pub area_error: f64,
+    /// Unit of the area, derived from the curve's intensity and X units (e.g. "counts·ms")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub area_unit: Option<String>,
     
     // === Peak width parameters (high precision) ===
     /// Full Width at Half Maximum (precision: 1e-6)
@@ -68,6 +76,9 @@ pub struct Peak {
     pub right_hwhm: f64,
     /// Peak shape asymmetry factor (right_hwhm / left_hwhm, precision: 1e-4)
     pub asymmetry_factor: f64,
+    /// USP tailing factor (W0.05 / 2f at 5% height, 0 if not computed, precision: 1e-4)
+    #[serde(default)]
+    pub tailing_factor_usp: f64,
     
     // === Peak boundary parameters (high precision) ===
     /// Left boundary position (peak start position, precision: 1e-6)
@@ -118,6 +129,9 @@ pub struct Peak {
     pub drift_time: Option<f64>,
     /// MS level
     pub ms_level: Option<u8>,
+    /// Acquisition polarity of the source spectra ("positive" / "negative" / "mixed")
+    #[serde(default)]
+    pub polarity: Option<String>,
     
     // === Detection parameters ===
     /// Detection algorithm name
@@ -134,6 +148,12 @@ pub struct Peak {
     pub fit_parameter_errors: Vec<f64>,
     /// Fit covariance matrix (precision: 1e-6)
     pub fit_covariance_matrix: Option<Vec<Vec<f64>>>,
+    /// Posterior 95% credible intervals of the fit parameters (Bayesian fits only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fit_credible_intervals: Option<Vec<(f64, f64)>>,
+    /// Overlap cluster ID, shared by peaks whose fit regions overlap
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cluster_id: Option<String>,
     
     // === Metadata ===
     pub metadata: HashMap<String, serde_json::Value>,
@@ -154,6 +174,8 @@ impl Peak {
             center,
             amplitude,
             area: 0.0,
+            area_error: 0.0,
+            area_unit: None,
             fwhm: 0.0,
             hwhm: 0.0,
             sigma: 0.0,
@@ -162,6 +184,7 @@ impl Peak {
             left_hwhm: 0.0,
             right_hwhm: 0.0,
             asymmetry_factor: 1.0,
+            tailing_factor_usp: 0.0,
             left_boundary: center,
             right_boundary: center,
             peak_span: 0.0,
@@ -181,12 +204,15 @@ impl Peak {
             retention_time: None,
             drift_time: None,
             ms_level: None,
+            polarity: None,
             detection_algorithm: DetectionAlgorithm::Simple,
             detection_threshold: 0.0,
             confidence: 0.0,
             fit_parameters: Vec::new(),
             fit_parameter_errors: Vec::new(),
             fit_covariance_matrix: None,
+            fit_credible_intervals: None,
+            cluster_id: None,
             metadata: HashMap::new(),
         }
     }
@@ -203,6 +229,16 @@ impl Peak {
         self.peak_span = self.right_boundary - self.left_boundary;
     }
     
+    /// Region covered by the peak: the boundaries when set, otherwise center ± half of the width
+    pub fn region(&self) -> (f64, f64) {
+        if self.right_boundary > self.left_boundary {
+            (self.left_boundary, self.right_boundary)
+        } else {
+            let half_width = self.fwhm.max(self.peak_span) / 2.0;
+            (self.center - half_width, self.center + half_width)
+        }
+    }
+    
     /// Calculate derivative ratio
     pub fn calculate_derivative_ratio(&mut self) {
         if self.left_derivative != 0.0 {
@@ -292,32 +328,31 @@ impl Peak {
                     self.area = voigt_area + tail_area;
                 }
             }
-            PeakType::PearsonIV => {
-                if self.fit_parameters.len() >= 5 {
-                    let amplitude = self.fit_parameters[0];
-                    let a = self.fit_parameters[2]; // 形状参数
-                    let b = self.fit_parameters[3]; // 形状参数
-                    let _c = self.fit_parameters[4]; // 形状参数
-                    // Pearson-IV面积计算（简化版本）
-                    self.area = amplitude * (std::f64::consts::PI * 2.0).sqrt() * (1.0 + a * a / (b * b)).sqrt();
+            PeakType::PearsonIV | PeakType::PearsonVII => {
+                if self.fit_parameters.len() >= pearson_parameter_count(&self.peak_type) {
+                    // Pearson面积没有简单闭式，按峰形函数数值积分
+                    self.area = pearson_area(&self.peak_type, &self.fit_parameters);
                 }
             }
             PeakType::NLC => {
                 if self.fit_parameters.len() >= 4 {
                     let amplitude = self.fit_parameters[0];
                     let sigma = self.fit_parameters[2];
-                    let nonlinear_param = self.fit_parameters[3];
-                    // 非线性曲线面积计算（简化版本）
-                    self.area = amplitude * sigma * (std::f64::consts::PI * 2.0).sqrt() * (1.0 + nonlinear_param);
+                    // NLC（Haarhoff-Van der Linde）面积与畸变项无关：A·σ·√(2π)，即 NLCCalculator 峰形的精确积分。
+                    // 早期实现按 A·σ·√(2π)·(1 + distortion) 计算，前沿峰（distortion < 0）面积偏小、拖尾峰偏大，
+                    // NLC 峰面积因此与旧版本不同
+                    self.area = amplitude * sigma * (std::f64::consts::PI * 2.0).sqrt();
                 }
             }
             PeakType::GMGBayesian => {
                 if self.fit_parameters.len() >= 4 {
                     let amplitude = self.fit_parameters[0];
                     let sigma = self.fit_parameters[2];
-                    let bayesian_weight = self.fit_parameters[3];
-                    // GMG贝叶斯面积计算
-                    self.area = amplitude * sigma * (std::f64::consts::PI * 2.0).sqrt() * bayesian_weight;
+                    let modifier = self.fit_parameters[3];
+                    // GMG面积：A·ω·√(2π)，ω = √(sigma² + modifier²)，即 GMGCalculator 峰形的精确积分。
+                    // 第4个参数是第二个高斯的宽度 modifier；早期实现把它当作"贝叶斯权重"直接乘在 A·σ·√(2π) 上，
+                    // 与峰形函数不符，GMG 峰面积因此与旧版本不同
+                    self.area = amplitude * (sigma.powi(2) + modifier.powi(2)).sqrt() * (std::f64::consts::PI * 2.0).sqrt();
                 }
             }
             _ => {
@@ -325,6 +360,107 @@ impl Peak {
                 self.area = self.amplitude * self.fwhm * 0.5;
             }
         }
+        self.calculate_area_error_from_fit();
+    }
+    
+    /// Propagate fit parameter errors into the area uncertainty
+    ///
+    /// σ_area² = Jᵀ·C·J, where J holds the partial derivatives of the area formula used by
+    /// `calculate_area_from_fit`; falls back to the diagonal (uncorrelated errors) when no
+    /// covariance matrix matches the parameters
+    pub fn calculate_area_error_from_fit(&mut self) {
+        let gradient = match self.area_gradient() {
+            Some(gradient) => gradient,
+            None => {
+                self.area_error = 0.0;
+                return;
+            }
+        };
+        let n = gradient.len();
+        let variance = match &self.fit_covariance_matrix {
+            Some(covariance) if covariance.len() >= n && covariance.iter().take(n).all(|row| row.len() >= n) => {
+                (0..n).flat_map(|i| (0..n).map(move |j| (i, j)))
+                    .map(|(i, j)| gradient[i] * gradient[j] * covariance[i][j])
+                    .sum::<f64>()
+            }
+            _ if self.fit_parameter_errors.len() >= n => {
+                gradient.iter().zip(&self.fit_parameter_errors)
+                    .map(|(d, e)| (d * e).powi(2))
+                    .sum::<f64>()
+            }
+            _ => 0.0,
+        };
+        self.area_error = if variance.is_finite() { variance.max(0.0).sqrt() } else { 0.0 };
+    }
+    
+    /// Partial derivatives of the fitted area with respect to each fit parameter
+    fn area_gradient(&self) -> Option<Vec<f64>> {
+        let p = &self.fit_parameters;
+        let k = (std::f64::consts::PI * 2.0).sqrt();
+        let pi = std::f64::consts::PI;
+        let mut gradient = vec![0.0; p.len()];
+        match self.peak_type {
+            PeakType::Gaussian | PeakType::NLC if p.len() >= 3 => {
+                let (amplitude, sigma) = (p[0], p[2]);
+                gradient[0] = sigma * k;
+                gradient[2] = amplitude * k;
+            }
+            PeakType::Lorentzian if p.len() >= 3 => {
+                let (amplitude, gamma) = (p[0], p[2]);
+                gradient[0] = gamma * pi;
+                gradient[2] = amplitude * pi;
+            }
+            PeakType::PseudoVoigt if p.len() >= 4 => {
+                let (amplitude, sigma, gamma) = (p[0], p[2], p[3]);
+                let mixing = self.mixing_parameter;
+                gradient[0] = mixing * gamma * pi + (1.0 - mixing) * sigma * k;
+                gradient[2] = (1.0 - mixing) * amplitude * k;
+                gradient[3] = mixing * amplitude * pi;
+            }
+            PeakType::EMG if p.len() >= 4 => {
+                let (amplitude, sigma, tau) = (p[0], p[2], p[3]);
+                let tail = (sigma * sigma / (2.0 * tau * tau)).exp();
+                gradient[0] = sigma * k * tail;
+                gradient[2] = amplitude * k * tail * (1.0 + sigma * sigma / (tau * tau));
+                gradient[3] = -amplitude * k * tail * sigma.powi(3) / tau.powi(3);
+            }
+            PeakType::BiGaussian if p.len() >= 6 => {
+                let (amplitude, sigma1, sigma2, mixing) = (p[0], p[2], p[3], p[4]);
+                gradient[0] = k * (mixing * sigma1 + (1.0 - mixing) * sigma2);
+                gradient[2] = mixing * amplitude * k;
+                gradient[3] = (1.0 - mixing) * amplitude * k;
+                gradient[4] = amplitude * k * (sigma1 - sigma2);
+            }
+            PeakType::VoigtExponentialTail if p.len() >= 5 => {
+                let (amplitude, sigma, tau) = (p[0], p[2], p[4]);
+                gradient[0] = sigma * k * 0.5 + tau;
+                gradient[2] = amplitude * k * 0.5;
+                gradient[4] = amplitude;
+            }
+            PeakType::PearsonIV | PeakType::PearsonVII if p.len() >= pearson_parameter_count(&self.peak_type) => {
+                // Central differences of the numerically integrated area; the center does not affect it
+                for i in (0..pearson_parameter_count(&self.peak_type)).filter(|&i| i != 1) {
+                    let h = 1e-6 * p[i].abs().max(1.0);
+                    let mut plus = p.clone();
+                    let mut minus = p.clone();
+                    plus[i] += h;
+                    minus[i] -= h;
+                    gradient[i] = (pearson_area(&self.peak_type, &plus) - pearson_area(&self.peak_type, &minus)) / (2.0 * h);
+                }
+            }
+            PeakType::GMGBayesian if p.len() >= 4 => {
+                let (amplitude, sigma, modifier) = (p[0], p[2], p[3]);
+                let omega = (sigma.powi(2) + modifier.powi(2)).sqrt();
+                if omega <= 0.0 {
+                    return None;
+                }
+                gradient[0] = omega * k;
+                gradient[2] = amplitude * k * sigma / omega;
+                gradient[3] = amplitude * k * modifier / omega;
+            }
+            _ => return None,
+        }
+        Some(gradient)
     }
     
     /// Get peak width at specified height
@@ -384,6 +520,48 @@ impl Peak {
         score.min(1.0)
     }
     
+    /// Gaussian-equivalent representation of the peak for reporting
+    ///
+    /// An EMG peak is replaced by the Gaussian with the same first two moments: centred on the
+    /// centroid μ + τ with σ_eq = √(σ² + τ²), keeping the area. Other peak types are returned as is.
+    pub fn to_gaussian_equivalent(&self) -> Peak {
+        let mut equivalent = self.clone();
+        if self.peak_type != PeakType::EMG {
+            return equivalent;
+        }
+        
+        let (mu, sigma, tau) = if self.fit_parameters.len() >= 4 {
+            (self.fit_parameters[1], self.fit_parameters[2], self.fit_parameters[3])
+        } else {
+            (self.center, self.sigma, self.tau)
+        };
+        let tau = tau.abs();
+        let sigma_eq = (sigma * sigma + tau * tau).sqrt();
+        if !sigma_eq.is_finite() || sigma_eq <= 0.0 {
+            return equivalent;
+        }
+        
+        equivalent.peak_type = PeakType::Gaussian;
+        equivalent.center = mu + tau;
+        equivalent.sigma = sigma_eq;
+        equivalent.tau = 0.0;
+        equivalent.fwhm = sigma_eq * 2.355;
+        equivalent.hwhm = sigma_eq * 1.1775;
+        equivalent.left_hwhm = equivalent.hwhm;
+        equivalent.right_hwhm = equivalent.hwhm;
+        equivalent.asymmetry_factor = 1.0;
+        if self.area > 0.0 {
+            equivalent.amplitude = self.area / (sigma_eq * (std::f64::consts::PI * 2.0).sqrt());
+        }
+        equivalent.set_fit_parameters(vec![equivalent.amplitude, equivalent.center, sigma_eq], Vec::new(), None);
+        equivalent.area_error = self.area_error;
+        equivalent.area_unit = self.area_unit.clone();
+        equivalent.add_metadata("gaussian_equivalent_of".to_string(), serde_json::json!("EMG"));
+        equivalent.add_metadata("source_sigma".to_string(), serde_json::json!(sigma));
+        equivalent.add_metadata("source_tau".to_string(), serde_json::json!(tau));
+        equivalent
+    }
+    
     /// Add metadata
     pub fn add_metadata(&mut self, key: String, value: serde_json::Value) {
         self.metadata.insert(key, value);
@@ -393,4 +571,116 @@ impl Peak {
     pub fn get_metadata(&self, key: &str) -> Option<&serde_json::Value> {
         self.metadata.get(key)
     }
+    
+    /// Set the user-assigned label (e.g. analyte or internal standard name)
+    pub fn set_label(&mut self, label: &str) {
+        self.metadata.insert("label".to_string(), serde_json::json!(label));
+    }
+    
+    /// Get the user-assigned label
+    pub fn get_label(&self) -> Option<&str> {
+        self.metadata.get("label").and_then(|v| v.as_str())
+    }
+}
+
+/// Number of fit parameters of a Pearson peak: [amplitude, center, width, m] plus nu for Pearson-IV
+fn pearson_parameter_count(peak_type: &PeakType) -> usize {
+    if *peak_type == PeakType::PearsonIV { 5 } else { 4 }
+}
+
+/// Integral of (1 + t²)^(-m)·exp(-nu·atan t) over the real line
+///
+/// Substituting t = tan θ gives ∫ cos^(2m-2)θ·exp(-nu·θ) dθ over (-π/2, π/2), whose integrand
+/// is bounded for m ≥ 1; evaluated with Simpson's rule
+fn pearson_integral(m: f64, nu: f64) -> f64 {
+    const STEPS: usize = 400;
+    let h = std::f64::consts::PI / STEPS as f64;
+    let sum: f64 = (0..=STEPS)
+        .map(|i| {
+            let theta = -std::f64::consts::FRAC_PI_2 + i as f64 * h;
+            let weight = if i == 0 || i == STEPS { 1.0 } else if i.is_multiple_of(2) { 2.0 } else { 4.0 };
+            weight * theta.cos().max(0.0).powf(2.0 * m - 2.0) * (-nu * theta).exp()
+        })
+        .sum();
+    sum * h / 3.0
+}
+
+/// Area of a height-normalized Pearson-VII / Pearson-IV peak (see `PearsonVIICalculator` / `PearsonIVCalculator`)
+fn pearson_area(peak_type: &PeakType, p: &[f64]) -> f64 {
+    let (amplitude, width, m) = (p[0], p[2], p[3]);
+    if *peak_type == PeakType::PearsonIV {
+        let nu = p[4];
+        let t0 = -nu / (2.0 * m);
+        let apex = (1.0 + t0 * t0).powf(-m) * (-nu * t0.atan()).exp();
+        amplitude * width * pearson_integral(m, nu) / apex
+    } else {
+        amplitude * width / (2f64.powf(1.0 / m) - 1.0).sqrt() * pearson_integral(m, 0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_emg_gaussian_equivalent_is_wider_and_keeps_area() {
+        let mut peak = Peak::new("emg".to_string(), "c".to_string(), 5.0, 100.0, PeakType::EMG);
+        peak.set_fit_parameters(vec![100.0, 5.0, 0.2, 0.3], vec![1.0, 0.01, 0.01, 0.02], None);
+        peak.calculate_area_from_fit();
+        let equivalent = peak.to_gaussian_equivalent();
+
+        assert_eq!(peak.peak_type, PeakType::EMG);
+        assert_eq!(peak.center, 5.0);
+        assert_eq!(peak.fit_parameters, vec![100.0, 5.0, 0.2, 0.3]);
+        assert_eq!(equivalent.peak_type, PeakType::Gaussian);
+        assert!(equivalent.sigma > 0.2, "sigma_eq {}", equivalent.sigma);
+        assert!((equivalent.sigma - (0.2f64.powi(2) + 0.3f64.powi(2)).sqrt()).abs() < 1e-12);
+        assert!((equivalent.center - 5.3).abs() < 1e-12);
+        let gaussian_area = equivalent.amplitude * equivalent.sigma * (std::f64::consts::PI * 2.0).sqrt();
+        assert!((gaussian_area - peak.area).abs() < 1e-9 * peak.area);
+        assert_eq!(equivalent.metadata["gaussian_equivalent_of"], serde_json::json!("EMG"));
+    }
+
+    #[test]
+    fn test_gmg_area_matches_integrated_profile() {
+        use crate::core::processors::peak_fitting::peak_shapes::{GMGCalculator, PeakShapeCalculator, PeakShapeParams, PeakShapeType};
+
+        let parameters = vec![50.0, 5.0, 0.2, 0.4];
+        let mut peak = Peak::new("gmg".to_string(), "c".to_string(), 5.0, 50.0, PeakType::GMGBayesian);
+        peak.set_fit_parameters(parameters.clone(), vec![0.0; 4], None);
+        peak.calculate_area_from_fit();
+
+        let mut params = PeakShapeParams::new(PeakShapeType::GMGBayesian);
+        params.parameters = parameters;
+        let step = 1e-3;
+        let integrated: f64 = (0..10_000).map(|i| GMGCalculator.calculate(i as f64 * step, &params)).sum::<f64>() * step;
+
+        let expected = 50.0 * (0.2f64.powi(2) + 0.4f64.powi(2)).sqrt() * (std::f64::consts::PI * 2.0).sqrt();
+        assert!((peak.area - expected).abs() < 1e-9 * expected);
+        assert!((peak.area - integrated).abs() < 1e-6 * integrated, "area {} vs integral {}", peak.area, integrated);
+        // 旧公式 A·σ·√(2π)·modifier 与峰形积分相差一个数量级
+        let legacy = 50.0 * 0.2 * (std::f64::consts::PI * 2.0).sqrt() * 0.4;
+        assert!(integrated > 5.0 * legacy);
+    }
+
+    #[test]
+    fn test_nlc_area_does_not_depend_on_distortion() {
+        use crate::core::processors::peak_fitting::peak_shapes::{NLCCalculator, PeakShapeCalculator, PeakShapeParams, PeakShapeType};
+
+        let expected = 80.0 * 0.25 * (std::f64::consts::PI * 2.0).sqrt();
+        for distortion in [-0.15, 0.0, 0.1] {
+            let parameters = vec![80.0, 5.0, 0.25, distortion];
+            let mut peak = Peak::new("nlc".to_string(), "c".to_string(), 5.0, 80.0, PeakType::NLC);
+            peak.set_fit_parameters(parameters.clone(), vec![0.0; 4], None);
+            peak.calculate_area_from_fit();
+
+            let mut params = PeakShapeParams::new(PeakShapeType::NLC);
+            params.parameters = parameters;
+            let step = 1e-3;
+            let integrated: f64 = (0..10_000).map(|i| NLCCalculator.calculate(i as f64 * step, &params)).sum::<f64>() * step;
+
+            assert!((peak.area - expected).abs() < 1e-9 * expected);
+            assert!((peak.area - integrated).abs() < 1e-6 * integrated, "distortion {}: area {} vs integral {}", distortion, peak.area, integrated);
+        }
+    }
 }
@@ -0,0 +1,204 @@
+//! 处理溯源
+//!
+//! 记录一个结果从源文件到最终输出的完整链条：文件校验和、加载器与程序版本，
+//! 以及每个处理阶段的配置和时间戳，供可重复性审查使用
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::core::data::{DataContainer, ProcessingError};
+
+/// 溯源记录在容器元数据中的键
+pub const PROVENANCE_KEY: &str = "provenance";
+
+/// 读取 mzML 使用的加载器，与 Cargo.toml 中的 mzdata 依赖版本一致
+pub const LOADER_VERSION: &str = "mzdata 0.58";
+
+/// 一个处理阶段
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProvenanceStage {
+    /// 阶段名称，如 "load" / "smooth" / "analyze"
+    pub stage: String,
+    pub config: Value,
+    /// 阶段完成时间（RFC 3339）
+    pub timestamp: String,
+}
+
+/// 一个结果的完整处理链
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Provenance {
+    pub result_id: String,
+    pub crate_version: String,
+    pub loader_version: String,
+    pub source_file: String,
+    /// 源文件内容的 SHA-256（小写十六进制）
+    pub file_checksum: String,
+    pub created_at: String,
+    /// 按执行顺序排列的处理阶段
+    pub stages: Vec<ProvenanceStage>,
+}
+
+impl Provenance {
+    /// 为源文件创建溯源记录并计算其校验和，尚不包含任何阶段
+    pub fn for_file(file_path: &str) -> Result<Self, ProcessingError> {
+        Ok(Self {
+            result_id: uuid::Uuid::new_v4().to_string(),
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            loader_version: LOADER_VERSION.to_string(),
+            source_file: file_path.to_string(),
+            file_checksum: file_sha256(Path::new(file_path))?,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            stages: Vec::new(),
+        })
+    }
+
+    /// 追加一个处理阶段
+    pub fn record(&mut self, stage: &str, config: Value) {
+        self.stages.push(ProvenanceStage {
+            stage: stage.to_string(),
+            config,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        });
+    }
+
+    /// 把溯源记录写入容器元数据，随容器一起保存
+    pub fn attach(&self, container: &mut DataContainer) -> Result<(), ProcessingError> {
+        container.metadata.insert(PROVENANCE_KEY.to_string(), serde_json::to_value(self)?);
+        Ok(())
+    }
+
+    /// 从容器元数据中读取溯源记录
+    pub fn from_container(container: &DataContainer) -> Option<Self> {
+        container.metadata.get(PROVENANCE_KEY)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+}
+
+/// 分块计算文件的 SHA-256，避免把大文件整个读入内存
+fn file_sha256(path: &Path) -> Result<String, ProcessingError> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1 << 16];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// 按 result_id 保存的溯源记录，并记住每个文件当前的工作结果
+#[derive(Debug, Default)]
+pub struct ProvenanceRegistry {
+    records: HashMap<String, Provenance>,
+    by_file: HashMap<String, String>,
+}
+
+impl ProvenanceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 为文件开始新的处理链并记录加载阶段，返回 result_id；该结果成为文件当前的工作结果
+    pub fn start(&mut self, file_path: &str, load_config: Value) -> Result<String, ProcessingError> {
+        let mut provenance = Provenance::for_file(file_path)?;
+        provenance.record("load", load_config);
+        let result_id = provenance.result_id.clone();
+        self.by_file.insert(file_path.to_string(), result_id.clone());
+        self.records.insert(result_id.clone(), provenance);
+        Ok(result_id)
+    }
+
+    /// 在已有的处理链上追加阶段
+    pub fn record(&mut self, result_id: &str, stage: &str, config: Value) -> Result<&Provenance, ProcessingError> {
+        let provenance = self.records.get_mut(result_id)
+            .ok_or_else(|| ProcessingError::ConfigError(format!("未知的结果ID: {}", result_id)))?;
+        provenance.record(stage, config);
+        Ok(provenance)
+    }
+
+    /// 文件当前的工作结果
+    pub fn result_for_file(&self, file_path: &str) -> Option<&str> {
+        self.by_file.get(file_path).map(String::as_str)
+    }
+
+    pub fn get(&self, result_id: &str) -> Option<&Provenance> {
+        self.records.get(result_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::loaders::mzdata_loader::DataLoader;
+    use crate::core::processors::core::Processor;
+    use crate::core::utils::test_fixtures::write_ms1_run;
+
+    /// 60 张 MS1 光谱，TIC 随保留时间呈高斯峰
+    fn write_sample(path: &Path) {
+        write_ms1_run(path, 60, 0.1, |i| {
+            let rt = i as f64 * 0.1;
+            let intensity = 10.0 + 1000.0 * (-(rt - 3.0).powi(2) / (2.0 * 0.4 * 0.4)).exp();
+            (0..3).map(|j| (500.0 + j as f64, intensity as f32)).collect()
+        });
+    }
+
+    #[tokio::test]
+    async fn load_smooth_analyze_are_recorded_in_order() {
+        let path = std::env::temp_dir().join(format!("mz_curve_provenance_{}.mzML", std::process::id()));
+        write_sample(&path);
+        let file_path = path.to_string_lossy().to_string();
+        let mut registry = ProvenanceRegistry::new();
+
+        // 加载
+        let load_config = serde_json::json!({"file_path": file_path});
+        let result_id = registry.start(&file_path, load_config.clone()).unwrap();
+        let mut container = DataLoader::load_from_file(&file_path).unwrap();
+
+        // 平滑
+        let smooth_config = serde_json::json!({"method": "savitzky_golay", "window_size": 5, "polynomial_order": 2});
+        let tic = container.compute_tic(Some(1)).unwrap();
+        let smoothed = crate::core::processors::noise_reduction::NoiseReductionProcessor::new("savitzky_golay")
+            .denoise(&tic, &smooth_config)
+            .unwrap();
+        let mut curve = tic.clone();
+        curve.y_values = smoothed;
+        registry.record(&result_id, "smooth", smooth_config.clone()).unwrap();
+
+        // 峰分析
+        let analyze_config = serde_json::json!({"detection_method": "simple", "fitting_method": "gaussian", "sensitivity": 0.5});
+        let mut analysis_input = DataContainer::new();
+        analysis_input.curves.push(curve);
+        crate::core::processors::peak_analysis::PeakAnalyzer::new()
+            .process(analysis_input, analyze_config.clone())
+            .await
+            .unwrap();
+        registry.record(&result_id, "analyze", analyze_config.clone()).unwrap();
+
+        let provenance = registry.get(&result_id).unwrap().clone();
+        provenance.attach(&mut container).unwrap();
+        let checksum = crate::core::exporters::manifest::sha256_hex(&std::fs::read(&path).unwrap());
+        let _ = std::fs::remove_file(&path);
+
+        let stages: Vec<&str> = provenance.stages.iter().map(|s| s.stage.as_str()).collect();
+        assert_eq!(stages, vec!["load", "smooth", "analyze"]);
+        assert_eq!(provenance.stages[0].config, load_config);
+        assert_eq!(provenance.stages[1].config, smooth_config);
+        assert_eq!(provenance.stages[2].config, analyze_config);
+        assert_eq!(provenance.file_checksum, checksum);
+        assert_eq!(provenance.crate_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(registry.result_for_file(&file_path), Some(result_id.as_str()));
+
+        // 记录随工作容器保存
+        let stored = Provenance::from_container(&container).unwrap();
+        assert_eq!(stored.result_id, result_id);
+        assert_eq!(stored.stages.len(), 3);
+    }
+}
@@ -2,7 +2,9 @@ use async_trait::async_trait;
 use serde_json::Value;
 use std::collections::HashMap;
 
-use crate::core::data::{DataContainer, ProcessingError};
+use crate::core::data::{DataContainer, Peak, ProcessingError};
+use crate::core::processors::peak_detection::estimate_noise_floor;
+use crate::core::utils::display_decimation::{decimate_for_display, DisplayInterpolation};
 
 /// Base trait for all data exporters
 #[async_trait]
@@ -22,6 +24,11 @@ pub trait Exporter: Send + Sync {
     /// Get the configuration schema for this exporter
     fn config_schema(&self) -> Value;
     
+    /// Whether the exporter writes files itself; such exports are never served from the export cache
+    fn writes_files(&self) -> bool {
+        false
+    }
+    
     /// Export data to the specified format
     async fn export(
         &self,
@@ -43,8 +50,21 @@ pub struct ExportResult {
     pub metadata: HashMap<String, serde_json::Value>,
 }
 
+/// Peaks of one overlap cluster with the region they span, used by grouped exports
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PeakCluster {
+    /// Shared `cluster_id`; None for a peak that was never clustered
+    pub cluster_id: Option<String>,
+    pub region_start: f64,
+    pub region_end: f64,
+    pub peak_count: usize,
+    pub summed_area: f64,
+    pub peaks: Vec<Peak>,
+}
+
 /// Export configuration for common options
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct ExportConfig {
     /// Include header row in the export
     pub include_header: bool,
@@ -62,6 +82,22 @@ pub struct ExportConfig {
     pub include_fitted_curves: Option<bool>,
     /// Number of points for fitted curves
     pub fitted_curve_points: Option<usize>,
+    /// Prepend the run-level TIC overview curve computed from the raw spectra
+    pub include_overview: Option<bool>,
+    /// Also prepend the BPC overview curve (requires include_overview)
+    pub include_bpc: Option<bool>,
+    /// Skip peaks whose quality score is below this value (0-1)
+    pub min_quality: Option<f64>,
+    /// Skip peaks whose area is below this value
+    pub min_area: Option<f64>,
+    /// Skip peaks whose height above the curve noise floor, in noise units, is below this value
+    pub min_snr: Option<f64>,
+    /// Peak order within each curve: "center", "area_desc", "amplitude_desc" or "quality_desc"
+    pub sort_by: Option<String>,
+    /// Decimate curves longer than this many points, keeping the maximum of each bucket
+    pub max_curve_points: Option<usize>,
+    /// Grid for fitted curves: "peak" (±3σ around each peak) or "original" (the curve's own x-values)
+    pub fitted_curve_grid: Option<String>,
 }
 
 impl Default for ExportConfig {
@@ -75,6 +111,14 @@ impl Default for ExportConfig {
             include_peaks: true,
             include_fitted_curves: Some(true),
             fitted_curve_points: Some(100),
+            include_overview: Some(false),
+            include_bpc: Some(false),
+            min_quality: None,
+            min_area: None,
+            min_snr: None,
+            sort_by: Some("center".to_string()),
+            max_curve_points: None,
+            fitted_curve_grid: Some("peak".to_string()),
         }
     }
 }
@@ -107,6 +151,183 @@ pub mod helpers {
         format!("{}", timestamp)
     }
     
+    /// Build a view of the data with the TIC/BPC overview curves prepended
+    /// 返回 None 表示未启用概览或没有可用的原始光谱
+    pub fn with_overview_curves(data: &DataContainer, config: &ExportConfig) -> Option<DataContainer> {
+        if !config.include_overview.unwrap_or(false) {
+            return None;
+        }
+        
+        let mut overview_curves = Vec::new();
+        if let Some(tic) = data.compute_tic(Some(1)) {
+            overview_curves.push(tic);
+        }
+        if config.include_bpc.unwrap_or(false) {
+            if let Some(bpc) = data.compute_bpc(Some(1)) {
+                overview_curves.push(bpc);
+            }
+        }
+        
+        if overview_curves.is_empty() {
+            return None;
+        }
+        
+        overview_curves.extend(data.curves.iter().cloned());
+        Some(DataContainer {
+            metadata: data.metadata.clone(),
+            spectra: Vec::new(),
+            curves: overview_curves,
+        })
+    }
+    
+    /// Validate an explicit `decimal_precision` override; absent means the exporter default
+    pub fn validate_decimal_precision(config: &Value) -> Result<(), ProcessingError> {
+        match config.get("decimal_precision") {
+            None | Some(Value::Null) => Ok(()),
+            Some(value) => match value.as_u64() {
+                Some(precision) if (1..=15).contains(&precision) => Ok(()),
+                _ => Err(ProcessingError::ConfigError(format!(
+                    "decimal_precision must be an integer between 1 and 15, got {}", value
+                ))),
+            },
+        }
+    }
+    
+    /// Round every floating point number in a JSON document to the given precision
+    pub fn round_json_floats(value: &mut Value, precision: usize) {
+        match value {
+            Value::Number(number) if number.is_f64() => {
+                if let Some(rounded) = number.as_f64()
+                    .and_then(|v| format_float(v, precision).parse::<f64>().ok())
+                    .and_then(serde_json::Number::from_f64)
+                {
+                    *number = rounded;
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| round_json_floats(item, precision)),
+            Value::Object(map) => map.values_mut().for_each(|item| round_json_floats(item, precision)),
+            _ => {}
+        }
+    }
+    
+    /// Whether any of the peak filters (min_quality / min_area / min_snr) is set
+    pub fn has_peak_filters(config: &ExportConfig) -> bool {
+        config.min_quality.is_some() || config.min_area.is_some() || config.min_snr.is_some()
+    }
+    
+    /// Build a view of the data without the peaks failing the configured filters
+    /// 返回过滤后的数据与被过滤掉的峰数量
+    pub fn filter_peaks(data: &DataContainer, config: &ExportConfig) -> (DataContainer, usize) {
+        let mut filtered = data.clone();
+        let mut removed = 0;
+        
+        for curve in &mut filtered.curves {
+            // 信噪比以曲线噪声水平为单位：(峰高 - 基线) / 噪声
+            let noise_floor = config.min_snr.map(|_| estimate_noise_floor(curve));
+            let before = curve.peaks.len();
+            curve.peaks.retain(|peak| {
+                let quality_ok = config.min_quality.is_none_or(|min| peak.get_quality_score() >= min);
+                let area_ok = config.min_area.is_none_or(|min| peak.area >= min);
+                let snr_ok = match (config.min_snr, &noise_floor) {
+                    (Some(min), Some(floor)) if floor.noise > 0.0 => {
+                        (peak.amplitude - floor.baseline) / floor.noise >= min
+                    }
+                    _ => true,
+                };
+                quality_ok && area_ok && snr_ok
+            });
+            removed += before - curve.peaks.len();
+        }
+        
+        (filtered, removed)
+    }
+    
+    /// Build a view of the data with the peaks of every curve ordered by `sort_by`
+    /// 排序稳定，取值相同的峰保持检测顺序；视图不含原始光谱
+    pub fn sort_peaks(data: &DataContainer, config: &ExportConfig) -> Result<DataContainer, ProcessingError> {
+        let sort_by = config.sort_by.as_deref().unwrap_or("center");
+        let key: fn(&Peak) -> f64 = match sort_by {
+            "center" => |peak| peak.center,
+            "area_desc" => |peak| -peak.area,
+            "amplitude_desc" => |peak| -peak.amplitude,
+            "quality_desc" => |peak| -peak.get_quality_score(),
+            other => return Err(ProcessingError::ConfigError(format!(
+                "Unsupported sort_by: '{}' (expected center, area_desc, amplitude_desc or quality_desc)", other
+            ))),
+        };
+        
+        let mut curves = data.curves.clone();
+        for curve in &mut curves {
+            curve.peaks.sort_by(|a, b| key(a).total_cmp(&key(b)));
+        }
+        
+        Ok(DataContainer {
+            metadata: data.metadata.clone(),
+            spectra: Vec::new(),
+            curves,
+        })
+    }
+    
+    /// Build a view of the data with curves longer than `max_curve_points` decimated
+    /// 使用与显示相同的保峰抽稀，原始点数记录在曲线元数据 `original_point_count` 中；
+    /// 返回 None 表示未设置上限或没有曲线超过上限
+    pub fn decimate_curves(data: &DataContainer, config: &ExportConfig) -> Result<Option<DataContainer>, ProcessingError> {
+        let max_points = match config.max_curve_points {
+            None => return Ok(None),
+            Some(n) if n < 2 => return Err(ProcessingError::ConfigError(format!(
+                "max_curve_points must be at least 2, got {}", n
+            ))),
+            Some(n) => n,
+        };
+        if data.curves.iter().all(|curve| curve.x_values.len() <= max_points) {
+            return Ok(None);
+        }
+        
+        let mut decimated = data.clone();
+        for curve in &mut decimated.curves {
+            let original_count = curve.x_values.len();
+            if original_count <= max_points {
+                continue;
+            }
+            let (x_values, y_values) = decimate_for_display(&curve.x_values, &curve.y_values, max_points, DisplayInterpolation::None);
+            curve.x_values = x_values;
+            curve.y_values = y_values;
+            curve.point_count = curve.x_values.len();
+            curve.metadata.insert("original_point_count".to_string(), serde_json::json!(original_count));
+        }
+        
+        Ok(Some(decimated))
+    }
+    
+    /// Group peaks by `cluster_id`, keeping the order in which each cluster first appears
+    /// 未分配簇的峰各自单独成组；区域取成员峰区域的并集
+    pub fn group_peaks_by_cluster(peaks: &[Peak]) -> Vec<PeakCluster> {
+        let mut clusters: Vec<PeakCluster> = Vec::new();
+        for peak in peaks {
+            let (start, end) = peak.region();
+            let existing = peak.cluster_id.as_ref()
+                .and_then(|id| clusters.iter_mut().find(|c| c.cluster_id.as_ref() == Some(id)));
+            match existing {
+                Some(cluster) => {
+                    cluster.region_start = cluster.region_start.min(start);
+                    cluster.region_end = cluster.region_end.max(end);
+                    cluster.peak_count += 1;
+                    cluster.summed_area += peak.area;
+                    cluster.peaks.push(peak.clone());
+                }
+                None => clusters.push(PeakCluster {
+                    cluster_id: peak.cluster_id.clone(),
+                    region_start: start,
+                    region_end: end,
+                    peak_count: 1,
+                    summed_area: peak.area,
+                    peaks: vec![peak.clone()],
+                }),
+            }
+        }
+        clusters
+    }
+    
     /// Create export metadata
     pub fn create_export_metadata(
         exporter_name: &str,
@@ -0,0 +1,159 @@
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::core::data::{DataContainer, ProcessingError};
+use super::base::{Exporter, ExportResult, ExportConfig, helpers};
+
+/// Convergence exporter - per-peak optimizer history (error after every iteration
+/// and termination reason) for diagnosing stalled or non-converging fits
+pub struct ConvergenceExporter;
+
+#[async_trait]
+impl Exporter for ConvergenceExporter {
+    fn name(&self) -> &str {
+        "convergence_exporter"
+    }
+
+    fn description(&self) -> &str {
+        "Export the fit optimizer's per-iteration error history and termination reason for every fitted peak"
+    }
+
+    fn file_extension(&self) -> &str {
+        "json"
+    }
+
+    fn mime_type(&self) -> &str {
+        "application/json"
+    }
+
+    fn config_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "pretty": {
+                    "type": "boolean",
+                    "default": true,
+                    "description": "Pretty-print the JSON output"
+                },
+                "only_unconverged": {
+                    "type": "boolean",
+                    "default": false,
+                    "description": "Only include peaks whose fit did not converge"
+                }
+            }
+        })
+    }
+
+    async fn export(
+        &self,
+        data: &DataContainer,
+        config: Value,
+    ) -> Result<ExportResult, ProcessingError> {
+        let pretty = config["pretty"].as_bool().unwrap_or(true);
+        let only_unconverged = config["only_unconverged"].as_bool().unwrap_or(false);
+        let export_config: ExportConfig = serde_json::from_value(config)
+            .unwrap_or_default();
+
+        let mut entries = Vec::new();
+        let mut unfitted_peaks = 0;
+        for curve in &data.curves {
+            for peak in &curve.peaks {
+                // Peaks that never went through the optimizer carry no history
+                let Some(history) = peak.get_metadata("error_history") else {
+                    unfitted_peaks += 1;
+                    continue;
+                };
+                let converged = peak.get_metadata("converged").and_then(|v| v.as_bool()).unwrap_or(false);
+                if only_unconverged && converged {
+                    continue;
+                }
+                entries.push(serde_json::json!({
+                    "curve_id": curve.id,
+                    "peak_id": peak.id,
+                    "center": peak.center,
+                    "iterations": peak.get_metadata("iterations").cloned().unwrap_or(Value::Null),
+                    "converged": converged,
+                    "termination_reason": peak.get_metadata("termination_reason").cloned().unwrap_or(Value::Null),
+                    "error_history": history,
+                }));
+            }
+        }
+
+        let document = serde_json::json!({
+            "peaks": entries,
+            "unfitted_peaks": unfitted_peaks,
+        });
+        let content = if pretty {
+            serde_json::to_vec_pretty(&document)?
+        } else {
+            serde_json::to_vec(&document)?
+        };
+
+        let filename = format!("fit_convergence_{}.json", helpers::generate_timestamp());
+        let mut metadata = helpers::create_export_metadata(
+            self.name(),
+            data.curves.len(),
+            data.total_peak_count(),
+            &export_config,
+        );
+        metadata.insert("exported_peaks".to_string(), serde_json::json!(entries.len()));
+        metadata.insert("unfitted_peaks".to_string(), serde_json::json!(unfitted_peaks));
+
+        Ok(ExportResult {
+            data: content,
+            filename,
+            mime_type: self.mime_type().to_string(),
+            metadata,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::data::Curve;
+    use crate::core::processors::core::Processor;
+    use crate::core::processors::peak_analysis::PeakAnalyzer;
+
+    #[tokio::test]
+    async fn history_length_matches_iterations_when_max_iterations_hit() {
+        let x_values: Vec<f64> = (0..200).map(|i| i as f64 * 0.05).collect();
+        let y_values: Vec<f64> = x_values.iter()
+            .map(|&x| 1000.0 * (-(x - 4.0).powi(2) / (2.0 * 0.3 * 0.3)).exp())
+            .collect();
+        let mut input = DataContainer::new();
+        input.curves.push(Curve::new(
+            "dt".to_string(), "DT".to_string(), x_values, y_values,
+            "Drift Time".to_string(), "Intensity".to_string(), "ms".to_string(), "counts".to_string(),
+        ));
+        let config = serde_json::json!({
+            "detection_method": "simple",
+            "fitting_method": "multi_peak",
+            "overlapping_processing": "none",
+            "quality_threshold": 0.0,
+            "max_iterations": 3,
+            "convergence_threshold": 1e-300
+        });
+
+        let result = PeakAnalyzer::new().process(input, config).await.unwrap();
+        let mut analyzed = DataContainer::new();
+        analyzed.curves = result.curves;
+        for peak in result.peaks {
+            if let Some(curve) = analyzed.curves.iter_mut().find(|c| c.id == peak.curve_id) {
+                curve.add_peak(peak);
+            }
+        }
+
+        let exported = ConvergenceExporter.export(&analyzed, serde_json::json!({})).await.unwrap();
+        let document: Value = serde_json::from_slice(&exported.data).unwrap();
+        let peaks = document["peaks"].as_array().unwrap();
+        assert!(!peaks.is_empty());
+        for peak in peaks {
+            let iterations = peak["iterations"].as_u64().unwrap();
+            assert_eq!(iterations, 3);
+            assert_eq!(peak["error_history"].as_array().unwrap().len() as u64, iterations);
+            assert_eq!(peak["termination_reason"], "max_iterations");
+            assert_eq!(peak["converged"], false);
+        }
+    }
+}
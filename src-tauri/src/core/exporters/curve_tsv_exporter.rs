@@ -5,7 +5,8 @@ use std::fs;
 use std::path::Path;
 
 use crate::core::data::{DataContainer, ProcessingError};
-use super::base::{Exporter, ExportResult};
+use super::base::{Exporter, ExportResult, ExportConfig, helpers};
+use super::manifest::ExportManifest;
 
 /// 优化的曲线TSV导出器 - 专门用于快速导出曲线数据
 pub struct CurveTsvExporter;
@@ -28,6 +29,10 @@ impl Exporter for CurveTsvExporter {
         "text/tab-separated-values"
     }
 
+    fn writes_files(&self) -> bool {
+        true
+    }
+
     fn config_schema(&self) -> Value {
         serde_json::json!({
             "type": "object",
@@ -49,9 +54,24 @@ impl Exporter for CurveTsvExporter {
                 "decimal_precision": {
                     "type": "integer",
                     "default": 6,
-                    "minimum": 0,
-                    "maximum": 10,
+                    "minimum": 1,
+                    "maximum": 15,
                     "description": "小数精度"
+                },
+                "include_overview": {
+                    "type": "boolean",
+                    "default": false,
+                    "description": "是否在曲线前添加整次运行的TIC概览曲线"
+                },
+                "include_bpc": {
+                    "type": "boolean",
+                    "default": false,
+                    "description": "是否同时添加BPC概览曲线"
+                },
+                "max_curve_points": {
+                    "type": "integer",
+                    "minimum": 2,
+                    "description": "每条曲线最多导出的点数，超出时按显示抽稀保留峰顶；不设置则导出全部点"
                 }
             },
             "required": ["output_folder"]
@@ -63,6 +83,27 @@ impl Exporter for CurveTsvExporter {
         data: &DataContainer,
         config: Value,
     ) -> Result<ExportResult, ProcessingError> {
+        self.export_with_progress(data, config, |_, _, _| {}, || false)
+    }
+}
+
+impl CurveTsvExporter {
+    /// 逐条写出曲线文件，每写完一个文件调用 `on_file_written(已完成数, 曲线总数, 文件名)`
+    ///
+    /// 写每条曲线前检查 `is_cancelled`，返回 true 时停止写入后续曲线；已写出的文件保留，
+    /// 汇总、元数据与清单只列出已写出的文件（清单同时收录汇总与元数据文件），结果元数据中 `cancelled` / `exported_count` 给出完成情况
+    pub fn export_with_progress<P, C>(
+        &self,
+        data: &DataContainer,
+        config: Value,
+        mut on_file_written: P,
+        is_cancelled: C,
+    ) -> Result<ExportResult, ProcessingError>
+    where
+        P: FnMut(usize, usize, &str),
+        C: Fn() -> bool,
+    {
+        helpers::validate_decimal_precision(&config)?;
         let output_folder = config["output_folder"]
             .as_str()
             .ok_or_else(|| ProcessingError::ConfigError("output_folder missing".to_string()))?;
@@ -70,6 +111,15 @@ impl Exporter for CurveTsvExporter {
         let include_curve_data = config["include_curve_data"].as_bool().unwrap_or(true);
         let include_metadata = config["include_metadata"].as_bool().unwrap_or(true);
         let decimal_precision = config["decimal_precision"].as_u64().unwrap_or(6) as usize;
+        
+        // 概览曲线（TIC/BPC）放在详细曲线之前，便于对照色谱上下文
+        let overview_config = ExportConfig {
+            include_overview: config["include_overview"].as_bool(),
+            include_bpc: config["include_bpc"].as_bool(),
+            ..ExportConfig::default()
+        };
+        let overview = helpers::with_overview_curves(data, &overview_config);
+        let data = overview.as_ref().unwrap_or(data);
 
         // 创建输出文件夹
         fs::create_dir_all(output_folder)
@@ -77,9 +127,19 @@ impl Exporter for CurveTsvExporter {
 
         let mut exported_files = Vec::new();
         let mut total_size = 0;
+        let mut manifest = ExportManifest::new();
+        let source = data.metadata.get("file_path").and_then(|v| v.as_str());
+        let total_curves = data.curves.len();
+        let mut cancelled = false;
 
         // 导出每条曲线到单独的TSV文件
         for (index, curve) in data.curves.iter().enumerate() {
+            if is_cancelled() {
+                log::info!("⏹️ 曲线导出已取消: 已导出 {}/{} 个文件", exported_files.len(), total_curves);
+                cancelled = true;
+                break;
+            }
+            
             let filename = format!("curve_{}_{}.tsv", index + 1, sanitize_filename(&curve.curve_type));
             let filepath = Path::new(output_folder).join(&filename);
             
@@ -92,6 +152,9 @@ impl Exporter for CurveTsvExporter {
                 content.push_str(&format!("# X Label: {} ({})\n", curve.x_label, curve.x_unit));
                 content.push_str(&format!("# Y Label: {} ({})\n", curve.y_label, curve.y_unit));
                 content.push_str(&format!("# Data Points: {}\n", curve.point_count));
+                if let Some(original) = curve.metadata.get("original_point_count") {
+                    content.push_str(&format!("# Original Data Points: {}\n", original));
+                }
                 
                 if let (Some(min), Some(max)) = (curve.x_values.first(), curve.x_values.last()) {
                     content.push_str(&format!("# X Range: {:.6} - {:.6}\n", min, max));
@@ -121,13 +184,15 @@ impl Exporter for CurveTsvExporter {
             }
             
             // 写入文件
-            fs::write(&filepath, content)
+            fs::write(&filepath, &content)
                 .map_err(|e| ProcessingError::DataError(format!("无法写入文件 {}: {}", filename, e)))?;
+            manifest.add_file(&filename, source, self.name(), content.as_bytes(), 1, curve.peaks.len());
             
             let file_size = fs::metadata(&filepath)
                 .map_err(|e| ProcessingError::DataError(format!("无法获取文件大小: {}", e)))?
                 .len();
             
+            on_file_written(index + 1, total_curves, &filename);
             exported_files.push(filename);
             total_size += file_size;
         }
@@ -140,6 +205,9 @@ impl Exporter for CurveTsvExporter {
         summary_content.push_str(&format!("导出时间: {}\n", chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")));
         summary_content.push_str(&format!("导出曲线数量: {}\n", data.curves.len()));
         summary_content.push_str(&format!("导出文件数量: {}\n", exported_files.len()));
+        if cancelled {
+            summary_content.push_str(&format!("导出已取消: 完成 {}/{} 条曲线\n", exported_files.len(), total_curves));
+        }
         summary_content.push_str(&format!("总文件大小: {} bytes\n", total_size));
         summary_content.push_str("\n导出的文件:\n");
         
@@ -148,13 +216,15 @@ impl Exporter for CurveTsvExporter {
         }
         
         summary_content.push_str("\n曲线信息:\n");
-        for (index, curve) in data.curves.iter().enumerate() {
+        for (index, curve) in data.curves.iter().enumerate().take(exported_files.len()) {
             summary_content.push_str(&format!("  {}: {} ({} 个数据点)\n", 
                 index + 1, curve.curve_type, curve.point_count));
         }
         
-        fs::write(&summary_path, summary_content)
+        fs::write(&summary_path, &summary_content)
             .map_err(|e| ProcessingError::DataError(format!("无法写入汇总文件: {}", e)))?;
+        let exported_peaks: usize = data.curves.iter().take(exported_files.len()).map(|curve| curve.peaks.len()).sum();
+        manifest.add_file(summary_filename, source, "summary", summary_content.as_bytes(), exported_files.len(), exported_peaks);
         
         // 创建元数据文件
         if include_metadata {
@@ -167,7 +237,7 @@ impl Exporter for CurveTsvExporter {
             metadata.insert("exported_files".to_string(), serde_json::json!(exported_files));
             metadata.insert("total_size_bytes".to_string(), serde_json::json!(total_size));
             
-            let curves_metadata: Vec<serde_json::Value> = data.curves.iter().map(|curve| {
+            let curves_metadata: Vec<serde_json::Value> = data.curves.iter().take(exported_files.len()).map(|curve| {
                 serde_json::json!({
                     "id": curve.id,
                     "type": curve.curve_type,
@@ -176,7 +246,8 @@ impl Exporter for CurveTsvExporter {
                     "x_unit": curve.x_unit,
                     "y_unit": curve.y_unit,
                     "point_count": curve.point_count,
-"mz_min": curve.mz_range.map(|r| r.0),
+                    "original_point_count": curve.metadata.get("original_point_count"),
+                    "mz_min": curve.mz_range.map(|r| r.0),
                     "mz_max": curve.mz_range.map(|r| r.1)
                 })
             }).collect();
@@ -186,14 +257,23 @@ impl Exporter for CurveTsvExporter {
             let metadata_json = serde_json::to_string_pretty(&metadata)
                 .map_err(|e| ProcessingError::DataError(format!("无法序列化元数据: {}", e)))?;
             
-            fs::write(&metadata_path, metadata_json)
+            fs::write(&metadata_path, &metadata_json)
                 .map_err(|e| ProcessingError::DataError(format!("无法写入元数据文件: {}", e)))?;
+            manifest.add_file(metadata_filename, source, "metadata", metadata_json.as_bytes(), exported_files.len(), exported_peaks);
         }
         
+        // 写入清单：列出每个曲线文件及汇总、元数据文件的来源、大小与校验和
+        let manifest_path = manifest.write(Path::new(output_folder))
+            .map_err(|e| ProcessingError::DataError(format!("无法写入清单文件: {}", e)))?;
+        
         let mut result_metadata = HashMap::new();
         result_metadata.insert("exported_files".to_string(), serde_json::json!(exported_files));
+        result_metadata.insert("manifest_path".to_string(), serde_json::json!(manifest_path.to_string_lossy()));
         result_metadata.insert("total_size_bytes".to_string(), serde_json::json!(total_size));
         result_metadata.insert("output_folder".to_string(), serde_json::json!(output_folder));
+        result_metadata.insert("exported_count".to_string(), serde_json::json!(exported_files.len()));
+        result_metadata.insert("total_curves".to_string(), serde_json::json!(total_curves));
+        result_metadata.insert("cancelled".to_string(), serde_json::json!(cancelled));
         
         Ok(ExportResult {
             data: format!("导出完成，共 {} 个文件，总大小 {} bytes", exported_files.len(), total_size).into_bytes(),
@@ -212,3 +292,111 @@ fn sanitize_filename(filename: &str) -> String {
         .map(|c| if c.is_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::data::Curve;
+    use std::cell::Cell;
+
+    fn container_with_curves(count: usize) -> DataContainer {
+        let mut container = DataContainer::new();
+        for i in 0..count {
+            let x: Vec<f64> = (0..20).map(|j| j as f64 * 0.1).collect();
+            let y: Vec<f64> = x.iter().map(|v| (i + 1) as f64 * v).collect();
+            container.curves.push(Curve::new(
+                format!("curve_{}", i), "DT".to_string(), x, y,
+                "Drift Time".to_string(), "Intensity".to_string(), "ms".to_string(), "counts".to_string(),
+            ));
+        }
+        container
+    }
+
+    fn output_folder(name: &str) -> std::path::PathBuf {
+        let folder = std::env::temp_dir().join(format!("mz_curve_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&folder);
+        folder
+    }
+
+    #[test]
+    fn reports_progress_once_per_curve() {
+        let folder = output_folder("curve_export_progress");
+        let data = container_with_curves(5);
+        let mut events = Vec::new();
+
+        let result = CurveTsvExporter.export_with_progress(
+            &data,
+            serde_json::json!({"output_folder": folder.to_string_lossy()}),
+            |current, total, filename| events.push((current, total, filename.to_string())),
+            || false,
+        );
+        let _ = fs::remove_dir_all(&folder);
+        let result = result.unwrap();
+
+        let currents: Vec<usize> = events.iter().map(|e| e.0).collect();
+        assert_eq!(currents, vec![1, 2, 3, 4, 5]);
+        assert!(events.iter().all(|e| e.1 == 5));
+        assert_eq!(events[0].2, "curve_1_DT.tsv");
+        assert_eq!(result.metadata["exported_count"], serde_json::json!(5));
+        assert_eq!(result.metadata["cancelled"], serde_json::json!(false));
+    }
+
+    #[test]
+    fn cancellation_stops_further_writes_and_keeps_written_files() {
+        let folder = output_folder("curve_export_cancel");
+        let data = container_with_curves(5);
+        let written = Cell::new(0);
+
+        let result = CurveTsvExporter.export_with_progress(
+            &data,
+            serde_json::json!({"output_folder": folder.to_string_lossy()}),
+            |current, _, _| written.set(current),
+            || written.get() >= 2,
+        );
+        let existing: Vec<bool> = (1..=5)
+            .map(|i| folder.join(format!("curve_{}_DT.tsv", i)).exists())
+            .collect();
+        let _ = fs::remove_dir_all(&folder);
+        let result = result.unwrap();
+
+        assert_eq!(written.get(), 2);
+        assert_eq!(existing, vec![true, true, false, false, false]);
+        assert_eq!(result.metadata["exported_count"], serde_json::json!(2));
+        assert_eq!(result.metadata["total_curves"], serde_json::json!(5));
+        assert_eq!(result.metadata["cancelled"], serde_json::json!(true));
+    }
+
+    #[test]
+    fn manifest_lists_summary_and_metadata_files() {
+        use crate::core::exporters::manifest::{sha256_hex, ExportManifest, MANIFEST_FILENAME};
+
+        let folder = output_folder("curve_export_manifest");
+        let data = container_with_curves(2);
+        let result = CurveTsvExporter.export_with_progress(
+            &data,
+            serde_json::json!({"output_folder": folder.to_string_lossy()}),
+            |_, _, _| {},
+            || false,
+        );
+        let listed = ExportManifest::load(&folder.join(MANIFEST_FILENAME)).map(|manifest| {
+            manifest.entries.iter()
+                .map(|entry| {
+                    let on_disk = fs::read(folder.join(&entry.file)).unwrap();
+                    (entry.file.clone(), entry.format.clone(), entry.checksum == sha256_hex(&on_disk))
+                })
+                .collect::<Vec<_>>()
+        });
+        let _ = fs::remove_dir_all(&folder);
+        result.unwrap();
+
+        let listed = listed.unwrap();
+        let files: Vec<(&str, &str)> = listed.iter().map(|(file, format, _)| (file.as_str(), format.as_str())).collect();
+        assert_eq!(files, vec![
+            ("curve_1_DT.tsv", "curve_tsv_exporter"),
+            ("curve_2_DT.tsv", "curve_tsv_exporter"),
+            ("export_summary.txt", "summary"),
+            ("metadata.json", "metadata"),
+        ]);
+        assert!(listed.iter().all(|(_, _, checksum_matches)| *checksum_matches));
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use mzdata::prelude::*;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::core::data::{DataContainer, ProcessingError};
+use super::base::ExportResult;
+
+/// Total size of the export results kept by a default `ExportManager` (64 MiB)
+pub const DEFAULT_EXPORT_CACHE_BYTES: usize = 64 * 1024 * 1024;
+
+/// Export result cache bounded by the total size of the cached results; the oldest entry is evicted first
+#[derive(Debug)]
+pub struct ExportCache {
+    max_bytes: usize,
+    total_bytes: usize,
+    entries: HashMap<String, ExportResult>,
+    order: VecDeque<String>,
+}
+
+impl ExportCache {
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            total_bytes: 0,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&ExportResult> {
+        self.entries.get(key)
+    }
+
+    /// Insert a result, evicting the oldest entries until the cache fits; results larger than the
+    /// whole cache are not kept
+    pub fn insert(&mut self, key: String, result: ExportResult) {
+        let size = result_size(&result);
+        if size > self.max_bytes {
+            return;
+        }
+        match self.entries.insert(key.clone(), result) {
+            Some(replaced) => self.total_bytes -= result_size(&replaced),
+            None => self.order.push_back(key),
+        }
+        self.total_bytes += size;
+        while self.total_bytes > self.max_bytes {
+            let Some(oldest) = self.order.pop_front() else { break };
+            if let Some(evicted) = self.entries.remove(&oldest) {
+                self.total_bytes -= result_size(&evicted);
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Total size in bytes of the cached results
+    pub fn size_bytes(&self) -> usize {
+        self.total_bytes
+    }
+}
+
+/// Bytes accounted to a cached result: its data plus the filename and MIME type
+fn result_size(result: &ExportResult) -> usize {
+    result.data.len() + result.filename.len() + result.mime_type.len()
+}
+
+/// Feeds serialized JSON straight into the hasher without building the whole document in memory
+struct HashWriter<'a>(&'a mut Sha256);
+
+impl std::io::Write for HashWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn hex_digest(hasher: Sha256) -> String {
+    hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// SHA-256 over the format name, the export config and the container content
+///
+/// Curves, peaks and metadata are hashed through `serde_json::Value`, whose maps are key-sorted, so
+/// equal containers give the same key regardless of `HashMap` iteration order. Spectra are not
+/// serialized with the container, so their IDs, scan times and peak lists are hashed separately.
+pub fn content_key(format: &str, data: &DataContainer, config: &Value) -> Result<String, ProcessingError> {
+    let mut hasher = Sha256::new();
+    hasher.update(format.as_bytes());
+    hasher.update([0u8]);
+    serde_json::to_writer(HashWriter(&mut hasher), config)?;
+    hasher.update([0u8]);
+    serde_json::to_writer(HashWriter(&mut hasher), &serde_json::to_value(data)?)?;
+    hasher.update([0u8]);
+    hasher.update((data.spectra.len() as u64).to_le_bytes());
+    for spectrum in &data.spectra {
+        hasher.update(spectrum.id().as_bytes());
+        hasher.update([0u8]);
+        hasher.update(spectrum.start_time().to_le_bytes());
+        for peak in spectrum.peaks().iter() {
+            hasher.update(peak.mz().to_le_bytes());
+            hasher.update(peak.intensity().to_le_bytes());
+        }
+    }
+
+    Ok(hex_digest(hasher))
+}
+
+/// SHA-256 over the format name, the export config and the source file's identity
+///
+/// The file is identified by its canonical path, size and modification time, so the key is computed
+/// without loading the file and changes whenever the file is rewritten. The config is hashed through
+/// `serde_json::Value`, whose maps are key-sorted, so the key does not depend on key order.
+pub fn source_key(format: &str, path: &Path, config: &Value) -> Result<String, ProcessingError> {
+    let file_metadata = std::fs::metadata(path)?;
+    let modified = file_metadata.modified()?
+        .duration_since(UNIX_EPOCH)
+        .map(|since_epoch| since_epoch.as_nanos())
+        .unwrap_or(0);
+
+    let mut hasher = Sha256::new();
+    hasher.update(format.as_bytes());
+    hasher.update([0u8]);
+    hasher.update(serde_json::to_vec(config)?);
+    hasher.update([0u8]);
+    hasher.update(std::fs::canonicalize(path)?.to_string_lossy().as_bytes());
+    hasher.update([0u8]);
+    hasher.update(file_metadata.len().to_le_bytes());
+    hasher.update(modified.to_le_bytes());
+
+    Ok(hex_digest(hasher))
+}
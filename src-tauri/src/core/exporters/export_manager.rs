@@ -1,19 +1,36 @@
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use serde_json::Value;
 
-use crate::core::data::{DataContainer, ProcessingError};
-use super::base::{Exporter, ExportResult, ExportConfig};
+use crate::core::data::{DataContainer, ProcessingError, SerializableDataContainer};
+use crate::core::loaders::mzdata_loader::DataLoader;
+use super::base::{Exporter, ExportResult, ExportConfig, helpers};
+use super::manifest::ExportManifest;
+use super::export_cache::{content_key, source_key, ExportCache, DEFAULT_EXPORT_CACHE_BYTES};
 
 /// Export manager that handles multiple export formats
 pub struct ExportManager {
     exporters: HashMap<String, Box<dyn Exporter>>,
+    /// Results of recent exports keyed by a hash of (container content, format, config), or of
+    /// (file path, size, mtime, format, config) for file exports
+    cache: Mutex<ExportCache>,
+    cache_hits: AtomicUsize,
 }
 
 impl ExportManager {
     /// Create a new export manager with default exporters
     pub fn new() -> Self {
+        Self::with_cache_bytes(DEFAULT_EXPORT_CACHE_BYTES)
+    }
+    
+    /// Create an export manager caching at most `max_bytes` of export results (0 disables the cache)
+    pub fn with_cache_bytes(max_bytes: usize) -> Self {
         let mut manager = Self {
             exporters: HashMap::new(),
+            cache: Mutex::new(ExportCache::new(max_bytes)),
+            cache_hits: AtomicUsize::new(0),
         };
         
         // Register default exporters
@@ -21,6 +38,11 @@ impl ExportManager {
         manager.register_exporter("plotly", Box::new(super::PlotlyExporter));
         manager.register_exporter("curve_tsv", Box::new(super::CurveTsvExporter));
         manager.register_exporter("spectro_tsv", Box::new(super::SpectroTsvExporter));
+        manager.register_exporter("json", Box::new(super::JsonExporter));
+        manager.register_exporter("mzml_annotated", Box::new(super::MzMLAnnotationExporter));
+        manager.register_exporter("inclusion_list", Box::new(super::TargetListExporter));
+        manager.register_exporter("convergence", Box::new(super::ConvergenceExporter));
+        manager.register_exporter("sqlite", Box::new(super::SqliteExporter));
         
         manager
     }
@@ -35,20 +57,125 @@ impl ExportManager {
         self.exporters.keys().cloned().collect()
     }
     
+    /// Number of exports served from the cache
+    pub fn cache_hits(&self) -> usize {
+        self.cache_hits.load(Ordering::SeqCst)
+    }
+    
     /// Export data using the specified exporter
+    ///
+    /// Results of exporters that do not write files are cached under a hash of the container content,
+    /// the format and the config, so exporting identical inputs again returns the cached bytes.
     pub async fn export(
         &self,
         exporter_name: &str,
         data: &DataContainer,
         config: Value,
     ) -> Result<ExportResult, ProcessingError> {
-        let exporter = self.exporters.get(exporter_name)
+        let cache_key = if self.exporter(exporter_name)?.writes_files() {
+            None
+        } else {
+            Some(content_key(exporter_name, data, &config)?)
+        };
+        if let Some(cached) = self.cached(cache_key.as_deref()) {
+            log::debug!("导出缓存命中: {}", exporter_name);
+            return Ok(cached);
+        }
+        
+        let result = self.export_uncached(exporter_name, data, config).await?;
+        self.store(cache_key, &result);
+        Ok(result)
+    }
+    
+    fn exporter(&self, exporter_name: &str) -> Result<&dyn Exporter, ProcessingError> {
+        self.exporters.get(exporter_name)
+            .map(|exporter| exporter.as_ref())
             .ok_or_else(|| ProcessingError::ConfigError(
                 format!("Exporter '{}' not found. Available exporters: {:?}", 
                     exporter_name, self.available_exporters())
-            ))?;
+            ))
+    }
+    
+    async fn export_uncached(
+        &self,
+        exporter_name: &str,
+        data: &DataContainer,
+        config: Value,
+    ) -> Result<ExportResult, ProcessingError> {
+        let exporter = self.exporter(exporter_name)?;
+        helpers::validate_decimal_precision(&config)?;
         
-        exporter.export(data, config).await
+        // 峰过滤与曲线抽稀在导出器之前统一进行，所有导出器都只看到处理后的数据
+        let export_config: ExportConfig = serde_json::from_value(config.clone())
+            .unwrap_or_default();
+        let filtered = helpers::has_peak_filters(&export_config)
+            .then(|| helpers::filter_peaks(data, &export_config));
+        let data = filtered.as_ref().map_or(data, |(filtered, _)| filtered);
+        let decimated = helpers::decimate_curves(data, &export_config)?;
+        let data = decimated.as_ref().unwrap_or(data);
+        
+        let mut result = exporter.export(data, config).await?;
+        if let Some((_, removed)) = filtered {
+            result.metadata.insert("filtered_peak_count".to_string(), serde_json::json!(removed));
+            result.metadata.insert("peak_filters".to_string(), serde_json::json!({
+                "min_quality": export_config.min_quality,
+                "min_area": export_config.min_area,
+                "min_snr": export_config.min_snr,
+            }));
+            log::info!("🔎 导出峰过滤: 过滤掉 {} 个峰", removed);
+        }
+        if let Some(decimated) = &decimated {
+            let original_counts: HashMap<&str, &Value> = decimated.curves.iter()
+                .filter_map(|curve| curve.metadata.get("original_point_count").map(|count| (curve.id.as_str(), count)))
+                .collect();
+            result.metadata.insert("max_curve_points".to_string(), serde_json::json!(export_config.max_curve_points));
+            result.metadata.insert("original_point_counts".to_string(), serde_json::json!(original_counts));
+        }
+        
+        Ok(result)
+    }
+    
+    /// Look up a cached result, counting the hit
+    fn cached(&self, key: Option<&str>) -> Option<ExportResult> {
+        let cached = self.cache.lock().ok()?.get(key?).cloned()?;
+        self.cache_hits.fetch_add(1, Ordering::SeqCst);
+        Some(cached)
+    }
+    
+    fn store(&self, key: Option<String>, result: &ExportResult) {
+        if let (Some(key), Ok(mut cache)) = (key, self.cache.lock()) {
+            cache.insert(key, result.clone());
+        }
+    }
+    
+    /// Load a data file and export it, reusing the result of an earlier export of the same file version
+    ///
+    /// The cache is checked before the file is loaded, keyed on the file's path, size and modification
+    /// time plus the format and config. Exporters that write files are always rerun. The result metadata
+    /// carries `source_point_count`, the number of curve points in the loaded file.
+    pub async fn export_file(
+        &self,
+        exporter_name: &str,
+        path: &str,
+        config: Value,
+    ) -> Result<ExportResult, ProcessingError> {
+        let cache_key = if self.exporter(exporter_name)?.writes_files() {
+            None
+        } else {
+            Some(source_key(exporter_name, Path::new(path), &config)?)
+        };
+        if let Some(cached) = self.cached(cache_key.as_deref()) {
+            log::debug!("导出缓存命中: {} {}", exporter_name, path);
+            return Ok(cached);
+        }
+        
+        let container = DataLoader::load_from_file(path)?;
+        let mut result = self.export_uncached(exporter_name, &container, config).await?;
+        let point_count: usize = container.curves.iter().map(|curve| curve.point_count).sum();
+        result.metadata.insert("source_point_count".to_string(), serde_json::json!(point_count));
+        
+        self.store(cache_key, &result);
+        Ok(result)
     }
     
     /// Export data to multiple formats
@@ -79,6 +206,58 @@ impl ExportManager {
         Ok(results)
     }
     
+    /// Resolve the exporter to use for an output file from its extension
+    pub fn exporter_for_path(path: &Path) -> Result<&'static str, ProcessingError> {
+        let extension = path.extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase())
+            .unwrap_or_default();
+        
+        match extension.as_str() {
+            "tsv" | "txt" => Ok("tsv"),
+            "json" => Ok("json"),
+            _ => Err(ProcessingError::ConfigError(
+                format!("Unsupported output format: '{}'", extension)
+            )),
+        }
+    }
+    
+    /// Load a previously exported TSV (combined) or JSON file back into a container
+    pub fn load_exported(path: &Path) -> Result<DataContainer, ProcessingError> {
+        let path_str = path.to_string_lossy();
+        match Self::exporter_for_path(path)? {
+            "tsv" => DataLoader::load_from_tsv_export(&path_str),
+            _ => {
+                let content = std::fs::read_to_string(path)?;
+                let container: SerializableDataContainer = serde_json::from_str(&content)?;
+                Ok(container.into())
+            }
+        }
+    }
+    
+    /// Convert an exported file to another format inferred from the output extension
+    pub async fn convert_file(
+        &self,
+        input: &Path,
+        output: &Path,
+    ) -> Result<ExportResult, ProcessingError> {
+        let data = Self::load_exported(input)?;
+        let exporter_name = Self::exporter_for_path(output)?;
+        
+        let config = serde_json::json!({
+            "export_format": "combined",
+            "include_fitted_curves": true,
+        });
+        let mut result = self.export(exporter_name, &data, config).await?;
+        
+        std::fs::write(output, &result.data)?;
+        if let Some(name) = output.file_name() {
+            result.filename = name.to_string_lossy().to_string();
+        }
+        
+        Ok(result)
+    }
+    
     /// Get exporter information
     pub fn get_exporter_info(&self, name: &str) -> Option<ExporterInfo> {
         self.exporters.get(name).map(|exporter| ExporterInfo {
@@ -156,6 +335,8 @@ pub struct BatchExportResult {
     pub failed_formats: Vec<String>,
     pub total_files: usize,
     pub total_size: usize,
+    /// Path of the manifest.json indexing the written files (only when output_dir is set)
+    pub manifest_path: Option<PathBuf>,
 }
 
 impl ExportManager {
@@ -164,39 +345,412 @@ impl ExportManager {
         &self,
         data: &DataContainer,
         config: BatchExportConfig,
+    ) -> Result<BatchExportResult, ProcessingError> {
+        self.batch_export_containers(std::slice::from_ref(data), config).await
+    }
+    
+    /// Export several containers to every configured format
+    ///
+    /// When `output_dir` is set the outputs are written there together with a
+    /// `manifest.json` listing each file's source, format, size, counts and checksum.
+    /// With more than one container, output names are prefixed with the source file stem
+    /// (or `container_N`) to keep them apart.
+    pub async fn batch_export_containers(
+        &self,
+        containers: &[DataContainer],
+        config: BatchExportConfig,
     ) -> Result<BatchExportResult, ProcessingError> {
         let mut results = Vec::new();
         let mut failed_formats = Vec::new();
         let mut total_size = 0;
+        let mut manifest = ExportManifest::new();
         
-        for format in &config.formats {
-            // Get format-specific config or use base config
-            let format_config = config.format_configs.get(format)
-                .cloned()
-                .unwrap_or_else(|| serde_json::to_value(&config.base_config).unwrap());
+        let output_dir = config.output_dir.as_ref().map(PathBuf::from);
+        if let Some(dir) = &output_dir {
+            std::fs::create_dir_all(dir)?;
+        }
+        
+        for (index, data) in containers.iter().enumerate() {
+            let source = data.metadata.get("file_path").and_then(|v| v.as_str());
+            let label = source
+                .and_then(|path| Path::new(path).file_stem())
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_else(|| format!("container_{}", index + 1));
             
-            match self.export(format, data, format_config).await {
-                Ok(mut result) => {
-                    // Apply file prefix if specified
-                    if let Some(prefix) = &config.file_prefix {
-                        result.filename = format!("{}_{}", prefix, result.filename);
+            for format in &config.formats {
+                // Get format-specific config or use base config
+                let format_config = config.format_configs.get(format)
+                    .cloned()
+                    .unwrap_or_else(|| serde_json::to_value(&config.base_config).unwrap());
+                
+                match self.export(format, data, format_config).await {
+                    Ok(mut result) => {
+                        if containers.len() > 1 {
+                            result.filename = format!("{}_{}", label, result.filename);
+                        }
+                        // Apply file prefix if specified
+                        if let Some(prefix) = &config.file_prefix {
+                            result.filename = format!("{}_{}", prefix, result.filename);
+                        }
+                        
+                        if let Some(dir) = &output_dir {
+                            std::fs::write(dir.join(&result.filename), &result.data)?;
+                            let count = |key: &str, fallback: usize| result.metadata.get(key)
+                                .and_then(|v| v.as_u64())
+                                .map_or(fallback, |v| v as usize);
+                            manifest.add_file(
+                                &result.filename,
+                                Some(source.unwrap_or(&label)),
+                                format,
+                                &result.data,
+                                count("curve_count", data.curves.len()),
+                                count("peak_count", data.total_peak_count()),
+                            );
+                        }
+                        
+                        total_size += result.data.len();
+                        results.push(result);
+                    }
+                    Err(e) => {
+                        log::warn!("Failed to export {} to {}: {}", label, format, e);
+                        failed_formats.push(format.clone());
                     }
-                    
-                    total_size += result.data.len();
-                    results.push(result);
-                }
-                Err(e) => {
-                    eprintln!("Failed to export to {}: {}", format, e);
-                    failed_formats.push(format.clone());
                 }
             }
         }
         
+        let manifest_path = match &output_dir {
+            Some(dir) => Some(manifest.write(dir)?),
+            None => None,
+        };
+        
         Ok(BatchExportResult {
             results,
             failed_formats,
-            total_files: config.formats.len(),
+            total_files: config.formats.len() * containers.len(),
             total_size,
+            manifest_path,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::exporters::manifest::{sha256_hex, MANIFEST_FILENAME};
+    use crate::core::data::{Curve, Peak, PeakType};
+    use crate::core::utils::test_fixtures::write_ms1_run;
+
+    fn sample_container() -> DataContainer {
+        let x: Vec<f64> = (0..50).map(|i| i as f64 * 0.2).collect();
+        let y: Vec<f64> = x.iter()
+            .map(|&v| 100.0 * (-0.5 * ((v - 3.0) / 0.4).powi(2)).exp() + 60.0 * (-0.5 * ((v - 7.0) / 0.5).powi(2)).exp())
+            .collect();
+        let mut curve = Curve::new(
+            "curve_1".to_string(), "DT".to_string(), x, y,
+            "Drift Time".to_string(), "Intensity".to_string(), "ms".to_string(), "counts".to_string(),
+        );
+        for (i, (center, amplitude)) in [(3.0, 100.0), (7.0, 60.0)].iter().enumerate() {
+            let mut peak = Peak::new(format!("peak_{}", i), "curve_1".to_string(), *center, *amplitude, PeakType::Gaussian);
+            peak.sigma = 0.4;
+            peak.fwhm = 0.4 * 2.355;
+            curve.add_peak(peak);
+        }
+
+        let mut container = DataContainer::new();
+        container.curves.push(curve);
+        container
+    }
+
+    #[tokio::test]
+    async fn converts_tsv_export_to_json() {
+        let manager = ExportManager::new();
+        let data = sample_container();
+        let tsv = manager.export("tsv", &data, serde_json::json!({})).await.unwrap();
+
+        let dir = std::env::temp_dir();
+        let input = dir.join(format!("mz_curve_convert_{}.tsv", std::process::id()));
+        let output = dir.join(format!("mz_curve_convert_{}.json", std::process::id()));
+        std::fs::write(&input, &tsv.data).unwrap();
+
+        let result = manager.convert_file(&input, &output).await;
+        let written = std::fs::read_to_string(&output);
+        let _ = std::fs::remove_file(&input);
+        let _ = std::fs::remove_file(&output);
+
+        result.unwrap();
+        let converted: SerializableDataContainer = serde_json::from_str(&written.unwrap()).unwrap();
+        assert_eq!(converted.total_peak_count(), data.total_peak_count());
+        assert_eq!(converted.curves[0].x_values.len(), 50);
+    }
+
+    #[tokio::test]
+    async fn quality_filter_skips_low_quality_peaks() {
+        let mut data = sample_container();
+        // 质量分 = 0.4·R² + 0.2·对称 + 0.2·置信度 + 0.2·分辨率
+        for (peak, (rsquared, confidence)) in data.curves[0].peaks.iter_mut().zip([(0.95, 0.9), (0.1, 0.0)]) {
+            peak.rsquared = rsquared;
+            peak.confidence = confidence;
+        }
+        let mut extra = Peak::new("peak_2".to_string(), "curve_1".to_string(), 5.0, 5.0, PeakType::Gaussian);
+        extra.rsquared = 0.8;
+        extra.confidence = 0.7;
+        data.curves[0].add_peak(extra);
+        let expected: Vec<String> = data.curves[0].peaks.iter()
+            .filter(|p| p.get_quality_score() >= 0.6)
+            .map(|p| p.id.clone())
+            .collect();
+        assert_eq!(expected, vec!["peak_0".to_string(), "peak_2".to_string()]);
+
+        let manager = ExportManager::new();
+        let result = manager.export("json", &data, serde_json::json!({ "min_quality": 0.6 })).await.unwrap();
+
+        let exported: SerializableDataContainer = serde_json::from_slice(&result.data).unwrap();
+        let ids: Vec<String> = exported.curves[0].peaks.iter().map(|p| p.id.clone()).collect();
+        assert_eq!(ids, expected);
+        assert_eq!(result.metadata["filtered_peak_count"], 1);
+        assert_eq!(result.metadata["peak_count"], 2);
+
+        // 未设置过滤条件时导出全部峰，且不记录过滤信息
+        let unfiltered = manager.export("json", &data, serde_json::json!({})).await.unwrap();
+        assert_eq!(unfiltered.metadata["peak_count"], 3);
+        assert!(!unfiltered.metadata.contains_key("filtered_peak_count"));
+    }
+
+    #[tokio::test]
+    async fn batch_export_writes_manifest_with_checksums() {
+        let containers: Vec<DataContainer> = (1..=3)
+            .map(|i| {
+                let mut data = sample_container();
+                data.metadata.insert("file_path".to_string(), serde_json::json!(format!("/data/run_{}.mzML", i)));
+                data.curves[0].peaks.truncate(i.min(2));
+                data
+            })
+            .collect();
+        let output_dir = std::env::temp_dir().join(format!("mz_curve_manifest_{}", std::process::id()));
+        let config = BatchExportConfig {
+            formats: vec!["json".to_string()],
+            output_dir: Some(output_dir.to_string_lossy().to_string()),
+            ..BatchExportConfig::default()
+        };
+
+        let result = ExportManager::new().batch_export_containers(&containers, config).await;
+        let outcome = result.as_ref().ok().and_then(|r| r.manifest_path.clone()).map(|path| {
+            let manifest = ExportManifest::load(&path).unwrap();
+            let on_disk: Vec<String> = manifest.entries.iter()
+                .map(|entry| sha256_hex(&std::fs::read(output_dir.join(&entry.file)).unwrap()))
+                .collect();
+            (path, manifest, on_disk)
+        });
+        let _ = std::fs::remove_dir_all(&output_dir);
+
+        let result = result.unwrap();
+        assert!(result.failed_formats.is_empty());
+        assert_eq!(result.total_files, 3);
+        let (path, manifest, on_disk) = outcome.expect("manifest written");
+        assert_eq!(path, output_dir.join(MANIFEST_FILENAME));
+        assert_eq!(manifest.entries.len(), 3);
+        for (i, (entry, checksum)) in manifest.entries.iter().zip(&on_disk).enumerate() {
+            assert_eq!(&entry.checksum, checksum);
+            assert_eq!(entry.checksum.len(), 64);
+            assert_eq!(entry.format, "json");
+            assert_eq!(entry.source.as_deref(), Some(format!("/data/run_{}.mzML", i + 1).as_str()));
+            assert!(entry.file.starts_with(&format!("run_{}_", i + 1)));
+            assert_eq!(entry.size_bytes, result.results[i].data.len() as u64);
+            assert_eq!(entry.peak_count, (i + 1).min(2));
+            assert_eq!(entry.curve_count, 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn decimal_precision_override_controls_rendered_digits() {
+        let manager = ExportManager::new();
+        let data = sample_container();
+        let fraction_digits = |value: &str| value.split('.').nth(1).map_or(0, str::len);
+
+        let mut tsv_digits = Vec::new();
+        let mut json_digits = Vec::new();
+        for precision in [3, 9] {
+            let config = serde_json::json!({ "export_format": "peaks_only", "decimal_precision": precision });
+            let tsv = String::from_utf8(manager.export("tsv", &data, config.clone()).await.unwrap().data).unwrap();
+            let center = tsv.lines().nth(1).unwrap().split('\t').nth(2).unwrap().to_string();
+            tsv_digits.push(fraction_digits(&center));
+
+            let json = manager.export("json", &data, config).await.unwrap();
+            let exported: Value = serde_json::from_slice(&json.data).unwrap();
+            let max_digits = exported["curves"][0]["y_values"].as_array().unwrap().iter()
+                .map(|v| fraction_digits(&v.to_string()))
+                .max()
+                .unwrap();
+            json_digits.push(max_digits);
+        }
+
+        assert_eq!(tsv_digits, vec![3, 9]);
+        assert_eq!(json_digits, vec![3, 9]);
+
+        for invalid in [0, 16] {
+            let error = manager.export("tsv", &data, serde_json::json!({ "decimal_precision": invalid })).await.unwrap_err();
+            assert!(matches!(error, ProcessingError::ConfigError(_)), "{}", error);
+        }
+    }
+
+    #[tokio::test]
+    async fn sort_by_area_desc_orders_exported_peaks() {
+        let mut data = sample_container();
+        for (i, area) in [(0, 40.0), (1, 90.0)] {
+            data.curves[0].peaks[i].area = area;
+        }
+        let mut extra = Peak::new("peak_2".to_string(), "curve_1".to_string(), 5.0, 20.0, PeakType::Gaussian);
+        extra.area = 65.0;
+        data.curves[0].add_peak(extra);
+
+        let manager = ExportManager::new();
+        let config = serde_json::json!({ "export_format": "peaks_only", "sort_by": "area_desc" });
+        let tsv = String::from_utf8(manager.export("tsv", &data, config.clone()).await.unwrap().data).unwrap();
+        let tsv_ids: Vec<&str> = tsv.lines().skip(1).map(|line| line.split('\t').next().unwrap()).collect();
+        assert_eq!(tsv_ids, vec!["peak_1", "peak_2", "peak_0"]);
+
+        let json = manager.export("json", &data, config).await.unwrap();
+        let exported: SerializableDataContainer = serde_json::from_slice(&json.data).unwrap();
+        let areas: Vec<f64> = exported.curves[0].peaks.iter().map(|p| p.area).collect();
+        assert_eq!(areas, vec![90.0, 65.0, 40.0]);
+
+        // 默认按中心升序
+        let default = manager.export("json", &data, serde_json::json!({})).await.unwrap();
+        let exported: SerializableDataContainer = serde_json::from_slice(&default.data).unwrap();
+        let centers: Vec<f64> = exported.curves[0].peaks.iter().map(|p| p.center).collect();
+        assert_eq!(centers, vec![3.0, 5.0, 7.0]);
+
+        let invalid = manager.export("tsv", &data, serde_json::json!({ "sort_by": "width" })).await;
+        assert!(matches!(invalid, Err(ProcessingError::ConfigError(_))));
+    }
+
+    #[tokio::test]
+    async fn max_curve_points_decimates_curve_export_and_keeps_peak_maxima() {
+        // 50k 点曲线上的三个窄峰，每个峰只覆盖约 50 个点
+        let x: Vec<f64> = (0..50_000).map(|i| i as f64 * 0.001).collect();
+        let centers = [10.0, 25.0, 40.0];
+        let y: Vec<f64> = x.iter()
+            .map(|&v| 1.0 + centers.iter().enumerate()
+                .map(|(i, c)| 100.0 * (i + 1) as f64 * (-0.5 * ((v - c) / 0.008).powi(2)).exp())
+                .sum::<f64>())
+            .collect();
+        let apex_values: Vec<String> = centers.iter()
+            .map(|&c| format!("{:.6}", y[(c / 0.001).round() as usize]))
+            .collect();
+        let mut data = DataContainer::new();
+        data.curves.push(Curve::new(
+            "long_curve".to_string(), "DT".to_string(), x, y,
+            "Drift Time".to_string(), "Intensity".to_string(), "ms".to_string(), "counts".to_string(),
+        ));
+
+        let output_dir = std::env::temp_dir().join(format!("mz_curve_decimated_{}", std::process::id()));
+        let config = serde_json::json!({
+            "output_folder": output_dir.to_string_lossy(),
+            "max_curve_points": 1000
+        });
+        let result = ExportManager::new().export("curve_tsv", &data, config).await;
+        let written = std::fs::read_to_string(output_dir.join("curve_1_DT.tsv"));
+        let _ = std::fs::remove_dir_all(&output_dir);
+
+        let result = result.unwrap();
+        assert_eq!(result.metadata["original_point_counts"]["long_curve"], 50_000);
+        let written = written.unwrap();
+        assert!(written.contains("# Original Data Points: 50000"));
+        let rows: Vec<&str> = written.lines().filter(|line| !line.starts_with('#')).skip(1).collect();
+        assert!(rows.len() <= 1000, "{} rows", rows.len());
+        let intensities: Vec<&str> = rows.iter().map(|row| row.split('\t').nth(1).unwrap()).collect();
+        for apex in &apex_values {
+            assert!(intensities.contains(&apex.as_str()), "peak maximum {} lost", apex);
+        }
+
+        let invalid = ExportManager::new().export("json", &data, serde_json::json!({ "max_curve_points": 1 })).await;
+        assert!(matches!(invalid, Err(ProcessingError::ConfigError(_))));
+    }
+
+    #[tokio::test]
+    async fn repeated_file_export_is_served_from_cache_until_the_file_changes() {
+        let dir = std::env::temp_dir().join(format!("mz_curve_export_cache_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("sample.mzML");
+        write_ms1_run(&path, 3, 0.1, |i| {
+            (0..4).map(|j| (500.0 + j as f64, (10 + i + j) as f32)).collect()
+        });
+        let path = path.to_string_lossy().to_string();
+
+        let manager = ExportManager::new();
+        let config = serde_json::json!({"decimal_precision": 4});
+        let first = manager.export_file("json", &path, config.clone()).await;
+        let second = manager.export_file("json", &path, config.clone()).await;
+        let hits_after_repeat = manager.cache_hits();
+
+        // A different config is a miss; so is the same file after it is rewritten
+        let other_config = manager.export_file("json", &path, serde_json::json!({"decimal_precision": 3})).await;
+        let modified = std::fs::File::options().write(true).open(&path)
+            .and_then(|file| file.set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(5)));
+        let rewritten = manager.export_file("json", &path, config).await;
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let (first, second) = (first.unwrap(), second.unwrap());
+        assert_eq!(hits_after_repeat, 1);
+        assert_eq!(first.data, second.data);
+        assert!(first.metadata.contains_key("source_point_count"));
+        other_config.unwrap();
+        modified.unwrap();
+        rewritten.unwrap();
+        assert_eq!(manager.cache_hits(), 1);
+    }
+
+    #[tokio::test]
+    async fn identical_containers_are_served_from_cache() {
+        let manager = ExportManager::new();
+        // Built separately, so their metadata maps are not clones of each other
+        let (mut first, mut second) = (sample_container(), sample_container());
+        for (key, value) in [("source", "run_a"), ("operator", "lab"), ("instrument", "tims")] {
+            first.metadata.insert(key.to_string(), serde_json::json!(value));
+        }
+        for (key, value) in [("instrument", "tims"), ("operator", "lab"), ("source", "run_a")] {
+            second.metadata.insert(key.to_string(), serde_json::json!(value));
+        }
+        let config = serde_json::json!({"decimal_precision": 4, "include_metadata": true});
+
+        let exported = manager.export("json", &first, config.clone()).await.unwrap();
+        assert_eq!(manager.cache_hits(), 0);
+        let repeated = manager.export("json", &second, config.clone()).await.unwrap();
+        assert_eq!(manager.cache_hits(), 1);
+        assert_eq!(exported.data, repeated.data);
+
+        // Changed content or a different format is a miss
+        second.curves[0].peaks[0].amplitude = 99.0;
+        manager.export("json", &second, config.clone()).await.unwrap();
+        manager.export("tsv", &first, config).await.unwrap();
+        assert_eq!(manager.cache_hits(), 1);
+    }
+
+    #[test]
+    fn export_cache_evicts_oldest_entries_beyond_byte_limit() {
+        let result = |name: &str, size: usize| ExportResult {
+            data: vec![b'x'; size],
+            filename: name.to_string(),
+            mime_type: "text/plain".to_string(),
+            metadata: HashMap::new(),
+        };
+        // Each entry takes 100 data bytes plus 1 + 10 bytes of filename and MIME type
+        let mut cache = ExportCache::new(250);
+        for key in ["a", "b", "c"] {
+            cache.insert(key.to_string(), result(key, 100));
+        }
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.size_bytes(), 222);
+        assert!(cache.get("a").is_none());
+        assert!(cache.get("c").is_some());
+
+        // A result larger than the whole cache is not kept and evicts nothing
+        cache.insert("huge".to_string(), result("huge", 1000));
+        assert!(cache.get("huge").is_none());
+        assert_eq!(cache.len(), 2);
+
+        cache.insert("c".to_string(), result("c", 10));
+        assert_eq!(cache.size_bytes(), 132);
+    }
+}
@@ -0,0 +1,231 @@
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::core::data::{DataContainer, Peak, PeakType, ProcessingError, SerializableDataContainer};
+use crate::core::processors::peak_fitting::peak_shapes::PeakShapeParams;
+use super::base::{Exporter, ExportResult, ExportConfig, helpers};
+
+/// JSON exporter - serializes curves (with nested peaks) and metadata; every fitted peak
+/// carries a `fit` object with its parameters and errors keyed by parameter name
+pub struct JsonExporter;
+
+#[async_trait]
+impl Exporter for JsonExporter {
+    fn name(&self) -> &str {
+        "json_exporter"
+    }
+
+    fn description(&self) -> &str {
+        "Export curves, peaks and metadata as a JSON document"
+    }
+
+    fn file_extension(&self) -> &str {
+        "json"
+    }
+
+    fn mime_type(&self) -> &str {
+        "application/json"
+    }
+
+    fn config_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "pretty": {
+                    "type": "boolean",
+                    "default": true,
+                    "description": "Pretty-print the JSON output"
+                },
+                "include_metadata": {
+                    "type": "boolean",
+                    "default": true,
+                    "description": "Include container metadata"
+                },
+                "sort_by": {
+                    "type": "string",
+                    "enum": ["center", "area_desc", "amplitude_desc", "quality_desc"],
+                    "default": "center",
+                    "description": "Peak order within each curve"
+                },
+                "decimal_precision": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 15,
+                    "description": "Round floating point values to this many decimals; full precision when omitted"
+                },
+                "max_curve_points": {
+                    "type": "integer",
+                    "minimum": 2,
+                    "description": "Decimate longer curves to this many points, keeping peak maxima; full resolution when omitted"
+                },
+                "group_by": {
+                    "type": "string",
+                    "enum": ["none", "cluster"],
+                    "default": "none",
+                    "description": "\"cluster\" replaces each curve's peak list with overlap clusters holding their region, summed area and member peaks"
+                }
+            }
+        })
+    }
+
+    async fn export(
+        &self,
+        data: &DataContainer,
+        config: Value,
+    ) -> Result<ExportResult, ProcessingError> {
+        let pretty = config["pretty"].as_bool().unwrap_or(true);
+        let precision_override = config["decimal_precision"].as_u64().map(|p| p as usize);
+        let group_by_cluster = match config["group_by"].as_str().unwrap_or("none") {
+            "none" => false,
+            "cluster" => true,
+            other => return Err(ProcessingError::ConfigError(format!(
+                "Unsupported group_by: '{}' (expected none or cluster)", other
+            ))),
+        };
+        let export_config: ExportConfig = serde_json::from_value(config)
+            .unwrap_or_default();
+
+        // Raw spectra are not embedded; the document only carries curves and peaks
+        let mut sorted = helpers::sort_peaks(data, &export_config)?;
+        for curve in &mut sorted.curves {
+            let area_unit = curve.area_unit();
+            for peak in curve.get_peaks_mut().iter_mut().filter(|peak| peak.area_unit.is_none()) {
+                peak.area_unit = Some(area_unit.clone());
+            }
+        }
+        let document = SerializableDataContainer {
+            metadata: if export_config.include_metadata { sorted.metadata } else { Default::default() },
+            spectra: Vec::new(),
+            curves: sorted.curves,
+        };
+
+        let clusters: Vec<_> = if group_by_cluster {
+            document.curves.iter().map(|curve| helpers::group_peaks_by_cluster(&curve.peaks)).collect()
+        } else {
+            Vec::new()
+        };
+        let curve_peaks: Vec<Vec<Peak>> = document.curves.iter().map(|curve| curve.peaks.clone()).collect();
+
+        let mut document = serde_json::to_value(&document)?;
+        document["total_integrated_area"] = serde_json::json!(data.total_integrated_area());
+        if let Some(curves) = document["curves"].as_array_mut() {
+            if group_by_cluster {
+                for (curve, curve_clusters) in curves.iter_mut().zip(clusters) {
+                    let mut cluster_values = serde_json::to_value(&curve_clusters)?;
+                    for (cluster_value, cluster) in cluster_values.as_array_mut().into_iter().flatten().zip(&curve_clusters) {
+                        attach_fit_objects(&mut cluster_value["peaks"], &cluster.peaks);
+                    }
+                    if let Some(object) = curve.as_object_mut() {
+                        object.remove("peaks");
+                        object.insert("clusters".to_string(), cluster_values);
+                    }
+                }
+            } else {
+                for (curve, peaks) in curves.iter_mut().zip(&curve_peaks) {
+                    attach_fit_objects(&mut curve["peaks"], peaks);
+                }
+            }
+        }
+        if let Some(precision) = precision_override {
+            helpers::round_json_floats(&mut document, precision);
+        }
+        let content = if pretty {
+            serde_json::to_vec_pretty(&document)?
+        } else {
+            serde_json::to_vec(&document)?
+        };
+
+        let filename = format!("ims_data_{}.json", helpers::generate_timestamp());
+        let mut metadata = helpers::create_export_metadata(
+            self.name(),
+            data.curves.len(),
+            data.total_peak_count(),
+            &export_config,
+        );
+        metadata.insert("total_integrated_area".to_string(), serde_json::json!(data.total_integrated_area()));
+
+        Ok(ExportResult {
+            data: content,
+            filename,
+            mime_type: self.mime_type().to_string(),
+            metadata,
+        })
+    }
+}
+
+/// Parameter names for a peak's `fit_parameters`, following the peak shape's parameter order;
+/// shapes without a fitting definition (and any extra positions) fall back to `param_<index>`
+fn fit_parameter_names(peak: &Peak) -> Vec<String> {
+    let shape_names = match peak.peak_type {
+        PeakType::AsymmetricGaussian | PeakType::VoigtExponentialTail | PeakType::Custom(_) => Vec::new(),
+        _ => PeakShapeParams::from_peak(peak).parameter_names,
+    };
+    (0..peak.fit_parameters.len())
+        .map(|i| shape_names.get(i).cloned().unwrap_or_else(|| format!("param_{}", i)))
+        .collect()
+}
+
+/// Named fit parameters with their errors, e.g. `{"sigma": {"value": 0.1, "error": 0.002}}`;
+/// None when the peak was never fitted
+fn fit_object(peak: &Peak) -> Option<Value> {
+    if peak.fit_parameters.is_empty() {
+        return None;
+    }
+    let parameters: serde_json::Map<String, Value> = fit_parameter_names(peak).into_iter()
+        .zip(&peak.fit_parameters)
+        .enumerate()
+        .map(|(i, (name, &value))| (name, serde_json::json!({
+            "value": value,
+            "error": peak.fit_parameter_errors.get(i),
+        })))
+        .collect();
+    Some(Value::Object(parameters))
+}
+
+/// Add a `fit` object to each serialized peak, matched by position
+fn attach_fit_objects(peak_values: &mut Value, peaks: &[Peak]) {
+    for (peak_value, peak) in peak_values.as_array_mut().into_iter().flatten().zip(peaks) {
+        if let (Some(object), Some(fit)) = (peak_value.as_object_mut(), fit_object(peak)) {
+            object.insert("fit".to_string(), fit);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::data::Curve;
+
+    #[tokio::test]
+    async fn emg_peak_exposes_named_fit_parameters() {
+        let mut curve = Curve::new(
+            "curve".to_string(),
+            "DT".to_string(),
+            vec![0.0, 1.0, 2.0],
+            vec![1.0, 2.0, 1.0],
+            "Drift Time".to_string(),
+            "Intensity".to_string(),
+            "ms".to_string(),
+            "counts".to_string(),
+        );
+        let mut peak = Peak::new("emg".to_string(), "curve".to_string(), 1.0, 100.0, PeakType::EMG);
+        peak.set_fit_parameters(vec![100.0, 1.0, 0.2, 0.5], vec![2.0, 0.01, 0.003, 0.04], None);
+        curve.add_peak(peak);
+        let mut data = DataContainer::new();
+        data.curves.push(curve);
+
+        let result = JsonExporter.export(&data, serde_json::json!({})).await.unwrap();
+        let document: Value = serde_json::from_slice(&result.data).unwrap();
+        let fit = &document["curves"][0]["peaks"][0]["fit"];
+        assert_eq!(fit["sigma"]["value"], 0.2);
+        assert_eq!(fit["sigma"]["error"], 0.003);
+        assert_eq!(fit["tau"]["value"], 0.5);
+        assert_eq!(fit["tau"]["error"], 0.04);
+        assert_eq!(fit["amplitude"]["error"], 2.0);
+        assert_eq!(document["curves"][0]["peaks"][0]["area_unit"], "counts·ms");
+
+        let result = JsonExporter.export(&data, serde_json::json!({"group_by": "cluster"})).await.unwrap();
+        let document: Value = serde_json::from_slice(&result.data).unwrap();
+        assert_eq!(document["curves"][0]["clusters"][0]["peaks"][0]["fit"]["tau"]["error"], 0.04);
+    }
+}
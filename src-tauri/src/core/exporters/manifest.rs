@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::core::data::ProcessingError;
+
+/// File name of the manifest written next to batch export outputs
+pub const MANIFEST_FILENAME: &str = "manifest.json";
+
+/// One output file of a batch export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Output file name, relative to the manifest's folder
+    pub file: String,
+    /// Input the file was generated from (data file path or container label)
+    pub source: Option<String>,
+    /// Exporter / format that produced the file
+    pub format: String,
+    /// File size in bytes
+    pub size_bytes: u64,
+    /// Number of curves in the file
+    pub curve_count: usize,
+    /// Number of peaks in the file
+    pub peak_count: usize,
+    /// SHA-256 of the file content, lowercase hex
+    pub checksum: String,
+}
+
+/// Index of every file written by a batch export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportManifest {
+    /// Creation time (RFC 3339)
+    pub created_at: String,
+    /// Algorithm used for `ManifestEntry::checksum`
+    pub checksum_algorithm: String,
+    pub entries: Vec<ManifestEntry>,
+}
+
+impl ExportManifest {
+    pub fn new() -> Self {
+        Self {
+            created_at: chrono::Utc::now().to_rfc3339(),
+            checksum_algorithm: "sha256".to_string(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Record an output file from its written content
+    pub fn add_file(
+        &mut self,
+        file: &str,
+        source: Option<&str>,
+        format: &str,
+        content: &[u8],
+        curve_count: usize,
+        peak_count: usize,
+    ) {
+        self.entries.push(ManifestEntry {
+            file: file.to_string(),
+            source: source.map(str::to_string),
+            format: format.to_string(),
+            size_bytes: content.len() as u64,
+            curve_count,
+            peak_count,
+            checksum: sha256_hex(content),
+        });
+    }
+
+    /// Write the manifest as `manifest.json` into the folder and return its path
+    pub fn write(&self, folder: &Path) -> Result<PathBuf, ProcessingError> {
+        let path = folder.join(MANIFEST_FILENAME);
+        fs::write(&path, serde_json::to_vec_pretty(self)?)?;
+        Ok(path)
+    }
+
+    /// Load a manifest written by `write`
+    pub fn load(path: &Path) -> Result<Self, ProcessingError> {
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+}
+
+impl Default for ExportManifest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// SHA-256 digest of the data as lowercase hex
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
@@ -2,12 +2,26 @@ pub mod base;
 pub mod tsv_exporter;
 pub mod plotly_exporter;
 pub mod export_manager;
+pub mod export_cache;
 pub mod curve_tsv_exporter;
 pub mod spectro_tsv_exporter;
+pub mod json_exporter;
+pub mod mzml_annotation_exporter;
+pub mod manifest;
+pub mod target_list_exporter;
+pub mod convergence_exporter;
+pub mod sqlite_exporter;
 
-pub use base::{Exporter, ExportResult, ExportConfig};
+pub use base::{Exporter, ExportResult, ExportConfig, PeakCluster};
 pub use tsv_exporter::TsvExporter;
 pub use plotly_exporter::PlotlyExporter;
 pub use curve_tsv_exporter::CurveTsvExporter;
 pub use spectro_tsv_exporter::SpectroTsvExporter;
+pub use json_exporter::JsonExporter;
+pub use mzml_annotation_exporter::MzMLAnnotationExporter;
+pub use target_list_exporter::TargetListExporter;
+pub use convergence_exporter::ConvergenceExporter;
+pub use sqlite_exporter::SqliteExporter;
+pub use manifest::{ExportManifest, ManifestEntry, MANIFEST_FILENAME};
 pub use export_manager::{ExportManager, ExporterInfo, BatchExportConfig, BatchExportResult};
+pub use export_cache::{ExportCache, DEFAULT_EXPORT_CACHE_BYTES};
@@ -0,0 +1,237 @@
+use async_trait::async_trait;
+use mzdata::prelude::*;
+use mzdata::io::MzMLWriter;
+use mzdata::params::Param;
+use mzdata::spectrum::{
+    ArrayType, BinaryArrayMap, BinaryDataArrayType, Chromatogram, ChromatogramDescription,
+    ChromatogramType, DataArray,
+};
+use mzdata::MZReader;
+use serde_json::Value;
+
+use crate::core::data::{Curve, DataContainer, ProcessingError};
+use super::base::{Exporter, ExportResult, ExportConfig, helpers};
+
+/// Prefix of the chromatogram ids that carry detected-peak annotations
+pub const FEATURE_CHROMATOGRAM_PREFIX: &str = "mz_curve_features=";
+/// userParam name holding one detected peak (JSON encoded) on a feature chromatogram
+pub const FEATURE_PARAM_NAME: &str = "mz_curve:feature";
+
+/// mzML annotation exporter - rewrites the source spectra and appends one
+/// chromatogram per analysed curve, annotated with its detected peaks
+pub struct MzMLAnnotationExporter;
+
+#[async_trait]
+impl Exporter for MzMLAnnotationExporter {
+    fn name(&self) -> &str {
+        "mzml_annotation_exporter"
+    }
+
+    fn description(&self) -> &str {
+        "Copy the input mzML and annotate detected peaks as feature chromatograms"
+    }
+
+    fn file_extension(&self) -> &str {
+        "mzML"
+    }
+
+    fn mime_type(&self) -> &str {
+        "application/xml"
+    }
+
+    fn config_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "source_file": {
+                    "type": "string",
+                    "description": "Input mzML to copy file-level metadata (and spectra, if the container has none) from; defaults to the container's file_path"
+                },
+                "include_empty_curves": {
+                    "type": "boolean",
+                    "default": false,
+                    "description": "Also write chromatograms for curves without detected peaks"
+                }
+            }
+        })
+    }
+
+    async fn export(
+        &self,
+        data: &DataContainer,
+        config: Value,
+    ) -> Result<ExportResult, ProcessingError> {
+        let source_file = config["source_file"].as_str()
+            .map(str::to_string)
+            .or_else(|| data.metadata.get("file_path").and_then(|v| v.as_str()).map(str::to_string));
+        let include_empty_curves = config["include_empty_curves"].as_bool().unwrap_or(false);
+        let export_config: ExportConfig = serde_json::from_value(config)
+            .unwrap_or_default();
+
+        // The source file supplies the file-level metadata and, when the container
+        // was stripped of its spectra, the spectra themselves
+        let mut source = match &source_file {
+            Some(path) if std::path::Path::new(path).exists() => Some(
+                MZReader::open_path(path).map_err(|e| ProcessingError::MzDataError(e.to_string()))?
+            ),
+            _ => None,
+        };
+        let source_spectra;
+        let spectra = if !data.spectra.is_empty() {
+            &data.spectra
+        } else if let Some(reader) = source.as_mut() {
+            source_spectra = reader.iter().collect::<Vec<_>>();
+            &source_spectra
+        } else {
+            return Err(ProcessingError::DataError(
+                "No spectra to annotate: the container has no spectra and no readable source_file".to_string()
+            ));
+        };
+
+        let curves: Vec<&Curve> = data.curves.iter()
+            .filter(|c| include_empty_curves || !c.peaks.is_empty())
+            .collect();
+
+        let mut buffer = Vec::new();
+        {
+            let mut writer = MzMLWriter::new(&mut buffer);
+            if let Some(reader) = source.as_ref() {
+                writer.copy_metadata_from(reader);
+            }
+            writer.set_spectrum_count(spectra.len() as u64);
+            // Two summary chromatograms (TIC, BPC) are always written on close
+            writer.chromatogram_count = 2 + curves.len() as u64;
+
+            for spectrum in spectra {
+                writer.write(spectrum)
+                    .map_err(|e| ProcessingError::MzDataError(e.to_string()))?;
+            }
+            for (index, curve) in curves.iter().enumerate() {
+                writer.write_chromatogram(&feature_chromatogram(curve, index)?)
+                    .map_err(|e| ProcessingError::MzDataError(e.to_string()))?;
+            }
+            writer.close()
+                .map_err(|e| ProcessingError::MzDataError(e.to_string()))?;
+        }
+
+        let filename = format!("annotated_{}.mzML", helpers::generate_timestamp());
+        let mut metadata = helpers::create_export_metadata(
+            self.name(),
+            data.curves.len(),
+            data.total_peak_count(),
+            &export_config,
+        );
+        metadata.insert("spectra_count".to_string(), serde_json::json!(spectra.len()));
+        metadata.insert("annotated_curves".to_string(), serde_json::json!(curves.len()));
+
+        Ok(ExportResult {
+            data: buffer,
+            filename,
+            mime_type: self.mime_type().to_string(),
+            metadata,
+        })
+    }
+}
+
+/// Build the annotation chromatogram for a curve: its x/y trace plus one
+/// `mz_curve:feature` userParam per detected peak
+fn feature_chromatogram(curve: &Curve, index: usize) -> Result<Chromatogram, ProcessingError> {
+    let mut description = ChromatogramDescription {
+        id: format!("{}{}", FEATURE_CHROMATOGRAM_PREFIX, curve.id),
+        // Indices 0 and 1 are taken by the summary chromatograms
+        index: index + 2,
+        chromatogram_type: match curve.curve_type.as_str() {
+            "XIC" | "EIC" => ChromatogramType::SelectedIonCurrentChromatogram,
+            "TIC" | "TIC_MS1" | "TIC_MS2" => ChromatogramType::TotalIonCurrentChromatogram,
+            "BPC" => ChromatogramType::BasePeakChromatogram,
+            _ => ChromatogramType::Unknown,
+        },
+        ..Default::default()
+    };
+    description.params.push(Param::new_key_value("mz_curve:curve_type", curve.curve_type.clone()));
+    description.params.push(Param::new_key_value("mz_curve:x_label", format!("{} ({})", curve.x_label, curve.x_unit)));
+    for peak in &curve.peaks {
+        let feature = serde_json::json!({
+            "id": peak.id,
+            "center": peak.center,
+            "amplitude": peak.amplitude,
+            "area": peak.area,
+            "fwhm": peak.fwhm,
+            "left_boundary": peak.left_boundary,
+            "right_boundary": peak.right_boundary,
+            "rsquared": peak.rsquared,
+            "peak_type": peak.peak_type,
+        });
+        description.params.push(Param::new_key_value(FEATURE_PARAM_NAME, serde_json::to_string(&feature)?));
+    }
+
+    let mut time = DataArray::from_name_and_type(&ArrayType::TimeArray, BinaryDataArrayType::Float64);
+    time.extend(&curve.x_values).map_err(|e| ProcessingError::MzDataError(e.to_string()))?;
+    let intensities: Vec<f32> = curve.y_values.iter().map(|&y| y as f32).collect();
+    let mut intensity = DataArray::from_name_and_type(&ArrayType::IntensityArray, BinaryDataArrayType::Float32);
+    intensity.extend(&intensities).map_err(|e| ProcessingError::MzDataError(e.to_string()))?;
+
+    let mut arrays = BinaryArrayMap::new();
+    arrays.add(time);
+    arrays.add(intensity);
+
+    Ok(Chromatogram::new(description, arrays))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::data::{Peak, PeakType};
+    use crate::core::loaders::mzdata_loader::DataLoader;
+    use crate::core::utils::test_fixtures::write_ms1_run;
+    use mzdata::io::MzMLReader;
+
+    fn write_source(path: &std::path::Path) {
+        write_ms1_run(path, 5, 0.5, |i| {
+            (0..3).map(|j| (400.0 + j as f64, (100 + 10 * i + j) as f32)).collect()
+        });
+    }
+
+    #[tokio::test]
+    async fn annotated_mzml_keeps_spectra_and_parses_features() {
+        let dir = std::env::temp_dir();
+        let source = dir.join(format!("mz_curve_annotate_src_{}.mzML", std::process::id()));
+        let output = dir.join(format!("mz_curve_annotate_out_{}.mzML", std::process::id()));
+        write_source(&source);
+
+        let mut container = DataLoader::load_from_file(source.to_str().unwrap()).unwrap();
+        let mut curve = Curve::new(
+            "TIC_MS1".to_string(), "TIC".to_string(),
+            vec![0.0, 0.5, 1.0, 1.5, 2.0], vec![303.0, 333.0, 363.0, 393.0, 423.0],
+            "Retention Time".to_string(), "Intensity".to_string(), "min".to_string(), "counts".to_string(),
+        );
+        let mut peak = Peak::new("peak_1".to_string(), curve.id.clone(), 1.5, 393.0, PeakType::Gaussian);
+        peak.area = 512.5;
+        curve.peaks.push(peak);
+        container.curves.push(curve);
+
+        let result = MzMLAnnotationExporter.export(&container, serde_json::json!({})).await.unwrap();
+        std::fs::write(&output, &result.data).unwrap();
+        let _ = std::fs::remove_file(&source);
+
+        let mut reader = MzMLReader::open_path(&output).unwrap();
+        let spectra: Vec<_> = reader.iter().collect();
+        let chromatogram = reader
+            .get_chromatogram_by_id(&format!("{}TIC_MS1", FEATURE_CHROMATOGRAM_PREFIX));
+        let _ = std::fs::remove_file(&output);
+
+        assert_eq!(spectra.len(), 5);
+        assert_eq!(spectra[2].id(), "scan=3");
+
+        let chromatogram = chromatogram.expect("feature chromatogram present");
+        assert_eq!(chromatogram.time().unwrap().len(), 5);
+        let features: Vec<Value> = chromatogram.params().iter()
+            .filter(|p| p.name == FEATURE_PARAM_NAME)
+            .map(|p| serde_json::from_str(&p.value.to_string()).unwrap())
+            .collect();
+        assert_eq!(features.len(), 1);
+        assert_eq!(features[0]["id"], "peak_1");
+        assert_eq!(features[0]["center"].as_f64().unwrap(), 1.5);
+        assert_eq!(features[0]["area"].as_f64().unwrap(), 512.5);
+    }
+}
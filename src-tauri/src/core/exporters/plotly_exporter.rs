@@ -1,11 +1,108 @@
 use async_trait::async_trait;
 use serde_json::Value;
+use mzdata::prelude::*;
 use crate::core::data::{DataContainer, ProcessingError, PeakType, Curve, Peak};
+use crate::core::processors::peak_detection::estimate_noise_floor;
+use crate::core::processors::peak_fitting::peak_shapes::{PeakShapeParams, PeakShapeCalculatorFactory};
+use crate::core::utils::display_decimation::min_max_decimation;
 use super::base::{Exporter, ExportResult, ExportConfig, helpers};
 
 /// Plotly exporter for interactive visualization of mass spectrometry data
 pub struct PlotlyExporter;
 
+/// Trace palettes for the supported color schemes
+const DEFAULT_PALETTE: &[&str] = &[
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd",
+    "#8c564b", "#e377c2", "#7f7f7f", "#bcbd22", "#17becf"
+];
+const VIRIDIS_PALETTE: &[&str] = &[
+    "#440154", "#482878", "#3e4989", "#31688e", "#26828e",
+    "#1f9e89", "#35b779", "#6ece58", "#b5de2b", "#fde725"
+];
+/// Okabe-Ito palette, distinguishable under common color vision deficiencies
+const COLORBLIND_PALETTE: &[&str] = &[
+    "#0072B2", "#E69F00", "#009E73", "#D55E00", "#56B4E9",
+    "#CC79A7", "#F0E442", "#000000"
+];
+const GRAYSCALE_PALETTE: &[&str] = &[
+    "#000000", "#404040", "#606060", "#808080", "#a0a0a0", "#c0c0c0"
+];
+
+/// Peaks closer than this fraction of the x span are treated as crowded
+const ANNOTATION_CROWDING_FRACTION: f64 = 0.04;
+/// Vertical arrow offsets (pixels) cycled through for crowded annotations
+const ANNOTATION_OFFSETS: &[i64] = &[-30, -55, -80];
+/// Default per-trace point cap; larger traces make the browser unresponsive
+const DEFAULT_MAX_POINTS_PER_TRACE: u64 = 5000;
+
+/// Get the trace palette for a color scheme name, falling back to the default palette
+fn palette_for_scheme(scheme: &str) -> &'static [&'static str] {
+    match scheme {
+        "viridis" => VIRIDIS_PALETTE,
+        "colorblind" => COLORBLIND_PALETTE,
+        "grayscale" => GRAYSCALE_PALETTE,
+        _ => DEFAULT_PALETTE,
+    }
+}
+
+/// RT x m/z intensity grid; one row of `z` per spectrum
+struct HeatmapGrid {
+    rt: Vec<f64>,
+    mz: Vec<f64>,
+    z: Vec<Vec<f64>>,
+}
+
+/// Plot options parsed from the export config
+struct PlotOptions<'a> {
+    chart_type: &'a str,
+    show_peaks: bool,
+    show_fit: bool,
+    show_components: bool,
+    palette: &'static [&'static str],
+    title: &'a str,
+    x_axis_title: &'a str,
+    y_axis_title: &'a str,
+    width: u64,
+    height: u64,
+    show_grid: bool,
+    show_legend: bool,
+    heatmap_mz_bins: usize,
+    heatmap_interpolation: &'a str,
+    log_intensity: bool,
+    annotate_peaks: bool,
+    annotation_template: &'a str,
+    show_noise_band: bool,
+    noise_band_sigma: f64,
+    max_points_per_trace: usize,
+}
+
+impl<'a> PlotOptions<'a> {
+    fn from_config(config: &'a Value) -> Self {
+        Self {
+            chart_type: config["chart_type"].as_str().unwrap_or("combined"),
+            show_peaks: config["show_peaks"].as_bool().unwrap_or(true),
+            show_fit: config["show_fit"].as_bool().unwrap_or(false),
+            show_components: config["show_components"].as_bool().unwrap_or(true),
+            palette: palette_for_scheme(config["color_scheme"].as_str().unwrap_or("default")),
+            title: config["title"].as_str().unwrap_or("IMS Data Visualization"),
+            x_axis_title: config["x_axis_title"].as_str().unwrap_or("Time"),
+            y_axis_title: config["y_axis_title"].as_str().unwrap_or("Intensity"),
+            width: config["width"].as_u64().unwrap_or(800),
+            height: config["height"].as_u64().unwrap_or(600),
+            show_grid: config["show_grid"].as_bool().unwrap_or(true),
+            show_legend: config["show_legend"].as_bool().unwrap_or(true),
+            heatmap_mz_bins: config["heatmap_mz_bins"].as_u64().unwrap_or(200).max(1) as usize,
+            heatmap_interpolation: config["heatmap_interpolation"].as_str().unwrap_or("none"),
+            log_intensity: config["log_intensity"].as_bool().unwrap_or(false),
+            annotate_peaks: config["annotate_peaks"].as_bool().unwrap_or(false),
+            annotation_template: config["annotation_template"].as_str().unwrap_or("center"),
+            show_noise_band: config["show_noise_band"].as_bool().unwrap_or(false),
+            noise_band_sigma: config["noise_band_sigma"].as_f64().unwrap_or(3.0).abs(),
+            max_points_per_trace: config["max_points_per_trace"].as_u64().unwrap_or(DEFAULT_MAX_POINTS_PER_TRACE).max(2) as usize,
+        }
+    }
+}
+
 #[async_trait]
 impl Exporter for PlotlyExporter {
     fn name(&self) -> &str {
@@ -40,7 +137,7 @@ impl Exporter for PlotlyExporter {
                 },
                 "chart_type": {
                     "type": "string",
-                    "enum": ["line", "scatter", "bar", "combined"],
+                    "enum": ["line", "scatter", "bar", "combined", "heatmap"],
                     "default": "combined",
                     "description": "Type of chart to generate"
                 },
@@ -54,6 +151,27 @@ impl Exporter for PlotlyExporter {
                     "default": false,
                     "description": "Show fitted curves"
                 },
+                "show_components": {
+                    "type": "boolean",
+                    "default": true,
+                    "description": "Show individual component peaks and their sum for overlapped regions"
+                },
+                "color_scheme": {
+                    "type": "string",
+                    "enum": ["default", "viridis", "colorblind", "grayscale"],
+                    "default": "default",
+                    "description": "Palette used for trace colors"
+                },
+                "show_grid": {
+                    "type": "boolean",
+                    "default": true,
+                    "description": "Show axis grid lines"
+                },
+                "show_legend": {
+                    "type": "boolean",
+                    "default": true,
+                    "description": "Show the trace legend"
+                },
                 "title": {
                     "type": "string",
                     "default": "IMS Data Visualization",
@@ -78,6 +196,66 @@ impl Exporter for PlotlyExporter {
                     "type": "integer",
                     "default": 600,
                     "description": "Chart height in pixels"
+                },
+                "heatmap_mz_bins": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "default": 200,
+                    "description": "Number of m/z bins in the RT x m/z heatmap"
+                },
+                "heatmap_interpolation": {
+                    "type": "string",
+                    "enum": ["none", "nearest", "linear"],
+                    "default": "none",
+                    "description": "How empty m/z bins between populated bins are filled in the heatmap"
+                },
+                "log_intensity": {
+                    "type": "boolean",
+                    "default": false,
+                    "description": "Map heatmap intensities through log10(1 + I)"
+                },
+                "include_overview": {
+                    "type": "boolean",
+                    "default": false,
+                    "description": "Prepend the run-level TIC overview curve"
+                },
+                "include_bpc": {
+                    "type": "boolean",
+                    "default": false,
+                    "description": "Also prepend the BPC overview curve"
+                },
+                "max_curve_points": {
+                    "type": "integer",
+                    "minimum": 2,
+                    "description": "Decimate longer curves to this many points, keeping peak maxima; full resolution when omitted"
+                },
+                "annotate_peaks": {
+                    "type": "boolean",
+                    "default": false,
+                    "description": "Add a text annotation at each peak apex"
+                },
+                "annotation_template": {
+                    "type": "string",
+                    "enum": ["center", "area", "label"],
+                    "default": "center",
+                    "description": "Text shown in peak annotations"
+                },
+                "show_noise_band": {
+                    "type": "boolean",
+                    "default": false,
+                    "description": "Shade a baseline ± N sigma noise band behind each curve"
+                },
+                "noise_band_sigma": {
+                    "type": "number",
+                    "minimum": 0,
+                    "default": 3.0,
+                    "description": "Half-width of the noise band in noise standard deviations"
+                },
+                "max_points_per_trace": {
+                    "type": "integer",
+                    "minimum": 2,
+                    "default": 5000,
+                    "description": "Min/max-decimate curve and fit traces to at most this many points, keeping peak apexes"
                 }
             }
         })
@@ -91,17 +269,16 @@ impl Exporter for PlotlyExporter {
         let export_config: ExportConfig = serde_json::from_value(config.clone())
             .unwrap_or_default();
         
-        let chart_type = config["chart_type"].as_str().unwrap_or("combined");
-        let show_peaks = config["show_peaks"].as_bool().unwrap_or(true);
-        let show_fit = config["show_fit"].as_bool().unwrap_or(false);
-        let title = config["title"].as_str().unwrap_or("IMS Data Visualization");
-        let x_axis_title = config["x_axis_title"].as_str().unwrap_or("Time");
-        let y_axis_title = config["y_axis_title"].as_str().unwrap_or("Intensity");
-        let width = config["width"].as_u64().unwrap_or(800);
-        let height = config["height"].as_u64().unwrap_or(600);
+        let overview = helpers::with_overview_curves(data, &export_config);
+        let data = overview.as_ref().unwrap_or(data);
         
-        let plotly_data = self.create_plotly_data(data, &export_config, chart_type, show_peaks, show_fit)?;
-        let layout = self.create_layout(title, x_axis_title, y_axis_title, width, height);
+        let options = PlotOptions::from_config(&config);
+        
+        let plotly_data = self.create_plotly_data(data, &export_config, &options)?;
+        let mut layout = self.create_layout(&options);
+        if options.annotate_peaks && options.chart_type != "heatmap" {
+            layout["annotations"] = Value::Array(self.create_peak_annotations(data, &options));
+        }
         
         let plotly_json = serde_json::json!({
             "data": plotly_data,
@@ -139,29 +316,42 @@ impl PlotlyExporter {
         &self,
         data: &DataContainer,
         config: &ExportConfig,
-        chart_type: &str,
-        show_peaks: bool,
-        show_fit: bool,
+        options: &PlotOptions,
     ) -> Result<Vec<Value>, ProcessingError> {
+        // The heatmap is built from the raw spectra and replaces the curve traces
+        if options.chart_type == "heatmap" {
+            return Ok(vec![self.create_heatmap_trace(data, options)?]);
+        }
+        
         let mut traces = Vec::new();
         
         // Add curve traces
         if config.include_curves {
             for (i, curve) in data.curves.iter().enumerate() {
-                let trace = self.create_curve_trace(curve, i, chart_type)?;
+                // The band goes first so the curve is drawn on top of it
+                if options.show_noise_band {
+                    traces.push(self.create_noise_band_trace(curve, options.palette[i % options.palette.len()], options.noise_band_sigma));
+                }
+                
+                let trace = self.create_curve_trace(curve, options.palette[i % options.palette.len()], options.chart_type, options.max_points_per_trace)?;
                 traces.push(trace);
                 
                 // Add fitted curve if requested
-                if show_fit {
-                    if let Ok(fit_trace) = self.create_fit_trace(curve, i) {
+                if options.show_fit {
+                    if let Ok(fit_trace) = self.create_fit_trace(curve, options.palette[i % options.palette.len()], options.max_points_per_trace) {
                         traces.push(fit_trace);
                     }
                 }
+                
+                // Add component traces for overlapped regions
+                if options.show_components {
+                    traces.extend(self.create_component_traces(curve, options.palette));
+                }
             }
         }
         
         // Add peak annotations
-        if config.include_peaks && show_peaks {
+        if config.include_peaks && options.show_peaks {
             // 收集所有峰
             let mut all_peaks = Vec::new();
             for curve in &data.curves {
@@ -176,8 +366,69 @@ impl PlotlyExporter {
         Ok(traces)
     }
     
+    /// Create an RT x m/z heatmap trace from the container spectra
+    fn create_heatmap_trace(&self, data: &DataContainer, options: &PlotOptions) -> Result<Value, ProcessingError> {
+        let grid = self.build_heatmap_grid(data, options)?;
+        let colorscale = if options.palette == VIRIDIS_PALETTE { "Viridis" } else { "Jet" };
+        
+        Ok(serde_json::json!({
+            "x": grid.mz,
+            "y": grid.rt,
+            "z": grid.z,
+            "type": "heatmap",
+            "name": "RT x m/z",
+            "colorscale": colorscale,
+            "colorbar": {
+                "title": if options.log_intensity { "log10(1 + Intensity)" } else { "Intensity" }
+            },
+            "meta": {
+                "role": "heatmap",
+                "interpolation": options.heatmap_interpolation,
+                "log_intensity": options.log_intensity
+            }
+        }))
+    }
+    
+    /// Bin every spectrum onto a shared m/z axis; one row per spectrum (retention time)
+    fn build_heatmap_grid(
+        &self,
+        data: &DataContainer,
+        options: &PlotOptions,
+    ) -> Result<HeatmapGrid, ProcessingError> {
+        let (mz_min, mz_max) = data.spectra.iter()
+            .flat_map(|s| s.peaks().iter().map(|p| p.mz()).collect::<Vec<_>>())
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), mz| (lo.min(mz), hi.max(mz)));
+        if !mz_min.is_finite() {
+            return Err(ProcessingError::DataError("No spectra data available for the heatmap".to_string()));
+        }
+        
+        let bins = options.heatmap_mz_bins;
+        let bin_width = if mz_max > mz_min { (mz_max - mz_min) / bins as f64 } else { 1.0 };
+        let mz: Vec<f64> = (0..bins).map(|i| mz_min + (i as f64 + 0.5) * bin_width).collect();
+        
+        let mut rt = Vec::with_capacity(data.spectra.len());
+        let mut z = Vec::with_capacity(data.spectra.len());
+        for spectrum in &data.spectra {
+            let mut row = vec![0.0; bins];
+            for peak in spectrum.peaks().iter() {
+                let bin = (((peak.mz() - mz_min) / bin_width) as usize).min(bins - 1);
+                row[bin] += peak.intensity() as f64;
+            }
+            
+            fill_mz_gaps(&mut row, options.heatmap_interpolation);
+            if options.log_intensity {
+                row.iter_mut().for_each(|v| *v = v.max(0.0).ln_1p() / std::f64::consts::LN_10);
+            }
+            
+            rt.push(spectrum.start_time());
+            z.push(row);
+        }
+        
+        Ok(HeatmapGrid { rt, mz, z })
+    }
+    
     /// Create a curve trace
-    fn create_curve_trace(&self, curve: &Curve, index: usize, chart_type: &str) -> Result<Value, ProcessingError> {
+    fn create_curve_trace(&self, curve: &Curve, color: &str, chart_type: &str, max_points: usize) -> Result<Value, ProcessingError> {
         let trace_type = match chart_type {
             "bar" => "bar",
             "scatter" => "scatter",
@@ -191,12 +442,12 @@ impl PlotlyExporter {
             _ => "lines+markers",
         };
         
-        let color = self.get_color_for_index(index);
         let name = format!("{} ({})", curve.curve_type, curve.id);
+        let (x_values, y_values) = min_max_decimation(&curve.x_values, &curve.y_values, max_points);
         
         let mut trace = serde_json::json!({
-            "x": curve.x_values,
-            "y": curve.y_values,
+            "x": x_values,
+            "y": y_values,
             "type": trace_type,
             "mode": mode,
             "name": name,
@@ -222,15 +473,47 @@ impl PlotlyExporter {
         Ok(trace)
     }
     
+    /// Create a filled baseline ± sigma × noise band spanning the curve's x range
+    fn create_noise_band_trace(&self, curve: &Curve, color: &str, sigma: f64) -> Value {
+        let noise_floor = estimate_noise_floor(curve);
+        let upper = noise_floor.baseline + sigma * noise_floor.noise;
+        let lower = noise_floor.baseline - sigma * noise_floor.noise;
+        let x_min = curve.x_values.first().copied().unwrap_or(0.0);
+        let x_max = curve.x_values.last().copied().unwrap_or(0.0);
+        
+        serde_json::json!({
+            "x": [x_min, x_max, x_max, x_min, x_min],
+            "y": [upper, upper, lower, lower, upper],
+            "type": "scatter",
+            "mode": "lines",
+            "fill": "toself",
+            "fillcolor": color,
+            "opacity": 0.15,
+            "line": {
+                "width": 0
+            },
+            "name": format!("{} noise ±{}σ", curve.curve_type, sigma),
+            "hoverinfo": "skip",
+            "showlegend": false,
+            "meta": {
+                "role": "noise_band",
+                "curve_id": curve.id,
+                "baseline": noise_floor.baseline,
+                "noise": noise_floor.noise,
+                "sigma": sigma
+            }
+        })
+    }
+    
     /// Create a fitted curve trace
-    fn create_fit_trace(&self, curve: &Curve, index: usize) -> Result<Value, ProcessingError> {
+    fn create_fit_trace(&self, curve: &Curve, color: &str, max_points: usize) -> Result<Value, ProcessingError> {
         // For now, create a simple fitted curve based on peak data
         // In a real implementation, you would use the actual fitted parameters
-        let color = self.get_color_for_index(index);
+        let (x_values, y_values) = min_max_decimation(&curve.x_values, &curve.y_values, max_points);
         
         Ok(serde_json::json!({
-            "x": curve.x_values,
-            "y": curve.y_values, // This should be the fitted values
+            "x": x_values,
+            "y": y_values, // This should be the fitted values
             "type": "scatter",
             "mode": "lines",
             "name": format!("{} (Fitted)", curve.curve_type),
@@ -244,6 +527,98 @@ impl PlotlyExporter {
         }))
     }
     
+    /// Group peaks into overlapped regions (sorted by center, intervals chained by overlap)
+    fn find_overlapped_regions<'a>(&self, peaks: &'a [Peak]) -> Vec<Vec<&'a Peak>> {
+        let mut sorted: Vec<&Peak> = peaks.iter().collect();
+        sorted.sort_by(|a, b| a.center.partial_cmp(&b.center).unwrap_or(std::cmp::Ordering::Equal));
+        
+        let mut regions: Vec<Vec<&Peak>> = Vec::new();
+        let mut region_end = f64::NEG_INFINITY;
+        
+        for peak in sorted {
+            let (start, end) = self.peak_extent(peak);
+            match regions.last_mut() {
+                Some(region) if start <= region_end => region.push(peak),
+                _ => regions.push(vec![peak]),
+            }
+            region_end = region_end.max(end);
+        }
+        
+        regions.into_iter().filter(|r| r.len() > 1).collect()
+    }
+    
+    /// Peak extent: fitted boundaries when available, otherwise center ± FWHM
+    fn peak_extent(&self, peak: &Peak) -> (f64, f64) {
+        if peak.right_boundary > peak.left_boundary {
+            (peak.left_boundary, peak.right_boundary)
+        } else {
+            (peak.center - peak.fwhm, peak.center + peak.fwhm)
+        }
+    }
+    
+    /// Create one trace per component peak plus a dashed sum trace for each overlapped region
+    fn create_component_traces(&self, curve: &Curve, palette: &[&str]) -> Vec<Value> {
+        let mut traces = Vec::new();
+        
+        for (region_index, region) in self.find_overlapped_regions(&curve.peaks).iter().enumerate() {
+            let start = region.iter().map(|p| self.peak_extent(p).0).fold(f64::INFINITY, f64::min);
+            let end = region.iter().map(|p| self.peak_extent(p).1).fold(f64::NEG_INFINITY, f64::max);
+            let x_values: Vec<f64> = curve.x_values.iter().copied().filter(|&x| x >= start && x <= end).collect();
+            if x_values.is_empty() {
+                continue;
+            }
+            
+            let group = format!("components_{}_{}", curve.id, region_index);
+            let mut sum = vec![0.0; x_values.len()];
+            
+            for (i, peak) in region.iter().enumerate() {
+                let params = PeakShapeParams::from_peak(peak);
+                let calculator = PeakShapeCalculatorFactory::create_calculator(&params.shape_type);
+                let y_values: Vec<f64> = x_values.iter().map(|&x| calculator.calculate(x, &params)).collect();
+                for (total, y) in sum.iter_mut().zip(&y_values) {
+                    *total += y;
+                }
+                
+                traces.push(serde_json::json!({
+                    "x": x_values,
+                    "y": y_values,
+                    "type": "scatter",
+                    "mode": "lines",
+                    "name": peak.id,
+                    "legendgroup": group,
+                    "line": {
+                        "color": palette[(i + 1) % palette.len()],
+                        "width": 1
+                    },
+                    "meta": {
+                        "role": "component",
+                        "peak_id": peak.id
+                    },
+                    "hovertemplate": format!("<b>{}</b><br>X: %{{x:.6f}}<br>Y: %{{y:.3f}}<extra></extra>", peak.id)
+                }));
+            }
+            
+            traces.push(serde_json::json!({
+                "x": x_values,
+                "y": sum,
+                "type": "scatter",
+                "mode": "lines",
+                "name": "Sum of components",
+                "legendgroup": group,
+                "line": {
+                    "color": "#333333",
+                    "width": 2,
+                    "dash": "dash"
+                },
+                "meta": {
+                    "role": "component_sum"
+                }
+            }));
+        }
+        
+        traces
+    }
+    
     /// Create peak annotations trace
     fn create_peak_trace(&self, peaks: &[Peak]) -> Result<Value, ProcessingError> {
         let mut peak_x = Vec::new();
@@ -278,6 +653,7 @@ impl PlotlyExporter {
                 PeakType::BiGaussian => "#6C5CE7",
                 PeakType::VoigtExponentialTail => "#FD79A8",
                 PeakType::PearsonIV => "#FDCB6E",
+                PeakType::PearsonVII => "#E1B12C",
                 PeakType::NLC => "#E17055",
                 PeakType::GMGBayesian => "#00B894",
             };
@@ -305,11 +681,62 @@ impl PlotlyExporter {
         }))
     }
     
+    /// Create layout annotations at each peak apex
+    ///
+    /// Peaks are visited in x order; an annotation closer than
+    /// `ANNOTATION_CROWDING_FRACTION` of the x span to the previous one is lifted to the
+    /// next offset in `ANNOTATION_OFFSETS` so neighbouring labels do not overlap.
+    fn create_peak_annotations(&self, data: &DataContainer, options: &PlotOptions) -> Vec<Value> {
+        let mut peaks: Vec<&Peak> = data.curves.iter().flat_map(|c| c.peaks.iter()).collect();
+        peaks.sort_by(|a, b| a.center.total_cmp(&b.center));
+        
+        let x_min = data.curves.iter().map(|c| c.x_min).fold(f64::INFINITY, f64::min);
+        let x_max = data.curves.iter().map(|c| c.x_max).fold(f64::NEG_INFINITY, f64::max);
+        let min_separation = if x_max > x_min { (x_max - x_min) * ANNOTATION_CROWDING_FRACTION } else { 0.0 };
+        
+        let mut level = 0;
+        let mut previous_center: Option<f64> = None;
+        peaks.iter().map(|peak| {
+            level = match previous_center {
+                Some(prev) if peak.center - prev < min_separation => (level + 1) % ANNOTATION_OFFSETS.len(),
+                _ => 0,
+            };
+            previous_center = Some(peak.center);
+            
+            serde_json::json!({
+                "x": peak.center,
+                "y": peak.amplitude,
+                "text": self.format_peak_annotation(peak, options.annotation_template),
+                "showarrow": true,
+                "arrowhead": 2,
+                "arrowsize": 0.8,
+                "ax": 0,
+                "ay": ANNOTATION_OFFSETS[level],
+                "font": {
+                    "size": 10
+                },
+                "bgcolor": "rgba(255,255,255,0.8)"
+            })
+        }).collect()
+    }
+    
+    /// Format the annotation text for a peak ("center", "area" or "label")
+    fn format_peak_annotation(&self, peak: &Peak, template: &str) -> String {
+        match template {
+            "area" => format!("{:.3}", peak.area),
+            "label" => peak.metadata.get("label")
+                .and_then(|v| v.as_str())
+                .unwrap_or(&peak.id)
+                .to_string(),
+            _ => format!("{:.4}", peak.center),
+        }
+    }
+    
     /// Create Plotly layout
-    fn create_layout(&self, title: &str, x_axis_title: &str, y_axis_title: &str, width: u64, height: u64) -> Value {
+    fn create_layout(&self, options: &PlotOptions) -> Value {
         serde_json::json!({
             "title": {
-                "text": title,
+                "text": options.title,
                 "x": 0.5,
                 "font": {
                     "size": 16
@@ -317,28 +744,28 @@ impl PlotlyExporter {
             },
             "xaxis": {
                 "title": {
-                    "text": x_axis_title,
+                    "text": options.x_axis_title,
                     "font": {
                         "size": 14
                     }
                 },
-                "showgrid": true,
+                "showgrid": options.show_grid,
                 "gridcolor": "#E5E5E5",
                 "zeroline": false
             },
             "yaxis": {
                 "title": {
-                    "text": y_axis_title,
+                    "text": options.y_axis_title,
                     "font": {
                         "size": 14
                     }
                 },
-                "showgrid": true,
+                "showgrid": options.show_grid,
                 "gridcolor": "#E5E5E5",
                 "zeroline": false
             },
-            "width": width,
-            "height": height,
+            "width": options.width,
+            "height": options.height,
             "margin": {
                 "l": 60,
                 "r": 60,
@@ -351,6 +778,7 @@ impl PlotlyExporter {
                 "family": "Arial, sans-serif",
                 "size": 12
             },
+            "showlegend": options.show_legend,
             "legend": {
                 "x": 1.02,
                 "y": 1,
@@ -362,15 +790,6 @@ impl PlotlyExporter {
         })
     }
     
-    /// Get color for trace index
-    fn get_color_for_index(&self, index: usize) -> &'static str {
-        let colors = [
-            "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd",
-            "#8c564b", "#e377c2", "#7f7f7f", "#bcbd22", "#17becf"
-        ];
-        colors[index % colors.len()]
-    }
-    
     /// Format peak type for display
     fn format_peak_type(&self, peak_type: &PeakType) -> String {
         match peak_type {
@@ -383,8 +802,250 @@ impl PlotlyExporter {
             PeakType::BiGaussian => "BiGaussian".to_string(),
             PeakType::VoigtExponentialTail => "Voigt+ExpTail".to_string(),
             PeakType::PearsonIV => "PearsonIV".to_string(),
+            PeakType::PearsonVII => "PearsonVII".to_string(),
             PeakType::NLC => "NLC".to_string(),
             PeakType::GMGBayesian => "GMGBayesian".to_string(),
         }
     }
 }
+
+/// Fill empty bins lying between two populated bins of a heatmap row.
+/// Bins before the first and after the last populated bin are left empty.
+fn fill_mz_gaps(row: &mut [f64], interpolation: &str) {
+    if interpolation != "nearest" && interpolation != "linear" {
+        return;
+    }
+    
+    let populated: Vec<usize> = (0..row.len()).filter(|&i| row[i] != 0.0).collect();
+    for pair in populated.windows(2) {
+        let (left, right) = (pair[0], pair[1]);
+        for i in (left + 1)..right {
+            row[i] = if interpolation == "linear" {
+                let t = (i - left) as f64 / (right - left) as f64;
+                row[left] + (row[right] - row[left]) * t
+            } else if i - left <= right - i {
+                row[left]
+            } else {
+                row[right]
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlapped_peaks_render_components_and_sum() {
+        let x_values: Vec<f64> = (0..200).map(|i| i as f64 * 0.05).collect();
+        let y_values = vec![0.0; x_values.len()];
+        let mut curve = Curve::new(
+            "curve".to_string(),
+            "DT".to_string(),
+            x_values,
+            y_values,
+            "Drift Time".to_string(),
+            "Intensity".to_string(),
+            "ms".to_string(),
+            "counts".to_string(),
+        );
+        for (id, center) in [("peak_a", 4.6), ("peak_b", 5.2)] {
+            let mut peak = Peak::new(id.to_string(), "curve".to_string(), center, 100.0, PeakType::Gaussian);
+            peak.sigma = 0.3;
+            peak.fwhm = 0.3 * 2.355;
+            curve.peaks.push(peak);
+        }
+
+        let mut data = DataContainer::new();
+        data.curves.push(curve);
+
+        let traces = PlotlyExporter
+            .create_plotly_data(&data, &ExportConfig::default(), &PlotOptions::from_config(&serde_json::json!({"chart_type": "line"})))
+            .unwrap();
+        let roles: Vec<&str> = traces.iter().filter_map(|t| t["meta"]["role"].as_str()).collect();
+
+        assert_eq!(roles, vec!["component", "component", "component_sum"]);
+        assert_eq!(traces.iter().filter(|t| t["name"] == "peak_a").count(), 1);
+    }
+
+    #[test]
+    fn test_color_scheme_and_legend_settings() {
+        let mut data = DataContainer::new();
+        for id in ["curve_a", "curve_b"] {
+            data.curves.push(Curve::new(
+                id.to_string(),
+                "DT".to_string(),
+                vec![0.0, 1.0, 2.0],
+                vec![1.0, 3.0, 1.0],
+                "Drift Time".to_string(),
+                "Intensity".to_string(),
+                "ms".to_string(),
+                "counts".to_string(),
+            ));
+        }
+
+        let config = serde_json::json!({
+            "color_scheme": "colorblind",
+            "show_legend": false,
+            "show_grid": false
+        });
+        let options = PlotOptions::from_config(&config);
+
+        let layout = PlotlyExporter.create_layout(&options);
+        assert_eq!(layout["showlegend"], false);
+        assert_eq!(layout["xaxis"]["showgrid"], false);
+
+        let traces = PlotlyExporter.create_plotly_data(&data, &ExportConfig::default(), &options).unwrap();
+        assert_eq!(traces[0]["line"]["color"], COLORBLIND_PALETTE[0]);
+        assert_eq!(traces[1]["line"]["color"], COLORBLIND_PALETTE[1]);
+    }
+
+    #[test]
+    fn test_heatmap_mz_interpolation() {
+        use crate::core::utils::test_fixtures::ms1_spectrum;
+        
+        let mut data = DataContainer::new();
+        for i in 0..3 {
+            data.spectra.push(ms1_spectrum(i, i as f64, &[(400.0, 100.0), (410.0, 300.0)]));
+        }
+        
+        let grid = |interpolation: &str, log: bool| {
+            let config = serde_json::json!({
+                "chart_type": "heatmap",
+                "heatmap_mz_bins": 21,
+                "heatmap_interpolation": interpolation,
+                "log_intensity": log
+            });
+            PlotlyExporter.build_heatmap_grid(&data, &PlotOptions::from_config(&config)).unwrap()
+        };
+        
+        let linear = grid("linear", false);
+        assert_eq!(linear.rt, vec![0.0, 1.0, 2.0]);
+        assert_eq!(linear.mz.len(), 21);
+        for row in &linear.z {
+            assert_eq!(row[0], 100.0);
+            assert_eq!(row[20], 300.0);
+            assert!(row[1..20].iter().all(|&v| v > 0.0));
+            assert!((row[10] - 200.0).abs() < 1e-9);
+        }
+        
+        let nearest = grid("nearest", false).z;
+        assert_eq!(nearest[0][9], 100.0);
+        assert_eq!(nearest[0][11], 300.0);
+        
+        let none = grid("none", false).z;
+        assert!(none.iter().all(|row| row[1..20].iter().all(|&v| v == 0.0)));
+        
+        let log = grid("none", true).z;
+        assert!((log[0][0] - 101.0_f64.log10()).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_peak_annotations_match_peak_count() {
+        let x_values: Vec<f64> = (0..100).map(|i| i as f64 * 0.1).collect();
+        let y_values = vec![1.0; x_values.len()];
+        let mut curve = Curve::new(
+            "curve".to_string(),
+            "DT".to_string(),
+            x_values,
+            y_values,
+            "Drift Time".to_string(),
+            "Intensity".to_string(),
+            "ms".to_string(),
+            "counts".to_string(),
+        );
+        // Two crowded peaks and one isolated peak
+        for (id, center) in [("peak_a", 3.0), ("peak_b", 3.1), ("peak_c", 8.0)] {
+            curve.peaks.push(Peak::new(id.to_string(), "curve".to_string(), center, 50.0, PeakType::Gaussian));
+        }
+        let mut data = DataContainer::new();
+        data.curves.push(curve);
+        
+        let config = serde_json::json!({"chart_type": "line", "annotate_peaks": true, "annotation_template": "label"});
+        let result = PlotlyExporter.export(&data, config).await.unwrap();
+        let plot: Value = serde_json::from_slice(&result.data).unwrap();
+        let annotations = plot["layout"]["annotations"].as_array().unwrap();
+        assert_eq!(annotations.len(), 3);
+        assert_eq!(annotations[0]["text"], "peak_a");
+        assert_ne!(annotations[0]["ay"], annotations[1]["ay"]);
+        assert_eq!(annotations[0]["ay"], annotations[2]["ay"]);
+        
+        let result = PlotlyExporter.export(&data, serde_json::json!({"chart_type": "line"})).await.unwrap();
+        let plot: Value = serde_json::from_slice(&result.data).unwrap();
+        assert!(plot["layout"]["annotations"].is_null());
+    }
+    
+    #[test]
+    fn test_noise_band_width_matches_sigma_multiple() {
+        // Irregular noise on a flat baseline of 10
+        let x_values: Vec<f64> = (0..100).map(|i| i as f64 * 0.1).collect();
+        let y_values: Vec<f64> = (0..100).map(|i| 10.0 + (i as f64 * 2.3).sin()).collect();
+        let curve = Curve::new(
+            "curve".to_string(),
+            "DT".to_string(),
+            x_values,
+            y_values,
+            "Drift Time".to_string(),
+            "Intensity".to_string(),
+            "ms".to_string(),
+            "counts".to_string(),
+        );
+        let noise_floor = estimate_noise_floor(&curve);
+        let noise = noise_floor.noise;
+        assert!(noise > 0.0);
+        let mut data = DataContainer::new();
+        data.curves.push(curve);
+        
+        let config = serde_json::json!({"chart_type": "line", "show_noise_band": true, "noise_band_sigma": 2.5});
+        let traces = PlotlyExporter.create_plotly_data(&data, &ExportConfig::default(), &PlotOptions::from_config(&config)).unwrap();
+        let bands: Vec<&Value> = traces.iter().filter(|t| t["meta"]["role"] == "noise_band").collect();
+        assert_eq!(bands.len(), 1);
+        assert_eq!(bands[0]["fill"], "toself");
+        
+        let y: Vec<f64> = bands[0]["y"].as_array().unwrap().iter().map(|v| v.as_f64().unwrap()).collect();
+        let upper = y.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let lower = y.iter().cloned().fold(f64::INFINITY, f64::min);
+        assert!((upper - lower - 2.0 * 2.5 * noise).abs() < 1e-9);
+        assert!(((upper + lower) / 2.0 - noise_floor.baseline).abs() < 1e-9);
+        
+        let traces = PlotlyExporter.create_plotly_data(&data, &ExportConfig::default(), &PlotOptions::from_config(&serde_json::json!({"chart_type": "line"}))).unwrap();
+        assert!(traces.iter().all(|t| t["meta"]["role"] != "noise_band"));
+    }
+    
+    #[test]
+    fn test_large_trace_is_capped_and_keeps_apex() {
+        // 60k points with a single-point apex well away from bucket edges
+        let x_values: Vec<f64> = (0..60_000).map(|i| i as f64 * 0.001).collect();
+        let y_values: Vec<f64> = x_values.iter()
+            .map(|&x| 10.0 + (x * 37.0).sin() + 5000.0 * (-(x - 31.4567).powi(2) / (2.0 * 0.05 * 0.05)).exp())
+            .collect();
+        let apex = y_values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let apex_x = x_values[y_values.iter().position(|&y| y == apex).unwrap()];
+        let curve = Curve::new(
+            "curve".to_string(),
+            "DT".to_string(),
+            x_values,
+            y_values,
+            "Drift Time".to_string(),
+            "Intensity".to_string(),
+            "ms".to_string(),
+            "counts".to_string(),
+        );
+        let mut data = DataContainer::new();
+        data.curves.push(curve);
+        
+        let config = serde_json::json!({"chart_type": "line", "max_points_per_trace": 1000});
+        let traces = PlotlyExporter.create_plotly_data(&data, &ExportConfig::default(), &PlotOptions::from_config(&config)).unwrap();
+        let x: Vec<f64> = traces[0]["x"].as_array().unwrap().iter().map(|v| v.as_f64().unwrap()).collect();
+        let y: Vec<f64> = traces[0]["y"].as_array().unwrap().iter().map(|v| v.as_f64().unwrap()).collect();
+        assert!(y.len() <= 1000);
+        assert!(x.windows(2).all(|w| w[1] > w[0]));
+        let index = y.iter().position(|&v| v == apex).unwrap();
+        assert_eq!(x[index], apex_x);
+        
+        // Default cap applies when the option is omitted
+        let traces = PlotlyExporter.create_plotly_data(&data, &ExportConfig::default(), &PlotOptions::from_config(&serde_json::json!({"chart_type": "line"}))).unwrap();
+        assert!(traces[0]["x"].as_array().unwrap().len() <= DEFAULT_MAX_POINTS_PER_TRACE as usize);
+    }
+}
@@ -28,6 +28,10 @@ impl Exporter for SpectroTsvExporter {
         "text/tab-separated-values"
     }
 
+    fn writes_files(&self) -> bool {
+        true
+    }
+
     fn config_schema(&self) -> Value {
         serde_json::json!({
             "type": "object",
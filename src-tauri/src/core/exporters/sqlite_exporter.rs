@@ -0,0 +1,374 @@
+use async_trait::async_trait;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+use crate::core::data::{DataContainer, ProcessingError};
+use super::base::{Exporter, ExportResult, ExportConfig, helpers};
+
+/// Tables created on first export; later exports append rows under a new `run_id`
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    run_id TEXT PRIMARY KEY,
+    exported_at TEXT NOT NULL,
+    metadata TEXT
+);
+CREATE TABLE IF NOT EXISTS curves (
+    run_id TEXT NOT NULL REFERENCES runs(run_id),
+    id TEXT NOT NULL,
+    curve_type TEXT NOT NULL,
+    x_label TEXT,
+    y_label TEXT,
+    x_unit TEXT,
+    y_unit TEXT,
+    point_count INTEGER NOT NULL,
+    x_min REAL,
+    x_max REAL,
+    y_min REAL,
+    y_max REAL,
+    total_ion_current REAL,
+    mz_min REAL,
+    mz_max REAL,
+    rt_min REAL,
+    rt_max REAL,
+    ms_level INTEGER,
+    x_values TEXT,
+    y_values TEXT,
+    metadata TEXT,
+    PRIMARY KEY (run_id, id)
+);
+CREATE TABLE IF NOT EXISTS peaks (
+    run_id TEXT NOT NULL,
+    id TEXT NOT NULL,
+    curve_id TEXT NOT NULL,
+    center REAL NOT NULL,
+    amplitude REAL,
+    area REAL,
+    area_error REAL,
+    area_unit TEXT,
+    area_percentage REAL,
+    fwhm REAL,
+    sigma REAL,
+    gamma REAL,
+    left_boundary REAL,
+    right_boundary REAL,
+    rsquared REAL,
+    peak_type TEXT,
+    cluster_id TEXT,
+    fit_parameters TEXT,
+    fit_parameter_errors TEXT,
+    metadata TEXT,
+    PRIMARY KEY (run_id, curve_id, id),
+    FOREIGN KEY (run_id, curve_id) REFERENCES curves(run_id, id)
+);
+CREATE INDEX IF NOT EXISTS peaks_by_curve ON peaks(run_id, curve_id);
+";
+
+fn sql_error(e: rusqlite::Error) -> ProcessingError {
+    ProcessingError::DataError(format!("SQLite error: {}", e))
+}
+
+/// Metadata map as JSON text, or NULL when metadata is excluded
+fn metadata_text(metadata: &HashMap<String, Value>, include: bool) -> Result<Option<String>, ProcessingError> {
+    Ok(if include { Some(serde_json::to_string(metadata)?) } else { None })
+}
+
+/// SQLite exporter - writes curves and peaks into typed tables of a queryable database,
+/// appending each export as a separate run
+pub struct SqliteExporter;
+
+impl SqliteExporter {
+    /// Create the tables if needed and insert the container's curves and peaks under `run_id`
+    ///
+    /// Returns the number of curves and peaks written. Fails without writing if `run_id` already exists.
+    pub fn write_run(
+        connection: &mut Connection,
+        data: &DataContainer,
+        run_id: &str,
+        include_metadata: bool,
+    ) -> Result<(usize, usize), ProcessingError> {
+        connection.execute_batch("PRAGMA foreign_keys = ON;").map_err(sql_error)?;
+        connection.execute_batch(SCHEMA).map_err(sql_error)?;
+
+        let transaction = connection.transaction().map_err(sql_error)?;
+        let existing: Option<String> = transaction
+            .query_row("SELECT run_id FROM runs WHERE run_id = ?1", params![run_id], |row| row.get(0))
+            .optional()
+            .map_err(sql_error)?;
+        if existing.is_some() {
+            return Err(ProcessingError::ConfigError(format!(
+                "Run '{}' already exists in the database; choose a different run_id", run_id
+            )));
+        }
+
+        transaction.execute(
+            "INSERT INTO runs (run_id, exported_at, metadata) VALUES (?1, ?2, ?3)",
+            params![run_id, chrono::Utc::now().to_rfc3339(), metadata_text(&data.metadata, include_metadata)?],
+        ).map_err(sql_error)?;
+
+        let mut peak_count = 0;
+        {
+            let mut insert_curve = transaction.prepare(
+                "INSERT INTO curves (run_id, id, curve_type, x_label, y_label, x_unit, y_unit, point_count,
+                    x_min, x_max, y_min, y_max, total_ion_current, mz_min, mz_max, rt_min, rt_max, ms_level,
+                    x_values, y_values, metadata)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21)"
+            ).map_err(sql_error)?;
+            let mut insert_peak = transaction.prepare(
+                "INSERT INTO peaks (run_id, id, curve_id, center, amplitude, area, area_error, area_unit, area_percentage,
+                    fwhm, sigma, gamma, left_boundary, right_boundary, rsquared, peak_type, cluster_id,
+                    fit_parameters, fit_parameter_errors, metadata)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20)"
+            ).map_err(sql_error)?;
+
+            for curve in &data.curves {
+                insert_curve.execute(params![
+                    run_id,
+                    curve.id,
+                    curve.curve_type,
+                    curve.x_label,
+                    curve.y_label,
+                    curve.x_unit,
+                    curve.y_unit,
+                    curve.point_count as i64,
+                    curve.x_min,
+                    curve.x_max,
+                    curve.y_min,
+                    curve.y_max,
+                    curve.total_ion_current,
+                    curve.mz_range.map(|(min, _)| min),
+                    curve.mz_range.map(|(_, max)| max),
+                    curve.rt_range.map(|(min, _)| min),
+                    curve.rt_range.map(|(_, max)| max),
+                    curve.ms_level,
+                    serde_json::to_string(&curve.x_values)?,
+                    serde_json::to_string(&curve.y_values)?,
+                    metadata_text(&curve.metadata, include_metadata)?,
+                ]).map_err(sql_error)?;
+
+                for peak in &curve.peaks {
+                    insert_peak.execute(params![
+                        run_id,
+                        peak.id,
+                        curve.id,
+                        peak.center,
+                        peak.amplitude,
+                        peak.area,
+                        peak.area_error,
+                        peak.area_unit.clone().unwrap_or_else(|| curve.area_unit()),
+                        peak.area_percentage,
+                        peak.fwhm,
+                        peak.sigma,
+                        peak.gamma,
+                        peak.left_boundary,
+                        peak.right_boundary,
+                        peak.rsquared,
+                        format!("{:?}", peak.peak_type),
+                        peak.cluster_id,
+                        serde_json::to_string(&peak.fit_parameters)?,
+                        serde_json::to_string(&peak.fit_parameter_errors)?,
+                        metadata_text(&peak.metadata, include_metadata)?,
+                    ]).map_err(sql_error)?;
+                    peak_count += 1;
+                }
+            }
+        }
+        transaction.commit().map_err(sql_error)?;
+
+        Ok((data.curves.len(), peak_count))
+    }
+}
+
+#[async_trait]
+impl Exporter for SqliteExporter {
+    fn name(&self) -> &str {
+        "sqlite_exporter"
+    }
+
+    fn description(&self) -> &str {
+        "Export curves and peaks into a SQLite database with typed columns, appending each export as a run"
+    }
+
+    fn file_extension(&self) -> &str {
+        "sqlite"
+    }
+
+    fn mime_type(&self) -> &str {
+        "application/vnd.sqlite3"
+    }
+
+    fn config_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "database_path": {
+                    "type": "string",
+                    "description": "Database file to create or append to (optional, a new database is returned when not provided)"
+                },
+                "run_id": {
+                    "type": "string",
+                    "description": "Identifier of this export within the database; must not already exist (default: a random UUID)"
+                },
+                "include_metadata": {
+                    "type": "boolean",
+                    "default": true,
+                    "description": "Store run, curve and peak metadata as JSON text"
+                }
+            }
+        })
+    }
+
+    fn writes_files(&self) -> bool {
+        true
+    }
+
+    async fn export(
+        &self,
+        data: &DataContainer,
+        config: Value,
+    ) -> Result<ExportResult, ProcessingError> {
+        let run_id = config["run_id"].as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("run_{}", Uuid::new_v4()));
+        let database_path = config["database_path"].as_str().map(PathBuf::from);
+        let export_config: ExportConfig = serde_json::from_value(config)
+            .unwrap_or_default();
+
+        // Without a target database the export is written to a scratch file and returned as bytes
+        let (path, scratch) = match database_path {
+            Some(path) => {
+                if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+                    std::fs::create_dir_all(parent)?;
+                }
+                (path, false)
+            },
+            None => (std::env::temp_dir().join(format!("mz_curve_export_{}.sqlite", Uuid::new_v4())), true),
+        };
+
+        let written = Connection::open(&path)
+            .map_err(sql_error)
+            .and_then(|mut connection| Self::write_run(&mut connection, data, &run_id, export_config.include_metadata));
+        // Only a scratch database is returned as bytes; a target database stays on disk and only its size is reported
+        let content = match &written {
+            Ok(_) if scratch => std::fs::read(&path).map_err(ProcessingError::from),
+            _ => Ok(Vec::new()),
+        };
+        if scratch {
+            let _ = std::fs::remove_file(&path);
+        }
+        let (curve_count, peak_count) = written?;
+        let content = content?;
+        let file_size = if scratch { content.len() as u64 } else { std::fs::metadata(&path)?.len() };
+
+        log::info!("🗄️ SQLite导出: run {} 写入 {} 条曲线、{} 个峰", run_id, curve_count, peak_count);
+
+        let filename = path.file_name()
+            .filter(|_| !scratch)
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| format!("ims_data_{}.sqlite", helpers::generate_timestamp()));
+        let mut metadata = helpers::create_export_metadata(
+            self.name(),
+            curve_count,
+            peak_count,
+            &export_config,
+        );
+        metadata.insert("run_id".to_string(), serde_json::json!(run_id));
+        metadata.insert("file_size_bytes".to_string(), serde_json::json!(file_size));
+        if !scratch {
+            metadata.insert("database_path".to_string(), serde_json::json!(path.to_string_lossy()));
+        }
+
+        Ok(ExportResult {
+            data: content,
+            filename,
+            mime_type: self.mime_type().to_string(),
+            metadata,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::data::{Curve, Peak, PeakType};
+
+    fn curve_with_peaks(id: &str, centers: &[f64]) -> Curve {
+        let mut curve = Curve::new(
+            id.to_string(),
+            "DT".to_string(),
+            vec![0.0, 1.0, 2.0, 3.0],
+            vec![1.0, 5.0, 4.0, 1.0],
+            "Drift Time".to_string(),
+            "Intensity".to_string(),
+            "ms".to_string(),
+            "counts".to_string(),
+        );
+        for (i, &center) in centers.iter().enumerate() {
+            let mut peak = Peak::new(format!("{}_peak_{}", id, i), id.to_string(), center, 5.0, PeakType::Gaussian);
+            peak.area = 10.0 * (i + 1) as f64;
+            curve.add_peak(peak);
+        }
+        curve
+    }
+
+    #[test]
+    fn in_memory_database_reports_peak_count_per_curve() {
+        let mut data = DataContainer::new();
+        data.curves.push(curve_with_peaks("dt_a", &[1.0, 2.0, 2.5]));
+        data.curves.push(curve_with_peaks("dt_b", &[1.5]));
+        data.curves.push(curve_with_peaks("dt_c", &[]));
+
+        let mut connection = Connection::open_in_memory().unwrap();
+        assert_eq!(SqliteExporter::write_run(&mut connection, &data, "run_1", true).unwrap(), (3, 4));
+        // Appending a second run keeps the first one intact
+        assert_eq!(SqliteExporter::write_run(&mut connection, &data, "run_2", false).unwrap(), (3, 4));
+        assert!(SqliteExporter::write_run(&mut connection, &data, "run_1", true).is_err());
+
+        let mut statement = connection.prepare(
+            "SELECT c.id, COUNT(p.id) FROM curves c
+             LEFT JOIN peaks p ON p.run_id = c.run_id AND p.curve_id = c.id
+             WHERE c.run_id = ?1 GROUP BY c.id ORDER BY c.id"
+        ).unwrap();
+        let counts: Vec<(String, i64)> = statement
+            .query_map(params!["run_1"], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(counts, vec![("dt_a".to_string(), 3), ("dt_b".to_string(), 1), ("dt_c".to_string(), 0)]);
+
+        let (runs, unit): (i64, String) = connection.query_row(
+            "SELECT (SELECT COUNT(*) FROM runs), (SELECT area_unit FROM peaks LIMIT 1)", [], |row| Ok((row.get(0)?, row.get(1)?))
+        ).unwrap();
+        assert_eq!(runs, 2);
+        assert_eq!(unit, "counts·ms");
+
+        // Peaks must reference a curve of the same run
+        let orphan = connection.execute(
+            "INSERT INTO peaks (run_id, id, curve_id, center) VALUES ('run_1', 'orphan', 'missing', 1.0)", []
+        );
+        assert!(orphan.is_err());
+    }
+
+    #[tokio::test]
+    async fn repeated_exports_to_a_database_get_distinct_run_ids() {
+        let path = std::env::temp_dir().join(format!("mz_curve_sqlite_runs_{}.sqlite", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut data = DataContainer::new();
+        data.curves.push(curve_with_peaks("dt_a", &[1.0, 2.0]));
+        let config = serde_json::json!({ "database_path": path.to_string_lossy() });
+
+        let first = SqliteExporter.export(&data, config.clone()).await.unwrap();
+        let second = SqliteExporter.export(&data, config).await.unwrap();
+
+        assert_ne!(first.metadata["run_id"], second.metadata["run_id"]);
+        assert!(second.data.is_empty());
+        assert_eq!(second.metadata["file_size_bytes"], std::fs::metadata(&path).unwrap().len());
+
+        let scratch = SqliteExporter.export(&data, serde_json::json!({})).await.unwrap();
+        assert!(!scratch.data.is_empty());
+        assert_eq!(scratch.metadata["file_size_bytes"], scratch.data.len() as u64);
+        let _ = std::fs::remove_file(&path);
+    }
+}
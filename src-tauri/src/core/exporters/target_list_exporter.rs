@@ -0,0 +1,213 @@
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::core::data::{Curve, DataContainer, Peak, ProcessingError};
+use super::base::{Exporter, ExportResult, ExportConfig, helpers};
+
+/// Target list exporter - writes an inclusion list (m/z, RT window, polarity)
+/// for targeted acquisition, one row per detected peak
+pub struct TargetListExporter;
+
+#[async_trait]
+impl Exporter for TargetListExporter {
+    fn name(&self) -> &str {
+        "target_list_exporter"
+    }
+
+    fn description(&self) -> &str {
+        "Export detected peaks as an instrument inclusion list (m/z, RT_start, RT_end, polarity)"
+    }
+
+    fn file_extension(&self) -> &str {
+        "csv"
+    }
+
+    fn mime_type(&self) -> &str {
+        "text/csv"
+    }
+
+    fn config_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "polarity": {
+                    "type": "string",
+                    "enum": ["positive", "negative"],
+                    "description": "Acquisition polarity; defaults to the container's detected polarity, then positive"
+                },
+                "rt_padding": {
+                    "type": "number",
+                    "default": 0.0,
+                    "description": "Widen every RT window by this amount on both sides"
+                },
+                "rt_window": {
+                    "type": "number",
+                    "default": 1.0,
+                    "description": "Window width around the peak retention time for peaks not found on a chromatogram"
+                },
+                "include_ccs": {
+                    "type": "boolean",
+                    "default": false,
+                    "description": "Append a CCS column taken from the peak's 'ccs' metadata"
+                }
+            }
+        })
+    }
+
+    async fn export(
+        &self,
+        data: &DataContainer,
+        config: Value,
+    ) -> Result<ExportResult, ProcessingError> {
+        let polarity = config["polarity"].as_str()
+            .or_else(|| data.metadata.get("polarity").and_then(|v| v.as_str()))
+            .unwrap_or("positive");
+        let polarity = match polarity.to_lowercase().as_str() {
+            "positive" | "pos" | "+" => "Positive",
+            "negative" | "neg" | "-" => "Negative",
+            other => return Err(ProcessingError::ConfigError(format!("Unsupported polarity: '{}'", other))),
+        };
+        let rt_padding = config["rt_padding"].as_f64().unwrap_or(0.0).max(0.0);
+        let rt_window = config["rt_window"].as_f64().unwrap_or(1.0);
+        let include_ccs = config["include_ccs"].as_bool().unwrap_or(false);
+        let export_config: ExportConfig = serde_json::from_value(config)
+            .unwrap_or_default();
+        let precision = export_config.decimal_precision;
+
+        let mut content = String::new();
+        if export_config.include_header {
+            content.push_str("m/z,RT_start,RT_end,polarity");
+            if include_ccs {
+                content.push_str(",CCS");
+            }
+            content.push('\n');
+        }
+
+        let mut target_count = 0;
+        let mut skipped_peaks = 0;
+        for curve in &data.curves {
+            for peak in &curve.peaks {
+                let (Some(mz), Some((rt_start, rt_end))) = (target_mz(curve, peak), rt_window_for(curve, peak, rt_window)) else {
+                    skipped_peaks += 1;
+                    continue;
+                };
+
+                let mut row = vec![
+                    helpers::format_float(mz, precision),
+                    helpers::format_float((rt_start - rt_padding).max(0.0), precision),
+                    helpers::format_float(rt_end + rt_padding, precision),
+                    polarity.to_string(),
+                ];
+                if include_ccs {
+                    row.push(peak.get_metadata("ccs")
+                        .and_then(|v| v.as_f64())
+                        .map(|ccs| helpers::format_float(ccs, precision))
+                        .unwrap_or_default());
+                }
+                content.push_str(&row.join(","));
+                content.push('\n');
+                target_count += 1;
+            }
+        }
+
+        let filename = format!("inclusion_list_{}.csv", helpers::generate_timestamp());
+        let mut metadata = helpers::create_export_metadata(
+            self.name(),
+            data.curves.len(),
+            data.total_peak_count(),
+            &export_config,
+        );
+        metadata.insert("target_count".to_string(), serde_json::json!(target_count));
+        metadata.insert("skipped_peaks".to_string(), serde_json::json!(skipped_peaks));
+        metadata.insert("polarity".to_string(), serde_json::json!(polarity));
+
+        Ok(ExportResult {
+            data: content.into_bytes(),
+            filename,
+            mime_type: self.mime_type().to_string(),
+            metadata,
+        })
+    }
+}
+
+/// Whether the curve's x axis is retention time (chromatogram-type curves)
+fn is_chromatogram(curve: &Curve) -> bool {
+    matches!(curve.curve_type.as_str(), "TIC" | "XIC" | "EIC" | "BPC" | "TIC_MS1" | "TIC_MS2")
+}
+
+/// Target m/z: the peak's own m/z, otherwise the centre of the curve's extraction range
+fn target_mz(curve: &Curve, peak: &Peak) -> Option<f64> {
+    peak.mz.or_else(|| curve.mz_range.map(|(min, max)| (min + max) / 2.0))
+}
+
+/// RT window of a peak
+///
+/// On chromatograms the window spans the peak boundaries (centre ± FWHM when the
+/// boundaries were never computed); on other curves it is centred on the peak's
+/// retention time, falling back to the curve's RT range
+fn rt_window_for(curve: &Curve, peak: &Peak, rt_window: f64) -> Option<(f64, f64)> {
+    if is_chromatogram(curve) {
+        if peak.right_boundary > peak.left_boundary {
+            return Some((peak.left_boundary, peak.right_boundary));
+        }
+        if peak.fwhm > 0.0 {
+            return Some((peak.center - peak.fwhm, peak.center + peak.fwhm));
+        }
+        return None;
+    }
+
+    match peak.retention_time {
+        Some(rt) => Some((rt - rt_window / 2.0, rt + rt_window / 2.0)),
+        None => curve.rt_range,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::data::PeakType;
+
+    fn xic_container() -> DataContainer {
+        let x: Vec<f64> = (0..100).map(|i| i as f64 * 0.1).collect();
+        let y = vec![0.0; x.len()];
+        let mut curve = Curve::new(
+            "xic_1".to_string(), "XIC".to_string(), x, y,
+            "Retention Time".to_string(), "Intensity".to_string(), "min".to_string(), "counts".to_string(),
+        );
+        curve.set_mz_range(500.0, 500.02);
+        for (i, (center, left, right)) in [(2.0, 1.6, 2.5), (6.0, 5.7, 6.4)].iter().enumerate() {
+            let mut peak = Peak::new(format!("peak_{}", i), "xic_1".to_string(), *center, 100.0, PeakType::Gaussian);
+            peak.left_boundary = *left;
+            peak.right_boundary = *right;
+            curve.add_peak(peak);
+        }
+
+        let mut container = DataContainer::new();
+        container.metadata.insert("polarity".to_string(), serde_json::json!("negative"));
+        container.curves.push(curve);
+        container
+    }
+
+    #[tokio::test]
+    async fn writes_one_row_per_peak_spanning_boundaries() {
+        let data = xic_container();
+        let result = TargetListExporter.export(&data, serde_json::json!({})).await.unwrap();
+        let text = String::from_utf8(result.data).unwrap();
+        let mut lines = text.lines();
+
+        assert_eq!(lines.next(), Some("m/z,RT_start,RT_end,polarity"));
+        let rows: Vec<Vec<&str>> = lines.map(|line| line.split(',').collect()).collect();
+        let peaks = &data.curves[0].peaks;
+        assert_eq!(rows.len(), peaks.len());
+
+        for (row, peak) in rows.iter().zip(peaks) {
+            let mz: f64 = row[0].parse().unwrap();
+            let rt_start: f64 = row[1].parse().unwrap();
+            let rt_end: f64 = row[2].parse().unwrap();
+            assert!((mz - 500.01).abs() < 1e-9);
+            assert!(rt_start <= peak.left_boundary && rt_end >= peak.right_boundary);
+            assert_eq!(row[3], "Negative");
+        }
+        assert_eq!(result.metadata["target_count"], serde_json::json!(2));
+    }
+}
@@ -1,6 +1,8 @@
 use async_trait::async_trait;
 use serde_json::Value;
 use crate::core::data::{DataContainer, ProcessingError, PeakType, DetectionAlgorithm, Peak, Curve};
+use crate::core::utils::signal::{savitzky_golay, mean_spacing};
+use crate::core::processors::peak_fitting::peak_shapes::evaluate_peak;
 use super::base::{Exporter, ExportResult, ExportConfig, helpers};
 
 /// TSV (Tab-Separated Values) exporter for mass spectrometry data
@@ -57,7 +59,7 @@ impl Exporter for TsvExporter {
                 },
                 "export_format": {
                     "type": "string",
-                    "enum": ["peaks_only", "curves_only", "combined", "summary", "fitted_curves"],
+                    "enum": ["peaks_only", "curves_only", "combined", "summary", "fitted_curves", "second_derivative"],
                     "default": "combined",
                     "description": "Export format type"
                 },
@@ -72,6 +74,40 @@ impl Exporter for TsvExporter {
                     "maximum": 1000,
                     "default": 100,
                     "description": "Number of points for fitted curves"
+                },
+                "fitted_curve_grid": {
+                    "type": "string",
+                    "enum": ["peak", "original"],
+                    "default": "peak",
+                    "description": "Sample each fit on its own ±3σ grid, or the summed fit on the curve's original x-values as aligned X, Y_Raw, Y_Fit rows"
+                },
+                "derivative_window": {
+                    "type": "integer",
+                    "minimum": 3,
+                    "default": 11,
+                    "description": "Savitzky-Golay window size for the second_derivative format (odd); curves with fewer points are skipped"
+                },
+                "derivative_order": {
+                    "type": "integer",
+                    "minimum": 2,
+                    "default": 3,
+                    "description": "Savitzky-Golay polynomial order for the second_derivative format"
+                },
+                "include_overview": {
+                    "type": "boolean",
+                    "default": false,
+                    "description": "Prepend the run-level TIC overview curve"
+                },
+                "include_bpc": {
+                    "type": "boolean",
+                    "default": false,
+                    "description": "Also prepend the BPC overview curve"
+                },
+                "sort_by": {
+                    "type": "string",
+                    "enum": ["center", "area_desc", "amplitude_desc", "quality_desc"],
+                    "default": "center",
+                    "description": "Peak order within each curve"
                 }
             }
         })
@@ -86,16 +122,23 @@ impl Exporter for TsvExporter {
             .as_str()
             .unwrap_or("combined")
             .to_string();
+        let derivative_window = config["derivative_window"].as_u64().unwrap_or(11) as usize;
+        let derivative_order = config["derivative_order"].as_u64().unwrap_or(3) as usize;
         
         let export_config: ExportConfig = serde_json::from_value(config)
             .unwrap_or_default();
         
+        let overview = helpers::with_overview_curves(data, &export_config);
+        let sorted = helpers::sort_peaks(overview.as_ref().unwrap_or(data), &export_config)?;
+        let data = &sorted;
+        
         let content = match export_format.as_str() {
             "peaks_only" => self.export_peaks_only(data, &export_config)?,
             "curves_only" => self.export_curves_only(data, &export_config)?,
             "combined" => self.export_combined(data, &export_config)?,
             "summary" => self.export_summary(data, &export_config)?,
             "fitted_curves" => self.export_fitted_curves(data, &export_config)?,
+            "second_derivative" => self.export_second_derivative(data, &export_config, derivative_window, derivative_order)?,
             _ => {
                 return Err(ProcessingError::ConfigError(
                     format!("Unsupported export format: {}", export_format)
@@ -126,34 +169,38 @@ impl TsvExporter {
         let mut content = String::new();
         
         if config.include_header {
-            content.push_str("Peak_ID\tCurve_ID\tCenter\tAmplitude\tArea\tFWHM\tHWHM\tSigma\tGamma\t");
-            content.push_str("Left_HWHM\tRight_HWHM\tAsymmetry_Factor\tLeft_Boundary\tRight_Boundary\tPeak_Span\t");
+            content.push_str("Peak_ID\tCurve_ID\tCenter\tAmplitude\tArea\tArea_Error\tArea_Unit\tFWHM\tHWHM\tSigma\tGamma\t");
+            content.push_str("Left_HWHM\tRight_HWHM\tAsymmetry_Factor\tTailing_Factor_USP\tShape_Direction\tLeft_Boundary\tRight_Boundary\tPeak_Span\t");
             content.push_str("R_Squared\tResidual_Sum_Squares\tStandard_Error\tParameter_Count\tPeak_Type\t");
             content.push_str("Mixing_Parameter\tSignal_to_Baseline_Ratio\tArea_Percentage\tIntensity_Percentage\t");
             content.push_str("Left_Derivative\tRight_Derivative\tDerivative_Ratio\tMZ\tRetention_Time\t");
-            content.push_str("Drift_Time\tMS_Level\tDetection_Algorithm\tDetection_Threshold\tConfidence\t");
+            content.push_str("Drift_Time\tMS_Level\tPolarity\tDetection_Algorithm\tDetection_Threshold\tConfidence\t");
             content.push_str("Fit_Parameters\tFit_Parameter_Errors\n");
         }
         
         // 遍历所有曲线中的峰
         for curve in &data.curves {
             for peak in curve.get_peaks() {
-            content.push_str(&format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t",
+            content.push_str(&format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t",
                 peak.id,
                 peak.curve_id,
                 helpers::format_float(peak.center, config.decimal_precision),
                 helpers::format_float(peak.amplitude, config.decimal_precision),
                 helpers::format_float(peak.area, config.decimal_precision),
+                helpers::format_float(peak.area_error, config.decimal_precision),
+                peak.area_unit.clone().unwrap_or_else(|| curve.area_unit()),
                 helpers::format_float(peak.fwhm, config.decimal_precision),
                 helpers::format_float(peak.hwhm, config.decimal_precision),
                 helpers::format_float(peak.sigma, config.decimal_precision),
                 helpers::format_float(peak.gamma, config.decimal_precision),
             ));
             
-            content.push_str(&format!("{}\t{}\t{}\t{}\t{}\t{}\t",
+            content.push_str(&format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t",
                 helpers::format_float(peak.left_hwhm, config.decimal_precision),
                 helpers::format_float(peak.right_hwhm, config.decimal_precision),
                 helpers::format_float(peak.asymmetry_factor, config.decimal_precision),
+                helpers::format_float(peak.tailing_factor_usp, config.decimal_precision),
+                peak.get_metadata("shape_direction").and_then(|v| v.as_str()).unwrap_or(""),
                 helpers::format_float(peak.left_boundary, config.decimal_precision),
                 helpers::format_float(peak.right_boundary, config.decimal_precision),
                 helpers::format_float(peak.peak_span, config.decimal_precision),
@@ -168,7 +215,7 @@ impl TsvExporter {
                 helpers::format_float(peak.mixing_parameter, config.decimal_precision),
             ));
             
-            content.push_str(&format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            content.push_str(&format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t",
                 helpers::format_float(peak.signal_to_baseline_ratio, config.decimal_precision),
                 helpers::format_float(peak.area_percentage, config.decimal_precision),
                 helpers::format_float(peak.intensity_percentage, config.decimal_precision),
@@ -179,6 +226,7 @@ impl TsvExporter {
                 peak.retention_time.map(|v| helpers::format_float(v, config.decimal_precision)).unwrap_or("".to_string()),
                 peak.drift_time.map(|v| helpers::format_float(v, config.decimal_precision)).unwrap_or("".to_string()),
                 peak.ms_level.map(|v| v.to_string()).unwrap_or("".to_string()),
+                peak.polarity.as_deref().unwrap_or(""),
                 self.format_detection_algorithm(&peak.detection_algorithm),
                 helpers::format_float(peak.detection_threshold, config.decimal_precision),
                 helpers::format_float(peak.confidence, config.decimal_precision)
@@ -319,6 +367,8 @@ impl TsvExporter {
         // Basic statistics
         content.push_str(&format!("Total_Curves\t{}\n", data.curves.len()));
         content.push_str(&format!("Total_Peaks\t{}\n", data.total_peak_count()));
+        content.push_str(&format!("Total_Integrated_Area\t{}\n",
+            helpers::format_float(data.total_integrated_area(), config.decimal_precision)));
         
         if !data.curves.is_empty() {
             let total_points: usize = data.curves.iter().map(|c| c.point_count).sum();
@@ -360,6 +410,16 @@ impl TsvExporter {
     
     /// Export fitted curves for visualization
     fn export_fitted_curves(&self, data: &DataContainer, config: &ExportConfig) -> Result<String, ProcessingError> {
+        match config.fitted_curve_grid.as_deref().unwrap_or("peak") {
+            "peak" => {},
+            "original" => return self.export_fitted_on_original_grid(data, config),
+            other => {
+                return Err(ProcessingError::ConfigError(
+                    format!("Unsupported fitted curve grid: {}", other)
+                ));
+            }
+        }
+        
         let mut content = String::new();
         
         if config.include_header {
@@ -395,6 +455,76 @@ impl TsvExporter {
         Ok(content)
     }
     
+    /// Export the summed fit of all peaks sampled on each curve's own x-values
+    /// 每行 X, Y_Raw, Y_Fit 对齐，外部绘图工具无需重采样即可叠加
+    fn export_fitted_on_original_grid(&self, data: &DataContainer, config: &ExportConfig) -> Result<String, ProcessingError> {
+        let mut content = String::new();
+        
+        if config.include_header {
+            content.push_str("Curve_ID\tX\tY_Raw\tY_Fit\n");
+        }
+        
+        for curve in &data.curves {
+            for (&x, &y) in curve.x_values.iter().zip(curve.y_values.iter()) {
+                let mut fitted = 0.0;
+                for peak in curve.get_peaks() {
+                    fitted += self.calculate_fitted_y(x, peak)?;
+                }
+                content.push_str(&format!("{}\t{}\t{}\t{}\n",
+                    curve.id,
+                    helpers::format_float(x, config.decimal_precision),
+                    helpers::format_float(y, config.decimal_precision),
+                    helpers::format_float(fitted, config.decimal_precision)
+                ));
+            }
+        }
+        
+        Ok(content)
+    }
+    
+    /// Export the Savitzky-Golay second derivative of each curve
+    /// 负的D2谷值对应峰（包括原始曲线上只表现为拐点的肩峰）
+    fn export_second_derivative(
+        &self,
+        data: &DataContainer,
+        config: &ExportConfig,
+        window_size: usize,
+        polynomial_order: usize,
+    ) -> Result<String, ProcessingError> {
+        let mut content = String::new();
+        
+        if config.include_header {
+            content.push_str("Curve_ID\tX\tY\tD2\n");
+        }
+        
+        for curve in &data.curves {
+            // 点数不足一个窗口的曲线无法求导，跳过而不是让整个导出失败
+            if curve.y_values.len() < window_size {
+                log::warn!("⚠️ 曲线 {} 只有 {} 个数据点，少于二阶导数窗口 {}，已跳过",
+                    curve.id, curve.y_values.len(), window_size);
+                continue;
+            }
+            let d2 = savitzky_golay(
+                &curve.y_values,
+                window_size,
+                polynomial_order,
+                2,
+                mean_spacing(&curve.x_values),
+            )?;
+            
+            for ((&x, &y), d2) in curve.x_values.iter().zip(curve.y_values.iter()).zip(d2) {
+                content.push_str(&format!("{}\t{}\t{}\t{}\n",
+                    curve.id,
+                    helpers::format_float(x, config.decimal_precision),
+                    helpers::format_float(y, config.decimal_precision),
+                    helpers::format_float(d2, config.decimal_precision)
+                ));
+            }
+        }
+        
+        Ok(content)
+    }
+    
     /// Generate fitted curve points for a peak
     fn generate_fitted_curve(&self, peak: &Peak, _curve: &Curve, config: &ExportConfig) -> Result<Vec<(f64, f64)>, ProcessingError> {
         let num_points = config.fitted_curve_points.unwrap_or(100);
@@ -461,6 +591,22 @@ impl TsvExporter {
                 
                 Ok(amplitude * (mixing * lorentzian + (1.0 - mixing) * gaussian))
             },
+            PeakType::NLC => {
+                // NLC (Haarhoff-Van der Linde): fronting/tailing shape with the distortion term from the fit parameters
+                if peak.sigma <= 0.0 {
+                    return Err(ProcessingError::ProcessError("Invalid sigma value".to_string()));
+                }
+                
+                Ok(evaluate_peak(peak, x))
+            },
+            PeakType::PearsonIV | PeakType::PearsonVII => {
+                // Pearson: width, m (and nu) come from the fit parameters
+                if peak.fit_parameters.len() < 4 {
+                    return Err(ProcessingError::ProcessError("Missing Pearson fit parameters".to_string()));
+                }
+
+                Ok(evaluate_peak(peak, x))
+            },
             _ => {
                 // For other peak types, use Gaussian as approximation
                 let amplitude = peak.amplitude;
@@ -489,6 +635,7 @@ impl TsvExporter {
             PeakType::BiGaussian => "BiGaussian".to_string(),
             PeakType::VoigtExponentialTail => "VoigtExponentialTail".to_string(),
             PeakType::PearsonIV => "PearsonIV".to_string(),
+            PeakType::PearsonVII => "PearsonVII".to_string(),
             PeakType::NLC => "NLC".to_string(),
             PeakType::GMGBayesian => "GMGBayesian".to_string(),
         }
@@ -505,3 +652,132 @@ impl TsvExporter {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_fitted_curve_on_original_grid_shares_raw_x_values() {
+        let x_values: Vec<f64> = (0..137).map(|i| 2.0 + i as f64 * 0.0371).collect();
+        let y_values: Vec<f64> = x_values.iter()
+            .map(|&x| 50.0 * (-0.5 * ((x - 4.5) / 0.2).powi(2)).exp() + 1.0)
+            .collect();
+        let mut curve = Curve::new(
+            "dt_curve".to_string(),
+            "DT".to_string(),
+            x_values.clone(),
+            y_values,
+            "Drift Time".to_string(),
+            "Intensity".to_string(),
+            "ms".to_string(),
+            "counts".to_string(),
+        );
+        let mut peak = Peak::new("peak_1".to_string(), "dt_curve".to_string(), 4.5, 50.0, PeakType::Gaussian);
+        peak.sigma = 0.2;
+        curve.add_peak(peak);
+        let mut data = DataContainer::new();
+        data.curves.push(curve);
+
+        let config = serde_json::json!({
+            "export_format": "fitted_curves",
+            "fitted_curve_grid": "original",
+            "decimal_precision": 10,
+        });
+        let result = TsvExporter.export(&data, config).await.unwrap();
+        let content = String::from_utf8(result.data).unwrap();
+        let mut lines = content.lines();
+
+        assert_eq!(lines.next(), Some("Curve_ID\tX\tY_Raw\tY_Fit"));
+        let rows: Vec<Vec<&str>> = lines.map(|line| line.split('\t').collect()).collect();
+        assert_eq!(rows.len(), x_values.len());
+        for (row, &x) in rows.iter().zip(&x_values) {
+            assert_eq!(row[1], helpers::format_float(x, 10));
+        }
+        let apex = rows.iter()
+            .max_by(|a, b| a[3].parse::<f64>().unwrap().total_cmp(&b[3].parse::<f64>().unwrap()))
+            .unwrap();
+        assert!((apex[1].parse::<f64>().unwrap() - 4.5).abs() < 0.04);
+    }
+
+    #[tokio::test]
+    async fn test_include_overview_prepends_tic_and_bpc_curves() {
+        use crate::core::utils::test_fixtures::ms1_spectrum;
+
+        let mut data = DataContainer::new();
+        for i in 0..3 {
+            data.spectra.push(ms1_spectrum(i, i as f64 * 0.5, &[(500.0, 10.0 + i as f32), (600.0, 30.0)]));
+        }
+        data.curves.push(Curve::new(
+            "xic_500".to_string(),
+            "XIC".to_string(),
+            vec![0.0, 0.5, 1.0],
+            vec![10.0, 11.0, 12.0],
+            "Retention Time".to_string(),
+            "Intensity".to_string(),
+            "min".to_string(),
+            "counts".to_string(),
+        ));
+        let curve_types = |content: &str| -> Vec<String> {
+            content.lines().skip(1).map(|line| line.split('\t').nth(1).unwrap().to_string()).collect()
+        };
+
+        let without = TsvExporter.export(&data, serde_json::json!({"export_format": "curves_only"})).await.unwrap();
+        assert_eq!(curve_types(&String::from_utf8(without.data).unwrap()), vec!["XIC"]);
+
+        let config = serde_json::json!({"export_format": "curves_only", "include_overview": true, "include_bpc": true});
+        let with = TsvExporter.export(&data, config).await.unwrap();
+        let content = String::from_utf8(with.data).unwrap();
+        assert_eq!(curve_types(&content), vec!["TIC", "BPC", "XIC"]);
+
+        // TIC 的总离子流为各光谱强度和之和：(40 + 41 + 42)
+        let tic_row: Vec<&str> = content.lines().nth(1).unwrap().split('\t').collect();
+        assert_eq!(tic_row[0], "overview_tic");
+        assert_eq!(tic_row[11].parse::<f64>().unwrap(), 123.0);
+    }
+
+    #[tokio::test]
+    async fn test_second_derivative_reveals_hidden_shoulder_and_skips_short_curves() {
+        let x_values: Vec<f64> = (0..=200).map(|i| i as f64 * 0.05).collect();
+        let gaussian = |x: f64, center: f64, amplitude: f64, sigma: f64| amplitude * (-(x - center).powi(2) / (2.0 * sigma * sigma)).exp();
+        let curve = |id: &str, x_values: Vec<f64>, y_values: Vec<f64>| Curve::new(
+            id.to_string(),
+            "XIC".to_string(),
+            x_values,
+            y_values,
+            "Retention Time".to_string(),
+            "Intensity".to_string(),
+            "min".to_string(),
+            "counts".to_string(),
+        );
+        // 主峰 5.0 加上 6.0 处的肩峰：原始曲线只有一个极大值，肩峰处只是拐点
+        let y_values: Vec<f64> = x_values.iter()
+            .map(|&x| gaussian(x, 5.0, 100.0, 0.5) + gaussian(x, 6.0, 30.0, 0.3))
+            .collect();
+        let local_maxima = (1..y_values.len() - 1)
+            .filter(|&i| y_values[i] > y_values[i - 1] && y_values[i] > y_values[i + 1])
+            .count();
+        assert_eq!(local_maxima, 1);
+
+        let mut data = DataContainer::new();
+        data.curves.push(curve("shoulder", x_values, y_values));
+        data.curves.push(curve("short", vec![0.0, 0.1, 0.2, 0.3, 0.4], vec![1.0, 2.0, 3.0, 2.0, 1.0]));
+
+        let config = serde_json::json!({"export_format": "second_derivative", "derivative_window": 11, "derivative_order": 3});
+        let result = TsvExporter.export(&data, config).await.unwrap();
+        let content = String::from_utf8(result.data).unwrap();
+        let rows: Vec<(f64, f64)> = content.lines().skip(1)
+            .map(|line| line.split('\t').collect::<Vec<_>>())
+            .inspect(|fields| assert_eq!(fields[0], "shoulder"))
+            .map(|fields| (fields[1].parse().unwrap(), fields[3].parse().unwrap()))
+            .collect();
+        assert_eq!(rows.len(), 201);
+
+        let troughs: Vec<f64> = (1..rows.len() - 1)
+            .filter(|&i| rows[i].1 < 0.0 && rows[i].1 < rows[i - 1].1 && rows[i].1 < rows[i + 1].1)
+            .map(|i| rows[i].0)
+            .collect();
+        assert!(troughs.iter().any(|&x| (x - 5.0).abs() <= 0.1), "troughs {:?}", troughs);
+        assert!(troughs.iter().any(|&x| (x - 6.0).abs() <= 0.1), "troughs {:?}", troughs);
+    }
+}
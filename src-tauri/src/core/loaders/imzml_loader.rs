@@ -0,0 +1,584 @@
+//! imzML 成像质谱加载
+//!
+//! imzML 由 XML 元数据（.imzML）和二进制数据（.ibd）两部分组成：XML 中每张光谱记录像素坐标，
+//! 以及 m/z、强度数组在 ibd 文件中的偏移量、长度和数据类型。
+//! 加载时只解析一次 XML，把像素坐标与数组位置存入容器元数据 `pixel_index`；
+//! 像素光谱不放进容器，生成离子图像时按位置从 ibd 读取所需的片段
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::Path;
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+
+use crate::core::data::{DataContainer, ProcessingError};
+
+/// 像素坐标 (IMS:1000050 / IMS:1000051)
+const POSITION_X: &str = "IMS:1000050";
+const POSITION_Y: &str = "IMS:1000051";
+/// scanSettings 中声明的图像尺寸 (max count of pixels x / y)
+const MAX_COUNT_X: &str = "IMS:1000042";
+const MAX_COUNT_Y: &str = "IMS:1000043";
+/// 未声明图像尺寸时允许的最大图像像素数（4096 × 4096）
+const MAX_UNDECLARED_IMAGE_PIXELS: u64 = 4096 * 4096;
+/// 二进制数组在 ibd 中的偏移量与元素个数
+const EXTERNAL_OFFSET: &str = "IMS:1000102";
+const EXTERNAL_ARRAY_LENGTH: &str = "IMS:1000103";
+/// 数组类型
+const MZ_ARRAY: &str = "MS:1000514";
+const INTENSITY_ARRAY: &str = "MS:1000515";
+/// 容器元数据中的像素索引
+const PIXEL_INDEX_KEY: &str = "pixel_index";
+const IBD_PATH_KEY: &str = "ibd_path";
+
+/// 单个像素的坐标，以及 m/z、强度数组在 ibd 文件中的位置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImzmlPixel {
+    pub x: u32,
+    pub y: u32,
+    pub mz: IbdArray,
+    pub intensity: IbdArray,
+}
+
+/// ibd 文件中的一个二进制数组
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct IbdArray {
+    /// 字节偏移量
+    pub offset: u64,
+    /// 元素个数
+    pub length: usize,
+    pub data_type: BinaryType,
+}
+
+/// 离子图像：按像素坐标排列的 m/z 窗口内强度和
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IonImage {
+    pub mz_range: (f64, f64),
+    pub width: usize,
+    pub height: usize,
+    /// 第一列 / 第一行对应的像素坐标
+    pub x_min: u32,
+    pub y_min: u32,
+    /// 按行（y）排列，`intensities[row][col]`，没有光谱的像素为 0
+    pub intensities: Vec<Vec<f64>>,
+}
+
+/// binaryDataArray 的数值类型
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BinaryType {
+    Float32,
+    Float64,
+    Int32,
+    Int64,
+}
+
+impl BinaryType {
+    fn from_accession(accession: &str) -> Option<Self> {
+        match accession {
+            "MS:1000521" => Some(Self::Float32),
+            "MS:1000523" => Some(Self::Float64),
+            "MS:1000519" | "IMS:1000141" => Some(Self::Int32),
+            "MS:1000522" | "IMS:1000142" => Some(Self::Int64),
+            _ => None,
+        }
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            Self::Float32 | Self::Int32 => 4,
+            Self::Float64 | Self::Int64 => 8,
+        }
+    }
+
+    fn decode(&self, bytes: &[u8]) -> f64 {
+        match self {
+            Self::Float32 => f32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+            Self::Float64 => f64::from_le_bytes(bytes.try_into().unwrap()),
+            Self::Int32 => i32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+            Self::Int64 => i64::from_le_bytes(bytes.try_into().unwrap()) as f64,
+        }
+    }
+}
+
+/// 按数组位置从 ibd 文件读取数值，只读取请求的元素区间
+struct IbdReader {
+    file: File,
+}
+
+impl IbdReader {
+    fn open(path: &Path) -> Result<Self, ProcessingError> {
+        let file = File::open(path)
+            .map_err(|e| ProcessingError::DataError(format!("无法读取ibd文件 {}: {}", path.display(), e)))?;
+        Ok(Self { file })
+    }
+
+    fn read(&mut self, array: &IbdArray, range: Range<usize>) -> Result<Vec<f64>, ProcessingError> {
+        let size = array.data_type.size();
+        let mut bytes = vec![0u8; range.len() * size];
+        self.file.seek(SeekFrom::Start(array.offset + (range.start * size) as u64))?;
+        self.file.read_exact(&mut bytes)?;
+        Ok(bytes.chunks_exact(size).map(|b| array.data_type.decode(b)).collect())
+    }
+
+    fn read_all(&mut self, array: &IbdArray) -> Result<Vec<f64>, ProcessingError> {
+        self.read(array, 0..array.length)
+    }
+}
+
+/// XML 中一张光谱的描述（数组尚未从 ibd 读取）
+#[derive(Debug, Default)]
+struct SpectrumEntry {
+    x: Option<u32>,
+    y: Option<u32>,
+    arrays: Vec<Vec<(String, String)>>,
+}
+
+/// scanSettings 中声明的图像尺寸
+#[derive(Debug, Default, Clone, Copy)]
+struct DeclaredSize {
+    max_x: Option<u32>,
+    max_y: Option<u32>,
+}
+
+/// imzML 加载器
+pub struct ImzmlLoader;
+
+impl ImzmlLoader {
+    /// 读取 imzML，容器元数据记录像素数、图像尺寸、m/z 范围，以及像素索引 `pixel_index` 与 ibd 路径
+    ///
+    /// 像素坐标必须落在 scanSettings 声明的图像尺寸内；未声明尺寸时图像不得超过 4096 × 4096 像素。
+    /// ibd 中只读取 m/z 数组（连续模式下各像素共用同一数组，只读一次）用于统计 m/z 范围
+    pub fn load(path: &str) -> Result<DataContainer, ProcessingError> {
+        let xml = std::fs::read_to_string(path)?;
+        let ibd_path = Path::new(path).with_extension("ibd");
+        let ibd_len = std::fs::metadata(&ibd_path)
+            .map_err(|e| ProcessingError::DataError(format!("无法读取ibd文件 {}: {}", ibd_path.display(), e)))?
+            .len();
+
+        let (entries, declared) = Self::parse_spectra(&xml)?;
+        let pixels = entries.iter()
+            .map(|entry| Self::index_pixel(entry, ibd_len))
+            .collect::<Result<Vec<_>, _>>()?;
+        if pixels.is_empty() {
+            return Err(ProcessingError::DataError(format!("imzML文件中没有光谱: {}", path)));
+        }
+        Self::validate_image_size(&pixels, declared)?;
+
+        let mut ibd = IbdReader::open(&ibd_path)?;
+        let mut mz_min = f64::INFINITY;
+        let mut mz_max = f64::NEG_INFINITY;
+        let mut last_mz: Option<IbdArray> = None;
+        for pixel in &pixels {
+            if last_mz == Some(pixel.mz) {
+                continue;
+            }
+            for mz in ibd.read_all(&pixel.mz)? {
+                mz_min = mz_min.min(mz);
+                mz_max = mz_max.max(mz);
+            }
+            last_mz = Some(pixel.mz);
+        }
+        let width = pixels.iter().map(|p| p.x).max().unwrap_or(0);
+        let height = pixels.iter().map(|p| p.y).max().unwrap_or(0);
+        log::info!("🗺️ imzML加载完成: {} 个像素 ({} × {})", pixels.len(), width, height);
+
+        let mut container = DataContainer::new();
+        container.metadata.insert("file_path".to_string(), serde_json::Value::String(path.to_string()));
+        container.metadata.insert("source_format".to_string(), serde_json::Value::String("imzML".to_string()));
+        container.metadata.insert("pixel_count".to_string(), serde_json::json!(pixels.len()));
+        container.metadata.insert("image_width".to_string(), serde_json::json!(width));
+        container.metadata.insert("image_height".to_string(), serde_json::json!(height));
+        if mz_min.is_finite() {
+            container.metadata.insert("mz_min".to_string(), serde_json::json!(mz_min));
+            container.metadata.insert("mz_max".to_string(), serde_json::json!(mz_max));
+        }
+        container.metadata.insert(IBD_PATH_KEY.to_string(), serde_json::Value::String(ibd_path.to_string_lossy().to_string()));
+        container.metadata.insert(PIXEL_INDEX_KEY.to_string(), serde_json::to_value(&pixels)?);
+
+        Ok(container)
+    }
+
+    /// 检查像素坐标：从 1 开始，且不超过声明的图像尺寸
+    fn validate_image_size(pixels: &[ImzmlPixel], declared: DeclaredSize) -> Result<(), ProcessingError> {
+        for pixel in pixels {
+            let outside = pixel.x == 0 || pixel.y == 0
+                || declared.max_x.is_some_and(|max| pixel.x > max)
+                || declared.max_y.is_some_and(|max| pixel.y > max);
+            if outside {
+                return Err(ProcessingError::DataError(format!(
+                    "像素 ({}, {}) 超出声明的图像尺寸 {} × {}", pixel.x, pixel.y,
+                    declared.max_x.map_or("?".to_string(), |x| x.to_string()),
+                    declared.max_y.map_or("?".to_string(), |y| y.to_string()),
+                )));
+            }
+        }
+
+        if declared.max_x.is_none() || declared.max_y.is_none() {
+            let (x_min, x_max, y_min, y_max) = pixels.iter().fold((u32::MAX, 0, u32::MAX, 0), |(x_lo, x_hi, y_lo, y_hi), p| {
+                (x_lo.min(p.x), x_hi.max(p.x), y_lo.min(p.y), y_hi.max(p.y))
+            });
+            let area = ((x_max - x_min) as u64 + 1) * ((y_max - y_min) as u64 + 1);
+            if area > MAX_UNDECLARED_IMAGE_PIXELS {
+                return Err(ProcessingError::DataError(format!(
+                    "imzML未声明图像尺寸，且像素坐标覆盖 {} 个像素，超过上限 {}", area, MAX_UNDECLARED_IMAGE_PIXELS
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 解析 XML：展开 referenceableParamGroupRef，收集每张光谱的坐标与数组参数，以及声明的图像尺寸
+    fn parse_spectra(xml: &str) -> Result<(Vec<SpectrumEntry>, DeclaredSize), ProcessingError> {
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+
+        let mut groups: HashMap<String, Vec<(String, String)>> = HashMap::new();
+        let mut current_group: Option<String> = None;
+        let mut current_spectrum: Option<SpectrumEntry> = None;
+        let mut current_array: Option<Vec<(String, String)>> = None;
+        let mut entries = Vec::new();
+        let mut declared = DeclaredSize::default();
+
+        loop {
+            let event = reader.read_event()
+                .map_err(|e| ProcessingError::DataError(format!("imzML解析失败: {}", e)))?;
+            match event {
+                Event::Start(ref e) | Event::Empty(ref e) => {
+                    let is_empty = matches!(event, Event::Empty(_));
+                    match e.name().as_ref() {
+                        b"referenceableParamGroup" if !is_empty => {
+                            current_group = attribute(e, "id")?;
+                            if let Some(id) = &current_group {
+                                groups.insert(id.clone(), Vec::new());
+                            }
+                        }
+                        b"spectrum" if !is_empty => current_spectrum = Some(SpectrumEntry::default()),
+                        b"binaryDataArray" if !is_empty => current_array = Some(Vec::new()),
+                        b"referenceableParamGroupRef" => {
+                            let params = attribute(e, "ref")?.and_then(|id| groups.get(&id).cloned()).unwrap_or_default();
+                            if let Some(array) = current_array.as_mut() {
+                                array.extend(params);
+                            }
+                        }
+                        b"cvParam" => {
+                            let accession = attribute(e, "accession")?.unwrap_or_default();
+                            let value = attribute(e, "value")?.unwrap_or_default();
+                            if let Some(array) = current_array.as_mut() {
+                                array.push((accession, value));
+                            } else if let Some(spectrum) = current_spectrum.as_mut() {
+                                match accession.as_str() {
+                                    POSITION_X => spectrum.x = value.parse().ok(),
+                                    POSITION_Y => spectrum.y = value.parse().ok(),
+                                    _ => {}
+                                }
+                            } else if let Some(id) = &current_group {
+                                groups.entry(id.clone()).or_default().push((accession, value));
+                            } else {
+                                match accession.as_str() {
+                                    MAX_COUNT_X => declared.max_x = value.parse().ok(),
+                                    MAX_COUNT_Y => declared.max_y = value.parse().ok(),
+                                    _ => {}
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                Event::End(ref e) => match e.name().as_ref() {
+                    b"referenceableParamGroup" => current_group = None,
+                    b"binaryDataArray" => {
+                        if let (Some(array), Some(spectrum)) = (current_array.take(), current_spectrum.as_mut()) {
+                            spectrum.arrays.push(array);
+                        }
+                    }
+                    b"spectrum" => {
+                        if let Some(spectrum) = current_spectrum.take() {
+                            entries.push(spectrum);
+                        }
+                    }
+                    _ => {}
+                },
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        Ok((entries, declared))
+    }
+
+    /// 按数组参数确定一个像素的 m/z 与强度数组在 ibd 中的位置，并检查其不超出文件范围
+    fn index_pixel(entry: &SpectrumEntry, ibd_len: u64) -> Result<ImzmlPixel, ProcessingError> {
+        let (x, y) = match (entry.x, entry.y) {
+            (Some(x), Some(y)) => (x, y),
+            _ => return Err(ProcessingError::DataError("imzML光谱缺少像素坐标".to_string())),
+        };
+
+        let mut mz = None;
+        let mut intensity = None;
+        for params in &entry.arrays {
+            let has = |accession: &str| params.iter().any(|(a, _)| a == accession);
+            let number = |accession: &str| params.iter()
+                .find(|(a, _)| a == accession)
+                .and_then(|(_, v)| v.parse::<u64>().ok());
+            let data_type = params.iter()
+                .find_map(|(a, _)| BinaryType::from_accession(a))
+                .ok_or_else(|| ProcessingError::DataError(format!("像素 ({}, {}) 的数组缺少数据类型", x, y)))?;
+            let (offset, length) = match (number(EXTERNAL_OFFSET), number(EXTERNAL_ARRAY_LENGTH)) {
+                (Some(offset), Some(length)) => (offset, length),
+                _ => return Err(ProcessingError::DataError(format!("像素 ({}, {}) 的数组缺少ibd偏移量或长度", x, y))),
+            };
+
+            let length = length.checked_mul(data_type.size() as u64)
+                .and_then(|size| offset.checked_add(size))
+                .filter(|&end| end <= ibd_len)
+                .and_then(|_| usize::try_from(length).ok())
+                .ok_or_else(|| ProcessingError::DataError(format!(
+                    "像素 ({}, {}) 的数组 (偏移 {}, 长度 {}) 超出ibd文件范围 ({} 字节)", x, y, offset, length, ibd_len
+                )))?;
+            let array = IbdArray { offset, length, data_type };
+
+            if has(MZ_ARRAY) {
+                mz = Some(array);
+            } else if has(INTENSITY_ARRAY) {
+                intensity = Some(array);
+            }
+        }
+
+        match (mz, intensity) {
+            (Some(mz), Some(intensity)) if mz.length == intensity.length => Ok(ImzmlPixel { x, y, mz, intensity }),
+            (Some(_), Some(_)) => Err(ProcessingError::DataError(format!("像素 ({}, {}) 的m/z与强度数组长度不一致", x, y))),
+            _ => Err(ProcessingError::DataError(format!("像素 ({}, {}) 缺少m/z或强度数组", x, y))),
+        }
+    }
+}
+
+fn attribute(element: &BytesStart, name: &str) -> Result<Option<String>, ProcessingError> {
+    let attribute = element.try_get_attribute(name)
+        .map_err(|e| ProcessingError::DataError(format!("imzML属性解析失败: {}", e)))?;
+    attribute
+        .map(|a| a.unescape_value().map(|v| v.into_owned()))
+        .transpose()
+        .map_err(|e| ProcessingError::DataError(format!("imzML属性解析失败: {}", e)))
+}
+
+/// 读取 `ImzmlLoader::load` 存入容器元数据的 ibd 路径与像素索引
+pub fn pixels_from_container(container: &DataContainer) -> Result<(String, Vec<ImzmlPixel>), ProcessingError> {
+    if container.metadata.get("source_format").and_then(|v| v.as_str()) != Some("imzML") {
+        return Err(ProcessingError::DataError("容器不是由imzML文件加载的".to_string()));
+    }
+    let ibd_path = container.metadata.get(IBD_PATH_KEY)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| ProcessingError::DataError("容器中没有ibd文件路径".to_string()))?;
+    let pixels = container.metadata.get(PIXEL_INDEX_KEY)
+        .ok_or_else(|| ProcessingError::DataError("容器中没有imzML像素索引".to_string()))?;
+    let pixels: Vec<ImzmlPixel> = serde_json::from_value(pixels.clone())?;
+    Ok((ibd_path.to_string(), pixels))
+}
+
+/// 生成 m/z 窗口 [mz_min, mz_max] 的离子图像
+///
+/// 像素位置取自容器中的像素索引，不重新解析 XML；每个像素只读取 m/z 数组（相邻像素共用时只读一次）
+/// 和强度数组中覆盖窗口的那一段
+pub fn extract_ion_image(container: &DataContainer, mz_min: f64, mz_max: f64) -> Result<IonImage, ProcessingError> {
+    if !mz_min.is_finite() || !mz_max.is_finite() || mz_min >= mz_max {
+        return Err(ProcessingError::ConfigError(format!("无效的m/z范围: {}-{}", mz_min, mz_max)));
+    }
+    let (ibd_path, pixels) = pixels_from_container(container)?;
+    if pixels.is_empty() {
+        return Err(ProcessingError::DataError("没有像素数据".to_string()));
+    }
+
+    let x_min = pixels.iter().map(|p| p.x).min().unwrap_or(0);
+    let x_max = pixels.iter().map(|p| p.x).max().unwrap_or(0);
+    let y_min = pixels.iter().map(|p| p.y).min().unwrap_or(0);
+    let y_max = pixels.iter().map(|p| p.y).max().unwrap_or(0);
+    let width = (x_max - x_min) as usize + 1;
+    let height = (y_max - y_min) as usize + 1;
+
+    let mut ibd = IbdReader::open(Path::new(&ibd_path))?;
+    let mut mz_values: Option<(IbdArray, Vec<f64>)> = None;
+    let mut intensities = vec![vec![0.0; width]; height];
+    for pixel in &pixels {
+        if mz_values.as_ref().is_none_or(|(array, _)| *array != pixel.mz) {
+            mz_values = Some((pixel.mz, ibd.read_all(&pixel.mz)?));
+        }
+        let Some((_, mz)) = mz_values.as_ref() else { continue };
+        let in_window: Vec<usize> = mz.iter()
+            .enumerate()
+            .filter(|(_, &m)| m >= mz_min && m <= mz_max)
+            .map(|(i, _)| i)
+            .collect();
+        let (Some(&first), Some(&last)) = (in_window.first(), in_window.last()) else { continue };
+
+        let segment = ibd.read(&pixel.intensity, first..last + 1)?;
+        let total: f64 = in_window.iter().map(|&i| segment[i - first]).sum();
+        intensities[(pixel.y - y_min) as usize][(pixel.x - x_min) as usize] += total;
+    }
+
+    Ok(IonImage {
+        mz_range: (mz_min, mz_max),
+        width,
+        height,
+        x_min,
+        y_min,
+        intensities,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 写出 3 × 2 像素的连续模式 imzML：共享 m/z 数组 [100, 200, 300]，
+    /// 像素 (x, y) 在 m/z 100 处的强度为 10x + y
+    fn write_fixture(dir: &Path) -> String {
+        let mz = [100.0f64, 200.0, 300.0];
+        let mut ibd = vec![0u8; 16]; // UUID 头
+        let mz_offset = ibd.len();
+        for value in mz {
+            ibd.extend_from_slice(&value.to_le_bytes());
+        }
+
+        let mut spectra = String::new();
+        let mut index = 0;
+        for y in 1..=2u32 {
+            for x in 1..=3u32 {
+                let intensity_offset = ibd.len();
+                for value in [(10 * x + y) as f32, 1.0, 2.0] {
+                    ibd.extend_from_slice(&value.to_le_bytes());
+                }
+                spectra.push_str(&format!(r#"
+      <spectrum id="Scan={index}" defaultArrayLength="0" index="{index}">
+        <scanList count="1">
+          <scan>
+            <cvParam cvRef="IMS" accession="IMS:1000050" name="position x" value="{x}"/>
+            <cvParam cvRef="IMS" accession="IMS:1000051" name="position y" value="{y}"/>
+          </scan>
+        </scanList>
+        <binaryDataArrayList count="2">
+          <binaryDataArray encodedLength="0">
+            <referenceableParamGroupRef ref="mzArray"/>
+            <cvParam cvRef="IMS" accession="IMS:1000103" name="external array length" value="3"/>
+            <cvParam cvRef="IMS" accession="IMS:1000102" name="external offset" value="{mz_offset}"/>
+            <binary/>
+          </binaryDataArray>
+          <binaryDataArray encodedLength="0">
+            <referenceableParamGroupRef ref="intensityArray"/>
+            <cvParam cvRef="IMS" accession="IMS:1000103" name="external array length" value="3"/>
+            <cvParam cvRef="IMS" accession="IMS:1000102" name="external offset" value="{intensity_offset}"/>
+            <binary/>
+          </binaryDataArray>
+        </binaryDataArrayList>
+      </spectrum>"#));
+                index += 1;
+            }
+        }
+
+        let xml = format!(r#"<?xml version="1.0" encoding="ISO-8859-1"?>
+<mzML xmlns="http://psi.hupo.org/ms/mzml" version="1.1">
+  <referenceableParamGroupList count="2">
+    <referenceableParamGroup id="mzArray">
+      <cvParam cvRef="MS" accession="MS:1000514" name="m/z array"/>
+      <cvParam cvRef="MS" accession="MS:1000523" name="64-bit float"/>
+      <cvParam cvRef="IMS" accession="IMS:1000101" name="external data" value="true"/>
+    </referenceableParamGroup>
+    <referenceableParamGroup id="intensityArray">
+      <cvParam cvRef="MS" accession="MS:1000515" name="intensity array"/>
+      <cvParam cvRef="MS" accession="MS:1000521" name="32-bit float"/>
+      <cvParam cvRef="IMS" accession="IMS:1000101" name="external data" value="true"/>
+    </referenceableParamGroup>
+  </referenceableParamGroupList>
+  <scanSettingsList count="1">
+    <scanSettings id="scansettings1">
+      <cvParam cvRef="IMS" accession="IMS:1000042" name="max count of pixels x" value="3"/>
+      <cvParam cvRef="IMS" accession="IMS:1000043" name="max count of pixels y" value="2"/>
+    </scanSettings>
+  </scanSettingsList>
+  <run id="fixture">
+    <spectrumList count="6">{spectra}
+    </spectrumList>
+  </run>
+</mzML>
+"#);
+
+        let imzml = dir.join("fixture.imzML");
+        std::fs::write(&imzml, xml).unwrap();
+        std::fs::write(dir.join("fixture.ibd"), ibd).unwrap();
+        imzml.to_string_lossy().to_string()
+    }
+
+    /// 把 fixture 的 XML 中第一处 `from` 替换为 `to`
+    fn patch_fixture(path: &str, from: &str, to: &str) {
+        let xml = std::fs::read_to_string(path).unwrap();
+        assert!(xml.contains(from));
+        std::fs::write(path, xml.replacen(from, to, 1)).unwrap();
+    }
+
+    #[test]
+    fn test_load_imzml_and_extract_ion_image() {
+        let dir = std::env::temp_dir().join(format!("mz_curve_imzml_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = write_fixture(&dir);
+        let loaded = ImzmlLoader::load(&path);
+        // 离子图像只依赖容器中的像素索引与 ibd 文件，不再读取 XML
+        let _ = std::fs::remove_file(&path);
+        let image = loaded.as_ref().map(|c| extract_ion_image(c, 99.5, 100.5));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let container = loaded.unwrap();
+        assert_eq!(container.metadata["pixel_count"], 6);
+        assert_eq!(container.metadata["image_width"], 3);
+        assert_eq!(container.metadata["image_height"], 2);
+        assert_eq!(container.metadata["mz_min"], 100.0);
+        assert_eq!(container.metadata["mz_max"], 300.0);
+
+        let (_, pixels) = pixels_from_container(&container).unwrap();
+        assert_eq!(pixels.len(), 6);
+        assert_eq!((pixels[5].x, pixels[5].y), (3, 2));
+        assert_eq!(pixels[0].mz, IbdArray { offset: 16, length: 3, data_type: BinaryType::Float64 });
+        assert_eq!(pixels[0].intensity.data_type, BinaryType::Float32);
+        assert!(pixels.iter().all(|p| p.mz == pixels[0].mz));
+
+        let image = image.unwrap().unwrap();
+        assert_eq!((image.width, image.height), (3, 2));
+        assert_eq!(image.intensities.len(), 2);
+        assert!(image.intensities.iter().all(|row| row.len() == 3));
+        assert_eq!(image.intensities[1][2], 32.0);
+        assert_eq!(image.intensities[0][0], 11.0);
+
+        assert!(extract_ion_image(&container, 200.0, 100.0).is_err());
+        assert!(extract_ion_image(&DataContainer::new(), 99.5, 100.5).is_err());
+    }
+
+    #[test]
+    fn test_out_of_range_offsets_and_pixels_are_rejected() {
+        let dir = std::env::temp_dir().join(format!("mz_curve_imzml_invalid_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // 长度 × 8 字节溢出 usize
+        let path = write_fixture(&dir);
+        patch_fixture(&path, r#"name="external array length" value="3""#, &format!(r#"name="external array length" value="{}""#, usize::MAX / 4));
+        let overflow = ImzmlLoader::load(&path);
+
+        // 偏移量超出 ibd 文件末尾
+        let path = write_fixture(&dir);
+        patch_fixture(&path, r#"name="external offset" value="16""#, r#"name="external offset" value="1000000""#);
+        let past_end = ImzmlLoader::load(&path);
+
+        // 像素 x = 3 超出声明的宽度 2
+        let path = write_fixture(&dir);
+        patch_fixture(&path, r#"name="max count of pixels x" value="3""#, r#"name="max count of pixels x" value="2""#);
+        let outside = ImzmlLoader::load(&path);
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert!(overflow.unwrap_err().to_string().contains("超出ibd文件范围"));
+        assert!(past_end.unwrap_err().to_string().contains("超出ibd文件范围"));
+        assert!(outside.unwrap_err().to_string().contains("超出声明的图像尺寸"));
+    }
+}
@@ -0,0 +1,107 @@
+//! 索引读取器
+//!
+//! 基于文件内光谱偏移索引的随机访问读取器，按需解码单个光谱，
+//! 适合对同一文件进行大量随机查询（如按保留时间取光谱）而无需全部加载到内存
+
+use mzdata::prelude::*;
+use mzdata::MZReader;
+use mzdata::spectrum::Spectrum;
+use crate::core::data::ProcessingError;
+use std::fs::File;
+
+/// 带偏移索引的光谱读取器
+pub struct IndexedSpectrumReader {
+    path: String,
+    reader: MZReader<File>,
+}
+
+impl IndexedSpectrumReader {
+    /// 打开文件并建立光谱偏移索引
+    ///
+    /// indexedmzML 直接读取文件尾部的索引，否则扫描一次文件记录每个光谱的偏移
+    pub fn open(path: &str) -> Result<Self, ProcessingError> {
+        let reader = MZReader::open_path(path).map_err(|e| ProcessingError::MzDataError(e.to_string()))?;
+
+        if reader.is_empty() {
+            return Err(ProcessingError::DataError(format!("文件没有可用的光谱索引: {}", path)));
+        }
+
+        log::info!("🗂️ 已建立光谱索引: {} ({} 个光谱)", path, reader.len());
+
+        Ok(Self {
+            path: path.to_string(),
+            reader,
+        })
+    }
+
+    /// 文件路径
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// 光谱数量
+    pub fn len(&self) -> usize {
+        self.reader.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.reader.is_empty()
+    }
+
+    /// 按索引读取光谱，通过偏移直接定位
+    pub fn get_spectrum(&mut self, index: usize) -> Option<Spectrum> {
+        self.reader.get_spectrum_by_index(index)
+    }
+
+    /// 按原生ID读取光谱
+    pub fn get_spectrum_by_id(&mut self, id: &str) -> Option<Spectrum> {
+        self.reader.get_spectrum_by_id(id)
+    }
+
+    /// 读取保留时间最接近 `rt` 的光谱（按时间二分查找，假设光谱按时间排序）
+    pub fn get_spectrum_at_rt(&mut self, rt: f64) -> Option<Spectrum> {
+        self.reader.get_spectrum_by_time(rt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::loaders::mzdata_loader::DataLoader;
+    use crate::core::utils::test_fixtures::write_ms1_run;
+
+    /// 写出一个包含20个光谱的 indexedmzML 测试文件
+    fn write_fixture(path: &std::path::Path) {
+        write_ms1_run(path, 20, 0.1, |i| {
+            (0..5).map(|j| (100.0 + i as f64 + j as f64 * 0.5, (10 * i + j) as f32)).collect()
+        });
+    }
+
+    fn peak_pairs(spectrum: &Spectrum) -> Vec<(f64, f32)> {
+        spectrum.peaks().iter().map(|p| (p.mz(), p.intensity())).collect()
+    }
+
+    #[test]
+    fn test_indexed_access_matches_full_load() {
+        let path = std::env::temp_dir().join(format!("mz_curve_indexed_{}.mzML", std::process::id()));
+        write_fixture(&path);
+        let path_str = path.to_string_lossy().to_string();
+
+        let full = DataLoader::load_from_file(&path_str).unwrap();
+        let mut reader = DataLoader::open_indexed(&path_str).unwrap();
+        assert_eq!(reader.len(), full.spectra.len());
+
+        for index in [2, 11, 17] {
+            let indexed = reader.get_spectrum(index).unwrap();
+            let expected = &full.spectra[index];
+            assert_eq!(indexed.id(), expected.id());
+            assert_eq!(indexed.start_time(), expected.start_time());
+            assert_eq!(peak_pairs(&indexed), peak_pairs(expected));
+        }
+
+        let by_rt = reader.get_spectrum_at_rt(1.12).unwrap();
+        assert_eq!(by_rt.id(), "scan=12");
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
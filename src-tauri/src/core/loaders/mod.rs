@@ -1 +1,5 @@
 pub mod mzdata_loader;
+pub mod indexed_reader;
+pub mod tsv_importer;
+pub mod raw_converter;
+pub mod imzml_loader;
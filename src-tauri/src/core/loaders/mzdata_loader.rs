@@ -1,12 +1,44 @@
 use mzdata::prelude::*;
 use mzdata::MZReader;
-use mzdata::spectrum::Spectrum;
+use mzdata::spectrum::{ScanPolarity, SignalContinuity, Spectrum};
+use mzdata::mzpeaks::{CentroidPeak, MZPeakSetType};
 use crate::core::data::{DataContainer, ProcessingError};
+use crate::core::loaders::indexed_reader::IndexedSpectrumReader;
+use crate::core::loaders::raw_converter::{is_raw_path, RawConverter};
+use crate::core::loaders::imzml_loader::ImzmlLoader;
+use crate::core::loaders::tsv_importer::TsvImporter;
 use std::collections::HashMap;
 
 /// 进度回调函数类型
 pub type ProgressCallback = Box<dyn Fn(usize, usize, &str) + Send + Sync>;
 
+/// 仪器序列号的CV编号 (MS:1000529)，提取仪器型号时需跳过
+const INSTRUMENT_SERIAL_NUMBER_ACCESSION: u32 = 1000529;
+
+/// 扫描平均时合并m/z点的容差 (Da)
+const SCAN_AVERAGING_MZ_TOLERANCE: f64 = 0.001;
+
+/// 多文件合并时对应光谱允许的保留时间偏差 (min)
+const COMBINE_RT_TOLERANCE: f64 = 0.01;
+
+/// 快速验证时默认解码的光谱数
+pub const QUICK_VALIDATE_SAMPLE_SPECTRA: usize = 3;
+
+/// 快速验证结果
+#[derive(Debug, Clone)]
+pub struct QuickValidation {
+    /// 检测到的文件格式，如 "mzML"
+    pub format: String,
+    /// 文件是否经过gzip压缩
+    pub gzipped: bool,
+    /// 索引中的光谱数量
+    pub spectrum_count: usize,
+    /// 实际解码检查的光谱数量
+    pub parsed_spectra: usize,
+    /// 抽样光谱中出现的MS级别
+    pub ms_levels: Vec<u8>,
+}
+
 /// 数据加载器 - 支持进度报告
 pub struct DataLoader;
 
@@ -15,36 +47,100 @@ impl DataLoader {
     pub fn load_from_file_with_progress(
         path: &str, 
         progress_callback: Option<ProgressCallback>
+    ) -> Result<DataContainer, ProcessingError> {
+        Self::load_with_mode(path, false, progress_callback)
+    }
+    
+    /// 容错加载：无法解析的光谱被跳过而不是使整个文件失败
+    ///
+    /// 跳过的光谱数记录在元数据 `skipped_spectra` 中，其索引在 `skipped_spectrum_indices`，
+    /// 并在 `load_warnings` 中给出提示
+    pub fn load_from_file_tolerant(path: &str) -> Result<DataContainer, ProcessingError> {
+        Self::load_with_mode(path, true, None)
+    }
+    
+    /// 读取文件；`tolerant` 为 false 时任一光谱无法解析即返回错误
+    fn load_with_mode(
+        path: &str,
+        tolerant: bool,
+        progress_callback: Option<ProgressCallback>
     ) -> Result<DataContainer, ProcessingError> {
         log::info!("🚀 开始加载文件: {}", path);
         
+        // Thermo RAW 需要外部转换器
+        if is_raw_path(path) {
+            return Self::load_raw(path);
+        }
+        // imzML 的二进制数据在外部 ibd 文件中，mzdata 无法读取
+        if std::path::Path::new(path).extension().and_then(|ext| ext.to_str()).is_some_and(|ext| ext.eq_ignore_ascii_case("imzml")) {
+            return Self::load_imzml(path);
+        }
+        
         // 使用MZReader自动推断文件格式
-        let reader = MZReader::open_path(path).map_err(|e| ProcessingError::MzDataError(e.to_string()))?;
+        let mut reader = MZReader::open_path(path).map_err(|e| ProcessingError::MzDataError(e.to_string()))?;
         
         if let Some(ref callback) = progress_callback {
             callback(0, 0, "开始读取光谱数据...");
         }
         
         let mut container = DataContainer {
-            metadata: HashMap::new(),
+            metadata: Self::extract_file_metadata(&reader),
             spectra: Vec::new(),
             curves: Vec::new(),
         };
         
         let mut processed_count = 0;
         const PROGRESS_UPDATE_INTERVAL: usize = 100; // 每100个光谱更新一次进度
-        
-        // 直接收集 mzdata::Spectrum，无需转换
-        for spectrum in reader {
-            container.spectra.push(spectrum);
-            processed_count += 1;
-            
-            // 定期更新进度
+        let report_progress = |processed_count: usize| {
             if processed_count % PROGRESS_UPDATE_INTERVAL == 0 {
                 if let Some(ref callback) = progress_callback {
                     callback(processed_count, 0, &format!("已读取 {} 个光谱", processed_count));
                 }
             }
+        };
+        
+        // 索引中的光谱数；读取器在遇到无法解析的光谱时提前结束迭代，据此发现损坏的光谱
+        let indexed_count = reader.len();
+        let mut skipped_indices = Vec::new();
+        if tolerant && indexed_count > 0 {
+            // 按索引逐个读取，单个光谱解析失败不影响后续光谱
+            for index in 0..indexed_count {
+                match reader.get_spectrum_by_index(index) {
+                    Some(spectrum) => {
+                        container.spectra.push(spectrum);
+                        processed_count += 1;
+                        report_progress(processed_count);
+                    },
+                    None => {
+                        log::warn!("⚠️ 跳过无法解析的光谱: 索引 {}", index);
+                        skipped_indices.push(index);
+                    },
+                }
+            }
+        } else {
+            // 直接收集 mzdata::Spectrum，无需转换
+            for spectrum in reader {
+                container.spectra.push(spectrum);
+                processed_count += 1;
+                report_progress(processed_count);
+            }
+            if processed_count < indexed_count {
+                return Err(ProcessingError::DataError(format!(
+                    "索引 {} 处的光谱无法解析（共 {} 个光谱），可使用容错模式跳过损坏的光谱",
+                    processed_count, indexed_count
+                )));
+            }
+        }
+        
+        if tolerant {
+            container.metadata.insert("skipped_spectra".to_string(), serde_json::json!(skipped_indices.len()));
+            if !skipped_indices.is_empty() {
+                log::warn!("⚠️ 容错加载跳过 {} 个无法解析的光谱", skipped_indices.len());
+                container.metadata.insert("skipped_spectrum_indices".to_string(), serde_json::json!(skipped_indices));
+                container.metadata.insert("load_warnings".to_string(), serde_json::json!([format!(
+                    "{} 个光谱无法解析已跳过，其余 {} 个光谱正常加载", skipped_indices.len(), processed_count
+                )]));
+            }
         }
         
         // 最终进度更新
@@ -54,10 +150,25 @@ impl DataLoader {
         
         log::info!("✅ 文件加载完成: {} 个光谱", processed_count);
         
+        // 清理非有限强度值 (NaN/Inf)，避免污染后续求和与拟合
+        let non_finite_fixed = Self::sanitize_spectra(&mut container.spectra);
+        if non_finite_fixed > 0 {
+            log::warn!("⚠️ 发现并置零 {} 个非有限强度值 (NaN/Inf)", non_finite_fixed);
+        }
+        container.metadata.insert("non_finite_intensities_fixed".to_string(), serde_json::Value::Number(serde_json::Number::from(non_finite_fixed)));
+        
         // 添加基本元数据
         container.metadata.insert("file_path".to_string(), serde_json::Value::String(path.to_string()));
         container.metadata.insert("spectrum_count".to_string(), serde_json::Value::Number(serde_json::Number::from(processed_count)));
         
+        if let Some(polarity) = Self::detect_polarity(&container.spectra) {
+            container.metadata.insert("polarity".to_string(), serde_json::Value::String(polarity.to_string()));
+        }
+        let mut ms_levels: Vec<u8> = container.spectra.iter().map(|s| s.ms_level()).collect();
+        ms_levels.sort_unstable();
+        ms_levels.dedup();
+        container.metadata.insert("ms_levels".to_string(), serde_json::json!(ms_levels));
+        
         // 自动计算 RT 和 m/z 范围
         if !container.spectra.is_empty() {
             if let Some(ref callback) = progress_callback {
@@ -82,6 +193,327 @@ impl DataLoader {
     pub fn load_from_file(path: &str) -> Result<DataContainer, ProcessingError> {
         Self::load_from_file_with_progress(path, None)
     }
+
+    /// 加载 Thermo RAW 文件：调用外部转换器生成临时 mzML 后读取，完成后删除临时文件
+    ///
+    /// 转换器路径可通过环境变量 `MZ_CURVE_RAW_CONVERTER` 指定，否则在 PATH 中查找
+    pub fn load_raw(path: &str) -> Result<DataContainer, ProcessingError> {
+        Self::load_raw_with_converter(path, None)
+    }
+
+    /// 使用指定的转换器加载 Thermo RAW 文件
+    pub fn load_raw_with_converter(path: &str, converter: Option<&str>) -> Result<DataContainer, ProcessingError> {
+        let raw_path = std::path::Path::new(path);
+        if !raw_path.exists() {
+            return Err(ProcessingError::DataError(format!("文件不存在: {}", path)));
+        }
+        let converter = RawConverter::locate(converter)?;
+        log::info!("🔄 使用 {} 转换RAW文件: {}", converter.executable.display(), path);
+
+        let temp_dir = std::env::temp_dir().join(format!(
+            "mz_curve_raw_{}_{}",
+            std::process::id(),
+            std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0)
+        ));
+        std::fs::create_dir_all(&temp_dir)?;
+
+        let result = converter.convert(raw_path, &temp_dir)
+            .and_then(|mzml| Self::load_from_file(&mzml.to_string_lossy()));
+        if let Err(e) = std::fs::remove_dir_all(&temp_dir) {
+            log::warn!("⚠️ 无法删除RAW转换临时目录 {}: {}", temp_dir.display(), e);
+        }
+
+        let mut container = result?;
+        container.metadata.insert("file_path".to_string(), serde_json::Value::String(path.to_string()));
+        container.metadata.insert("source_format".to_string(), serde_json::Value::String("Thermo RAW".to_string()));
+        Ok(container)
+    }
+
+    /// 加载 imzML 成像数据（需同目录下的同名 .ibd 文件），像素坐标与光谱位于元数据 `pixels`
+    pub fn load_imzml(path: &str) -> Result<DataContainer, ProcessingError> {
+        ImzmlLoader::load(path)
+    }
+
+    /// 由文件名与文件头识别格式，返回 (格式名, 是否gzip压缩)
+    pub fn detect_format(path: &str) -> Result<(&'static str, bool), ProcessingError> {
+        let (format, gzipped) = mzdata::io::infer_format(path)?;
+        let format = match format {
+            mzdata::io::MassSpectrometryFormat::MzML => "mzML",
+            mzdata::io::MassSpectrometryFormat::MzMLb => "mzMLb",
+            mzdata::io::MassSpectrometryFormat::MGF => "MGF",
+            mzdata::io::MassSpectrometryFormat::ThermoRaw => "Thermo RAW",
+            mzdata::io::MassSpectrometryFormat::BrukerTDF => "Bruker TDF",
+            _ => return Err(ProcessingError::DataError(format!("无法识别的文件格式: {}", path))),
+        };
+        Ok((format, gzipped))
+    }
+
+    /// 快速验证文件：识别格式、读取光谱索引，并只解码前 `sample_spectra` 个光谱
+    ///
+    /// 不加载整个文件，适合在完整验证或加载之前快速判断文件是否可用
+    pub fn quick_validate(path: &str, sample_spectra: usize) -> Result<QuickValidation, ProcessingError> {
+        let (format, gzipped) = Self::detect_format(path)?;
+
+        let mut reader = MZReader::open_path(path).map_err(|e| ProcessingError::MzDataError(e.to_string()))?;
+        let spectrum_count = reader.len();
+        if spectrum_count == 0 {
+            return Err(ProcessingError::DataError(format!("文件中没有可读取的光谱: {}", path)));
+        }
+
+        let mut parsed_spectra = 0;
+        let mut ms_levels = Vec::new();
+        for spectrum in reader.by_ref().take(sample_spectra.max(1)) {
+            if spectrum.ms_level() == 0 {
+                return Err(ProcessingError::DataError(format!("光谱 {} 缺少MS级别", spectrum.id())));
+            }
+            if spectrum.peaks().iter().any(|p| !p.mz().is_finite()) {
+                return Err(ProcessingError::DataError(format!("光谱 {} 包含无效的m/z值", spectrum.id())));
+            }
+            ms_levels.push(spectrum.ms_level());
+            parsed_spectra += 1;
+        }
+        if parsed_spectra == 0 {
+            return Err(ProcessingError::DataError(format!("无法解码文件中的光谱: {}", path)));
+        }
+        ms_levels.sort_unstable();
+        ms_levels.dedup();
+
+        log::info!("⚡ 快速验证通过: {} ({}, {} 个光谱，解码 {} 个)", path, format, spectrum_count, parsed_spectra);
+        Ok(QuickValidation {
+            format: format.to_string(),
+            gzipped,
+            spectrum_count,
+            parsed_spectra,
+            ms_levels,
+        })
+    }
+
+    /// 以索引方式打开文件，支持按索引/保留时间随机访问单个光谱而不解码整个文件
+    pub fn open_indexed(path: &str) -> Result<IndexedSpectrumReader, ProcessingError> {
+        IndexedSpectrumReader::open(path)
+    }
+
+    /// 从 TsvExporter 导出的 combined TSV 文件重建曲线和峰（不含原始光谱）
+    pub fn load_from_tsv_export(path: &str) -> Result<DataContainer, ProcessingError> {
+        TsvImporter::import_file(path)
+    }
+
+    /// 将非有限强度 (NaN/Inf) 置零（保留数据点以维持m/z与强度数组对齐），返回修复的点数
+    pub fn sanitize_spectra(spectra: &mut [Spectrum]) -> usize {
+        spectra.iter_mut().map(Self::sanitize_spectrum).sum()
+    }
+
+    fn sanitize_spectrum(spectrum: &mut Spectrum) -> usize {
+        let mut fixed_in_arrays = 0;
+        let mut fixed_in_peaks = 0;
+        
+        if let Some(arrays) = spectrum.arrays.as_mut() {
+            // 仅在确有非有限值时才解码为可变数组，避免无谓地改变存储类型
+            let has_non_finite = arrays.intensities()
+                .map(|values| values.iter().any(|v| !v.is_finite()))
+                .unwrap_or(false);
+            if has_non_finite {
+                if let Ok(intensities) = arrays.intensities_mut() {
+                    for value in intensities.iter_mut().filter(|v| !v.is_finite()) {
+                        *value = 0.0;
+                        fixed_in_arrays += 1;
+                    }
+                }
+            }
+        }
+        
+        if let Some(peaks) = spectrum.peaks.as_mut() {
+            for peak in peaks.iter_mut().filter(|p| !p.intensity.is_finite()) {
+                peak.intensity = 0.0;
+                fixed_in_peaks += 1;
+            }
+        }
+        
+        // 质心光谱的峰列表与原始数组描述同一组数据点，不重复计数
+        fixed_in_arrays.max(fixed_in_peaks)
+    }
+
+    /// 加载文件并对同一MS级别的连续N个光谱做扫描平均
+    pub fn load_from_file_with_averaging(path: &str, scan_averaging: usize) -> Result<DataContainer, ProcessingError> {
+        let mut container = Self::load_from_file_with_progress(path, None)?;
+        Self::apply_scan_averaging(&mut container, scan_averaging);
+        Ok(container)
+    }
+
+    /// 对容器中的光谱做扫描平均，并在元数据中记录平均因子
+    pub fn apply_scan_averaging(container: &mut DataContainer, scan_averaging: usize) {
+        if scan_averaging <= 1 || container.spectra.is_empty() {
+            return;
+        }
+
+        let original_count = container.spectra.len();
+        container.spectra = Self::average_scans(&container.spectra, scan_averaging);
+
+        container.metadata.insert("scan_averaging".to_string(), serde_json::json!(scan_averaging));
+        container.metadata.insert("original_spectrum_count".to_string(), serde_json::json!(original_count));
+        container.metadata.insert("spectrum_count".to_string(), serde_json::json!(container.spectra.len()));
+
+        log::info!("📉 扫描平均: {} 个光谱 -> {} 个光谱 (因子 {})", original_count, container.spectra.len(), scan_averaging);
+    }
+
+    /// 扫描平均 - 将同一MS级别的连续N个光谱合并为一个平均光谱
+    ///
+    /// 以损失保留时间分辨率为代价提高信噪比，末尾不足N个的分组按实际数量平均。
+    /// 合并后的光谱以质心峰形式保存，保留时间取组内平均值。
+    pub fn average_scans(spectra: &[Spectrum], scan_averaging: usize) -> Vec<Spectrum> {
+        if scan_averaging <= 1 {
+            return spectra.to_vec();
+        }
+
+        let mut pending: HashMap<u8, Vec<&Spectrum>> = HashMap::new();
+        let mut averaged = Vec::with_capacity(spectra.len() / scan_averaging + 1);
+
+        for spectrum in spectra {
+            let group = pending.entry(spectrum.ms_level()).or_default();
+            group.push(spectrum);
+            if group.len() == scan_averaging {
+                averaged.push(Self::merge_spectra(group, group.len() as f64));
+                group.clear();
+            }
+        }
+
+        let mut remaining: Vec<Vec<&Spectrum>> = pending.into_values().filter(|g| !g.is_empty()).collect();
+        remaining.sort_by(|a, b| a[0].start_time().partial_cmp(&b[0].start_time()).unwrap_or(std::cmp::Ordering::Equal));
+        for group in remaining {
+            averaged.push(Self::merge_spectra(&group, group.len() as f64));
+        }
+
+        averaged.sort_by(|a, b| a.start_time().partial_cmp(&b.start_time()).unwrap_or(std::cmp::Ordering::Equal));
+        for (index, spectrum) in averaged.iter_mut().enumerate() {
+            spectrum.description.index = index;
+        }
+
+        averaged
+    }
+
+    /// 合并一组光谱：m/z在容差内的点合并，强度求和后除以 divisor（平均时为组大小，叠加时为 1）
+    fn merge_spectra(group: &[&Spectrum], divisor: f64) -> Spectrum {
+        let n = group.len() as f64;
+
+        let mut points: Vec<(f64, f64)> = group
+            .iter()
+            .flat_map(|s| s.peaks().iter().map(|p| (p.mz(), p.intensity() as f64)).collect::<Vec<_>>())
+            .collect();
+        points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        let mut merged: Vec<CentroidPeak> = Vec::new();
+        let mut i = 0;
+        while i < points.len() {
+            let start_mz = points[i].0;
+            let mut intensity_sum = 0.0;
+            let mut weighted_mz = 0.0;
+            let mut mz_sum = 0.0;
+            let mut count = 0;
+            while i < points.len() && points[i].0 - start_mz <= SCAN_AVERAGING_MZ_TOLERANCE {
+                intensity_sum += points[i].1;
+                weighted_mz += points[i].0 * points[i].1;
+                mz_sum += points[i].0;
+                count += 1;
+                i += 1;
+            }
+            let mz = if intensity_sum > 0.0 { weighted_mz / intensity_sum } else { mz_sum / count as f64 };
+            merged.push(CentroidPeak::new(mz, (intensity_sum / divisor) as f32, merged.len() as u32));
+        }
+
+        let mean_rt = group.iter().map(|s| s.start_time()).sum::<f64>() / n;
+
+        let mut description = group[0].description().clone();
+        description.signal_continuity = SignalContinuity::Centroid;
+        if let Some(scan) = description.acquisition.first_scan_mut() {
+            scan.start_time = mean_rt;
+        }
+
+        let mut spectrum = Spectrum::new(description, None, Some(MZPeakSetType::new(merged)), None);
+        spectrum.update_summaries();
+        spectrum
+    }
+    
+    /// 加载多个技术重复文件并叠加对应光谱的强度
+    ///
+    /// 各文件中同一MS级别的第k个光谱互相对应，保留时间偏差须在容差内，
+    /// 否则视为保留时间网格不兼容。合并后的元数据以第一个文件为准，并记录来源文件。
+    pub fn load_and_combine(paths: &[&str]) -> Result<DataContainer, ProcessingError> {
+        let (first_path, other_paths) = paths.split_first()
+            .ok_or_else(|| ProcessingError::ConfigError("未提供要合并的文件".to_string()))?;
+
+        log::info!("🧩 开始合并 {} 个文件的光谱", paths.len());
+        let reference = Self::load_from_file(first_path)?;
+        let others = other_paths.iter()
+            .map(|path| Self::load_from_file(path))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let by_level = |spectra: &[Spectrum]| -> HashMap<u8, Vec<usize>> {
+            let mut indices: HashMap<u8, Vec<usize>> = HashMap::new();
+            for (index, spectrum) in spectra.iter().enumerate() {
+                indices.entry(spectrum.ms_level()).or_default().push(index);
+            }
+            indices
+        };
+        let reference_levels = by_level(&reference.spectra);
+        let other_levels: Vec<HashMap<u8, Vec<usize>>> = others.iter().map(|c| by_level(&c.spectra)).collect();
+
+        // 校验保留时间网格
+        for ((path, container), levels) in other_paths.iter().zip(&others).zip(&other_levels) {
+            let mut level_keys: Vec<&u8> = reference_levels.keys().chain(levels.keys()).collect();
+            level_keys.sort_unstable();
+            level_keys.dedup();
+            for level in level_keys {
+                let expected = reference_levels.get(level).map_or(0, Vec::len);
+                let actual = levels.get(level).map_or(0, Vec::len);
+                if expected != actual {
+                    return Err(ProcessingError::DataError(format!(
+                        "保留时间网格不兼容: {} 有 {} 个 MS{} 光谱，{} 有 {} 个",
+                        first_path, expected, level, path, actual
+                    )));
+                }
+                for (&i, &j) in reference_levels[level].iter().zip(&levels[level]) {
+                    let (rt_ref, rt) = (reference.spectra[i].start_time(), container.spectra[j].start_time());
+                    if (rt_ref - rt).abs() > COMBINE_RT_TOLERANCE {
+                        return Err(ProcessingError::DataError(format!(
+                            "保留时间网格不兼容: {} 的 MS{} 光谱 RT {:.4} 与 {} 的 RT {:.4} 相差超过 {} min",
+                            path, level, rt, first_path, rt_ref, COMBINE_RT_TOLERANCE
+                        )));
+                    }
+                }
+            }
+        }
+
+        // 按参考文件的光谱顺序逐个叠加
+        let mut spectra = Vec::with_capacity(reference.spectra.len());
+        for (level, reference_indices) in &reference_levels {
+            for (k, &i) in reference_indices.iter().enumerate() {
+                let mut group = vec![&reference.spectra[i]];
+                group.extend(others.iter().zip(&other_levels).map(|(c, levels)| &c.spectra[levels[level][k]]));
+                let mut combined = Self::merge_spectra(&group, 1.0);
+                combined.description.index = i;
+                spectra.push(combined);
+            }
+        }
+        spectra.sort_by_key(|s| s.description.index);
+
+        let mut container = DataContainer {
+            metadata: reference.metadata,
+            spectra,
+            curves: Vec::new(),
+        };
+        let (rt_min, rt_max) = Self::calculate_rt_range(&container.spectra);
+        let (mz_min, mz_max) = Self::calculate_mz_range(&container.spectra);
+        container.metadata.insert("source_files".to_string(), serde_json::json!(paths));
+        container.metadata.insert("combined_file_count".to_string(), serde_json::json!(paths.len()));
+        container.metadata.insert("spectrum_count".to_string(), serde_json::json!(container.spectra.len()));
+        container.metadata.insert("rt_min".to_string(), serde_json::json!(rt_min));
+        container.metadata.insert("rt_max".to_string(), serde_json::json!(rt_max));
+        container.metadata.insert("mz_min".to_string(), serde_json::json!(mz_min));
+        container.metadata.insert("mz_max".to_string(), serde_json::json!(mz_max));
+
+        log::info!("✅ 合并完成: {} 个文件 -> {} 个光谱", paths.len(), container.spectra.len());
+        Ok(container)
+    }
     
     /// 过滤光谱数据 - 保留此函数，因为被其他模块使用
     pub fn filter_spectra(
@@ -130,6 +562,71 @@ impl DataLoader {
             .collect()
     }
     
+    /// 从文件头提取仪器型号、序列号与采集软件信息
+    ///
+    /// 只写入文件中实际存在的字段，缺失的字段不会以默认值出现在元数据中
+    fn extract_file_metadata<R: MSDataFileMetadata>(reader: &R) -> HashMap<String, serde_json::Value> {
+        let mut metadata = HashMap::new();
+        
+        // 按ID排序，保证多配置文件中结果稳定
+        let mut configs: Vec<_> = reader.instrument_configurations().values().collect();
+        configs.sort_by_key(|c| c.id);
+        
+        if let Some(config) = configs.first() {
+            let model = config.params.iter()
+                .find(|p| p.accession != Some(INSTRUMENT_SERIAL_NUMBER_ACCESSION) && !p.name.is_empty());
+            if let Some(model) = model {
+                metadata.insert("instrument_model".to_string(), serde_json::Value::String(model.name.clone()));
+            }
+            
+            let serial = config.params.iter()
+                .find(|p| p.accession == Some(INSTRUMENT_SERIAL_NUMBER_ACCESSION));
+            if let Some(serial) = serial {
+                metadata.insert("instrument_serial_number".to_string(), serde_json::Value::String(serial.value.to_string()));
+            }
+        }
+        
+        // 优先使用仪器配置引用的软件，其次是标记为采集软件的条目
+        let software_ref = configs.first().map(|c| c.software_reference.as_str()).unwrap_or("");
+        let software = reader.softwares().iter()
+            .find(|s| !software_ref.is_empty() && s.id == software_ref)
+            .or_else(|| reader.softwares().iter().find(|s| s.is_acquisition()));
+        
+        if let Some(software) = software {
+            let name = software.find_software_term()
+                .map(|term| term.name().to_string())
+                .or_else(|| software.params.first().map(|p| p.name.clone()))
+                .unwrap_or_else(|| software.id.clone());
+            metadata.insert("acquisition_software".to_string(), serde_json::Value::String(name));
+            if !software.version.is_empty() {
+                metadata.insert("acquisition_software_version".to_string(), serde_json::Value::String(software.version.clone()));
+            }
+        }
+        
+        metadata
+    }
+    
+    /// 根据光谱判断采集极性："positive"、"negative" 或 "mixed"，未知时返回 None
+    pub fn detect_polarity<'a>(spectra: impl IntoIterator<Item = &'a Spectrum>) -> Option<&'static str> {
+        let mut has_positive = false;
+        let mut has_negative = false;
+        
+        for spectrum in spectra {
+            match spectrum.polarity() {
+                ScanPolarity::Positive => has_positive = true,
+                ScanPolarity::Negative => has_negative = true,
+                ScanPolarity::Unknown => {}
+            }
+        }
+        
+        match (has_positive, has_negative) {
+            (true, true) => Some("mixed"),
+            (true, false) => Some("positive"),
+            (false, true) => Some("negative"),
+            (false, false) => None,
+        }
+    }
+    
     /// 计算保留时间范围
     fn calculate_rt_range(spectra: &[Spectrum]) -> (f64, f64) {
         if spectra.is_empty() {
@@ -169,3 +666,237 @@ impl DataLoader {
         (min_mz, max_mz)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::utils::test_fixtures::{corrupt_second_spectrum, ms1_spectrum, write_mzml};
+    use rand::{Rng, SeedableRng};
+
+    /// 构造平坦噪声光谱：所有光谱共享同一m/z网格，强度为 100 + 均匀噪声
+    fn flat_noise_spectra(count: usize, points: usize) -> Vec<Spectrum> {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+
+        (0..count)
+            .map(|i| {
+                let peaks: Vec<(f64, f32)> = (0..points)
+                    .map(|j| (100.0 + j as f64 * 0.1, (100.0 + rng.gen_range(-10.0..10.0)) as f32))
+                    .collect();
+                ms1_spectrum(i, i as f64 * 0.01, &peaks)
+            })
+            .collect()
+    }
+
+    fn intensity_variance(spectra: &[Spectrum]) -> f64 {
+        let values: Vec<f64> = spectra
+            .iter()
+            .flat_map(|s| s.peaks().iter().map(|p| p.intensity() as f64).collect::<Vec<_>>())
+            .collect();
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64
+    }
+
+    /// 带仪器信息的最小mzML文件
+    const INSTRUMENT_MZML: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<mzML xmlns="http://psi.hupo.org/ms/mzml" version="1.1.0">
+  <cvList count="2">
+    <cv id="MS" fullName="Proteomics Standards Initiative Mass Spectrometry Ontology" version="4.1.30" URI="https://raw.githubusercontent.com/HUPO-PSI/psi-ms-CV/master/psi-ms.obo"/>
+    <cv id="UO" fullName="Unit Ontology" URI="http://ontologies.berkeleybop.org/uo.obo"/>
+  </cvList>
+  <fileDescription>
+    <fileContent>
+      <cvParam cvRef="MS" accession="MS:1000579" name="MS1 spectrum" value=""/>
+    </fileContent>
+  </fileDescription>
+  <softwareList count="1">
+    <software id="Xcalibur" version="4.1.31.9">
+      <cvParam cvRef="MS" accession="MS:1000532" name="Xcalibur" value=""/>
+    </software>
+  </softwareList>
+  <instrumentConfigurationList count="1">
+    <instrumentConfiguration id="IC1">
+      <cvParam cvRef="MS" accession="MS:1001911" name="Q Exactive" value=""/>
+      <cvParam cvRef="MS" accession="MS:1000529" name="instrument serial number" value="Exactive Series slot #1"/>
+      <softwareRef ref="Xcalibur"/>
+    </instrumentConfiguration>
+  </instrumentConfigurationList>
+  <dataProcessingList count="1">
+    <dataProcessing id="pwiz_Reader_conversion">
+      <processingMethod order="0" softwareRef="Xcalibur">
+        <cvParam cvRef="MS" accession="MS:1000544" name="Conversion to mzML" value=""/>
+      </processingMethod>
+    </dataProcessing>
+  </dataProcessingList>
+  <run id="fixture" defaultInstrumentConfigurationRef="IC1">
+    <spectrumList count="1" defaultDataProcessingRef="pwiz_Reader_conversion">
+      <spectrum index="0" id="scan=1" defaultArrayLength="2">
+        <cvParam cvRef="MS" accession="MS:1000511" name="ms level" value="1"/>
+        <cvParam cvRef="MS" accession="MS:1000130" name="positive scan" value=""/>
+        <cvParam cvRef="MS" accession="MS:1000127" name="centroid spectrum" value=""/>
+        <scanList count="1">
+          <cvParam cvRef="MS" accession="MS:1000795" name="no combination" value=""/>
+          <scan>
+            <cvParam cvRef="MS" accession="MS:1000016" name="scan start time" value="0.5" unitCvRef="UO" unitAccession="UO:0000031" unitName="minute"/>
+          </scan>
+        </scanList>
+        <binaryDataArrayList count="2">
+          <binaryDataArray encodedLength="24">
+            <cvParam cvRef="MS" accession="MS:1000523" name="64-bit float" value=""/>
+            <cvParam cvRef="MS" accession="MS:1000576" name="no compression" value=""/>
+            <cvParam cvRef="MS" accession="MS:1000514" name="m/z array" value="" unitCvRef="MS" unitAccession="MS:1000040" unitName="m/z"/>
+            <binary>AAAAAAAAWUAAAAAAAMBiQA==</binary>
+          </binaryDataArray>
+          <binaryDataArray encodedLength="12">
+            <cvParam cvRef="MS" accession="MS:1000521" name="32-bit float" value=""/>
+            <cvParam cvRef="MS" accession="MS:1000576" name="no compression" value=""/>
+            <cvParam cvRef="MS" accession="MS:1000515" name="intensity array" value="" unitCvRef="MS" unitAccession="MS:1000131" unitName="number of detector counts"/>
+            <binary>AADIQgAASEM=</binary>
+          </binaryDataArray>
+        </binaryDataArrayList>
+      </spectrum>
+    </spectrumList>
+  </run>
+</mzML>
+"#;
+
+    #[test]
+    fn test_file_metadata_captures_instrument_model() {
+        let path = std::env::temp_dir().join(format!("mz_curve_metadata_{}.mzML", std::process::id()));
+        std::fs::write(&path, INSTRUMENT_MZML).unwrap();
+
+        let container = DataLoader::load_from_file(&path.to_string_lossy()).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(container.spectra.len(), 1);
+        assert_eq!(container.metadata.get("instrument_model").and_then(|v| v.as_str()), Some("Q Exactive"));
+        assert_eq!(container.metadata.get("acquisition_software").and_then(|v| v.as_str()), Some("Xcalibur"));
+        assert_eq!(container.metadata.get("polarity").and_then(|v| v.as_str()), Some("positive"));
+    }
+
+    #[test]
+    fn test_scan_averaging_reduces_noise_variance() {
+        let spectra = flat_noise_spectra(60, 200);
+        let averaged = DataLoader::average_scans(&spectra, 3);
+
+        assert_eq!(averaged.len(), 20);
+        assert_eq!(averaged[0].peaks().len(), 200);
+
+        let ratio = intensity_variance(&spectra) / intensity_variance(&averaged);
+        assert!(ratio > 2.5 && ratio < 3.5, "variance ratio {}", ratio);
+    }
+
+    #[test]
+    fn test_non_finite_intensities_are_sanitized() {
+        // 将第一个强度替换为 NaN：[NaN, 200.0]
+        let fixture = INSTRUMENT_MZML.replace("AADIQgAASEM=", "AADAfwAASEM=");
+        let path = std::env::temp_dir().join(format!("mz_curve_nan_{}.mzML", std::process::id()));
+        std::fs::write(&path, fixture).unwrap();
+
+        let container = DataLoader::load_from_file(&path.to_string_lossy());
+        let _ = std::fs::remove_file(&path);
+        let container = container.unwrap();
+
+        let tic: f64 = container.spectra.iter()
+            .flat_map(|s| s.peaks().iter().map(|p| p.intensity() as f64).collect::<Vec<_>>())
+            .sum();
+        assert!(tic.is_finite());
+        assert_eq!(tic, 200.0);
+        assert_eq!(container.metadata["non_finite_intensities_fixed"], serde_json::json!(1));
+    }
+
+    #[test]
+    fn test_combine_sums_matching_scans() {
+        let dir = std::env::temp_dir();
+        let paths: Vec<String> = (0..2)
+            .map(|i| dir.join(format!("mz_curve_combine_{}_{}.mzML", std::process::id(), i)).to_string_lossy().to_string())
+            .collect();
+        for path in &paths {
+            std::fs::write(path, INSTRUMENT_MZML).unwrap();
+        }
+        let shifted = dir.join(format!("mz_curve_combine_{}_shifted.mzML", std::process::id())).to_string_lossy().to_string();
+        std::fs::write(&shifted, INSTRUMENT_MZML.replace(r#"name="scan start time" value="0.5""#, r#"name="scan start time" value="0.9""#)).unwrap();
+
+        let combined = DataLoader::load_and_combine(&[&paths[0], &paths[1]]);
+        let incompatible = DataLoader::load_and_combine(&[&paths[0], &shifted]);
+        for path in paths.iter().chain([&shifted]) {
+            let _ = std::fs::remove_file(path);
+        }
+        let combined = combined.unwrap();
+
+        assert_eq!(combined.spectra.len(), 1);
+        let intensities: Vec<f32> = combined.spectra[0].peaks().iter().map(|p| p.intensity()).collect();
+        assert_eq!(intensities, vec![200.0, 400.0]);
+        assert_eq!(combined.spectra[0].start_time(), 0.5);
+        assert_eq!(combined.metadata["source_files"], serde_json::json!(paths));
+        assert_eq!(combined.metadata["combined_file_count"], serde_json::json!(2));
+
+        assert!(matches!(incompatible, Err(ProcessingError::DataError(_))));
+    }
+
+    #[test]
+    fn test_quick_validate_decodes_only_a_sample() {
+        let path = std::env::temp_dir().join(format!("mz_curve_quick_{}.mzML", std::process::id()));
+        write_mzml(&path, &flat_noise_spectra(20, 10));
+        let garbage = std::env::temp_dir().join(format!("mz_curve_quick_bad_{}.mzML", std::process::id()));
+        std::fs::write(&garbage, "not an mzML file").unwrap();
+
+        let path_str = path.to_string_lossy().to_string();
+        let quick = DataLoader::quick_validate(&path_str, QUICK_VALIDATE_SAMPLE_SPECTRA);
+        let full = DataLoader::load_from_file(&path_str);
+        let bad = DataLoader::quick_validate(&garbage.to_string_lossy(), QUICK_VALIDATE_SAMPLE_SPECTRA);
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&garbage);
+
+        let quick = quick.unwrap();
+        let full = full.unwrap();
+        assert_eq!(quick.format, "mzML");
+        assert_eq!(quick.spectrum_count, 20);
+        assert_eq!(quick.parsed_spectra, QUICK_VALIDATE_SAMPLE_SPECTRA);
+        assert_eq!(quick.ms_levels, vec![1]);
+        assert!(quick.parsed_spectra * 5 <= full.spectra.len());
+        assert!(bad.is_err());
+    }
+
+    #[test]
+    fn test_tolerant_load_skips_corrupt_spectrum() {
+        let path = std::env::temp_dir().join(format!("mz_curve_corrupt_{}.mzML", std::process::id()));
+        write_mzml(&path, &flat_noise_spectra(3, 10));
+        corrupt_second_spectrum(&path);
+
+        let path_str = path.to_string_lossy().to_string();
+        let strict = DataLoader::load_from_file(&path_str);
+        let tolerant = DataLoader::load_from_file_tolerant(&path_str);
+        let _ = std::fs::remove_file(&path);
+
+        assert!(matches!(strict, Err(ProcessingError::DataError(_))));
+        let tolerant = tolerant.unwrap();
+        let ids: Vec<&str> = tolerant.spectra.iter().map(|s| s.id()).collect();
+        assert_eq!(ids, vec!["scan=1", "scan=3"]);
+        assert_eq!(tolerant.metadata["skipped_spectra"], serde_json::json!(1));
+        assert_eq!(tolerant.metadata["skipped_spectrum_indices"], serde_json::json!([1]));
+        assert!(tolerant.metadata.contains_key("load_warnings"));
+    }
+
+    #[test]
+    fn test_load_raw_reports_missing_converter() {
+        let raw = std::env::temp_dir().join(format!("mz_curve_missing_converter_{}.raw", std::process::id()));
+        std::fs::write(&raw, b"raw").unwrap();
+        let result = DataLoader::load_raw_with_converter(&raw.to_string_lossy(), Some("/nonexistent/ThermoRawFileParser"));
+        let _ = std::fs::remove_file(&raw);
+
+        assert!(matches!(result, Err(ProcessingError::ConfigError(_))));
+    }
+
+    /// 需要本机安装转换器，并通过 `MZ_CURVE_RAW_FIXTURE` 指向一个 .raw 文件
+    #[test]
+    fn test_load_raw_fixture_when_converter_available() {
+        let fixture = match std::env::var("MZ_CURVE_RAW_FIXTURE") {
+            Ok(fixture) if RawConverter::locate(None).is_ok() => fixture,
+            _ => return,
+        };
+
+        let container = DataLoader::load_raw(&fixture).unwrap();
+        assert!(!container.spectra.is_empty());
+        assert_eq!(container.metadata["source_format"], "Thermo RAW");
+    }
+}
@@ -0,0 +1,123 @@
+//! Thermo RAW 转换
+//!
+//! 调用外部的 ThermoRawFileParser 或 ProteoWizard msconvert 把 `.raw` 转为临时 mzML，
+//! 再交给 mzdata 读取
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::core::data::ProcessingError;
+
+/// 指定转换器可执行文件路径的环境变量
+pub const RAW_CONVERTER_ENV: &str = "MZ_CURVE_RAW_CONVERTER";
+
+/// 未指定路径时在 PATH 中依次查找的转换器
+const CONVERTER_CANDIDATES: &[&str] = &[
+    "ThermoRawFileParser",
+    "ThermoRawFileParser.exe",
+    "msconvert",
+    "msconvert.exe",
+];
+
+/// 转换器类型，决定命令行参数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RawConverterKind {
+    ThermoRawFileParser,
+    MsConvert,
+}
+
+/// 可用的 RAW 转换器
+#[derive(Debug, Clone)]
+pub struct RawConverter {
+    pub executable: PathBuf,
+    pub kind: RawConverterKind,
+}
+
+impl RawConverter {
+    /// 使用指定的可执行文件，按文件名区分 msconvert 与 ThermoRawFileParser
+    pub fn new(executable: impl Into<PathBuf>) -> Self {
+        let executable = executable.into();
+        let name = executable.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        let kind = if name.starts_with("msconvert") {
+            RawConverterKind::MsConvert
+        } else {
+            RawConverterKind::ThermoRawFileParser
+        };
+        Self { executable, kind }
+    }
+
+    /// 查找转换器：显式路径优先，其次环境变量 `MZ_CURVE_RAW_CONVERTER`，最后搜索 PATH
+    pub fn locate(explicit: Option<&str>) -> Result<Self, ProcessingError> {
+        if let Some(path) = explicit.map(str::to_string).or_else(|| std::env::var(RAW_CONVERTER_ENV).ok()) {
+            let path = PathBuf::from(path);
+            if !path.is_file() {
+                return Err(ProcessingError::ConfigError(format!("RAW转换器不存在: {}", path.display())));
+            }
+            return Ok(Self::new(path));
+        }
+
+        let search_path = std::env::var_os("PATH").unwrap_or_default();
+        std::env::split_paths(&search_path)
+            .flat_map(|dir| CONVERTER_CANDIDATES.iter().map(move |name| dir.join(name)))
+            .find(|candidate| candidate.is_file())
+            .map(Self::new)
+            .ok_or_else(|| ProcessingError::ConfigError(format!(
+                "未找到Thermo RAW转换器：请安装 ThermoRawFileParser 或 msconvert，或通过环境变量 {} 指定路径",
+                RAW_CONVERTER_ENV
+            )))
+    }
+
+    /// 把 RAW 文件转换为 `output_dir` 中的 mzML，返回生成的文件路径
+    pub fn convert(&self, raw_path: &Path, output_dir: &Path) -> Result<PathBuf, ProcessingError> {
+        let stem = raw_path.file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or_else(|| ProcessingError::DataError(format!("无效的RAW文件名: {}", raw_path.display())))?;
+        let output = output_dir.join(format!("{}.mzML", stem));
+
+        let mut command = Command::new(&self.executable);
+        match self.kind {
+            RawConverterKind::ThermoRawFileParser => {
+                // -f=1 输出 mzML
+                command
+                    .arg(format!("-i={}", raw_path.display()))
+                    .arg(format!("-o={}", output_dir.display()))
+                    .arg("-f=1");
+            }
+            RawConverterKind::MsConvert => {
+                command
+                    .arg(raw_path)
+                    .arg("--mzML")
+                    .arg("-o")
+                    .arg(output_dir)
+                    .arg("--outfile")
+                    .arg(format!("{}.mzML", stem));
+            }
+        }
+
+        let result = command.output()
+            .map_err(|e| ProcessingError::ProcessError(format!("无法启动RAW转换器 {}: {}", self.executable.display(), e)))?;
+        if !result.status.success() {
+            return Err(ProcessingError::ProcessError(format!(
+                "RAW转换失败 ({}): {}",
+                result.status,
+                String::from_utf8_lossy(&result.stderr).trim()
+            )));
+        }
+        if !output.is_file() {
+            return Err(ProcessingError::ProcessError(format!("RAW转换器未生成mzML文件: {}", output.display())));
+        }
+
+        Ok(output)
+    }
+}
+
+/// 是否为 Thermo RAW 文件（按扩展名判断）
+pub fn is_raw_path(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("raw"))
+}
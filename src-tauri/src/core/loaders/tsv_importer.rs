@@ -0,0 +1,437 @@
+use std::collections::HashMap;
+
+use crate::core::data::{Curve, DataContainer, DetectionAlgorithm, Peak, PeakType, ProcessingError};
+
+/// TsvExporter combined 格式中的分节标记
+const CURVES_SECTION: &str = "# === CURVES ===";
+const PEAKS_SECTION: &str = "# === PEAKS ===";
+const FITTED_CURVES_SECTION: &str = "# === FITTED CURVES FOR VISUALIZATION ===";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Section {
+    None,
+    Curves,
+    Peaks,
+    FittedCurves,
+}
+
+/// 表格中的一行，按表头名称取值
+struct TsvRow<'a> {
+    columns: &'a HashMap<String, usize>,
+    fields: Vec<&'a str>,
+}
+
+impl<'a> TsvRow<'a> {
+    fn get(&self, name: &str) -> Option<&'a str> {
+        self.columns
+            .get(name)
+            .and_then(|&i| self.fields.get(i).copied())
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+    }
+
+    fn required(&self, name: &str) -> Result<&'a str, ProcessingError> {
+        self.get(name)
+            .ok_or_else(|| ProcessingError::DataError(format!("TSV行缺少必需列: {}", name)))
+    }
+
+    fn f64(&self, name: &str) -> Option<f64> {
+        self.get(name).and_then(|v| v.parse().ok())
+    }
+}
+
+/// TSV导入器 - 从 TsvExporter 导出的 combined 文件重建 DataContainer
+pub struct TsvImporter;
+
+impl TsvImporter {
+    /// 从文件导入
+    pub fn import_file(path: &str) -> Result<DataContainer, ProcessingError> {
+        let content = std::fs::read_to_string(path)?;
+        let mut container = Self::parse_combined(&content)?;
+        container.metadata.insert("file_path".to_string(), serde_json::Value::String(path.to_string()));
+        Ok(container)
+    }
+
+    /// 解析 combined 格式：曲线表、峰表，以及可选的原始数据点（拟合曲线分节中的 Original 行）
+    ///
+    /// 也接受 peaks_only / curves_only 导出的单表文件：没有分节标记时按表头识别表格，
+    /// 既没有分节标记也无法识别表头时返回 DataError
+    pub fn parse_combined(content: &str) -> Result<DataContainer, ProcessingError> {
+        let mut section = Section::None;
+        let mut found_table = false;
+        let mut columns: Option<HashMap<String, usize>> = None;
+
+        let mut curves: Vec<Curve> = Vec::new();
+        let mut peaks: Vec<Peak> = Vec::new();
+        let mut points: HashMap<String, (Vec<f64>, Vec<f64>)> = HashMap::new();
+
+        for line in content.lines() {
+            let line = line.trim_end_matches('\r');
+            if line.starts_with('#') {
+                let next = match line.trim() {
+                    CURVES_SECTION => Section::Curves,
+                    PEAKS_SECTION => Section::Peaks,
+                    FITTED_CURVES_SECTION => Section::FittedCurves,
+                    _ => continue,
+                };
+                section = next;
+                found_table = true;
+                columns = None;
+                continue;
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+            if section == Section::None {
+                if found_table {
+                    continue;
+                }
+                // 未分节的单表文件：第一行非注释内容即表头
+                section = Self::section_for_header(line).ok_or_else(|| ProcessingError::DataError(
+                    format!("TSV文件没有分节标记，也无法识别表头: {}", line)
+                ))?;
+                found_table = true;
+            }
+
+            // 每个分节的第一行为表头
+            let header = match &columns {
+                Some(header) => header,
+                None => {
+                    columns = Some(
+                        line.split('\t')
+                            .enumerate()
+                            .map(|(i, name)| (name.trim().to_string(), i))
+                            .collect(),
+                    );
+                    continue;
+                }
+            };
+
+            let row = TsvRow { columns: header, fields: line.split('\t').collect() };
+            match section {
+                Section::Curves => curves.push(Self::parse_curve(&row)?),
+                Section::Peaks => peaks.push(Self::parse_peak(&row)?),
+                Section::FittedCurves => {
+                    if row.get("Curve_Type") == Some("Original") {
+                        if let (Some(curve_id), Some(x), Some(y)) = (row.get("Curve_ID"), row.f64("X_Value"), row.f64("Y_Value")) {
+                            let entry = points.entry(curve_id.to_string()).or_default();
+                            entry.0.push(x);
+                            entry.1.push(y);
+                        }
+                    }
+                }
+                Section::None => {}
+            }
+        }
+
+        if !found_table {
+            return Err(ProcessingError::DataError("TSV文件中没有曲线表或峰表".to_string()));
+        }
+
+        // 恢复原始数据点；统计值沿用曲线表中的汇总列
+        for curve in curves.iter_mut() {
+            if let Some((x_values, y_values)) = points.remove(&curve.id) {
+                curve.point_count = x_values.len();
+                curve.x_values = x_values;
+                curve.y_values = y_values;
+            }
+        }
+
+        let mut container = DataContainer::new();
+        container.curves = curves;
+        for peak in peaks {
+            // 导出时未包含曲线表：为峰创建仅含ID的占位曲线
+            if !container.curves.iter().any(|c| c.id == peak.curve_id) {
+                let placeholder = Self::empty_curve(peak.curve_id.clone());
+                container.curves.push(placeholder);
+            }
+            let curve_id = peak.curve_id.clone();
+            container
+                .add_peak_to_curve(&curve_id, peak)
+                .map_err(ProcessingError::DataError)?;
+        }
+
+        log::info!("📥 TSV导入完成: {} 条曲线, {} 个峰", container.curves.len(), container.total_peak_count());
+        Ok(container)
+    }
+
+    /// 按表头识别 peaks_only / curves_only 导出的表格
+    fn section_for_header(line: &str) -> Option<Section> {
+        let mut names = line.split('\t').map(str::trim);
+        match (names.next(), names.next()) {
+            (Some("Peak_ID"), _) => Some(Section::Peaks),
+            (Some("Curve_ID"), Some("Curve_Type")) => Some(Section::Curves),
+            _ => None,
+        }
+    }
+
+    fn parse_curve(row: &TsvRow) -> Result<Curve, ProcessingError> {
+        let text = |name: &str| row.get(name).unwrap_or("").to_string();
+        let mut curve = Self::empty_curve(row.required("Curve_ID")?.to_string());
+        curve.curve_type = text("Curve_Type");
+        curve.x_label = text("X_Label");
+        curve.y_label = text("Y_Label");
+        curve.x_unit = text("X_Unit");
+        curve.y_unit = text("Y_Unit");
+        Self::apply_curve_summary(&mut curve, row);
+        Ok(curve)
+    }
+
+    /// 不含数据点的曲线；Curve::new 对空数据会得到 NaN/Inf 统计值，这里统一归零
+    fn empty_curve(id: String) -> Curve {
+        let mut curve = Curve::new(
+            id,
+            String::new(),
+            Vec::new(),
+            Vec::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+        );
+        curve.x_min = 0.0;
+        curve.y_min = 0.0;
+        curve.mean_intensity = 0.0;
+        curve.intensity_std = 0.0;
+        curve.baseline_intensity = 0.0;
+        curve.noise_level = 0.0;
+        curve.detection_threshold = 0.0;
+        curve
+    }
+
+    /// 用曲线表中的汇总列覆盖统计值；缺失的可选列保持默认
+    fn apply_curve_summary(curve: &mut Curve, row: &TsvRow) {
+        let range = |min: &str, max: &str| row.f64(min).zip(row.f64(max));
+
+        curve.x_min = row.f64("X_Min").unwrap_or(curve.x_min);
+        curve.x_max = row.f64("X_Max").unwrap_or(curve.x_max);
+        curve.y_min = row.f64("Y_Min").unwrap_or(curve.y_min);
+        curve.y_max = row.f64("Y_Max").unwrap_or(curve.y_max);
+        curve.point_count = row.get("Point_Count").and_then(|v| v.parse().ok()).unwrap_or(curve.point_count);
+        curve.total_ion_current = row.f64("Total_Ion_Current").unwrap_or(curve.total_ion_current);
+        curve.mean_intensity = row.f64("Mean_Intensity").unwrap_or(curve.mean_intensity);
+        curve.intensity_std = row.f64("Intensity_Std").unwrap_or(curve.intensity_std);
+        curve.baseline_intensity = row.f64("Baseline_Intensity").unwrap_or(curve.baseline_intensity);
+        curve.signal_to_noise_ratio = row.f64("Signal_to_Noise_Ratio").unwrap_or(curve.signal_to_noise_ratio);
+        curve.mz_range = range("MZ_Range_Min", "MZ_Range_Max");
+        curve.rt_range = range("RT_Range_Min", "RT_Range_Max");
+        curve.dt_range = range("DT_Range_Min", "DT_Range_Max");
+        curve.ms_level = row.get("MS_Level").and_then(|v| v.parse().ok());
+        curve.smoothing_factor = row.f64("Smoothing_Factor");
+        curve.baseline_correction = row.get("Baseline_Correction").map(str::to_string);
+        curve.noise_level = row.f64("Noise_Level").unwrap_or(curve.noise_level);
+        curve.detection_threshold = row.f64("Detection_Threshold").unwrap_or(curve.detection_threshold);
+        curve.quality_score = row.f64("Quality_Score").unwrap_or(curve.quality_score);
+        curve.completeness = row.f64("Completeness").unwrap_or(curve.completeness);
+        curve.has_missing_points = row.get("Has_Missing_Points") == Some("true");
+    }
+
+    fn parse_peak(row: &TsvRow) -> Result<Peak, ProcessingError> {
+        let center = row.required("Center")?;
+        let center: f64 = center
+            .parse()
+            .map_err(|_| ProcessingError::DataError(format!("无效的峰中心: {}", center)))?;
+
+        let mut peak = Peak::new(
+            row.required("Peak_ID")?.to_string(),
+            row.required("Curve_ID")?.to_string(),
+            center,
+            row.f64("Amplitude").unwrap_or(0.0),
+            row.get("Peak_Type").map(Self::parse_peak_type).unwrap_or(PeakType::Gaussian),
+        );
+        let value = |name: &str, default: f64| row.f64(name).unwrap_or(default);
+        let list = |name: &str| -> Vec<f64> {
+            row.get(name)
+                .map(|v| v.split(',').filter_map(|p| p.trim().parse().ok()).collect())
+                .unwrap_or_default()
+        };
+
+        peak.area = value("Area", 0.0);
+        peak.area_error = value("Area_Error", 0.0);
+        peak.area_unit = row.get("Area_Unit").filter(|v| !v.is_empty()).map(str::to_string);
+        peak.fwhm = value("FWHM", 0.0);
+        peak.hwhm = value("HWHM", peak.fwhm / 2.0);
+        peak.sigma = value("Sigma", 0.0);
+        peak.gamma = value("Gamma", 0.0);
+        peak.left_hwhm = value("Left_HWHM", peak.hwhm);
+        peak.right_hwhm = value("Right_HWHM", peak.hwhm);
+        peak.asymmetry_factor = value("Asymmetry_Factor", 1.0);
+        peak.tailing_factor_usp = value("Tailing_Factor_USP", 0.0);
+        if let Some(direction) = row.get("Shape_Direction") {
+            peak.add_metadata("shape_direction".to_string(), serde_json::json!(direction));
+        }
+        peak.left_boundary = value("Left_Boundary", center);
+        peak.right_boundary = value("Right_Boundary", center);
+        peak.peak_span = value("Peak_Span", peak.right_boundary - peak.left_boundary);
+        peak.rsquared = value("R_Squared", 0.0);
+        peak.residual_sum_squares = value("Residual_Sum_Squares", 0.0);
+        peak.standard_error = value("Standard_Error", 0.0);
+        peak.parameter_count = row.get("Parameter_Count").and_then(|v| v.parse().ok()).unwrap_or(0);
+        peak.mixing_parameter = value("Mixing_Parameter", 0.0);
+        peak.signal_to_baseline_ratio = value("Signal_to_Baseline_Ratio", 0.0);
+        peak.area_percentage = value("Area_Percentage", 0.0);
+        peak.intensity_percentage = value("Intensity_Percentage", 0.0);
+        peak.left_derivative = value("Left_Derivative", 0.0);
+        peak.right_derivative = value("Right_Derivative", 0.0);
+        peak.derivative_ratio = value("Derivative_Ratio", 0.0);
+        peak.set_ms_parameters(
+            row.f64("MZ"),
+            row.f64("Retention_Time"),
+            row.f64("Drift_Time"),
+            row.get("MS_Level").and_then(|v| v.parse().ok()),
+        );
+        peak.polarity = row.get("Polarity").map(str::to_string);
+        if let Some(algorithm) = row.get("Detection_Algorithm") {
+            peak.detection_algorithm = Self::parse_detection_algorithm(algorithm);
+        }
+        peak.detection_threshold = value("Detection_Threshold", 0.0);
+        peak.confidence = value("Confidence", 0.0);
+        peak.fit_parameters = list("Fit_Parameters");
+        peak.fit_parameter_errors = list("Fit_Parameter_Errors");
+        Ok(peak)
+    }
+
+    /// 与 TsvExporter::format_peak_type 互逆
+    fn parse_peak_type(value: &str) -> PeakType {
+        match value {
+            "Gaussian" => PeakType::Gaussian,
+            "Lorentzian" => PeakType::Lorentzian,
+            "PseudoVoigt" => PeakType::PseudoVoigt,
+            "AsymmetricGaussian" => PeakType::AsymmetricGaussian,
+            "EMG" => PeakType::EMG,
+            "BiGaussian" => PeakType::BiGaussian,
+            "VoigtExponentialTail" => PeakType::VoigtExponentialTail,
+            "PearsonIV" => PeakType::PearsonIV,
+            "PearsonVII" => PeakType::PearsonVII,
+            "NLC" => PeakType::NLC,
+            "GMGBayesian" => PeakType::GMGBayesian,
+            other => PeakType::Custom(
+                other
+                    .strip_prefix("Custom(")
+                    .and_then(|v| v.strip_suffix(')'))
+                    .unwrap_or(other)
+                    .to_string(),
+            ),
+        }
+    }
+
+    /// 与 TsvExporter::format_detection_algorithm 互逆
+    fn parse_detection_algorithm(value: &str) -> DetectionAlgorithm {
+        match value {
+            "CWT" => DetectionAlgorithm::CWT,
+            "PeakFinder" => DetectionAlgorithm::PeakFinder,
+            "Simple" => DetectionAlgorithm::Simple,
+            "SavitzkyGolay" => DetectionAlgorithm::SavitzkyGolay,
+            other => DetectionAlgorithm::Custom(
+                other
+                    .strip_prefix("Custom(")
+                    .and_then(|v| v.strip_suffix(')'))
+                    .unwrap_or(other)
+                    .to_string(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::exporters::{Exporter, TsvExporter};
+
+    fn sample_container() -> DataContainer {
+        let x: Vec<f64> = (0..40).map(|i| i as f64 * 0.25).collect();
+        let y: Vec<f64> = x.iter().map(|&v| 50.0 * (-0.5 * ((v - 4.0) / 0.6).powi(2)).exp() + 1.0).collect();
+        let mut curve = Curve::new(
+            "dt_curve".to_string(), "DT".to_string(), x, y,
+            "Drift Time".to_string(), "Intensity".to_string(), "ms".to_string(), "counts".to_string(),
+        );
+        curve.set_mz_range(500.0, 501.0);
+        curve.ms_level = Some(1);
+
+        let mut peak = Peak::new("peak_0".to_string(), "dt_curve".to_string(), 4.0, 50.0, PeakType::EMG);
+        peak.area = 75.2;
+        peak.fwhm = 1.41;
+        peak.sigma = 0.6;
+        peak.left_boundary = 2.5;
+        peak.right_boundary = 5.75;
+        peak.rsquared = 0.987;
+        peak.mz = Some(500.5);
+        peak.fit_parameters = vec![50.0, 4.0, 0.6, 0.1];
+        curve.add_peak(peak);
+        let mut shoulder = Peak::new("peak_1".to_string(), "dt_curve".to_string(), 7.5, 3.0, PeakType::Gaussian);
+        shoulder.sigma = 0.3;
+        curve.add_peak(shoulder);
+
+        let mut container = DataContainer::new();
+        container.curves.push(curve);
+        container
+    }
+
+    #[tokio::test]
+    async fn round_trips_combined_export() {
+        let original = sample_container();
+        let exported = TsvExporter.export(&original, serde_json::json!({})).await.unwrap();
+        let path = std::env::temp_dir().join(format!("mz_curve_roundtrip_{}.tsv", std::process::id()));
+        std::fs::write(&path, &exported.data).unwrap();
+
+        let imported = crate::core::loaders::mzdata_loader::DataLoader::load_from_tsv_export(&path.to_string_lossy());
+        let _ = std::fs::remove_file(&path);
+        let imported = imported.unwrap();
+
+        assert_eq!(imported.curves.len(), 1);
+        assert_eq!(imported.total_peak_count(), 2);
+
+        let curve = &imported.curves[0];
+        assert_eq!(curve.curve_type, "DT");
+        assert_eq!(curve.x_unit, "ms");
+        assert_eq!(curve.x_values.len(), 40);
+        assert_eq!(curve.mz_range, Some((500.0, 501.0)));
+        assert_eq!(curve.ms_level, Some(1));
+
+        let peak = &curve.peaks[0];
+        assert_eq!(peak.id, "peak_0");
+        assert_eq!(peak.peak_type, PeakType::EMG);
+        assert!((peak.center - 4.0).abs() < 1e-9);
+        assert!((peak.area - 75.2).abs() < 1e-6);
+        assert!((peak.right_boundary - 5.75).abs() < 1e-9);
+        assert!((peak.rsquared - 0.987).abs() < 1e-9);
+        assert_eq!(peak.mz, Some(500.5));
+        assert_eq!(peak.fit_parameters, vec![50.0, 4.0, 0.6, 0.1]);
+    }
+
+    #[test]
+    fn tolerates_missing_optional_columns() {
+        let content = "# === PEAKS ===\nPeak_ID\tCurve_ID\tCenter\tAmplitude\np1\tc1\t2.5\t10\n";
+        let container = TsvImporter::parse_combined(content).unwrap();
+
+        assert_eq!(container.curves.len(), 1);
+        let peak = &container.curves[0].peaks[0];
+        assert_eq!(peak.amplitude, 10.0);
+        assert_eq!(peak.peak_type, PeakType::Gaussian);
+        assert_eq!(peak.left_boundary, 2.5);
+        assert!(peak.mz.is_none());
+    }
+
+    #[tokio::test]
+    async fn imports_peaks_only_and_curves_only_exports() {
+        let original = sample_container();
+
+        let peaks_only = TsvExporter.export(&original, serde_json::json!({"export_format": "peaks_only"})).await.unwrap();
+        let container = TsvImporter::parse_combined(&String::from_utf8(peaks_only.data).unwrap()).unwrap();
+        assert_eq!(container.total_peak_count(), 2);
+        assert_eq!(container.curves[0].id, "dt_curve");
+        assert_eq!(container.curves[0].peaks[0].peak_type, PeakType::EMG);
+
+        let curves_only = TsvExporter.export(&original, serde_json::json!({"export_format": "curves_only"})).await.unwrap();
+        let container = TsvImporter::parse_combined(&String::from_utf8(curves_only.data).unwrap()).unwrap();
+        assert_eq!(container.curves.len(), 1);
+        assert_eq!(container.curves[0].curve_type, "DT");
+        assert_eq!(container.total_peak_count(), 0);
+    }
+
+    #[test]
+    fn rejects_unrecognised_tables() {
+        assert!(matches!(TsvImporter::parse_combined("Curve_ID\tX\tY_Raw\tY_Fit\nc1\t1.0\t2.0\t2.1\n"), Err(ProcessingError::DataError(_))));
+        assert!(matches!(TsvImporter::parse_combined("# only comments\n\n"), Err(ProcessingError::DataError(_))));
+    }
+}
@@ -0,0 +1,210 @@
+//! 色谱对齐
+//!
+//! 用动态时间规整（DTW）求样品曲线与参考曲线之间的规整路径，再把样品强度重采样到参考曲线的时间轴上，
+//! 用于校正线性平移无法处理的非线性RT漂移。规整限制在沿（按长度缩放的）对角线的 Sakoe-Chiba 带内，
+//! 计算量为 O(n·带宽)。两条曲线先各自做 z 标准化，使不同运行间的强度差异不影响路径
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::data::{Curve, ProcessingError};
+
+/// 未指定带宽时取较长曲线点数的比例
+const DEFAULT_BAND_FRACTION: f64 = 0.1;
+
+/// DTW 对齐结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DtwAlignment {
+    /// 重采样到参考时间轴上的样品曲线
+    pub curve: Curve,
+    /// 规整路径 (参考索引, 样品索引)，从 (0, 0) 单调到两条曲线的末点
+    pub warp_path: Vec<(usize, usize)>,
+    /// 实际使用的 Sakoe-Chiba 带宽（点数）
+    pub band: usize,
+    /// 路径上标准化强度差平方的累计代价
+    pub distance: f64,
+}
+
+/// 默认带宽：较长曲线点数的 10%，至少 1 点
+pub fn default_band(sample: &Curve, reference: &Curve) -> usize {
+    let n = sample.x_values.len().max(reference.x_values.len());
+    ((n as f64 * DEFAULT_BAND_FRACTION) as usize).max(1)
+}
+
+/// z 标准化；常数曲线只减去均值
+fn standardize(y: &[f64]) -> Vec<f64> {
+    let mean = y.iter().sum::<f64>() / y.len() as f64;
+    let std = (y.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / y.len() as f64).sqrt();
+    let scale = if std > 0.0 { std } else { 1.0 };
+    y.iter().map(|v| (v - mean) / scale).collect()
+}
+
+/// 带内的累计代价矩阵，每行只保存 [start, start + len) 列
+struct BandedMatrix {
+    starts: Vec<usize>,
+    rows: Vec<Vec<f64>>,
+}
+
+impl BandedMatrix {
+    fn get(&self, i: usize, j: usize) -> f64 {
+        let start = self.starts[i];
+        if j < start {
+            return f64::INFINITY;
+        }
+        self.rows[i].get(j - start).copied().unwrap_or(f64::INFINITY)
+    }
+}
+
+/// 把样品曲线按 DTW 规整到参考曲线的时间轴上，`band` 为 Sakoe-Chiba 带宽（点数）
+///
+/// 参考曲线的每个点取路径上与之匹配的样品点强度均值。样品上的峰不随曲线搬移，需在对齐后的曲线上重新检测
+pub fn dtw_align(sample: &Curve, reference: &Curve, band: usize) -> Result<DtwAlignment, ProcessingError> {
+    let m = sample.x_values.len().min(sample.y_values.len());
+    let n = reference.x_values.len().min(reference.y_values.len());
+    if m < 2 || n < 2 {
+        return Err(ProcessingError::DataError("对齐需要样品和参考曲线各至少 2 个数据点".to_string()));
+    }
+    if band == 0 {
+        return Err(ProcessingError::ConfigError("Sakoe-Chiba 带宽必须至少为 1".to_string()));
+    }
+    if sample.y_values[..m].iter().chain(&reference.y_values[..n]).any(|v| !v.is_finite()) {
+        return Err(ProcessingError::DataError("曲线强度包含非有限值".to_string()));
+    }
+
+    let a = standardize(&reference.y_values[..n]);
+    let b = standardize(&sample.y_values[..m]);
+
+    // 第 i 行的带以缩放对角线 i·(m-1)/(n-1) 为中心；带宽不小于相邻行中心的间距时路径必然存在
+    let mut matrix = BandedMatrix { starts: Vec::with_capacity(n), rows: Vec::with_capacity(n) };
+    for (i, &ai) in a.iter().enumerate() {
+        let center = (i * (m - 1) + (n - 1) / 2) / (n - 1);
+        let start = center.saturating_sub(band);
+        let end = (center + band).min(m - 1);
+        let mut row = Vec::with_capacity(end - start + 1);
+        for (k, &bj) in b[start..=end].iter().enumerate() {
+            let j = start + k;
+            let previous = if i == 0 && j == 0 {
+                0.0
+            } else {
+                let up = if i > 0 { matrix.get(i - 1, j) } else { f64::INFINITY };
+                let diagonal = if i > 0 && j > 0 { matrix.get(i - 1, j - 1) } else { f64::INFINITY };
+                let left = if k > 0 { row[k - 1] } else { f64::INFINITY };
+                up.min(diagonal).min(left)
+            };
+            row.push((ai - bj).powi(2) + previous);
+        }
+        matrix.starts.push(start);
+        matrix.rows.push(row);
+    }
+
+    let distance = matrix.get(n - 1, m - 1);
+    if !distance.is_finite() {
+        return Err(ProcessingError::ProcessError(format!("带宽 {} 内不存在规整路径", band)));
+    }
+
+    // 从末点回溯，代价相同时优先走对角线
+    let (mut i, mut j) = (n - 1, m - 1);
+    let mut warp_path = vec![(i, j)];
+    while i > 0 || j > 0 {
+        let diagonal = if i > 0 && j > 0 { matrix.get(i - 1, j - 1) } else { f64::INFINITY };
+        let up = if i > 0 { matrix.get(i - 1, j) } else { f64::INFINITY };
+        let left = if j > 0 { matrix.get(i, j - 1) } else { f64::INFINITY };
+        if diagonal <= up && diagonal <= left {
+            i -= 1;
+            j -= 1;
+        } else if up <= left {
+            i -= 1;
+        } else {
+            j -= 1;
+        }
+        warp_path.push((i, j));
+    }
+    warp_path.reverse();
+
+    let mut sums = vec![0.0; n];
+    let mut counts = vec![0usize; n];
+    for &(i, j) in &warp_path {
+        sums[i] += sample.y_values[j];
+        counts[i] += 1;
+    }
+    let warped: Vec<f64> = sums.iter().zip(&counts).map(|(sum, &count)| sum / count as f64).collect();
+
+    let mut curve = Curve::new(
+        sample.id.clone(),
+        sample.curve_type.clone(),
+        reference.x_values[..n].to_vec(),
+        warped,
+        sample.x_label.clone(),
+        sample.y_label.clone(),
+        sample.x_unit.clone(),
+        sample.y_unit.clone(),
+    );
+    curve.mz_range = sample.mz_range;
+    curve.rt_range = sample.rt_range;
+    curve.dt_range = sample.dt_range;
+    curve.ms_level = sample.ms_level;
+    curve.metadata = sample.metadata.clone();
+    curve.add_metadata("alignment_method".to_string(), serde_json::json!("dtw"));
+    curve.add_metadata("aligned_to".to_string(), serde_json::json!(reference.id));
+    curve.add_metadata("dtw_band".to_string(), serde_json::json!(band));
+
+    log::info!("🧭 DTW 对齐 {} -> {}: 带宽 {}, 路径长度 {}, 代价 {:.4}",
+        sample.id, reference.id, band, warp_path.len(), distance);
+
+    Ok(DtwAlignment { curve, warp_path, band, distance })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn three_peaks(t: f64) -> f64 {
+        [(3.0, 100.0), (6.0, 80.0), (9.0, 60.0)].iter()
+            .map(|&(center, amplitude)| amplitude * (-(t - center).powi(2) / (2.0 * 0.15 * 0.15)).exp())
+            .sum::<f64>() + 2.0
+    }
+
+    fn apex_near(x: &[f64], y: &[f64], center: f64) -> f64 {
+        x.iter().zip(y)
+            .filter(|(&xi, _)| (xi - center).abs() <= 1.0)
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(&xi, _)| xi)
+            .unwrap()
+    }
+
+    #[test]
+    fn nonlinear_drift_is_removed_by_warping() {
+        let x_values: Vec<f64> = (0..=600).map(|i| i as f64 * 0.02).collect();
+        let curve = |id: &str, y_values: Vec<f64>| Curve::new(
+            id.to_string(),
+            "TIC".to_string(),
+            x_values.clone(),
+            y_values,
+            "Retention Time".to_string(),
+            "Intensity".to_string(),
+            "min".to_string(),
+            "counts".to_string(),
+        );
+        let reference = curve("reference", x_values.iter().map(|&t| three_peaks(t)).collect());
+        // 非线性漂移：样品在 t 处的信号对应参考的 t - 0.4·sin(πt/12)，中段偏移最大，并带强度差异
+        let sample = curve("sample", x_values.iter()
+            .map(|&t| 1.5 * three_peaks(t - 0.4 * (std::f64::consts::PI * t / 12.0).sin()))
+            .collect());
+
+        let drifted = apex_near(&sample.x_values, &sample.y_values, 6.0);
+        assert!((drifted - 6.0).abs() > 0.3, "sample apex {}", drifted);
+
+        let alignment = dtw_align(&sample, &reference, 40).unwrap();
+        assert_eq!(alignment.warp_path.first(), Some(&(0, 0)));
+        assert_eq!(alignment.warp_path.last(), Some(&(600, 600)));
+        assert!(alignment.warp_path.windows(2).all(|w| w[1].0 >= w[0].0 && w[1].1 >= w[0].1));
+        assert_eq!(alignment.curve.x_values, reference.x_values);
+        assert_eq!(alignment.curve.get_metadata("aligned_to"), Some(&serde_json::json!("reference")));
+
+        for center in [3.0, 6.0, 9.0] {
+            let apex = apex_near(&alignment.curve.x_values, &alignment.curve.y_values, center);
+            assert!((apex - center).abs() <= 0.04, "peak at {} aligned to {}", center, apex);
+        }
+
+        assert!(dtw_align(&sample, &reference, 0).is_err());
+    }
+}
@@ -10,6 +10,9 @@ pub struct BaselineConfig {
     pub preserve_original: bool,
     /// 是否输出基线数据
     pub output_baseline: bool,
+    /// 扣除基线后负强度的处理方式
+    #[serde(default)]
+    pub negative_handling: NegativeHandling,
     /// 自定义参数
     pub custom_params: std::collections::HashMap<String, serde_json::Value>,
 }
@@ -20,6 +23,7 @@ impl Default for BaselineConfig {
             method: BaselineMethod::Linear,
             preserve_original: true,
             output_baseline: false,
+            negative_handling: NegativeHandling::default(),
             custom_params: std::collections::HashMap::new(),
         }
     }
@@ -44,6 +48,54 @@ pub enum BaselineMethod {
     Manual { baseline_points: Vec<(f64, f64)> },
 }
 
+/// 扣除基线后负强度的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NegativeHandling {
+    /// 负值截断为零
+    #[default]
+    Clamp,
+    /// 保留负值
+    Keep,
+    /// 整条曲线上移，使最小值为零
+    Shift,
+}
+
+impl NegativeHandling {
+    /// 按名称创建（"clamp" / "keep" / "shift"）
+    pub fn from_name(name: &str) -> Result<Self, BaselineError> {
+        match name {
+            "clamp" => Ok(Self::Clamp),
+            "keep" => Ok(Self::Keep),
+            "shift" => Ok(Self::Shift),
+            _ => Err(BaselineError::InvalidConfig(format!("Unknown negative_handling: {}", name))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Clamp => "clamp",
+            Self::Keep => "keep",
+            Self::Shift => "shift",
+        }
+    }
+
+    /// 对扣除基线后的强度应用处理方式
+    pub fn apply(&self, mut values: Vec<f64>) -> Vec<f64> {
+        match self {
+            Self::Clamp => values.iter_mut().for_each(|v| *v = v.max(0.0)),
+            Self::Keep => {}
+            Self::Shift => {
+                let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+                if min < 0.0 {
+                    values.iter_mut().for_each(|v| *v -= min);
+                }
+            }
+        }
+        values
+    }
+}
+
 /// 基线校准结果
 #[derive(Debug, Clone)]
 pub struct BaselineResult {
@@ -145,6 +197,27 @@ impl BaselineUtils {
         minima
     }
     
+    /// 标记无信号的点
+    ///
+    /// 对强度做窗口为 `window_size` 点的形态学开运算（先取窗口最小值再取窗口最大值），
+    /// 比窗口窄的峰被削去而平滑背景基本保持不变；原始强度高出开运算结果不超过
+    /// `threshold` 的点视为无信号
+    pub fn signal_free_mask(curve: &Curve, window_size: usize, threshold: f64) -> Vec<bool> {
+        let n = curve.y_values.len();
+        let half_window = window_size.max(1) / 2;
+        let window = |i: usize| i.saturating_sub(half_window)..(i + half_window + 1).min(n);
+
+        let eroded: Vec<f64> = (0..n)
+            .map(|i| curve.y_values[window(i)].iter().cloned().fold(f64::INFINITY, f64::min))
+            .collect();
+        (0..n)
+            .map(|i| {
+                let opened = eroded[window(i)].iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+                curve.y_values[i] - opened <= threshold
+            })
+            .collect()
+    }
+
     /// 线性插值
     pub fn linear_interpolation(
         x_values: &[f64],
@@ -2,11 +2,19 @@ use async_trait::async_trait;
 use serde_json::Value;
 use crate::core::data::{DataContainer, ProcessingResult, ProcessingError};
 use super::{
-    BaselineAlgorithm, BaselineConfig, BaselineMethod,
+    BaselineAlgorithm, BaselineConfig, BaselineMethod, BaselineResult, BaselineUtils, NegativeHandling,
     LinearBaselineCorrector, PolynomialBaselineCorrector, 
     MovingAverageBaselineCorrector, AsymmetricLeastSquaresCorrector
 };
+use crate::core::data::Curve;
 use crate::core::processors::base::Processor;
+use crate::core::processors::peak_detection::estimate_noise_floor;
+
+/// "auto" 方法依次尝试的候选方法
+const AUTO_CANDIDATES: &[&str] = &["linear", "polynomial", "moving_average", "asymmetric_least_squares"];
+
+/// 非对称最小二乘使用稠密矩阵求解，点数超过该值的曲线在自动选择时跳过
+const AUTO_ASLS_MAX_POINTS: usize = 500;
 
 /// 基线校准处理器
 pub struct BaselineProcessor {
@@ -73,6 +81,8 @@ impl BaselineProcessor {
             .and_then(|v| v.as_bool())
             .unwrap_or(false);
         
+        let negative_handling = Self::negative_handling_from_config(config)?;
+        
         let mut custom_params = std::collections::HashMap::new();
         if let Some(params) = config.get("custom_params") {
             if let Some(obj) = params.as_object() {
@@ -86,10 +96,115 @@ impl BaselineProcessor {
             method,
             preserve_original,
             output_baseline,
+            negative_handling,
             custom_params,
         })
     }
     
+    /// 解析 `negative_handling`，默认截断为零
+    fn negative_handling_from_config(config: &Value) -> Result<NegativeHandling, ProcessingError> {
+        match config.get("negative_handling").and_then(|v| v.as_str()) {
+            Some(name) => NegativeHandling::from_name(name)
+                .map_err(|e| ProcessingError::ConfigError(e.to_string())),
+            None => Ok(NegativeHandling::default()),
+        }
+    }
+    
+    /// 基线方法的描述名称，写入元数据
+    fn method_label(method: &BaselineMethod) -> String {
+        match method {
+            BaselineMethod::Linear => "linear".to_string(),
+            BaselineMethod::Polynomial { degree } => format!("polynomial_degree_{}", degree),
+            BaselineMethod::MovingAverage { window_size } => format!("moving_average_window_{}", window_size),
+            BaselineMethod::AsymmetricLeastSquares { lambda, p, .. } => {
+                format!("asymmetric_least_squares_lambda_{}_p_{}", lambda, p)
+            },
+            BaselineMethod::Manual { .. } => "manual".to_string(),
+        }
+    }
+    
+    /// 自动选择基线方法
+    ///
+    /// 用形态学开运算找出无信号区域（见 `BaselineUtils::signal_free_mask`），对每个候选方法计算
+    /// 这些区域内 原始强度 − 基线 的均方根，残差越小说明扣除后背景越平，选择最小者。
+    /// 其余参数（degree、window_size 等）沿用配置。返回所选方法的结果及记录各候选得分的元数据
+    fn correct_baseline_auto(&self, curve: &Curve, config: &Value) -> Result<(BaselineResult, Value), ProcessingError> {
+        let n = curve.y_values.len();
+        let window_size = config.get("auto_window")
+            .and_then(|v| v.as_u64())
+            .map(|w| w as usize)
+            .unwrap_or((n / 5).max(5));
+        let (y_min, y_max) = curve.y_values.iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &y| (lo.min(y), hi.max(y)));
+        let threshold = (3.0 * estimate_noise_floor(curve).noise).max(0.01 * (y_max - y_min));
+        let signal_free = BaselineUtils::signal_free_mask(curve, window_size, threshold);
+        let signal_free_points = signal_free.iter().filter(|&&free| free).count();
+        if signal_free_points == 0 {
+            return Err(ProcessingError::ProcessError(format!("曲线 {} 没有无信号区域，无法自动选择基线方法", curve.id)));
+        }
+        
+        let mut best: Option<(f64, String, BaselineResult)> = None;
+        let mut scores = serde_json::Map::new();
+        for &candidate in AUTO_CANDIDATES {
+            if candidate == "asymmetric_least_squares" && n > AUTO_ASLS_MAX_POINTS {
+                scores.insert(candidate.to_string(), serde_json::json!({"skipped": "too_many_points"}));
+                continue;
+            }
+            
+            let mut candidate_config = config.clone();
+            candidate_config["method"] = Value::String(candidate.to_string());
+            let mut baseline_config = self.create_baseline_config(&candidate_config)?;
+            let output_baseline = baseline_config.output_baseline;
+            baseline_config.output_baseline = true;
+            let label = Self::method_label(&baseline_config.method);
+            
+            let mut result = match self.select_algorithm(&baseline_config.method)
+                .and_then(|algorithm| algorithm.correct_baseline(curve, &baseline_config)
+                    .map_err(|e| ProcessingError::ProcessError(e.to_string())))
+            {
+                Ok(result) => result,
+                Err(e) => {
+                    log::debug!("基线候选方法 {} 失败: {}", candidate, e);
+                    scores.insert(candidate.to_string(), serde_json::json!({"error": e.to_string()}));
+                    continue;
+                }
+            };
+            
+            let baseline = match &result.baseline_curve {
+                Some(baseline_curve) => &baseline_curve.y_values,
+                None => continue,
+            };
+            let sum_squares: f64 = curve.y_values.iter()
+                .zip(baseline)
+                .zip(&signal_free)
+                .filter(|(_, free)| **free)
+                .map(|((y, b), _)| (y - b).powi(2))
+                .sum();
+            let score = (sum_squares / signal_free_points as f64).sqrt();
+            scores.insert(candidate.to_string(), serde_json::json!({"method": label, "residual_rms": score}));
+            
+            if !output_baseline {
+                result.baseline_curve = None;
+            }
+            if best.as_ref().is_none_or(|(best_score, _, _)| score < *best_score) {
+                best = Some((score, label, result));
+            }
+        }
+        
+        let (score, label, mut result) = best.ok_or_else(|| ProcessingError::ProcessError(
+            format!("曲线 {} 所有候选基线方法均失败", curve.id)
+        ))?;
+        log::info!("📉 曲线 {} 自动选择基线方法: {} (残差RMS={:.4})", curve.id, label, score);
+        result.corrected_curve.add_metadata("baseline_method".to_string(), Value::String(label.clone()));
+        
+        Ok((result, serde_json::json!({
+            "curve_id": curve.id,
+            "selected_method": label,
+            "signal_free_points": signal_free_points,
+            "scores": scores,
+        })))
+    }
+    
     /// 选择适当的算法
     fn select_algorithm(&self, method: &BaselineMethod) -> Result<&(dyn BaselineAlgorithm + Send + Sync), ProcessingError> {
         let algorithm_name = match method {
@@ -128,9 +243,14 @@ impl Processor for BaselineProcessor {
             "properties": {
                 "method": {
                     "type": "string",
-                    "enum": ["linear", "polynomial", "moving_average", "asymmetric_least_squares"],
+                    "enum": ["auto", "linear", "polynomial", "moving_average", "asymmetric_least_squares"],
                     "default": "linear",
-                    "description": "Baseline correction method to use"
+                    "description": "Baseline correction method to use; \"auto\" tries each method and keeps the one leaving the flattest residual in signal-free regions"
+                },
+                "auto_window": {
+                    "type": "integer",
+                    "minimum": 3,
+                    "description": "Opening window (points) used to find signal-free regions for the auto method; defaults to 1/5 of the curve length"
                 },
                 "degree": {
                     "type": "integer",
@@ -174,6 +294,12 @@ impl Processor for BaselineProcessor {
                     "default": false,
                     "description": "Whether to output baseline curve"
                 },
+                "negative_handling": {
+                    "type": "string",
+                    "enum": ["clamp", "keep", "shift"],
+                    "default": "clamp",
+                    "description": "Treatment of negative intensities after subtraction: clamp to zero, keep them, or shift the whole curve up so its minimum is zero"
+                },
                 "custom_params": {
                     "type": "object",
                     "description": "Custom parameters for specific algorithms"
@@ -188,21 +314,40 @@ impl Processor for BaselineProcessor {
         input: DataContainer,
         config: Value,
     ) -> Result<ProcessingResult, ProcessingError> {
-        // 创建基线配置
-        let baseline_config = self.create_baseline_config(&config)?;
+        // "auto" 对每条曲线分别选择方法
+        let auto = config.get("method").and_then(|v| v.as_str()) == Some("auto");
         
-        // 选择算法
-        let algorithm = self.select_algorithm(&baseline_config.method)?;
+        // 创建基线配置并选择算法
+        let fixed = if auto {
+            None
+        } else {
+            let baseline_config = self.create_baseline_config(&config)?;
+            let algorithm = self.select_algorithm(&baseline_config.method)?;
+            Some((baseline_config, algorithm))
+        };
+        let negative_handling = Self::negative_handling_from_config(&config)?;
+        let method_label = match &fixed {
+            Some((baseline_config, _)) => Self::method_label(&baseline_config.method),
+            None => "auto".to_string(),
+        };
         
         // 处理所有曲线
         let mut processed_curves = Vec::new();
         let mut baseline_curves = Vec::new();
         let mut processing_stats = Vec::new();
+        let mut auto_selections = Vec::new();
         
         for curve in &input.curves {
             // 执行基线校准
-            let result = algorithm.correct_baseline(curve, &baseline_config)
-                .map_err(|e| ProcessingError::ProcessError(e.to_string()))?;
+            let result = match &fixed {
+                Some((baseline_config, algorithm)) => algorithm.correct_baseline(curve, baseline_config)
+                    .map_err(|e| ProcessingError::ProcessError(e.to_string()))?,
+                None => {
+                    let (result, selection) = self.correct_baseline_auto(curve, &config)?;
+                    auto_selections.push(selection);
+                    result
+                }
+            };
             
             // 添加校准后的曲线
             processed_curves.push(result.corrected_curve);
@@ -236,15 +381,17 @@ impl Processor for BaselineProcessor {
         );
         output_container.metadata.insert(
             "baseline_correction_method".to_string(),
-            serde_json::Value::String(match baseline_config.method {
-                BaselineMethod::Linear => "linear".to_string(),
-                BaselineMethod::Polynomial { degree } => format!("polynomial_degree_{}", degree),
-                BaselineMethod::MovingAverage { window_size } => format!("moving_average_window_{}", window_size),
-                BaselineMethod::AsymmetricLeastSquares { lambda, p, .. } => {
-                    format!("asymmetric_least_squares_lambda_{}_p_{}", lambda, p)
-                },
-                BaselineMethod::Manual { .. } => "manual".to_string(),
-            })
+            serde_json::Value::String(method_label.clone())
+        );
+        if auto {
+            output_container.metadata.insert(
+                "baseline_auto_selection".to_string(),
+                serde_json::Value::Array(auto_selections)
+            );
+        }
+        output_container.metadata.insert(
+            "negative_handling".to_string(),
+            serde_json::Value::String(negative_handling.name().to_string())
         );
         output_container.metadata.insert(
             "baseline_correction_stats".to_string(),
@@ -259,15 +406,7 @@ impl Processor for BaselineProcessor {
         
         // 添加处理元数据
         result.add_metadata("processor".to_string(), serde_json::Value::String(self.name().to_string()));
-        result.add_metadata("method".to_string(), serde_json::Value::String(match baseline_config.method {
-            BaselineMethod::Linear => "linear".to_string(),
-            BaselineMethod::Polynomial { degree } => format!("polynomial_degree_{}", degree),
-            BaselineMethod::MovingAverage { window_size } => format!("moving_average_window_{}", window_size),
-            BaselineMethod::AsymmetricLeastSquares { lambda, p, .. } => {
-                format!("asymmetric_least_squares_lambda_{}_p_{}", lambda, p)
-            },
-            BaselineMethod::Manual { .. } => "manual".to_string(),
-        }));
+        result.add_metadata("method".to_string(), serde_json::Value::String(method_label));
         result.add_metadata("curves_processed".to_string(), serde_json::Value::Number(serde_json::Number::from(input.curves.len())));
         result.add_metadata("baseline_curves_generated".to_string(), serde_json::Value::Number(serde_json::Number::from(baseline_curves_count)));
         result.add_metadata("processing_stats".to_string(), serde_json::Value::Array(processing_stats));
@@ -309,3 +448,71 @@ pub async fn quick_baseline_correction(
     
     processor.process(input, config).await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+
+    #[tokio::test]
+    async fn auto_prefers_nonlinear_method_on_curved_background() {
+        // 抛物线背景上的两个高斯峰，叠加小幅确定性噪声
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let x_values: Vec<f64> = (0..101).map(|i| i as f64).collect();
+        let y_values: Vec<f64> = x_values
+            .iter()
+            .map(|&x| {
+                let noise = rng.gen_range(-0.1..0.1);
+                let background = 10.0 + 0.01 * (x - 50.0).powi(2);
+                let peaks = 50.0 * (-(x - 30.0).powi(2) / 8.0).exp() + 40.0 * (-(x - 70.0).powi(2) / 8.0).exp();
+                background + peaks + noise
+            })
+            .collect();
+        let curve = Curve::new(
+            "curved".to_string(), "DT".to_string(), x_values, y_values,
+            "Drift Time".to_string(), "Intensity".to_string(), "ms".to_string(), "counts".to_string(),
+        );
+        let input = DataContainer { metadata: std::collections::HashMap::new(), spectra: vec![], curves: vec![curve] };
+
+        let result = BaselineProcessor::new()
+            .process(input, serde_json::json!({"method": "auto"}))
+            .await
+            .unwrap();
+
+        assert_eq!(result.metadata["baseline_correction_method"], "auto");
+        let selection = &result.metadata["baseline_auto_selection"][0];
+        let selected = selection["selected_method"].as_str().unwrap();
+        assert_ne!(selected, "linear");
+        let score = |name: &str| selection["scores"][name]["residual_rms"].as_f64().unwrap();
+        assert!(score("linear") > score("polynomial"), "linear {} vs polynomial {}", score("linear"), score("polynomial"));
+        assert_eq!(result.curves.len(), 1);
+        assert_eq!(result.curves[0].metadata["baseline_method"], selected);
+    }
+
+    #[tokio::test]
+    async fn clamp_removes_negatives_and_keep_preserves_them() {
+        // 平坦基线上的单个尖峰：最小二乘直线被尖峰抬高，扣除后基线区域为负
+        let x_values: Vec<f64> = (0..41).map(|i| i as f64).collect();
+        let y_values: Vec<f64> = x_values.iter().map(|&x| if x == 20.0 { 100.0 } else { 5.0 }).collect();
+        let curve = Curve::new(
+            "spike".to_string(), "DT".to_string(), x_values, y_values,
+            "Drift Time".to_string(), "Intensity".to_string(), "ms".to_string(), "counts".to_string(),
+        );
+        let input = DataContainer { metadata: std::collections::HashMap::new(), spectra: vec![], curves: vec![curve] };
+        let processor = BaselineProcessor::new();
+
+        let clamped = processor.process(input.clone(), serde_json::json!({"method": "linear"})).await.unwrap();
+        assert_eq!(clamped.metadata["negative_handling"], "clamp");
+        assert!(clamped.curves[0].y_values.iter().all(|&y| y >= 0.0));
+
+        let kept = processor.process(input.clone(), serde_json::json!({"method": "linear", "negative_handling": "keep"})).await.unwrap();
+        assert_eq!(kept.metadata["negative_handling"], "keep");
+        assert!(kept.curves[0].y_values.iter().any(|&y| y < 0.0));
+
+        let shifted = processor.process(input.clone(), serde_json::json!({"method": "linear", "negative_handling": "shift"})).await.unwrap();
+        let shifted_min = shifted.curves[0].y_values.iter().cloned().fold(f64::INFINITY, f64::min);
+        assert!(shifted_min.abs() < 1e-9);
+
+        assert!(processor.process(input, serde_json::json!({"method": "linear", "negative_handling": "abs"})).await.is_err());
+    }
+}
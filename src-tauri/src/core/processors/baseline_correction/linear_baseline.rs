@@ -80,10 +80,10 @@ impl BaselineAlgorithm for LinearBaselineCorrector {
         let baseline_values = self.calculate_linear_baseline(curve)?;
         
         // 计算校准后的数据
-        let corrected_y_values: Vec<f64> = curve.y_values.iter()
+        let corrected_y_values = config.negative_handling.apply(curve.y_values.iter()
             .zip(baseline_values.iter())
-            .map(|(original, baseline)| (original - baseline).max(0.0))
-            .collect();
+            .map(|(original, baseline)| original - baseline)
+            .collect());
         
         // 创建校准后的曲线
         let mut corrected_curve = curve.clone();
@@ -0,0 +1,247 @@
+//! 定量校准曲线
+//!
+//! 由一组标准品的 (浓度, 峰面积) 拟合响应曲线 面积 = c0 + c1·x (+ c2·x²)，给出 R²、LOD、LOQ，
+//! 并由样品峰面积反算浓度。LOD/LOQ 按 ICH Q2 取 3.3σ/S 与 10σ/S，σ 为回归残差标准差，S 为零浓度处的斜率
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::data::ProcessingError;
+
+/// 校准模型
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CalibrationModel {
+    Linear,
+    Quadratic,
+    /// 1/x² 加权的线性回归，低浓度点的相对误差权重更高
+    WeightedLinear,
+}
+
+impl CalibrationModel {
+    pub fn from_name(name: &str) -> Result<Self, ProcessingError> {
+        match name {
+            "linear" => Ok(Self::Linear),
+            "quadratic" => Ok(Self::Quadratic),
+            "weighted_linear" => Ok(Self::WeightedLinear),
+            other => Err(ProcessingError::ConfigError(format!(
+                "未知的校准模型: {}（可选 linear、quadratic、weighted_linear）", other))),
+        }
+    }
+
+    fn degree(&self) -> usize {
+        match self {
+            Self::Quadratic => 2,
+            Self::Linear | Self::WeightedLinear => 1,
+        }
+    }
+}
+
+/// 拟合得到的校准曲线
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalibrationCurve {
+    pub model: CalibrationModel,
+    /// 多项式系数 [c0, c1, (c2)]，面积 = Σ ci·浓度^i
+    pub coefficients: Vec<f64>,
+    pub r_squared: f64,
+    /// 回归残差标准差（自由度 n - 参数个数）
+    pub residual_std: f64,
+    pub lod: f64,
+    pub loq: f64,
+    pub point_count: usize,
+    /// 标准品的浓度范围
+    pub concentration_range: (f64, f64),
+}
+
+/// 加权多项式最小二乘：求解正规方程 (XᵀWX)c = XᵀWy
+fn weighted_polyfit(x: &[f64], y: &[f64], w: &[f64], degree: usize) -> Result<Vec<f64>, ProcessingError> {
+    let size = degree + 1;
+    let mut matrix = vec![vec![0.0; size + 1]; size];
+    for ((&xi, &yi), &wi) in x.iter().zip(y).zip(w) {
+        for (row, equation) in matrix.iter_mut().enumerate() {
+            for (col, value) in equation[..size].iter_mut().enumerate() {
+                *value += wi * xi.powi((row + col) as i32);
+            }
+            equation[size] += wi * yi * xi.powi(row as i32);
+        }
+    }
+
+    // 列主元高斯消元
+    for col in 0..size {
+        let pivot = (col..size)
+            .max_by(|&a, &b| matrix[a][col].abs().total_cmp(&matrix[b][col].abs()))
+            .unwrap_or(col);
+        if matrix[pivot][col].abs() < 1e-12 {
+            return Err(ProcessingError::DataError("标准品浓度不足以确定校准曲线".to_string()));
+        }
+        matrix.swap(col, pivot);
+        for row in col + 1..size {
+            let factor = matrix[row][col] / matrix[col][col];
+            for k in col..=size {
+                matrix[row][k] -= factor * matrix[col][k];
+            }
+        }
+    }
+    let mut coefficients = vec![0.0; size];
+    for row in (0..size).rev() {
+        let known: f64 = (row + 1..size).map(|k| matrix[row][k] * coefficients[k]).sum();
+        coefficients[row] = (matrix[row][size] - known) / matrix[row][row];
+    }
+    Ok(coefficients)
+}
+
+fn evaluate(coefficients: &[f64], x: f64) -> f64 {
+    coefficients.iter().rev().fold(0.0, |acc, c| acc * x + c)
+}
+
+/// 拟合校准曲线，`points` 为 (浓度, 峰面积)
+pub fn fit_calibration_curve(points: &[(f64, f64)], model: CalibrationModel) -> Result<CalibrationCurve, ProcessingError> {
+    if points.iter().any(|&(x, y)| !x.is_finite() || !y.is_finite() || x < 0.0) {
+        return Err(ProcessingError::DataError("标准品浓度必须为非负有限值，面积必须为有限值".to_string()));
+    }
+    let parameter_count = model.degree() + 1;
+    if points.len() <= parameter_count {
+        return Err(ProcessingError::DataError(format!(
+            "{:?} 模型至少需要 {} 个标准品，当前 {} 个", model, parameter_count + 1, points.len())));
+    }
+    if model == CalibrationModel::WeightedLinear && points.iter().any(|&(x, _)| x == 0.0) {
+        return Err(ProcessingError::DataError("1/x² 加权模型不能包含零浓度标准品".to_string()));
+    }
+
+    let x: Vec<f64> = points.iter().map(|&(x, _)| x).collect();
+    let y: Vec<f64> = points.iter().map(|&(_, y)| y).collect();
+    let weights: Vec<f64> = match model {
+        CalibrationModel::WeightedLinear => x.iter().map(|xi| 1.0 / (xi * xi)).collect(),
+        _ => vec![1.0; x.len()],
+    };
+    let coefficients = weighted_polyfit(&x, &y, &weights, model.degree())?;
+
+    // R² 与残差标准差按权重计算，未加权模型即普通定义
+    let weight_sum: f64 = weights.iter().sum();
+    let y_mean = y.iter().zip(&weights).map(|(yi, wi)| yi * wi).sum::<f64>() / weight_sum;
+    let (ss_res, ss_tot) = x.iter().zip(&y).zip(&weights)
+        .fold((0.0, 0.0), |(ss_res, ss_tot), ((&xi, &yi), &wi)| {
+            (ss_res + wi * (yi - evaluate(&coefficients, xi)).powi(2), ss_tot + wi * (yi - y_mean).powi(2))
+        });
+    let r_squared = if ss_tot > 0.0 { 1.0 - ss_res / ss_tot } else { 0.0 };
+    let unweighted_ss_res: f64 = x.iter().zip(&y).map(|(&xi, &yi)| (yi - evaluate(&coefficients, xi)).powi(2)).sum();
+    let residual_std = (unweighted_ss_res / (points.len() - parameter_count) as f64).sqrt();
+
+    let slope = coefficients[1];
+    if slope <= 0.0 {
+        return Err(ProcessingError::DataError(format!("校准曲线在零浓度处的斜率非正: {}", slope)));
+    }
+    let (min, max) = x.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &xi| (min.min(xi), max.max(xi)));
+
+    Ok(CalibrationCurve {
+        model,
+        coefficients,
+        r_squared,
+        residual_std,
+        lod: 3.3 * residual_std / slope,
+        loq: 10.0 * residual_std / slope,
+        point_count: points.len(),
+        concentration_range: (min, max),
+    })
+}
+
+impl CalibrationCurve {
+    /// 由峰面积反算浓度
+    ///
+    /// 二次模型取落在非负浓度上的根中离标准品浓度范围最近的一个
+    pub fn concentration(&self, area: f64) -> Result<f64, ProcessingError> {
+        if !area.is_finite() {
+            return Err(ProcessingError::DataError(format!("无效的峰面积: {}", area)));
+        }
+        self.validate()?;
+        let c0 = self.coefficients[0];
+        let c1 = self.coefficients[1];
+        let c2 = self.coefficients.get(2).copied().unwrap_or(0.0);
+        if c2.abs() < 1e-15 {
+            return Ok((area - c0) / c1);
+        }
+
+        let discriminant = c1 * c1 - 4.0 * c2 * (c0 - area);
+        if discriminant < 0.0 {
+            return Err(ProcessingError::DataError(format!("峰面积 {} 超出二次校准曲线的响应范围", area)));
+        }
+        let root = discriminant.sqrt();
+        let (low, high) = self.concentration_range;
+        let distance = |x: f64| if x < low { low - x } else if x > high { x - high } else { 0.0 };
+        [(-c1 + root) / (2.0 * c2), (-c1 - root) / (2.0 * c2)]
+            .into_iter()
+            .filter(|&x| x >= 0.0)
+            .min_by(|a, b| distance(*a).total_cmp(&distance(*b)))
+            .ok_or_else(|| ProcessingError::DataError(format!("峰面积 {} 在二次校准曲线上没有非负浓度解", area)))
+    }
+
+    /// 校准曲线可能来自前端反序列化，反算前检查系数个数与模型一致、系数有限且斜率非零
+    fn validate(&self) -> Result<(), ProcessingError> {
+        let expected = self.model.degree() + 1;
+        if self.coefficients.len() != expected {
+            return Err(ProcessingError::DataError(format!(
+                "{:?} 校准曲线需要 {} 个系数，当前 {} 个", self.model, expected, self.coefficients.len())));
+        }
+        if self.coefficients.iter().any(|c| !c.is_finite()) {
+            return Err(ProcessingError::DataError(format!("校准曲线系数必须为有限值: {:?}", self.coefficients)));
+        }
+        if self.coefficients[1] == 0.0 {
+            return Err(ProcessingError::DataError("校准曲线的斜率为零，无法反算浓度".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear_standards_round_trip_through_back_calculation() {
+        // 面积 = 12 + 250·浓度，叠加小的确定性扰动
+        let concentrations = [0.5, 1.0, 2.0, 5.0, 10.0, 20.0, 50.0];
+        let points: Vec<(f64, f64)> = concentrations.iter()
+            .enumerate()
+            .map(|(i, &c)| (c, 12.0 + 250.0 * c + if i % 2 == 0 { 1.5 } else { -1.5 }))
+            .collect();
+
+        for model in [CalibrationModel::Linear, CalibrationModel::WeightedLinear, CalibrationModel::Quadratic] {
+            let curve = fit_calibration_curve(&points, model).unwrap();
+            assert!(curve.r_squared > 0.999, "{:?} R² {}", model, curve.r_squared);
+            assert!((curve.coefficients[1] - 250.0).abs() < 1.0, "{:?} slope {}", model, curve.coefficients[1]);
+            assert!(curve.lod > 0.0 && (curve.loq / curve.lod - 10.0 / 3.3).abs() < 1e-9);
+
+            for &c in &concentrations {
+                let back = curve.concentration(12.0 + 250.0 * c).unwrap();
+                assert!((back - c).abs() < 0.01, "{:?}: {} -> {}", model, c, back);
+            }
+        }
+
+        assert!(fit_calibration_curve(&points[..2], CalibrationModel::Linear).is_err());
+        assert!(CalibrationModel::from_name("cubic").is_err());
+    }
+
+    #[test]
+    fn malformed_curves_are_rejected_before_back_calculation() {
+        let curve = |model: CalibrationModel, coefficients: Vec<f64>| CalibrationCurve {
+            model,
+            coefficients,
+            r_squared: 1.0,
+            residual_std: 0.0,
+            lod: 0.0,
+            loq: 0.0,
+            point_count: 5,
+            concentration_range: (0.0, 10.0),
+        };
+
+        for malformed in [
+            curve(CalibrationModel::Linear, vec![]),
+            curve(CalibrationModel::Linear, vec![12.0]),
+            curve(CalibrationModel::Quadratic, vec![12.0, 250.0]),
+            curve(CalibrationModel::Linear, vec![12.0, 0.0]),
+            curve(CalibrationModel::Linear, vec![12.0, f64::NAN]),
+        ] {
+            assert!(matches!(malformed.concentration(500.0), Err(ProcessingError::DataError(_))), "{:?}", malformed.coefficients);
+        }
+        assert_eq!(curve(CalibrationModel::Linear, vec![12.0, 250.0]).concentration(512.0).unwrap(), 2.0);
+    }
+}
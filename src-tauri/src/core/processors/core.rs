@@ -21,6 +21,9 @@ pub enum ProcessorType {
     // 基线校正
     BaselineCorrection,
     
+    // 噪声降低
+    NoiseReduction,
+    
     // 峰检测
     PeakDetection,
     
@@ -109,6 +112,56 @@ pub trait Processor: Send + Sync {
     }
 }
 
+/// 超出配置模式范围的参数
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ConfigWarning {
+    pub parameter: String,
+    pub value: f64,
+    /// 调整后的值（模式给出的 minimum 或 maximum）
+    pub clamped_to: f64,
+    pub message: String,
+}
+
+/// 按配置模式 `properties` 中的 minimum/maximum 软校验数值参数
+///
+/// 超出范围的参数被截断到边界并返回警告，而不是报错；不在模式中或没有范围的参数保持不变
+pub fn clamp_config_to_schema(schema: &Value, config: &mut Value) -> Vec<ConfigWarning> {
+    let (Some(properties), Some(config)) = (schema["properties"].as_object(), config.as_object_mut()) else {
+        return Vec::new();
+    };
+
+    let mut warnings = Vec::new();
+    for (name, property) in properties {
+        let Some(value) = config.get(name).and_then(Value::as_f64) else {
+            continue;
+        };
+        let minimum = property["minimum"].as_f64();
+        let maximum = property["maximum"].as_f64();
+        let clamped = match (minimum, maximum) {
+            (Some(min), _) if value < min => min,
+            (_, Some(max)) if value > max => max,
+            _ => continue,
+        };
+        let is_integer = match &property["type"] {
+            Value::String(kind) => kind == "integer",
+            Value::Array(kinds) => kinds.iter().any(|kind| kind == "integer"),
+            _ => false,
+        };
+        let message = format!(
+            "参数 {} = {} 超出范围 [{}, {}]，已调整为 {}",
+            name,
+            value,
+            minimum.map_or("-∞".to_string(), |v| v.to_string()),
+            maximum.map_or("+∞".to_string(), |v| v.to_string()),
+            clamped,
+        );
+        log::warn!("⚠️ {}", message);
+        config.insert(name.clone(), if is_integer { serde_json::json!(clamped as i64) } else { serde_json::json!(clamped) });
+        warnings.push(ConfigWarning { parameter: name.clone(), value, clamped_to: clamped, message });
+    }
+    warnings
+}
+
 /// 处理器工厂
 pub struct ProcessorFactory;
 
@@ -132,6 +185,9 @@ impl ProcessorFactory {
                 // 暂时返回错误，因为BaselineProcessor没有实现Processor trait
                 Err(ProcessingError::ConfigError("BaselineProcessor暂时不可用".to_string()))
             },
+            ProcessorType::NoiseReduction => {
+                crate::core::processors::noise_reduction::NoiseReductionProcessor::create(&config.method)
+            },
             ProcessorType::PeakDetection => {
                 let detector = crate::core::processors::peak_detection::create_detector(&config.method)?;
                 Ok(std::sync::Arc::new(detector))
@@ -158,6 +214,7 @@ impl ProcessorFactory {
             ProcessorType::TICExtractor,
             ProcessorType::XICExtractor,
             ProcessorType::BaselineCorrection,
+            ProcessorType::NoiseReduction,
             ProcessorType::PeakDetection,
             ProcessorType::PeakFitting,
             ProcessorType::OverlappingPeaks,
@@ -0,0 +1,220 @@
+//! 曲线健康诊断
+//!
+//! 在峰分析之前汇总一条曲线是否适合分析：信噪比、基线漂移、饱和（削顶）、点密度和缺失点，
+//! 每项检查给出等级和可操作的建议，整体结论取最严重的一项
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::data::Curve;
+use crate::core::processors::peak_detection::estimate_noise_floor;
+
+/// 信噪比低于该值时判定为不可分析
+const SNR_FAIL: f64 = 3.0;
+/// 信噪比低于该值时给出警告
+const SNR_WARN: f64 = 10.0;
+/// 首尾基线差超过信号高度的该比例时给出警告
+const DRIFT_WARN: f64 = 0.1;
+/// 与最大值相同的点数达到该值时视为饱和
+const SATURATION_MIN_POINTS: usize = 3;
+/// 点数少于该值时给出警告
+const MIN_POINTS_WARN: usize = 20;
+/// 相邻点间距超过中位间距的该倍数时视为缺失点
+const GAP_FACTOR: f64 = 3.0;
+
+/// 诊断等级，按严重程度递增排序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthVerdict {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// 单项检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthCheck {
+    /// 检查项名称：snr / baseline_drift / saturation / point_density / missing_points
+    pub check: String,
+    pub verdict: HealthVerdict,
+    pub message: String,
+}
+
+/// 一条曲线的健康诊断报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurveHealthReport {
+    pub curve_id: String,
+    /// 整体结论，取各项检查中最严重的等级
+    pub verdict: HealthVerdict,
+    /// (最大值 - 基线) / 噪声；噪声为零时为 None
+    pub snr: Option<f64>,
+    /// 首尾各 10% 点的强度中位数之差，相对信号高度
+    pub baseline_drift: f64,
+    /// 与最大值相同的点数
+    pub saturated_points: usize,
+    pub point_count: usize,
+    /// 每单位X的点数
+    pub point_density: f64,
+    /// 非有限值的点数
+    pub non_finite_points: usize,
+    /// 间距超过中位间距 3 倍的区间数
+    pub gaps: usize,
+    pub checks: Vec<HealthCheck>,
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) { (values[mid - 1] + values[mid]) / 2.0 } else { values[mid] }
+}
+
+/// 诊断曲线是否适合峰分析
+pub fn diagnose_curve(curve: &Curve) -> CurveHealthReport {
+    let mut checks = Vec::new();
+    let mut push = |check: &str, verdict: HealthVerdict, message: String| {
+        checks.push(HealthCheck { check: check.to_string(), verdict, message });
+    };
+
+    // 缺失点：非有限值与X间距异常
+    let non_finite_points = curve.x_values.iter().zip(&curve.y_values)
+        .filter(|(x, y)| !x.is_finite() || !y.is_finite())
+        .count();
+    let (x, y): (Vec<f64>, Vec<f64>) = curve.x_values.iter().zip(&curve.y_values)
+        .filter(|(x, y)| x.is_finite() && y.is_finite())
+        .map(|(&x, &y)| (x, y))
+        .unzip();
+    let spacings: Vec<f64> = x.windows(2).map(|w| w[1] - w[0]).collect();
+    let median_spacing = median(&mut spacings.clone());
+    let gaps = if median_spacing > 0.0 {
+        spacings.iter().filter(|&&s| s > GAP_FACTOR * median_spacing).count()
+    } else {
+        0
+    };
+    let length_mismatch = curve.x_values.len() != curve.y_values.len();
+    if length_mismatch {
+        push("missing_points", HealthVerdict::Fail, format!(
+            "X/Y 点数不一致（{} / {}），请重新提取曲线", curve.x_values.len(), curve.y_values.len()));
+    } else if non_finite_points > 0 || gaps > 0 {
+        push("missing_points", HealthVerdict::Warn, format!(
+            "{} 个非有限值点，{} 处间距超过中位间距 {} 倍，建议检查数据采集或对缺失区间插值",
+            non_finite_points, gaps, GAP_FACTOR));
+    } else {
+        push("missing_points", HealthVerdict::Pass, "无缺失点".to_string());
+    }
+
+    // 点密度
+    let point_count = y.len();
+    let x_span = match (x.first(), x.last()) {
+        (Some(first), Some(last)) => last - first,
+        _ => 0.0,
+    };
+    let point_density = if x_span > 0.0 { point_count as f64 / x_span } else { 0.0 };
+    if point_count < 3 || x_span <= 0.0 {
+        push("point_density", HealthVerdict::Fail, format!("仅有 {} 个有效点，无法进行峰分析", point_count));
+    } else if point_count < MIN_POINTS_WARN {
+        push("point_density", HealthVerdict::Warn, format!(
+            "仅有 {} 个有效点（每单位X {:.2} 点），峰形拟合可能不可靠，建议缩小提取步长", point_count, point_density));
+    } else {
+        push("point_density", HealthVerdict::Pass, format!("{} 个有效点，每单位X {:.2} 点", point_count, point_density));
+    }
+
+    let finite_curve = Curve { x_values: x, y_values: y, ..curve.clone() };
+    let noise_floor = estimate_noise_floor(&finite_curve);
+    let y = &finite_curve.y_values;
+    let max = y.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let height = max - noise_floor.baseline;
+
+    // 信噪比
+    let snr = if noise_floor.noise > 0.0 && point_count > 0 { Some(height / noise_floor.noise) } else { None };
+    match snr {
+        Some(snr) if snr < SNR_FAIL => push("snr", HealthVerdict::Fail, format!(
+            "信噪比 {:.1} 低于 {}，信号无法与噪声区分，建议增大提取窗口或合并更多扫描", snr, SNR_FAIL)),
+        Some(snr) if snr < SNR_WARN => push("snr", HealthVerdict::Warn, format!(
+            "信噪比 {:.1} 低于 {}，建议先平滑再分析", snr, SNR_WARN)),
+        Some(snr) => push("snr", HealthVerdict::Pass, format!("信噪比 {:.1}", snr)),
+        None => push("snr", HealthVerdict::Pass, "噪声为零，无法估计信噪比".to_string()),
+    }
+
+    // 基线漂移：首尾各 10% 点的中位数之差相对信号高度
+    let edge = (point_count / 10).max(1);
+    let baseline_drift = if point_count > 0 && height > 0.0 {
+        let start = median(&mut y[..edge].to_vec());
+        let end = median(&mut y[point_count - edge..].to_vec());
+        (end - start).abs() / height
+    } else {
+        0.0
+    };
+    if baseline_drift > DRIFT_WARN {
+        push("baseline_drift", HealthVerdict::Warn, format!(
+            "首尾基线相差信号高度的 {:.0}%，建议先做基线校正", baseline_drift * 100.0));
+    } else {
+        push("baseline_drift", HealthVerdict::Pass, format!("基线漂移 {:.1}%", baseline_drift * 100.0));
+    }
+
+    // 饱和：多个点等于最大值说明检测器削顶
+    let saturated_points = if height > 0.0 { y.iter().filter(|&&v| v == max).count() } else { 0 };
+    if saturated_points >= SATURATION_MIN_POINTS {
+        push("saturation", HealthVerdict::Warn, format!(
+            "{} 个点处于最大强度 {:.4e}，疑似检测器饱和，峰高和面积会被低估，建议降低进样量或排除饱和峰",
+            saturated_points, max));
+    } else {
+        push("saturation", HealthVerdict::Pass, "未发现饱和".to_string());
+    }
+
+    let verdict = checks.iter().map(|c| c.verdict).max().unwrap_or(HealthVerdict::Pass);
+
+    CurveHealthReport {
+        curve_id: curve.id.clone(),
+        verdict,
+        snr,
+        baseline_drift,
+        saturated_points,
+        point_count,
+        point_density,
+        non_finite_points,
+        gaps,
+        checks,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn curve(y_values: Vec<f64>) -> Curve {
+        let x_values: Vec<f64> = (0..y_values.len()).map(|i| i as f64 * 0.1).collect();
+        Curve::new(
+            "health_curve".to_string(),
+            "DT".to_string(),
+            x_values,
+            y_values,
+            "Drift Time".to_string(),
+            "Intensity".to_string(),
+            "ms".to_string(),
+            "counts".to_string(),
+        )
+    }
+
+    #[test]
+    fn saturated_curve_raises_saturation_warning() {
+        // 高斯峰在 5000 处削顶
+        let y_values: Vec<f64> = (0..100)
+            .map(|i| {
+                let x = i as f64 * 0.1;
+                let noise = (i as f64 * 2.3).sin();
+                (10.0 + noise + 20000.0 * (-(x - 5.0).powi(2) / (2.0 * 0.5 * 0.5)).exp()).min(5000.0)
+            })
+            .collect();
+
+        let report = diagnose_curve(&curve(y_values));
+
+        assert!(report.saturated_points >= SATURATION_MIN_POINTS);
+        let saturation = report.checks.iter().find(|c| c.check == "saturation").unwrap();
+        assert_eq!(saturation.verdict, HealthVerdict::Warn);
+        assert!(report.verdict >= HealthVerdict::Warn);
+        assert_eq!(report.non_finite_points, 0);
+        assert_eq!(report.gaps, 0);
+    }
+}
@@ -3,10 +3,10 @@ use serde_json::Value;
 use std::collections::HashMap;
 use uuid::Uuid;
 
-use crate::core::data::{DataContainer, Curve, ProcessingError, ProcessingResult};
+use crate::core::data::{AxisLabels, DataContainer, Curve, ProcessingError, ProcessingResult};
 use crate::core::loaders::mzdata_loader::DataLoader;
 use crate::core::processors::base::Processor;
-use mzdata::prelude::{SpectrumLike, MZLocated, IntensityMeasurement};
+use crate::core::processors::projection::{project_spectra, ProjectionAxis, Reducer, SpectrumFilter};
 
 /// DT提取器 - 专门负责DT曲线数据提取，不进行峰值检测
 #[derive(Debug)]
@@ -65,15 +65,15 @@ impl Processor for DTExtractor {
         let (mz_min, mz_max) = parse_range(mz_range)?;
         let (rt_min, rt_max) = parse_range(rt_range)?;
 
+        // 请求的MS级别必须存在于文件中
+        input.ensure_ms_level(ms_level)?;
+
         // 过滤光谱
-        let filtered_spectra = DataLoader::filter_spectra(
-            &input.spectra,
-            Some(ms_level),
-            Some(rt_min),
-            Some(rt_max),
-            Some(mz_min),
-            Some(mz_max),
-        );
+        let filtered_spectra = SpectrumFilter {
+            ms_level: Some(ms_level),
+            rt_range: Some((rt_min, rt_max)),
+            mz_range: Some((mz_min, mz_max)),
+        }.select(&input.spectra);
 
         if filtered_spectra.is_empty() {
             return Err(ProcessingError::DataError(
@@ -82,7 +82,9 @@ impl Processor for DTExtractor {
         }
 
         // 生成DT曲线
-        let dt_curve = self.generate_dt_curve(&filtered_spectra, mz_min, mz_max)?;
+        let mut dt_curve = self.generate_dt_curve(&filtered_spectra, mz_min, mz_max)?;
+        AxisLabels::from_config(&config).apply(&mut dt_curve);
+        dt_curve.set_acquisition(ms_level, DataLoader::detect_polarity(filtered_spectra.iter().copied()));
 
         // 添加到数据容器
         input.curves.push(dt_curve.clone());
@@ -111,37 +113,17 @@ impl DTExtractor {
         mz_min: f64,
         mz_max: f64,
     ) -> Result<Curve, ProcessingError> {
-        let mut dt_data: HashMap<u64, f64> = HashMap::new();
-
-        for spectrum in spectra {
-            // 使用正确的API获取离子迁移率数据
-            if let Some(ion_mobility) = spectrum.ion_mobility() {
-                let dt_key = (ion_mobility * 1000.0) as u64; // 精确到毫秒
-
-                let peaks = spectrum.peaks();
-                
-                // 累加指定m/z范围内的强度
-                for peak in peaks.iter() {
-                    let mz = peak.mz();
-                    if mz >= mz_min && mz <= mz_max {
-                        *dt_data.entry(dt_key).or_insert(0.0) += peak.intensity() as f64;
-                    }
-                }
-            }
-        }
+        let filter = SpectrumFilter { mz_range: Some((mz_min, mz_max)), ..SpectrumFilter::default() };
+        let points = project_spectra(spectra, &filter, ProjectionAxis::drift_time(), Reducer::Sum);
 
-        if dt_data.is_empty() {
+        if points.is_empty() {
             return Err(ProcessingError::DataError(
                 "No ion mobility data found in the specified range".to_string(),
             ));
         }
 
-        // 排序并生成曲线数据
-        let mut sorted_data: Vec<(u64, f64)> = dt_data.into_iter().collect();
-        sorted_data.sort_by(|a, b| a.0.cmp(&b.0));
-        
-        let x_values: Vec<f64> = sorted_data.iter().map(|(k, _)| *k as f64 / 1000.0).collect();
-        let y_values: Vec<f64> = sorted_data.iter().map(|(_, v)| *v).collect();
+        let x_values: Vec<f64> = points.iter().map(|(x, _)| *x).collect();
+        let y_values: Vec<f64> = points.iter().map(|(_, y)| *y).collect();
 
         let mut curve = Curve::new(
             format!("dt_curve_{}", Uuid::new_v4()),
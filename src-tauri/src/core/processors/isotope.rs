@@ -0,0 +1,114 @@
+//! 同位素峰分组
+//!
+//! m/z 空间中相邻同位素峰相距约 1.00335/z Da（¹³C-¹²C 质量差）。按 m/z 从低到高取未分组的峰作为
+//! 单同位素峰候选，对每个候选电荷沿间距向高 m/z 连成链，取最长的链作为一个同位素包络
+
+use crate::core::data::{Peak, ProcessingError};
+
+/// ¹³C 与 ¹²C 的质量差 (Da)
+pub const ISOTOPE_SPACING: f64 = 1.003355;
+
+/// 峰的 m/z：优先取 `mz`，否则以峰中心为 m/z
+fn peak_mz(peak: &Peak) -> f64 {
+    peak.mz.unwrap_or(peak.center)
+}
+
+/// 把峰分组为同位素包络
+///
+/// 成组的峰在元数据中记录 `isotope_envelope_id`、`isotope_charge`、`isotope_index`（0 为单同位素峰）
+/// 和 `monoisotopic`；未能成组的峰原样返回。返回顺序与输入一致
+pub fn group_isotopes(peaks: &[Peak], charge_states: &[u8], mz_tolerance: f64) -> Result<Vec<Peak>, ProcessingError> {
+    if charge_states.is_empty() || charge_states.contains(&0) {
+        return Err(ProcessingError::ConfigError(format!("电荷态必须为正整数: {:?}", charge_states)));
+    }
+    if !mz_tolerance.is_finite() || mz_tolerance <= 0.0 {
+        return Err(ProcessingError::ConfigError(format!("m/z 容差必须为正数: {}", mz_tolerance)));
+    }
+
+    let mut order: Vec<usize> = (0..peaks.len()).collect();
+    order.sort_by(|&a, &b| peak_mz(&peaks[a]).total_cmp(&peak_mz(&peaks[b])));
+
+    let mut assigned = vec![false; peaks.len()];
+    let mut grouped = peaks.to_vec();
+    let mut envelope_count = 0;
+
+    for (position, &start) in order.iter().enumerate() {
+        if assigned[start] {
+            continue;
+        }
+
+        // 每个电荷沿间距连链，取最长的；等长时取较低电荷
+        let mut best: Option<(u8, Vec<usize>)> = None;
+        let mut charges = charge_states.to_vec();
+        charges.sort_unstable();
+        for charge in charges {
+            let spacing = ISOTOPE_SPACING / charge as f64;
+            let mut chain = vec![start];
+            let mut last_mz = peak_mz(&peaks[start]);
+            loop {
+                let expected = last_mz + spacing;
+                let next = order[position + 1..].iter()
+                    .copied()
+                    .filter(|&i| !assigned[i] && !chain.contains(&i))
+                    .filter(|&i| (peak_mz(&peaks[i]) - expected).abs() <= mz_tolerance)
+                    .min_by(|&a, &b| (peak_mz(&peaks[a]) - expected).abs().total_cmp(&(peak_mz(&peaks[b]) - expected).abs()));
+                match next {
+                    Some(i) => {
+                        last_mz = peak_mz(&peaks[i]);
+                        chain.push(i);
+                    },
+                    None => break,
+                }
+            }
+            if chain.len() > best.as_ref().map_or(1, |(_, c)| c.len()) {
+                best = Some((charge, chain));
+            }
+        }
+
+        let Some((charge, chain)) = best else {
+            continue;
+        };
+        let envelope_id = format!("isotope_envelope_{}", envelope_count);
+        envelope_count += 1;
+        for (isotope_index, &i) in chain.iter().enumerate() {
+            assigned[i] = true;
+            let peak = &mut grouped[i];
+            peak.add_metadata("isotope_envelope_id".to_string(), serde_json::json!(envelope_id));
+            peak.add_metadata("isotope_charge".to_string(), serde_json::json!(charge));
+            peak.add_metadata("isotope_index".to_string(), serde_json::json!(isotope_index));
+            peak.add_metadata("monoisotopic".to_string(), serde_json::json!(isotope_index == 0));
+        }
+    }
+
+    Ok(grouped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::data::PeakType;
+
+    #[test]
+    fn groups_charge_one_envelope_with_lowest_mz_monoisotopic() {
+        let mut peaks: Vec<Peak> = [(501.2545, 60.0), (500.2510, 100.0), (502.2580, 20.0), (507.9000, 50.0)]
+            .iter()
+            .enumerate()
+            .map(|(i, &(mz, amplitude))| {
+                Peak::new(format!("peak_{}", i), "ms".to_string(), mz, amplitude, PeakType::Gaussian)
+            })
+            .collect();
+        peaks[0].mz = Some(501.2545);
+
+        let grouped = group_isotopes(&peaks, &[1, 2, 3], 0.01).unwrap();
+
+        let envelope = grouped[1].get_metadata("isotope_envelope_id").unwrap().clone();
+        for i in [0, 1, 2] {
+            assert_eq!(grouped[i].get_metadata("isotope_envelope_id"), Some(&envelope));
+            assert_eq!(grouped[i].get_metadata("isotope_charge"), Some(&serde_json::json!(1)));
+        }
+        assert_eq!(grouped[1].get_metadata("monoisotopic"), Some(&serde_json::json!(true)));
+        assert_eq!(grouped[0].get_metadata("monoisotopic"), Some(&serde_json::json!(false)));
+        assert_eq!(grouped[2].get_metadata("isotope_index"), Some(&serde_json::json!(2)));
+        assert!(grouped[3].get_metadata("isotope_envelope_id").is_none());
+    }
+}
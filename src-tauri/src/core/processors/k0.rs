@@ -0,0 +1,103 @@
+//! 约化迁移率（K0）换算模块
+//!
+//! 由漂移时间和仪器参数计算约化迁移率：
+//! K = L² / (V·t)，K0 = K · (P / 760) · (273.15 / T)
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::data::{Peak, ProcessingError};
+
+/// 标准压力 (Torr)
+const STANDARD_PRESSURE_TORR: f64 = 760.0;
+/// 标准温度 (K)
+const STANDARD_TEMPERATURE_K: f64 = 273.15;
+
+/// 漂移管仪器参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct K0Params {
+    /// 漂移区长度 (cm)
+    pub drift_length_cm: f64,
+    /// 漂移区电压 (V)
+    pub drift_voltage_v: f64,
+    /// 漂移气压力 (Torr)
+    pub pressure_torr: f64,
+    /// 漂移气温度 (K)
+    pub temperature_k: f64,
+}
+
+impl K0Params {
+    /// 校验物理范围：所有参数必须为有限正数
+    pub fn validate(&self) -> Result<(), ProcessingError> {
+        let checks = [
+            ("漂移区长度", self.drift_length_cm),
+            ("漂移区电压", self.drift_voltage_v),
+            ("压力", self.pressure_torr),
+            ("温度", self.temperature_k),
+        ];
+        for (name, value) in checks {
+            if !value.is_finite() || value <= 0.0 {
+                return Err(ProcessingError::ConfigError(format!("{}必须为正数: {}", name, value)));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 漂移时间 (ms) 换算为约化迁移率 K0 (cm²/(V·s))
+pub fn drift_time_to_k0(dt_ms: f64, params: &K0Params) -> Result<f64, ProcessingError> {
+    params.validate()?;
+    if !dt_ms.is_finite() || dt_ms <= 0.0 {
+        return Err(ProcessingError::DataError(format!("漂移时间必须为正数: {}", dt_ms)));
+    }
+
+    let dt_s = dt_ms / 1000.0;
+    let mobility = params.drift_length_cm.powi(2) / (params.drift_voltage_v * dt_s);
+    Ok(mobility * (params.pressure_torr / STANDARD_PRESSURE_TORR) * (STANDARD_TEMPERATURE_K / params.temperature_k))
+}
+
+/// 以峰中心作为漂移时间 (ms)，为每个峰添加 "k0" 元数据
+pub fn annotate_peaks_with_k0(peaks: &[Peak], params: &K0Params) -> Result<Vec<Peak>, ProcessingError> {
+    params.validate()?;
+    peaks.iter()
+        .map(|peak| {
+            let k0 = drift_time_to_k0(peak.center, params)?;
+            let mut annotated = peak.clone();
+            annotated.add_metadata("k0".to_string(), serde_json::json!(k0));
+            Ok(annotated)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instrument() -> K0Params {
+        K0Params {
+            drift_length_cm: 10.0,
+            drift_voltage_v: 2000.0,
+            pressure_torr: 700.0,
+            temperature_k: 423.15,
+        }
+    }
+
+    #[test]
+    fn dtbp_calibrant_reproduces_reference_k0() {
+        // 2,6-二叔丁基吡啶（DTBP）质子化单体，参考 K0 = 1.42 cm²/(V·s)
+        let k0 = drift_time_to_k0(20.94, &instrument()).unwrap();
+        assert!((k0 - 1.42).abs() < 0.005, "k0 = {}", k0);
+    }
+
+    #[test]
+    fn rejects_non_physical_parameters() {
+        let mut params = instrument();
+        params.pressure_torr = 0.0;
+        assert!(matches!(drift_time_to_k0(20.0, &params), Err(ProcessingError::ConfigError(_))));
+
+        let mut params = instrument();
+        params.temperature_k = -10.0;
+        assert!(matches!(drift_time_to_k0(20.0, &params), Err(ProcessingError::ConfigError(_))));
+
+        assert!(matches!(drift_time_to_k0(0.0, &instrument()), Err(ProcessingError::DataError(_))));
+    }
+}
@@ -0,0 +1,163 @@
+//! 谱库检索
+//!
+//! 把一张全谱与谱库条目逐一比对：两边的峰在 m/z 容差内一一配对（按强度乘积从大到小贪心），
+//! 以余弦相似度（归一化点积）作为匹配分数，未配对的峰只计入范数
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::data::ProcessingError;
+
+/// 默认 m/z 配对容差 (Da)
+pub const DEFAULT_MZ_TOLERANCE: f64 = 0.01;
+
+/// m/z-强度列表
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PeakList {
+    pub mz_values: Vec<f64>,
+    pub intensities: Vec<f64>,
+}
+
+impl PeakList {
+    fn validate(&self) -> Result<(), ProcessingError> {
+        if self.mz_values.len() != self.intensities.len() {
+            return Err(ProcessingError::DataError(format!(
+                "m/z 与强度数量不一致（{} / {}）", self.mz_values.len(), self.intensities.len())));
+        }
+        Ok(())
+    }
+
+    /// 有效峰：有限 m/z 与正的有限强度
+    fn peaks(&self) -> Vec<(f64, f64)> {
+        self.mz_values.iter().zip(&self.intensities)
+            .filter(|(mz, intensity)| mz.is_finite() && intensity.is_finite() && **intensity > 0.0)
+            .map(|(&mz, &intensity)| (mz, intensity))
+            .collect()
+    }
+}
+
+/// 谱库条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryEntry {
+    pub name: String,
+    #[serde(flatten)]
+    pub peaks: PeakList,
+}
+
+/// 一个谱库条目的匹配结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryMatch {
+    pub name: String,
+    /// 余弦相似度，0-1
+    pub score: f64,
+    /// 配对成功的峰数
+    pub matched_peaks: usize,
+    pub library_peaks: usize,
+}
+
+/// 从 JSON 文本解析谱库（条目数组）
+pub fn parse_library(json: &str) -> Result<Vec<LibraryEntry>, ProcessingError> {
+    let library: Vec<LibraryEntry> = serde_json::from_str(json)?;
+    for entry in &library {
+        entry.peaks.validate()
+            .map_err(|e| ProcessingError::DataError(format!("谱库条目 {}: {}", entry.name, e)))?;
+    }
+    Ok(library)
+}
+
+/// 计算光谱与谱库条目的余弦相似度，返回 (分数, 配对峰数)
+pub fn match_spectrum(
+    spectrum: &PeakList,
+    library_entry: &LibraryEntry,
+    mz_tolerance: f64,
+) -> (f64, usize) {
+    let query = spectrum.peaks();
+    let reference = library_entry.peaks.peaks();
+
+    let query_norm = query.iter().map(|(_, i)| i * i).sum::<f64>().sqrt();
+    let reference_norm = reference.iter().map(|(_, i)| i * i).sum::<f64>().sqrt();
+    if query_norm == 0.0 || reference_norm == 0.0 {
+        return (0.0, 0);
+    }
+
+    // 容差内的候选配对，强度乘积大的优先，每个峰只配对一次
+    let mut candidates: Vec<(usize, usize, f64)> = Vec::new();
+    for (qi, (query_mz, query_intensity)) in query.iter().enumerate() {
+        for (ri, (reference_mz, reference_intensity)) in reference.iter().enumerate() {
+            if (query_mz - reference_mz).abs() <= mz_tolerance {
+                candidates.push((qi, ri, query_intensity * reference_intensity));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut query_used = vec![false; query.len()];
+    let mut reference_used = vec![false; reference.len()];
+    let mut dot = 0.0;
+    let mut matched = 0;
+    for (qi, ri, product) in candidates {
+        if query_used[qi] || reference_used[ri] {
+            continue;
+        }
+        query_used[qi] = true;
+        reference_used[ri] = true;
+        dot += product;
+        matched += 1;
+    }
+
+    ((dot / (query_norm * reference_norm)).clamp(0.0, 1.0), matched)
+}
+
+/// 对整个谱库打分，按分数从高到低排序
+pub fn search_library(
+    spectrum: &PeakList,
+    library: &[LibraryEntry],
+    mz_tolerance: f64,
+) -> Result<Vec<LibraryMatch>, ProcessingError> {
+    spectrum.validate()?;
+    if !mz_tolerance.is_finite() || mz_tolerance < 0.0 {
+        return Err(ProcessingError::ConfigError(format!("m/z 容差必须为非负数: {}", mz_tolerance)));
+    }
+
+    let mut matches: Vec<LibraryMatch> = library.iter()
+        .map(|entry| {
+            let (score, matched_peaks) = match_spectrum(spectrum, entry, mz_tolerance);
+            LibraryMatch {
+                name: entry.name.clone(),
+                score,
+                matched_peaks,
+                library_peaks: entry.peaks.mz_values.len(),
+            }
+        })
+        .collect();
+    matches.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_entry_scores_near_one_and_dissimilar_scores_low() {
+        let library = parse_library(r#"[
+            {"name": "dissimilar", "mz_values": [150.0, 250.0, 350.0], "intensities": [100.0, 80.0, 60.0]},
+            {"name": "identical", "mz_values": [100.0, 200.0, 300.0, 400.0], "intensities": [10.0, 100.0, 40.0, 5.0]}
+        ]"#).unwrap();
+        let spectrum = PeakList {
+            mz_values: vec![100.002, 200.001, 299.998, 400.0],
+            intensities: vec![10.0, 100.0, 40.0, 5.0],
+        };
+
+        let (score, matched) = match_spectrum(&spectrum, &library[1], DEFAULT_MZ_TOLERANCE);
+        assert!((score - 1.0).abs() < 1e-9);
+        assert_eq!(matched, 4);
+
+        let (score, matched) = match_spectrum(&spectrum, &library[0], DEFAULT_MZ_TOLERANCE);
+        assert!(score < 0.1);
+        assert_eq!(matched, 0);
+
+        let ranked = search_library(&spectrum, &library, DEFAULT_MZ_TOLERANCE).unwrap();
+        assert_eq!(ranked[0].name, "identical");
+        assert_eq!(ranked[1].name, "dissimilar");
+    }
+}
@@ -8,3 +8,17 @@ pub mod peak_detection;
 pub mod peak_fitting;
 pub mod overlapping_peaks;
 pub mod baseline_correction;
+pub mod quantitation;
+pub mod overlay_extractor;
+pub mod peak_tracking;
+pub mod k0;
+pub mod projection;
+pub mod curve_health;
+pub mod library_search;
+pub mod isotope;
+pub mod refine_extraction;
+pub mod region_stats;
+pub mod calibration;
+pub mod quick_analysis;
+pub mod noise_reduction;
+pub mod alignment;
@@ -0,0 +1,349 @@
+//! 噪声降低处理器
+//!
+//! 将傅里叶、维纳滤波与 Savitzky-Golay 平滑封装为统一的 `Processor`，可在处理链中组合使用。
+//! 小波与中值滤波尚未实现，创建时返回配置错误
+
+pub mod wiener;
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::core::data::{Curve, DataContainer, ProcessingError, ProcessingResult};
+use crate::core::processors::core::{Processor, ProcessorType};
+use crate::core::processors::peak_detection::estimate_noise_floor;
+use crate::core::utils::signal::{fft_denoise, savitzky_golay};
+use crate::core::utils::windows::WindowFunction;
+use wiener::{estimate_noise_variance, wiener_filter};
+
+/// 找不到可测宽度的峰时，自动模式使用的 Savitzky-Golay 窗口
+const DEFAULT_SAVITZKY_GOLAY_WINDOW: usize = 5;
+
+/// 噪声降低处理器
+#[derive(Debug)]
+pub struct NoiseReductionProcessor {
+    method: String,
+}
+
+impl NoiseReductionProcessor {
+    pub fn new(method: &str) -> Self {
+        Self {
+            method: method.to_string(),
+        }
+    }
+
+    /// 创建噪声降低处理器实例
+    pub fn create(method: &str) -> Result<std::sync::Arc<dyn Processor>, ProcessingError> {
+        match method {
+            "fourier" | "wiener_filter" | "savitzky_golay" => Ok(std::sync::Arc::new(Self::new(method))),
+            "wavelet" | "median_filter" => Err(ProcessingError::ConfigError(format!("噪声降低方法 {} 尚未实现", method))),
+            _ => Err(ProcessingError::ConfigError(format!("不支持的噪声降低方法: {}", method))),
+        }
+    }
+
+    /// 读取参数：可以直接给出，也可以来自 `ProcessorConfig` 的 `parameters`
+    fn parameter<'a>(config: &'a Value, key: &str) -> Option<&'a Value> {
+        config.get(key)
+            .filter(|v| !v.is_null())
+            .or_else(|| config.get("parameters").and_then(|p| p.get(key)).filter(|v| !v.is_null()))
+    }
+
+    /// 对单条曲线降噪，返回降噪后的强度
+    pub fn denoise(&self, curve: &Curve, config: &Value) -> Result<Vec<f64>, ProcessingError> {
+        let y = &curve.y_values;
+        let threshold = Self::parameter(config, "threshold").and_then(|v| v.as_f64());
+        let window_size = Self::parameter(config, "window_size").and_then(|v| v.as_u64()).unwrap_or(5) as usize;
+
+        match self.method.as_str() {
+            "fourier" => {
+                let cutoff_frequency = Self::parameter(config, "cutoff_frequency").and_then(|v| v.as_f64()).unwrap_or(0.1);
+                let window_name = Self::parameter(config, "window").and_then(|v| v.as_str()).unwrap_or("hann");
+                let tukey_alpha = Self::parameter(config, "tukey_alpha").and_then(|v| v.as_f64());
+                let window = WindowFunction::from_name(window_name, tukey_alpha)?;
+                fft_denoise(y, cutoff_frequency, window)
+            }
+            "wiener_filter" => {
+                // 噪声方差由噪声区间或 MAD 估计，threshold 作为下限
+                let noise_region = Self::parameter(config, "noise_region")
+                    .and_then(|v| v.as_array())
+                    .and_then(|range| Some((range.first()?.as_f64()?, range.get(1)?.as_f64()?)));
+                let noise_variance = estimate_noise_variance(curve, noise_region).max(threshold.unwrap_or(0.0));
+                Ok(wiener_filter(y, window_size, noise_variance))
+            }
+            "savitzky_golay" => {
+                let polynomial_order = Self::parameter(config, "polynomial_order").and_then(|v| v.as_u64()).unwrap_or(2) as usize;
+                let window_size = match Self::parameter(config, "window_size") {
+                    Some(Value::String(mode)) if mode == "auto" => {
+                        let window = auto_savitzky_golay_window(curve, polynomial_order);
+                        log::info!("📊 曲线 {} 自动选择Savitzky-Golay窗口: {}", curve.id, window);
+                        window
+                    }
+                    Some(Value::String(mode)) => {
+                        return Err(ProcessingError::ConfigError(format!("无效的窗口大小: {}", mode)));
+                    }
+                    _ => window_size,
+                };
+                savitzky_golay(y, window_size, polynomial_order, 0, 1.0)
+            }
+            _ => Err(ProcessingError::ConfigError(format!("不支持的噪声降低方法: {}", self.method))),
+        }
+    }
+}
+
+#[async_trait]
+impl Processor for NoiseReductionProcessor {
+    fn name(&self) -> &str {
+        "noise_reduction"
+    }
+
+    fn description(&self) -> &str {
+        "噪声降低处理器，支持傅里叶、维纳滤波与Savitzky-Golay平滑"
+    }
+
+    fn processor_type(&self) -> ProcessorType {
+        ProcessorType::NoiseReduction
+    }
+
+    fn supported_methods(&self) -> Vec<String> {
+        vec![
+            "fourier".to_string(),
+            "wiener_filter".to_string(),
+            "savitzky_golay".to_string(),
+        ]
+    }
+
+    fn config_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "threshold": {
+                    "type": "number",
+                    "description": "维纳滤波的噪声方差下限"
+                },
+                "noise_region": {
+                    "type": "array",
+                    "items": {"type": "number"},
+                    "minItems": 2,
+                    "maxItems": 2,
+                    "description": "维纳滤波估计噪声方差用的纯噪声区间 [x_start, x_end]（默认由MAD估计）"
+                },
+                "cutoff_frequency": {
+                    "type": "number",
+                    "default": 0.1,
+                    "description": "傅里叶滤波截止频率（相对奈奎斯特频率的比例，0-1）"
+                },
+                "window": {
+                    "type": "string",
+                    "enum": ["hann", "hamming", "blackman", "tukey", "rectangular"],
+                    "default": "hann",
+                    "description": "FFT前的窗函数"
+                },
+                "window_size": {
+                    "oneOf": [
+                        {"type": "integer", "minimum": 3},
+                        {"type": "string", "enum": ["auto"]}
+                    ],
+                    "default": 5,
+                    "description": "维纳滤波与Savitzky-Golay的窗口大小（奇数）；Savitzky-Golay可设为 \"auto\"，按最窄峰的半高宽自动选择"
+                },
+                "polynomial_order": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "default": 2,
+                    "description": "Savitzky-Golay多项式阶数"
+                }
+            }
+        })
+    }
+
+    async fn process(
+        &self,
+        input: DataContainer,
+        config: serde_json::Value,
+    ) -> Result<ProcessingResult, ProcessingError> {
+        if input.curves.is_empty() {
+            return Err(ProcessingError::DataError("没有可处理的曲线数据".to_string()));
+        }
+
+        let mut result_curves = Vec::new();
+        let mut snr_improvements = Vec::new();
+
+        for curve in &input.curves {
+            let denoised = with_values(curve, self.denoise(curve, &config)?);
+
+            let noise_before = estimate_noise_floor(curve).noise;
+            let noise_after = estimate_noise_floor(&denoised).noise;
+            let snr_improvement = if noise_after > 0.0 { noise_before / noise_after } else { 1.0 };
+            snr_improvements.push(serde_json::json!({
+                "curve_id": curve.id,
+                "snr_improvement": snr_improvement,
+            }));
+
+            result_curves.push(denoised);
+        }
+
+        let mut metadata = input.metadata.clone();
+        metadata.insert("noise_reduction_method".to_string(), Value::String(self.method.clone()));
+        metadata.insert("snr_improvements".to_string(), Value::Array(snr_improvements));
+
+        Ok(ProcessingResult {
+            curves: result_curves,
+            peaks: Vec::new(),
+            metadata,
+        })
+    }
+}
+
+/// 用新的强度替换曲线数据并重新计算统计量，保留范围、元数据与已有峰
+fn with_values(curve: &Curve, y_values: Vec<f64>) -> Curve {
+    let mut result = Curve::new(
+        curve.id.clone(),
+        curve.curve_type.clone(),
+        curve.x_values.clone(),
+        y_values,
+        curve.x_label.clone(),
+        curve.y_label.clone(),
+        curve.x_unit.clone(),
+        curve.y_unit.clone(),
+    );
+    result.mz_range = curve.mz_range;
+    result.rt_range = curve.rt_range;
+    result.dt_range = curve.dt_range;
+    result.ms_level = curve.ms_level;
+    result.smoothing_factor = curve.smoothing_factor;
+    result.baseline_correction = curve.baseline_correction.clone();
+    result.metadata = curve.metadata.clone();
+    result.peaks = curve.peaks.clone();
+    result
+}
+
+/// 按曲线中最窄峰的宽度自动选择 Savitzky-Golay 窗口
+///
+/// 对高于噪声阈值的局部极大值测量半高处的宽度（采样点数），窗口取最窄宽度的一半并向下取奇数，
+/// 但不小于多项式阶数所需的最小窗口；找不到峰时使用默认窗口
+pub fn auto_savitzky_golay_window(curve: &Curve, polynomial_order: usize) -> usize {
+    let y = &curve.y_values;
+    let floor = estimate_noise_floor(curve);
+    let threshold = floor.threshold(5.0);
+
+    let narrowest = (1..y.len().saturating_sub(1))
+        .filter(|&i| y[i] > threshold && y[i] > y[i - 1] && y[i] >= y[i + 1])
+        .filter_map(|i| {
+            let half_height = floor.baseline + (y[i] - floor.baseline) / 2.0;
+            let left = (0..i).rev().find(|&j| y[j] <= half_height)?;
+            let right = ((i + 1)..y.len()).find(|&j| y[j] <= half_height)?;
+            Some(right - left)
+        })
+        .min();
+
+    let minimum = (polynomial_order + 2) | 1;
+    match narrowest {
+        Some(width) => {
+            let half = width / 2;
+            let odd = if half.is_multiple_of(2) { half.saturating_sub(1) } else { half };
+            odd.max(minimum).max(3)
+        }
+        None => DEFAULT_SAVITZKY_GOLAY_WINDOW.max(minimum),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use crate::core::processors::core::{ProcessorChain, ProcessorConfig};
+
+    /// 中心 5.0 的高斯峰叠加确定性伪随机噪声
+    fn noisy_curve() -> Curve {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(12345);
+        let x_values: Vec<f64> = (0..400).map(|i| i as f64 * 0.025).collect();
+        let y_values: Vec<f64> = x_values
+            .iter()
+            .map(|&x| {
+                let noise = rng.gen_range(-15.0..15.0);
+                100.0 + 100.0 * (-(x - 5.0).powi(2) / (2.0 * 0.4 * 0.4)).exp() + noise
+            })
+            .collect();
+        Curve::new(
+            "noisy".to_string(),
+            "DT".to_string(),
+            x_values,
+            y_values,
+            "Drift Time".to_string(),
+            "Intensity".to_string(),
+            "ms".to_string(),
+            "counts".to_string(),
+        )
+    }
+
+    async fn detected_peak_count(chain: ProcessorChain) -> usize {
+        let mut input = DataContainer::new();
+        input.curves.push(noisy_curve());
+        let result = chain.execute(input).await.unwrap();
+        result.curves[0].peaks.len()
+    }
+
+    #[tokio::test]
+    async fn test_denoise_then_detect_finds_fewer_spurious_peaks() {
+        let detect = || ProcessorConfig::new(ProcessorType::PeakDetection, "simple".to_string());
+
+        let raw_chain = ProcessorChain::new().add_processor(detect()).unwrap();
+        let raw_count = detected_peak_count(raw_chain).await;
+
+        for method in ["fourier", "wiener_filter"] {
+            let chain = ProcessorChain::new()
+                .add_processor(ProcessorConfig::new(ProcessorType::NoiseReduction, method.to_string())
+                    .with_parameter("window_size".to_string(), serde_json::json!(9)))
+                .unwrap()
+                .add_processor(detect())
+                .unwrap();
+            let denoised_count = detected_peak_count(chain).await;
+
+            assert!(denoised_count >= 1, "{}: no peak detected", method);
+            assert!(denoised_count < raw_count, "{}: {} peaks after denoising vs {} raw", method, denoised_count, raw_count);
+        }
+
+        // 尚未实现的方法在建链时就报配置错误，而不是退化为其他算法
+        for method in ["wavelet", "median_filter"] {
+            let chain = ProcessorChain::new()
+                .add_processor(ProcessorConfig::new(ProcessorType::NoiseReduction, method.to_string()));
+            assert!(matches!(chain, Err(ProcessingError::ConfigError(_))), "{}", method);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_auto_savitzky_golay_window_preserves_narrow_peak() {
+        // 窄峰 σ = 0.06（约 6 个采样点）与宽峰 σ = 0.5，基线 10
+        let x_values: Vec<f64> = (0..1000).map(|i| i as f64 * 0.01).collect();
+        let y_values: Vec<f64> = x_values
+            .iter()
+            .map(|&x| {
+                10.0 + 100.0 * (-(x - 3.0).powi(2) / (2.0 * 0.06 * 0.06)).exp()
+                    + 100.0 * (-(x - 7.0).powi(2) / (2.0 * 0.5 * 0.5)).exp()
+            })
+            .collect();
+        let curve = Curve::new(
+            "two_widths".to_string(),
+            "DT".to_string(),
+            x_values,
+            y_values,
+            "Drift Time".to_string(),
+            "Intensity".to_string(),
+            "ms".to_string(),
+            "counts".to_string(),
+        );
+        let narrow_height = |y: &[f64]| y[250..350].iter().cloned().fold(f64::MIN, f64::max) - 10.0;
+
+        let window = auto_savitzky_golay_window(&curve, 2);
+        assert!(!window.is_multiple_of(2) && (3..=9).contains(&window), "window {}", window);
+
+        let processor = NoiseReductionProcessor::new("savitzky_golay");
+        let auto = processor.denoise(&curve, &serde_json::json!({"window_size": "auto"})).unwrap();
+        assert!((narrow_height(&auto) / 100.0 - 1.0).abs() < 0.05, "auto height {}", narrow_height(&auto));
+
+        // 按宽峰选的大窗口会明显压低窄峰
+        let wide = processor.denoise(&curve, &serde_json::json!({"window_size": 41})).unwrap();
+        assert!(narrow_height(&wide) < 95.0, "fixed window height {}", narrow_height(&wide));
+
+        assert!(processor.denoise(&curve, &serde_json::json!({"window_size": "large"})).is_err());
+    }
+}
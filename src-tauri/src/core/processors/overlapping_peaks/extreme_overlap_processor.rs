@@ -4,7 +4,7 @@
 
 use crate::core::data::{Curve, Peak, ProcessingError};
 use crate::core::processors::overlapping_peaks::{
-    OverlappingPeakProcessor, OverlappingPeakStrategy,
+    OverlappingPeakProcessor, OverlappingPeakStrategy, StrategyThresholds,
     sharpen_cwt_preprocessor::SharpenCWTPreprocessor,
     emg_nlls_fitter::EMGNLLSFitter,
 };
@@ -158,8 +158,9 @@ impl ExtremeOverlapProcessor {
         curve: &Curve,
         config: &Value,
     ) -> Result<Vec<Peak>, ProcessingError> {
-        // 根据峰特征选择处理策略
-        let strategy = OverlappingPeakStrategy::auto_select(peaks, curve);
+        // 根据峰特征选择处理策略，分界阈值可由处理器参数 strategy_thresholds 调整
+        let thresholds = StrategyThresholds::from_config(&config["parameters"])?;
+        let strategy = OverlappingPeakStrategy::auto_select(peaks, curve, &thresholds);
         
         match strategy {
             OverlappingPeakStrategy::SinglePeak => Ok(peaks.to_vec()),
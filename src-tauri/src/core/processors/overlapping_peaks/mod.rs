@@ -9,6 +9,7 @@ pub mod extreme_overlap_processor;
 
 use crate::core::data::{Curve, Peak, ProcessingError, DataContainer, ProcessingResult};
 use crate::core::processors::core::Processor;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use async_trait::async_trait;
 
@@ -109,7 +110,8 @@ impl Processor for OverlappingPeakProcessorEnum {
 
         let curve = &input.curves[0];
         
-        let processed_peaks = self.process_overlapping_peaks(&curve.peaks, curve, &config)?;
+        let mut processed_peaks = self.process_overlapping_peaks(&curve.peaks, curve, &config)?;
+        let cluster_count = assign_cluster_ids(&mut processed_peaks);
         
         // 将处理后的峰添加到曲线中
         let mut result_curves = input.curves.clone();
@@ -117,10 +119,13 @@ impl Processor for OverlappingPeakProcessorEnum {
             result_curve.peaks = processed_peaks.clone();
         }
 
+        let mut metadata = input.metadata;
+        metadata.insert("cluster_count".to_string(), serde_json::json!(cluster_count));
+
         Ok(ProcessingResult {
             curves: result_curves,
             peaks: processed_peaks,
-            metadata: input.metadata,
+            metadata,
         })
     }
 
@@ -137,6 +142,83 @@ pub fn create_overlapping_processor(method: &str) -> Result<OverlappingPeakProce
     }
 }
 
+/// 按拟合区域为峰分配簇 ID
+///
+/// 峰区域（见 `Peak::region`）相互重叠的峰连通为一个簇，簇按区域起点排序编号为
+/// `{curve_id}_cluster_{n}`；孤立峰自成一簇。返回簇数量
+pub fn assign_cluster_ids(peaks: &mut [Peak]) -> usize {
+    let mut order: Vec<usize> = (0..peaks.len()).collect();
+    order.sort_by(|&a, &b| peaks[a].region().0.total_cmp(&peaks[b].region().0));
+
+    let mut cluster_count = 0;
+    let mut cluster_end = f64::NEG_INFINITY;
+    for index in order {
+        let (start, end) = peaks[index].region();
+        if cluster_count == 0 || start > cluster_end {
+            cluster_count += 1;
+            cluster_end = end;
+        } else {
+            cluster_end = cluster_end.max(end);
+        }
+        peaks[index].cluster_id = Some(format!("{}_cluster_{}", peaks[index].curve_id, cluster_count));
+    }
+
+    cluster_count
+}
+
+/// 自动策略选择的分界阈值
+///
+/// 重叠量为峰对的平均半峰宽减去中心间距（X轴单位）：低于 `light_overlap` 视为单峰，
+/// 低于 `medium_overlap` 为轻度重叠，低于 `extreme_overlap` 为中度重叠；更大的重叠在
+/// 信噪比低于 `low_snr` 时按极度重叠处理，否则仍按中度重叠处理
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StrategyThresholds {
+    pub light_overlap: f64,
+    pub medium_overlap: f64,
+    pub extreme_overlap: f64,
+    pub low_snr: f64,
+}
+
+impl Default for StrategyThresholds {
+    fn default() -> Self {
+        Self {
+            light_overlap: 0.1,
+            medium_overlap: 0.5,
+            extreme_overlap: 1.0,
+            low_snr: 10.0,
+        }
+    }
+}
+
+impl StrategyThresholds {
+    /// 从配置中解析 `strategy_thresholds`，未设置的项使用默认值；重叠阈值须为有限值且严格递增
+    pub fn from_config(config: &Value) -> Result<Self, ProcessingError> {
+        let value = &config["strategy_thresholds"];
+        if value.is_null() {
+            return Ok(Self::default());
+        }
+        let thresholds: Self = serde_json::from_value(value.clone())
+            .map_err(|e| ProcessingError::ConfigError(format!("strategy_thresholds 格式无效: {}", e)))?;
+        thresholds.validate()?;
+        Ok(thresholds)
+    }
+
+    pub fn validate(&self) -> Result<(), ProcessingError> {
+        let values = [self.light_overlap, self.medium_overlap, self.extreme_overlap, self.low_snr];
+        if values.iter().any(|v| !v.is_finite()) {
+            return Err(ProcessingError::ConfigError("策略阈值必须为有限数值".to_string()));
+        }
+        if !(self.light_overlap < self.medium_overlap && self.medium_overlap < self.extreme_overlap) {
+            return Err(ProcessingError::ConfigError(format!(
+                "重叠阈值须满足 light_overlap < medium_overlap < extreme_overlap，实际为 {} / {} / {}",
+                self.light_overlap, self.medium_overlap, self.extreme_overlap
+            )));
+        }
+        Ok(())
+    }
+}
+
 /// 重叠峰处理策略
 #[derive(Debug, Clone)]
 pub enum OverlappingPeakStrategy {
@@ -151,8 +233,8 @@ pub enum OverlappingPeakStrategy {
 }
 
 impl OverlappingPeakStrategy {
-    /// 根据峰特征自动选择策略
-    pub fn auto_select(peaks: &[Peak], curve: &Curve) -> Self {
+    /// 根据峰特征和分界阈值自动选择策略
+    pub fn auto_select(peaks: &[Peak], curve: &Curve, thresholds: &StrategyThresholds) -> Self {
         if peaks.len() <= 1 {
             return Self::SinglePeak;
         }
@@ -174,13 +256,13 @@ impl OverlappingPeakStrategy {
         let snr = Self::estimate_snr(curve);
         
         // 根据重叠程度和信噪比选择策略
-        if max_overlap < 0.1 {
+        if max_overlap < thresholds.light_overlap {
             Self::SinglePeak
-        } else if max_overlap < 0.5 {
+        } else if max_overlap < thresholds.medium_overlap {
             Self::LightOverlap
-        } else if max_overlap < 1.0 {
+        } else if max_overlap < thresholds.extreme_overlap {
             Self::MediumOverlap
-        } else if snr < 10.0 {
+        } else if snr < thresholds.low_snr {
             Self::ExtremeOverlapLowSNR
         } else {
             Self::MediumOverlap
@@ -203,6 +285,16 @@ impl OverlappingPeakStrategy {
         }
     }
     
+    /// 策略名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::SinglePeak => "single_peak",
+            Self::LightOverlap => "light_overlap",
+            Self::MediumOverlap => "medium_overlap",
+            Self::ExtremeOverlapLowSNR => "extreme_overlap_low_snr",
+        }
+    }
+    
     /// 获取对应的处理方法
     pub fn get_processor_method(&self) -> &'static str {
         match self {
@@ -213,3 +305,175 @@ impl OverlappingPeakStrategy {
         }
     }
 }
+
+/// 一对峰的重叠情况
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeakPairOverlap {
+    pub peak_a: String,
+    pub peak_b: String,
+    /// 峰中心间距
+    pub distance: f64,
+    /// 平均半峰宽减去中心间距，正值表示重叠
+    pub overlap: f64,
+    pub overlapping: bool,
+}
+
+/// 重叠峰处理前的重叠程度估计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverlapEstimate {
+    /// 重叠峰对占全部峰对的比例
+    pub overlap_ratio: f64,
+    /// 推荐策略名称
+    pub recommended_strategy: String,
+    /// 推荐策略对应的处理方法
+    pub recommended_method: String,
+    pub pairs: Vec<PeakPairOverlap>,
+}
+
+/// 在执行解卷积之前估计峰的重叠程度并给出推荐策略
+pub fn estimate_overlap(peaks: &[Peak], curve: &Curve, thresholds: &StrategyThresholds) -> OverlapEstimate {
+    let overlap_ratio = crate::core::processors::peak_analysis::PeakAnalyzer::new().estimate_overlap_level(peaks);
+    let strategy = OverlappingPeakStrategy::auto_select(peaks, curve, thresholds);
+    
+    let mut pairs = Vec::new();
+    for i in 0..peaks.len() {
+        for j in (i + 1)..peaks.len() {
+            let distance = (peaks[i].center - peaks[j].center).abs();
+            let overlap = (peaks[i].fwhm + peaks[j].fwhm) / 2.0 - distance;
+            pairs.push(PeakPairOverlap {
+                peak_a: peaks[i].id.clone(),
+                peak_b: peaks[j].id.clone(),
+                distance,
+                overlap,
+                overlapping: overlap > 0.0,
+            });
+        }
+    }
+    
+    OverlapEstimate {
+        overlap_ratio,
+        recommended_strategy: strategy.name().to_string(),
+        recommended_method: strategy.get_processor_method().to_string(),
+        pairs,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::data::PeakType;
+
+    fn peak(id: &str, center: f64, fwhm: f64) -> Peak {
+        let mut peak = Peak::new(id.to_string(), "test_curve".to_string(), center, 100.0, PeakType::Gaussian);
+        peak.fwhm = fwhm;
+        peak
+    }
+
+    #[test]
+    fn test_estimate_overlap_three_peaks_one_pair() {
+        let x_values: Vec<f64> = (0..200).map(|i| i as f64 * 0.05).collect();
+        let y_values: Vec<f64> = x_values.iter().map(|&x| 1.0 + 100.0 * (-(x - 2.0).powi(2) / 0.05).exp()).collect();
+        let curve = Curve::new(
+            "test_curve".to_string(),
+            "DT".to_string(),
+            x_values,
+            y_values,
+            "Drift Time".to_string(),
+            "Intensity".to_string(),
+            "ms".to_string(),
+            "counts".to_string(),
+        );
+        // a 与 b 重叠 0.2（轻度），c 远离两者
+        let peaks = vec![peak("a", 2.0, 0.5), peak("b", 2.3, 0.5), peak("c", 6.0, 0.5)];
+
+        let estimate = estimate_overlap(&peaks, &curve, &StrategyThresholds::default());
+
+        assert!((estimate.overlap_ratio - 1.0 / 3.0).abs() < 1e-12);
+        assert_eq!(estimate.recommended_strategy, "light_overlap");
+        assert_eq!(estimate.recommended_method, "fbf");
+        assert_eq!(estimate.pairs.len(), 3);
+        let overlapping: Vec<_> = estimate.pairs.iter().filter(|p| p.overlapping).collect();
+        assert_eq!(overlapping.len(), 1);
+        assert_eq!((overlapping[0].peak_a.as_str(), overlapping[0].peak_b.as_str()), ("a", "b"));
+        assert!((overlapping[0].overlap - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_lower_medium_threshold_routes_borderline_pair_to_sharpen_cwt() {
+        let x_values: Vec<f64> = (0..200).map(|i| i as f64 * 0.05).collect();
+        let y_values: Vec<f64> = x_values.iter().map(|&x| 1.0 + 100.0 * (-(x - 2.0).powi(2) / 0.05).exp()).collect();
+        let curve = Curve::new(
+            "test_curve".to_string(),
+            "DT".to_string(),
+            x_values,
+            y_values,
+            "Drift Time".to_string(),
+            "Intensity".to_string(),
+            "ms".to_string(),
+            "counts".to_string(),
+        );
+        // 重叠 0.4：默认阈值下为轻度重叠
+        let peaks = vec![peak("a", 2.0, 0.6), peak("b", 2.2, 0.6)];
+
+        let default_estimate = estimate_overlap(&peaks, &curve, &StrategyThresholds::default());
+        assert_eq!(default_estimate.recommended_method, "fbf");
+
+        let thresholds = StrategyThresholds { medium_overlap: 0.3, ..StrategyThresholds::default() };
+        let estimate = estimate_overlap(&peaks, &curve, &thresholds);
+        assert_eq!(estimate.recommended_strategy, "medium_overlap");
+        assert_eq!(estimate.recommended_method, "sharpen_cwt");
+
+        let parsed = StrategyThresholds::from_config(&serde_json::json!({"strategy_thresholds": {"medium_overlap": 0.3}})).unwrap();
+        assert_eq!(parsed, thresholds);
+        assert!(StrategyThresholds::from_config(&serde_json::json!({"strategy_thresholds": {"medium_overlap": 2.0}})).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_triple_overlap_shares_cluster_and_sums_area() {
+        // 解卷积后的三重重叠峰 + 一个孤立峰
+        let mut peaks: Vec<Peak> = [(10.0, 120.0), (11.0, 80.0), (12.0, 45.0), (30.0, 60.0)]
+            .iter()
+            .enumerate()
+            .map(|(i, &(center, area))| {
+                let mut p = peak(&format!("p{}", i), center, 1.5);
+                p.area = area;
+                p
+            })
+            .collect();
+        peaks.swap(0, 2);
+
+        assert_eq!(assign_cluster_ids(&mut peaks), 2);
+        let triple: Vec<&Peak> = peaks.iter().filter(|p| p.center < 20.0).collect();
+        assert_eq!(triple.len(), 3);
+        assert!(triple.iter().all(|p| p.cluster_id == triple[0].cluster_id));
+        assert_eq!(triple[0].cluster_id.as_deref(), Some("test_curve_cluster_1"));
+        let isolated = peaks.iter().find(|p| p.center > 20.0).unwrap();
+        assert_eq!(isolated.cluster_id.as_deref(), Some("test_curve_cluster_2"));
+
+        let mut curve = Curve::new(
+            "test_curve".to_string(), "DT".to_string(), vec![0.0, 40.0], vec![0.0, 0.0],
+            "Drift Time".to_string(), "Intensity".to_string(), "ms".to_string(), "counts".to_string(),
+        );
+        curve.peaks = peaks;
+        let data = DataContainer { metadata: std::collections::HashMap::new(), spectra: vec![], curves: vec![curve] };
+        let exported = crate::core::exporters::Exporter::export(
+            &crate::core::exporters::JsonExporter,
+            &data,
+            serde_json::json!({"group_by": "cluster"}),
+        ).await.unwrap();
+
+        let document: Value = serde_json::from_slice(&exported.data).unwrap();
+        let clusters = document["curves"][0]["clusters"].as_array().unwrap();
+        assert!(document["curves"][0].get("peaks").is_none());
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0]["cluster_id"], "test_curve_cluster_1");
+        assert_eq!(clusters[0]["peak_count"], 3);
+        let member_area: f64 = clusters[0]["peaks"].as_array().unwrap().iter()
+            .map(|p| p["area"].as_f64().unwrap())
+            .sum();
+        assert!((clusters[0]["summed_area"].as_f64().unwrap() - member_area).abs() < 1e-9);
+        assert!((member_area - 245.0).abs() < 1e-9);
+        assert!((clusters[0]["region_start"].as_f64().unwrap() - 9.25).abs() < 1e-9);
+        assert!((clusters[0]["region_end"].as_f64().unwrap() - 12.75).abs() < 1e-9);
+    }
+}
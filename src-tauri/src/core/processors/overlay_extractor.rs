@@ -0,0 +1,266 @@
+//! 多文件叠加提取
+//!
+//! 对多个重复样本文件提取相同窗口的曲线，合并为一个多曲线容器，按来源文件名标注，便于叠加比较
+
+use serde_json::Value;
+
+use crate::core::data::{AxisLabels, DataContainer, ProcessingError, ProcessingRequest, ProcessingResult};
+use crate::core::processors::base::Processor;
+use crate::core::processors::core::Processor as AnalysisProcessor;
+use crate::core::processors::core::clamp_config_to_schema;
+
+/// 按曲线类型选择提取器并执行提取（"dt" / "tic" / "xic"），`labels` 中设置的标签与单位写入提取出的曲线
+pub async fn extract_by_type(
+    container: DataContainer,
+    curve_type: &str,
+    mz_range: &str,
+    rt_range: &str,
+    ms_level: u8,
+    labels: &AxisLabels,
+) -> Result<ProcessingResult, ProcessingError> {
+    match curve_type {
+        "dt" => {
+            let mut config = serde_json::json!({
+                "mz_range": mz_range,
+                "rt_range": rt_range,
+                "ms_level": ms_level
+            });
+            labels.merge_into(&mut config);
+            run_extractor(&crate::core::processors::dt_extractor::DTExtractor, container, config).await
+        },
+        "tic" => {
+            // TIC不需要mz_range，会使用全m/z范围
+            let mut config = serde_json::json!({
+                "rt_range": rt_range,
+                "ms_level": ms_level
+            });
+            labels.merge_into(&mut config);
+            run_extractor(&crate::core::processors::tic_extractor::TICExtractor, container, config).await
+        },
+        "xic" => {
+            let mut config = serde_json::json!({
+                "mz_range": mz_range,
+                "rt_range": rt_range,
+                "ms_level": ms_level
+            });
+            labels.merge_into(&mut config);
+            run_extractor(&crate::core::processors::xic_extractor::XICExtractor, container, config).await
+        },
+        _ => Err(ProcessingError::ConfigError(format!("不支持的曲线类型: {}", curve_type))),
+    }
+}
+
+/// 按处理请求的模式处理已加载的数据："dt" / "tic" / "xic" 提取曲线，"peak" 提取 DT 曲线后进行峰分析
+pub async fn process_request(
+    container: DataContainer,
+    request: &ProcessingRequest,
+) -> Result<DataContainer, ProcessingError> {
+    let curve_type = if request.mode == "peak" { "dt" } else { request.mode.as_str() };
+    let extracted = extract_by_type(
+        container,
+        curve_type,
+        &request.mz_range,
+        &request.rt_range,
+        request.ms_level,
+        &AxisLabels::default(),
+    ).await?;
+    let mut result = DataContainer {
+        metadata: extracted.metadata,
+        spectra: Vec::new(),
+        curves: extracted.curves,
+    };
+
+    if request.mode == "peak" {
+        let analysis = crate::core::processors::peak_analysis::PeakAnalyzer::new()
+            .process(result, serde_json::json!({}))
+            .await?;
+        result = DataContainer {
+            metadata: analysis.metadata,
+            spectra: Vec::new(),
+            curves: analysis.curves,
+        };
+    }
+
+    Ok(result)
+}
+
+/// 按提取器的配置模式截断超出范围的参数后执行提取，警告写入结果元数据 `config_warnings`
+async fn run_extractor<P: Processor>(
+    extractor: &P,
+    container: DataContainer,
+    mut config: Value,
+) -> Result<ProcessingResult, ProcessingError> {
+    let warnings = clamp_config_to_schema(&extractor.config_schema(), &mut config);
+    let mut result = extractor.process(container, config).await?;
+    if !warnings.is_empty() {
+        result.add_metadata("config_warnings".to_string(), serde_json::to_value(&warnings)?);
+    }
+    Ok(result)
+}
+
+/// 从每个文件提取相同的曲线并合并
+///
+/// `load` 负责按路径加载文件（调用方可接入缓存）。单个文件加载或提取失败时记录到
+/// 元数据 `failed_files` 中并继续处理其余文件；所有文件都失败时返回错误。
+pub async fn extract_overlay<F>(
+    file_paths: &[String],
+    curve_type: &str,
+    mz_range: &str,
+    rt_range: &str,
+    ms_level: u8,
+    labels: &AxisLabels,
+    mut load: F,
+) -> Result<DataContainer, ProcessingError>
+where
+    F: FnMut(&str) -> Result<DataContainer, ProcessingError>,
+{
+    let mut overlay = DataContainer::new();
+    let mut failed_files = Vec::new();
+    let mut labels: Vec<String> = Vec::new();
+
+    for file_path in file_paths {
+        let extracted = match load(file_path) {
+            Ok(container) => extract_by_type(container, curve_type, mz_range, rt_range, ms_level, labels).await,
+            Err(e) => Err(e),
+        };
+
+        let result = match extracted {
+            Ok(result) if !result.curves.is_empty() => result,
+            Ok(_) => {
+                failed_files.push(failure(file_path, "未找到符合条件的曲线数据"));
+                continue;
+            }
+            Err(e) => {
+                log::warn!("⚠️ 叠加提取跳过文件 {}: {}", file_path, e);
+                failed_files.push(failure(file_path, &e.to_string()));
+                continue;
+            }
+        };
+
+        // 以文件名作为曲线标签；同名文件追加序号避免冲突
+        let file_name = std::path::Path::new(file_path)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or(file_path)
+            .to_string();
+        let duplicates = labels.iter().filter(|l| **l == file_name).count();
+        labels.push(file_name.clone());
+        let label = if duplicates == 0 { file_name.clone() } else { format!("{} ({})", file_name, duplicates + 1) };
+
+        let multiple = result.curves.len() > 1;
+        for (i, mut curve) in result.curves.into_iter().enumerate() {
+            curve.id = if multiple { format!("{} #{}", label, i + 1) } else { label.clone() };
+            curve.add_metadata("source_file".to_string(), Value::String(file_name.clone()));
+            curve.add_metadata("source_path".to_string(), Value::String(file_path.clone()));
+            overlay.curves.push(curve);
+        }
+    }
+
+    if overlay.curves.is_empty() {
+        return Err(ProcessingError::DataError(format!(
+            "所有 {} 个文件均提取失败", file_paths.len()
+        )));
+    }
+
+    log::info!("📊 叠加提取完成: {} 条曲线, {} 个文件失败", overlay.curves.len(), failed_files.len());
+
+    overlay.metadata.insert("overlay".to_string(), Value::Bool(true));
+    overlay.metadata.insert("curve_type".to_string(), Value::String(curve_type.to_string()));
+    overlay.metadata.insert("mz_range".to_string(), Value::String(mz_range.to_string()));
+    overlay.metadata.insert("rt_range".to_string(), Value::String(rt_range.to_string()));
+    overlay.metadata.insert("failed_files".to_string(), Value::Array(failed_files));
+
+    Ok(overlay)
+}
+
+fn failure(file_path: &str, reason: &str) -> Value {
+    serde_json::json!({
+        "file_path": file_path,
+        "reason": reason,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::loaders::mzdata_loader::DataLoader;
+    use crate::core::utils::test_fixtures::write_ms1_run;
+
+    /// 写出一个包含10个MS1光谱的重复样本文件，强度按 scale 缩放
+    fn write_replicate(path: &std::path::Path, scale: f32) {
+        write_ms1_run(path, 10, 0.1, |i| {
+            (0..4).map(|j| (500.0 + j as f64, scale * (10 + i + j) as f32)).collect()
+        });
+    }
+
+    #[tokio::test]
+    async fn overlays_one_labeled_curve_per_file() {
+        let dir = std::env::temp_dir();
+        let pid = std::process::id();
+        let files: Vec<String> = (1..=3)
+            .map(|i| {
+                let path = dir.join(format!("mz_curve_overlay_{}_rep{}.mzML", pid, i));
+                write_replicate(&path, i as f32);
+                path.to_string_lossy().to_string()
+            })
+            .collect();
+        let mut inputs = files.clone();
+        inputs.insert(1, dir.join("mz_curve_overlay_missing.mzML").to_string_lossy().to_string());
+
+        let overlay = extract_overlay(&inputs, "tic", "0-2000", "0-10", 1, &AxisLabels::default(), DataLoader::load_from_file).await;
+        for file in &files {
+            let _ = std::fs::remove_file(file);
+        }
+        let overlay = overlay.unwrap();
+
+        let labels: Vec<&str> = overlay.curves.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(labels, vec![
+            format!("mz_curve_overlay_{}_rep1.mzML", pid),
+            format!("mz_curve_overlay_{}_rep2.mzML", pid),
+            format!("mz_curve_overlay_{}_rep3.mzML", pid),
+        ]);
+        assert_eq!(overlay.metadata["failed_files"].as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn rejects_ms_level_missing_from_file() {
+        let path = std::env::temp_dir().join(format!("mz_curve_ms_level_{}.mzML", std::process::id()));
+        write_replicate(&path, 1.0);
+        let container = DataLoader::load_from_file(&path.to_string_lossy());
+        let _ = std::fs::remove_file(&path);
+        let container = container.unwrap();
+        assert_eq!(container.available_ms_levels(), vec![1]);
+
+        for curve_type in ["dt", "tic", "xic"] {
+            let error = extract_by_type(container.clone(), curve_type, "0-2000", "0-10", 3, &AxisLabels::default()).await.unwrap_err();
+            assert!(matches!(error, ProcessingError::DataError(_)));
+            assert_eq!(error.to_string(), "Data error: 文件中没有 MS3 光谱，可用的MS级别: MS1", "{}", curve_type);
+        }
+    }
+
+    #[tokio::test]
+    async fn label_overrides_flow_into_extracted_curve() {
+        let path = std::env::temp_dir().join(format!("mz_curve_axis_labels_{}.mzML", std::process::id()));
+        write_replicate(&path, 1.0);
+        let container = DataLoader::load_from_file(&path.to_string_lossy());
+        let _ = std::fs::remove_file(&path);
+        let container = container.unwrap();
+
+        let labels = AxisLabels {
+            x_label: Some("Acquisition Time".to_string()),
+            y_unit: Some("a.u.".to_string()),
+            ..AxisLabels::default()
+        };
+        let overridden = extract_by_type(container.clone(), "tic", "0-2000", "0-10", 1, &labels).await.unwrap();
+        let curve = &overridden.curves[0];
+        assert_eq!(curve.x_label, "Acquisition Time");
+        assert_eq!(curve.y_unit, "a.u.");
+        // 未设置的项保留提取器默认值
+        assert_eq!(curve.y_label, "Intensity");
+        assert_eq!(curve.x_unit, "min");
+
+        let defaults = extract_by_type(container, "tic", "0-2000", "0-10", 1, &AxisLabels::default()).await.unwrap();
+        assert_eq!(defaults.curves[0].x_label, "Retention Time");
+        assert_eq!(defaults.curves[0].y_unit, "counts");
+    }
+}
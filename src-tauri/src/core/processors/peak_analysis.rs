@@ -3,11 +3,308 @@
 //! 使用新的统一架构实现峰分析功能
 
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 
-use crate::core::data::{DataContainer, ProcessingError, ProcessingResult};
-use crate::core::processors::core::{Processor, ProcessorType, ProcessorConfig};
+use crate::core::data::{Curve, DataContainer, Peak, ProcessingError, ProcessingResult};
+use crate::core::processors::core::{clamp_config_to_schema, Processor, ProcessorType, ProcessorConfig};
+use crate::core::processors::peak_detection::{estimate_noise_floor_in_region, noise_region_from_config, PeakWidthBounds};
+use crate::core::processors::peak_fitting::peak_shapes::PeakShapeType;
+use crate::core::processors::peak_fitting::multi_peak_fitter::{MultiPeakFitter, AUTO_SHAPE};
+use crate::core::processors::overlapping_peaks::{OverlappingPeakStrategy, StrategyThresholds};
+use crate::core::processors::baseline_correction::BaselineProcessor;
+
+/// 未检测到峰的原因：曲线平坦（无信号起伏）
+pub const NO_PEAKS_FLAT_CURVE: &str = "flat_curve";
+/// 未检测到峰的原因：所有候选峰均低于检测阈值
+pub const NO_PEAKS_BELOW_THRESHOLD: &str = "below_detection_threshold";
+/// 未检测到峰的原因：检测到的峰均未通过质量过滤
+pub const NO_PEAKS_BELOW_QUALITY: &str = "below_quality_threshold";
+
+/// 肩峰判定：肩峰顶点到两峰之间最低点的落差不超过其高度的该比例
+const SHOULDER_MAX_PROMINENCE: f64 = 0.1;
+
+/// 肩峰处理策略：作为独立峰报告、并入相邻主峰、或直接丢弃
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShoulderPolicy {
+    Separate,
+    Merge,
+    Ignore,
+}
+
+impl ShoulderPolicy {
+    /// 解析 "separate" / "merge" / "ignore"
+    pub fn from_name(name: &str) -> Result<Self, ProcessingError> {
+        match name {
+            "separate" => Ok(Self::Separate),
+            "merge" => Ok(Self::Merge),
+            "ignore" => Ok(Self::Ignore),
+            _ => Err(ProcessingError::ConfigError(format!(
+                "不支持的肩峰策略: {}（可选 separate、merge、ignore）", name
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Separate => "separate",
+            Self::Merge => "merge",
+            Self::Ignore => "ignore",
+        }
+    }
+}
+
+/// 找出肩峰及其主峰：按中心排序后，相邻且更高的峰中心落在该峰 FWHM 内，
+/// 且两峰之间没有明显低谷（相对落差不超过 `SHOULDER_MAX_PROMINENCE`）时，该峰为肩峰。
+/// 返回 (肩峰下标, 主峰下标)，下标对应按中心排序后的峰列表
+fn find_shoulders(curve: &Curve, peaks: &[Peak]) -> Vec<(usize, usize)> {
+    let height_at = |x: f64| curve.get_intensity_at(x).unwrap_or(0.0);
+    let mut shoulders = Vec::new();
+    for (i, candidate) in peaks.iter().enumerate() {
+        let height = height_at(candidate.center);
+        if height <= 0.0 {
+            continue;
+        }
+        let neighbours = [i.checked_sub(1), (i + 1 < peaks.len()).then_some(i + 1)];
+        let main = neighbours.iter().flatten()
+            .copied()
+            .filter(|&j| {
+                let main = &peaks[j];
+                if main.amplitude <= candidate.amplitude || (main.center - candidate.center).abs() > main.fwhm {
+                    return false;
+                }
+                let (lo, hi) = if main.center < candidate.center { (main.center, candidate.center) } else { (candidate.center, main.center) };
+                let valley = curve.x_values.iter().zip(&curve.y_values)
+                    .filter(|(x, _)| **x > lo && **x < hi)
+                    .map(|(_, &y)| y)
+                    .fold(height, f64::min);
+                (height - valley) / height <= SHOULDER_MAX_PROMINENCE
+            })
+            .max_by(|&a, &b| peaks[a].amplitude.partial_cmp(&peaks[b].amplitude).unwrap_or(std::cmp::Ordering::Equal));
+        if let Some(j) = main {
+            shoulders.push((i, j));
+        }
+    }
+    shoulders
+}
+
+/// 按肩峰策略处理拟合后的峰
+///
+/// - separate：保留肩峰，在其元数据 `shoulder_of` 中记录主峰ID
+/// - merge：肩峰面积并入主峰（面积误差按平方和合成，边界取并集），主峰元数据 `merged_shoulders` 记录被并入的峰
+/// - ignore：丢弃肩峰，主峰不变
+///
+/// 主峰本身也是更高峰的肩峰时，合并沿链条归到最高的峰
+pub fn apply_shoulder_policy(curve: &Curve, peaks: Vec<Peak>, policy: ShoulderPolicy) -> Vec<Peak> {
+    let mut peaks = peaks;
+    peaks.sort_by(|a, b| a.center.partial_cmp(&b.center).unwrap_or(std::cmp::Ordering::Equal));
+    let shoulders = find_shoulders(curve, &peaks);
+    if shoulders.is_empty() {
+        return peaks;
+    }
+    let main_of: HashMap<usize, usize> = shoulders.into_iter().collect();
+
+    if policy == ShoulderPolicy::Separate {
+        for (&shoulder, &main) in &main_of {
+            let main_id = peaks[main].id.clone();
+            peaks[shoulder].add_metadata("shoulder_of".to_string(), Value::String(main_id));
+        }
+        return peaks;
+    }
+
+    // 主峰振幅严格大于肩峰，链条不会成环
+    let root = |mut i: usize| {
+        while let Some(&j) = main_of.get(&i) {
+            i = j;
+        }
+        i
+    };
+    let mut absorbed: HashMap<usize, Vec<usize>> = HashMap::new();
+    for &shoulder in main_of.keys() {
+        absorbed.entry(root(shoulder)).or_default().push(shoulder);
+    }
+
+    let mut result = Vec::new();
+    for (i, peak) in peaks.iter().enumerate() {
+        if main_of.contains_key(&i) {
+            continue;
+        }
+        let mut peak = peak.clone();
+        if let Some(members) = absorbed.get_mut(&i) {
+            members.sort_unstable();
+            let ids: Vec<Value> = members.iter().map(|&m| Value::String(peaks[m].id.clone())).collect();
+            if policy == ShoulderPolicy::Merge {
+                for &m in members.iter() {
+                    let shoulder = &peaks[m];
+                    peak.area += shoulder.area;
+                    peak.area_error = (peak.area_error.powi(2) + shoulder.area_error.powi(2)).sqrt();
+                    if shoulder.left_boundary < peak.left_boundary {
+                        peak.left_boundary = shoulder.left_boundary;
+                    }
+                    if shoulder.right_boundary > peak.right_boundary {
+                        peak.right_boundary = shoulder.right_boundary;
+                    }
+                }
+                peak.peak_span = peak.right_boundary - peak.left_boundary;
+                peak.add_metadata("merged_shoulders".to_string(), Value::Array(ids));
+            } else {
+                peak.add_metadata("ignored_shoulders".to_string(), Value::Array(ids));
+            }
+        }
+        result.push(peak);
+    }
+    result
+}
+
+/// 报告的峰中心（峰顶）定义
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ApexDefinition {
+    /// 峰附近强度最高的采样点
+    RawMax,
+    /// 峰形拟合得到的中心
+    FittedCenter,
+    /// 峰顶两侧强度不低于峰高10%的连续区域的强度加权平均位置
+    Centroid,
+}
+
+impl ApexDefinition {
+    /// 解析 "raw_max" / "fitted_center" / "centroid"
+    pub fn from_name(name: &str) -> Result<Self, ProcessingError> {
+        match name {
+            "raw_max" => Ok(Self::RawMax),
+            "fitted_center" => Ok(Self::FittedCenter),
+            "centroid" => Ok(Self::Centroid),
+            _ => Err(ProcessingError::ConfigError(format!(
+                "不支持的峰顶定义: {}（可选 raw_max、fitted_center、centroid）", name
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::RawMax => "raw_max",
+            Self::FittedCenter => "fitted_center",
+            Self::Centroid => "centroid",
+        }
+    }
+}
+
+/// 峰附近（拟合中心 ± 半高半宽，至少包含最近的采样点）强度最高的采样点下标
+fn raw_max_index(curve: &Curve, peak: &Peak) -> Option<usize> {
+    let n = curve.x_values.len().min(curve.y_values.len());
+    let nearest = (0..n).min_by(|&a, &b| {
+        (curve.x_values[a] - peak.center).abs().partial_cmp(&(curve.x_values[b] - peak.center).abs()).unwrap_or(std::cmp::Ordering::Equal)
+    })?;
+    let half_width = peak.hwhm.max(0.0);
+    (0..n)
+        .filter(|&i| i == nearest || (curve.x_values[i] - peak.center).abs() <= half_width)
+        .max_by(|&a, &b| curve.y_values[a].partial_cmp(&curve.y_values[b]).unwrap_or(std::cmp::Ordering::Equal))
+}
+
+/// 从峰顶采样点向两侧扩展，直到强度低于峰高10%或开始回升（进入相邻峰），返回区域内的强度加权平均位置
+fn centroid_around(curve: &Curve, apex: usize) -> f64 {
+    let n = curve.x_values.len().min(curve.y_values.len());
+    let floor = 0.1 * curve.y_values[apex];
+    let mut left = apex;
+    while left > 0 && curve.y_values[left - 1] >= floor && curve.y_values[left - 1] <= curve.y_values[left] {
+        left -= 1;
+    }
+    let mut right = apex;
+    while right + 1 < n && curve.y_values[right + 1] >= floor && curve.y_values[right + 1] <= curve.y_values[right] {
+        right += 1;
+    }
+    let (weighted, total) = (left..=right)
+        .fold((0.0, 0.0), |(weighted, total), i| (weighted + curve.x_values[i] * curve.y_values[i], total + curve.y_values[i]));
+    if total > 0.0 { weighted / total } else { curve.x_values[apex] }
+}
+
+/// 按峰顶定义设置报告的峰中心，原拟合中心记录在元数据 `fitted_center` 中
+pub fn apply_apex_definition(curve: &Curve, peak: &mut Peak, definition: ApexDefinition) {
+    let fitted_center = peak.center;
+    let center = match (definition, raw_max_index(curve, peak)) {
+        (ApexDefinition::FittedCenter, _) | (_, None) => fitted_center,
+        (ApexDefinition::RawMax, Some(apex)) => curve.x_values[apex],
+        (ApexDefinition::Centroid, Some(apex)) => centroid_around(curve, apex),
+    };
+    peak.center = center;
+    peak.add_metadata("fitted_center".to_string(), serde_json::json!(fitted_center));
+    peak.add_metadata("apex_definition".to_string(), Value::String(definition.name().to_string()));
+}
+
+/// 分析配方：检测、拟合与重叠处理的一组设置，可原样应用到多个容器
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnalysisRecipe {
+    pub name: String,
+    pub detection_method: String,
+    pub fitting_method: String,
+    pub overlapping_processing: String,
+    pub sensitivity: f64,
+    pub adaptive_sensitivity: bool,
+    pub threshold_multiplier: f64,
+    pub quality_threshold: f64,
+    pub boundary_method: String,
+    pub shoulder_policy: String,
+    pub apex_definition: String,
+    pub force_shape: Option<String>,
+    pub max_iterations: Option<usize>,
+    pub convergence_threshold: Option<f64>,
+}
+
+impl Default for AnalysisRecipe {
+    fn default() -> Self {
+        Self {
+            name: "default".to_string(),
+            detection_method: "auto".to_string(),
+            fitting_method: "auto".to_string(),
+            overlapping_processing: "auto".to_string(),
+            sensitivity: 0.5,
+            adaptive_sensitivity: false,
+            threshold_multiplier: 3.0,
+            quality_threshold: 0.7,
+            boundary_method: "threshold".to_string(),
+            shoulder_policy: "separate".to_string(),
+            apex_definition: "fitted_center".to_string(),
+            force_shape: None,
+            max_iterations: None,
+            convergence_threshold: None,
+        }
+    }
+}
+
+impl AnalysisRecipe {
+    /// 转换为 `PeakAnalyzer::process` 使用的配置
+    pub fn to_config(&self) -> Value {
+        serde_json::json!({
+            "detection_method": self.detection_method,
+            "fitting_method": self.fitting_method,
+            "overlapping_processing": self.overlapping_processing,
+            "sensitivity": self.sensitivity,
+            "adaptive_sensitivity": self.adaptive_sensitivity,
+            "threshold_multiplier": self.threshold_multiplier,
+            "quality_threshold": self.quality_threshold,
+            "boundary_method": self.boundary_method,
+            "shoulder_policy": self.shoulder_policy,
+            "apex_definition": self.apex_definition,
+            "force_shape": self.force_shape,
+            "max_iterations": self.max_iterations,
+            "convergence_threshold": self.convergence_threshold
+        })
+    }
+}
+
+/// 按配方处理单个容器的结果；失败时 `error` 给出原因，其余字段为空
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecipeRunResult {
+    /// 容器在输入中的位置
+    pub index: usize,
+    pub curves: Vec<Curve>,
+    pub peaks: Vec<Peak>,
+    pub metadata: HashMap<String, Value>,
+    pub processing_time_ms: u64,
+    pub error: Option<String>,
+}
 
 /// 峰分析器
 #[derive(Debug)]
@@ -30,6 +327,54 @@ impl PeakAnalyzer {
             _ => Err(ProcessingError::ConfigError(format!("不支持的峰分析方法: {}", method))),
         }
     }
+    
+    /// 把同一配方并行应用到多个容器，结果按输入顺序返回并附带每个容器的耗时
+    ///
+    /// 每个容器在单独的任务中处理，其中的峰拟合受全局并行任务上限约束（见 `thread_pool::set_thread_pool_size`）
+    pub async fn process_many(containers: Vec<DataContainer>, recipe: &AnalysisRecipe) -> Vec<RecipeRunResult> {
+        let config = recipe.to_config();
+        let handles: Vec<_> = containers.into_iter()
+            .map(|container| {
+                let config = config.clone();
+                tokio::spawn(async move {
+                    let start_time = std::time::Instant::now();
+                    let result = Self::new().process(container, config).await;
+                    (result, start_time.elapsed().as_millis() as u64)
+                })
+            })
+            .collect();
+        
+        let mut runs = Vec::with_capacity(handles.len());
+        for (index, handle) in handles.into_iter().enumerate() {
+            let (result, processing_time_ms) = match handle.await {
+                Ok(outcome) => outcome,
+                Err(e) => (Err(ProcessingError::ProcessError(format!("分析任务异常终止: {}", e))), 0),
+            };
+            runs.push(match result {
+                Ok(result) => RecipeRunResult {
+                    index,
+                    curves: result.curves,
+                    peaks: result.peaks,
+                    metadata: result.metadata,
+                    processing_time_ms,
+                    error: None,
+                },
+                Err(e) => {
+                    log::warn!("⚠️ 容器 {} 按配方 {} 处理失败: {}", index, recipe.name, e);
+                    RecipeRunResult {
+                        index,
+                        curves: Vec::new(),
+                        peaks: Vec::new(),
+                        metadata: HashMap::new(),
+                        processing_time_ms,
+                        error: Some(e.to_string()),
+                    }
+                }
+            });
+        }
+        
+        runs
+    }
 }
 
 #[async_trait]
@@ -82,12 +427,99 @@ impl Processor for PeakAnalyzer {
                     "default": 0.5,
                     "description": "检测敏感度"
                 },
+                "adaptive_sensitivity": {
+                    "type": "boolean",
+                    "default": false,
+                    "description": "按每条曲线自身的噪声基底重新计算检测阈值（基线 + threshold_multiplier × 噪声），忽略全局敏感度"
+                },
+                "threshold_multiplier": {
+                    "type": "number",
+                    "minimum": 0.0,
+                    "default": 3.0,
+                    "description": "自适应敏感度下阈值相对噪声的倍数"
+                },
                 "quality_threshold": {
                     "type": "number",
                     "minimum": 0.0,
                     "maximum": 1.0,
                     "default": 0.7,
                     "description": "峰质量阈值"
+                },
+                "boundary_method": {
+                    "type": "string",
+                    "enum": ["threshold", "valley", "inflection"],
+                    "default": "threshold",
+                    "description": "峰边界定义：10%峰高阈值、相邻峰之间的谷底、或拐点切线与基线的交点"
+                },
+                "shoulder_policy": {
+                    "type": "string",
+                    "enum": ["separate", "merge", "ignore"],
+                    "default": "separate",
+                    "description": "肩峰（相邻更高峰FWHM内、无明显低谷的峰）作为独立峰报告、并入主峰面积、或丢弃"
+                },
+                "apex_definition": {
+                    "type": "string",
+                    "enum": ["raw_max", "fitted_center", "centroid"],
+                    "default": "fitted_center",
+                    "description": "报告的峰中心：峰附近强度最高的采样点、拟合中心、或峰顶两侧10%峰高以上区域的强度加权平均位置"
+                },
+                "noise_region": {
+                    "type": "array",
+                    "items": {"type": "number"},
+                    "minItems": 2,
+                    "maxItems": 2,
+                    "description": "无信号的背景区间 [x_min, x_max]，噪声基底与检测阈值只由该区间计算；不设置时使用全曲线MAD估计"
+                },
+                "min_peak_width": {
+                    "type": "number",
+                    "minimum": 0.0,
+                    "description": "最小峰宽（半高全宽），检测后剔除更窄的噪声尖峰；不设置时不限制"
+                },
+                "max_peak_width": {
+                    "type": "number",
+                    "minimum": 0.0,
+                    "description": "最大峰宽（半高全宽），检测后剔除更宽的鼓包；不设置时不限制"
+                },
+                "shape_deadband": {
+                    "type": "number",
+                    "minimum": 0.0,
+                    "default": 0.1,
+                    "description": "峰形方向判定的对称容差：右/左半峰宽之比偏离1不超过该值时标记为 symmetric"
+                },
+                "fail_fast": {
+                    "type": "boolean",
+                    "default": false,
+                    "description": "任一曲线分析失败时立即返回错误，而不是记录失败并返回其余结果"
+                },
+                "fixed_parameters": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "default": [],
+                    "description": "拟合时保持初始值不变的参数（如 center、sigma）"
+                },
+                "force_shape": {
+                    "type": ["string", "null"],
+                    "enum": ["gaussian", "lorentzian", "pseudo_voigt", "emg", "bi_gaussian", "gmg", "nlc", "pearson_vii", "pearson_iv", "auto_shape", null],
+                    "default": null,
+                    "description": "对所有峰强制使用同一峰形，忽略峰形分析器的推荐；auto_shape 对每个峰拟合候选峰形（含 Pearson-VII/IV）并按AIC选择"
+                },
+                "max_iterations": {
+                    "type": ["integer", "null"],
+                    "minimum": 1,
+                    "maximum": 10000,
+                    "default": null,
+                    "description": "拟合优化器的最大迭代次数，不设置时使用拟合器默认值（100）"
+                },
+                "convergence_threshold": {
+                    "type": ["number", "null"],
+                    "exclusiveMinimum": 0.0,
+                    "default": null,
+                    "description": "拟合优化器的收敛阈值，不设置时使用拟合器默认值（1e-6）"
+                },
+                "strategy_thresholds": {
+                    "type": ["object", "null"],
+                    "default": null,
+                    "description": "重叠策略自动选择的分界阈值 {light_overlap, medium_overlap, extreme_overlap, low_snr}，默认 0.1 / 0.5 / 1.0 / 10.0"
                 }
             }
         })
@@ -98,6 +530,9 @@ impl Processor for PeakAnalyzer {
         input: DataContainer,
         config: serde_json::Value,
     ) -> Result<ProcessingResult, ProcessingError> {
+        // 超出模式范围的数值参数截断到边界，警告随结果返回
+        let mut config = config;
+        let config_warnings = clamp_config_to_schema(&self.config_schema(), &mut config);
         let detection_method = config.get("detection_method")
             .and_then(|v| v.as_str())
             .unwrap_or("auto")
@@ -113,46 +548,161 @@ impl Processor for PeakAnalyzer {
         let sensitivity = config.get("sensitivity")
             .and_then(|v| v.as_f64())
             .unwrap_or(0.5);
+        let adaptive_sensitivity = config.get("adaptive_sensitivity")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let threshold_multiplier = config.get("threshold_multiplier")
+            .and_then(|v| v.as_f64())
+            .unwrap_or(3.0);
         let quality_threshold = config.get("quality_threshold")
             .and_then(|v| v.as_f64())
             .unwrap_or(0.7);
+        let fixed_parameters = config.get("fixed_parameters")
+            .cloned()
+            .unwrap_or_else(|| Value::Array(Vec::new()));
+        let force_shape = config.get("force_shape")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+        if let Some(name) = force_shape.as_deref().filter(|name| *name != AUTO_SHAPE) {
+            PeakShapeType::from_name(name)?;
+        }
+        let fail_fast = config.get("fail_fast")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let boundary_method = config.get("boundary_method")
+            .and_then(|v| v.as_str())
+            .unwrap_or("threshold")
+            .to_string();
+        let shoulder_policy = ShoulderPolicy::from_name(
+            config.get("shoulder_policy").and_then(|v| v.as_str()).unwrap_or("separate")
+        )?;
+        let apex_definition = ApexDefinition::from_name(
+            config.get("apex_definition").and_then(|v| v.as_str()).unwrap_or("fitted_center")
+        )?;
+        let shape_deadband = config.get("shape_deadband")
+            .and_then(|v| v.as_f64())
+            .unwrap_or(0.1)
+            .max(0.0);
+        let noise_region = noise_region_from_config(&config)?;
+        let width_bounds = PeakWidthBounds::from_config(&config)?;
+        let (max_iterations, convergence_threshold) = MultiPeakFitter::iteration_limits_from_config(&config)?;
+        let strategy_thresholds = StrategyThresholds::from_config(&config)?;
+        let (input, auto_baseline_method) = match config.get("auto_baseline") {
+            None | Some(Value::Null) | Some(Value::Bool(false)) => (input, None),
+            Some(setting) => Self::apply_auto_baseline(input, setting).await?,
+        };
         
         let mut result_curves = Vec::new();
         let mut result_peaks = Vec::new();
+        let mut failed_curves = Vec::new();
+        let mut empty_curves = Vec::new();
+        let mut effective_thresholds = Vec::new();
+        let mut width_rejections = Vec::new();
         let mut metadata = HashMap::new();
         
-        // 对每条曲线进行峰分析
+        // 对每条曲线进行峰分析，单条曲线失败时记录原因并继续（fail_fast 时立即返回错误）
         for curve in input.curves.iter() {
-            // 1. 峰检测
-            let detected_peaks = self.detect_peaks(curve, &detection_method, sensitivity).await?;
-            
-            // 2. 重叠峰处理
-            let processed_peaks = if detected_peaks.len() > 1 && overlapping_processing != "none" {
-                self.process_overlapping_peaks(&detected_peaks, curve, &overlapping_processing).await?
+            let mut curve = curve.clone();
+            let curve_sensitivity = if adaptive_sensitivity {
+                let (effective_sensitivity, record) = Self::adaptive_curve_sensitivity(&curve, threshold_multiplier, noise_region);
+                curve.add_metadata("effective_threshold".to_string(), record["effective_threshold"].clone());
+                curve.add_metadata("effective_sensitivity".to_string(), record["effective_sensitivity"].clone());
+                effective_thresholds.push(record);
+                effective_sensitivity
             } else {
-                detected_peaks
+                sensitivity
             };
             
-            // 3. 峰拟合
-            let fitted_peaks = self.fit_peaks(&processed_peaks, curve, &fitting_method).await?;
-            
-            // 4. 质量过滤
-            let quality_peaks: Vec<_> = fitted_peaks.into_iter()
-                .filter(|peak| peak.get_quality_score() >= quality_threshold)
-                .collect();
-            
-            // 5. 增强峰信息
-            let enhanced_peaks = self.enhance_peak_information(&quality_peaks, curve).await?;
+            let analysis = self.analyze_curve(
+                &mut curve,
+                &detection_method,
+                &fitting_method,
+                &overlapping_processing,
+                curve_sensitivity,
+                quality_threshold,
+                &fixed_parameters,
+                force_shape.as_deref(),
+                &boundary_method,
+                shoulder_policy,
+                apex_definition,
+                shape_deadband,
+                noise_region,
+                &width_bounds,
+                max_iterations,
+                convergence_threshold,
+                &strategy_thresholds,
+            ).await;
+            if let Some(Value::Array(rejections)) = curve.metadata.get("width_rejections") {
+                width_rejections.extend(rejections.iter().cloned());
+            }
             
-            result_peaks.extend(enhanced_peaks);
-            result_curves.push(curve.clone());
+            match analysis {
+                Ok((mut peaks, no_peaks_reason)) => {
+                    if let Some(reason) = no_peaks_reason {
+                        log::info!("ℹ️ 曲线 {} 未检测到峰: {}", curve.id, reason);
+                        empty_curves.push(serde_json::json!({
+                            "curve_id": curve.id,
+                            "reason": reason,
+                        }));
+                    }
+                    // 峰面积单位 = 强度单位 × X单位
+                    let area_unit = curve.area_unit();
+                    for peak in &mut peaks {
+                        peak.area_unit = Some(area_unit.clone());
+                    }
+                    curve.stamp_area_units();
+                    result_peaks.extend(peaks);
+                    result_curves.push(curve);
+                },
+                Err(e) if fail_fast => return Err(e),
+                Err(e) => {
+                    log::warn!("⚠️ 曲线 {} 峰分析失败: {}", curve.id, e);
+                    failed_curves.push(serde_json::json!({
+                        "curve_id": curve.id,
+                        "reason": e.to_string(),
+                    }));
+                },
+            }
         }
         
         // 更新元数据
+        metadata.insert("failed_curves".to_string(), Value::Array(failed_curves));
+        metadata.insert("no_peaks_found".to_string(), Value::Bool(result_peaks.is_empty()));
+        metadata.insert("no_peak_curves".to_string(), Value::Array(empty_curves));
         metadata.insert("total_peaks".to_string(), Value::Number(serde_json::Number::from(result_peaks.len())));
+        metadata.insert("total_integrated_area".to_string(), serde_json::json!(result_peaks.iter().map(|peak| peak.area).sum::<f64>()));
         metadata.insert("detection_method".to_string(), Value::String(detection_method));
         metadata.insert("fitting_method".to_string(), Value::String(fitting_method));
+        metadata.insert("boundary_method".to_string(), Value::String(boundary_method));
+        metadata.insert("shoulder_policy".to_string(), Value::String(shoulder_policy.name().to_string()));
+        metadata.insert("apex_definition".to_string(), Value::String(apex_definition.name().to_string()));
+        if let Some(name) = force_shape {
+            metadata.insert("force_shape".to_string(), Value::String(name));
+        }
+        if let Some((start, end)) = noise_region {
+            metadata.insert("noise_region".to_string(), serde_json::json!([start, end]));
+        }
+        if width_bounds.is_active() {
+            metadata.insert("min_peak_width".to_string(), serde_json::json!(width_bounds.min));
+            metadata.insert("max_peak_width".to_string(), serde_json::json!(width_bounds.max));
+            metadata.insert("width_rejections".to_string(), Value::Array(width_rejections));
+        }
+        if let Some(limit) = max_iterations {
+            metadata.insert("max_iterations".to_string(), serde_json::json!(limit));
+        }
+        if let Some(threshold) = convergence_threshold {
+            metadata.insert("convergence_threshold".to_string(), serde_json::json!(threshold));
+        }
+        metadata.insert("auto_baseline_applied".to_string(), Value::Bool(auto_baseline_method.is_some()));
+        if let Some(method) = auto_baseline_method {
+            metadata.insert("auto_baseline_method".to_string(), Value::String(method));
+        }
+        metadata.insert("adaptive_sensitivity".to_string(), Value::Bool(adaptive_sensitivity));
+        if adaptive_sensitivity {
+            metadata.insert("effective_thresholds".to_string(), Value::Array(effective_thresholds));
+        }
         metadata.insert("quality_threshold".to_string(), Value::Number(serde_json::Number::from_f64(quality_threshold).unwrap()));
+        metadata.insert("config_warnings".to_string(), serde_json::to_value(&config_warnings)?);
         
         Ok(ProcessingResult {
             curves: result_curves,
@@ -163,13 +713,162 @@ impl Processor for PeakAnalyzer {
 }
 
 impl PeakAnalyzer {
-    /// 峰检测
+    /// 检测前扣除基线：`auto_baseline` 为方法名（见 `BaselineProcessor`，含 "auto"）、`true`（即 "auto"）
+    /// 或完整的基线配置对象。校正后的曲线替换输入曲线，曲线元数据记录 `baseline_corrected` 与 `baseline_method`
+    async fn apply_auto_baseline(mut input: DataContainer, setting: &Value) -> Result<(DataContainer, Option<String>), ProcessingError> {
+        let mut baseline_config = match setting {
+            Value::String(method) => serde_json::json!({"method": method}),
+            Value::Bool(true) => serde_json::json!({"method": "auto"}),
+            Value::Object(_) => setting.clone(),
+            other => return Err(ProcessingError::ConfigError(format!("无效的 auto_baseline 配置: {}", other))),
+        };
+        baseline_config["output_baseline"] = Value::Bool(false);
+        
+        let mut curves = DataContainer::new();
+        curves.curves = std::mem::take(&mut input.curves);
+        let corrected = crate::core::processors::base::Processor::process(&BaselineProcessor::new(), curves, baseline_config).await?;
+        let method = corrected.metadata.get("baseline_correction_method")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown")
+            .to_string();
+        log::info!("📉 峰分析前自动扣除基线: {}", method);
+        
+        input.curves = corrected.curves;
+        for curve in &mut input.curves {
+            curve.add_metadata("baseline_corrected".to_string(), Value::Bool(true));
+            curve.add_metadata("baseline_method".to_string(), Value::String(method.clone()));
+        }
+        Ok((input, Some(method)))
+    }
+    
+    /// 分析单条曲线：检测、重叠峰处理、拟合、质量过滤与信息增强
+    ///
+    /// 未得到任何峰时同时返回原因（见 `NO_PEAKS_*` 常量），便于前端提示调整敏感度；
+    /// 因峰宽约束被剔除的候选峰记录在曲线元数据 `width_rejections` 中
+    async fn analyze_curve(
+        &self,
+        curve: &mut crate::core::data::Curve,
+        detection_method: &str,
+        fitting_method: &str,
+        overlapping_processing: &str,
+        sensitivity: f64,
+        quality_threshold: f64,
+        fixed_parameters: &Value,
+        force_shape: Option<&str>,
+        boundary_method: &str,
+        shoulder_policy: ShoulderPolicy,
+        apex_definition: ApexDefinition,
+        shape_deadband: f64,
+        noise_region: Option<(f64, f64)>,
+        width_bounds: &PeakWidthBounds,
+        max_iterations: Option<usize>,
+        convergence_threshold: Option<f64>,
+        strategy_thresholds: &StrategyThresholds,
+    ) -> Result<(Vec<crate::core::data::Peak>, Option<&'static str>), ProcessingError> {
+        // 0. 退化曲线检查
+        if curve.y_values.len() < 3 || curve.x_values.len() != curve.y_values.len() {
+            return Err(ProcessingError::DataError(format!(
+                "曲线 {} 数据点不足或X/Y长度不一致", curve.id
+            )));
+        }
+        if curve.x_values.iter().chain(curve.y_values.iter()).any(|v| !v.is_finite()) {
+            return Err(ProcessingError::DataError(format!(
+                "曲线 {} 包含非有限数值 (NaN/Inf)", curve.id
+            )));
+        }
+        
+        if Self::is_flat(curve) {
+            return Ok((Vec::new(), Some(NO_PEAKS_FLAT_CURVE)));
+        }
+        
+        // 1. 峰检测
+        let (detected_peaks, width_rejections) = self.detect_peaks(curve, detection_method, sensitivity, noise_region, width_bounds).await?;
+        if !width_rejections.is_empty() {
+            curve.add_metadata("width_rejections".to_string(), Value::Array(width_rejections));
+        }
+        if detected_peaks.is_empty() {
+            return Ok((Vec::new(), Some(NO_PEAKS_BELOW_THRESHOLD)));
+        }
+        
+        // 2. 重叠峰处理，实际使用的方法记录在曲线元数据 overlapping_method 中
+        let processed_peaks = if detected_peaks.len() > 1 && overlapping_processing != "none" {
+            let method = if overlapping_processing == "auto" {
+                self.select_overlapping_method(&detected_peaks, curve, strategy_thresholds)
+            } else {
+                overlapping_processing.to_string()
+            };
+            curve.add_metadata("overlapping_method".to_string(), Value::String(method.clone()));
+            self.process_overlapping_peaks(&detected_peaks, curve, &method, strategy_thresholds).await?
+        } else {
+            detected_peaks
+        };
+        
+        // 3. 峰拟合
+        let fitted_peaks = self.fit_peaks(&processed_peaks, curve, fitting_method, fixed_parameters, force_shape, max_iterations, convergence_threshold).await?;
+        
+        // 4. 质量过滤
+        let quality_peaks: Vec<_> = fitted_peaks.into_iter()
+            .filter(|peak| peak.get_quality_score() >= quality_threshold)
+            .collect();
+        
+        if quality_peaks.is_empty() {
+            return Ok((Vec::new(), Some(NO_PEAKS_BELOW_QUALITY)));
+        }
+        
+        // 5. 肩峰处理
+        let mut quality_peaks = apply_shoulder_policy(curve, quality_peaks, shoulder_policy);
+        
+        // 6. 统一峰顶定义，后续边界与拖尾计算都基于报告的中心
+        for peak in quality_peaks.iter_mut() {
+            apply_apex_definition(curve, peak, apex_definition);
+        }
+        
+        // 7. 增强峰信息
+        let peaks = self.enhance_peak_information(&quality_peaks, curve, boundary_method, shape_deadband).await?;
+        Ok((peaks, None))
+    }
+    
+    /// 自适应敏感度：由曲线自身的噪声基底得到检测阈值，并换算为相对最大强度的敏感度
+    ///
+    /// 给出背景区间时噪声基底只由该区间计算。返回 (敏感度, 记录该曲线有效阈值的元数据)
+    fn adaptive_curve_sensitivity(curve: &crate::core::data::Curve, threshold_multiplier: f64, noise_region: Option<(f64, f64)>) -> (f64, Value) {
+        let noise_floor = estimate_noise_floor_in_region(curve, noise_region);
+        let threshold = noise_floor.threshold(threshold_multiplier);
+        let max_intensity = curve.y_values.iter().fold(0.0_f64, |a, &b| a.max(b));
+        let effective_sensitivity = if max_intensity > 0.0 {
+            (threshold / max_intensity).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        
+        log::info!("🎚️ 曲线 {} 自适应阈值: 基线={:.3}, 噪声={:.3}, 阈值={:.3}, 敏感度={:.4}",
+            curve.id, noise_floor.baseline, noise_floor.noise, threshold, effective_sensitivity);
+        
+        (effective_sensitivity, serde_json::json!({
+            "curve_id": curve.id,
+            "baseline": noise_floor.baseline,
+            "noise_floor": noise_floor.noise,
+            "effective_threshold": threshold,
+            "effective_sensitivity": effective_sensitivity,
+        }))
+    }
+    
+    /// 曲线是否平坦：强度起伏相对于强度水平可忽略
+    fn is_flat(curve: &crate::core::data::Curve) -> bool {
+        let (min, max) = curve.y_values.iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &y| (lo.min(y), hi.max(y)));
+        max - min <= 1e-9 * max.abs().max(1.0)
+    }
+    
+    /// 峰检测，返回保留的峰与因峰宽约束被剔除的记录
     async fn detect_peaks(
         &self,
         curve: &crate::core::data::Curve,
         method: &str,
         sensitivity: f64,
-    ) -> Result<Vec<crate::core::data::Peak>, ProcessingError> {
+        noise_region: Option<(f64, f64)>,
+        width_bounds: &PeakWidthBounds,
+    ) -> Result<(Vec<crate::core::data::Peak>, Vec<Value>), ProcessingError> {
         let actual_method = if method == "auto" {
             self.select_detection_method(curve)
         } else {
@@ -177,8 +876,17 @@ impl PeakAnalyzer {
         };
         
         // 创建检测器配置
-        let config = ProcessorConfig::new(ProcessorType::PeakDetection, actual_method)
+        let mut config = ProcessorConfig::new(ProcessorType::PeakDetection, actual_method)
             .with_parameter("sensitivity".to_string(), Value::Number(serde_json::Number::from_f64(sensitivity).unwrap()));
+        if let Some((start, end)) = noise_region {
+            config = config.with_parameter("noise_region".to_string(), serde_json::json!([start, end]));
+        }
+        if let Some(min) = width_bounds.min {
+            config = config.with_parameter("min_peak_width".to_string(), serde_json::json!(min));
+        }
+        if let Some(max) = width_bounds.max {
+            config = config.with_parameter("max_peak_width".to_string(), serde_json::json!(max));
+        }
         
         // 创建检测器
         let detector = crate::core::processors::core::ProcessorFactory::create_processor(config.clone())?;
@@ -190,17 +898,22 @@ impl PeakAnalyzer {
             spectra: vec![],
         };
         
-        // 执行检测
-        let result = detector.process(input, serde_json::to_value(&config)?).await?;
+        // 执行检测：检测器从配置顶层读取 sensitivity、noise_region 等键，因此只传参数表。
+        // 传整个 ProcessorConfig 时这些键嵌套在 "parameters" 下，检测器会静默使用自身默认值
+        let result = detector.process(input, serde_json::to_value(&config.parameters)?).await?;
+        let width_rejections = match result.metadata.get("width_rejections") {
+            Some(Value::Array(rejections)) => rejections.clone(),
+            _ => Vec::new(),
+        };
         if let Some(curve) = result.curves.first() {
-            Ok(curve.peaks.clone())
+            Ok((curve.peaks.clone(), width_rejections))
         } else {
-            Ok(vec![])
+            Ok((vec![], width_rejections))
         }
     }
     
     /// 选择检测方法
-    fn select_detection_method(&self, curve: &crate::core::data::Curve) -> String {
+    pub fn select_detection_method(&self, curve: &crate::core::data::Curve) -> String {
         // 分析曲线特征
         let noise_level = self.estimate_noise_level(curve);
         let signal_strength = self.estimate_signal_strength(curve);
@@ -220,19 +933,15 @@ impl PeakAnalyzer {
         peaks: &[crate::core::data::Peak],
         curve: &crate::core::data::Curve,
         method: &str,
+        strategy_thresholds: &StrategyThresholds,
     ) -> Result<Vec<crate::core::data::Peak>, ProcessingError> {
         if method == "none" {
             return Ok(peaks.to_vec());
         }
         
-        let actual_method = if method == "auto" {
-            self.select_overlapping_method(peaks, curve)
-        } else {
-            method.to_string()
-        };
-        
         // 创建重叠峰处理器配置
-        let config = ProcessorConfig::new(ProcessorType::OverlappingPeaks, actual_method);
+        let config = ProcessorConfig::new(ProcessorType::OverlappingPeaks, method.to_string())
+            .with_parameter("strategy_thresholds".to_string(), serde_json::to_value(strategy_thresholds)?);
         
         // 创建处理器
         let processor = crate::core::processors::core::ProcessorFactory::create_processor(config.clone())?;
@@ -255,20 +964,16 @@ impl PeakAnalyzer {
         }
     }
     
-    /// 选择重叠峰处理方法
-    fn select_overlapping_method(&self, peaks: &[crate::core::data::Peak], curve: &crate::core::data::Curve) -> String {
-        let overlap_level = self.estimate_overlap_level(peaks);
-        let snr = self.estimate_snr(curve);
-        
-        if overlap_level > 0.8 && snr < 10.0 {
-            "extreme_overlap".to_string()
-        } else if overlap_level > 0.5 {
-            "sharpen_cwt".to_string()
-        } else if overlap_level > 0.2 {
-            "fbf".to_string()
-        } else {
-            "none".to_string()
-        }
+    /// 选择重叠峰处理方法：与极度重叠处理器相同，按 `strategy_thresholds` 的分界自动选择策略
+    fn select_overlapping_method(
+        &self,
+        peaks: &[crate::core::data::Peak],
+        curve: &crate::core::data::Curve,
+        strategy_thresholds: &StrategyThresholds,
+    ) -> String {
+        OverlappingPeakStrategy::auto_select(peaks, curve, strategy_thresholds)
+            .get_processor_method()
+            .to_string()
     }
     
     /// 峰拟合
@@ -277,18 +982,38 @@ impl PeakAnalyzer {
         peaks: &[crate::core::data::Peak],
         curve: &crate::core::data::Curve,
         method: &str,
+        fixed_parameters: &Value,
+        force_shape: Option<&str>,
+        max_iterations: Option<usize>,
+        convergence_threshold: Option<f64>,
     ) -> Result<Vec<crate::core::data::Peak>, ProcessingError> {
-        let actual_method = if method == "auto" {
+        // 强制峰形由多峰拟合器处理，自动模式下不再按复杂度选择单一峰形方法
+        let actual_method = if method == "auto" && force_shape.is_some() {
+            "multi_peak".to_string()
+        } else if method == "auto" {
             self.select_fitting_method(peaks, curve)
         } else {
             method.to_string()
         };
         
         let mut fitted_peaks = Vec::new();
+        // 每次只把一个峰放到曲线上拟合，同一曲线的全部峰作为多峰拟合的初值另行传入
+        let seed_peaks = serde_json::to_value(peaks)?;
         
         for peak in peaks {
             // 创建拟合器配置
-            let config = ProcessorConfig::new(ProcessorType::PeakFitting, actual_method.clone());
+            let mut config = ProcessorConfig::new(ProcessorType::PeakFitting, actual_method.clone())
+                .with_parameter("fixed_parameters".to_string(), fixed_parameters.clone())
+                .with_parameter("seed_peaks".to_string(), seed_peaks.clone());
+            if let Some(name) = force_shape {
+                config = config.with_parameter("force_shape".to_string(), Value::String(name.to_string()));
+            }
+            if let Some(limit) = max_iterations {
+                config = config.with_parameter("max_iterations".to_string(), serde_json::json!(limit));
+            }
+            if let Some(threshold) = convergence_threshold {
+                config = config.with_parameter("convergence_threshold".to_string(), serde_json::json!(threshold));
+            }
             
             // 创建拟合器
             let fitter = crate::core::processors::core::ProcessorFactory::create_processor(config.clone())?;
@@ -302,12 +1027,15 @@ impl PeakAnalyzer {
                 spectra: vec![],
             };
             
-            // 执行拟合
-            let result = fitter.process(input, serde_json::to_value(&config)?).await?;
-            if let Some(curve) = result.curves.first() {
-                if let Some(fitted_peak) = curve.peaks.first() {
-                    fitted_peaks.push(fitted_peak.clone());
-                }
+            // 执行拟合，同时进行的拟合数受全局并行任务上限约束
+            // 拟合器把拟合后的峰放在 result.peaks 中，result.curves 原样返回输入曲线，
+            // 其中的峰仍是上面放入的未拟合检测峰，不能从那里取结果
+            let result = {
+                let _permit = crate::core::utils::thread_pool::acquire_task_permit().await;
+                fitter.process(input, serde_json::to_value(&config)?).await?
+            };
+            if let Some(fitted_peak) = result.peaks.into_iter().next() {
+                fitted_peaks.push(fitted_peak);
             }
         }
         
@@ -332,6 +1060,8 @@ impl PeakAnalyzer {
         &self,
         peaks: &[crate::core::data::Peak],
         curve: &crate::core::data::Curve,
+        boundary_method: &str,
+        shape_deadband: f64,
     ) -> Result<Vec<crate::core::data::Peak>, ProcessingError> {
         let mut enhanced_peaks = Vec::new();
         
@@ -339,17 +1069,29 @@ impl PeakAnalyzer {
             let mut enhanced_peak = peak.clone();
             
             // 计算峰边界
-            self.calculate_peak_boundaries(&mut enhanced_peak, curve)?;
+            self.calculate_peak_boundaries(&mut enhanced_peak, curve, peaks, boundary_method)?;
             
             // 计算拖尾信息
             self.calculate_peak_tailing(&mut enhanced_peak, curve)?;
             
+            // 峰形方向分类
+            self.calculate_shape_direction(&mut enhanced_peak, shape_deadband)?;
+            
+            // 计算USP拖尾因子
+            self.calculate_usp_tailing_factor(&mut enhanced_peak, curve)?;
+            
             // 计算分离度
             self.calculate_peak_separation(&mut enhanced_peak, peaks)?;
             
             // 计算质量评分
             self.calculate_peak_quality(&mut enhanced_peak)?;
             
+            // 采集信息（MS级别、极性）沿用提取曲线
+            enhanced_peak.ms_level = enhanced_peak.ms_level.or(curve.ms_level);
+            if enhanced_peak.polarity.is_none() {
+                enhanced_peak.polarity = curve.metadata.get("polarity").and_then(|v| v.as_str()).map(str::to_string);
+            }
+            
             enhanced_peaks.push(enhanced_peak);
         }
         
@@ -386,8 +1128,8 @@ impl PeakAnalyzer {
         }
     }
     
-    /// 估计重叠水平
-    fn estimate_overlap_level(&self, peaks: &[crate::core::data::Peak]) -> f64 {
+    /// 估计重叠水平：中心间距小于平均半峰宽的峰对所占比例
+    pub fn estimate_overlap_level(&self, peaks: &[crate::core::data::Peak]) -> f64 {
         if peaks.len() < 2 {
             return 0.0;
         }
@@ -417,22 +1159,6 @@ impl PeakAnalyzer {
         }
     }
     
-    /// 估计信噪比
-    fn estimate_snr(&self, curve: &crate::core::data::Curve) -> f64 {
-        if curve.y_values.is_empty() {
-            return 0.0;
-        }
-        
-        let max_signal = curve.y_values.iter().fold(0.0_f64, |a, &b| a.max(b));
-        let noise_level = self.estimate_noise_level(curve);
-        
-        if noise_level > 0.0 {
-            max_signal / noise_level
-        } else {
-            0.0
-        }
-    }
-    
     /// 估计峰复杂度
     fn estimate_peak_complexity(&self, peaks: &[crate::core::data::Peak], curve: &crate::core::data::Curve) -> f64 {
         if peaks.is_empty() {
@@ -456,34 +1182,127 @@ impl PeakAnalyzer {
     }
     
     /// 计算峰边界
-    fn calculate_peak_boundaries(&self, peak: &mut crate::core::data::Peak, curve: &crate::core::data::Curve) -> Result<(), ProcessingError> {
-        let threshold = peak.amplitude * 0.1; // 10%阈值
-        
-        // 寻找左边界
-        let mut left_boundary = peak.center;
-        for (i, &x) in curve.x_values.iter().enumerate() {
-            if x < peak.center && curve.y_values[i] <= threshold {
-                left_boundary = x;
-                break;
-            }
+    ///
+    /// - `threshold`: 从峰顶向两侧搜索，强度降到峰高10%处；某一侧没有降到则取曲线端点。
+    ///   旧实现从曲线起点向右扫描，左边界会落在曲线开头第一个低于阈值的点、找不到时退回峰中心，
+    ///   改为从峰顶出发后左边界（以及依赖边界的面积、跨度）会比以前更窄
+    /// - `valley`: 与相邻峰共享边界，取两峰之间的强度最低点；无相邻峰的一侧退回阈值法
+    /// - `inflection`: 由二阶导数找到两侧拐点，取拐点切线与基线的交点
+    fn calculate_peak_boundaries(
+        &self,
+        peak: &mut crate::core::data::Peak,
+        curve: &crate::core::data::Curve,
+        peaks: &[crate::core::data::Peak],
+        method: &str,
+    ) -> Result<(), ProcessingError> {
+        if curve.x_values.is_empty() {
+            return Ok(());
         }
+        let apex = Self::nearest_index(&curve.x_values, peak.center);
         
-        // 寻找右边界
-        let mut right_boundary = peak.center;
-        for (i, &x) in curve.x_values.iter().enumerate() {
-            if x > peak.center && curve.y_values[i] <= threshold {
-                right_boundary = x;
-                break;
+        let (left, right) = match method {
+            "threshold" => {
+                let (left, right) = Self::threshold_boundaries(curve, apex, peak.amplitude);
+                (curve.x_values[left], curve.x_values[right])
             }
-        }
+            "valley" => {
+                let (threshold_left, threshold_right) = Self::threshold_boundaries(curve, apex, peak.amplitude);
+                
+                // 相邻峰：中心位于当前峰两侧且距离最近的峰
+                let left_neighbour = peaks.iter()
+                    .map(|p| p.center)
+                    .filter(|&c| c < peak.center)
+                    .fold(None, |acc: Option<f64>, c| Some(acc.map_or(c, |a| a.max(c))));
+                let right_neighbour = peaks.iter()
+                    .map(|p| p.center)
+                    .filter(|&c| c > peak.center)
+                    .fold(None, |acc: Option<f64>, c| Some(acc.map_or(c, |a| a.min(c))));
+                
+                let left = left_neighbour
+                    .map(|c| Self::valley_index(&curve.y_values, Self::nearest_index(&curve.x_values, c), apex))
+                    .unwrap_or(threshold_left);
+                let right = right_neighbour
+                    .map(|c| Self::valley_index(&curve.y_values, apex, Self::nearest_index(&curve.x_values, c)))
+                    .unwrap_or(threshold_right);
+                (curve.x_values[left], curve.x_values[right])
+            }
+            "inflection" => Self::inflection_boundaries(curve, apex),
+            _ => {
+                return Err(ProcessingError::ConfigError(format!("不支持的峰边界方法: {}", method)));
+            }
+        };
         
-        peak.left_boundary = left_boundary;
-        peak.right_boundary = right_boundary;
+        peak.left_boundary = left;
+        peak.right_boundary = right;
         peak.calculate_peak_span();
+        peak.add_metadata("boundary_method".to_string(), Value::String(method.to_string()));
         
         Ok(())
     }
     
+    /// 与给定x最接近的数据点索引
+    fn nearest_index(x_values: &[f64], x: f64) -> usize {
+        x_values.iter()
+            .enumerate()
+            .min_by(|a, b| (a.1 - x).abs().partial_cmp(&(b.1 - x).abs()).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(i, _)| i)
+            .unwrap_or(0)
+    }
+    
+    /// 从峰顶向两侧搜索强度降到峰高10%的位置，未降到则取曲线端点
+    fn threshold_boundaries(curve: &crate::core::data::Curve, apex: usize, amplitude: f64) -> (usize, usize) {
+        let threshold = amplitude * 0.1; // 10%阈值
+        let y = &curve.y_values;
+        
+        let left = (0..apex).rev().find(|&i| y[i] <= threshold).unwrap_or(0);
+        let right = (apex + 1..y.len()).find(|&i| y[i] <= threshold).unwrap_or(y.len() - 1);
+        (left, right)
+    }
+    
+    /// [start, end] 区间内强度最低点的索引
+    fn valley_index(y_values: &[f64], start: usize, end: usize) -> usize {
+        let (start, end) = (start.min(end), start.max(end));
+        (start..=end)
+            .min_by(|&a, &b| y_values[a].partial_cmp(&y_values[b]).unwrap_or(std::cmp::Ordering::Equal))
+            .unwrap_or(start)
+    }
+    
+    /// 两侧拐点（二阶导数由负变非负）处的切线与基线交点的x坐标
+    fn inflection_boundaries(curve: &crate::core::data::Curve, apex: usize) -> (f64, f64) {
+        let x = &curve.x_values;
+        let y = &curve.y_values;
+        let n = y.len();
+        let baseline = curve.y_min;
+        
+        let second_derivative = |i: usize| -> f64 {
+            let h1 = x[i] - x[i - 1];
+            let h2 = x[i + 1] - x[i];
+            2.0 * (h1 * y[i + 1] - (h1 + h2) * y[i] + h2 * y[i - 1]) / (h1 * h2 * (h1 + h2))
+        };
+        let tangent_intercept = |i: usize, fallback: f64| -> f64 {
+            let slope = (y[i + 1] - y[i - 1]) / (x[i + 1] - x[i - 1]);
+            if slope.abs() > f64::EPSILON {
+                x[i] - (y[i] - baseline) / slope
+            } else {
+                fallback
+            }
+        };
+        
+        if n < 3 {
+            return (x[0], x[n - 1]);
+        }
+        
+        let left = (1..apex.min(n - 1)).rev()
+            .find(|&i| second_derivative(i) >= 0.0)
+            .map(|i| tangent_intercept(i, x[0]).max(x[0]))
+            .unwrap_or(x[0]);
+        let right = (apex.max(1)..n - 1)
+            .find(|&i| second_derivative(i) >= 0.0)
+            .map(|i| tangent_intercept(i, x[n - 1]).min(x[n - 1]))
+            .unwrap_or(x[n - 1]);
+        (left, right)
+    }
+    
     /// 计算峰拖尾
     fn calculate_peak_tailing(&self, peak: &mut crate::core::data::Peak, curve: &crate::core::data::Curve) -> Result<(), ProcessingError> {
         let half_max = peak.amplitude / 2.0;
@@ -513,6 +1332,71 @@ impl PeakAnalyzer {
         Ok(())
     }
     
+    /// 峰形方向分类：由右/左半峰宽之比得到 fronting / symmetric / tailing 标签
+    ///
+    /// 比值偏离1不超过 deadband 时视为对称；任一侧半峰宽未知时不写入标签
+    fn calculate_shape_direction(&self, peak: &mut crate::core::data::Peak, deadband: f64) -> Result<(), ProcessingError> {
+        if peak.left_hwhm <= 0.0 || peak.right_hwhm <= 0.0 {
+            return Ok(());
+        }
+        
+        let ratio = peak.right_hwhm / peak.left_hwhm;
+        let direction = if ratio > 1.0 + deadband {
+            "tailing"
+        } else if ratio < 1.0 - deadband {
+            "fronting"
+        } else {
+            "symmetric"
+        };
+        peak.add_metadata("shape_direction".to_string(), Value::String(direction.to_string()));
+        
+        Ok(())
+    }
+    
+    /// 计算USP拖尾因子
+    /// 
+    /// T = W0.05 / (2f)，W0.05 为5%峰高处的峰宽，f 为5%峰高处前沿到峰顶的距离。
+    /// 与 `asymmetry_factor`（半峰高处的右/左半宽比）不同，这是药典QC中使用的定义。
+    fn calculate_usp_tailing_factor(&self, peak: &mut crate::core::data::Peak, curve: &crate::core::data::Curve) -> Result<(), ProcessingError> {
+        if curve.x_values.len() < 3 || peak.amplitude <= 0.0 {
+            return Ok(());
+        }
+        
+        // 峰顶索引：距离峰中心最近的数据点
+        let apex = curve.x_values.iter()
+            .enumerate()
+            .min_by(|a, b| (a.1 - peak.center).abs().partial_cmp(&(b.1 - peak.center).abs()).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(i, _)| i)
+            .unwrap_or(0);
+        let threshold = peak.amplitude * 0.05;
+        
+        // 从峰顶向两侧搜索穿越5%峰高的位置，并线性插值
+        let crossing = |from: usize, to: usize| -> f64 {
+            let (x1, y1, x2, y2) = (curve.x_values[from], curve.y_values[from], curve.x_values[to], curve.y_values[to]);
+            if (y1 - y2).abs() < f64::EPSILON {
+                x2
+            } else {
+                x1 + (threshold - y1) * (x2 - x1) / (y2 - y1)
+            }
+        };
+        
+        let left = (1..=apex).rev()
+            .find(|&i| curve.y_values[i - 1] <= threshold)
+            .map(|i| crossing(i, i - 1));
+        let right = (apex..curve.x_values.len() - 1)
+            .find(|&i| curve.y_values[i + 1] <= threshold)
+            .map(|i| crossing(i, i + 1));
+        
+        if let (Some(left), Some(right)) = (left, right) {
+            let front_half_width = peak.center - left;
+            if front_half_width > 0.0 {
+                peak.tailing_factor_usp = (right - left) / (2.0 * front_half_width);
+            }
+        }
+        
+        Ok(())
+    }
+    
     /// 计算峰分离度
     fn calculate_peak_separation(&self, peak: &mut crate::core::data::Peak, all_peaks: &[crate::core::data::Peak]) -> Result<(), ProcessingError> {
         let mut min_separation = f64::INFINITY;
@@ -529,8 +1413,10 @@ impl PeakAnalyzer {
             }
         }
         
-        // 添加分离度信息到元数据
-        peak.add_metadata("min_separation".to_string(), Value::Number(serde_json::Number::from_f64(min_separation).unwrap()));
+        // 添加分离度信息到元数据（单峰时没有相邻峰，分离度为无穷大，不写入数值）
+        if let Some(separation) = serde_json::Number::from_f64(min_separation) {
+            peak.add_metadata("min_separation".to_string(), Value::Number(separation));
+        }
         peak.add_metadata("is_resolved".to_string(), Value::Bool(min_separation > 1.0));
         
         Ok(())
@@ -552,3 +1438,696 @@ impl PeakAnalyzer {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gaussian_curve(id: &str, center: f64) -> Curve {
+        let x_values: Vec<f64> = (0..200).map(|i| i as f64 * 0.05).collect();
+        let y_values: Vec<f64> = x_values
+            .iter()
+            .map(|&x| 1000.0 * (-(x - center).powi(2) / (2.0 * 0.2 * 0.2)).exp())
+            .collect();
+        Curve::new(
+            id.to_string(),
+            "DT".to_string(),
+            x_values,
+            y_values,
+            "Drift Time".to_string(),
+            "Intensity".to_string(),
+            "ms".to_string(),
+            "counts".to_string(),
+        )
+    }
+
+    fn analysis_input() -> DataContainer {
+        let mut degenerate = gaussian_curve("degenerate", 5.0);
+        degenerate.y_values[100] = f64::NAN;
+
+        let mut input = DataContainer::new();
+        input.curves = vec![gaussian_curve("good_a", 3.0), degenerate, gaussian_curve("good_b", 6.0)];
+        input
+    }
+
+    fn analysis_config(fail_fast: bool) -> Value {
+        serde_json::json!({
+            "detection_method": "simple",
+            "fitting_method": "multi_peak",
+            "overlapping_processing": "none",
+            "quality_threshold": 0.0,
+            "fail_fast": fail_fast
+        })
+    }
+
+    #[tokio::test]
+    async fn test_partial_results_when_one_curve_fails() {
+        let result = PeakAnalyzer::new().process(analysis_input(), analysis_config(false)).await.unwrap();
+
+        let curve_ids: Vec<&str> = result.curves.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(curve_ids, vec!["good_a", "good_b"]);
+        assert!(result.peaks.iter().any(|p| p.curve_id == "good_a"));
+        assert!(result.peaks.iter().any(|p| p.curve_id == "good_b"));
+
+        let failed = result.metadata["failed_curves"].as_array().unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0]["curve_id"], "degenerate");
+        assert!(failed[0]["reason"].as_str().unwrap().contains("NaN"));
+    }
+
+    #[tokio::test]
+    async fn test_auto_baseline_improves_area_on_sloping_background() {
+        // 斜坡背景 20 + 3x 上的高斯峰，真实面积 A·σ·√(2π)
+        let x_values: Vec<f64> = (0..400).map(|i| i as f64 * 0.05).collect();
+        let y_values: Vec<f64> = x_values
+            .iter()
+            .map(|&x| 20.0 + 3.0 * x + 100.0 * (-(x - 10.0).powi(2) / (2.0 * 0.4 * 0.4)).exp())
+            .collect();
+        let true_area = 100.0 * 0.4 * (2.0 * std::f64::consts::PI).sqrt();
+        let mut input = DataContainer::new();
+        input.curves.push(Curve::new(
+            "sloped".to_string(),
+            "DT".to_string(),
+            x_values,
+            y_values,
+            "Drift Time".to_string(),
+            "Intensity".to_string(),
+            "ms".to_string(),
+            "counts".to_string(),
+        ));
+
+        let area_error = |result: &ProcessingResult| {
+            let peak = result.peaks.iter()
+                .min_by(|a, b| (a.center - 10.0).abs().total_cmp(&(b.center - 10.0).abs()))
+                .unwrap();
+            (peak.area - true_area).abs()
+        };
+
+        let plain = PeakAnalyzer::new().process(input.clone(), analysis_config(false)).await.unwrap();
+        let mut config = analysis_config(false);
+        config["auto_baseline"] = serde_json::json!("linear");
+        let corrected = PeakAnalyzer::new().process(input, config).await.unwrap();
+
+        assert_eq!(plain.metadata["auto_baseline_applied"], Value::Bool(false));
+        assert_eq!(corrected.metadata["auto_baseline_applied"], Value::Bool(true));
+        assert_eq!(corrected.metadata["auto_baseline_method"], Value::String("linear".to_string()));
+        assert_eq!(corrected.curves[0].get_metadata("baseline_corrected"), Some(&Value::Bool(true)));
+        assert!(area_error(&corrected) < area_error(&plain),
+            "corrected error {} >= plain error {}", area_error(&corrected), area_error(&plain));
+    }
+
+    #[tokio::test]
+    async fn test_total_integrated_area_and_area_units() {
+        let mut input = DataContainer::new();
+        input.curves = vec![gaussian_curve("good_a", 3.0), gaussian_curve("good_b", 6.0)];
+        let result = PeakAnalyzer::new().process(input, analysis_config(false)).await.unwrap();
+
+        assert!(result.peaks.len() >= 2);
+        let summed: f64 = result.peaks.iter().map(|peak| peak.area).sum();
+        let total = result.metadata["total_integrated_area"].as_f64().unwrap();
+        assert!(summed > 0.0);
+        assert!((summed - total).abs() < 1e-9 * total, "{} != {}", summed, total);
+        for peak in &result.peaks {
+            assert_eq!(peak.area_unit.as_deref(), Some("counts·ms"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_out_of_range_sensitivity_is_clamped_with_warning() {
+        let mut input = DataContainer::new();
+        input.curves.push(gaussian_curve("good_a", 3.0));
+        let mut config = analysis_config(false);
+        config["sensitivity"] = serde_json::json!(5.0);
+
+        let result = PeakAnalyzer::new().process(input, config).await.unwrap();
+        let warnings = result.metadata["config_warnings"].as_array().unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0]["parameter"], "sensitivity");
+        assert_eq!(warnings[0]["value"], 5.0);
+        assert_eq!(warnings[0]["clamped_to"], 1.0);
+
+        let mut config = serde_json::json!({"sensitivity": 5.0, "max_iterations": 0, "quality_threshold": 0.5});
+        clamp_config_to_schema(&PeakAnalyzer::new().config_schema(), &mut config);
+        assert_eq!(config["sensitivity"], 1.0);
+        assert_eq!(config["max_iterations"], 1);
+        assert_eq!(config["quality_threshold"], 0.5);
+    }
+
+    #[tokio::test]
+    async fn test_sensitivity_reaches_detector() {
+        // 小峰低于噪声阈值（均值 + 3σ），只有低敏感度的动态阈值能检出它
+        let mut curve = gaussian_curve("good_a", 3.0);
+        for (x, y) in curve.x_values.iter().zip(curve.y_values.iter_mut()) {
+            *y += 100.0 * (-(x - 7.0).powi(2) / (2.0 * 0.2 * 0.2)).exp();
+        }
+        let mut input = DataContainer::new();
+        input.curves = vec![curve];
+
+        let default = PeakAnalyzer::new().process(input.clone(), analysis_config(false)).await.unwrap();
+        assert_eq!(default.peaks.len(), 1);
+
+        let mut config = analysis_config(false);
+        config["sensitivity"] = serde_json::json!(0.05);
+        let sensitive = PeakAnalyzer::new().process(input, config).await.unwrap();
+        assert_eq!(sensitive.peaks.len(), 2);
+        assert!(sensitive.peaks.iter().any(|p| (p.center - 7.0).abs() < 0.1));
+    }
+
+    #[tokio::test]
+    async fn test_fail_fast_aborts_on_first_failure() {
+        let result = PeakAnalyzer::new().process(analysis_input(), analysis_config(true)).await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_valley_boundary_splits_overlapping_peaks_at_minimum() {
+        let x_values: Vec<f64> = (0..200).map(|i| i as f64 * 0.05).collect();
+        let y_values: Vec<f64> = x_values
+            .iter()
+            .map(|&x| 1000.0 * (-(x - 4.0).powi(2) / (2.0 * 0.5 * 0.5)).exp() + 600.0 * (-(x - 5.6).powi(2) / (2.0 * 0.5 * 0.5)).exp())
+            .collect();
+        let curve = Curve::new(
+            "overlap".to_string(), "DT".to_string(), x_values.clone(), y_values.clone(),
+            "Drift Time".to_string(), "Intensity".to_string(), "ms".to_string(), "counts".to_string(),
+        );
+        let peaks = vec![
+            crate::core::data::Peak::new("a".to_string(), "overlap".to_string(), 4.0, 1000.0, crate::core::data::PeakType::Gaussian),
+            crate::core::data::Peak::new("b".to_string(), "overlap".to_string(), 5.6, 600.0, crate::core::data::PeakType::Gaussian),
+        ];
+
+        // 两峰之间的实际强度最低点
+        let valley = (80..=112)
+            .min_by(|&a, &b| y_values[a].partial_cmp(&y_values[b]).unwrap())
+            .unwrap();
+
+        let analyzer = PeakAnalyzer::new();
+        let mut left_peak = peaks[0].clone();
+        let mut right_peak = peaks[1].clone();
+        analyzer.calculate_peak_boundaries(&mut left_peak, &curve, &peaks, "valley").unwrap();
+        analyzer.calculate_peak_boundaries(&mut right_peak, &curve, &peaks, "valley").unwrap();
+
+        assert_eq!(left_peak.right_boundary, x_values[valley]);
+        assert_eq!(right_peak.left_boundary, x_values[valley]);
+
+        // 阈值法在重叠区找不到10%点，边界会越过另一个峰
+        let mut threshold_peak = peaks[0].clone();
+        analyzer.calculate_peak_boundaries(&mut threshold_peak, &curve, &peaks, "threshold").unwrap();
+        assert!(threshold_peak.right_boundary > 5.6);
+    }
+
+    #[test]
+    fn test_threshold_boundary_searches_outward_from_apex() {
+        // σ = 0.2 的高斯峰：10% 峰高在 center ± 0.43，向外取第一个不高于阈值的网格点
+        let curve = gaussian_curve("single", 5.0);
+        let boundaries = |curve: &Curve| {
+            let mut peak = crate::core::data::Peak::new("p".to_string(), curve.id.clone(), 5.0, 1000.0, crate::core::data::PeakType::Gaussian);
+            PeakAnalyzer::new().calculate_peak_boundaries(&mut peak, curve, &[], "threshold").unwrap();
+            (peak.left_boundary, peak.right_boundary)
+        };
+
+        // 旧实现的左边界是曲线起点 0.0
+        let (left, right) = boundaries(&curve);
+        assert!((left - 4.55).abs() < 1e-9, "left = {}", left);
+        assert!((right - 5.45).abs() < 1e-9, "right = {}", right);
+
+        // 右侧未降到阈值时取曲线终点（旧实现退回峰中心）
+        let mut truncated = curve.clone();
+        truncated.x_values.truncate(105);
+        truncated.y_values.truncate(105);
+        let (left, right) = boundaries(&truncated);
+        assert!((left - 4.55).abs() < 1e-9, "left = {}", left);
+        assert_eq!(right, truncated.x_values[104]);
+    }
+
+    #[test]
+    fn test_inflection_boundary_uses_tangent_baseline_intercept() {
+        // σ = 0.2 的高斯峰：拐点在 center ± σ，切线与基线交于 center ± 2σ
+        let curve = gaussian_curve("single", 5.0);
+        let mut peak = crate::core::data::Peak::new("p".to_string(), "single".to_string(), 5.0, 1000.0, crate::core::data::PeakType::Gaussian);
+        PeakAnalyzer::new().calculate_peak_boundaries(&mut peak, &curve, &[], "inflection").unwrap();
+
+        assert!((peak.left_boundary - 4.6).abs() < 0.06, "left = {}", peak.left_boundary);
+        assert!((peak.right_boundary - 5.4).abs() < 0.06, "right = {}", peak.right_boundary);
+    }
+
+    #[test]
+    fn test_shape_direction_labels_tailing_and_symmetric_peaks() {
+        let analyzer = PeakAnalyzer::new();
+        let labelled = |left_hwhm: f64, right_hwhm: f64| {
+            let mut peak = crate::core::data::Peak::new("p".to_string(), "c".to_string(), 5.0, 100.0, crate::core::data::PeakType::Gaussian);
+            peak.left_hwhm = left_hwhm;
+            peak.right_hwhm = right_hwhm;
+            analyzer.calculate_shape_direction(&mut peak, 0.1).unwrap();
+            peak.get_metadata("shape_direction").and_then(|v| v.as_str()).map(str::to_string)
+        };
+
+        assert_eq!(labelled(0.2, 0.8).as_deref(), Some("tailing"));
+        assert_eq!(labelled(0.2, 0.21).as_deref(), Some("symmetric"));
+        assert_eq!(labelled(0.8, 0.2).as_deref(), Some("fronting"));
+        assert_eq!(labelled(0.0, 0.2), None);
+    }
+
+    #[test]
+    fn test_usp_tailing_factor_on_symmetric_and_tailing_peaks() {
+        let analyzer = PeakAnalyzer::new();
+        let tailing_factor = |curve: &Curve| {
+            let mut peak = crate::core::data::Peak::new("p".to_string(), curve.id.clone(), 5.0, 1000.0, crate::core::data::PeakType::Gaussian);
+            analyzer.calculate_usp_tailing_factor(&mut peak, curve).unwrap();
+            peak.tailing_factor_usp
+        };
+
+        let symmetric = tailing_factor(&gaussian_curve("symmetric", 5.0));
+        assert!((symmetric - 1.0).abs() < 0.05, "symmetric T = {}", symmetric);
+
+        // 双高斯拖尾峰：前沿 σ = 0.2，后沿 σ = 0.5，T = (0.2 + 0.5) / (2 × 0.2) = 1.75
+        let mut tailing = gaussian_curve("tailing", 5.0);
+        for (x, y) in tailing.x_values.iter().zip(tailing.y_values.iter_mut()) {
+            let sigma = if *x < 5.0 { 0.2 } else { 0.5 };
+            *y = 1000.0 * (-(x - 5.0).powi(2) / (2.0 * sigma * sigma)).exp();
+        }
+        let tailed = tailing_factor(&tailing);
+        assert!(tailed > 1.0);
+        assert!((tailed - 1.75).abs() < 0.1, "tailing T = {}", tailed);
+    }
+
+    #[tokio::test]
+    async fn test_flat_curve_reports_no_peaks_with_reason() {
+        let x_values: Vec<f64> = (0..100).map(|i| i as f64 * 0.1).collect();
+        let flat = Curve::new(
+            "flat".to_string(), "DT".to_string(), x_values, vec![250.0; 100],
+            "Drift Time".to_string(), "Intensity".to_string(), "ms".to_string(), "counts".to_string(),
+        );
+        let mut input = DataContainer::new();
+        input.curves = vec![flat];
+
+        let result = PeakAnalyzer::new().process(input, analysis_config(false)).await.unwrap();
+
+        assert!(result.peaks.is_empty());
+        assert_eq!(result.metadata["no_peaks_found"], Value::Bool(true));
+        let empty = result.metadata["no_peak_curves"].as_array().unwrap();
+        assert_eq!(empty.len(), 1);
+        assert_eq!(empty[0]["reason"], NO_PEAKS_FLAT_CURVE);
+    }
+
+    /// 高斯峰叠加基线与确定性噪声
+    fn noisy_curve(id: &str, peaks: &[(f64, f64)], offset: f64, noise: f64) -> Curve {
+        let x_values: Vec<f64> = (0..300).map(|i| i as f64 * 0.1).collect();
+        let y_values: Vec<f64> = x_values
+            .iter()
+            .enumerate()
+            .map(|(i, &x)| {
+                let signal: f64 = peaks.iter()
+                    .map(|&(c, a)| a * (-0.5 * ((x - c) / 0.5).powi(2)).exp())
+                    .sum();
+                signal + offset + noise * ((i as f64) * 2.3).sin()
+            })
+            .collect();
+        Curve::new(
+            id.to_string(), "DT".to_string(), x_values, y_values,
+            "Drift Time".to_string(), "Intensity".to_string(), "ms".to_string(), "counts".to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_adaptive_sensitivity_per_curve() {
+        let mut input = DataContainer::new();
+        input.curves = vec![
+            noisy_curve("strong", &[(6.0, 20000.0), (15.0, 1500.0), (24.0, 600.0)], 100.0, 20.0),
+            noisy_curve("weak", &[(8.0, 60.0), (20.0, 40.0)], 10.0, 2.0),
+        ];
+        let count = |result: &ProcessingResult, id: &str| result.peaks.iter().filter(|p| p.curve_id == id).count();
+
+        // 全局敏感度下强样本的小峰被漏检
+        let global = PeakAnalyzer::new().process(input.clone(), analysis_config(false)).await.unwrap();
+        assert!(count(&global, "strong") < 3);
+
+        let mut config = analysis_config(false);
+        config["adaptive_sensitivity"] = Value::Bool(true);
+        let result = PeakAnalyzer::new().process(input, config).await.unwrap();
+        assert_eq!(count(&result, "strong"), 3);
+        assert_eq!(count(&result, "weak"), 2);
+
+        let thresholds = result.metadata["effective_thresholds"].as_array().unwrap();
+        assert_eq!(thresholds.len(), 2);
+        let strong = thresholds[0]["effective_threshold"].as_f64().unwrap();
+        let weak = thresholds[1]["effective_threshold"].as_f64().unwrap();
+        assert!(strong > weak);
+        assert_eq!(result.curves[1].metadata["effective_threshold"].as_f64().unwrap(), weak);
+    }
+
+    #[tokio::test]
+    async fn test_max_iterations_reaches_fitter() {
+        // 以高斯拟合洛伦兹峰：残差不为零，LM 线性收敛，默认设置下需要远多于 5 次迭代
+        let mut curve = gaussian_curve("good_a", 3.0);
+        curve.y_values = curve.x_values.iter().map(|&x| 1000.0 / (1.0 + ((x - 3.0) / 0.2).powi(2))).collect();
+        let mut input = DataContainer::new();
+        input.curves = vec![curve];
+        let iterations = |result: &ProcessingResult| -> Vec<u64> {
+            result.peaks.iter().map(|p| p.metadata["iterations"].as_u64().unwrap()).collect()
+        };
+        let mut base_config = analysis_config(false);
+        base_config["force_shape"] = serde_json::json!("gaussian");
+
+        let default = PeakAnalyzer::new().process(input.clone(), base_config.clone()).await.unwrap();
+        assert!(!default.peaks.is_empty());
+        assert!(iterations(&default).iter().all(|&i| i <= 100));
+        assert!(iterations(&default).iter().any(|&i| i > 5), "default iterations {:?}", iterations(&default));
+        assert!(default.metadata.get("max_iterations").is_none());
+
+        let mut config = base_config;
+        config["max_iterations"] = serde_json::json!(5);
+        let limited = PeakAnalyzer::new().process(input, config).await.unwrap();
+        assert!(!limited.peaks.is_empty());
+        assert!(iterations(&limited).iter().all(|&i| i <= 5));
+        assert_eq!(limited.metadata["max_iterations"], 5);
+    }
+
+    #[tokio::test]
+    async fn test_fit_peaks_returns_fitted_not_input_peaks() {
+        let curve = gaussian_curve("good_a", 3.0);
+        // 检测峰的中心和宽度都偏离真实峰，拟合后应回到 3.0
+        let mut detected = crate::core::data::Peak::new("detected".to_string(), "good_a".to_string(), 3.1, 800.0, crate::core::data::PeakType::Gaussian);
+        detected.sigma = 0.3;
+        detected.fwhm = 0.3 * 2.355;
+
+        let fitted = PeakAnalyzer::new()
+            .fit_peaks(&[detected], &curve, "multi_peak", &serde_json::json!([]), Some("gaussian"), None, None)
+            .await
+            .unwrap();
+
+        assert_eq!(fitted.len(), 1);
+        assert_eq!(fitted[0].id, "detected");
+        assert_eq!(fitted[0].curve_id, "good_a");
+        assert_eq!(fitted[0].get_metadata("fitting_method"), Some(&serde_json::json!("multi_peak")));
+        assert!(fitted[0].get_metadata("iterations").is_some());
+        assert!((fitted[0].center - 3.0).abs() < 0.01, "center {}", fitted[0].center);
+        assert!((fitted[0].sigma - 0.2).abs() < 0.01, "sigma {}", fitted[0].sigma);
+    }
+
+    #[tokio::test]
+    async fn test_auto_overlap_method_follows_strategy_thresholds() {
+        // 两个相距 2.0、FWHM ≈ 0.7 的峰，重叠量约 -1.3，默认阈值下视为单峰
+        let x_values: Vec<f64> = (0..200).map(|i| i as f64 * 0.05).collect();
+        let y_values: Vec<f64> = x_values
+            .iter()
+            .map(|&x| [4.0, 6.0].iter().map(|&c| 1000.0 * (-(x - c).powi(2) / (2.0 * 0.3 * 0.3)).exp()).sum())
+            .collect();
+        let mut input = DataContainer::new();
+        input.curves = vec![Curve::new(
+            "pair".to_string(),
+            "DT".to_string(),
+            x_values,
+            y_values,
+            "Drift Time".to_string(),
+            "Intensity".to_string(),
+            "ms".to_string(),
+            "counts".to_string(),
+        )];
+        let mut config = analysis_config(false);
+        config["overlapping_processing"] = serde_json::json!("auto");
+
+        let default = PeakAnalyzer::new().process(input.clone(), config.clone()).await.unwrap();
+        assert_eq!(default.curves[0].metadata["overlapping_method"], "none");
+
+        config["strategy_thresholds"] = serde_json::json!({"light_overlap": -2.0, "medium_overlap": 0.0, "extreme_overlap": 1.0});
+        let adjusted = PeakAnalyzer::new().process(input, config).await.unwrap();
+        assert_eq!(adjusted.curves[0].metadata["overlapping_method"], "fbf");
+    }
+
+    #[tokio::test]
+    async fn test_multi_peak_fit_is_seeded_with_the_other_detected_peaks() {
+        // 两个相距 0.4 的高斯峰，区域检测只能找到一个
+        let x_values: Vec<f64> = (0..401).map(|i| i as f64 * 0.025).collect();
+        let y_values: Vec<f64> = x_values
+            .iter()
+            .map(|&x| 100.0 * (-(x - 5.0).powi(2) / (2.0 * 0.1 * 0.1)).exp() + 60.0 * (-(x - 5.4).powi(2) / (2.0 * 0.1 * 0.1)).exp())
+            .collect();
+        let mut input = DataContainer::new();
+        input.curves = vec![Curve::new(
+            "pair".to_string(),
+            "DT".to_string(),
+            x_values,
+            y_values,
+            "Drift Time".to_string(),
+            "Intensity".to_string(),
+            "ms".to_string(),
+            "counts".to_string(),
+        )];
+
+        let result = PeakAnalyzer::new().process(input, analysis_config(false)).await.unwrap();
+
+        let mut peaks = result.peaks.clone();
+        peaks.sort_by(|a, b| a.center.total_cmp(&b.center));
+        assert_eq!(peaks.len(), 2);
+        for peak in &peaks {
+            assert_eq!(peak.get_metadata("fit_components"), Some(&serde_json::json!(2)));
+            assert_eq!(peak.get_metadata("fit_seed_source"), Some(&serde_json::json!("detected_peaks")));
+        }
+        assert!((peaks[0].center - 5.0).abs() < 0.02, "center {}", peaks[0].center);
+        assert!((peaks[1].center - 5.4).abs() < 0.02, "center {}", peaks[1].center);
+    }
+
+    #[tokio::test]
+    async fn test_cluster_ids_survive_multi_peak_fitting() {
+        // 两个相距 0.4 的重叠峰，重叠处理阶段分配簇 ID，随后由多峰拟合重建
+        let x_values: Vec<f64> = (0..401).map(|i| i as f64 * 0.025).collect();
+        let y_values: Vec<f64> = x_values
+            .iter()
+            .map(|&x| 100.0 * (-(x - 5.0).powi(2) / (2.0 * 0.1 * 0.1)).exp() + 60.0 * (-(x - 5.4).powi(2) / (2.0 * 0.1 * 0.1)).exp())
+            .collect();
+        let mut input = DataContainer::new();
+        input.curves = vec![Curve::new(
+            "pair".to_string(),
+            "DT".to_string(),
+            x_values,
+            y_values,
+            "Drift Time".to_string(),
+            "Intensity".to_string(),
+            "ms".to_string(),
+            "counts".to_string(),
+        )];
+        let mut config = analysis_config(false);
+        config["overlapping_processing"] = serde_json::json!("fbf");
+
+        let result = PeakAnalyzer::new().process(input, config).await.unwrap();
+
+        assert_eq!(result.peaks.len(), 2);
+        for peak in &result.peaks {
+            assert_eq!(peak.get_metadata("fit_components"), Some(&serde_json::json!(2)));
+            assert!(peak.cluster_id.as_deref().is_some_and(|id| id.starts_with("pair_cluster_")), "{:?}", peak.cluster_id);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_process_many_applies_recipe_to_each_container() {
+        let containers: Vec<DataContainer> = [2.0, 4.0, 6.0].iter()
+            .map(|&center| {
+                let mut container = DataContainer::new();
+                container.curves = vec![gaussian_curve(&format!("curve_{}", center), center)];
+                container
+            })
+            .collect();
+        let recipe = AnalysisRecipe {
+            name: "simple_gaussian".to_string(),
+            detection_method: "simple".to_string(),
+            fitting_method: "multi_peak".to_string(),
+            overlapping_processing: "none".to_string(),
+            quality_threshold: 0.0,
+            ..AnalysisRecipe::default()
+        };
+
+        let runs = PeakAnalyzer::process_many(containers, &recipe).await;
+
+        assert_eq!(runs.len(), 3);
+        for (i, run) in runs.iter().enumerate() {
+            assert_eq!(run.index, i);
+            assert!(run.error.is_none(), "{:?}", run.error);
+            assert!(!run.peaks.is_empty());
+            assert_eq!(run.metadata["detection_method"], "simple");
+            assert_eq!(run.metadata["fitting_method"], "multi_peak");
+            assert_eq!(run.metadata["boundary_method"], "threshold");
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_pool_of_two_limits_concurrent_fits_to_two() {
+        use crate::core::utils::thread_pool;
+
+        let containers: Vec<DataContainer> = (0..8)
+            .map(|i| {
+                let mut container = DataContainer::new();
+                container.curves = vec![gaussian_curve(&format!("curve_{}", i), 2.0 + i as f64 * 0.5)];
+                container
+            })
+            .collect();
+        let recipe = AnalysisRecipe {
+            name: "pool_of_two".to_string(),
+            detection_method: "simple".to_string(),
+            fitting_method: "multi_peak".to_string(),
+            overlapping_processing: "none".to_string(),
+            quality_threshold: 0.0,
+            ..AnalysisRecipe::default()
+        };
+
+        assert_eq!(thread_pool::set_thread_pool_size(Some(2)).unwrap(), 2);
+        thread_pool::take_peak_active_tasks();
+        let runs = PeakAnalyzer::process_many(containers, &recipe).await;
+        let peak = thread_pool::take_peak_active_tasks();
+        assert_eq!(thread_pool::set_thread_pool_size(None).unwrap(), thread_pool::default_pool_size());
+
+        assert!(runs.iter().all(|run| run.error.is_none() && !run.peaks.is_empty()));
+        assert_eq!(peak, 2, "同时进行的拟合数 {}", peak);
+    }
+
+    /// 前40张为正离子光谱、后40张为负离子光谱，两段各有一个TIC高斯峰（RT 2.0 与 6.0）
+    fn write_mixed_polarity(path: &std::path::Path) {
+        use crate::core::utils::test_fixtures::{ms1_spectrum, write_mzml};
+        use mzdata::prelude::SpectrumLike;
+        use mzdata::spectrum::ScanPolarity;
+
+        let spectra: Vec<_> = (0..80)
+            .map(|i| {
+                let rt = i as f64 * 0.1;
+                let (polarity, center) = if i < 40 { (ScanPolarity::Positive, 2.0) } else { (ScanPolarity::Negative, 6.0) };
+                let intensity = 10.0 + 1000.0 * (-(rt - center).powi(2) / (2.0 * 0.4 * 0.4)).exp();
+                let peaks: Vec<(f64, f32)> = (0..3).map(|j| (500.0 + j as f64, intensity as f32)).collect();
+                let mut spectrum = ms1_spectrum(i, rt, &peaks);
+                spectrum.description_mut().polarity = polarity;
+                spectrum
+            })
+            .collect();
+        write_mzml(path, &spectra);
+    }
+
+    #[tokio::test]
+    async fn test_exported_peaks_carry_ms_level_and_polarity() {
+        use crate::core::data::AxisLabels;
+        use crate::core::processors::overlay_extractor::extract_by_type;
+
+        let path = std::env::temp_dir().join(format!("mz_curve_mixed_polarity_{}.mzML", std::process::id()));
+        write_mixed_polarity(&path);
+        let container = crate::core::loaders::mzdata_loader::DataLoader::load_from_file(&path.to_string_lossy());
+        let _ = std::fs::remove_file(&path);
+        let container = container.unwrap();
+        assert_eq!(container.metadata["polarity"], "mixed");
+
+        // 按极性分段提取TIC
+        let mut input = DataContainer::new();
+        for rt_range in ["0-3.95", "4-8"] {
+            let extracted = extract_by_type(container.clone(), "tic", "0-2000", rt_range, 1, &AxisLabels::default()).await.unwrap();
+            input.curves.extend(extracted.curves);
+        }
+
+        let result = PeakAnalyzer::new().process(input, analysis_config(false)).await.unwrap();
+        let mut analyzed = DataContainer::new();
+        analyzed.curves = result.curves;
+        for peak in result.peaks {
+            if let Some(curve) = analyzed.curves.iter_mut().find(|c| c.id == peak.curve_id) {
+                curve.add_peak(peak);
+            }
+        }
+
+        let exported = crate::core::exporters::ExportManager::new()
+            .export("tsv", &analyzed, serde_json::json!({"export_format": "peaks_only"}))
+            .await
+            .unwrap();
+        let content = String::from_utf8(exported.data).unwrap();
+        let mut lines = content.lines();
+        let header: Vec<&str> = lines.next().unwrap().split('\t').collect();
+        let column = |name: &str| header.iter().position(|h| *h == name).unwrap();
+        let (center, ms_level, polarity) = (column("Center"), column("MS_Level"), column("Polarity"));
+
+        let rows: Vec<(f64, String, String)> = lines
+            .map(|line| {
+                let fields: Vec<&str> = line.split('\t').collect();
+                (fields[center].parse().unwrap(), fields[ms_level].to_string(), fields[polarity].to_string())
+            })
+            .collect();
+        assert!(rows.iter().any(|r| (r.0 - 2.0).abs() < 0.2 && r.2 == "positive"));
+        assert!(rows.iter().any(|r| (r.0 - 6.0).abs() < 0.2 && r.2 == "negative"));
+        for (center, ms_level, polarity) in &rows {
+            assert_eq!(ms_level, "1");
+            assert_eq!(polarity, if *center < 4.0 { "positive" } else { "negative" });
+        }
+    }
+
+    /// 主峰（5.0，σ=0.4）右侧带一个无低谷的肩峰（5.6，σ=0.3）
+    fn shoulder_fixture() -> (Curve, Vec<Peak>) {
+        let x_values: Vec<f64> = (0..200).map(|i| i as f64 * 0.05).collect();
+        let y_values: Vec<f64> = x_values
+            .iter()
+            .map(|&x| 1000.0 * (-(x - 5.0).powi(2) / (2.0 * 0.4 * 0.4)).exp() + 250.0 * (-(x - 5.6).powi(2) / (2.0 * 0.3 * 0.3)).exp())
+            .collect();
+        let curve = Curve::new(
+            "shoulder".to_string(), "DT".to_string(), x_values, y_values,
+            "Drift Time".to_string(), "Intensity".to_string(), "ms".to_string(), "counts".to_string(),
+        );
+        let peak = |id: &str, center: f64, amplitude: f64, sigma: f64| {
+            let mut peak = Peak::new(id.to_string(), "shoulder".to_string(), center, amplitude, crate::core::data::PeakType::Gaussian);
+            peak.sigma = sigma;
+            peak.fwhm = 2.355 * sigma;
+            peak.area = amplitude * sigma * (2.0 * std::f64::consts::PI).sqrt();
+            peak.left_boundary = center - 2.0 * peak.fwhm;
+            peak.right_boundary = center + 2.0 * peak.fwhm;
+            peak
+        };
+        (curve, vec![peak("shoulder_peak", 5.6, 250.0, 0.3), peak("main_peak", 5.0, 1000.0, 0.4)])
+    }
+
+    #[test]
+    fn test_shoulder_policy_merge_and_separate() {
+        let (curve, peaks) = shoulder_fixture();
+        let total_area: f64 = peaks.iter().map(|p| p.area).sum();
+
+        let merged = apply_shoulder_policy(&curve, peaks.clone(), ShoulderPolicy::Merge);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].id, "main_peak");
+        assert!((merged[0].area - total_area).abs() < 1e-9);
+        assert_eq!(merged[0].right_boundary, peaks[0].right_boundary);
+        assert_eq!(merged[0].metadata["merged_shoulders"], serde_json::json!(["shoulder_peak"]));
+
+        let separate = apply_shoulder_policy(&curve, peaks.clone(), ShoulderPolicy::Separate);
+        assert_eq!(separate.len(), 2);
+        let shoulder = separate.iter().find(|p| p.id == "shoulder_peak").unwrap();
+        assert_eq!(shoulder.metadata["shoulder_of"], "main_peak");
+
+        let ignored = apply_shoulder_policy(&curve, peaks, ShoulderPolicy::Ignore);
+        assert_eq!(ignored.len(), 1);
+        assert!((ignored[0].area - total_area).abs() > 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_apex_definitions_on_right_tailed_peak() {
+        // 双高斯峰：左侧 σ=0.2，右侧 σ=0.6，峰顶在 4.0
+        let x_values: Vec<f64> = (0..400).map(|i| i as f64 * 0.02).collect();
+        let y_values: Vec<f64> = x_values
+            .iter()
+            .map(|&x| {
+                let sigma = if x < 4.0 { 0.2 } else { 0.6 };
+                1000.0 * (-(x - 4.0).powi(2) / (2.0 * sigma * sigma)).exp()
+            })
+            .collect();
+        let mut input = DataContainer::new();
+        input.curves = vec![Curve::new(
+            "tailed".to_string(), "DT".to_string(), x_values, y_values,
+            "Drift Time".to_string(), "Intensity".to_string(), "ms".to_string(), "counts".to_string(),
+        )];
+
+        let mut centers = Vec::new();
+        for definition in ["raw_max", "fitted_center", "centroid"] {
+            let mut config = analysis_config(false);
+            config["force_shape"] = serde_json::json!("gaussian");
+            config["apex_definition"] = serde_json::json!(definition);
+            let result = PeakAnalyzer::new().process(input.clone(), config).await.unwrap();
+            assert_eq!(result.peaks.len(), 1);
+            assert_eq!(result.metadata["apex_definition"], definition);
+            assert_eq!(result.peaks[0].metadata["apex_definition"], definition);
+            centers.push(result.peaks[0].center);
+        }
+
+        let (raw_max, fitted_center, centroid) = (centers[0], centers[1], centers[2]);
+        assert!((raw_max - 4.0).abs() < 1e-9, "raw_max = {}", raw_max);
+        assert!(fitted_center > raw_max, "fitted_center = {}", fitted_center);
+        assert!(centroid > fitted_center, "centroid = {}, fitted_center = {}", centroid, fitted_center);
+    }
+}
@@ -0,0 +1,110 @@
+//! 峰检测评估
+//!
+//! 把检测到的峰中心与已知的真值列表一一匹配（中心差不超过容差），统计真/假阳性与漏检，
+//! 给出 precision / recall / F1，用于客观比较检测算法的改动
+
+use crate::core::data::ProcessingError;
+use serde::{Deserialize, Serialize};
+
+/// 一对匹配的检测峰与真值峰
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectionMatch {
+    pub detected: f64,
+    pub truth: f64,
+    /// 检测中心 - 真值中心
+    pub error: f64,
+}
+
+/// 检测评估结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectionEvaluation {
+    pub tolerance: f64,
+    pub true_positives: usize,
+    pub false_positives: usize,
+    pub false_negatives: usize,
+    pub precision: f64,
+    pub recall: f64,
+    pub f1: f64,
+    pub matches: Vec<DetectionMatch>,
+    /// 未匹配到真值的检测峰中心
+    pub spurious: Vec<f64>,
+    /// 未被检测到的真值峰中心
+    pub missed: Vec<f64>,
+}
+
+/// 按中心匹配检测峰与真值峰并计算 precision / recall / F1
+///
+/// 匹配是一对一的：所有容差内的候选对按中心差从小到大依次配对，已配对的峰不再参与。
+/// 分母为零时对应指标为 0
+pub fn evaluate_detection(detected: &[f64], truth: &[f64], tolerance: f64) -> Result<DetectionEvaluation, ProcessingError> {
+    if !tolerance.is_finite() || tolerance < 0.0 {
+        return Err(ProcessingError::ConfigError(format!("容差必须为非负数: {}", tolerance)));
+    }
+
+    let mut candidates: Vec<(f64, usize, usize)> = Vec::new();
+    for (i, &d) in detected.iter().enumerate() {
+        for (j, &t) in truth.iter().enumerate() {
+            let distance = (d - t).abs();
+            if distance <= tolerance {
+                candidates.push((distance, i, j));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut detected_used = vec![false; detected.len()];
+    let mut truth_used = vec![false; truth.len()];
+    let mut matches = Vec::new();
+    for (_, i, j) in candidates {
+        if detected_used[i] || truth_used[j] {
+            continue;
+        }
+        detected_used[i] = true;
+        truth_used[j] = true;
+        matches.push(DetectionMatch { detected: detected[i], truth: truth[j], error: detected[i] - truth[j] });
+    }
+    matches.sort_by(|a, b| a.truth.partial_cmp(&b.truth).unwrap_or(std::cmp::Ordering::Equal));
+
+    let spurious: Vec<f64> = detected.iter().zip(&detected_used).filter(|(_, used)| !**used).map(|(d, _)| *d).collect();
+    let missed: Vec<f64> = truth.iter().zip(&truth_used).filter(|(_, used)| !**used).map(|(t, _)| *t).collect();
+
+    let true_positives = matches.len();
+    let ratio = |n: usize, d: usize| if d > 0 { n as f64 / d as f64 } else { 0.0 };
+    let precision = ratio(true_positives, detected.len());
+    let recall = ratio(true_positives, truth.len());
+    let f1 = if precision + recall > 0.0 { 2.0 * precision * recall / (precision + recall) } else { 0.0 };
+
+    Ok(DetectionEvaluation {
+        tolerance,
+        true_positives,
+        false_positives: spurious.len(),
+        false_negatives: missed.len(),
+        precision,
+        recall,
+        f1,
+        matches,
+        spurious,
+        missed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spurious_detection_lowers_precision_only() {
+        let truth = [6.0, 15.0, 24.0];
+        let detected = [6.05, 14.9, 19.5, 24.1];
+        let evaluation = evaluate_detection(&detected, &truth, 0.2).unwrap();
+
+        assert_eq!(evaluation.true_positives, 3);
+        assert_eq!(evaluation.false_positives, 1);
+        assert_eq!(evaluation.false_negatives, 0);
+        assert_eq!(evaluation.recall, 1.0);
+        assert_eq!(evaluation.precision, 0.75);
+        assert!((evaluation.f1 - 6.0 / 7.0).abs() < 1e-12);
+        assert_eq!(evaluation.spurious, vec![19.5]);
+        assert!(evaluation.missed.is_empty());
+    }
+}
@@ -5,6 +5,8 @@
 pub mod cwt_detector;
 pub mod simple_detector;
 pub mod peak_finder_detector;
+pub mod sensitivity_calibration;
+pub mod evaluation;
 
 use crate::core::data::{Curve, Peak, ProcessingError, DataContainer, ProcessingResult};
 use crate::core::processors::core::Processor;
@@ -35,11 +37,20 @@ impl PeakDetector for PeakDetectorEnum {
     }
 
     fn detect_peaks(&self, curve: &Curve, config: &Value) -> Result<Vec<Peak>, ProcessingError> {
-        match self {
+        let mut peaks = match self {
             PeakDetectorEnum::CWT(d) => d.detect_peaks(curve, config),
             PeakDetectorEnum::Simple(d) => d.detect_peaks(curve, config),
             PeakDetectorEnum::PeakFinder(d) => d.detect_peaks(curve, config),
+        }?;
+        
+        // 检测到的中心受采样间隔限制，用抛物线插值细化到亚采样精度
+        if config["refine_center"].as_bool().unwrap_or(true) {
+            for peak in peaks.iter_mut() {
+                refine_center_parabolic(curve, peak);
+            }
         }
+        
+        Ok(peaks)
     }
 }
 
@@ -80,6 +91,28 @@ impl Processor for PeakDetectorEnum {
                 "method": {
                     "type": "string",
                     "enum": ["cwt", "simple", "peak_finder"]
+                },
+                "refine_center": {
+                    "type": "boolean",
+                    "default": true,
+                    "description": "用最大值附近三点的抛物线顶点细化峰中心"
+                },
+                "noise_region": {
+                    "type": "array",
+                    "items": {"type": "number"},
+                    "minItems": 2,
+                    "maxItems": 2,
+                    "description": "无信号的背景区间 [x_min, x_max]，检测阈值由区间内的基线与噪声标准差计算；不设置时使用全曲线统计"
+                },
+                "min_peak_width": {
+                    "type": "number",
+                    "minimum": 0,
+                    "description": "最小峰宽（X轴单位，半高全宽），更窄的候选峰视为噪声尖峰被剔除"
+                },
+                "max_peak_width": {
+                    "type": "number",
+                    "minimum": 0,
+                    "description": "最大峰宽（X轴单位，半高全宽），更宽的候选峰视为宽鼓包被剔除"
                 }
             }
         })
@@ -91,19 +124,29 @@ impl Processor for PeakDetectorEnum {
         }
 
         let curve = &input.curves[0];
+        let width_bounds = PeakWidthBounds::from_config(&config)?;
         
         let peaks = self.detect_peaks(curve, &config)?;
+        let (peaks, width_rejections) = enforce_width_bounds(curve, peaks, &width_bounds);
         
         // 将检测到的峰添加到曲线中
         let mut result_curves = input.curves.clone();
         if let Some(result_curve) = result_curves.first_mut() {
             result_curve.peaks = peaks.clone();
+            if !width_rejections.is_empty() {
+                result_curve.add_metadata("width_rejections".to_string(), Value::Array(width_rejections.clone()));
+            }
+        }
+
+        let mut metadata = input.metadata;
+        if width_bounds.is_active() {
+            metadata.insert("width_rejections".to_string(), Value::Array(width_rejections));
         }
 
         Ok(ProcessingResult {
             curves: result_curves,
             peaks,
-            metadata: input.metadata,
+            metadata,
         })
     }
 
@@ -118,3 +161,392 @@ pub fn create_detector(method: &str) -> Result<PeakDetectorEnum, ProcessingError
         _ => Err(ProcessingError::ConfigError(format!("不支持的检测方法: {}", method))),
     }
 }
+
+/// 曲线噪声基底估计
+#[derive(Debug, Clone, Copy)]
+pub struct NoiseFloor {
+    /// 基线水平（强度中位数）
+    pub baseline: f64,
+    /// 噪声标准差（一阶差分的MAD稳健估计）
+    pub noise: f64,
+}
+
+impl NoiseFloor {
+    /// 噪声相对阈值：基线 + multiplier × 噪声
+    pub fn threshold(&self, multiplier: f64) -> f64 {
+        self.baseline + multiplier * self.noise
+    }
+}
+
+/// 估计曲线的噪声基底
+///
+/// 噪声取一阶差分的中位数绝对偏差（MAD / 0.6745 / √2），峰本身只占少数点，不会抬高估计值
+pub fn estimate_noise_floor(curve: &Curve) -> NoiseFloor {
+    fn median(values: &mut [f64]) -> f64 {
+        if values.is_empty() {
+            return 0.0;
+        }
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let mid = values.len() / 2;
+        if values.len().is_multiple_of(2) { (values[mid - 1] + values[mid]) / 2.0 } else { values[mid] }
+    }
+
+    let baseline = median(&mut curve.y_values.clone());
+    let mut diffs: Vec<f64> = curve.y_values.windows(2).map(|w| w[1] - w[0]).collect();
+    let diff_median = median(&mut diffs.clone());
+    for d in diffs.iter_mut() {
+        *d = (*d - diff_median).abs();
+    }
+    let noise = median(&mut diffs) / 0.6745 / std::f64::consts::SQRT_2;
+
+    NoiseFloor { baseline, noise }
+}
+
+/// 从配置中解析背景区间 `noise_region: [x_min, x_max]`
+pub fn noise_region_from_config(config: &Value) -> Result<Option<(f64, f64)>, ProcessingError> {
+    let value = &config["noise_region"];
+    if value.is_null() {
+        return Ok(None);
+    }
+    match value.as_array().map(|items| items.iter().map(|v| v.as_f64()).collect::<Vec<_>>()).as_deref() {
+        Some([Some(start), Some(end)]) if start.is_finite() && end.is_finite() && start != end => {
+            Ok(Some((start.min(*end), start.max(*end))))
+        }
+        _ => Err(ProcessingError::ConfigError(format!(
+            "noise_region 应为两个不同的数值 [x_min, x_max]，实际为 {}", value
+        ))),
+    }
+}
+
+/// 估计曲线的噪声基底，可指定无信号的背景区间
+///
+/// 给出区间时基线取区间内强度均值、噪声取区间内强度的样本标准差；
+/// 区间内少于3个数据点或未给出区间时回退到全曲线的MAD估计（见 `estimate_noise_floor`）
+pub fn estimate_noise_floor_in_region(curve: &Curve, noise_region: Option<(f64, f64)>) -> NoiseFloor {
+    if let Some((start, end)) = noise_region {
+        let region: Vec<f64> = curve.x_values.iter()
+            .zip(&curve.y_values)
+            .filter(|(&x, _)| x >= start.min(end) && x <= start.max(end))
+            .map(|(_, &y)| y)
+            .collect();
+        if region.len() >= 3 {
+            let baseline = region.iter().sum::<f64>() / region.len() as f64;
+            let variance = region.iter().map(|y| (y - baseline).powi(2)).sum::<f64>() / (region.len() - 1) as f64;
+            return NoiseFloor { baseline, noise: variance.sqrt() };
+        }
+        log::warn!("⚠️ 噪声区间 [{}, {}] 内数据点不足，改用全曲线MAD估计噪声", start, end);
+    }
+
+    estimate_noise_floor(curve)
+}
+
+/// 峰宽约束（半高全宽，X轴单位），未设置的一侧不限制
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PeakWidthBounds {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl PeakWidthBounds {
+    /// 从配置中解析 `min_peak_width` / `max_peak_width`
+    pub fn from_config(config: &Value) -> Result<Self, ProcessingError> {
+        let bound = |key: &str| -> Result<Option<f64>, ProcessingError> {
+            match config[key].as_f64() {
+                None => Ok(None),
+                Some(width) if width.is_finite() && width >= 0.0 => Ok(Some(width)),
+                Some(width) => Err(ProcessingError::ConfigError(format!("{} 必须为非负数，实际为 {}", key, width))),
+            }
+        };
+        let bounds = Self { min: bound("min_peak_width")?, max: bound("max_peak_width")? };
+        if let (Some(min), Some(max)) = (bounds.min, bounds.max) {
+            if min > max {
+                return Err(ProcessingError::ConfigError(format!(
+                    "min_peak_width ({}) 不能大于 max_peak_width ({})", min, max
+                )));
+            }
+        }
+        Ok(bounds)
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.min.is_some() || self.max.is_some()
+    }
+
+    /// 峰宽超出范围时返回剔除原因
+    pub fn rejection_reason(&self, width: f64) -> Option<&'static str> {
+        if self.min.is_some_and(|min| width < min) {
+            Some("too_narrow")
+        } else if self.max.is_some_and(|max| width > max) {
+            Some("too_wide")
+        } else {
+            None
+        }
+    }
+}
+
+/// 估计峰在半高处的全宽
+///
+/// 半高取基线（`estimate_noise_floor` 的强度中位数）与峰顶之间的一半，抬高的基线不会把半高压到峰腰以下。
+/// 从离峰中心最近的采样点向两侧查找强度降到半高的位置，并在相邻采样点间线性插值；
+/// 峰顶不高于基线或某一侧在曲线内没有降到半高时宽度未知，返回 None
+pub fn estimate_half_height_width(curve: &Curve, center: f64) -> Option<f64> {
+    half_height_width_above(curve, center, estimate_noise_floor(curve).baseline)
+}
+
+fn half_height_width_above(curve: &Curve, center: f64, baseline: f64) -> Option<f64> {
+    let n = curve.x_values.len().min(curve.y_values.len());
+    let (x, y) = (&curve.x_values[..n], &curve.y_values[..n]);
+    let apex = x.iter().enumerate()
+        .min_by(|a, b| (a.1 - center).abs().total_cmp(&(b.1 - center).abs()))
+        .map(|(index, _)| index)?;
+    if y[apex] <= baseline {
+        return None;
+    }
+    let half = baseline + (y[apex] - baseline) / 2.0;
+
+    let crossing = |inner: usize, outer: usize| -> f64 {
+        let (y_inner, y_outer) = (y[inner], y[outer]);
+        if y_inner == y_outer {
+            return x[outer];
+        }
+        x[inner] + (x[outer] - x[inner]) * (y_inner - half) / (y_inner - y_outer)
+    };
+    let left = (0..apex).rev()
+        .find(|&i| y[i] <= half)
+        .map(|i| crossing(i + 1, i))?;
+    let right = (apex + 1..n)
+        .find(|&i| y[i] <= half)
+        .map(|i| crossing(i - 1, i))?;
+
+    Some(right - left)
+}
+
+/// 剔除峰宽超出约束的峰
+///
+/// 峰宽优先取半高处插值估计，无法估计时使用检测器给出的 `fwhm`。
+/// 返回保留的峰与剔除记录（curve_id、center、width、reason）
+pub fn enforce_width_bounds(curve: &Curve, peaks: Vec<Peak>, bounds: &PeakWidthBounds) -> (Vec<Peak>, Vec<Value>) {
+    if !bounds.is_active() {
+        return (peaks, Vec::new());
+    }
+
+    let baseline = estimate_noise_floor(curve).baseline;
+    let mut kept = Vec::with_capacity(peaks.len());
+    let mut rejections = Vec::new();
+    for peak in peaks {
+        let width = half_height_width_above(curve, peak.center, baseline).unwrap_or(peak.fwhm);
+        match bounds.rejection_reason(width) {
+            Some(reason) => {
+                log::debug!("🚫 曲线 {} 峰 {:.4} 宽度 {:.4} 超出范围，已剔除 ({})", curve.id, peak.center, width, reason);
+                rejections.push(serde_json::json!({
+                    "curve_id": curve.id,
+                    "center": peak.center,
+                    "width": width,
+                    "reason": reason,
+                }));
+            }
+            None => kept.push(peak),
+        }
+    }
+
+    (kept, rejections)
+}
+
+/// 抛物线插值细化峰中心
+///
+/// 取离峰中心最近的采样点及其左右相邻点拟合抛物线，将 `center` 更新为抛物线顶点。
+/// 该点不是局部最大值、位于曲线端点或三点不构成开口向下的抛物线时保持原中心不变。
+/// 返回是否进行了细化，原采样中心记录在 `sample_center` 元数据中
+pub fn refine_center_parabolic(curve: &Curve, peak: &mut Peak) -> bool {
+    let n = curve.x_values.len().min(curve.y_values.len());
+    if n < 3 {
+        return false;
+    }
+    
+    let index = match curve.x_values[..n].iter().enumerate()
+        .min_by(|a, b| (a.1 - peak.center).abs().partial_cmp(&(b.1 - peak.center).abs()).unwrap_or(std::cmp::Ordering::Equal))
+    {
+        Some((index, _)) if index > 0 && index < n - 1 => index,
+        _ => return false,
+    };
+    
+    let (x0, x1, x2) = (curve.x_values[index - 1], curve.x_values[index], curve.x_values[index + 1]);
+    let (y0, y1, y2) = (curve.y_values[index - 1], curve.y_values[index], curve.y_values[index + 1]);
+    if y1 < y0 || y1 < y2 {
+        return false;
+    }
+    
+    // 过三点的抛物线 y = a·x² + b·x + c（允许非均匀采样）
+    let denom = (x0 - x1) * (x0 - x2) * (x1 - x2);
+    if denom == 0.0 {
+        return false;
+    }
+    let a = (x2 * (y1 - y0) + x1 * (y0 - y2) + x0 * (y2 - y1)) / denom;
+    let b = (x2 * x2 * (y0 - y1) + x1 * x1 * (y2 - y0) + x0 * x0 * (y1 - y2)) / denom;
+    if a >= 0.0 {
+        return false;
+    }
+    
+    let vertex = -b / (2.0 * a);
+    if !vertex.is_finite() || vertex < x0 || vertex > x2 {
+        return false;
+    }
+    
+    peak.add_metadata("sample_center".to_string(), serde_json::json!(x1));
+    peak.center = vertex;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_parabolic_refinement_moves_center_toward_truth() {
+        // 真实中心 5.03 位于采样点 5.0 与 5.1 之间
+        let true_center = 5.03;
+        let x_values: Vec<f64> = (0..101).map(|i| i as f64 * 0.1).collect();
+        let y_values: Vec<f64> = x_values
+            .iter()
+            .map(|&x| 100.0 * (-(x - true_center).powi(2) / (2.0 * 0.3 * 0.3)).exp())
+            .collect();
+        let curve = Curve::new(
+            "test_curve".to_string(),
+            "DT".to_string(),
+            x_values,
+            y_values,
+            "Drift Time".to_string(),
+            "Intensity".to_string(),
+            "ms".to_string(),
+            "counts".to_string(),
+        );
+
+        let peaks = create_detector("simple").unwrap()
+            .detect_peaks(&curve, &serde_json::json!({"refine_center": false}))
+            .unwrap();
+        assert_eq!(peaks.len(), 1);
+        let sample_center = peaks[0].center;
+        assert!((sample_center - 5.0).abs() < 1e-9);
+
+        let mut peak = peaks[0].clone();
+        assert!(refine_center_parabolic(&curve, &mut peak));
+        assert!((peak.center - true_center).abs() < (sample_center - true_center).abs());
+        assert!((peak.center - true_center).abs() < 0.01, "refined center {}", peak.center);
+
+        // 默认开启细化
+        let refined = create_detector("simple").unwrap()
+            .detect_peaks(&curve, &serde_json::json!({}))
+            .unwrap();
+        assert_eq!(refined[0].center, peak.center);
+    }
+
+    #[test]
+    fn test_noise_region_estimate_beats_whole_curve_on_dense_peaks() {
+        // 0–1 为纯噪声；1–10 每 0.15 一个 σ = 0.04 的窄峰。均匀噪声 ±5，标准差 10/√12
+        let true_noise = 10.0 / 12f64.sqrt();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(99);
+        let x_values: Vec<f64> = (0..1000).map(|i| i as f64 * 0.01).collect();
+        let y_values: Vec<f64> = x_values
+            .iter()
+            .map(|&x| {
+                let noise = rng.gen_range(-5.0..5.0);
+                let signal: f64 = (0..60)
+                    .map(|k| 1.1 + k as f64 * 0.15)
+                    .map(|center| 100.0 * (-(x - center).powi(2) / (2.0 * 0.04 * 0.04)).exp())
+                    .sum();
+                20.0 + signal + noise
+            })
+            .collect();
+        let curve = Curve::new(
+            "dense".to_string(), "DT".to_string(), x_values, y_values,
+            "Drift Time".to_string(), "Intensity".to_string(), "ms".to_string(), "counts".to_string(),
+        );
+
+        let region = noise_region_from_config(&serde_json::json!({"noise_region": [1.0, 0.0]})).unwrap();
+        assert_eq!(region, Some((0.0, 1.0)));
+        let regional = estimate_noise_floor_in_region(&curve, region);
+        let whole_curve = estimate_noise_floor_in_region(&curve, None);
+
+        let regional_error = (regional.noise - true_noise).abs();
+        let whole_curve_error = (whole_curve.noise - true_noise).abs();
+        assert!(regional_error < 0.15 * true_noise, "regional noise {}", regional.noise);
+        assert!(regional_error < whole_curve_error, "regional {} vs whole curve {}", regional.noise, whole_curve.noise);
+        assert!((regional.baseline - 20.0).abs() < 1.0, "baseline {}", regional.baseline);
+
+        assert!(noise_region_from_config(&serde_json::json!({"noise_region": [1.0]})).is_err());
+        assert_eq!(noise_region_from_config(&serde_json::json!({})).unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_width_bounds_reject_spike_and_broad_hump() {
+        // x = 20 处单点尖峰，x = 50 处 FWHM ≈ 1.18 的真实峰，x = 80 处 FWHM ≈ 14 的宽鼓包
+        let x_values: Vec<f64> = (0..2000).map(|i| i as f64 * 0.05).collect();
+        let y_values: Vec<f64> = x_values
+            .iter()
+            .map(|&x| {
+                let spike = if (x - 20.0).abs() < 1e-9 { 80.0 } else { 0.0 };
+                let real = 100.0 * (-(x - 50.0).powi(2) / (2.0 * 0.5 * 0.5)).exp();
+                let hump = 60.0 * (-(x - 80.0).powi(2) / (2.0 * 6.0 * 6.0)).exp();
+                1.0 + spike + real + hump
+            })
+            .collect();
+        let curve = Curve::new(
+            "widths".to_string(), "DT".to_string(), x_values, y_values,
+            "Drift Time".to_string(), "Intensity".to_string(), "ms".to_string(), "counts".to_string(),
+        );
+        let input = DataContainer { curves: vec![curve], metadata: std::collections::HashMap::new(), spectra: vec![] };
+        let detector = create_detector("simple").unwrap();
+
+        let unbounded = detector.process(input.clone(), serde_json::json!({"sensitivity": 0.3})).await.unwrap();
+        assert_eq!(unbounded.peaks.len(), 3);
+        assert!(unbounded.metadata.get("width_rejections").is_none());
+
+        let config = serde_json::json!({"sensitivity": 0.3, "min_peak_width": 0.3, "max_peak_width": 5.0});
+        let bounded = detector.process(input, config).await.unwrap();
+        assert_eq!(bounded.peaks.len(), 1);
+        assert!((bounded.peaks[0].center - 50.0).abs() < 0.05, "center {}", bounded.peaks[0].center);
+
+        let rejections = bounded.metadata["width_rejections"].as_array().unwrap();
+        assert_eq!(rejections.len(), 2);
+        let reason_at = |center: f64| rejections.iter()
+            .find(|r| (r["center"].as_f64().unwrap() - center).abs() < 0.5)
+            .map(|r| r["reason"].as_str().unwrap().to_string());
+        assert_eq!(reason_at(20.0).as_deref(), Some("too_narrow"));
+        assert_eq!(reason_at(80.0).as_deref(), Some("too_wide"));
+        assert_eq!(bounded.curves[0].metadata["width_rejections"].as_array().unwrap().len(), 2);
+
+        assert!(PeakWidthBounds::from_config(&serde_json::json!({"min_peak_width": 2.0, "max_peak_width": 1.0})).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_width_bounds_measure_half_height_above_elevated_baseline() {
+        // FWHM ≈ 1.18 的峰坐在 500 的基线上，峰高只有 100，从未降到峰顶强度的一半
+        let x_values: Vec<f64> = (0..2000).map(|i| i as f64 * 0.05).collect();
+        let y_values: Vec<f64> = x_values
+            .iter()
+            .map(|&x| 500.0 + 100.0 * (-(x - 50.0).powi(2) / (2.0 * 0.5 * 0.5)).exp())
+            .collect();
+        let curve = Curve::new(
+            "elevated".to_string(), "DT".to_string(), x_values, y_values,
+            "Drift Time".to_string(), "Intensity".to_string(), "ms".to_string(), "counts".to_string(),
+        );
+
+        let width = estimate_half_height_width(&curve, 50.0).unwrap();
+        assert!((width - 1.1774).abs() < 0.02, "width {}", width);
+
+        let input = DataContainer { curves: vec![curve], metadata: std::collections::HashMap::new(), spectra: vec![] };
+        let config = serde_json::json!({"sensitivity": 0.3, "min_peak_width": 0.3, "max_peak_width": 5.0});
+        let bounded = create_detector("simple").unwrap().process(input, config).await.unwrap();
+        assert_eq!(bounded.peaks.len(), 1);
+        assert!((bounded.peaks[0].center - 50.0).abs() < 0.05, "center {}", bounded.peaks[0].center);
+        assert!(bounded.metadata["width_rejections"].as_array().unwrap().is_empty());
+
+        // 峰顶在曲线端点、右侧没有半高交点时宽度未知
+        let ramp = Curve::new(
+            "ramp".to_string(), "DT".to_string(), (0..=10).map(f64::from).collect(), (0..=10).map(f64::from).collect(),
+            "Drift Time".to_string(), "Intensity".to_string(), "ms".to_string(), "counts".to_string(),
+        );
+        assert!(estimate_half_height_width(&ramp, 10.0).is_none());
+    }
+}
@@ -3,7 +3,7 @@
 //! 基于peak_finder库的峰检测算法
 
 use crate::core::data::{Curve, Peak, ProcessingError, PeakType, DetectionAlgorithm};
-use crate::core::processors::peak_detection::PeakDetector;
+use crate::core::processors::peak_detection::{estimate_noise_floor_in_region, noise_region_from_config, PeakDetector};
 use serde_json::Value;
 use uuid::Uuid;
 
@@ -19,10 +19,11 @@ impl PeakDetector for PeakFinderDetector {
     fn detect_peaks(&self, curve: &Curve, config: &Value) -> Result<Vec<Peak>, ProcessingError> {
         let threshold_multiplier = config["threshold_multiplier"].as_f64().unwrap_or(3.0);
         
-        // 计算阈值
-        let mean = curve.mean_intensity;
-        let std = curve.intensity_std;
-        let threshold = mean + threshold_multiplier * std;
+        // 计算阈值：指定背景区间时使用区间内的基线与噪声
+        let threshold = match noise_region_from_config(config)? {
+            Some(region) => estimate_noise_floor_in_region(curve, Some(region)).threshold(threshold_multiplier),
+            None => curve.mean_intensity + threshold_multiplier * curve.intensity_std,
+        };
 
         // 简化的peak_finder实现（不依赖外部库）
         let peak_indices = self.find_peaks_simple(&curve.y_values, threshold);
@@ -0,0 +1,125 @@
+//! 敏感度自动校准
+//!
+//! 扫描一组敏感度取值并运行峰检测，取检测峰数保持稳定的最长区间（"拐点"之后的平台）作为推荐值
+
+use crate::core::data::{Curve, ProcessingError};
+use crate::core::processors::peak_detection::{create_detector, PeakDetector};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 扫描的敏感度取值：0.05, 0.10, ..., 0.95
+const SWEEP_STEP: f64 = 0.05;
+const SWEEP_STEPS: usize = 19;
+
+/// 单个扫描点
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SensitivityPoint {
+    pub sensitivity: f64,
+    pub peak_count: usize,
+}
+
+/// 敏感度校准结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SensitivityCalibration {
+    /// 实际使用的检测方法
+    pub detection_method: String,
+    /// 推荐敏感度（稳定平台的中点）
+    pub recommended_sensitivity: f64,
+    /// 推荐敏感度下的检测峰数
+    pub recommended_peak_count: usize,
+    /// 峰数开始稳定的敏感度（拐点）
+    pub knee_sensitivity: f64,
+    /// 稳定平台的敏感度范围
+    pub plateau: (f64, f64),
+    /// 完整的敏感度-峰数曲线，供前端展示
+    pub sweep: Vec<SensitivityPoint>,
+}
+
+/// 对单条曲线扫描敏感度并返回推荐值
+///
+/// `config` 中的其它检测参数（如 threshold_multiplier）原样传给检测器，仅覆盖 sensitivity。
+pub fn calibrate_sensitivity(
+    curve: &Curve,
+    detection_method: &str,
+    config: &Value,
+) -> Result<SensitivityCalibration, ProcessingError> {
+    let detector = create_detector(detection_method)?;
+    let mut detector_config = if config.is_object() { config.clone() } else { serde_json::json!({}) };
+
+    let mut sweep = Vec::with_capacity(SWEEP_STEPS);
+    for step in 1..=SWEEP_STEPS {
+        let sensitivity = step as f64 * SWEEP_STEP;
+        detector_config["sensitivity"] = serde_json::json!(sensitivity);
+        let peak_count = detector.detect_peaks(curve, &detector_config)?.len();
+        sweep.push(SensitivityPoint { sensitivity, peak_count });
+    }
+
+    // 寻找峰数不变且非零的最长连续区间；等长时取敏感度较低者
+    let mut best: Option<(usize, usize)> = None;
+    let mut start = 0;
+    for i in 1..=sweep.len() {
+        if i == sweep.len() || sweep[i].peak_count != sweep[start].peak_count {
+            if sweep[start].peak_count > 0 && best.is_none_or(|(s, e)| i - start > e - s) {
+                best = Some((start, i));
+            }
+            start = i;
+        }
+    }
+
+    let (plateau_start, plateau_end) = best.ok_or_else(|| ProcessingError::ProcessError(
+        format!("曲线 {} 在所有敏感度下均未检测到峰", curve.id)
+    ))?;
+    let recommended = &sweep[(plateau_start + plateau_end - 1) / 2];
+
+    log::info!("🎯 敏感度校准完成: 方法={}, 推荐={:.2}, 峰数={}",
+        detection_method, recommended.sensitivity, recommended.peak_count);
+
+    Ok(SensitivityCalibration {
+        detection_method: detection_method.to_string(),
+        recommended_sensitivity: recommended.sensitivity,
+        recommended_peak_count: recommended.peak_count,
+        knee_sensitivity: sweep[plateau_start].sensitivity,
+        plateau: (sweep[plateau_start].sensitivity, sweep[plateau_end - 1].sensitivity),
+        sweep,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 三个清晰的高斯峰叠加小幅确定性噪声
+    fn three_peak_curve() -> Curve {
+        let x: Vec<f64> = (0..300).map(|i| i as f64 * 0.1).collect();
+        let y: Vec<f64> = x.iter()
+            .enumerate()
+            .map(|(i, &v)| {
+                let peaks = [(6.0, 100.0), (15.0, 85.0), (24.0, 70.0)]
+                    .iter()
+                    .map(|&(c, a)| a * (-0.5 * ((v - c) / 0.5).powi(2)).exp())
+                    .sum::<f64>();
+                peaks + 5.0 + 3.0 * ((i as f64) * 2.3).sin()
+            })
+            .collect();
+        Curve::new(
+            "calibration".to_string(), "DT".to_string(), x, y,
+            "Drift Time".to_string(), "Intensity".to_string(), "ms".to_string(), "counts".to_string(),
+        )
+    }
+
+    #[test]
+    fn recommends_sensitivity_that_detects_three_peaks() {
+        let curve = three_peak_curve();
+        let calibration = calibrate_sensitivity(&curve, "simple", &serde_json::json!({})).unwrap();
+
+        // 最低敏感度下噪声会被当作峰
+        assert!(calibration.sweep[0].peak_count > 3);
+        assert_eq!(calibration.recommended_peak_count, 3);
+
+        let detector = create_detector("simple").unwrap();
+        let peaks = detector
+            .detect_peaks(&curve, &serde_json::json!({"sensitivity": calibration.recommended_sensitivity}))
+            .unwrap();
+        assert_eq!(peaks.len(), 3);
+    }
+}
@@ -3,7 +3,7 @@
 //! 基于局部最大值和阈值的简单峰检测算法
 
 use crate::core::data::{Curve, Peak, ProcessingError, PeakType, DetectionAlgorithm};
-use crate::core::processors::peak_detection::PeakDetector;
+use crate::core::processors::peak_detection::{estimate_noise_floor_in_region, noise_region_from_config, PeakDetector};
 use serde_json::Value;
 use uuid::Uuid;
 
@@ -23,7 +23,10 @@ impl PeakDetector for SimpleDetector {
 
         let mut peaks = Vec::new();
         let max_intensity: f64 = curve.y_values.iter().fold(0.0, |a, &b| a.max(b));
-        let threshold = curve.baseline_intensity + threshold_multiplier * curve.intensity_std;
+        let threshold = match noise_region_from_config(config)? {
+            Some(region) => estimate_noise_floor_in_region(curve, Some(region)).threshold(threshold_multiplier),
+            None => curve.baseline_intensity + threshold_multiplier * curve.intensity_std,
+        };
         let dynamic_threshold = max_intensity * sensitivity;
 
         // 使用滑动窗口检测峰值
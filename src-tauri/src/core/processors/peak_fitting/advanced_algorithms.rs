@@ -3,7 +3,10 @@
 //! 针对特殊峰形的专门算法实现
 
 use crate::core::data::ProcessingError;
-use crate::core::processors::peak_fitting::peak_shapes::{PeakShapeType, PeakShapeParams};
+use crate::core::processors::peak_fitting::peak_shapes::{PeakShapeType, PeakShapeParams, PeakShapeCalculator, GMGCalculator, NLCCalculator};
+use crate::core::processors::peak_fitting::parameter_optimizer::{ParameterOptimizer, OptimizationAlgorithm, OptimizationResult};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
 /// 复杂峰形算法trait
 pub trait AdvancedPeakAlgorithm {
@@ -424,6 +427,446 @@ impl BiGaussianAlgorithm {
     }
 }
 
+/// NLC（非线性色谱）峰专门算法
+pub struct NLCAlgorithm;
+
+impl AdvancedPeakAlgorithm for NLCAlgorithm {
+    fn name(&self) -> &str {
+        "nlc_algorithm"
+    }
+    
+    fn supported_shape_types(&self) -> Vec<PeakShapeType> {
+        vec![PeakShapeType::NLC]
+    }
+    
+    fn fit_peak(&self, x_data: &[f64], y_data: &[f64], initial_params: &PeakShapeParams) -> Result<PeakShapeParams, ProcessingError> {
+        let optimizer = ParameterOptimizer::new(OptimizationAlgorithm::LevenbergMarquardt {
+            max_iterations: 200,
+            convergence_threshold: 1e-8,
+            damping_factor: 0.1,
+        });
+        Ok(self.fit_with_optimizer(&optimizer, x_data, y_data, initial_params)?.optimized_params)
+    }
+    
+    fn requires_special_initialization(&self) -> bool {
+        true
+    }
+}
+
+impl NLCAlgorithm {
+    /// 由峰的不对称性初始化后，用给定优化器拟合全部参数，返回完整的优化结果（含参数误差）
+    ///
+    /// 初始参数中被固定的参数（边界收紧为单点）在初始化后仍保持原值
+    pub fn fit_with_optimizer(
+        &self,
+        optimizer: &ParameterOptimizer,
+        x_data: &[f64],
+        y_data: &[f64],
+        initial_params: &PeakShapeParams,
+    ) -> Result<OptimizationResult, ProcessingError> {
+        if x_data.len() != y_data.len() || x_data.len() < 5 {
+            return Err(ProcessingError::DataError("NLC拟合需要至少5个数据点".to_string()));
+        }
+        let mut params = initial_params.clone();
+        if params.shape_type != PeakShapeType::NLC {
+            params = PeakShapeParams::new(PeakShapeType::NLC);
+        }
+        
+        // NLC特殊初始化
+        if self.requires_special_initialization() {
+            self.initialize_nlc_parameters(&mut params, x_data, y_data);
+        }
+        
+        optimizer.optimize(
+            |x: &[f64], y: &[f64], p: &PeakShapeParams| self.calculate_nlc_error(x, y, p),
+            params,
+            x_data,
+            y_data,
+        )
+    }
+    
+    /// NLC参数初始化：由半高处左右宽度的不对称性估计畸变项的符号与大小
+    fn initialize_nlc_parameters(&self, params: &mut PeakShapeParams, x_data: &[f64], y_data: &[f64]) {
+        let max_idx = y_data.iter().enumerate()
+            .max_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(i, _)| i)
+            .unwrap_or(0);
+        let apex = x_data[max_idx];
+        let amplitude = y_data[max_idx];
+        let half_height = amplitude / 2.0;
+        
+        // 从峰顶向两侧找到半高位置
+        let left_hwhm = (0..max_idx).rev()
+            .find(|&i| y_data[i] <= half_height)
+            .map(|i| apex - x_data[i]);
+        let right_hwhm = (max_idx + 1..x_data.len())
+            .find(|&i| y_data[i] <= half_height)
+            .map(|i| x_data[i] - apex);
+        let (left_hwhm, right_hwhm) = match (left_hwhm, right_hwhm) {
+            (Some(left), Some(right)) if left > 0.0 && right > 0.0 => (left, right),
+            (Some(width), None) | (None, Some(width)) if width > 0.0 => (width, width),
+            _ => {
+                let span = (x_data[x_data.len() - 1] - x_data[0]) / 10.0;
+                (span, span)
+            }
+        };
+        
+        let sigma = (left_hwhm + right_hwhm) / 2.355;
+        // 不对称因子 > 1 为拖尾（d > 0），< 1 为前沿（d < 0）；在 |d/σ²| ≤ 5 范围内 d/σ² ≈ 8·ln(不对称因子)
+        let asymmetry = right_hwhm / left_hwhm;
+        let ratio = (8.0 * asymmetry.ln()).clamp(-5.0, 5.0);
+        // 拖尾时峰顶前移、前沿时峰顶后移，偏移量约为 0.38·(d/σ²)·σ
+        let center = apex + 0.38 * ratio * sigma;
+        
+        params.parameters = vec![amplitude, center, sigma, ratio * sigma.powi(2)];
+        params.clamp_parameters();
+    }
+    
+    /// 计算NLC误差
+    fn calculate_nlc_error(&self, x_data: &[f64], y_data: &[f64], params: &PeakShapeParams) -> f64 {
+        x_data.iter().zip(y_data)
+            .map(|(&x, &y)| (y - NLCCalculator.calculate(x, params)).powi(2))
+            .sum()
+    }
+}
+
+/// GMG贝叶斯拟合的后验摘要
+#[derive(Debug, Clone)]
+pub struct GMGPosterior {
+    /// 后验均值对应的峰形参数
+    pub params: PeakShapeParams,
+    /// 各参数的后验标准差
+    pub std_devs: Vec<f64>,
+    /// 各参数的95%可信区间（后验样本的2.5%与97.5%分位数）
+    pub credible_intervals: Vec<(f64, f64)>,
+    /// 噪声标准差的后验估计
+    pub noise_sigma: f64,
+    /// 采样阶段的Metropolis接受率
+    pub acceptance_rate: f64,
+    /// 保留的后验样本数
+    pub sample_count: usize,
+}
+
+/// GMG（高斯修正高斯）贝叶斯峰算法
+///
+/// 参数取均匀先验（限制在峰形参数边界内），噪声为未知方差的高斯噪声；
+/// 对噪声方差取Jeffreys先验并积分后，对数后验为 -n/2·ln(RSS)。
+/// 使用逐参数更新的随机游走Metropolis采样，预热阶段自适应调整步长。
+pub struct GMGBayesianAlgorithm {
+    /// 保留的采样迭代次数
+    pub samples: usize,
+    /// 预热（丢弃）迭代次数
+    pub burn_in: usize,
+    /// 随机数种子，保证结果可复现
+    pub seed: u64,
+}
+
+impl Default for GMGBayesianAlgorithm {
+    fn default() -> Self {
+        Self {
+            samples: 4000,
+            burn_in: 2000,
+            seed: 42,
+        }
+    }
+}
+
+impl AdvancedPeakAlgorithm for GMGBayesianAlgorithm {
+    fn name(&self) -> &str {
+        "gmg_bayesian_algorithm"
+    }
+    
+    fn supported_shape_types(&self) -> Vec<PeakShapeType> {
+        vec![PeakShapeType::GMGBayesian]
+    }
+    
+    fn fit_peak(&self, x_data: &[f64], y_data: &[f64], initial_params: &PeakShapeParams) -> Result<PeakShapeParams, ProcessingError> {
+        Ok(self.sample_posterior(x_data, y_data, initial_params)?.params)
+    }
+    
+    fn requires_special_initialization(&self) -> bool {
+        true
+    }
+}
+
+impl GMGBayesianAlgorithm {
+    /// Metropolis采样并汇总后验：均值、标准差与95%可信区间
+    pub fn sample_posterior(&self, x_data: &[f64], y_data: &[f64], initial_params: &PeakShapeParams) -> Result<GMGPosterior, ProcessingError> {
+        if x_data.len() != y_data.len() {
+            return Err(ProcessingError::DataError("x与y数据长度不一致".to_string()));
+        }
+        let mut params = PeakShapeParams::new(PeakShapeType::GMGBayesian);
+        if x_data.len() <= params.parameters.len() {
+            return Err(ProcessingError::DataError("数据点数不足以进行GMG贝叶斯拟合".to_string()));
+        }
+        if self.samples == 0 {
+            return Err(ProcessingError::ConfigError("采样次数必须大于0".to_string()));
+        }
+        
+        if self.requires_special_initialization() {
+            self.initialize_gmg_parameters(&mut params, x_data, y_data);
+        } else {
+            params.parameters = initial_params.parameters.clone();
+        }
+        // 沿用初始参数中被固定的参数
+        if initial_params.shape_type == PeakShapeType::GMGBayesian {
+            for i in 0..params.parameters.len() {
+                if initial_params.is_fixed(i) {
+                    params.parameters[i] = initial_params.parameters[i];
+                    params.bounds[i] = initial_params.bounds[i];
+                }
+            }
+        }
+        params.clamp_parameters();
+        
+        let n_params = params.parameters.len();
+        let free: Vec<usize> = (0..n_params).filter(|&j| !params.is_fixed(j)).collect();
+        let dimension = free.len().max(1);
+        let sigma = params.parameters[2];
+        let mut steps = [
+            (0.02 * params.parameters[0]).max(1e-6),
+            0.05 * sigma,
+            0.05 * sigma,
+            0.05 * sigma,
+        ];
+        
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut current_rss = self.residual_sum_squares(x_data, y_data, &params);
+        let mut current_log_post = self.log_posterior(x_data.len(), current_rss);
+        
+        // 预热前1/4逐参数更新；之后用预热历史的协方差做联合提议（自适应Metropolis），
+        // 以便沿 center 与 modifier 之间的强相关方向移动。协方差在采样阶段保持不变
+        let adapt_start = self.burn_in / 4;
+        let mut history: Vec<Vec<f64>> = Vec::with_capacity(self.burn_in);
+        let mut proposal_factor: Option<Vec<Vec<f64>>> = None;
+        let mut joint_scale = 2.38_f64.powi(2) / dimension as f64;
+        
+        let mut window_accepted = vec![0usize; n_params];
+        let mut window_joint_accepted = 0usize;
+        let mut proposals = 0usize;
+        let mut accepted = 0usize;
+        let mut samples: Vec<Vec<f64>> = vec![Vec::with_capacity(self.samples); n_params];
+        let mut rss_sum = 0.0;
+        
+        for iteration in 0..(self.burn_in + self.samples) {
+            let sampling = iteration >= self.burn_in;
+            
+            // 每次迭代的候选：联合提议一次，或逐参数各一次
+            let candidates: Vec<(Option<usize>, PeakShapeParams)> = match &proposal_factor {
+                Some(factor) => {
+                    let z: Vec<f64> = (0..free.len()).map(|_| standard_normal(&mut rng)).collect();
+                    let mut proposal = params.clone();
+                    for (row, &j) in free.iter().enumerate() {
+                        let delta: f64 = (0..=row).map(|k| factor[row][k] * z[k]).sum();
+                        proposal.parameters[j] += joint_scale.sqrt() * delta;
+                    }
+                    vec![(None, proposal)]
+                }
+                None => free.iter()
+                    .map(|&j| {
+                        let mut proposal = params.clone();
+                        proposal.parameters[j] += steps[j] * standard_normal(&mut rng);
+                        (Some(j), proposal)
+                    })
+                    .collect(),
+            };
+            
+            for (component, mut proposal) in candidates {
+                // 逐参数模式下以当前状态为基础重新应用该参数的扰动
+                if let Some(j) = component {
+                    let value = proposal.parameters[j];
+                    proposal = params.clone();
+                    proposal.parameters[j] = value;
+                }
+                if sampling {
+                    proposals += 1;
+                }
+                
+                // 先验之外，直接拒绝
+                let in_bounds = proposal.parameters.iter()
+                    .zip(&proposal.bounds)
+                    .all(|(v, (min, max))| v >= min && v <= max);
+                if !in_bounds {
+                    continue;
+                }
+                
+                let proposal_rss = self.residual_sum_squares(x_data, y_data, &proposal);
+                let proposal_log_post = self.log_posterior(x_data.len(), proposal_rss);
+                if rng.gen::<f64>().ln() < proposal_log_post - current_log_post {
+                    params = proposal;
+                    current_rss = proposal_rss;
+                    current_log_post = proposal_log_post;
+                    match component {
+                        Some(j) => window_accepted[j] += 1,
+                        None => window_joint_accepted += 1,
+                    }
+                    if sampling {
+                        accepted += 1;
+                    }
+                }
+            }
+            
+            if !sampling {
+                history.push(free.iter().map(|&j| params.parameters[j]).collect());
+                
+                // 每100次迭代调整步长：逐参数接受率接近0.44，联合提议接受率接近0.23
+                if (iteration + 1) % 100 == 0 {
+                    if proposal_factor.is_some() {
+                        let rate = window_joint_accepted as f64 / 100.0;
+                        if rate > 0.35 {
+                            joint_scale *= 1.5;
+                        } else if rate < 0.15 {
+                            joint_scale /= 1.5;
+                        }
+                    } else {
+                        for j in 0..n_params {
+                            let rate = window_accepted[j] as f64 / 100.0;
+                            if rate > 0.5 {
+                                steps[j] *= 1.5;
+                            } else if rate < 0.3 {
+                                steps[j] /= 1.5;
+                            }
+                        }
+                    }
+                    window_accepted.iter_mut().for_each(|count| *count = 0);
+                    window_joint_accepted = 0;
+                    
+                    // 用后一半预热历史估计协方差，早期远离后验的状态不参与
+                    if iteration + 1 >= adapt_start && !free.is_empty() {
+                        let recent = &history[history.len() / 2..];
+                        if let Some(factor) = cholesky(&sample_covariance(recent)) {
+                            proposal_factor = Some(factor);
+                        }
+                    }
+                }
+            } else {
+                for (j, sample) in samples.iter_mut().enumerate() {
+                    sample.push(params.parameters[j]);
+                }
+                rss_sum += current_rss;
+            }
+        }
+        
+        let mut posterior_params = params.clone();
+        let mut std_devs = Vec::with_capacity(n_params);
+        let mut credible_intervals = Vec::with_capacity(n_params);
+        for (j, sample) in samples.iter_mut().enumerate() {
+            let count = sample.len() as f64;
+            let mean = sample.iter().sum::<f64>() / count;
+            let variance = sample.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / count;
+            sample.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+            
+            posterior_params.parameters[j] = mean;
+            std_devs.push(variance.sqrt());
+            credible_intervals.push((quantile(sample, 0.025), quantile(sample, 0.975)));
+        }
+        
+        let degrees_of_freedom = (x_data.len() - free.len()) as f64;
+        
+        Ok(GMGPosterior {
+            params: posterior_params,
+            std_devs,
+            credible_intervals,
+            noise_sigma: (rss_sum / self.samples as f64 / degrees_of_freedom).sqrt(),
+            acceptance_rate: accepted as f64 / proposals.max(1) as f64,
+            sample_count: self.samples,
+        })
+    }
+    
+    /// GMG参数初始化：峰顶位置与高度、半高宽估计sigma
+    fn initialize_gmg_parameters(&self, params: &mut PeakShapeParams, x_data: &[f64], y_data: &[f64]) {
+        let max_idx = y_data.iter().enumerate()
+            .max_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(i, _)| i)
+            .unwrap_or(0);
+        let amplitude = y_data[max_idx];
+        
+        let above: Vec<f64> = x_data.iter().zip(y_data)
+            .filter(|(_, &y)| y > amplitude / 2.0)
+            .map(|(&x, _)| x)
+            .collect();
+        let fwhm = match (above.first(), above.last()) {
+            (Some(left), Some(right)) if right > left => right - left,
+            _ => (x_data[x_data.len() - 1] - x_data[0]) / 10.0,
+        };
+        let sigma = fwhm / 2.355;
+        
+        params.parameters = vec![amplitude.max(0.0), x_data[max_idx], sigma, 0.1 * sigma];
+    }
+    
+    /// 残差平方和
+    fn residual_sum_squares(&self, x_data: &[f64], y_data: &[f64], params: &PeakShapeParams) -> f64 {
+        x_data.iter().zip(y_data)
+            .map(|(&x, &y)| (y - GMGCalculator.calculate(x, params)).powi(2))
+            .sum()
+    }
+    
+    /// 对噪声方差积分后的对数后验（差一个常数）
+    fn log_posterior(&self, n_points: usize, rss: f64) -> f64 {
+        -(n_points as f64) / 2.0 * rss.max(f64::MIN_POSITIVE).ln()
+    }
+}
+
+/// Box-Muller变换生成标准正态随机数
+fn standard_normal(rng: &mut StdRng) -> f64 {
+    let u1: f64 = rng.gen::<f64>().max(f64::MIN_POSITIVE);
+    let u2: f64 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+/// 样本协方差矩阵，对角线加微小抖动保证正定
+fn sample_covariance(samples: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let dimension = samples.first().map_or(0, |s| s.len());
+    let count = samples.len().max(2) as f64;
+    let means: Vec<f64> = (0..dimension)
+        .map(|j| samples.iter().map(|s| s[j]).sum::<f64>() / samples.len().max(1) as f64)
+        .collect();
+    
+    let mut covariance = vec![vec![0.0; dimension]; dimension];
+    for a in 0..dimension {
+        for b in 0..=a {
+            let value = samples.iter()
+                .map(|s| (s[a] - means[a]) * (s[b] - means[b]))
+                .sum::<f64>() / (count - 1.0);
+            covariance[a][b] = value;
+            covariance[b][a] = value;
+        }
+    }
+    for (a, row) in covariance.iter_mut().enumerate() {
+        row[a] += 1e-10 * (1.0 + means[a].abs()).powi(2);
+    }
+    covariance
+}
+
+/// Cholesky分解 A = L·Lᵀ，矩阵非正定时返回None
+fn cholesky(matrix: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let n = matrix.len();
+    let mut lower = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in 0..=i {
+            let sum: f64 = (0..j).map(|k| lower[i][k] * lower[j][k]).sum();
+            if i == j {
+                let diagonal = matrix[i][i] - sum;
+                if diagonal <= 0.0 || !diagonal.is_finite() {
+                    return None;
+                }
+                lower[i][j] = diagonal.sqrt();
+            } else {
+                lower[i][j] = (matrix[i][j] - sum) / lower[j][j];
+            }
+        }
+    }
+    Some(lower)
+}
+
+/// 已排序样本的线性插值分位数
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q * (sorted.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64)
+}
+
 /// 复杂峰形算法工厂
 pub struct AdvancedAlgorithmFactory;
 
@@ -432,6 +875,8 @@ impl AdvancedAlgorithmFactory {
         match shape_type {
             PeakShapeType::ExponentiallyModifiedGaussian => Some(Box::new(EMGAlgorithm)),
             PeakShapeType::BiGaussian => Some(Box::new(BiGaussianAlgorithm)),
+            PeakShapeType::GMGBayesian => Some(Box::new(GMGBayesianAlgorithm::default())),
+            PeakShapeType::NLC => Some(Box::new(NLCAlgorithm)),
             _ => None,
         }
     }
@@ -440,6 +885,97 @@ impl AdvancedAlgorithmFactory {
         vec![
             Box::new(EMGAlgorithm),
             Box::new(BiGaussianAlgorithm),
+            Box::new(GMGBayesianAlgorithm::default()),
+            Box::new(NLCAlgorithm),
         ]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::processors::peak_fitting::peak_shapes::PeakShapeCalculatorFactory;
+
+    /// 中心 5.0、sigma 0.2、modifier 0.4 的GMG峰（明显拖尾），叠加给定标准差的高斯噪声
+    fn noisy_gmg_peak(noise_sd: f64, seed: u64) -> (Vec<f64>, Vec<f64>) {
+        let mut truth = PeakShapeParams::new(PeakShapeType::GMGBayesian);
+        truth.parameters = vec![50.0, 5.0, 0.2, 0.4];
+        let mut rng = StdRng::seed_from_u64(seed);
+        let x_data: Vec<f64> = (0..200).map(|i| 3.0 + i as f64 * 0.02).collect();
+        let y_data = x_data.iter()
+            .map(|&x| GMGCalculator.calculate(x, &truth) + noise_sd * standard_normal(&mut rng))
+            .collect();
+        (x_data, y_data)
+    }
+
+    #[test]
+    fn test_gmg_posterior_center_and_credible_interval_width() {
+        let algorithm = AdvancedAlgorithmFactory::create_algorithm(&PeakShapeType::GMGBayesian).unwrap();
+        assert_eq!(algorithm.name(), "gmg_bayesian_algorithm");
+
+        let gmg = GMGBayesianAlgorithm::default();
+        let initial = PeakShapeParams::new(PeakShapeType::GMGBayesian);
+        let width = |posterior: &GMGPosterior| posterior.credible_intervals[1].1 - posterior.credible_intervals[1].0;
+
+        let (x_data, y_data) = noisy_gmg_peak(2.0, 7);
+        let low_noise = gmg.sample_posterior(&x_data, &y_data, &initial).unwrap();
+        let center = low_noise.params.parameters[1];
+        assert!((center - 5.0).abs() < 0.05, "posterior mean center {}", center);
+        assert!((center - 5.0).abs() < 3.0 * low_noise.std_devs[1], "center {} ± {}", center, low_noise.std_devs[1]);
+        let (lower, upper) = low_noise.credible_intervals[1];
+        assert!(lower < center && center < upper);
+        assert!((low_noise.noise_sigma - 2.0).abs() < 0.4, "noise sigma {}", low_noise.noise_sigma);
+        assert!(low_noise.acceptance_rate > 0.1 && low_noise.acceptance_rate < 0.8);
+
+        let (x_data, y_data) = noisy_gmg_peak(8.0, 7);
+        let high_noise = gmg.sample_posterior(&x_data, &y_data, &initial).unwrap();
+        assert!(width(&high_noise) > 2.0 * width(&low_noise),
+            "CI width {} (noise 8) vs {} (noise 2)", width(&high_noise), width(&low_noise));
+    }
+
+    #[test]
+    fn test_nlc_fits_fronting_peak_better_than_gaussian() {
+        let mut truth = PeakShapeParams::new(PeakShapeType::NLC);
+        truth.parameters = vec![80.0, 5.0, 0.25, -0.15];
+        let mut rng = StdRng::seed_from_u64(3);
+        let x_data: Vec<f64> = (0..200).map(|i| 3.5 + i as f64 * 0.015).collect();
+        let y_data: Vec<f64> = x_data.iter()
+            .map(|&x| NLCCalculator.calculate(x, &truth) + 0.5 * standard_normal(&mut rng))
+            .collect();
+
+        // 前沿峰：半高处峰顶左侧比右侧宽
+        let apex = y_data.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1)).unwrap().0;
+        let half = y_data[apex] / 2.0;
+        let left = (0..apex).rev().find(|&i| y_data[i] <= half).unwrap();
+        let right = (apex..y_data.len()).find(|&i| y_data[i] <= half).unwrap();
+        assert!(x_data[apex] - x_data[left] > 1.2 * (x_data[right] - x_data[apex]));
+        assert!(x_data[apex] > 5.0);
+
+        let algorithm = AdvancedAlgorithmFactory::create_algorithm(&PeakShapeType::NLC).unwrap();
+        let nlc = algorithm.fit_peak(&x_data, &y_data, &PeakShapeParams::new(PeakShapeType::NLC)).unwrap();
+        assert!(nlc.parameters[3] < 0.0, "distortion {}", nlc.parameters[3]);
+        assert!((nlc.parameters[1] - 5.0).abs() < 0.02, "center {}", nlc.parameters[1]);
+
+        let gaussian_calculator = PeakShapeCalculatorFactory::create_calculator(&PeakShapeType::Gaussian);
+        let gaussian_error = |x: &[f64], y: &[f64], p: &PeakShapeParams| -> f64 {
+            x.iter().zip(y).map(|(&xi, &yi)| (yi - gaussian_calculator.calculate(xi, p)).powi(2)).sum()
+        };
+        let mut gaussian = PeakShapeParams::new(PeakShapeType::Gaussian);
+        gaussian.parameters = vec![y_data[apex], x_data[apex], 0.25];
+        let optimizer = ParameterOptimizer::new(OptimizationAlgorithm::LevenbergMarquardt {
+            max_iterations: 200,
+            convergence_threshold: 1e-8,
+            damping_factor: 0.1,
+        });
+        let gaussian = optimizer.optimize(gaussian_error, gaussian, &x_data, &y_data).unwrap().optimized_params;
+
+        let rss = |calculator: &dyn PeakShapeCalculator, p: &PeakShapeParams| -> f64 {
+            x_data.iter().zip(&y_data).map(|(&x, &y)| (y - calculator.calculate(x, p)).powi(2)).sum()
+        };
+        let nlc_rss = rss(&NLCCalculator, &nlc);
+        let gaussian_rss = rss(gaussian_calculator.as_ref(), &gaussian);
+        assert!(nlc_rss < 0.5 * gaussian_rss, "NLC RSS {} vs Gaussian RSS {}", nlc_rss, gaussian_rss);
+        // 噪声方差 0.25，200 个点的期望残差平方和约 50
+        assert!(nlc_rss < 80.0, "NLC RSS {}", nlc_rss);
+    }
+}
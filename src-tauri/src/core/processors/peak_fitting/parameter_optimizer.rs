@@ -3,7 +3,7 @@
 //! 对单个峰进行参数优化，支持多种优化算法
 
 use crate::core::data::ProcessingError;
-use crate::core::processors::peak_fitting::peak_shapes::PeakShapeParams;
+use crate::core::processors::peak_fitting::peak_shapes::{PeakShapeParams, PeakShapeCalculatorFactory};
 
 /// 优化算法类型
 #[derive(Debug, Clone)]
@@ -33,6 +33,30 @@ pub enum OptimizationAlgorithm {
     },
 }
 
+/// 优化终止原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminationReason {
+    /// 误差或参数更新量低于收敛阈值
+    Converged,
+    /// 达到迭代上限
+    MaxIterations,
+    /// 网格搜索遍历完全部网格点
+    GridExhausted,
+    /// 模拟退火温度降到下限
+    TemperatureFloor,
+}
+
+impl TerminationReason {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Converged => "converged",
+            Self::MaxIterations => "max_iterations",
+            Self::GridExhausted => "grid_exhausted",
+            Self::TemperatureFloor => "temperature_floor",
+        }
+    }
+}
+
 /// 优化结果
 #[derive(Debug, Clone)]
 pub struct OptimizationResult {
@@ -40,7 +64,15 @@ pub struct OptimizationResult {
     pub final_error: f64,
     pub iterations: usize,
     pub converged: bool,
+    /// 参数标准误差
     pub parameter_errors: Vec<f64>,
+    /// 参数协方差矩阵 s²(JᵀJ)⁻¹（仅Levenberg-Marquardt提供）
+    pub covariance: Option<Vec<Vec<f64>>>,
+    /// 各参数的95%置信区间 (下限, 上限)
+    pub confidence_intervals: Vec<(f64, f64)>,
+    /// 每次迭代后的目标函数值，长度等于 `iterations`
+    pub error_history: Vec<f64>,
+    pub termination: TerminationReason,
 }
 
 /// 参数优化器
@@ -54,6 +86,31 @@ impl ParameterOptimizer {
         Self { algorithm }
     }
     
+    /// 返回覆盖了迭代上限和收敛阈值的优化器副本，未给出的项保持原算法设置
+    ///
+    /// 网格搜索和模拟退火没有收敛阈值，只覆盖迭代上限
+    pub fn with_limits(&self, max_iterations: Option<usize>, convergence_threshold: Option<f64>) -> Self {
+        let mut algorithm = self.algorithm.clone();
+        match &mut algorithm {
+            OptimizationAlgorithm::GridSearch { max_iterations: iterations, .. }
+            | OptimizationAlgorithm::SimulatedAnnealing { max_iterations: iterations, .. } => {
+                if let Some(limit) = max_iterations {
+                    *iterations = limit;
+                }
+            },
+            OptimizationAlgorithm::GradientDescent { max_iterations: iterations, convergence_threshold: threshold, .. }
+            | OptimizationAlgorithm::LevenbergMarquardt { max_iterations: iterations, convergence_threshold: threshold, .. } => {
+                if let Some(limit) = max_iterations {
+                    *iterations = limit;
+                }
+                if let Some(tolerance) = convergence_threshold {
+                    *threshold = tolerance;
+                }
+            },
+        }
+        Self { algorithm }
+    }
+    
     /// 执行参数优化
     pub fn optimize<F>(
         &self,
@@ -97,6 +154,7 @@ impl ParameterOptimizer {
         let mut best_params = initial_params.clone();
         let mut best_error = objective_function(x_data, y_data, &best_params);
         let mut iterations = 0;
+        let mut error_history = Vec::new();
         
         // 为每个参数创建搜索范围
         let mut param_ranges = Vec::new();
@@ -128,9 +186,11 @@ impl ParameterOptimizer {
             &mut best_error,
             &mut iterations,
             max_iterations,
+            &mut error_history,
         );
         
         let parameter_errors = self.estimate_parameter_errors(&objective_function, x_data, y_data, &best_params);
+        let confidence_intervals = confidence_intervals_95(&best_params.parameters, &parameter_errors, x_data.len().saturating_sub(best_params.parameters.len()));
         
         Ok(OptimizationResult {
             optimized_params: best_params,
@@ -138,6 +198,10 @@ impl ParameterOptimizer {
             iterations,
             converged: iterations < max_iterations,
             parameter_errors,
+            covariance: None,
+            confidence_intervals,
+            error_history,
+            termination: if iterations < max_iterations { TerminationReason::GridExhausted } else { TerminationReason::MaxIterations },
         })
     }
     
@@ -154,6 +218,7 @@ impl ParameterOptimizer {
         best_error: &mut f64,
         iterations: &mut usize,
         max_iterations: usize,
+        error_history: &mut Vec<f64>,
     ) where
         F: Fn(&[f64], &[f64], &PeakShapeParams) -> f64,
     {
@@ -173,6 +238,7 @@ impl ParameterOptimizer {
                     *best_error = error;
                     *best_params = current_params.clone();
                 }
+                error_history.push(*best_error);
             } else {
                 // 递归设置下一个参数
                 self.grid_search_recursive(
@@ -186,6 +252,7 @@ impl ParameterOptimizer {
                     best_error,
                     iterations,
                     max_iterations,
+                    error_history,
                 );
             }
         }
@@ -207,6 +274,8 @@ impl ParameterOptimizer {
     {
         let mut iterations = 0;
         let mut previous_error = f64::INFINITY;
+        let mut error_history = Vec::new();
+        let mut termination = TerminationReason::MaxIterations;
         
         for _ in 0..max_iterations {
             iterations += 1;
@@ -224,9 +293,11 @@ impl ParameterOptimizer {
             
             // 计算新的误差
             let current_error = objective_function(x_data, y_data, &params);
+            error_history.push(current_error);
             
             // 检查收敛
             if (previous_error - current_error).abs() < convergence_threshold {
+                termination = TerminationReason::Converged;
                 break;
             }
             
@@ -235,6 +306,7 @@ impl ParameterOptimizer {
         
         let final_error = objective_function(x_data, y_data, &params);
         let parameter_errors = self.estimate_parameter_errors(&objective_function, x_data, y_data, &params);
+        let confidence_intervals = confidence_intervals_95(&params.parameters, &parameter_errors, x_data.len().saturating_sub(params.parameters.len()));
         
         Ok(OptimizationResult {
             optimized_params: params,
@@ -242,6 +314,10 @@ impl ParameterOptimizer {
             iterations,
             converged: iterations < max_iterations,
             parameter_errors,
+            covariance: None,
+            confidence_intervals,
+            error_history,
+            termination,
         })
     }
     
@@ -261,6 +337,8 @@ impl ParameterOptimizer {
     {
         let mut iterations = 0;
         let mut lambda = damping_factor;
+        let mut error_history = Vec::new();
+        let mut termination = TerminationReason::MaxIterations;
         
         for _ in 0..max_iterations {
             iterations += 1;
@@ -271,10 +349,11 @@ impl ParameterOptimizer {
             // 计算参数更新
             let parameter_update = self.solve_linear_system(&jacobian, &residuals, lambda)?;
             
-            // 更新参数
+            // 更新参数：残差 r = y - f、J = ∂f/∂p 时，(JᵀJ + λI)Δp = Jᵀr 的解是下降方向，应加到参数上。
+            // 早期实现减去 Δp，步长总是被拒绝、λ 不断增大，拟合停在初值附近
             let mut new_params = params.clone();
             for (i, param) in new_params.parameters.iter_mut().enumerate() {
-                *param -= parameter_update[i];
+                *param += parameter_update[i];
             }
             
             // 应用边界约束
@@ -291,15 +370,31 @@ impl ParameterOptimizer {
             } else {
                 lambda *= 2.0;
             }
+            error_history.push(current_error.min(previous_error));
             
             // 检查收敛
             if parameter_update.iter().map(|&x| x.abs()).sum::<f64>() < convergence_threshold {
+                termination = TerminationReason::Converged;
                 break;
             }
         }
         
         let final_error = objective_function(x_data, y_data, &params);
-        let parameter_errors = self.estimate_parameter_errors(&objective_function, x_data, y_data, &params);
+        
+        // 由最终点的雅可比矩阵计算协方差 s²(JᵀJ)⁻¹；矩阵奇异时退回有限差分误差估计
+        let (parameter_errors, covariance, degrees_of_freedom) =
+            match self.compute_covariance(&objective_function, x_data, y_data, &params) {
+                Ok((covariance, degrees_of_freedom)) => {
+                    let errors = (0..covariance.len()).map(|i| covariance[i][i].max(0.0).sqrt()).collect();
+                    (errors, Some(covariance), degrees_of_freedom)
+                }
+                Err(e) => {
+                    log::warn!("⚠️ 协方差矩阵计算失败，使用有限差分误差估计: {}", e);
+                    let errors = self.estimate_parameter_errors(&objective_function, x_data, y_data, &params);
+                    (errors, None, x_data.len().saturating_sub(params.parameters.len()))
+                }
+            };
+        let confidence_intervals = confidence_intervals_95(&params.parameters, &parameter_errors, degrees_of_freedom);
         
         Ok(OptimizationResult {
             optimized_params: params,
@@ -307,9 +402,68 @@ impl ParameterOptimizer {
             iterations,
             converged: iterations < max_iterations,
             parameter_errors,
+            covariance,
+            confidence_intervals,
+            error_history,
+            termination,
         })
     }
     
+    /// 协方差矩阵 s²(JᵀJ)⁻¹，s² = RSS / (n - p)
+    ///
+    /// 只对自由参数求逆，固定参数对应的行列为零。返回 (协方差矩阵, 自由度)
+    fn compute_covariance<F>(
+        &self,
+        objective_function: &F,
+        x_data: &[f64],
+        y_data: &[f64],
+        params: &PeakShapeParams,
+    ) -> Result<(Vec<Vec<f64>>, usize), ProcessingError>
+    where
+        F: Fn(&[f64], &[f64], &PeakShapeParams) -> f64,
+    {
+        let (residuals, jacobian) = self.compute_residuals_and_jacobian(objective_function, x_data, y_data, params)?;
+        let n_params = params.parameters.len();
+        let free: Vec<usize> = (0..n_params).filter(|&i| !params.is_fixed(i)).collect();
+        
+        if residuals.len() <= free.len() {
+            return Err(ProcessingError::process_error("数据点数不足以估计参数协方差"));
+        }
+        let degrees_of_freedom = residuals.len() - free.len();
+        let residual_variance = residuals.iter().map(|r| r * r).sum::<f64>() / degrees_of_freedom as f64;
+        
+        let jtj: Vec<Vec<f64>> = free.iter()
+            .map(|&a| free.iter()
+                .map(|&b| jacobian.iter().map(|row| row[a] * row[b]).sum())
+                .collect())
+            .collect();
+        let inverse = self.invert_matrix(&jtj)?;
+        
+        let mut covariance = vec![vec![0.0; n_params]; n_params];
+        for (i, &a) in free.iter().enumerate() {
+            for (j, &b) in free.iter().enumerate() {
+                covariance[a][b] = residual_variance * inverse[i][j];
+            }
+        }
+        
+        Ok((covariance, degrees_of_freedom))
+    }
+    
+    /// 逐列求解 A x = e_i 得到逆矩阵
+    fn invert_matrix(&self, matrix: &[Vec<f64>]) -> Result<Vec<Vec<f64>>, ProcessingError> {
+        let n = matrix.len();
+        let mut inverse = vec![vec![0.0; n]; n];
+        for col in 0..n {
+            let mut unit = vec![0.0; n];
+            unit[col] = 1.0;
+            let solution = self.gaussian_elimination(matrix, &unit)?;
+            for row in 0..n {
+                inverse[row][col] = solution[row];
+            }
+        }
+        Ok(inverse)
+    }
+    
     /// 模拟退火优化
     fn simulated_annealing_optimization<F>(
         &self,
@@ -328,6 +482,7 @@ impl ParameterOptimizer {
         let mut temperature = initial_temperature;
         let mut best_params = params.clone();
         let mut best_error = objective_function(x_data, y_data, &best_params);
+        let mut error_history = Vec::new();
         
         for _ in 0..max_iterations {
             iterations += 1;
@@ -352,6 +507,8 @@ impl ParameterOptimizer {
                 }
             }
             
+            error_history.push(best_error);
+            
             // 降温
             temperature *= cooling_rate;
             
@@ -361,6 +518,7 @@ impl ParameterOptimizer {
         }
         
         let parameter_errors = self.estimate_parameter_errors(&objective_function, x_data, y_data, &best_params);
+        let confidence_intervals = confidence_intervals_95(&best_params.parameters, &parameter_errors, x_data.len().saturating_sub(best_params.parameters.len()));
         
         Ok(OptimizationResult {
             optimized_params: best_params,
@@ -368,6 +526,10 @@ impl ParameterOptimizer {
             iterations,
             converged: temperature < 1e-6,
             parameter_errors,
+            covariance: None,
+            confidence_intervals,
+            error_history,
+            termination: if temperature < 1e-6 { TerminationReason::TemperatureFloor } else { TerminationReason::MaxIterations },
         })
     }
     
@@ -428,6 +590,11 @@ impl ParameterOptimizer {
         let h = 1e-6;
         for i in 0..n_points {
             for j in 0..n_params {
+                // 固定参数不参与更新，对应列保持为零
+                if params.is_fixed(j) {
+                    continue;
+                }
+                
                 let mut params_plus = params.clone();
                 let mut params_minus = params.clone();
                 
@@ -463,6 +630,10 @@ impl ParameterOptimizer {
                 let denominator = 1.0 + ((x - center) / gamma).powi(2);
                 amplitude / denominator
             },
+            crate::core::processors::peak_fitting::peak_shapes::PeakShapeType::GMGBayesian
+            | crate::core::processors::peak_fitting::peak_shapes::PeakShapeType::NLC => {
+                PeakShapeCalculatorFactory::create_calculator(&params.shape_type).calculate(x, params)
+            },
             _ => {
                 // 默认使用高斯
                 let amplitude = params.get_parameter("amplitude").unwrap_or(0.0);
@@ -586,6 +757,11 @@ impl ParameterOptimizer {
         let mut errors = Vec::new();
         
         for i in 0..params.parameters.len() {
+            if params.is_fixed(i) {
+                errors.push(0.0);
+                continue;
+            }
+            
             let mut params_plus = params.clone();
             let mut params_minus = params.clone();
             
@@ -610,3 +786,115 @@ impl ParameterOptimizer {
         errors
     }
 }
+
+/// 双侧95%置信区间的t分布临界值
+fn t_critical_95(degrees_of_freedom: usize) -> f64 {
+    const TABLE: [f64; 30] = [
+        12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228,
+        2.201, 2.179, 2.160, 2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086,
+        2.080, 2.074, 2.069, 2.064, 2.060, 2.056, 2.052, 2.048, 2.045, 2.042,
+    ];
+    match degrees_of_freedom {
+        0 => f64::INFINITY,
+        1..=30 => TABLE[degrees_of_freedom - 1],
+        // 大自由度时的渐近展开
+        _ => 1.959964 + 2.37 / degrees_of_freedom as f64,
+    }
+}
+
+/// 由参数值与标准误差计算95%置信区间：p ± t(0.975, dof)·σ
+fn confidence_intervals_95(parameters: &[f64], errors: &[f64], degrees_of_freedom: usize) -> Vec<(f64, f64)> {
+    let t = t_critical_95(degrees_of_freedom);
+    parameters.iter()
+        .zip(errors)
+        .map(|(&p, &e)| if e == 0.0 { (p, p) } else { (p - t * e, p + t * e) })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use crate::core::processors::peak_fitting::peak_shapes::PeakShapeType;
+
+    /// 确定性的近似正态噪声（12个均匀分布之和减6），标准差为 sd
+    fn gaussian_noise(count: usize, sd: f64) -> Vec<f64> {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        (0..count)
+            .map(|_| ((0..12).map(|_| rng.gen::<f64>()).sum::<f64>() - 6.0) * sd)
+            .collect()
+    }
+
+    #[test]
+    fn test_lm_confidence_interval_contains_true_center() {
+        let x_data: Vec<f64> = (0..=200).map(|i| i as f64 * 0.05).collect();
+        let noise = gaussian_noise(x_data.len(), 2.0);
+        let y_data: Vec<f64> = x_data.iter()
+            .zip(&noise)
+            .map(|(&x, n)| 100.0 * (-(x - 5.0).powi(2) / (2.0 * 0.5 * 0.5)).exp() + n)
+            .collect();
+
+        let mut initial = PeakShapeParams::new(PeakShapeType::Gaussian);
+        initial.parameters = vec![90.0, 5.2, 0.6];
+
+        let calculator = PeakShapeCalculatorFactory::create_calculator(&PeakShapeType::Gaussian);
+        let objective = |x: &[f64], y: &[f64], p: &PeakShapeParams| -> f64 {
+            x.iter().zip(y).map(|(&xi, &yi)| (yi - calculator.calculate(xi, p)).powi(2)).sum()
+        };
+
+        let optimizer = ParameterOptimizer::new(OptimizationAlgorithm::LevenbergMarquardt {
+            max_iterations: 100,
+            convergence_threshold: 1e-6,
+            damping_factor: 0.1,
+        });
+        let result = optimizer.optimize(objective, initial, &x_data, &y_data).unwrap();
+
+        assert!(result.covariance.is_some());
+        assert_eq!(result.confidence_intervals.len(), 3);
+        let (lower, upper) = result.confidence_intervals[1];
+        assert!(lower <= 5.0 && 5.0 <= upper, "center CI ({}, {})", lower, upper);
+        assert!(upper - lower < 0.1, "center CI too wide: ({}, {})", lower, upper);
+        // 标准误差与协方差对角线一致
+        let covariance = result.covariance.unwrap();
+        assert!((result.parameter_errors[1] - covariance[1][1].sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_lm_step_is_added_for_y_minus_f_residuals() {
+        let x_data: Vec<f64> = (0..=200).map(|i| i as f64 * 0.05).collect();
+        let y_data: Vec<f64> = x_data.iter()
+            .map(|&x| 100.0 * (-(x - 5.0).powi(2) / (2.0 * 0.5 * 0.5)).exp())
+            .collect();
+        let mut initial = PeakShapeParams::new(PeakShapeType::Gaussian);
+        initial.parameters = vec![90.0, 5.2, 0.6];
+
+        let calculator = PeakShapeCalculatorFactory::create_calculator(&PeakShapeType::Gaussian);
+        let objective = |x: &[f64], y: &[f64], p: &PeakShapeParams| -> f64 {
+            x.iter().zip(y).map(|(&xi, &yi)| (yi - calculator.calculate(xi, p)).powi(2)).sum()
+        };
+        let optimizer = ParameterOptimizer::new(OptimizationAlgorithm::LevenbergMarquardt {
+            max_iterations: 100,
+            convergence_threshold: 1e-6,
+            damping_factor: 0.1,
+        });
+
+        // 单步：加上增量使误差下降，减去增量（旧实现）使误差上升
+        let (residuals, jacobian) = optimizer.compute_residuals_and_jacobian(&objective, &x_data, &y_data, &initial).unwrap();
+        let step = optimizer.solve_linear_system(&jacobian, &residuals, 0.1).unwrap();
+        let error_after = |sign: f64| {
+            let mut params = initial.clone();
+            for (value, delta) in params.parameters.iter_mut().zip(&step) {
+                *value += sign * delta;
+            }
+            objective(&x_data, &y_data, &params)
+        };
+        let start = objective(&x_data, &y_data, &initial);
+        assert!(error_after(1.0) < 0.5 * start, "added step: {} -> {}", start, error_after(1.0));
+        assert!(error_after(-1.0) > start, "subtracted step: {} -> {}", start, error_after(-1.0));
+
+        let result = optimizer.optimize(objective, initial, &x_data, &y_data).unwrap();
+        assert!(result.converged);
+        assert!((result.optimized_params.parameters[1] - 5.0).abs() < 1e-4, "center {}", result.optimized_params.parameters[1]);
+        assert!((result.optimized_params.parameters[0] - 100.0).abs() < 1e-2, "amplitude {}", result.optimized_params.parameters[0]);
+    }
+}
@@ -0,0 +1,161 @@
+//! 峰追踪模块
+//!
+//! 在按保留时间排序的一系列曲线（如不同RT处提取的迁移谱）中追踪同一个峰，
+//! 得到其中心与面积随时间的变化轨迹
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::data::{Curve, Peak, ProcessingError};
+use crate::core::processors::peak_detection::{create_detector, PeakDetector};
+
+/// 单帧的追踪结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackPoint {
+    /// 帧序号（输入曲线的顺序）
+    pub frame: usize,
+    /// 曲线ID
+    pub curve_id: String,
+    /// 该帧的保留时间（取曲线 rt_range 的中点）
+    pub retention_time: Option<f64>,
+    /// 是否在容差内找到峰
+    pub found: bool,
+    /// 峰中心
+    pub center: Option<f64>,
+    /// 峰面积
+    pub area: Option<f64>,
+    /// 峰高
+    pub amplitude: Option<f64>,
+    /// 相对上一次找到位置的偏移
+    pub shift: Option<f64>,
+}
+
+/// 峰追踪结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeakTrack {
+    pub initial_center: f64,
+    pub tolerance: f64,
+    /// 每帧的追踪点
+    pub trajectory: Vec<TrackPoint>,
+    /// 丢失峰的帧序号
+    pub lost_frames: Vec<usize>,
+}
+
+/// 沿曲线序列追踪距离参考位置最近的峰
+///
+/// 参考位置从 `initial_center` 开始，每找到一次峰就更新为该峰中心，因此可以跟随缓慢漂移的峰；
+/// 丢失的帧不更新参考位置。曲线没有峰时使用简单检测器检测。
+pub fn track_peak(curves: &[Curve], initial_center: f64, tolerance: f64) -> Result<PeakTrack, ProcessingError> {
+    if curves.is_empty() {
+        return Err(ProcessingError::DataError("没有可追踪的曲线".to_string()));
+    }
+    if tolerance.is_nan() || tolerance <= 0.0 {
+        return Err(ProcessingError::ConfigError(format!("追踪容差必须为正数: {}", tolerance)));
+    }
+
+    let detector = create_detector("simple")?;
+    let detector_config = serde_json::json!({});
+
+    let mut reference = initial_center;
+    let mut trajectory = Vec::with_capacity(curves.len());
+    let mut lost_frames = Vec::new();
+
+    for (frame, curve) in curves.iter().enumerate() {
+        let detected;
+        let peaks: &[Peak] = if curve.peaks.is_empty() {
+            detected = detector.detect_peaks(curve, &detector_config)?;
+            &detected
+        } else {
+            &curve.peaks
+        };
+
+        let nearest = peaks.iter()
+            .filter(|p| (p.center - reference).abs() <= tolerance)
+            .min_by(|a, b| (a.center - reference).abs().total_cmp(&(b.center - reference).abs()));
+
+        let retention_time = curve.rt_range.map(|(start, end)| (start + end) / 2.0);
+        let point = match nearest {
+            Some(peak) => {
+                let shift = peak.center - reference;
+                reference = peak.center;
+                TrackPoint {
+                    frame,
+                    curve_id: curve.id.clone(),
+                    retention_time,
+                    found: true,
+                    center: Some(peak.center),
+                    area: Some(peak.area),
+                    amplitude: Some(peak.amplitude),
+                    shift: Some(shift),
+                }
+            }
+            None => {
+                lost_frames.push(frame);
+                TrackPoint {
+                    frame,
+                    curve_id: curve.id.clone(),
+                    retention_time,
+                    found: false,
+                    center: None,
+                    area: None,
+                    amplitude: None,
+                    shift: None,
+                }
+            }
+        };
+        trajectory.push(point);
+    }
+
+    log::info!("📈 峰追踪完成: {} 帧, 丢失 {} 帧", curves.len(), lost_frames.len());
+
+    Ok(PeakTrack {
+        initial_center,
+        tolerance,
+        trajectory,
+        lost_frames,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 迁移谱：追踪峰（center 处，可缺失）加一个固定位置的干扰峰
+    fn mobilogram(frame: usize, center: Option<f64>) -> Curve {
+        let x: Vec<f64> = (0..200).map(|i| i as f64 * 0.05).collect();
+        let y: Vec<f64> = x.iter()
+            .map(|&v| {
+                let tracked = center.map_or(0.0, |c| 1000.0 * (-0.5 * ((v - c) / 0.2).powi(2)).exp());
+                tracked + 600.0 * (-0.5 * ((v - 8.0) / 0.2).powi(2)).exp()
+            })
+            .collect();
+        let mut curve = Curve::new(
+            format!("rt_{}", frame), "DT".to_string(), x, y,
+            "Drift Time".to_string(), "Intensity".to_string(), "ms".to_string(), "counts".to_string(),
+        );
+        curve.rt_range = Some((frame as f64, frame as f64 + 1.0));
+        curve
+    }
+
+    #[test]
+    fn follows_linearly_drifting_peak() {
+        let expected: Vec<f64> = (0..10).map(|k| 3.0 + 0.2 * k as f64).collect();
+        let curves: Vec<Curve> = expected.iter()
+            .enumerate()
+            .map(|(k, &c)| mobilogram(k, if k == 5 { None } else { Some(c) }))
+            .collect();
+
+        let track = track_peak(&curves, 3.0, 0.5).unwrap();
+
+        assert_eq!(track.lost_frames, vec![5]);
+        let first_area = track.trajectory[0].area.unwrap();
+        for (point, &center) in track.trajectory.iter().zip(&expected) {
+            if point.frame == 5 {
+                assert!(!point.found);
+                continue;
+            }
+            assert!((point.center.unwrap() - center).abs() < 0.03, "frame {}: {:?}", point.frame, point.center);
+            assert!((point.area.unwrap() / first_area - 1.0).abs() < 0.05);
+        }
+        assert_eq!(track.trajectory[3].retention_time, Some(3.5));
+    }
+}
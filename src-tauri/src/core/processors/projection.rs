@@ -0,0 +1,186 @@
+//! 光谱投影
+//!
+//! 把一组光谱投影为一条曲线：先按 MS级别/保留时间 选择光谱，每张光谱在 m/z 范围内的峰强度
+//! 由归约方式（求和/取最大）合成一个值，再按投影轴（保留时间或离子迁移率）落到X上。
+//! DT/TIC/XIC 提取器与 TIC/BPC 概览曲线共用这一遍历
+
+use mzdata::prelude::{SpectrumLike, MZLocated, IntensityMeasurement};
+use mzdata::spectrum::Spectrum;
+
+use crate::core::loaders::mzdata_loader::DataLoader;
+
+/// 光谱选择条件，未设置的项不做限制
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SpectrumFilter {
+    pub ms_level: Option<u8>,
+    pub rt_range: Option<(f64, f64)>,
+    /// 只累加该 m/z 范围内的峰；选择光谱时要求至少有一个峰落在范围内
+    pub mz_range: Option<(f64, f64)>,
+}
+
+impl SpectrumFilter {
+    /// 选出满足条件的光谱
+    pub fn select<'a>(&self, spectra: &'a [Spectrum]) -> Vec<&'a Spectrum> {
+        DataLoader::filter_spectra(
+            spectra,
+            self.ms_level,
+            self.rt_range.map(|(min, _)| min),
+            self.rt_range.map(|(_, max)| max),
+            self.mz_range.map(|(min, _)| min),
+            self.mz_range.map(|(_, max)| max),
+        )
+    }
+
+    fn contains_mz(&self, mz: f64) -> bool {
+        self.mz_range.is_none_or(|(min, max)| mz >= min && mz <= max)
+    }
+}
+
+/// 投影轴的取值来源
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AxisSource {
+    /// 光谱起始时间
+    RetentionTime,
+    /// 扫描的离子迁移率（漂移时间），没有该值的光谱被跳过
+    IonMobility,
+}
+
+/// 投影轴：X取值来源与分箱
+#[derive(Debug, Clone, Copy)]
+pub struct ProjectionAxis {
+    pub source: AxisSource,
+    /// 每单位X的分箱数，X截断到 1/bins_per_unit 后落在同一箱的光谱合并；None 时每张光谱各为一点
+    pub bins_per_unit: Option<f64>,
+}
+
+impl ProjectionAxis {
+    /// 提取器使用的毫秒级分箱
+    pub const EXTRACTION_BINS_PER_UNIT: f64 = 1000.0;
+
+    pub fn retention_time() -> Self {
+        Self { source: AxisSource::RetentionTime, bins_per_unit: Some(Self::EXTRACTION_BINS_PER_UNIT) }
+    }
+
+    pub fn drift_time() -> Self {
+        Self { source: AxisSource::IonMobility, bins_per_unit: Some(Self::EXTRACTION_BINS_PER_UNIT) }
+    }
+
+    /// 不分箱，每张光谱各为一点
+    pub fn unbinned(self) -> Self {
+        Self { bins_per_unit: None, ..self }
+    }
+
+    fn value(&self, spectrum: &Spectrum) -> Option<f64> {
+        match self.source {
+            AxisSource::RetentionTime => Some(spectrum.start_time()),
+            AxisSource::IonMobility => spectrum.ion_mobility(),
+        }
+    }
+}
+
+/// 强度归约方式，同时用于光谱内的峰和同一X上的多张光谱
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reducer {
+    /// 强度求和（TIC/XIC/DT）
+    Sum,
+    /// 取最大强度（BPC）
+    Max,
+}
+
+impl Reducer {
+    fn combine(&self, acc: f64, value: f64) -> f64 {
+        match self {
+            Self::Sum => acc + value,
+            Self::Max => acc.max(value),
+        }
+    }
+}
+
+/// 把光谱投影为按X升序排列的 (X, 强度) 点
+pub fn project_spectra(
+    spectra: &[&Spectrum],
+    filter: &SpectrumFilter,
+    axis: ProjectionAxis,
+    reducer: Reducer,
+) -> Vec<(f64, f64)> {
+    let mut points: Vec<(f64, f64)> = Vec::new();
+    let mut bins: std::collections::HashMap<u64, f64> = std::collections::HashMap::new();
+
+    for spectrum in spectra {
+        if filter.ms_level.is_some_and(|level| spectrum.ms_level() != level) {
+            continue;
+        }
+        if filter.rt_range.is_some_and(|(min, max)| spectrum.start_time() < min || spectrum.start_time() > max) {
+            continue;
+        }
+        let Some(x) = axis.value(spectrum) else {
+            continue;
+        };
+
+        let value = spectrum.peaks().iter()
+            .filter(|peak| filter.contains_mz(peak.mz()))
+            .fold(0.0, |acc, peak| reducer.combine(acc, peak.intensity() as f64));
+
+        match axis.bins_per_unit {
+            Some(bins_per_unit) => {
+                let key = (x * bins_per_unit) as u64;
+                let entry = bins.entry(key).or_insert(0.0);
+                *entry = reducer.combine(*entry, value);
+            },
+            None => points.push((x, value)),
+        }
+    }
+
+    if let Some(bins_per_unit) = axis.bins_per_unit {
+        let mut sorted: Vec<(u64, f64)> = bins.into_iter().collect();
+        sorted.sort_by(|a, b| a.0.cmp(&b.0));
+        points = sorted.into_iter().map(|(key, value)| (key as f64 / bins_per_unit, value)).collect();
+    } else {
+        points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+    }
+
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::data::DataContainer;
+    use crate::core::processors::base::Processor;
+    use crate::core::processors::dt_extractor::DTExtractor;
+    use crate::core::processors::tic_extractor::TICExtractor;
+    use crate::core::utils::test_fixtures::{ms1_spectrum, with_drift_time};
+
+    /// 6 张 MS1 光谱：前两张落在同一个保留时间箱，第 3/4 张落在同一个漂移时间箱；
+    /// m/z 100 与 200 的峰在 50-500 范围内，m/z 900 的峰在范围外
+    fn fixture() -> DataContainer {
+        let rts = [0.1004, 0.1006, 0.2004, 0.3004, 0.4004, 0.5004];
+        let dts = [2.0004, 2.0006, 3.0004, 3.0004, 4.5004, 5.0004];
+        let mut container = DataContainer::new();
+        for (i, (&rt, &dt)) in rts.iter().zip(&dts).enumerate() {
+            let peaks = [(100.0, 10.0 * (i + 1) as f32), (200.0, 5.0), (900.0, 1000.0)];
+            container.spectra.push(with_drift_time(ms1_spectrum(i, rt, &peaks), dt));
+        }
+        container
+    }
+
+    #[tokio::test]
+    async fn refactored_tic_and_dt_match_pre_refactor_baseline() {
+        let container = fixture();
+        let config = serde_json::json!({"mz_range": "50-500", "rt_range": "0-1", "ms_level": 1});
+
+        // 重构前的逐光谱累加结果：强度为 10·(i+1) + 5，同一箱内求和
+        let tic = TICExtractor.process(container.clone(), config.clone()).await.unwrap();
+        assert_eq!(tic.curves[0].x_values, vec![0.1, 0.2, 0.3, 0.4, 0.5]);
+        assert_eq!(tic.curves[0].y_values, vec![40.0, 35.0, 45.0, 55.0, 65.0]);
+
+        let dt = DTExtractor.process(container.clone(), config).await.unwrap();
+        assert_eq!(dt.curves[0].x_values, vec![2.0, 3.0, 4.5, 5.0]);
+        assert_eq!(dt.curves[0].y_values, vec![40.0, 80.0, 55.0, 65.0]);
+
+        // 概览曲线不分箱，BPC 取每张光谱的最大峰
+        let bpc = container.compute_bpc(Some(1)).unwrap();
+        assert_eq!(bpc.x_values, vec![0.1004, 0.1006, 0.2004, 0.3004, 0.4004, 0.5004]);
+        assert_eq!(bpc.y_values, vec![1000.0; 6]);
+    }
+}
@@ -0,0 +1,108 @@
+//! 定量分析模块
+//! 
+//! 基于内标法（Internal Standard）计算分析物/内标峰面积比
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::data::{Peak, ProcessingError};
+
+/// 内标定量结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuantitationResult {
+    /// 分析物标签
+    pub analyte_label: String,
+    /// 内标标签
+    pub is_label: String,
+    /// 分析物峰面积
+    pub analyte_area: f64,
+    /// 内标峰面积
+    pub is_area: f64,
+    /// 面积比 (analyte / IS)
+    pub area_ratio: f64,
+    /// 面积比的传播不确定度 (1σ)
+    pub ratio_uncertainty: f64,
+    /// 面积比的相对不确定度
+    pub relative_uncertainty: f64,
+}
+
+/// 计算分析物与内标的峰面积比及其传播不确定度
+/// 
+/// 不确定度按比值的一阶误差传播计算：
+/// σ_R = R · sqrt((σ_A / A)² + (σ_IS / IS)²)，其中 σ 取自峰的 `area_error`
+/// （由拟合参数误差传播得到的面积不确定度；未拟合的峰为 0）
+pub fn quantify(peaks: &[Peak], analyte_label: &str, is_label: &str) -> Result<QuantitationResult, ProcessingError> {
+    let analyte = find_labeled_peak(peaks, analyte_label)?;
+    let internal_standard = find_labeled_peak(peaks, is_label)?;
+    
+    if internal_standard.area == 0.0 {
+        return Err(ProcessingError::MathError(format!(
+            "内标峰 '{}' 的面积为0，无法计算面积比", is_label
+        )));
+    }
+    
+    let area_ratio = analyte.area / internal_standard.area;
+    let relative_uncertainty = (relative_error(analyte).powi(2)
+        + relative_error(internal_standard).powi(2))
+        .sqrt();
+    
+    Ok(QuantitationResult {
+        analyte_label: analyte_label.to_string(),
+        is_label: is_label.to_string(),
+        analyte_area: analyte.area,
+        is_area: internal_standard.area,
+        area_ratio,
+        ratio_uncertainty: area_ratio.abs() * relative_uncertainty,
+        relative_uncertainty,
+    })
+}
+
+/// 按标签查找峰
+fn find_labeled_peak<'a>(peaks: &'a [Peak], label: &str) -> Result<&'a Peak, ProcessingError> {
+    peaks.iter()
+        .find(|peak| peak.get_label() == Some(label))
+        .ok_or_else(|| ProcessingError::DataError(format!("未找到标签为 '{}' 的峰", label)))
+}
+
+/// 峰面积的相对误差
+fn relative_error(peak: &Peak) -> f64 {
+    if peak.area != 0.0 {
+        peak.area_error / peak.area.abs()
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::data::PeakType;
+
+    fn labeled_peak(id: &str, label: &str, area: f64, area_error: f64) -> Peak {
+        let mut peak = Peak::new(id.to_string(), "xic".to_string(), 5.0, 100.0, PeakType::Gaussian);
+        peak.area = area;
+        peak.area_error = area_error;
+        // 拟合残差不参与面积不确定度
+        peak.standard_error = 50.0;
+        peak.set_label(label);
+        peak
+    }
+
+    #[test]
+    fn ratio_of_labeled_peaks_with_propagated_area_error() {
+        let peaks = vec![
+            Peak::new("unlabeled".to_string(), "xic".to_string(), 1.0, 10.0, PeakType::Gaussian),
+            labeled_peak("p1", "analyte", 300.0, 9.0),
+            labeled_peak("p2", "IS", 200.0, 8.0),
+        ];
+
+        let result = quantify(&peaks, "analyte", "IS").unwrap();
+        assert_eq!(result.analyte_area, 300.0);
+        assert_eq!(result.is_area, 200.0);
+        assert_eq!(result.area_ratio, 300.0 / 200.0);
+        // sqrt(0.03² + 0.04²) = 0.05
+        assert!((result.relative_uncertainty - 0.05).abs() < 1e-12);
+        assert!((result.ratio_uncertainty - 1.5 * 0.05).abs() < 1e-12);
+
+        assert!(matches!(quantify(&peaks, "analyte", "missing"), Err(ProcessingError::DataError(_))));
+    }
+}
@@ -0,0 +1,195 @@
+//! 一键分析
+//!
+//! 不需要手动调参：估计噪声基底和主峰半峰宽，按峰宽选择 Savitzky-Golay 平滑窗口做轻度平滑，
+//! 用敏感度自动校准选出检测敏感度，再交给峰分析器完成检测与拟合。所有自动选出的参数都随结果返回
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::data::{Curve, DataContainer, ProcessingError, ProcessingResult};
+use crate::core::processors::core::Processor;
+use crate::core::processors::peak_analysis::PeakAnalyzer;
+use crate::core::processors::peak_detection::estimate_noise_floor;
+use crate::core::processors::peak_detection::sensitivity_calibration::calibrate_sensitivity;
+use crate::core::utils::signal::{mean_spacing, savitzky_golay};
+
+/// 一键分析使用的检测方法
+const QUICK_DETECTION_METHOD: &str = "simple";
+/// 平滑多项式阶数；二阶在窗口不超过半峰宽时基本不削峰
+const SMOOTHING_ORDER: usize = 2;
+/// 平滑窗口占半峰宽点数的比例
+const SMOOTHING_FWHM_FRACTION: f64 = 0.5;
+const MIN_SMOOTHING_WINDOW: usize = 5;
+
+/// 一条曲线上自动选出的参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuickAnalysisParameters {
+    pub curve_id: String,
+    pub noise_level: f64,
+    pub baseline: f64,
+    /// 最高峰的半峰宽估计（X单位）
+    pub estimated_fwhm: f64,
+    /// 平滑窗口（点数）；曲线过短时不平滑
+    pub smoothing_window: Option<usize>,
+    pub smoothing_order: usize,
+    /// 检测的最小峰宽，取半峰宽估计的一半
+    pub min_peak_width: f64,
+    pub detection_method: String,
+    pub sensitivity: f64,
+}
+
+/// 估计最高峰的半峰宽（点数），在 5 点二阶预平滑后的曲线上从峰顶向两侧找基线以上半高处
+fn estimate_fwhm_points(y: &[f64], baseline: f64, dx: f64) -> usize {
+    let smoothed = savitzky_golay(y, MIN_SMOOTHING_WINDOW, SMOOTHING_ORDER, 0, dx).unwrap_or_else(|_| y.to_vec());
+    let Some((apex, &height)) = smoothed.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1)) else {
+        return 0;
+    };
+    let half = baseline + (height - baseline) / 2.0;
+    let left = smoothed[..apex].iter().rposition(|&v| v <= half).unwrap_or(0);
+    let right = smoothed[apex..].iter().position(|&v| v <= half).map_or(smoothed.len() - 1, |i| apex + i);
+    right - left
+}
+
+/// 对一条曲线自动选参、平滑，返回平滑后的曲线与所选参数
+fn prepare_curve(curve: &Curve) -> Result<(Curve, QuickAnalysisParameters), ProcessingError> {
+    let n = curve.x_values.len().min(curve.y_values.len());
+    if n < MIN_SMOOTHING_WINDOW {
+        return Err(ProcessingError::DataError(format!("曲线 {} 数据点不足", curve.id)));
+    }
+    let noise_floor = estimate_noise_floor(curve);
+    let dx = mean_spacing(&curve.x_values);
+    let fwhm_points = estimate_fwhm_points(&curve.y_values[..n], noise_floor.baseline, dx).max(1);
+
+    let mut window = ((fwhm_points as f64 * SMOOTHING_FWHM_FRACTION) as usize).max(MIN_SMOOTHING_WINDOW);
+    if window % 2 == 0 {
+        window += 1;
+    }
+    let smoothing_window = (window <= n).then_some(window);
+
+    let smoothed = match smoothing_window {
+        Some(window) => {
+            // 重新构造曲线以更新强度统计量（检测阈值依赖这些统计量）
+            let mut smoothed = Curve::new(
+                curve.id.clone(),
+                curve.curve_type.clone(),
+                curve.x_values[..n].to_vec(),
+                savitzky_golay(&curve.y_values[..n], window, SMOOTHING_ORDER, 0, dx)?,
+                curve.x_label.clone(),
+                curve.y_label.clone(),
+                curve.x_unit.clone(),
+                curve.y_unit.clone(),
+            );
+            smoothed.mz_range = curve.mz_range;
+            smoothed.rt_range = curve.rt_range;
+            smoothed.dt_range = curve.dt_range;
+            smoothed.ms_level = curve.ms_level;
+            smoothed.metadata = curve.metadata.clone();
+            smoothed.add_metadata("smoothing_method".to_string(), serde_json::json!("savitzky_golay"));
+            smoothed.add_metadata("smoothing_window".to_string(), serde_json::json!(window));
+            smoothed
+        },
+        None => curve.clone(),
+    };
+
+    let estimated_fwhm = fwhm_points as f64 * dx;
+    let min_peak_width = estimated_fwhm / 2.0;
+    let calibration = calibrate_sensitivity(
+        &smoothed,
+        QUICK_DETECTION_METHOD,
+        &serde_json::json!({"min_peak_width": min_peak_width}),
+    )?;
+
+    let parameters = QuickAnalysisParameters {
+        curve_id: curve.id.clone(),
+        noise_level: noise_floor.noise,
+        baseline: noise_floor.baseline,
+        estimated_fwhm,
+        smoothing_window,
+        smoothing_order: SMOOTHING_ORDER,
+        min_peak_width,
+        detection_method: calibration.detection_method,
+        sensitivity: calibration.recommended_sensitivity,
+    };
+    Ok((smoothed, parameters))
+}
+
+/// 一键分析容器中的所有曲线
+///
+/// 每条曲线按自身的参数单独分析；结果元数据 `quick_analysis_parameters` 记录各曲线自动选出的参数
+pub async fn quick_analyze(input: DataContainer) -> Result<ProcessingResult, ProcessingError> {
+    if input.curves.is_empty() {
+        return Err(ProcessingError::DataError("容器中没有曲线".to_string()));
+    }
+
+    let analyzer = PeakAnalyzer::new();
+    let mut result = ProcessingResult::new();
+    let mut parameters = Vec::with_capacity(input.curves.len());
+    for curve in &input.curves {
+        let (smoothed, curve_parameters) = prepare_curve(curve)?;
+        log::info!("⚡ 一键分析 {}: 噪声 {:.4}, 半峰宽 {:.4}, 平滑窗口 {:?}, 敏感度 {:.2}",
+            curve.id, curve_parameters.noise_level, curve_parameters.estimated_fwhm,
+            curve_parameters.smoothing_window, curve_parameters.sensitivity);
+
+        let mut container = DataContainer::new();
+        container.curves.push(smoothed);
+        let config = serde_json::json!({
+            "detection_method": curve_parameters.detection_method,
+            "fitting_method": "multi_peak",
+            "overlapping_processing": "auto",
+            "sensitivity": curve_parameters.sensitivity,
+            "min_peak_width": curve_parameters.min_peak_width,
+        });
+        let analysis = analyzer.process(container, config).await?;
+        result.curves.extend(analysis.curves);
+        result.peaks.extend(analysis.peaks);
+        parameters.push(curve_parameters);
+    }
+
+    result.metadata = input.metadata;
+    result.add_metadata("quick_analysis_parameters".to_string(), serde_json::to_value(&parameters)
+        .map_err(ProcessingError::SerializationError)?);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+
+    #[tokio::test]
+    async fn three_noisy_peaks_found_without_manual_parameters() {
+        // 三个分离的高斯峰，叠加约为最高峰 3% 的确定性伪随机噪声
+        let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+        let x_values: Vec<f64> = (0..600).map(|i| i as f64 * 0.02).collect();
+        let y_values: Vec<f64> = x_values
+            .iter()
+            .map(|&x| {
+                let noise = rng.gen_range(-3.0..3.0);
+                [(3.0, 100.0), (6.0, 80.0), (9.0, 60.0)].iter()
+                    .map(|&(center, amplitude)| amplitude * (-(x - center).powi(2) / (2.0 * 0.2 * 0.2)).exp())
+                    .sum::<f64>() + 5.0 + noise
+            })
+            .collect();
+        let mut input = DataContainer::new();
+        input.curves.push(Curve::new(
+            "noisy".to_string(),
+            "DT".to_string(),
+            x_values,
+            y_values,
+            "Drift Time".to_string(),
+            "Intensity".to_string(),
+            "ms".to_string(),
+            "counts".to_string(),
+        ));
+
+        let result = quick_analyze(input).await.unwrap();
+
+        assert_eq!(result.peaks.len(), 3, "{:?}", result.peaks.iter().map(|p| p.center).collect::<Vec<_>>());
+        for expected in [3.0, 6.0, 9.0] {
+            assert!(result.peaks.iter().any(|p| (p.center - expected).abs() < 0.1), "missing peak at {}", expected);
+        }
+        let parameters = &result.metadata["quick_analysis_parameters"][0];
+        assert!(parameters["smoothing_window"].as_u64().unwrap() >= 5);
+        assert!((parameters["estimated_fwhm"].as_f64().unwrap() - 0.471).abs() < 0.1);
+        assert!(parameters["sensitivity"].as_f64().unwrap() > 0.0);
+    }
+}
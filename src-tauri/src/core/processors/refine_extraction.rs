@@ -0,0 +1,157 @@
+//! 精确 m/z 重提取
+//!
+//! 宽 m/z 窗口提取的曲线混入了窗口内的其他离子。检测到特征峰后，从峰区间内贡献的光谱计算特征的
+//! m/z 质心，再以质心为中心、较窄的窗口重新提取同类型曲线，降低背景、提高信噪比
+
+use serde::{Deserialize, Serialize};
+use mzdata::prelude::{SpectrumLike, MZLocated, IntensityMeasurement};
+
+use crate::core::data::{AxisLabels, Curve, DataContainer, Peak, ProcessingError};
+use crate::core::processors::overlay_extractor::extract_by_type;
+use crate::core::processors::projection::SpectrumFilter;
+
+/// 重提取结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefinedExtraction {
+    /// 特征的强度加权 m/z 质心
+    pub mz_centroid: f64,
+    /// 重提取使用的 m/z 窗口
+    pub mz_range: (f64, f64),
+    /// 峰区间内参与质心计算的光谱数
+    pub contributing_spectra: usize,
+    pub curve: Curve,
+}
+
+/// 以峰的 m/z 质心为中心、`mz_expansion` 为半宽重新提取曲线
+///
+/// 仅支持带 m/z 窗口的 DT 与 XIC 曲线。质心计算先取峰区间内贡献光谱在原窗口中强度最大的 m/z 点，
+/// 再对其 ±mz_expansion 内的点做强度加权平均，窗口内其他离子不参与
+pub async fn refine_extraction(
+    container: DataContainer,
+    curve: &Curve,
+    peak: &Peak,
+    mz_expansion: f64,
+) -> Result<RefinedExtraction, ProcessingError> {
+    if !mz_expansion.is_finite() || mz_expansion <= 0.0 {
+        return Err(ProcessingError::ConfigError(format!("mz_expansion 必须为正数: {}", mz_expansion)));
+    }
+    let curve_type = curve.curve_type.to_lowercase();
+    if curve_type != "dt" && curve_type != "xic" {
+        return Err(ProcessingError::ConfigError(format!("仅 DT 和 XIC 曲线支持精确 m/z 重提取: {}", curve.curve_type)));
+    }
+    let mz_range = curve.mz_range
+        .ok_or_else(|| ProcessingError::ConfigError(format!("曲线 {} 没有记录 m/z 范围", curve.id)))?;
+    let ms_level = curve.ms_level.unwrap_or(1);
+    let rt_range = match curve.rt_range {
+        Some(range) => range,
+        None => container.spectra.iter()
+            .map(|s| s.start_time())
+            .fold(None, |range: Option<(f64, f64)>, rt| Some(range.map_or((rt, rt), |(min, max)| (min.min(rt), max.max(rt)))))
+            .ok_or_else(|| ProcessingError::DataError("容器中没有光谱".to_string()))?,
+    };
+
+    // 峰区间内贡献的光谱在原窗口中的 m/z 点
+    let filter = SpectrumFilter { ms_level: Some(ms_level), rt_range: Some(rt_range), mz_range: Some(mz_range) };
+    let (region_start, region_end) = peak.region();
+    let contributing: Vec<_> = filter.select(&container.spectra).into_iter()
+        .filter(|spectrum| {
+            let x = if curve_type == "dt" { spectrum.ion_mobility() } else { Some(spectrum.start_time()) };
+            x.is_some_and(|x| x >= region_start && x <= region_end)
+        })
+        .collect();
+    let points: Vec<(f64, f64)> = contributing.iter()
+        .flat_map(|spectrum| spectrum.peaks().iter()
+            .map(|p| (p.mz(), p.intensity() as f64))
+            .filter(|&(mz, intensity)| mz >= mz_range.0 && mz <= mz_range.1 && intensity > 0.0)
+            .collect::<Vec<_>>())
+        .collect();
+
+    let seed = points.iter()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|&(mz, _)| mz)
+        .ok_or_else(|| ProcessingError::DataError(format!(
+            "峰 {} 区间 {:.4}-{:.4} 内没有落在 m/z 窗口中的信号", peak.id, region_start, region_end)))?;
+    let (weighted, total) = points.iter()
+        .filter(|(mz, _)| (mz - seed).abs() <= mz_expansion)
+        .fold((0.0, 0.0), |(weighted, total), (mz, intensity)| (weighted + mz * intensity, total + intensity));
+    let mz_centroid = weighted / total;
+    let refined_range = (mz_centroid - mz_expansion, mz_centroid + mz_expansion);
+
+    let labels = AxisLabels {
+        x_label: Some(curve.x_label.clone()),
+        y_label: Some(curve.y_label.clone()),
+        x_unit: Some(curve.x_unit.clone()),
+        y_unit: Some(curve.y_unit.clone()),
+    };
+    let result = extract_by_type(
+        container,
+        &curve_type,
+        &format!("{}-{}", refined_range.0, refined_range.1),
+        &format!("{}-{}", rt_range.0, rt_range.1),
+        ms_level,
+        &labels,
+    ).await?;
+    let mut refined = result.curves.into_iter().next()
+        .ok_or_else(|| ProcessingError::DataError("重提取没有得到曲线".to_string()))?;
+    refined.metadata.insert("refined_from".to_string(), serde_json::json!(curve.id));
+    refined.metadata.insert("refined_mz_centroid".to_string(), serde_json::json!(mz_centroid));
+    refined.metadata.insert("original_mz_range".to_string(), serde_json::json!([mz_range.0, mz_range.1]));
+
+    Ok(RefinedExtraction {
+        mz_centroid,
+        mz_range: refined_range,
+        contributing_spectra: contributing.len(),
+        curve: refined,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::data::PeakType;
+    use crate::core::processors::base::Processor;
+    use crate::core::processors::dt_extractor::DTExtractor;
+    use crate::core::processors::peak_detection::estimate_noise_floor;
+    use crate::core::utils::test_fixtures::{ms1_spectrum, with_drift_time};
+
+    /// 100 张 MS1 光谱，漂移时间 0-9.9 ms：m/z 500.002 处的特征在 5 ms 出峰，
+    /// 495 与 505 处的干扰离子强度起伏，只在宽窗口内
+    fn fixture() -> DataContainer {
+        let mut container = DataContainer::new();
+        for i in 0..100 {
+            let dt = i as f64 * 0.1;
+            let analyte = 1.0 + 0.5 * (i as f64 * 2.3).sin() + 1000.0 * (-(dt - 5.0).powi(2) / (2.0 * 0.5 * 0.5)).exp();
+            let peaks = [
+                (495.0, (300.0 + 200.0 * (i as f64 * 1.7).sin()) as f32),
+                (500.002, analyte as f32),
+                (505.0, (300.0 + 200.0 * (i as f64 * 0.9).cos()) as f32),
+            ];
+            container.spectra.push(with_drift_time(ms1_spectrum(i, 0.5, &peaks), dt));
+        }
+        container
+    }
+
+    fn snr(curve: &Curve) -> f64 {
+        let noise_floor = estimate_noise_floor(curve);
+        let max = curve.y_values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        (max - noise_floor.baseline) / noise_floor.noise
+    }
+
+    #[tokio::test]
+    async fn refined_window_improves_snr() {
+        let container = fixture();
+        let config = serde_json::json!({"mz_range": "490-510", "rt_range": "0-1", "ms_level": 1});
+        let broad = DTExtractor.process(container.clone(), config).await.unwrap().curves.remove(0);
+
+        let mut peak = Peak::new("feature".to_string(), broad.id.clone(), 5.0, 1000.0, PeakType::Gaussian);
+        peak.left_boundary = 3.5;
+        peak.right_boundary = 6.5;
+
+        let refined = refine_extraction(container, &broad, &peak, 0.05).await.unwrap();
+
+        assert!((refined.mz_centroid - 500.002).abs() < 1e-3);
+        assert!(refined.contributing_spectra >= 30);
+        assert!(snr(&refined.curve) > snr(&broad), "{} <= {}", snr(&refined.curve), snr(&broad));
+        assert_eq!(refined.curve.mz_range, Some(refined.mz_range));
+    }
+}
@@ -0,0 +1,148 @@
+//! 感兴趣区域统计
+//!
+//! 手动框选一段X区间后，不做峰拟合直接给出区间内的面积、最大值、质心、均值和标准差，
+//! 以及区间面积占整条曲线面积的比例。区间端点按线性插值补点，面积和质心用梯形积分
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::data::{Curve, ProcessingError};
+
+/// 一条曲线在区间内的统计量
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegionStats {
+    pub curve_id: String,
+    /// 截断到曲线X范围后的实际区间
+    pub x_min: f64,
+    pub x_max: f64,
+    /// 区间内（含端点插值）的梯形面积
+    pub area: f64,
+    pub max: f64,
+    /// 最大值所在的X
+    pub max_x: f64,
+    /// 强度加权的X质心；区间面积为零时为 None
+    pub centroid: Option<f64>,
+    /// 区间内原始数据点的强度均值与总体标准差
+    pub mean: f64,
+    pub std: f64,
+    pub point_count: usize,
+    /// 区间面积占整条曲线面积的比例；整条曲线面积为零时为 None
+    pub area_fraction: Option<f64>,
+}
+
+/// 在 `at` 处线性插值，`x` 须升序
+fn interpolate(x: &[f64], y: &[f64], at: f64) -> f64 {
+    let right = x.partition_point(|&v| v < at).min(x.len() - 1);
+    if right == 0 || x[right] == at {
+        return y[right];
+    }
+    let left = right - 1;
+    y[left] + (y[right] - y[left]) * (at - x[left]) / (x[right] - x[left])
+}
+
+/// 梯形积分，返回 (∫y dx, ∫x·y dx)
+fn integrate(points: &[(f64, f64)]) -> (f64, f64) {
+    points.windows(2).fold((0.0, 0.0), |(area, moment), w| {
+        let dx = w[1].0 - w[0].0;
+        (area + dx * (w[0].1 + w[1].1) / 2.0, moment + dx * (w[0].0 * w[0].1 + w[1].0 * w[1].1) / 2.0)
+    })
+}
+
+/// 计算曲线在 [x_min, x_max] 内的统计量，区间截断到曲线X范围
+pub fn region_stats(curve: &Curve, x_min: f64, x_max: f64) -> Result<RegionStats, ProcessingError> {
+    if !x_min.is_finite() || !x_max.is_finite() || x_min >= x_max {
+        return Err(ProcessingError::ConfigError(format!("无效的区间: {} - {}", x_min, x_max)));
+    }
+    let n = curve.x_values.len().min(curve.y_values.len());
+    if n < 2 {
+        return Err(ProcessingError::DataError(format!("曲线 {} 数据点不足", curve.id)));
+    }
+    let x = &curve.x_values[..n];
+    let y = &curve.y_values[..n];
+
+    let lo = x_min.max(x[0]);
+    let hi = x_max.min(x[n - 1]);
+    if lo >= hi {
+        return Err(ProcessingError::ConfigError(format!(
+            "区间 {} - {} 与曲线 {} 的X范围 {} - {} 没有重叠", x_min, x_max, curve.id, x[0], x[n - 1])));
+    }
+
+    let inside: Vec<(f64, f64)> = x.iter().zip(y)
+        .filter(|(xi, _)| **xi >= lo && **xi <= hi)
+        .map(|(&xi, &yi)| (xi, yi))
+        .collect();
+    let mut points = Vec::with_capacity(inside.len() + 2);
+    if inside.first().is_none_or(|&(xi, _)| xi > lo) {
+        points.push((lo, interpolate(x, y, lo)));
+    }
+    points.extend_from_slice(&inside);
+    if inside.last().is_none_or(|&(xi, _)| xi < hi) {
+        points.push((hi, interpolate(x, y, hi)));
+    }
+
+    let (area, moment) = integrate(&points);
+    let (total_area, _) = integrate(&x.iter().copied().zip(y.iter().copied()).collect::<Vec<_>>());
+    let (max_x, max) = points.iter()
+        .copied()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap_or((lo, 0.0));
+
+    let (mean, std) = if inside.is_empty() {
+        (0.0, 0.0)
+    } else {
+        let mean = inside.iter().map(|(_, yi)| yi).sum::<f64>() / inside.len() as f64;
+        let variance = inside.iter().map(|(_, yi)| (yi - mean).powi(2)).sum::<f64>() / inside.len() as f64;
+        (mean, variance.sqrt())
+    };
+
+    Ok(RegionStats {
+        curve_id: curve.id.clone(),
+        x_min: lo,
+        x_max: hi,
+        area,
+        max,
+        max_x,
+        centroid: (area != 0.0).then(|| moment / area),
+        mean,
+        std,
+        point_count: inside.len(),
+        area_fraction: (total_area != 0.0).then(|| area / total_area),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_around_gaussian_matches_expected_fraction_and_centroid() {
+        let x_values: Vec<f64> = (0..=1000).map(|i| i as f64 * 0.01).collect();
+        let y_values: Vec<f64> = x_values.iter()
+            .map(|&x| 100.0 * (-(x - 5.0).powi(2) / (2.0 * 0.5 * 0.5)).exp())
+            .collect();
+        let curve = Curve::new(
+            "gaussian".to_string(),
+            "DT".to_string(),
+            x_values,
+            y_values,
+            "Drift Time".to_string(),
+            "Intensity".to_string(),
+            "ms".to_string(),
+            "counts".to_string(),
+        );
+
+        // ±2σ 覆盖 95.45% 的面积
+        let stats = region_stats(&curve, 4.0, 6.0).unwrap();
+        assert!((stats.area_fraction.unwrap() - 0.9545).abs() < 1e-3);
+        assert!((stats.centroid.unwrap() - 5.0).abs() < 1e-6);
+        assert!((stats.max - 100.0).abs() < 1e-9);
+        assert_eq!(stats.max_x, 5.0);
+
+        // 偏向一侧的非网格区间：端点插值，质心偏向高强度一侧
+        let stats = region_stats(&curve, 4.995, 20.0).unwrap();
+        assert_eq!(stats.x_max, 10.0);
+        assert!((stats.area_fraction.unwrap() - 0.5040).abs() < 1e-3);
+        assert!(stats.centroid.unwrap() > 5.0);
+
+        assert!(region_stats(&curve, 11.0, 12.0).is_err());
+    }
+}
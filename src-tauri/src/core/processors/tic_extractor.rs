@@ -3,11 +3,11 @@ use serde_json::Value;
 use std::collections::HashMap;
 use uuid::Uuid;
 
-use crate::core::data::{DataContainer, Curve, ProcessingError};
+use crate::core::data::{AxisLabels, DataContainer, Curve, ProcessingError};
 use crate::core::data::ProcessingResult;
 use crate::core::loaders::mzdata_loader::DataLoader;
 use crate::core::processors::base::Processor;
-use mzdata::prelude::{SpectrumLike, MZLocated, IntensityMeasurement};
+use crate::core::processors::projection::{project_spectra, ProjectionAxis, Reducer, SpectrumFilter};
 
 /// TIC提取器 - 基于mzdata实现, 提取出TIC曲线,同时可以进行分析?
 pub struct TICExtractor;
@@ -68,15 +68,15 @@ impl Processor for TICExtractor {
         
         let (rt_min, rt_max) = parse_range(rt_range)?;
 
+        // 请求的MS级别必须存在于文件中
+        input.ensure_ms_level(ms_level)?;
+
         // 过滤光谱
-        let filtered_spectra = DataLoader::filter_spectra(
-            &input.spectra,
-            Some(ms_level),
-            Some(rt_min),
-            Some(rt_max),
-            Some(mz_min),
-            Some(mz_max),
-        );
+        let filtered_spectra = SpectrumFilter {
+            ms_level: Some(ms_level),
+            rt_range: Some((rt_min, rt_max)),
+            mz_range: Some((mz_min, mz_max)),
+        }.select(&input.spectra);
 
         if filtered_spectra.is_empty() {
             return Err(ProcessingError::DataError(
@@ -85,7 +85,9 @@ impl Processor for TICExtractor {
         }
 
         // 生成TIC曲线
-        let tic_curve = self.generate_tic_curve(&filtered_spectra, mz_min, mz_max)?;
+        let mut tic_curve = self.generate_tic_curve(&filtered_spectra, mz_min, mz_max)?;
+        AxisLabels::from_config(&config).apply(&mut tic_curve);
+        tic_curve.set_acquisition(ms_level, DataLoader::detect_polarity(filtered_spectra.iter().copied()));
 
         // 添加到数据容器
         input.curves.push(tic_curve.clone());
@@ -113,39 +115,17 @@ impl TICExtractor {
         mz_min: f64,
         mz_max: f64,
     ) -> Result<Curve, ProcessingError> {
-        let mut rt_data: HashMap<u64, f64> = HashMap::new();
-
-        for spectrum in spectra {
-            // 使用正确的API获取保留时间数据
-            let rt = spectrum.start_time();
-            let rt_key = (rt * 1000.0) as u64; // 精确到毫秒
-
-            let peaks = spectrum.peaks();
-            
-            // 累加指定m/z范围内的强度
-            let mut total_intensity = 0.0;
-            for peak in peaks.iter() {
-                let mz = peak.mz();
-                if mz >= mz_min && mz <= mz_max {
-                    total_intensity += peak.intensity() as f64;
-                }
-            }
-            
-            *rt_data.entry(rt_key).or_insert(0.0) += total_intensity;
-        }
+        let filter = SpectrumFilter { mz_range: Some((mz_min, mz_max)), ..SpectrumFilter::default() };
+        let points = project_spectra(spectra, &filter, ProjectionAxis::retention_time(), Reducer::Sum);
 
-        if rt_data.is_empty() {
+        if points.is_empty() {
             return Err(ProcessingError::DataError(
                 "No retention time data found in the specified range".to_string(),
             ));
         }
 
-        // 排序并生成曲线数据
-        let mut sorted_data: Vec<(u64, f64)> = rt_data.into_iter().collect();
-        sorted_data.sort_by(|a, b| a.0.cmp(&b.0));
-        
-        let x_values: Vec<f64> = sorted_data.iter().map(|(k, _)| *k as f64 / 1000.0).collect();
-        let y_values: Vec<f64> = sorted_data.iter().map(|(_, v)| *v).collect();
+        let x_values: Vec<f64> = points.iter().map(|(x, _)| *x).collect();
+        let y_values: Vec<f64> = points.iter().map(|(_, y)| *y).collect();
 
         let mut curve = Curve::new(
             format!("tic_curve_{}", Uuid::new_v4()),
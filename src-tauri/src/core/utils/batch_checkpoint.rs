@@ -0,0 +1,300 @@
+//! 批量处理检查点
+//!
+//! 记录已完成的文件，批量任务中断（崩溃或取消）后可从检查点恢复，跳过已完成的文件。
+//! 检查点写在输出目录或应用数据目录中，不写入输入数据目录；批量全部成功后删除
+
+use std::collections::HashSet;
+use std::future::Future;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Semaphore;
+
+use crate::core::data::ProcessingError;
+
+/// 默认检查点文件名
+pub const CHECKPOINT_FILE_NAME: &str = ".mz_curve_batch_checkpoint.json";
+
+/// 批量处理检查点
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatchCheckpoint {
+    /// 已完成的文件（按完成顺序）
+    pub completed_files: Vec<String>,
+    /// 最后更新时间
+    pub updated_at: Option<String>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl BatchCheckpoint {
+    /// 创建新的空检查点（不读取已有文件）
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            ..Self::default()
+        }
+    }
+
+    /// 读取检查点；文件不存在时返回空检查点
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, ProcessingError> {
+        let path = path.into();
+        if !path.exists() {
+            return Ok(Self::new(path));
+        }
+
+        let content = std::fs::read_to_string(&path)?;
+        let mut checkpoint: Self = serde_json::from_str(&content)?;
+        checkpoint.path = path;
+        log::info!("📌 读取批量检查点: {} 个文件已完成", checkpoint.completed_files.len());
+        Ok(checkpoint)
+    }
+
+    /// 输出目录中的检查点位置
+    pub fn path_in(output_dir: &Path) -> PathBuf {
+        output_dir.join(CHECKPOINT_FILE_NAME)
+    }
+
+    /// 未指定输出目录时的默认位置：应用数据目录（无法获取时退回系统临时目录）
+    pub fn default_path() -> Result<PathBuf, ProcessingError> {
+        let dir = dirs::data_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("mz_curve_gui");
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self::path_in(&dir))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn is_completed(&self, file: &str) -> bool {
+        self.completed_files.iter().any(|f| f == file)
+    }
+
+    /// 尚未完成的文件，保持输入顺序
+    pub fn pending_files<'a>(&self, files: &'a [String]) -> Vec<&'a String> {
+        let completed: HashSet<&str> = self.completed_files.iter().map(String::as_str).collect();
+        files.iter().filter(|f| !completed.contains(f.as_str())).collect()
+    }
+
+    /// 标记文件完成并立即持久化
+    pub fn mark_completed(&mut self, file: &str) -> Result<(), ProcessingError> {
+        if !self.is_completed(file) {
+            self.completed_files.push(file.to_string());
+        }
+        self.save()
+    }
+
+    /// 原子写入：先写临时文件并刷盘，再重命名覆盖，避免崩溃时留下半截JSON
+    pub fn save(&mut self) -> Result<(), ProcessingError> {
+        self.updated_at = Some(chrono::Utc::now().to_rfc3339());
+
+        let tmp_path = self.path.with_extension("json.tmp");
+        {
+            let mut file = std::fs::File::create(&tmp_path)?;
+            file.write_all(serde_json::to_string_pretty(self)?.as_bytes())?;
+            file.sync_all()?;
+        }
+        std::fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    /// 删除检查点文件
+    pub fn clear(&mut self) -> Result<(), ProcessingError> {
+        self.completed_files.clear();
+        if self.path.exists() {
+            std::fs::remove_file(&self.path)?;
+        }
+        Ok(())
+    }
+}
+
+/// 一次批量运行的结果
+#[derive(Debug, Clone)]
+pub struct BatchRun<T> {
+    /// 本次成功处理的文件及其结果
+    pub processed: Vec<(String, T)>,
+    /// 处理失败的文件及错误信息；失败的文件不记入检查点，恢复时会重新处理
+    pub failed: Vec<(String, String)>,
+    /// 检查点中已完成而被跳过的文件
+    pub skipped: Vec<String>,
+    pub cancelled: bool,
+}
+
+/// 并行运行可恢复的批量任务，同时处理的文件数不超过 `concurrency`
+///
+/// 每个文件在阻塞线程池中处理，文件加载等同步工作不会占住异步运行时。
+/// 跳过检查点中已完成的文件；`process` 成功返回后才把文件标记为完成。
+/// 每个文件取得并发许可后检查 `is_cancelled`，取消后尚未开始的文件不再处理。
+/// 结果保持输入顺序；没有取消且没有失败时删除检查点
+pub async fn run_batch<T, C, F, Fut>(
+    files: &[String],
+    checkpoint: &mut BatchCheckpoint,
+    concurrency: usize,
+    is_cancelled: C,
+    mut process: F,
+) -> BatchRun<T>
+where
+    T: Send + 'static,
+    C: Fn() -> bool,
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Result<T, String>> + Send + 'static,
+{
+    let mut run = BatchRun { processed: Vec::new(), failed: Vec::new(), skipped: Vec::new(), cancelled: false };
+
+    let mut pending = Vec::new();
+    for file in files {
+        if checkpoint.is_completed(file) {
+            log::info!("⏭️ 跳过已完成文件: {}", file);
+            run.skipped.push(file.clone());
+        } else {
+            pending.push(file.clone());
+        }
+    }
+
+    // 许可按 FIFO 发放，文件按输入顺序开始处理
+    let outcomes = {
+        let semaphore = Semaphore::new(concurrency.max(1));
+        let runtime = tokio::runtime::Handle::current();
+        let shared_checkpoint = Mutex::new(&mut *checkpoint);
+        let tasks = pending.into_iter().map(|file| {
+            let task = process(file.clone());
+            let runtime = runtime.clone();
+            let (semaphore, shared_checkpoint, is_cancelled) = (&semaphore, &shared_checkpoint, &is_cancelled);
+            async move {
+                let _permit = semaphore.acquire().await.expect("批量信号量不会被关闭");
+                if is_cancelled() {
+                    return (file, None);
+                }
+                let outcome = tokio::task::spawn_blocking(move || runtime.block_on(task))
+                    .await
+                    .unwrap_or_else(|e| Err(format!("处理任务异常终止: {}", e)));
+                if outcome.is_ok() {
+                    let mut checkpoint = shared_checkpoint.lock().unwrap_or_else(|e| e.into_inner());
+                    if let Err(e) = checkpoint.mark_completed(&file) {
+                        log::warn!("⚠️ 检查点写入失败: {}", e);
+                    }
+                }
+                (file, Some(outcome))
+            }
+        });
+        join_all(tasks).await
+    };
+
+    for (file, outcome) in outcomes {
+        match outcome {
+            Some(Ok(output)) => run.processed.push((file, output)),
+            Some(Err(e)) => {
+                log::warn!("⚠️ 文件处理失败: {} - {}", file, e);
+                run.failed.push((file, e));
+            }
+            None => run.cancelled = true,
+        }
+    }
+
+    if !run.cancelled && run.failed.is_empty() {
+        if let Err(e) = checkpoint.clear() {
+            log::warn!("⚠️ 检查点删除失败: {}", e);
+        }
+    }
+
+    run
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn resume_skips_completed_files_and_retries_failures() {
+        let path = std::env::temp_dir().join(format!("mz_curve_checkpoint_{}.json", std::process::id()));
+        let files: Vec<String> = (1..=5).map(|i| format!("/data/run_{}.mzML", i)).collect();
+
+        // 第一次运行（并发 1）：run_2 处理失败，尝试三个文件后中断
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let mut checkpoint = BatchCheckpoint::new(&path);
+        let first = run_batch(&files, &mut checkpoint, 1, || attempts.load(Ordering::SeqCst) >= 3, |file| {
+            let attempts = attempts.clone();
+            async move {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                if file.ends_with("run_2.mzML") { Err("损坏的文件".to_string()) } else { Ok(file.len()) }
+            }
+        }).await;
+        assert!(first.cancelled);
+        assert_eq!(first.processed.iter().map(|(f, _)| f.clone()).collect::<Vec<_>>(), vec![files[0].clone(), files[2].clone()]);
+        assert_eq!(first.failed.len(), 1);
+        assert!(!path.with_extension("json.tmp").exists());
+
+        let stored = BatchCheckpoint::load(&path).unwrap();
+        assert_eq!(stored.completed_files, vec![files[0].clone(), files[2].clone()]);
+
+        // 恢复：已完成的文件被跳过，失败的文件重新处理，全部成功后删除检查点
+        let mut checkpoint = BatchCheckpoint::load(&path).unwrap();
+        let resumed = run_batch(&files, &mut checkpoint, 4, || false, |file| async move { Ok(file.len()) }).await;
+        let exists_after = path.exists();
+        let _ = std::fs::remove_file(&path);
+
+        assert!(!resumed.cancelled);
+        assert_eq!(resumed.skipped, vec![files[0].clone(), files[2].clone()]);
+        assert_eq!(
+            resumed.processed.iter().map(|(f, _)| f.clone()).collect::<Vec<_>>(),
+            vec![files[1].clone(), files[3].clone(), files[4].clone()]
+        );
+        assert!(!exists_after);
+    }
+
+    #[tokio::test]
+    async fn files_are_processed_concurrently_up_to_the_limit() {
+        let path = std::env::temp_dir().join(format!("mz_curve_checkpoint_parallel_{}.json", std::process::id()));
+        let files: Vec<String> = (1..=12).map(|i| format!("/data/run_{}.mzML", i)).collect();
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let mut checkpoint = BatchCheckpoint::new(&path);
+        let run = run_batch(&files, &mut checkpoint, 3, || false, |file| {
+            let (active, peak) = (active.clone(), peak.clone());
+            async move {
+                let running = active.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(running, Ordering::SeqCst);
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                active.fetch_sub(1, Ordering::SeqCst);
+                Ok::<_, String>(file)
+            }
+        }).await;
+
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+        assert_eq!(run.processed.iter().map(|(f, _)| f.clone()).collect::<Vec<_>>(), files);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn blocking_file_work_runs_in_parallel() {
+        let path = std::env::temp_dir().join(format!("mz_curve_checkpoint_blocking_{}.json", std::process::id()));
+        let files: Vec<String> = (1..=8).map(|i| format!("/data/run_{}.mzML", i)).collect();
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        // 同步加载文件的情形：处理过程中阻塞线程而不让出运行时
+        let mut checkpoint = BatchCheckpoint::new(&path);
+        let run = run_batch(&files, &mut checkpoint, 4, || false, |file| {
+            let (active, peak) = (active.clone(), peak.clone());
+            async move {
+                let running = active.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(running, Ordering::SeqCst);
+                std::thread::sleep(std::time::Duration::from_millis(50));
+                active.fetch_sub(1, Ordering::SeqCst);
+                Ok::<_, String>(file)
+            }
+        }).await;
+
+        let peak = peak.load(Ordering::SeqCst);
+        assert!(peak > 1 && peak <= 4, "同时处理的文件数 {}", peak);
+        assert_eq!(run.processed.len(), 8);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,220 @@
+// 曲线显示抽稀：把长曲线缩减为适合前端渲染的点集，可选插值生成平滑预览
+use crate::core::data::ProcessingError;
+
+/// 插值预览时每两个保留点之间生成的点数（含起点）
+pub const DISPLAY_UPSAMPLE_FACTOR: usize = 4;
+
+/// 显示点集的插值方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DisplayInterpolation {
+    /// 只做保峰抽稀，不生成新点
+    None,
+    Linear,
+    /// 单调三次 Hermite（Fritsch–Carlson），相邻保留点之间不会过冲
+    MonotoneCubic,
+}
+
+impl DisplayInterpolation {
+    /// 按名称创建插值方式（"none" / "linear" / "monotone_cubic"）
+    pub fn from_name(name: &str) -> Result<Self, ProcessingError> {
+        match name {
+            "none" => Ok(Self::None),
+            "linear" => Ok(Self::Linear),
+            "monotone_cubic" => Ok(Self::MonotoneCubic),
+            _ => Err(ProcessingError::ConfigError(format!("不支持的插值方式: {}", name))),
+        }
+    }
+}
+
+/// 将曲线缩减为最多 `max_points` 个显示点
+///
+/// 不插值时按桶保留强度最大的点，峰顶不会被抽掉；插值时先抽稀到约 max_points / 4 个保留点，
+/// 再在相邻保留点之间插值补点，保留点本身原样输出
+pub fn decimate_for_display(
+    x_values: &[f64],
+    y_values: &[f64],
+    max_points: usize,
+    interpolation: DisplayInterpolation,
+) -> (Vec<f64>, Vec<f64>) {
+    let n = x_values.len().min(y_values.len());
+    if n < 2 || max_points < 2 {
+        return (x_values[..n].to_vec(), y_values[..n].to_vec());
+    }
+
+    if interpolation == DisplayInterpolation::None {
+        return peak_preserving_decimation(&x_values[..n], &y_values[..n], max_points);
+    }
+
+    let knot_count = ((max_points - 1) / DISPLAY_UPSAMPLE_FACTOR + 1).max(2);
+    let (knot_x, knot_y) = peak_preserving_decimation(&x_values[..n], &y_values[..n], knot_count);
+    let slopes = match interpolation {
+        DisplayInterpolation::MonotoneCubic => monotone_slopes(&knot_x, &knot_y),
+        _ => Vec::new(),
+    };
+
+    let mut x_out = Vec::with_capacity((knot_x.len() - 1) * DISPLAY_UPSAMPLE_FACTOR + 1);
+    let mut y_out = Vec::with_capacity(x_out.capacity());
+    for i in 0..knot_x.len() - 1 {
+        let h = knot_x[i + 1] - knot_x[i];
+        for step in 0..DISPLAY_UPSAMPLE_FACTOR {
+            let t = step as f64 / DISPLAY_UPSAMPLE_FACTOR as f64;
+            let y = match interpolation {
+                DisplayInterpolation::MonotoneCubic => {
+                    hermite(knot_y[i], knot_y[i + 1], slopes[i] * h, slopes[i + 1] * h, t)
+                }
+                _ => knot_y[i] + (knot_y[i + 1] - knot_y[i]) * t,
+            };
+            x_out.push(knot_x[i] + h * t);
+            y_out.push(y);
+        }
+    }
+    x_out.push(knot_x[knot_x.len() - 1]);
+    y_out.push(knot_y[knot_y.len() - 1]);
+
+    (x_out, y_out)
+}
+
+/// 等分为 max_points 个桶，每桶保留强度最大的点
+fn peak_preserving_decimation(x_values: &[f64], y_values: &[f64], max_points: usize) -> (Vec<f64>, Vec<f64>) {
+    let n = x_values.len();
+    if n <= max_points {
+        return (x_values.to_vec(), y_values.to_vec());
+    }
+
+    let mut x_out = Vec::with_capacity(max_points);
+    let mut y_out = Vec::with_capacity(max_points);
+    for bucket in 0..max_points {
+        let start = bucket * n / max_points;
+        let end = ((bucket + 1) * n / max_points).max(start + 1);
+        let index = (start..end)
+            .max_by(|&a, &b| y_values[a].total_cmp(&y_values[b]))
+            .unwrap_or(start);
+        x_out.push(x_values[index]);
+        y_out.push(y_values[index]);
+    }
+
+    (x_out, y_out)
+}
+
+/// 最小/最大值抽稀：等分为 max_points / 2 个桶，每桶按X顺序保留最小值点和最大值点
+///
+/// 同时保留峰顶和谷底，绘图时包络与原始曲线一致；输出不超过 `max_points` 个点
+pub fn min_max_decimation(x_values: &[f64], y_values: &[f64], max_points: usize) -> (Vec<f64>, Vec<f64>) {
+    let n = x_values.len().min(y_values.len());
+    if n <= max_points || max_points < 2 {
+        return (x_values[..n].to_vec(), y_values[..n].to_vec());
+    }
+
+    let buckets = max_points / 2;
+    let mut x_out = Vec::with_capacity(buckets * 2);
+    let mut y_out = Vec::with_capacity(buckets * 2);
+    for bucket in 0..buckets {
+        let start = bucket * n / buckets;
+        let end = ((bucket + 1) * n / buckets).max(start + 1);
+        let min_index = (start..end)
+            .min_by(|&a, &b| y_values[a].total_cmp(&y_values[b]))
+            .unwrap_or(start);
+        let max_index = (start..end)
+            .max_by(|&a, &b| y_values[a].total_cmp(&y_values[b]))
+            .unwrap_or(start);
+        let (first, second) = if min_index <= max_index { (min_index, max_index) } else { (max_index, min_index) };
+        x_out.push(x_values[first]);
+        y_out.push(y_values[first]);
+        if second != first {
+            x_out.push(x_values[second]);
+            y_out.push(y_values[second]);
+        }
+    }
+
+    (x_out, y_out)
+}
+
+/// Fritsch–Carlson 单调斜率：割线变号或为零处斜率取零，其余按 α² + β² ≤ 9 限幅
+fn monotone_slopes(x: &[f64], y: &[f64]) -> Vec<f64> {
+    let n = x.len();
+    let secants: Vec<f64> = (0..n - 1).map(|i| (y[i + 1] - y[i]) / (x[i + 1] - x[i])).collect();
+
+    let mut slopes = vec![0.0; n];
+    slopes[0] = secants[0];
+    slopes[n - 1] = secants[n - 2];
+    for i in 1..n - 1 {
+        slopes[i] = if secants[i - 1] * secants[i] <= 0.0 {
+            0.0
+        } else {
+            (secants[i - 1] + secants[i]) / 2.0
+        };
+    }
+
+    for i in 0..n - 1 {
+        if secants[i] == 0.0 {
+            slopes[i] = 0.0;
+            slopes[i + 1] = 0.0;
+            continue;
+        }
+        let alpha = slopes[i] / secants[i];
+        let beta = slopes[i + 1] / secants[i];
+        let magnitude = alpha * alpha + beta * beta;
+        if magnitude > 9.0 {
+            let tau = 3.0 / magnitude.sqrt();
+            slopes[i] = tau * alpha * secants[i];
+            slopes[i + 1] = tau * beta * secants[i];
+        }
+    }
+
+    slopes
+}
+
+/// 三次 Hermite 基函数插值，m0 / m1 为已乘区间长度的端点斜率
+fn hermite(y0: f64, y1: f64, m0: f64, m1: f64, t: f64) -> f64 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    (2.0 * t3 - 3.0 * t2 + 1.0) * y0 + (t3 - 2.0 * t2 + t) * m0 + (-2.0 * t3 + 3.0 * t2) * y1 + (t3 - t2) * m1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 平坦基线上的尖峰：峰顶只有一个点
+    fn sharp_peak() -> (Vec<f64>, Vec<f64>) {
+        let x: Vec<f64> = (0..40).map(|i| i as f64).collect();
+        let y: Vec<f64> = x.iter().map(|&x| if x == 20.0 { 1000.0 } else if (x - 20.0).abs() == 1.0 { 50.0 } else { 1.0 }).collect();
+        (x, y)
+    }
+
+    #[test]
+    fn monotone_cubic_does_not_overshoot_sharp_peak() {
+        let (x, y) = sharp_peak();
+        let (x_out, y_out) = decimate_for_display(&x, &y, 200, DisplayInterpolation::MonotoneCubic);
+
+        assert!(x_out.len() <= 200);
+        assert!(x_out.windows(2).all(|w| w[1] > w[0]));
+        let data_max = y.iter().cloned().fold(f64::MIN, f64::max);
+        assert!(y_out.contains(&data_max));
+        for (xi, yi) in x_out.iter().zip(&y_out) {
+            // 每个插值点都落在其所在原始区间两端点之间
+            let left = x.iter().rposition(|&v| v <= *xi).unwrap();
+            let right = (left + 1).min(x.len() - 1);
+            let local_max = y[left].max(y[right]);
+            let local_min = y[left].min(y[right]);
+            assert!(*yi <= local_max + 1e-9, "x {}: {} > {}", xi, yi, local_max);
+            assert!(*yi >= local_min - 1e-9, "x {}: {} < {}", xi, yi, local_min);
+        }
+    }
+
+    #[test]
+    fn none_keeps_peak_apex_when_decimating() {
+        let (x, y) = sharp_peak();
+        let (x_out, y_out) = decimate_for_display(&x, &y, 10, DisplayInterpolation::None);
+
+        assert_eq!(x_out.len(), 10);
+        assert!(y_out.contains(&1000.0));
+        assert!(x_out.iter().all(|v| x.contains(v)));
+    }
+
+    #[test]
+    fn rejects_unknown_interpolation() {
+        assert_eq!(DisplayInterpolation::from_name("monotone_cubic").unwrap(), DisplayInterpolation::MonotoneCubic);
+        assert!(DisplayInterpolation::from_name("spline").is_err());
+    }
+}
@@ -0,0 +1,189 @@
+//! 文件夹监视
+//!
+//! 通过 `notify` 监听文件夹中新建或被写入的 mzML 文件。采集仪器往往边采集边写文件，
+//! 因此文件在两次间隔 `stability_interval` 的检查中大小不变且非空后才视为写入完成。
+//! 同一路径、同一修改时间的文件只回调一次；同名文件被重新采集（修改时间变化）时会再次回调
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::core::data::ProcessingError;
+
+/// 默认的大小稳定检查间隔
+pub const DEFAULT_STABILITY_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// 是否为 mzML 文件（按扩展名判断）
+pub fn is_mzml_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("mzml"))
+}
+
+/// 等待写入完成的文件：上次检查时的大小与检查时间
+struct PendingFile {
+    size: Option<u64>,
+    checked_at: Instant,
+}
+
+/// 文件夹监视器，停止或释放时结束监听线程
+pub struct FolderWatcher {
+    dir: PathBuf,
+    watcher: Option<RecommendedWatcher>,
+    stop: Arc<AtomicBool>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl FolderWatcher {
+    /// 开始监视 `dir`（不递归），每个写入完成的 mzML 文件调用一次 `on_ready`
+    pub fn start<F>(dir: &Path, stability_interval: Duration, mut on_ready: F) -> Result<Self, ProcessingError>
+    where
+        F: FnMut(PathBuf) + Send + 'static,
+    {
+        if !dir.is_dir() {
+            return Err(ProcessingError::ConfigError(format!("监视路径不是文件夹: {}", dir.display())));
+        }
+
+        let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = tx.send(event);
+        }).map_err(|e| ProcessingError::ConfigError(format!("无法创建文件夹监视器: {}", e)))?;
+        watcher.watch(dir, RecursiveMode::NonRecursive)
+            .map_err(|e| ProcessingError::ConfigError(format!("无法监视文件夹 {}: {}", dir.display(), e)))?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let worker_stop = stop.clone();
+        let worker = std::thread::spawn(move || {
+            let mut pending: HashMap<PathBuf, PendingFile> = HashMap::new();
+            // 按 (路径, 修改时间) 记录已回调的文件
+            let mut processed: HashSet<(PathBuf, SystemTime)> = HashSet::new();
+
+            while !worker_stop.load(Ordering::SeqCst) {
+                match rx.recv_timeout(stability_interval / 4) {
+                    Ok(Ok(event)) => {
+                        if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                            for path in event.paths {
+                                if is_mzml_path(&path) {
+                                    pending.entry(path).or_insert_with(|| PendingFile {
+                                        size: None,
+                                        checked_at: Instant::now(),
+                                    });
+                                }
+                            }
+                        }
+                    }
+                    Ok(Err(e)) => log::warn!("⚠️ 文件夹监视错误: {}", e),
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+
+                let now = Instant::now();
+                let mut ready = Vec::new();
+                pending.retain(|path, file| {
+                    if file.size.is_some() && now.duration_since(file.checked_at) < stability_interval {
+                        return true;
+                    }
+                    let metadata = match std::fs::metadata(path) {
+                        Ok(metadata) => metadata,
+                        // 文件已被删除或移走
+                        Err(_) => return false,
+                    };
+                    let size = metadata.len();
+                    if size > 0 && file.size == Some(size) {
+                        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                        if processed.insert((path.clone(), modified)) {
+                            ready.push(path.clone());
+                        }
+                        return false;
+                    }
+                    file.size = Some(size);
+                    file.checked_at = now;
+                    true
+                });
+
+                for path in ready {
+                    log::info!("📥 文件写入完成: {}", path.display());
+                    on_ready(path);
+                }
+            }
+        });
+
+        log::info!("👀 开始监视文件夹: {}", dir.display());
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            watcher: Some(watcher),
+            stop,
+            worker: Some(worker),
+        })
+    }
+
+    /// 被监视的文件夹
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 停止监视并等待监听线程结束
+    pub fn stop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        // 释放监视器会关闭事件通道，监听线程随即退出
+        self.watcher.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+            log::info!("🛑 停止监视文件夹: {}", self.dir.display());
+        }
+    }
+}
+
+impl Drop for FolderWatcher {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn new_file_triggers_one_ready_event_per_acquisition() {
+        let dir = std::env::temp_dir().join(format!("mz_curve_watch_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = FolderWatcher::start(&dir, Duration::from_millis(200), move |path| {
+            let _ = tx.send(path);
+        }).unwrap();
+
+        // 分两次写入，模拟仍在采集中的文件；非 mzML 文件被忽略
+        let path = dir.join("run_1.mzML");
+        let mut file = std::fs::File::create(&path).unwrap();
+        file.write_all(b"<mzML>").unwrap();
+        file.flush().unwrap();
+        std::thread::sleep(Duration::from_millis(50));
+        file.write_all(b"</mzML>").unwrap();
+        drop(file);
+        std::fs::write(dir.join("notes.txt"), b"ignored").unwrap();
+
+        let ready = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(ready, path);
+        assert!(rx.recv_timeout(Duration::from_millis(800)).is_err());
+
+        // 重新采集同名文件：修改时间变化，再次回调
+        std::fs::remove_file(&path).unwrap();
+        std::thread::sleep(Duration::from_millis(50));
+        std::fs::write(&path, b"<mzML></mzML>").unwrap();
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), path);
+        assert!(rx.recv_timeout(Duration::from_millis(800)).is_err());
+
+        watcher.stop();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1 +1,10 @@
 pub mod math;
+pub mod signal;
+pub mod windows;
+pub mod batch_checkpoint;
+pub mod display_decimation;
+pub mod stdio_server;
+pub mod folder_watcher;
+pub mod thread_pool;
+#[cfg(test)]
+pub mod test_fixtures;
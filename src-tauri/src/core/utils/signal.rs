@@ -0,0 +1,242 @@
+// 信号处理工具函数
+use crate::core::data::ProcessingError;
+use crate::core::utils::windows::WindowFunction;
+
+/// FFT降噪时窗系数低于此值的端点保留原始值，避免除以接近0的窗系数放大噪声
+const WINDOW_FLOOR: f64 = 0.1;
+
+/// Savitzky-Golay 滤波/求导
+///
+/// 在每个点的滑动窗口内做最小二乘多项式拟合，返回拟合多项式在该点的 `derivative` 阶导数。
+/// 边缘点使用贴边的完整窗口并在偏移位置求值，避免端点畸变。
+/// `dx` 为平均采样间隔，用于把导数换算到 x 的物理单位。
+pub fn savitzky_golay(
+    y: &[f64],
+    window_size: usize,
+    polynomial_order: usize,
+    derivative: usize,
+    dx: f64,
+) -> Result<Vec<f64>, ProcessingError> {
+    if window_size < 3 || window_size % 2 == 0 {
+        return Err(ProcessingError::ConfigError(format!(
+            "Savitzky-Golay窗口大小必须为不小于3的奇数: {}", window_size
+        )));
+    }
+    if polynomial_order >= window_size {
+        return Err(ProcessingError::ConfigError(format!(
+            "多项式阶数 {} 必须小于窗口大小 {}", polynomial_order, window_size
+        )));
+    }
+    if derivative > polynomial_order {
+        return Err(ProcessingError::ConfigError(format!(
+            "导数阶数 {} 不能超过多项式阶数 {}", derivative, polynomial_order
+        )));
+    }
+    if y.len() < window_size {
+        return Err(ProcessingError::DataError(format!(
+            "数据点数 {} 少于窗口大小 {}", y.len(), window_size
+        )));
+    }
+    if dx <= 0.0 || !dx.is_finite() {
+        return Err(ProcessingError::DataError(format!("无效的采样间隔: {}", dx)));
+    }
+    
+    let half_window = (window_size / 2) as isize;
+    let n = y.len();
+    let scale = dx.powi(derivative as i32);
+    
+    // 中心点权重对所有内部点通用，只需计算一次
+    let center_weights = savitzky_golay_weights(half_window, polynomial_order, derivative, 0.0)?;
+    let mut result = vec![0.0; n];
+    
+    for i in 0..n {
+        let (start, weights) = if i < half_window as usize {
+            let offset = i as f64 - half_window as f64;
+            (0, savitzky_golay_weights(half_window, polynomial_order, derivative, offset)?)
+        } else if i + half_window as usize >= n {
+            let start = n - window_size;
+            let offset = (i - start) as f64 - half_window as f64;
+            (start, savitzky_golay_weights(half_window, polynomial_order, derivative, offset)?)
+        } else {
+            (i - half_window as usize, center_weights.clone())
+        };
+        
+        result[i] = weights.iter()
+            .zip(&y[start..start + window_size])
+            .map(|(w, v)| w * v)
+            .sum::<f64>() / scale;
+    }
+    
+    Ok(result)
+}
+
+/// 计算 Savitzky-Golay 卷积权重，`position` 为相对窗口中心的求值位置
+fn savitzky_golay_weights(
+    half_window: isize,
+    polynomial_order: usize,
+    derivative: usize,
+    position: f64,
+) -> Result<Vec<f64>, ProcessingError> {
+    let terms = polynomial_order + 1;
+    let offsets: Vec<f64> = (-half_window..=half_window).map(|k| k as f64).collect();
+    
+    // 正规方程 (JᵀJ)
+    let mut normal = vec![vec![0.0; terms]; terms];
+    for &u in &offsets {
+        for r in 0..terms {
+            for c in 0..terms {
+                normal[r][c] += u.powi((r + c) as i32);
+            }
+        }
+    }
+    
+    // 求值向量：d^derivative/du^derivative (u^j) 在 position 处的值
+    let mut basis = vec![0.0; terms];
+    for j in derivative..terms {
+        let falling: f64 = ((j - derivative + 1)..=j).map(|k| k as f64).product();
+        basis[j] = falling * position.powi((j - derivative) as i32);
+    }
+    
+    // 解 (JᵀJ) z = basis，则权重 w_k = Σ_j z_j u_k^j
+    let z = solve_linear_system(normal, basis)?;
+    Ok(offsets.iter()
+        .map(|&u| z.iter().enumerate().map(|(j, zj)| zj * u.powi(j as i32)).sum())
+        .collect())
+}
+
+/// 高斯消元（部分主元）求解小型线性方程组
+fn solve_linear_system(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Result<Vec<f64>, ProcessingError> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&i, &j| a[i][col].abs().partial_cmp(&a[j][col].abs()).unwrap_or(std::cmp::Ordering::Equal))
+            .unwrap_or(col);
+        if a[pivot][col].abs() < 1e-12 {
+            return Err(ProcessingError::MathError("线性方程组奇异".to_string()));
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        
+        for row in (col + 1)..n {
+            let factor = a[row][col] / a[col][col];
+            for k in col..n {
+                a[row][k] -= factor * a[col][k];
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+    
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let sum: f64 = ((row + 1)..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+    Ok(x)
+}
+
+/// 计算平均采样间隔
+pub fn mean_spacing(x: &[f64]) -> f64 {
+    if x.len() < 2 {
+        return 0.0;
+    }
+    (x[x.len() - 1] - x[0]) / (x.len() - 1) as f64
+}
+
+/// 原地基2快速傅里叶变换，长度必须为2的幂；`inverse` 时做逆变换并除以 n
+fn fft_in_place(re: &mut [f64], im: &mut [f64], inverse: bool) {
+    let n = re.len();
+    if n <= 1 {
+        return;
+    }
+    
+    // 位反转重排
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+    
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut len = 2;
+    while len <= n {
+        let angle = sign * 2.0 * std::f64::consts::PI / len as f64;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (w_im, w_re) = (angle * k as f64).sin_cos();
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * w_re - im[b] * w_im;
+                let t_im = re[b] * w_im + im[b] * w_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len <<= 1;
+    }
+    
+    if inverse {
+        for i in 0..n {
+            re[i] /= n as f64;
+            im[i] /= n as f64;
+        }
+    }
+}
+
+/// 实信号的单边幅度谱（补零到2的幂），返回 0..=N/2 各频率点的幅度
+pub fn amplitude_spectrum(signal: &[f64]) -> Vec<f64> {
+    let n = signal.len().next_power_of_two();
+    let mut re = signal.to_vec();
+    re.resize(n, 0.0);
+    let mut im = vec![0.0; n];
+    fft_in_place(&mut re, &mut im, false);
+    
+    (0..=n / 2).map(|k| re[k].hypot(im[k])).collect()
+}
+
+/// FFT低通降噪
+///
+/// 去均值后加窗以抑制截断泄漏，补零到2的幂做FFT，去除高于 `cutoff_fraction`（相对奈奎斯特频率）
+/// 的分量后逆变换，再除以窗系数还原幅度。
+pub fn fft_denoise(y: &[f64], cutoff_fraction: f64, window: WindowFunction) -> Result<Vec<f64>, ProcessingError> {
+    if !(cutoff_fraction > 0.0 && cutoff_fraction <= 1.0) {
+        return Err(ProcessingError::ConfigError(format!(
+            "截止频率比例必须在 (0, 1] 范围内: {}", cutoff_fraction
+        )));
+    }
+    if y.len() < 4 {
+        return Err(ProcessingError::DataError(format!("数据点数 {} 过少，无法进行FFT降噪", y.len())));
+    }
+    
+    let mean = y.iter().sum::<f64>() / y.len() as f64;
+    let coefficients = window.coefficients(y.len());
+    let n = y.len().next_power_of_two();
+    
+    let mut re: Vec<f64> = y.iter().zip(&coefficients).map(|(v, w)| (v - mean) * w).collect();
+    re.resize(n, 0.0);
+    let mut im = vec![0.0; n];
+    fft_in_place(&mut re, &mut im, false);
+    
+    let cutoff_bin = (cutoff_fraction * (n / 2) as f64).round() as usize;
+    for k in 0..n {
+        if k.min(n - k) > cutoff_bin {
+            re[k] = 0.0;
+            im[k] = 0.0;
+        }
+    }
+    fft_in_place(&mut re, &mut im, true);
+    
+    Ok(y.iter()
+        .zip(&coefficients)
+        .zip(&re)
+        .map(|((&original, &w), &filtered)| if w >= WINDOW_FLOOR { filtered / w + mean } else { original })
+        .collect())
+}
@@ -0,0 +1,234 @@
+//! 标准输入/输出批处理服务
+//!
+//! 逐行读取 JSON 请求（`ProcessingRequest` 加上 `id`），每个请求输出一行 JSON 响应。
+//! 进程常驻期间最近使用的文件保存在有界缓存中，同一文件的后续请求不再重新加载
+
+use std::collections::{HashMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::core::data::{DataContainer, ProcessingError, ProcessingRequest};
+use crate::core::loaders::mzdata_loader::DataLoader;
+use crate::core::processors::overlay_extractor::process_request;
+
+/// 默认最多缓存的文件数
+pub const DEFAULT_CACHE_CAPACITY: usize = 4;
+
+/// 一行输入：处理请求及用于对应响应的 id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServeRequest {
+    pub id: Value,
+    #[serde(flatten)]
+    pub request: ProcessingRequest,
+}
+
+/// 一行输出；无法解析的请求 id 为 null
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServeResponse {
+    pub id: Value,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<DataContainer>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub processing_time_ms: u64,
+}
+
+type FileLoader = fn(&str) -> Result<DataContainer, ProcessingError>;
+
+/// 常驻请求服务，`load` 负责按路径加载文件
+pub struct RequestServer<F = FileLoader> {
+    load: F,
+    capacity: usize,
+    cache: HashMap<String, DataContainer>,
+    /// 缓存键按最近使用排序，最久未使用的在前
+    recent: VecDeque<String>,
+}
+
+impl RequestServer {
+    /// 使用 `DataLoader::load_from_file` 加载文件
+    pub fn new() -> Self {
+        Self::with_loader(DataLoader::load_from_file)
+    }
+}
+
+impl Default for RequestServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F> RequestServer<F>
+where
+    F: FnMut(&str) -> Result<DataContainer, ProcessingError>,
+{
+    pub fn with_loader(load: F) -> Self {
+        Self { load, capacity: DEFAULT_CACHE_CAPACITY, cache: HashMap::new(), recent: VecDeque::new() }
+    }
+
+    /// 设置最多缓存的文件数；为 0 时不缓存
+    pub fn with_cache_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// 已缓存的文件数
+    pub fn cached_files(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// 逐行处理请求直到输入结束，返回处理的请求数。空行被忽略
+    pub async fn serve<R, W>(&mut self, reader: R, mut writer: W) -> std::io::Result<usize>
+    where
+        R: AsyncBufRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let mut lines = reader.lines();
+        let mut handled = 0;
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response = self.handle_line(&line).await;
+            let mut output = serde_json::to_string(&response)?;
+            output.push('\n');
+            writer.write_all(output.as_bytes()).await?;
+            writer.flush().await?;
+            handled += 1;
+        }
+        Ok(handled)
+    }
+
+    /// 处理一行请求；格式错误或处理失败时返回错误响应
+    pub async fn handle_line(&mut self, line: &str) -> ServeResponse {
+        let start_time = std::time::Instant::now();
+        let (id, outcome) = match serde_json::from_str::<ServeRequest>(line) {
+            Ok(request) => (request.id, self.process(&request.request).await),
+            Err(e) => {
+                // 尽量取回 id，便于调用方对应错误
+                let id = serde_json::from_str::<Value>(line)
+                    .ok()
+                    .and_then(|v| v.get("id").cloned())
+                    .unwrap_or(Value::Null);
+                (id, Err(ProcessingError::ConfigError(format!("无效的请求: {}", e))))
+            }
+        };
+
+        let processing_time_ms = start_time.elapsed().as_millis() as u64;
+        match outcome {
+            Ok(result) => ServeResponse { id, ok: true, result: Some(result), error: None, processing_time_ms },
+            Err(e) => {
+                log::warn!("⚠️ 请求 {} 处理失败: {}", id, e);
+                ServeResponse { id, ok: false, result: None, error: Some(e.to_string()), processing_time_ms }
+            }
+        }
+    }
+
+    /// 从缓存取出文件或加载文件，随后按请求模式处理
+    async fn process(&mut self, request: &ProcessingRequest) -> Result<DataContainer, ProcessingError> {
+        let container = self.cached_or_load(&request.file_path)?;
+        process_request(container, request).await
+    }
+
+    /// 命中时把文件移到最近使用的位置；未命中时加载，超过容量则淘汰最久未使用的文件
+    fn cached_or_load(&mut self, file_path: &str) -> Result<DataContainer, ProcessingError> {
+        if let Some(container) = self.cache.get(file_path) {
+            let container = container.clone();
+            self.recent.retain(|path| path != file_path);
+            self.recent.push_back(file_path.to_string());
+            return Ok(container);
+        }
+
+        let container = (self.load)(file_path)?;
+        if self.capacity > 0 {
+            self.cache.insert(file_path.to_string(), container.clone());
+            self.recent.push_back(file_path.to_string());
+            while self.recent.len() > self.capacity {
+                if let Some(oldest) = self.recent.pop_front() {
+                    self.cache.remove(&oldest);
+                }
+            }
+        }
+        Ok(container)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::utils::test_fixtures::write_ms1_run;
+
+    fn write_sample(path: &std::path::Path) {
+        write_ms1_run(path, 10, 0.1, |i| {
+            (0..4).map(|j| (500.0 + j as f64, (10 + i + j) as f32)).collect()
+        });
+    }
+
+    #[tokio::test]
+    async fn serve_answers_each_request_with_its_id() {
+        let path = std::env::temp_dir().join(format!("mz_curve_serve_{}.mzML", std::process::id()));
+        write_sample(&path);
+        let file_path = path.to_string_lossy().to_string();
+
+        let mut loads = 0;
+        let mut server = RequestServer::with_loader(|path: &str| {
+            loads += 1;
+            DataLoader::load_from_file(path)
+        });
+        let request = |id: &str| serde_json::json!({
+            "id": id,
+            "file_path": file_path,
+            "mz_range": "0-2000",
+            "rt_range": "0-10",
+            "ms_level": 1,
+            "mode": "tic",
+        }).to_string();
+        let input = format!("{}\n{{not json\n\n{}\n", request("first"), request("second"));
+
+        let mut output = Vec::new();
+        let handled = server.serve(input.as_bytes(), &mut output).await.unwrap();
+        let cached_files = server.cached_files();
+        drop(server);
+        let _ = std::fs::remove_file(&path);
+
+        let responses: Vec<ServeResponse> = String::from_utf8(output).unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(handled, 3);
+        assert_eq!(responses.len(), 3);
+        assert_eq!(responses[0].id, "first");
+        assert!(responses[0].ok, "{:?}", responses[0].error);
+        assert_eq!(responses[0].result.as_ref().unwrap().curves.len(), 1);
+        assert_eq!(responses[1].id, Value::Null);
+        assert!(!responses[1].ok);
+        assert!(responses[1].error.is_some());
+        assert_eq!(responses[2].id, "second");
+        assert!(responses[2].ok);
+
+        // 第二个请求命中缓存，文件只加载一次
+        assert_eq!(loads, 1);
+        assert_eq!(cached_files, 1);
+    }
+
+    #[test]
+    fn cache_evicts_least_recently_used_file() {
+        let mut loads = Vec::new();
+        let mut server = RequestServer::with_loader(|path: &str| {
+            loads.push(path.to_string());
+            Ok(DataContainer::new())
+        }).with_cache_capacity(2);
+
+        for path in ["a.mzML", "b.mzML", "a.mzML", "c.mzML", "a.mzML", "b.mzML"] {
+            server.cached_or_load(path).unwrap();
+        }
+        let cached_files = server.cached_files();
+        drop(server);
+
+        // c 淘汰 b（a 刚被使用），之后 b 需要重新加载
+        assert_eq!(loads, vec!["a.mzML", "b.mzML", "c.mzML", "b.mzML"]);
+        assert_eq!(cached_files, 2);
+    }
+}
@@ -0,0 +1,60 @@
+//! 测试夹具
+//!
+//! 各模块测试共用的质心 MS1 光谱构造与 mzML 写出
+
+use std::path::Path;
+
+use mzdata::MzMLWriter;
+use mzdata::params::ControlledVocabulary;
+use mzdata::prelude::{ParamDescribed, SpectrumLike, SpectrumWriter};
+use mzdata::mzpeaks::{CentroidPeak, MZPeakSetType};
+use mzdata::spectrum::{SignalContinuity, Spectrum, SpectrumDescription};
+
+/// 第 `index` 张质心 MS1 光谱（id 为 `scan={index + 1}`），峰为 (m/z, 强度)
+pub fn ms1_spectrum(index: usize, rt: f64, peaks: &[(f64, f32)]) -> Spectrum {
+    let peaks: Vec<CentroidPeak> = peaks.iter()
+        .enumerate()
+        .map(|(j, &(mz, intensity))| CentroidPeak::new(mz, intensity, j as u32))
+        .collect();
+    let mut description = SpectrumDescription::default();
+    description.id = format!("scan={}", index + 1);
+    description.index = index;
+    description.ms_level = 1;
+    description.signal_continuity = SignalContinuity::Centroid;
+    description.acquisition.first_scan_mut().unwrap().start_time = rt;
+    Spectrum::new(description, None, Some(MZPeakSetType::new(peaks)), None)
+}
+
+/// 在光谱的扫描上记录漂移时间（MS:1002476）
+pub fn with_drift_time(mut spectrum: Spectrum, dt: f64) -> Spectrum {
+    spectrum.description_mut().acquisition.first_scan_mut().unwrap()
+        .add_param(ControlledVocabulary::MS.param_val(1002476u32, "ion mobility drift time", dt));
+    spectrum
+}
+
+/// 把光谱写成带索引的 mzML 文件
+pub fn write_mzml(path: &Path, spectra: &[Spectrum]) {
+    let file = std::fs::File::create(path).unwrap();
+    let mut writer = MzMLWriter::new(std::io::BufWriter::new(file));
+    writer.set_spectrum_count(spectra.len() as u64);
+    for spectrum in spectra {
+        writer.write(spectrum).unwrap();
+    }
+    writer.close().unwrap();
+}
+
+/// 写出 `count` 张保留时间间隔为 `rt_step` 的 MS1 光谱，`peaks_at(i)` 给出第 i 张光谱的峰
+pub fn write_ms1_run(path: &Path, count: usize, rt_step: f64, peaks_at: impl Fn(usize) -> Vec<(f64, f32)>) {
+    let spectra: Vec<Spectrum> = (0..count)
+        .map(|i| ms1_spectrum(i, i as f64 * rt_step, &peaks_at(i)))
+        .collect();
+    write_mzml(path, &spectra);
+}
+
+/// 等长破坏第二张光谱的XML结构，索引偏移保持有效
+pub fn corrupt_second_spectrum(path: &Path) {
+    let xml = std::fs::read_to_string(path).unwrap();
+    let start = xml.find(r#"id="scan=2""#).unwrap();
+    let offset = start + xml[start..].find("</scanList>").unwrap();
+    std::fs::write(path, format!("{}</scanLisX>{}", &xml[..offset], &xml[offset + "</scanList>".len()..])).unwrap();
+}
@@ -0,0 +1,166 @@
+// 并行任务上限：峰拟合等计算任务共用一个全局信号量，在共享服务器上按 cgroup 或核数配额限制并发
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+use crate::core::data::ProcessingError;
+
+struct PoolSize {
+    size: usize,
+    /// 缩小上限时仍被占用、归还时需要作废的许可数
+    deficit: usize,
+}
+
+struct TaskLimiter {
+    semaphore: Semaphore,
+    pool: Mutex<PoolSize>,
+    active: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl TaskLimiter {
+    fn new(size: usize) -> Self {
+        Self {
+            semaphore: Semaphore::new(size),
+            pool: Mutex::new(PoolSize { size, deficit: 0 }),
+            active: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+        }
+    }
+
+    fn size(&self) -> usize {
+        self.pool.lock().unwrap_or_else(|e| e.into_inner()).size
+    }
+
+    /// 在原信号量上增减许可，已发出的许可与新上限共用同一个计数
+    fn resize(&self, size: usize) {
+        let mut pool = self.pool.lock().unwrap_or_else(|e| e.into_inner());
+        if size > pool.size {
+            let grow = size - pool.size;
+            let repaid = grow.min(pool.deficit);
+            pool.deficit -= repaid;
+            self.semaphore.add_permits(grow - repaid);
+        } else {
+            let shrink = pool.size - size;
+            let forgotten = self.semaphore.forget_permits(shrink);
+            pool.deficit += shrink - forgotten;
+        }
+        pool.size = size;
+    }
+
+    async fn acquire(&self) -> TaskPermit<'_> {
+        let permit = self.semaphore.acquire().await.expect("任务信号量不会被关闭");
+        let running = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(running, Ordering::SeqCst);
+        TaskPermit { limiter: self, permit: Some(permit) }
+    }
+}
+
+/// 任务许可，释放时归还；上限缩小后归还的许可先用于抵消超出部分
+pub struct TaskPermit<'a> {
+    limiter: &'a TaskLimiter,
+    permit: Option<SemaphorePermit<'a>>,
+}
+
+impl Drop for TaskPermit<'_> {
+    fn drop(&mut self) {
+        self.limiter.active.fetch_sub(1, Ordering::SeqCst);
+        let mut pool = self.limiter.pool.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(permit) = self.permit.take() {
+            if pool.deficit > 0 {
+                pool.deficit -= 1;
+                permit.forget();
+            }
+        }
+    }
+}
+
+fn limiter() -> &'static TaskLimiter {
+    static LIMITER: OnceLock<TaskLimiter> = OnceLock::new();
+    LIMITER.get_or_init(|| TaskLimiter::new(default_pool_size()))
+}
+
+/// 默认并发数：进程可用的并行度（已考虑 cgroup 配额与 CPU 亲和性）
+pub fn default_pool_size() -> usize {
+    std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+/// 当前并发上限
+pub fn thread_pool_size() -> usize {
+    limiter().size()
+}
+
+/// 设置并发上限，`None` 恢复为默认值，返回生效的上限
+///
+/// 上限在原信号量上调整：调大立即放行排队的任务；调小时运行中的任务继续执行，
+/// 它们归还的许可被作废，直到同时运行的任务数降到新上限
+pub fn set_thread_pool_size(size: Option<usize>) -> Result<usize, ProcessingError> {
+    let size = match size {
+        Some(0) => return Err(ProcessingError::ConfigError("线程池大小必须至少为 1".to_string())),
+        Some(size) => size,
+        None => default_pool_size(),
+    };
+    limiter().resize(size);
+    log::info!("🧵 并行任务上限设置为 {}", size);
+    Ok(size)
+}
+
+/// 等待一个任务许可，许可在返回值释放时归还
+pub async fn acquire_task_permit() -> TaskPermit<'static> {
+    limiter().acquire().await
+}
+
+/// 返回自上次调用以来同时持有许可的最大任务数，并从当前值重新计数
+#[cfg(test)]
+pub(crate) fn take_peak_active_tasks() -> usize {
+    let limiter = limiter();
+    limiter.peak.swap(limiter.active.load(Ordering::SeqCst), Ordering::SeqCst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn resizing_keeps_outstanding_permits_within_the_limit() {
+        let limiter = TaskLimiter::new(3);
+        let held = [limiter.acquire().await, limiter.acquire().await, limiter.acquire().await];
+
+        // 三个许可都在使用中，缩小到 1 只能记为欠额
+        limiter.resize(1);
+        assert_eq!(limiter.size(), 1);
+        assert_eq!(limiter.semaphore.available_permits(), 0);
+
+        drop(held);
+        assert_eq!(limiter.active.load(Ordering::SeqCst), 0);
+        assert_eq!(limiter.semaphore.available_permits(), 1);
+
+        let first = limiter.acquire().await;
+        assert!(limiter.semaphore.try_acquire().is_err());
+
+        limiter.resize(2);
+        assert_eq!(limiter.semaphore.available_permits(), 1);
+        drop(first);
+        assert_eq!(limiter.semaphore.available_permits(), 2);
+        assert_eq!(limiter.peak.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn growing_repays_the_deficit_before_adding_permits() {
+        let limiter = TaskLimiter::new(2);
+        let held = (limiter.acquire().await, limiter.acquire().await);
+
+        limiter.resize(1);
+        limiter.resize(3);
+        assert_eq!(limiter.semaphore.available_permits(), 1);
+
+        drop(held);
+        assert_eq!(limiter.semaphore.available_permits(), 3);
+    }
+
+    #[test]
+    fn zero_pool_size_is_rejected() {
+        assert!(set_thread_pool_size(Some(0)).is_err());
+    }
+}
@@ -0,0 +1,169 @@
+// 窗函数：FFT 前对信号加窗以减少边缘截断造成的频谱泄漏
+use crate::core::data::ProcessingError;
+
+/// Tukey 窗默认的锥形比例
+pub const DEFAULT_TUKEY_ALPHA: f64 = 0.5;
+
+/// 窗函数类型
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WindowFunction {
+    /// 矩形窗（不加窗）
+    Rectangular,
+    Hann,
+    Hamming,
+    Blackman,
+    /// Tukey（锥形余弦）窗，alpha 为锥形部分占窗长的比例：0 为矩形窗，1 为 Hann 窗
+    Tukey(f64),
+}
+
+impl WindowFunction {
+    /// 按名称创建窗函数（"rectangular" / "hann" / "hamming" / "blackman" / "tukey"）
+    pub fn from_name(name: &str, tukey_alpha: Option<f64>) -> Result<Self, ProcessingError> {
+        match name {
+            "rectangular" | "none" => Ok(Self::Rectangular),
+            "hann" | "hanning" => Ok(Self::Hann),
+            "hamming" => Ok(Self::Hamming),
+            "blackman" => Ok(Self::Blackman),
+            "tukey" => {
+                let alpha = tukey_alpha.unwrap_or(DEFAULT_TUKEY_ALPHA);
+                if !(0.0..=1.0).contains(&alpha) {
+                    return Err(ProcessingError::ConfigError(format!("Tukey窗alpha必须在0到1之间: {}", alpha)));
+                }
+                Ok(Self::Tukey(alpha))
+            }
+            _ => Err(ProcessingError::ConfigError(format!("不支持的窗函数: {}", name))),
+        }
+    }
+
+    /// 长度为 n 的对称窗系数
+    pub fn coefficients(&self, n: usize) -> Vec<f64> {
+        match *self {
+            Self::Rectangular => vec![1.0; n],
+            Self::Hann => hann(n),
+            Self::Hamming => hamming(n),
+            Self::Blackman => blackman(n),
+            Self::Tukey(alpha) => tukey(n, alpha),
+        }
+    }
+
+    /// 对信号逐点加窗
+    pub fn apply(&self, signal: &[f64]) -> Vec<f64> {
+        self.coefficients(signal.len())
+            .iter()
+            .zip(signal)
+            .map(|(w, s)| w * s)
+            .collect()
+    }
+}
+
+/// 余弦和窗：w[i] = Σ (-1)^k a_k cos(2πki / (n-1))
+fn cosine_sum(n: usize, coefficients: &[f64]) -> Vec<f64> {
+    if n == 1 {
+        return vec![1.0];
+    }
+    let denominator = (n - 1) as f64;
+    (0..n)
+        .map(|i| {
+            let phase = 2.0 * std::f64::consts::PI * i as f64 / denominator;
+            coefficients.iter()
+                .enumerate()
+                .map(|(k, a)| {
+                    let sign = if k % 2 == 0 { 1.0 } else { -1.0 };
+                    sign * a * (k as f64 * phase).cos()
+                })
+                .sum()
+        })
+        .collect()
+}
+
+/// Hann 窗：0.5 - 0.5cos(2πi/(n-1))
+pub fn hann(n: usize) -> Vec<f64> {
+    cosine_sum(n, &[0.5, 0.5])
+}
+
+/// Hamming 窗：0.54 - 0.46cos(2πi/(n-1))
+pub fn hamming(n: usize) -> Vec<f64> {
+    cosine_sum(n, &[0.54, 0.46])
+}
+
+/// Blackman 窗：0.42 - 0.5cos(2πi/(n-1)) + 0.08cos(4πi/(n-1))
+pub fn blackman(n: usize) -> Vec<f64> {
+    cosine_sum(n, &[0.42, 0.5, 0.08])
+}
+
+/// Tukey 窗：两端各 alpha/2 的部分为半个余弦锥形，中间为 1
+pub fn tukey(n: usize, alpha: f64) -> Vec<f64> {
+    if n == 1 || alpha <= 0.0 {
+        return vec![1.0; n];
+    }
+    let denominator = (n - 1) as f64;
+    let taper = alpha * denominator / 2.0;
+    (0..n)
+        .map(|i| {
+            // 利用对称性，只按到最近端点的距离计算
+            let distance = (i as f64).min(denominator - i as f64);
+            if distance < taper {
+                0.5 * (1.0 - (std::f64::consts::PI * distance / taper).cos())
+            } else {
+                1.0
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::utils::signal::amplitude_spectrum;
+
+    #[test]
+    fn window_coefficients_match_reference_properties() {
+        let n = 65;
+        for (window, expected_sum) in [
+            (WindowFunction::Hann, 0.5 * (n - 1) as f64),
+            (WindowFunction::Hamming, 0.54 * n as f64 - 0.46),
+            (WindowFunction::Blackman, 0.42 * (n - 1) as f64),
+            (WindowFunction::Tukey(0.5), 0.75 * (n - 1) as f64),
+        ] {
+            let w = window.coefficients(n);
+            assert_eq!(w.len(), n);
+            assert!((w.iter().sum::<f64>() - expected_sum).abs() < 1e-9, "{:?}", window);
+            for i in 0..n {
+                assert!((w[i] - w[n - 1 - i]).abs() < 1e-12, "{:?} not symmetric", window);
+            }
+            // 奇数长度的对称窗中心为 1
+            assert!((w[n / 2] - 1.0).abs() < 1e-12);
+        }
+
+        assert!(hann(n)[0].abs() < 1e-12);
+        assert!((hamming(n)[0] - 0.08).abs() < 1e-12);
+        assert!(blackman(n)[0].abs() < 1e-12);
+        assert_eq!(tukey(n, 0.0), vec![1.0; n]);
+        assert!(tukey(n, 1.0).iter().zip(hann(n)).all(|(a, b)| (a - b).abs() < 1e-12));
+    }
+
+    #[test]
+    fn hann_reduces_leakage_of_non_integer_period_sinusoid() {
+        // 256 点内 10.5 个周期：矩形窗截断会把能量泄漏到远处的频率点
+        let n = 256;
+        let signal: Vec<f64> = (0..n)
+            .map(|i| (2.0 * std::f64::consts::PI * 10.5 * i as f64 / n as f64).sin())
+            .collect();
+
+        let leakage = |window: WindowFunction| {
+            let spectrum = amplitude_spectrum(&window.apply(&signal));
+            let power: Vec<f64> = spectrum.iter().map(|a| a * a).collect();
+            let total: f64 = power.iter().sum();
+            let near: f64 = power.iter().enumerate()
+                .filter(|(k, _)| (*k as f64 - 10.5).abs() <= 3.0)
+                .map(|(_, p)| p)
+                .sum();
+            (total - near) / total
+        };
+
+        let rectangular = leakage(WindowFunction::Rectangular);
+        let hann = leakage(WindowFunction::Hann);
+        assert!(rectangular > 0.01);
+        assert!(hann < rectangular / 10.0, "hann {} vs rectangular {}", hann, rectangular);
+    }
+}
@@ -14,15 +14,48 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .manage(AppStateManager::new(AppState::default()))
+        .setup(|app| {
+            crate::tauri::logging::init(app.handle().clone());
+            Ok(())
+        })
         .invoke_handler(::tauri::generate_handler![
             // 文件操作API
             load_file,
             validate_file,
+            quick_validate,
+            get_file_metadata,
+            get_provenance,
+            get_spectrum_at_rt,
             clear_file_cache,
             // 数据处理API
             extract_curve,
+            extract_overlay,
+            merge_containers,
+            align_curves,
+            extract_ion_image,
+            refine_extraction,
             analyze_peaks,
+            calibrate_sensitivity,
+            evaluate_detection,
+            quantify,
+            fit_calibration_curve,
+            apply_calibration,
+            track_peak,
+            get_gaussian_equivalents,
+            calculate_k0,
+            estimate_overlap,
+            diagnose_curve,
+            search_library,
+            group_isotopes,
+            region_stats,
+            split_peak,
+            process_many,
+            normalize_peak_areas,
+            quick_analyze,
             batch_process_files,
+            cancel_batch_processing,
+            watch_folder,
+            stop_watching,
             // 流水线API - 暂时注释掉，因为命令不存在
             // detect_peaks,
             // fit_peaks,
@@ -33,12 +66,16 @@ pub fn run() {
             // 状态管理API
             get_app_state,
             update_processing_params,
+            set_thread_pool_size,
+            set_progress_rate,
             get_processing_status,
             // 数据导出API
             get_curve_data_for_display,
             export_curves_to_folder,
+            cancel_export,
             export_tsv,
             export_json,
+            export_sqlite,
             export_plot,
             export_spectro_tsv,
             // 高级处理API
@@ -46,6 +83,7 @@ pub fn run() {
             overlapping_peaks,
             smooth_data,
             noise_reduction,
+            compute_second_derivative,
             // 配置管理API
             save_config,
             load_config,
@@ -306,7 +306,7 @@ async fn process_batch_queue(
         task.started_at = Some(chrono::Utc::now());
 
         // 发送进度更新
-        state.emit_progress_update(&app, 0, 1, &format!("处理文件: {}", task.file_path));
+        state.emit_progress_update(&app, "batch_queue", 0, 1, &format!("处理文件: {}", task.file_path));
 
         // 处理任务
         let result = process_single_batch_task(&task, &app, &state).await;
@@ -348,7 +348,7 @@ async fn process_batch_queue(
         queue.current_task = None;
     }
 
-    state.emit_progress_update(&app, 1, 1, "批量处理完成");
+    state.emit_progress_update(&app, "batch_queue", 1, 1, "批量处理完成");
 }
 
 /// 处理单个批量任务
@@ -10,6 +10,9 @@ pub struct UserConfig {
     pub ui_settings: UiSettings,
     pub export_settings: ExportSettings,
     pub visualization_settings: VisualizationSettings,
+    /// 并行任务上限，未设置时使用可用并行度
+    #[serde(default)]
+    pub thread_pool_size: Option<usize>,
     pub last_updated: String,
 }
 
@@ -102,6 +105,9 @@ pub async fn save_config(config: UserConfig, _app: tauri::AppHandle, state: Stat
     
     let config_file = config_dir.join("config.json");
     
+    crate::core::utils::thread_pool::set_thread_pool_size(config_with_timestamp.thread_pool_size)
+        .map_err(|e| e.to_string())?;
+    
     // 序列化配置为JSON
     let config_json = serde_json::to_string_pretty(&config_with_timestamp)
         .map_err(|e| format!("配置序列化失败: {}", e))?;
@@ -147,6 +153,8 @@ pub async fn load_config(_app: tauri::AppHandle, state: State<'_, AppStateManage
         // 反序列化配置
         let loaded_config: UserConfig = serde_json::from_str(&config_content)
             .map_err(|e| format!("配置文件格式错误: {}", e))?;
+        crate::core::utils::thread_pool::set_thread_pool_size(loaded_config.thread_pool_size)
+            .map_err(|e| e.to_string())?;
         
         log::info!("✅ 配置加载成功");
         app_state.add_message("success", "配置加载完成", "用户配置已加载");
@@ -202,6 +210,7 @@ pub async fn load_config(_app: tauri::AppHandle, state: State<'_, AppStateManage
                 auto_scale: true,
                 peak_highlighting: true,
             },
+            thread_pool_size: None,
             last_updated: chrono::Utc::now().to_rfc3339(),
         };
         
@@ -264,8 +273,10 @@ pub async fn reset_config(_app: tauri::AppHandle, state: State<'_, AppStateManag
             auto_scale: true,
             peak_highlighting: true,
         },
+        thread_pool_size: None,
         last_updated: chrono::Utc::now().to_rfc3339(),
     };
+    crate::core::utils::thread_pool::set_thread_pool_size(None).map_err(|e| e.to_string())?;
     
     app_state.add_message("success", "配置重置完成", "已重置为默认配置");
     
@@ -276,6 +287,29 @@ pub async fn reset_config(_app: tauri::AppHandle, state: State<'_, AppStateManag
     })
 }
 
+/// 设置并行任务上限，`size` 为空时恢复为可用并行度；返回生效的上限
+#[tauri::command]
+pub fn set_thread_pool_size(size: Option<usize>, state: State<'_, AppStateManager>) -> Result<usize, String> {
+    let size = crate::core::utils::thread_pool::set_thread_pool_size(size).map_err(|e| e.to_string())?;
+    let mut app_state = state.lock();
+    app_state.add_message("info", "并行设置", &format!("并行任务上限: {}", size));
+    Ok(size)
+}
+
+/// 设置进度事件节流：每秒最多发送 `max_events_per_second` 个进度事件（非正数不限），
+/// 给出 `min_percent_step` 时百分比每前进该值也发送一次
+#[tauri::command]
+pub fn set_progress_rate(
+    max_events_per_second: f64,
+    min_percent_step: Option<f64>,
+    state: State<'_, AppStateManager>
+) -> Result<(), String> {
+    state.set_progress_throttle(max_events_per_second, min_percent_step);
+    let mut app_state = state.lock();
+    app_state.add_message("info", "进度节流", &format!("进度事件上限: 每秒 {}", max_events_per_second));
+    Ok(())
+}
+
 /// 获取默认处理参数
 #[tauri::command]
 pub async fn get_default_params(_app: tauri::AppHandle, state: State<'_, AppStateManager>) -> Result<ProcessingParams, String> {
@@ -1,10 +1,11 @@
 //! 曲线提取相关命令
 
-use tauri::State;
+use tauri::{Manager, State};
 use crate::tauri::state::{AppStateManager, ProcessingStatus};
 use crate::core::loaders::mzdata_loader::DataLoader;
-use crate::core::processors::base::Processor;
-use super::{CurveExtractionParams, BatchProcessingResult, CurveDisplayData};
+use crate::core::utils::batch_checkpoint::{run_batch, BatchCheckpoint};
+use crate::core::utils::display_decimation::{decimate_for_display, DisplayInterpolation};
+use super::{CurveExtractionParams, BatchProcessingResult, FailedFile, CurveDisplayData};
 
 /// 步骤3: 提取曲线数据
 #[tauri::command]
@@ -50,41 +51,14 @@ pub async fn extract_curve(
     };
     
     // 根据曲线类型选择不同的提取器
-    let result = match params.curve_type.as_str() {
-        "dt" => {
-            // 使用DTExtractor
-            let extractor = crate::core::processors::dt_extractor::DTExtractor;
-            let config = serde_json::json!({
-                "mz_range": params.mz_range,
-                "rt_range": params.rt_range,
-                "ms_level": params.ms_level
-            });
-            extractor.process(container, config).await
-        },
-        "tic" => {
-            // 使用TICExtractor
-            let extractor = crate::core::processors::tic_extractor::TICExtractor;
-            let config = serde_json::json!({
-                "rt_range": params.rt_range,
-                "ms_level": params.ms_level
-                // TIC不需要mz_range，会使用全m/z范围
-            });
-            extractor.process(container, config).await
-        },
-        "xic" => {
-            // 使用XICExtractor
-            let extractor = crate::core::processors::xic_extractor::XICExtractor;
-            let config = serde_json::json!({
-                "mz_range": params.mz_range,
-                "rt_range": params.rt_range,
-                "ms_level": params.ms_level
-            });
-            extractor.process(container, config).await
-        },
-        _ => {
-            return Err(format!("不支持的曲线类型: {}", params.curve_type));
-        }
-    };
+    let result = crate::core::processors::overlay_extractor::extract_by_type(
+        container,
+        &params.curve_type,
+        &params.mz_range,
+        &params.rt_range,
+        params.ms_level,
+        &params.axis_labels,
+    ).await;
     
     let result = match result {
         Ok(result) => result,
@@ -109,6 +83,13 @@ pub async fn extract_curve(
     let processing_time = start_time.elapsed().as_millis() as u64;
     log::info!("⏱️ 曲线提取完成，总耗时: {}ms", processing_time);
     
+    // 超出模式范围的参数已被截断，警告随容器元数据 config_warnings 一起返回
+    if let Some(warnings) = result.metadata.get("config_warnings").and_then(|v| v.as_array()) {
+        let messages: Vec<&str> = warnings.iter().filter_map(|w| w["message"].as_str()).collect();
+        let mut app_state = state.lock();
+        app_state.add_message("warning", "参数超出范围", &messages.join("; "));
+    }
+    
     {
         let mut app_state = state.lock();
         app_state.set_processing_status(ProcessingStatus::Idle);
@@ -128,11 +109,196 @@ pub async fn extract_curve(
     Ok(serializable_container)
 }
 
+/// 多文件叠加提取：从每个文件提取相同窗口的曲线，按文件名标注后合并返回
+#[tauri::command]
+pub async fn extract_overlay(
+    file_paths: Vec<String>,
+    params: CurveExtractionParams,
+    _app: tauri::AppHandle,
+    state: State<'_, AppStateManager>
+) -> Result<crate::core::data::container::SerializableDataContainer, String> {
+    {
+        let mut app_state = state.lock();
+        app_state.set_processing_status(ProcessingStatus::Extracting);
+        app_state.add_message("info", "叠加提取", &format!("开始从 {} 个文件提取 {} 曲线", file_paths.len(), params.curve_type));
+    }
+    
+    // 优先使用缓存的文件数据
+    let load = |path: &str| -> Result<crate::core::data::DataContainer, crate::core::data::ProcessingError> {
+        if let Some(cached) = state.get_cached_file(path) {
+            return Ok(cached);
+        }
+        let container = DataLoader::load_from_file(path)?;
+        state.cache_file(path, container.clone());
+        Ok(container)
+    };
+    
+    let result = crate::core::processors::overlay_extractor::extract_overlay(
+        &file_paths,
+        &params.curve_type,
+        &params.mz_range,
+        &params.rt_range,
+        params.ms_level,
+        &params.axis_labels,
+        load,
+    ).await;
+    
+    let mut app_state = state.lock();
+    app_state.set_processing_status(ProcessingStatus::Idle);
+    
+    match result {
+        Ok(overlay) => {
+            if let Some(failed) = overlay.metadata.get("failed_files").and_then(|v| v.as_array()) {
+                if !failed.is_empty() {
+                    let reasons: Vec<String> = failed.iter()
+                        .map(|f| format!("{}: {}", f["file_path"].as_str().unwrap_or("?"), f["reason"].as_str().unwrap_or("")))
+                        .collect();
+                    app_state.add_message("error", "部分文件叠加提取失败", &reasons.join("; "));
+                }
+            }
+            app_state.add_message("success", "叠加提取完成", &format!("提取了 {} 条曲线", overlay.curves.len()));
+            Ok(crate::core::data::container::SerializableDataContainer::from(overlay))
+        }
+        Err(e) => {
+            app_state.add_message("error", "叠加提取失败", &format!("错误: {}", e));
+            Err(format!("叠加提取失败: {}", e))
+        }
+    }
+}
+
+/// 合并两个提取结果（如 DT 与 XIC 曲线），ID相同的曲线去重，元数据冲突时保留 base 的值
+#[tauri::command]
+pub async fn merge_containers(
+    base: crate::core::data::container::SerializableDataContainer,
+    other: crate::core::data::container::SerializableDataContainer,
+    state: State<'_, AppStateManager>
+) -> Result<crate::core::data::container::SerializableDataContainer, String> {
+    // 可序列化容器中的光谱只是摘要，转换时会被丢弃，这里单独拼接
+    let mut spectra = base.spectra.clone();
+    spectra.extend(other.spectra.iter().cloned());
+    
+    let mut merged = base.to_data_container();
+    let report = merged.merge(other.to_data_container());
+    
+    {
+        let mut app_state = state.lock();
+        if !report.metadata_conflicts.is_empty() {
+            app_state.add_message("warning", "元数据冲突", &format!("以下键保留了原容器的值: {}", report.metadata_conflicts.join(", ")));
+        }
+        app_state.add_message("success", "合并完成", &format!("新增 {} 条曲线、{} 个峰，去重 {} 条曲线",
+            report.curves_added, report.peaks_added, report.curves_deduplicated));
+    }
+    
+    let mut result = crate::core::data::container::SerializableDataContainer::from(merged);
+    result.spectra = spectra;
+    Ok(result)
+}
+
+/// 跨运行对齐：用 DTW 把样品曲线规整到参考曲线的时间轴上，返回对齐后的曲线与规整路径
+///
+/// `band` 为 Sakoe-Chiba 带宽（点数），不设置时取较长曲线点数的 10%
+#[tauri::command]
+pub async fn align_curves(
+    sample: crate::core::data::Curve,
+    reference: crate::core::data::Curve,
+    band: Option<usize>,
+    state: State<'_, AppStateManager>
+) -> Result<crate::core::processors::alignment::DtwAlignment, String> {
+    let band = band.unwrap_or_else(|| crate::core::processors::alignment::default_band(&sample, &reference));
+    let result = crate::core::processors::alignment::dtw_align(&sample, &reference, band);
+    
+    let mut app_state = state.lock();
+    match result {
+        Ok(alignment) => {
+            app_state.add_message("success", "曲线对齐完成", &format!("{} 已对齐到 {}，带宽 {}，规整代价 {:.4}",
+                sample.id, reference.id, alignment.band, alignment.distance));
+            Ok(alignment)
+        },
+        Err(e) => {
+            app_state.add_message("error", "曲线对齐失败", &e.to_string());
+            Err(e.to_string())
+        }
+    }
+}
+
+/// 成像质谱离子图像：对 imzML 文件每个像素求 m/z 窗口内强度和，按像素坐标排成二维矩阵
+///
+/// 像素索引随缓存的容器保存，重复调用只从 ibd 读取所需的数组片段
+#[tauri::command]
+pub async fn extract_ion_image(
+    file_path: String,
+    mz_range: String,
+    state: State<'_, AppStateManager>
+) -> Result<crate::core::loaders::imzml_loader::IonImage, String> {
+    let (mz_min, mz_max) = mz_range.split_once('-')
+        .and_then(|(min, max)| Some((min.trim().parse::<f64>().ok()?, max.trim().parse::<f64>().ok()?)))
+        .ok_or_else(|| format!("无效的m/z范围: {}", mz_range))?;
+    
+    let container = match state.get_cached_file(&file_path) {
+        Some(cached) => cached,
+        None => {
+            let container = DataLoader::load_imzml(&file_path).map_err(|e| format!("无法加载imzML文件: {}", e))?;
+            state.cache_file(&file_path, container.clone());
+            container
+        }
+    };
+    
+    let result = crate::core::loaders::imzml_loader::extract_ion_image(&container, mz_min, mz_max);
+    
+    let mut app_state = state.lock();
+    match result {
+        Ok(image) => {
+            app_state.add_message("success", "离子图像", &format!("m/z {}-{}: {} × {} 像素", mz_min, mz_max, image.width, image.height));
+            Ok(image)
+        }
+        Err(e) => {
+            app_state.add_message("error", "离子图像生成失败", &format!("错误: {}", e));
+            Err(format!("离子图像生成失败: {}", e))
+        }
+    }
+}
+
+/// 精确 m/z 重提取：由峰区间内贡献光谱计算特征 m/z 质心，以质心 ± `mz_expansion` 重新提取同类型曲线
+#[tauri::command]
+pub async fn refine_extraction(
+    file_path: String,
+    curve: crate::core::data::Curve,
+    peak: crate::core::data::Peak,
+    mz_expansion: f64,
+    state: State<'_, AppStateManager>
+) -> Result<crate::core::processors::refine_extraction::RefinedExtraction, String> {
+    let container = match state.get_cached_file(&file_path) {
+        Some(cached) => cached,
+        None => {
+            let container = DataLoader::load_from_file(&file_path).map_err(|e| format!("无法加载文件: {}", e))?;
+            state.cache_file(&file_path, container.clone());
+            container
+        }
+    };
+    
+    let result = crate::core::processors::refine_extraction::refine_extraction(container, &curve, &peak, mz_expansion).await;
+    
+    let mut app_state = state.lock();
+    match result {
+        Ok(refined) => {
+            app_state.add_message("success", "精确m/z重提取", &format!("m/z 质心 {:.4}，窗口 {:.4}-{:.4}",
+                refined.mz_centroid, refined.mz_range.0, refined.mz_range.1));
+            Ok(refined)
+        }
+        Err(e) => {
+            app_state.add_message("error", "精确m/z重提取失败", &format!("错误: {}", e));
+            Err(format!("精确m/z重提取失败: {}", e))
+        }
+    }
+}
+
 /// 批量处理多个文件 - 优化版本
 #[tauri::command]
 pub async fn batch_process_files(
     file_paths: Vec<String>,
     params: CurveExtractionParams,
+    resume: Option<bool>,
+    checkpoint_path: Option<String>,
     app: tauri::AppHandle,
     state: State<'_, AppStateManager>
 ) -> Result<BatchProcessingResult, String> {
@@ -145,59 +311,82 @@ pub async fn batch_process_files(
     }
     
     // 发送进度更新事件
-    state.emit_progress_update(&app, 0, total_files, "开始批量处理...");
+    state.start_batch_progress(total_files);
+    state.emit_progress_update(&app, "batch", 0, total_files, "开始批量处理...");
+    
+    // 检查点：每个文件成功后原子写入，resume 时跳过已完成的文件；默认写在应用数据目录
+    let checkpoint_path = match checkpoint_path {
+        Some(path) => std::path::PathBuf::from(path),
+        None => BatchCheckpoint::default_path().map_err(|e| format!("无法创建检查点目录: {}", e))?,
+    };
+    let mut checkpoint = if resume.unwrap_or(false) {
+        BatchCheckpoint::load(&checkpoint_path).map_err(|e| format!("无法读取检查点: {}", e))?
+    } else {
+        BatchCheckpoint::new(&checkpoint_path)
+    };
     
     let start_time = std::time::Instant::now();
-    let mut processed_files = Vec::new();
-    let mut failed_files = Vec::new();
-    let mut total_curves = 0;
-    let mut total_peaks = 0;
-    
-    for (index, file_path) in file_paths.iter().enumerate() {
-        // 更新进度
-        state.emit_progress_update(&app, index, total_files, &format!("处理文件: {}", file_path));
-        
+    
+    // 文件并行处理，同时处理的文件数取全局并行任务上限
+    let concurrency = crate::core::utils::thread_pool::thread_pool_size();
+    let run = run_batch(&file_paths, &mut checkpoint, concurrency, || state.is_batch_cancelled(), |file_path| {
         let mut file_params = params.clone();
         file_params.file_path = file_path.clone();
-        
-        match extract_curve(file_params, app.clone(), state.clone()).await {
-            Ok(container) => {
-                processed_files.push(file_path.clone());
-                total_curves += container.curves.len();
-                total_peaks += container.total_peak_count();
-                
-                {
-                    let mut app_state = state.lock();
-                    app_state.add_message("success", "文件处理完成", &format!("成功处理: {} 条曲线, {} 个峰值", container.curves.len(), container.total_peak_count()));
-                }
-            }
-            Err(e) => {
-                failed_files.push(file_path.clone());
-                {
-                    let mut app_state = state.lock();
-                    app_state.add_message("error", "文件处理失败", &format!("处理失败: {} - {}", file_path, e));
+        let app = app.clone();
+        async move {
+            let state = app.state::<AppStateManager>();
+            let outcome = extract_curve(file_params, app.clone(), state.clone()).await;
+            {
+                let mut app_state = state.lock();
+                match &outcome {
+                    Ok(container) => app_state.add_message("success", "文件处理完成", &format!("成功处理: {} 条曲线, {} 个峰值", container.curves.len(), container.total_peak_count())),
+                    Err(e) => app_state.add_message("error", "文件处理失败", &format!("处理失败: {} - {}", file_path, e)),
                 }
             }
+            
+            // 更新进度（基于原子完成计数）
+            state.emit_batch_task_completed(&app, &format!("已处理文件: {}", file_path));
+            
+            // 短暂延迟，避免阻塞UI
+            tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+            
+            outcome.map(|container| (container.curves.len(), container.total_peak_count()))
         }
-        
-        // 短暂延迟，避免阻塞UI
-        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+    }).await;
+    
+    for file_path in &run.skipped {
+        state.emit_batch_task_completed(&app, &format!("已跳过（检查点）: {}", file_path));
+    }
+    if run.cancelled {
+        let mut app_state = state.lock();
+        app_state.add_message("warning", "批量处理已取消", &format!("已保存检查点: {}", checkpoint.path().display()));
     }
     
+    let cancelled = run.cancelled;
+    let skipped_files = run.skipped;
+    let failed_files: Vec<FailedFile> = run.failed.iter().map(|(path, e)| FailedFile::new(path, e)).collect();
+    let total_curves: usize = run.processed.iter().map(|(_, (curves, _))| curves).sum();
+    let total_peaks: usize = run.processed.iter().map(|(_, (_, peaks))| peaks).sum();
+    let processed_files: Vec<String> = run.processed.into_iter().map(|(path, _)| path).collect();
+    
     let processing_time = start_time.elapsed().as_millis() as u64;
     
     let result = BatchProcessingResult {
-        success: !processed_files.is_empty(),
+        success: !processed_files.is_empty() || !skipped_files.is_empty(),
         processed_files,
-        failed_files: failed_files.clone(),
+        error: BatchProcessingResult::failure_summary(&failed_files),
+        failed_files,
+        skipped_files,
+        cancelled,
         total_curves,
         total_peaks,
         processing_time,
-        error: if failed_files.is_empty() { None } else { Some("部分文件处理失败".to_string()) },
     };
     
     // 发送最终进度更新
-    state.emit_progress_update(&app, total_files, total_files, "批量处理完成");
+    let final_progress = state.batch_progress("批量处理完成");
+    state.emit_progress_update(&app, "batch", final_progress.current, final_progress.total, &final_progress.message);
+    state.finish_progress(&app, "batch");
     
     {
         let mut app_state = state.lock();
@@ -213,12 +402,31 @@ pub async fn batch_process_files(
     Ok(result)
 }
 
-/// 获取曲线数据用于显示
+/// 取消正在进行的批量处理；当前文件完成后停止，已完成的文件保留在检查点中
+#[tauri::command]
+pub async fn cancel_batch_processing(
+    _app: tauri::AppHandle,
+    state: State<'_, AppStateManager>
+) -> Result<String, String> {
+    state.cancel_batch();
+    {
+        let mut app_state = state.lock();
+        app_state.add_message("info", "批量处理", "已请求取消批量处理");
+    }
+    Ok("已请求取消批量处理".to_string())
+}
+
+/// 获取曲线数据用于显示，`interpolation` 为 "none"（默认）/ "linear" / "monotone_cubic"
 #[tauri::command]
 pub async fn get_curve_data_for_display(
+    interpolation: Option<String>,
     _app: tauri::AppHandle,
     state: State<'_, AppStateManager>
 ) -> Result<Vec<CurveDisplayData>, String> {
+    // 默认不插值，显示点都是真实数据点
+    let interpolation = DisplayInterpolation::from_name(interpolation.as_deref().unwrap_or("none"))
+        .map_err(|e| e.to_string())?;
+    
     // 从应用状态获取当前处理的数据
     let current_files = {
         let app_state = state.lock();
@@ -240,10 +448,9 @@ pub async fn get_curve_data_for_display(
     // 转换为显示格式
     let mut display_data = Vec::new();
     for curve in &container.curves {
-        // 只取前100个数据点用于显示
+        // 整条曲线抽稀到最多100个显示点
         let max_points = 100;
-        let x_values: Vec<f64> = curve.x_values.iter().take(max_points).cloned().collect();
-        let y_values: Vec<f64> = curve.y_values.iter().take(max_points).cloned().collect();
+        let (x_values, y_values) = decimate_for_display(&curve.x_values, &curve.y_values, max_points, interpolation);
         
         display_data.push(CurveDisplayData {
             id: curve.id.clone(),
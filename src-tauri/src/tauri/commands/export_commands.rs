@@ -6,11 +6,15 @@ use crate::core::loaders::mzdata_loader::DataLoader;
 use super::{ExportParams, ExportResultInfo};
 
 /// 快速导出曲线数据到文件夹
+///
+/// 每写出一个曲线文件发送一次进度事件；`cancel_export` 可中止导出，已写出的文件保留。
+/// 导出使用自己的取消标志，不影响正在进行的批量处理
 #[tauri::command]
 pub async fn export_curves_to_folder(
     output_folder: String,
     container: crate::core::data::container::SerializableDataContainer,
-    _app: tauri::AppHandle,
+    decimal_precision: Option<usize>,
+    app: tauri::AppHandle,
     state: State<'_, AppStateManager>
 ) -> Result<ExportResultInfo, String> {
     {
@@ -19,14 +23,14 @@ pub async fn export_curves_to_folder(
     }
     
     // 使用优化的曲线TSV导出器
-    let export_manager = crate::core::exporters::export_manager::ExportManager::new();
+    let exporter = crate::core::exporters::curve_tsv_exporter::CurveTsvExporter;
     
     // 准备导出配置
     let export_config = serde_json::json!({
         "output_folder": output_folder,
         "include_curve_data": true,
         "include_metadata": true,
-        "decimal_precision": 6
+        "decimal_precision": decimal_precision.unwrap_or(6)
     });
     
     // 将SerializableDataContainer转换为DataContainer
@@ -40,17 +44,38 @@ pub async fn export_curves_to_folder(
         return Err("没有可导出的曲线数据".to_string());
     }
     
-    // 执行导出
-    match export_manager.export("curve_tsv", &data_container, export_config).await {
+    // 执行导出，进度按已写出的文件数计算，不改动批量处理的计数与取消标志
+    state.start_export();
+    let result = exporter.export_with_progress(
+        &data_container,
+        export_config,
+        |current, total, filename| state.emit_progress_update(&app, "export", current, total, &format!("已导出: {}", filename)),
+        || state.is_export_cancelled(),
+    );
+    // 取消或失败时进度停在 100% 之前，补发被节流合并掉的最后一次更新
+    state.finish_progress(&app, "export");
+    
+    match result {
         Ok(result) => {
+            let exported_count = result.metadata.get("exported_count").and_then(|v| v.as_u64()).unwrap_or(0);
+            let total_curves = result.metadata.get("total_curves").and_then(|v| v.as_u64()).unwrap_or(0);
+            let cancelled = result.metadata.get("cancelled").and_then(|v| v.as_bool()).unwrap_or(false);
+            
             let mut app_state = state.lock();
-            app_state.add_message("success", "曲线导出完成", &format!("成功导出到文件夹: {}", output_folder));
+            let message = if cancelled {
+                app_state.add_message("warning", "曲线导出已取消", &format!("已导出 {}/{} 个文件到文件夹: {}", exported_count, total_curves, output_folder));
+                format!("导出已取消，已导出 {}/{} 个文件到文件夹: {}", exported_count, total_curves, output_folder)
+            } else {
+                app_state.add_message("success", "曲线导出完成", &format!("成功导出到文件夹: {}", output_folder));
+                format!("成功导出 {} 个文件到文件夹: {}", exported_count, output_folder)
+            };
+            if let Some(manifest_path) = result.metadata.get("manifest_path").and_then(|v| v.as_str()) {
+                app_state.add_message("info", "导出清单", &format!("清单文件: {}", manifest_path));
+            }
             
             Ok(ExportResultInfo {
                 success: true,
-                message: format!("成功导出 {} 个文件到文件夹: {}", 
-                    result.metadata.get("exported_files").and_then(|v| v.as_array()).map(|arr| arr.len()).unwrap_or(0),
-                    output_folder),
+                message,
                 filename: result.filename,
                 file_size: result.metadata.get("total_size_bytes").and_then(|v| v.as_u64()).unwrap_or(0) as usize,
                 mime_type: result.mime_type,
@@ -66,6 +91,75 @@ pub async fn export_curves_to_folder(
     }
 }
 
+/// 取消正在进行的曲线导出；当前文件写完后停止，已写出的文件保留
+#[tauri::command]
+pub async fn cancel_export(
+    state: State<'_, AppStateManager>
+) -> Result<String, String> {
+    state.cancel_export();
+    {
+        let mut app_state = state.lock();
+        app_state.add_message("info", "曲线导出", "已请求取消曲线导出");
+    }
+    Ok("已请求取消曲线导出".to_string())
+}
+
+/// 导出到SQLite数据库：曲线与峰写入带类型列的表，已有数据库时以新的 `run_id` 追加
+#[tauri::command]
+pub async fn export_sqlite(
+    container: crate::core::data::container::SerializableDataContainer,
+    database_path: String,
+    run_id: Option<String>,
+    state: State<'_, AppStateManager>
+) -> Result<ExportResultInfo, String> {
+    {
+        let mut app_state = state.lock();
+        app_state.add_message("info", "SQLite导出", &format!("开始导出到数据库: {}", database_path));
+    }
+    
+    let data_container: crate::core::data::DataContainer = container.into();
+    if data_container.curves.is_empty() {
+        {
+            let mut app_state = state.lock();
+            app_state.add_message("error", "导出失败", "没有可导出的曲线数据");
+        }
+        return Err("没有可导出的曲线数据".to_string());
+    }
+    
+    let mut export_config = serde_json::json!({ "database_path": database_path });
+    if let Some(run_id) = run_id {
+        export_config["run_id"] = serde_json::json!(run_id);
+    }
+    
+    let export_manager = crate::core::exporters::export_manager::ExportManager::new();
+    match export_manager.export("sqlite", &data_container, export_config).await {
+        Ok(result) => {
+            let run_id = result.metadata.get("run_id").and_then(|v| v.as_str()).unwrap_or("").to_string();
+            let peak_count = result.metadata.get("peak_count").and_then(|v| v.as_u64()).unwrap_or(0);
+            let file_size = result.metadata.get("file_size_bytes").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let message = format!("已写入 run {}：{} 条曲线、{} 个峰", run_id, data_container.curves.len(), peak_count);
+            {
+                let mut app_state = state.lock();
+                app_state.add_message("success", "SQLite导出完成", &message);
+            }
+            Ok(ExportResultInfo {
+                success: true,
+                message,
+                filename: result.filename,
+                file_size,
+                mime_type: result.mime_type,
+            })
+        },
+        Err(e) => {
+            {
+                let mut app_state = state.lock();
+                app_state.add_message("error", "导出失败", &format!("错误: {}", e));
+            }
+            Err(format!("导出失败: {}", e))
+        }
+    }
+}
+
 /// 导出TSV数据
 #[tauri::command]
 pub async fn export_tsv(params: ExportParams, _app: tauri::AppHandle, state: State<'_, AppStateManager>) -> Result<ExportResultInfo, String> {
@@ -78,13 +172,26 @@ pub async fn export_tsv(params: ExportParams, _app: tauri::AppHandle, state: Sta
     let export_manager = crate::core::exporters::export_manager::ExportManager::new();
     
     // 准备导出配置
-    let export_config = serde_json::json!({
+    let mut export_config = serde_json::json!({
         "output_path": params.output_path,
         "include_curves": params.include_curves,
         "include_peaks": params.include_peaks,
-        "include_metadata": params.include_metadata
+        "include_metadata": params.include_metadata,
+        "include_overview": params.include_overview.unwrap_or(false),
+        "include_bpc": params.include_bpc.unwrap_or(false),
+        "min_quality": params.min_quality,
+        "min_area": params.min_area,
+        "min_snr": params.min_snr
     });
     
+    // 单次导出的精度覆盖，未设置时保留导出器默认值
+    if let Some(precision) = params.decimal_precision {
+        export_config["decimal_precision"] = serde_json::json!(precision);
+    }
+    if let Some(sort_by) = &params.sort_by {
+        export_config["sort_by"] = serde_json::json!(sort_by);
+    }
+    
     // 创建数据容器（这里需要从当前状态获取数据）
     let mut container = crate::core::data::DataContainer::new();
     
@@ -114,6 +221,9 @@ pub async fn export_tsv(params: ExportParams, _app: tauri::AppHandle, state: Sta
             {
                 let mut app_state = state.lock();
                 app_state.add_message("success", "TSV导出完成", &format!("文件已导出: {}", result.filename));
+                if let Some(filtered) = result.metadata.get("filtered_peak_count").and_then(|v| v.as_u64()) {
+                    app_state.add_message("info", "峰过滤", &format!("已过滤 {} 个未达到阈值的峰", filtered));
+                }
             }
             
             Ok(ExportResultInfo {
@@ -136,12 +246,80 @@ pub async fn export_tsv(params: ExportParams, _app: tauri::AppHandle, state: Sta
 
 /// 导出JSON数据
 #[tauri::command]
-pub async fn export_json(_params: ExportParams, _app: tauri::AppHandle, state: State<'_, AppStateManager>) -> Result<ExportResultInfo, String> {
+pub async fn export_json(params: ExportParams, _app: tauri::AppHandle, state: State<'_, AppStateManager>) -> Result<ExportResultInfo, String> {
     {
         let mut app_state = state.lock();
-        app_state.add_message("error", "JSON导出失败", "JSON导出器尚未实现");
+        app_state.add_message("info", "JSON导出", &format!("开始导出JSON数据: {}", params.file_path));
+    }
+    
+    let export_manager = crate::core::exporters::export_manager::ExportManager::new();
+    
+    // 准备导出配置
+    let mut export_config = serde_json::json!({
+        "output_path": params.output_path,
+        "include_curves": params.include_curves,
+        "include_peaks": params.include_peaks,
+        "include_metadata": params.include_metadata,
+        "min_quality": params.min_quality,
+        "min_area": params.min_area,
+        "min_snr": params.min_snr
+    });
+    
+    // 单次导出的精度覆盖，未设置时保留完整精度
+    if let Some(precision) = params.decimal_precision {
+        export_config["decimal_precision"] = serde_json::json!(precision);
+    }
+    if let Some(sort_by) = &params.sort_by {
+        export_config["sort_by"] = serde_json::json!(sort_by);
+    }
+    
+    // 从应用状态获取当前处理的数据
+    let mut container = crate::core::data::DataContainer::new();
+    let current_files = {
+        let app_state = state.lock();
+        app_state.current_files.clone()
+    };
+    
+    if !current_files.is_empty() {
+        match DataLoader::load_from_file(&current_files[0]) {
+            Ok(data) => container = data,
+            Err(e) => {
+                {
+                    let mut app_state = state.lock();
+                    app_state.add_message("error", "导出失败", &format!("无法加载数据: {}", e));
+                }
+                return Err(format!("无法加载数据: {}", e));
+            }
+        }
+    }
+    
+    // 执行导出
+    match export_manager.export("json", &container, export_config).await {
+        Ok(result) => {
+            {
+                let mut app_state = state.lock();
+                app_state.add_message("success", "JSON导出完成", &format!("文件已导出: {}", result.filename));
+                if let Some(filtered) = result.metadata.get("filtered_peak_count").and_then(|v| v.as_u64()) {
+                    app_state.add_message("info", "峰过滤", &format!("已过滤 {} 个未达到阈值的峰", filtered));
+                }
+            }
+            
+            Ok(ExportResultInfo {
+                success: true,
+                filename: result.filename,
+                file_size: result.data.len(),
+                mime_type: result.mime_type,
+                message: "JSON导出成功".to_string(),
+            })
+        }
+        Err(e) => {
+            {
+                let mut app_state = state.lock();
+                app_state.add_message("error", "JSON导出失败", &format!("错误: {}", e));
+            }
+            Err(format!("JSON导出失败: {}", e))
+        }
     }
-    Err("JSON导出器尚未实现".to_string())
 }
 
 /// 导出图表数据
@@ -156,11 +334,16 @@ pub async fn export_plot(params: ExportParams, _app: tauri::AppHandle, state: St
     let export_manager = crate::core::exporters::export_manager::ExportManager::new();
     
     // 准备Plotly导出配置
-    let export_config = serde_json::json!({
+    let mut export_config = serde_json::json!({
         "output_path": params.output_path,
         "include_curves": params.include_curves,
         "include_peaks": params.include_peaks,
         "include_metadata": params.include_metadata,
+        "include_overview": params.include_overview.unwrap_or(false),
+        "include_bpc": params.include_bpc.unwrap_or(false),
+        "min_quality": params.min_quality,
+        "min_area": params.min_area,
+        "min_snr": params.min_snr,
         "chart_type": "combined",
         "show_peaks": true,
         "show_fit": false,
@@ -171,6 +354,13 @@ pub async fn export_plot(params: ExportParams, _app: tauri::AppHandle, state: St
         "height": 600
     });
     
+    // 应用用户的可视化设置
+    if let Some(settings) = &params.visualization_settings {
+        export_config["color_scheme"] = serde_json::json!(settings.color_scheme);
+        export_config["show_grid"] = serde_json::json!(settings.show_grid);
+        export_config["show_legend"] = serde_json::json!(settings.show_legend);
+    }
+    
     // 创建数据容器
     let mut container = crate::core::data::DataContainer::new();
     
@@ -199,6 +389,9 @@ pub async fn export_plot(params: ExportParams, _app: tauri::AppHandle, state: St
             {
                 let mut app_state = state.lock();
                 app_state.add_message("success", "Plotly图表导出完成", &format!("文件已导出: {}", result.filename));
+                if let Some(filtered) = result.metadata.get("filtered_peak_count").and_then(|v| v.as_u64()) {
+                    app_state.add_message("info", "峰过滤", &format!("已过滤 {} 个未达到阈值的峰", filtered));
+                }
             }
             
             Ok(ExportResultInfo {
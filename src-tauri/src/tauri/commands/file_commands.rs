@@ -3,17 +3,17 @@
 use tauri::State;
 use crate::tauri::state::{AppStateManager, ProcessingStatus};
 use crate::core::loaders::mzdata_loader::DataLoader;
-use mzdata::prelude::SpectrumLike;
-use super::{FileInfo, ValidationResult, DataRanges};
+use mzdata::prelude::{SpectrumLike, MZLocated, IntensityMeasurement};
+use super::{FileInfo, ValidationResult, DataRanges, SpectrumData};
 
 /// 步骤1: 加载文件并获取基本信息
 #[tauri::command]
-pub async fn load_file(file_path: String, app: tauri::AppHandle, state: State<'_, AppStateManager>) -> Result<FileInfo, String> {
+pub async fn load_file(file_path: String, tolerant: Option<bool>, app: tauri::AppHandle, state: State<'_, AppStateManager>) -> Result<FileInfo, String> {
     log::info!("🚀 开始加载文件: {}", file_path);
     
     // 发送状态更新事件
     state.emit_status_update(&app, &ProcessingStatus::Loading);
-    state.emit_progress_update(&app, 0, 100, "开始加载文件...");
+    state.emit_progress_update(&app, "load_file", 0, 100, "开始加载文件...");
     
     let mut app_state = state.lock();
     
@@ -21,26 +21,22 @@ pub async fn load_file(file_path: String, app: tauri::AppHandle, state: State<'_
     app_state.set_processing_status(ProcessingStatus::Loading);
     app_state.add_message("info", "文件加载", &format!("开始加载文件: {}", file_path));
     
-    // 发送日志消息事件
-    if let Some(last_message) = app_state.messages.last() {
-        state.emit_log_message(&app, last_message);
-    }
-    
     log::info!("📊 状态已更新为: Loading");
     
     // 获取文件信息
     log::info!("📁 获取文件元数据...");
-    state.emit_progress_update(&app, 10, 100, "获取文件元数据...");
+    state.emit_progress_update(&app, "load_file", 10, 100, "获取文件元数据...");
     
     let metadata = std::fs::metadata(&file_path)
         .map_err(|e| {
             log::error!("❌ 无法读取文件元数据: {}", e);
             state.emit_status_update(&app, &ProcessingStatus::Error(format!("无法读取文件: {}", e)));
+            state.finish_progress(&app, "load_file");
             format!("无法读取文件: {}", e)
         })?;
     
     log::info!("📊 文件大小: {} bytes", metadata.len());
-    state.emit_progress_update(&app, 20, 100, &format!("文件大小: {} bytes", metadata.len()));
+    state.emit_progress_update(&app, "load_file", 20, 100, &format!("文件大小: {} bytes", metadata.len()));
     
     let file_name = std::path::Path::new(&file_path)
         .file_name()
@@ -58,18 +54,38 @@ pub async fn load_file(file_path: String, app: tauri::AppHandle, state: State<'_
     
     // 使用真实的DataLoader加载文件，支持进度报告
     log::info!("🔄 开始使用DataLoader加载文件...");
-    state.emit_progress_update(&app, 30, 100, "使用DataLoader加载文件...");
+    state.emit_progress_update(&app, "load_file", 30, 100, "使用DataLoader加载文件...");
     
     // 使用带进度报告的DataLoader
-    let (is_valid, spectra_count, data_ranges) = match DataLoader::load_from_file(&file_path) {
+    // 容错模式跳过无法解析的光谱，默认严格加载
+    let loaded = if tolerant.unwrap_or(false) {
+        DataLoader::load_from_file_tolerant(&file_path)
+    } else {
+        DataLoader::load_from_file(&file_path)
+    };
+    let (is_valid, spectra_count, data_ranges, result_id) = match loaded {
         Ok(container) => {
             let count = container.spectra.len();
             log::info!("✅ 文件加载成功: {} 个光谱", count);
             log::info!("📈 曲线数量: {}", container.curves.len());
             log::info!("🔍 峰数量: {}", container.total_peak_count());
             
+            let non_finite_fixed = container.metadata.get("non_finite_intensities_fixed")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0);
+            if non_finite_fixed > 0 {
+                app_state.add_message("warning", "数据清理", &format!("{} 个非有限强度值 (NaN/Inf) 已置零", non_finite_fixed));
+            }
+            
+            let skipped_spectra = container.metadata.get("skipped_spectra")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0);
+            if skipped_spectra > 0 {
+                app_state.add_message("warning", "容错加载", &format!("{} 个无法解析的光谱已跳过", skipped_spectra));
+            }
+            
             // 发送加载完成进度更新
-            state.emit_progress_update(&app, 70, 100, &format!("成功加载 {} 个光谱", count));
+            state.emit_progress_update(&app, "load_file", 70, 100, &format!("成功加载 {} 个光谱", count));
             
             // 从元数据中提取数据范围
             let ranges = if let (Some(rt_min), Some(rt_max), Some(mz_min), Some(mz_max)) = (
@@ -104,28 +120,23 @@ pub async fn load_file(file_path: String, app: tauri::AppHandle, state: State<'_
             // 缓存文件数据以提高后续操作性能
             state.cache_file(&file_path, container.clone());
             
-            state.emit_progress_update(&app, 80, 100, &format!("成功加载 {} 个光谱", count));
+            // 开始处理溯源链，记录随缓存的工作容器保存
+            let result_id = state.start_provenance(&file_path, serde_json::json!({"file_path": file_path}))
+                .map_err(|e| log::warn!("⚠️ 溯源记录失败: {}", e))
+                .ok();
             
-            app_state.add_message("success", "文件加载成功", &format!("成功加载 {} 个光谱", count));
+            state.emit_progress_update(&app, "load_file", 80, 100, &format!("成功加载 {} 个光谱", count));
             
-            // 发送成功消息
-            if let Some(last_message) = app_state.messages.last() {
-                state.emit_log_message(&app, last_message);
-            }
+            app_state.add_message("success", "文件加载成功", &format!("成功加载 {} 个光谱", count));
             
-            (true, Some(count), ranges)
+            (true, Some(count), ranges, result_id)
         }
         Err(e) => {
             log::error!("❌ 文件加载失败: {}", e);
             state.emit_status_update(&app, &ProcessingStatus::Error(format!("文件加载失败: {}", e)));
             app_state.add_message("error", "文件加载失败", &format!("错误: {}", e));
             
-            // 发送错误消息
-            if let Some(last_message) = app_state.messages.last() {
-                state.emit_log_message(&app, last_message);
-            }
-            
-            (false, None, None)
+            (false, None, None, None)
         }
     };
     
@@ -137,11 +148,12 @@ pub async fn load_file(file_path: String, app: tauri::AppHandle, state: State<'_
         is_valid,
         spectra_count,
         data_ranges,
+        result_id,
     };
     
     // 更新状态
     log::info!("🔄 更新应用状态...");
-    state.emit_progress_update(&app, 90, 100, "更新应用状态...");
+    state.emit_progress_update(&app, "load_file", 90, 100, "更新应用状态...");
     
     app_state.set_current_files(vec![file_path.clone()]);
     app_state.set_processing_status(ProcessingStatus::Idle);
@@ -149,17 +161,84 @@ pub async fn load_file(file_path: String, app: tauri::AppHandle, state: State<'_
     
     // 发送最终状态更新
     state.emit_status_update(&app, &ProcessingStatus::Idle);
-    state.emit_progress_update(&app, 100, 100, "文件加载完成");
-    
-    // 发送最终日志消息
-    if let Some(last_message) = app_state.messages.last() {
-        state.emit_log_message(&app, last_message);
-    }
+    state.emit_progress_update(&app, "load_file", 100, 100, "文件加载完成");
     
     log::info!("✅ 文件加载命令完成: {}", file_info.name);
     Ok(file_info)
 }
 
+/// 获取结果的完整处理溯源：源文件校验和、程序与加载器版本，以及按顺序记录的各处理阶段配置
+#[tauri::command]
+pub async fn get_provenance(
+    result_id: String,
+    state: State<'_, AppStateManager>
+) -> Result<crate::core::data::provenance::Provenance, String> {
+    state.get_provenance(&result_id)
+        .ok_or_else(|| format!("未找到结果的溯源记录: {}", result_id))
+}
+
+/// 获取文件元数据（仪器型号、极性、采集软件及数据范围）
+#[tauri::command]
+pub async fn get_file_metadata(
+    file_path: String,
+    _app: tauri::AppHandle,
+    state: State<'_, AppStateManager>
+) -> Result<std::collections::HashMap<String, serde_json::Value>, String> {
+    log::info!("📋 获取文件元数据: {}", file_path);
+    
+    let container = if let Some(cached_container) = state.get_cached_file(&file_path) {
+        cached_container
+    } else {
+        match DataLoader::load_from_file(&file_path) {
+            Ok(container) => {
+                state.cache_file(&file_path, container.clone());
+                container
+            },
+            Err(e) => {
+                let mut app_state = state.lock();
+                app_state.add_message("error", "元数据读取失败", &format!("错误: {}", e));
+                return Err(format!("无法加载文件: {}", e));
+            }
+        }
+    };
+    
+    Ok(container.metadata)
+}
+
+/// 获取保留时间最接近 `rt` 的光谱（通过缓存的索引读取器随机访问，不加载整个文件）
+#[tauri::command]
+pub async fn get_spectrum_at_rt(
+    file_path: String,
+    rt: f64,
+    _app: tauri::AppHandle,
+    state: State<'_, AppStateManager>
+) -> Result<SpectrumData, String> {
+    let reader = state.get_indexed_reader(&file_path)?;
+    let spectrum = {
+        let mut reader = reader.lock().map_err(|_| "索引读取器锁定失败".to_string())?;
+        reader.get_spectrum_at_rt(rt)
+    };
+    
+    let spectrum = match spectrum {
+        Some(spectrum) => spectrum,
+        None => {
+            let mut app_state = state.lock();
+            app_state.add_message("error", "光谱读取失败", &format!("RT {:.4} 处没有光谱", rt));
+            return Err(format!("RT {:.4} 处没有光谱", rt));
+        }
+    };
+    
+    let peaks = spectrum.peaks();
+    Ok(SpectrumData {
+        index: spectrum.index(),
+        id: spectrum.id().to_string(),
+        rt: spectrum.start_time(),
+        ms_level: spectrum.ms_level(),
+        mz_values: peaks.iter().map(|p| p.mz()).collect(),
+        intensities: peaks.iter().map(|p| p.intensity() as f64).collect(),
+    })
+}
+
 /// 清理文件缓存
 #[tauri::command]
 pub async fn clear_file_cache(_app: tauri::AppHandle, state: State<'_, AppStateManager>) -> Result<String, String> {
@@ -228,6 +307,8 @@ pub async fn validate_file(file_path: String, _app: tauri::AppHandle, state: Sta
                 spectra_count: Some(spectra_count),
                 file_size: std::fs::metadata(&file_path).ok().map(|m| m.len()),
                 data_ranges,
+                format: DataLoader::detect_format(&file_path).ok().map(|(format, _)| format.to_string()),
+                full_validation: true,
             }
         }
         Err(e) => {
@@ -238,6 +319,8 @@ pub async fn validate_file(file_path: String, _app: tauri::AppHandle, state: Sta
                 spectra_count: None,
                 file_size: std::fs::metadata(&file_path).ok().map(|m| m.len()),
                 data_ranges: None,
+                format: None,
+                full_validation: true,
             }
         }
     };
@@ -250,3 +333,45 @@ pub async fn validate_file(file_path: String, _app: tauri::AppHandle, state: Sta
     
     Ok(result)
 }
+
+/// 快速验证文件：只检查格式、光谱索引和前几个光谱，完整验证可稍后按需进行
+#[tauri::command]
+pub async fn quick_validate(file_path: String, _app: tauri::AppHandle, state: State<'_, AppStateManager>) -> Result<ValidationResult, String> {
+    log::info!("⚡ 快速验证文件: {}", file_path);
+    
+    let file_size = std::fs::metadata(&file_path).ok().map(|m| m.len());
+    let result = match DataLoader::quick_validate(&file_path, crate::core::loaders::mzdata_loader::QUICK_VALIDATE_SAMPLE_SPECTRA) {
+        Ok(validation) => ValidationResult {
+            is_valid: true,
+            message: format!("文件格式有效 ({}，已检查前 {} 个光谱)", validation.format, validation.parsed_spectra),
+            spectra_count: Some(validation.spectrum_count),
+            file_size,
+            data_ranges: None,
+            format: Some(validation.format),
+            full_validation: false,
+        },
+        Err(e) => {
+            log::error!("❌ 快速验证失败: {}", e);
+            ValidationResult {
+                is_valid: false,
+                message: format!("文件验证失败: {}", e),
+                spectra_count: None,
+                file_size,
+                data_ranges: None,
+                format: None,
+                full_validation: false,
+            }
+        }
+    };
+    
+    {
+        let mut app_state = state.lock();
+        if result.is_valid {
+            app_state.add_message("info", "快速验证", &format!("文件包含 {} 个光谱", result.spectra_count.unwrap_or(0)));
+        } else {
+            app_state.add_message("error", "验证失败", &result.message);
+        }
+    }
+    
+    Ok(result)
+}
@@ -9,6 +9,7 @@ pub mod config_commands;
 pub mod visualization_commands;
 pub mod processing_commands;
 pub mod peak_processing_commands;
+pub mod watch_commands;
 
 // 重新导出所有命令
 pub use file_commands::*;
@@ -19,6 +20,7 @@ pub use config_commands::*;
 pub use visualization_commands::*;
 pub use processing_commands::*;
 pub use peak_processing_commands::*;
+pub use watch_commands::*;
 
 // 公共结构体定义
 use serde::{Deserialize, Serialize};
@@ -33,6 +35,8 @@ pub struct FileInfo {
     pub is_valid: bool,
     pub spectra_count: Option<usize>,
     pub data_ranges: Option<DataRanges>,
+    #[serde(default)]
+    pub result_id: Option<String>, // 处理溯源链ID，后续处理命令据此追加阶段
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,6 +56,21 @@ pub struct ValidationResult {
     pub spectra_count: Option<usize>,
     pub file_size: Option<u64>,
     pub data_ranges: Option<DataRanges>,
+    #[serde(default)]
+    pub format: Option<String>, // 检测到的文件格式，如 "mzML"
+    #[serde(default)]
+    pub full_validation: bool, // false 表示快速验证结果，只检查了索引与前几个光谱
+}
+
+// 单个光谱数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpectrumData {
+    pub index: usize,
+    pub id: String,
+    pub rt: f64,
+    pub ms_level: u8,
+    pub mz_values: Vec<f64>,
+    pub intensities: Vec<f64>,
 }
 
 // 曲线提取参数
@@ -62,6 +81,9 @@ pub struct CurveExtractionParams {
     pub rt_range: String,
     pub ms_level: u8,
     pub curve_type: String, // "dt", "tic", "xic"
+    /// 可选的 x_label / y_label / x_unit / y_unit 覆盖，未设置时使用提取器默认值
+    #[serde(default, flatten)]
+    pub axis_labels: crate::core::data::AxisLabels,
 }
 
 // 峰检测参数
@@ -94,6 +116,39 @@ pub struct PeakAnalysisParams {
     pub threshold_multiplier: f64,
     pub min_peak_width: f64,
     pub max_peak_width: f64,
+    #[serde(default)]
+    pub fixed_parameters: Option<Vec<String>>, // 拟合时固定的参数，如 ["center"]
+    #[serde(default)]
+    pub boundary_method: Option<String>, // "threshold" | "valley" | "inflection"
+    #[serde(default)]
+    pub shoulder_policy: Option<String>, // "separate" | "merge" | "ignore"，不设置时为 "separate"
+    #[serde(default)]
+    pub apex_definition: Option<String>, // "raw_max" | "fitted_center" | "centroid"，不设置时为 "fitted_center"
+    #[serde(default)]
+    pub adaptive_sensitivity: Option<bool>, // 按曲线噪声基底自适应检测阈值
+    #[serde(default)]
+    pub force_shape: Option<String>, // 对所有峰强制使用的峰形，如 "lorentzian"
+    #[serde(default)]
+    pub noise_region: Option<(f64, f64)>, // 估计噪声基底用的无信号背景区间
+    #[serde(default)]
+    pub max_iterations: Option<usize>, // 拟合优化器最大迭代次数，不设置时为100
+    #[serde(default)]
+    pub convergence_threshold: Option<f64>, // 拟合优化器收敛阈值，不设置时为1e-6
+    #[serde(default)]
+    pub strategy_thresholds: Option<crate::core::processors::overlapping_peaks::StrategyThresholds>, // 重叠策略自动选择的分界阈值，不设置时为默认值
+    #[serde(default)]
+    pub result_id: Option<String>, // 处理溯源链ID，设置时把分析阶段记入溯源
+    #[serde(default)]
+    pub auto_baseline: Option<serde_json::Value>, // 检测前自动扣除基线：方法名（如 "linear"、"auto"）或基线配置对象
+}
+
+// 敏感度校准参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SensitivityCalibrationParams {
+    pub curve_data: crate::core::state::CurveData,
+    pub detection_method: String, // "auto" 时按曲线特征自动选择
+    pub threshold_multiplier: Option<f64>,
+    pub min_peak_width: Option<f64>,
 }
 
 // 峰分析结果
@@ -103,8 +158,39 @@ pub struct PeakAnalysisResult {
     pub peaks_tsv: String, // 峰数据TSV
     pub fitted_curve_tsv: String, // 拟合曲线TSV
     pub peak_count: usize,
+    pub no_peaks_found: bool,
+    pub no_peaks_reason: Option<String>, // "flat_curve" | "below_detection_threshold" | "below_quality_threshold"
+    #[serde(default)]
+    pub total_integrated_area: f64, // 所有峰面积之和
+    #[serde(default)]
+    pub area_unit: Option<String>, // 峰面积单位，如 "counts·ms"
+    #[serde(default)]
+    pub config_warnings: Vec<crate::core::processors::core::ConfigWarning>, // 超出范围已被截断的参数
     pub processing_time: u64,
     pub error: Option<String>,
+    #[serde(default)]
+    pub result_id: Option<String>,
+}
+
+// 单峰拆分结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeakSplitResult {
+    pub accepted: bool,
+    pub split: crate::core::processors::peak_fitting::multi_peak_fitter::PeakSplit,
+    pub container: crate::core::data::container::SerializableDataContainer, // 接受拆分时原峰已被组分替换
+}
+
+// 批量处理中失败的文件及其失败原因
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailedFile {
+    pub path: String,
+    pub reason: String,
+}
+
+impl FailedFile {
+    pub fn new(path: &str, reason: impl ToString) -> Self {
+        Self { path: path.to_string(), reason: reason.to_string() }
+    }
 }
 
 // 批量处理结果
@@ -112,13 +198,31 @@ pub struct PeakAnalysisResult {
 pub struct BatchProcessingResult {
     pub success: bool,
     pub processed_files: Vec<String>,
-    pub failed_files: Vec<String>,
+    pub failed_files: Vec<FailedFile>, // 每个失败文件的路径与实际错误
+    #[serde(default)]
+    pub skipped_files: Vec<String>, // 恢复时跳过的已完成文件
+    #[serde(default)]
+    pub cancelled: bool,
     pub total_curves: usize,
     pub total_peaks: usize,
     pub processing_time: u64,
     pub error: Option<String>,
 }
 
+impl BatchProcessingResult {
+    /// 汇总失败文件为一条错误信息，没有失败时为 None
+    pub fn failure_summary(failed_files: &[FailedFile]) -> Option<String> {
+        if failed_files.is_empty() {
+            return None;
+        }
+        let details = failed_files.iter()
+            .map(|f| format!("{}: {}", f.path, f.reason))
+            .collect::<Vec<_>>()
+            .join("; ");
+        Some(format!("{} 个文件处理失败 - {}", failed_files.len(), details))
+    }
+}
+
 // 进度更新事件
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProgressUpdate {
@@ -147,6 +251,19 @@ pub struct ExportParams {
     pub include_curves: bool,
     pub include_peaks: bool,
     pub include_metadata: bool,
+    pub include_overview: Option<bool>, // 是否附带TIC概览曲线
+    pub include_bpc: Option<bool>, // 是否同时附带BPC概览曲线
+    pub visualization_settings: Option<VisualizationSettings>, // 图表配色、网格与图例设置
+    #[serde(default)]
+    pub min_quality: Option<f64>, // 跳过质量分低于该值的峰 (0-1)
+    #[serde(default)]
+    pub min_area: Option<f64>, // 跳过面积低于该值的峰
+    #[serde(default)]
+    pub min_snr: Option<f64>, // 跳过信噪比低于该值的峰
+    #[serde(default)]
+    pub decimal_precision: Option<usize>, // 本次导出的数值小数位数 (1-15)，不设置时使用导出器默认值
+    #[serde(default)]
+    pub sort_by: Option<String>, // 峰表排序: center / area_desc / amplitude_desc / quality_desc
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -163,3 +280,43 @@ pub struct CurveDisplayData {
     pub mz_min: Option<f64>,
     pub mz_max: Option<f64>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::loaders::mzdata_loader::DataLoader;
+    use crate::core::utils::test_fixtures::{corrupt_second_spectrum, write_ms1_run};
+
+    /// 写入三张光谱的 mzML，并等长破坏第二张光谱的XML结构
+    fn write_mzml_with_corrupt_spectrum(path: &std::path::Path) {
+        write_ms1_run(path, 3, 0.01, |_| vec![(500.0, 100.0)]);
+        corrupt_second_spectrum(path);
+    }
+
+    #[test]
+    fn test_failed_files_carry_distinct_reasons() {
+        let missing = std::env::temp_dir().join(format!("mz_curve_batch_missing_{}.mzML", std::process::id()));
+        let corrupt = std::env::temp_dir().join(format!("mz_curve_batch_corrupt_{}.mzML", std::process::id()));
+        write_mzml_with_corrupt_spectrum(&corrupt);
+
+        let failed_files: Vec<FailedFile> = [&missing, &corrupt].iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .filter_map(|path| DataLoader::load_from_file(&path).err().map(|e| FailedFile::new(&path, format!("无法加载文件: {}", e))))
+            .collect();
+        let _ = std::fs::remove_file(&corrupt);
+
+        assert_eq!(failed_files.len(), 2);
+        assert_eq!(failed_files[0].path, missing.to_string_lossy());
+        assert_eq!(failed_files[1].path, corrupt.to_string_lossy());
+        assert_ne!(failed_files[0].reason, failed_files[1].reason);
+
+        let summary = BatchProcessingResult::failure_summary(&failed_files).unwrap();
+        assert!(summary.starts_with("2 个文件处理失败"));
+        assert!(summary.contains(&failed_files[0].reason) && summary.contains(&failed_files[1].reason));
+        assert!(BatchProcessingResult::failure_summary(&[]).is_none());
+
+        let json = serde_json::to_value(&failed_files[0]).unwrap();
+        assert_eq!(json["path"], serde_json::json!(failed_files[0].path));
+        assert!(json["reason"].is_string());
+    }
+}
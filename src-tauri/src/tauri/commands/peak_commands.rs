@@ -3,7 +3,18 @@
 use tauri::State;
 use crate::tauri::state::{AppStateManager, ProcessingStatus};
 use crate::core::processors::core::Processor;
-use super::{PeakAnalysisParams, PeakAnalysisResult};
+use crate::core::processors::quantitation::QuantitationResult;
+use crate::core::processors::calibration::{CalibrationCurve, CalibrationModel};
+use crate::core::processors::peak_tracking::PeakTrack;
+use crate::core::processors::k0::K0Params;
+use crate::core::processors::overlapping_peaks::OverlapEstimate;
+use crate::core::processors::curve_health::{CurveHealthReport, HealthVerdict};
+use crate::core::processors::library_search::{LibraryMatch, PeakList};
+use crate::core::processors::region_stats::RegionStats;
+use crate::core::processors::peak_analysis::{AnalysisRecipe, RecipeRunResult};
+use crate::core::processors::peak_detection::sensitivity_calibration::SensitivityCalibration;
+use crate::core::processors::peak_detection::evaluation::DetectionEvaluation;
+use super::{PeakAnalysisParams, PeakAnalysisResult, PeakSplitResult, SensitivityCalibrationParams, SpectrumData};
 
 /// 步骤4: 峰分析（保留向后兼容）
 #[tauri::command]
@@ -64,10 +75,22 @@ pub async fn analyze_peaks(
         "sensitivity": params.sensitivity,
         "threshold_multiplier": params.threshold_multiplier,
         "min_peak_width": params.min_peak_width,
-        "max_peak_width": params.max_peak_width
+        "max_peak_width": params.max_peak_width,
+        "fixed_parameters": params.fixed_parameters.clone().unwrap_or_default(),
+        "boundary_method": params.boundary_method.clone().unwrap_or_else(|| "threshold".to_string()),
+        "shoulder_policy": params.shoulder_policy.clone().unwrap_or_else(|| "separate".to_string()),
+        "apex_definition": params.apex_definition.clone().unwrap_or_else(|| "fitted_center".to_string()),
+        "adaptive_sensitivity": params.adaptive_sensitivity.unwrap_or(false),
+        "force_shape": params.force_shape.clone(),
+        "noise_region": params.noise_region,
+        "max_iterations": params.max_iterations,
+        "convergence_threshold": params.convergence_threshold,
+        "strategy_thresholds": params.strategy_thresholds,
+        "auto_baseline": params.auto_baseline
     });
     
     // 执行峰分析
+    let stage_config = config.clone();
     let result = match peak_analyzer.process(container.clone(), config).await {
         Ok(result) => result,
         Err(e) => {
@@ -79,6 +102,27 @@ pub async fn analyze_peaks(
         }
     };
     
+    // 超出模式范围的参数已被截断，提示用户
+    let config_warnings: Vec<crate::core::processors::core::ConfigWarning> = result.metadata.get("config_warnings")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+    if !config_warnings.is_empty() {
+        let messages: Vec<&str> = config_warnings.iter().map(|w| w.message.as_str()).collect();
+        let mut app_state = state.lock();
+        app_state.add_message("warning", "参数超出范围", &messages.join("; "));
+    }
+    
+    // 记录分析失败的曲线（其余曲线的结果仍然返回）
+    if let Some(failed) = result.metadata.get("failed_curves").and_then(|v| v.as_array()) {
+        if !failed.is_empty() {
+            let reasons: Vec<String> = failed.iter()
+                .map(|f| format!("{}: {}", f["curve_id"].as_str().unwrap_or("?"), f["reason"].as_str().unwrap_or("")))
+                .collect();
+            let mut app_state = state.lock();
+            app_state.add_message("error", "部分曲线峰分析失败", &reasons.join("; "));
+        }
+    }
+    
     // 生成TSV格式的峰数据
     log::info!("📊 生成峰数据TSV...");
     let mut peaks_tsv = String::new();
@@ -133,20 +177,584 @@ pub async fn analyze_peaks(
     
     let processing_time = start_time.elapsed().as_millis() as u64;
     
+    // 零峰结果：取检测阶段给出的原因，提示用户调整参数
+    let no_peaks_found = result.peaks.is_empty();
+    let no_peaks_reason = result.metadata.get("no_peak_curves")
+        .and_then(|v| v.as_array())
+        .and_then(|curves| curves.first())
+        .and_then(|c| c["reason"].as_str())
+        .map(|r| r.to_string());
+    
+    // 调用方给出溯源链ID时记录分析阶段
+    let result_id = params.result_id.as_deref().and_then(|result_id| {
+        state.record_provenance(Some(result_id), None, "analyze", stage_config)
+            .map_err(|e| log::warn!("⚠️ 溯源记录失败: {}", e))
+            .ok()
+    });
+    
     let analysis_result = PeakAnalysisResult {
         success: true,
         peaks_tsv,
         fitted_curve_tsv,
         peak_count: result.peaks.len(),
+        no_peaks_found,
+        no_peaks_reason: if no_peaks_found { no_peaks_reason } else { None },
+        total_integrated_area: result.peaks.iter().map(|peak| peak.area).sum(),
+        area_unit: result.peaks.first().and_then(|peak| peak.area_unit.clone()),
+        config_warnings,
         processing_time,
         error: None,
+        result_id,
     };
     
     {
         let mut app_state = state.lock();
         app_state.set_processing_status(ProcessingStatus::Idle);
-        app_state.add_message("success", "峰分析完成", &format!("检测到 {} 个峰，耗时 {}ms", analysis_result.peak_count, processing_time));
+        if analysis_result.no_peaks_found {
+            let hint = match analysis_result.no_peaks_reason.as_deref() {
+                Some(crate::core::processors::peak_analysis::NO_PEAKS_FLAT_CURVE) => "曲线平坦，没有可检测的信号",
+                Some(crate::core::processors::peak_analysis::NO_PEAKS_BELOW_QUALITY) => "检测到的峰均未通过质量过滤，可尝试降低质量阈值",
+                _ => "所有候选峰均低于检测阈值，可尝试降低敏感度",
+            };
+            app_state.add_message("warning", "未检测到峰", hint);
+        } else {
+            app_state.add_message("success", "峰分析完成", &format!("检测到 {} 个峰，耗时 {}ms", analysis_result.peak_count, processing_time));
+        }
     }
     
     Ok(analysis_result)
 }
+
+/// 敏感度校准：扫描敏感度并返回峰数稳定时的推荐值及敏感度-峰数曲线
+#[tauri::command]
+pub async fn calibrate_sensitivity(
+    params: SensitivityCalibrationParams,
+    _app: tauri::AppHandle,
+    state: State<'_, AppStateManager>
+) -> Result<SensitivityCalibration, String> {
+    {
+        let mut app_state = state.lock();
+        app_state.add_message("info", "敏感度校准", "开始扫描检测敏感度...");
+    }
+    
+    let x_values: Vec<f64> = params.curve_data.data_points.iter().map(|p| p.drift_time).collect();
+    let y_values: Vec<f64> = params.curve_data.data_points.iter().map(|p| p.intensity).collect();
+    let curve = crate::core::data::Curve::new(
+        format!("curve_{}", uuid::Uuid::new_v4()),
+        params.curve_data.curve_type.clone(),
+        x_values,
+        y_values,
+        "Drift Time".to_string(),
+        "Intensity".to_string(),
+        "ms".to_string(),
+        "counts".to_string(),
+    );
+    
+    let detection_method = if params.detection_method == "auto" {
+        crate::core::processors::peak_analysis::PeakAnalyzer::new().select_detection_method(&curve)
+    } else {
+        params.detection_method.clone()
+    };
+    
+    let mut config = serde_json::json!({});
+    if let Some(threshold_multiplier) = params.threshold_multiplier {
+        config["threshold_multiplier"] = serde_json::json!(threshold_multiplier);
+    }
+    if let Some(min_peak_width) = params.min_peak_width {
+        config["min_peak_width"] = serde_json::json!(min_peak_width);
+    }
+    
+    match crate::core::processors::peak_detection::sensitivity_calibration::calibrate_sensitivity(&curve, &detection_method, &config) {
+        Ok(calibration) => {
+            let mut app_state = state.lock();
+            app_state.add_message("success", "敏感度校准完成", &format!(
+                "推荐敏感度 {:.2}（{} 个峰，方法: {}）",
+                calibration.recommended_sensitivity, calibration.recommended_peak_count, calibration.detection_method
+            ));
+            Ok(calibration)
+        }
+        Err(e) => {
+            let mut app_state = state.lock();
+            app_state.add_message("error", "敏感度校准失败", &format!("错误: {}", e));
+            Err(format!("敏感度校准失败: {}", e))
+        }
+    }
+}
+
+/// 检测评估：按中心（容差内一对一）匹配检测峰与真值峰，返回 precision / recall / F1
+#[tauri::command]
+pub async fn evaluate_detection(
+    detected: Vec<f64>,
+    truth: Vec<f64>,
+    tolerance: f64,
+) -> Result<DetectionEvaluation, String> {
+    let evaluation = crate::core::processors::peak_detection::evaluation::evaluate_detection(&detected, &truth, tolerance)
+        .map_err(|e| format!("检测评估失败: {}", e))?;
+    log::info!("📏 检测评估: TP={}, FP={}, FN={}, precision={:.3}, recall={:.3}, F1={:.3}",
+        evaluation.true_positives, evaluation.false_positives, evaluation.false_negatives,
+        evaluation.precision, evaluation.recall, evaluation.f1);
+    Ok(evaluation)
+}
+
+/// 内标定量：计算分析物/内标峰面积比及其不确定度
+#[tauri::command]
+pub async fn quantify(
+    peaks: Vec<crate::core::data::Peak>,
+    analyte_label: String,
+    is_label: String,
+    state: State<'_, AppStateManager>
+) -> Result<QuantitationResult, String> {
+    match crate::core::processors::quantitation::quantify(&peaks, &analyte_label, &is_label) {
+        Ok(result) => {
+            let mut app_state = state.lock();
+            app_state.add_message("success", "定量完成", &format!("{} / {} 面积比: {:.6} ± {:.6}",
+                analyte_label, is_label, result.area_ratio, result.ratio_uncertainty));
+            Ok(result)
+        }
+        Err(e) => {
+            let mut app_state = state.lock();
+            app_state.add_message("error", "定量失败", &format!("错误: {}", e));
+            Err(format!("定量失败: {}", e))
+        }
+    }
+}
+
+/// 校准曲线：由标准品 (浓度, 峰面积) 拟合 linear / quadratic / weighted_linear 模型，返回系数、R²、LOD 和 LOQ
+///
+/// 结果保存在应用状态中，供 `apply_calibration` 反算浓度
+#[tauri::command]
+pub async fn fit_calibration_curve(
+    points: Vec<(f64, f64)>,
+    model: Option<String>,
+    state: State<'_, AppStateManager>
+) -> Result<CalibrationCurve, String> {
+    let result = CalibrationModel::from_name(model.as_deref().unwrap_or("linear"))
+        .and_then(|model| crate::core::processors::calibration::fit_calibration_curve(&points, model));
+    
+    let mut app_state = state.lock();
+    match result {
+        Ok(calibration) => {
+            app_state.add_message("success", "校准曲线", &format!("{:?}: {} 个标准品, R² = {:.6}, LOD = {:.6}, LOQ = {:.6}",
+                calibration.model, calibration.point_count, calibration.r_squared, calibration.lod, calibration.loq));
+            app_state.calibration = Some(calibration.clone());
+            Ok(calibration)
+        }
+        Err(e) => {
+            app_state.add_message("error", "校准曲线拟合失败", &format!("错误: {}", e));
+            Err(format!("校准曲线拟合失败: {}", e))
+        }
+    }
+}
+
+/// 由峰面积反算浓度；未传入校准曲线时使用最近一次拟合的校准曲线
+#[tauri::command]
+pub async fn apply_calibration(
+    area: f64,
+    calibration: Option<CalibrationCurve>,
+    state: State<'_, AppStateManager>
+) -> Result<f64, String> {
+    let mut app_state = state.lock();
+    let calibration = calibration
+        .or_else(|| app_state.calibration.clone())
+        .ok_or_else(|| "尚未拟合校准曲线".to_string())?;
+    match calibration.concentration(area) {
+        Ok(concentration) => {
+            if concentration < calibration.loq {
+                app_state.add_message("warning", "浓度低于定量限", &format!("面积 {} 对应浓度 {:.6} < LOQ {:.6}", area, concentration, calibration.loq));
+            }
+            Ok(concentration)
+        }
+        Err(e) => {
+            app_state.add_message("error", "浓度反算失败", &format!("错误: {}", e));
+            Err(format!("浓度反算失败: {}", e))
+        }
+    }
+}
+
+/// 峰追踪：沿按保留时间排序的曲线序列追踪同一个峰，返回中心/面积轨迹并标记丢失的帧
+#[tauri::command]
+pub async fn track_peak(
+    container_series: Vec<crate::core::data::Curve>,
+    initial_center: f64,
+    tolerance: f64,
+    state: State<'_, AppStateManager>
+) -> Result<PeakTrack, String> {
+    match crate::core::processors::peak_tracking::track_peak(&container_series, initial_center, tolerance) {
+        Ok(track) => {
+            let mut app_state = state.lock();
+            if track.lost_frames.is_empty() {
+                app_state.add_message("success", "峰追踪完成", &format!("在全部 {} 帧中追踪到峰", track.trajectory.len()));
+            } else {
+                app_state.add_message("warning", "峰追踪完成", &format!("{} 帧中有 {} 帧丢失峰: {:?}",
+                    track.trajectory.len(), track.lost_frames.len(), track.lost_frames));
+            }
+            Ok(track)
+        }
+        Err(e) => {
+            let mut app_state = state.lock();
+            app_state.add_message("error", "峰追踪失败", &format!("错误: {}", e));
+            Err(format!("峰追踪失败: {}", e))
+        }
+    }
+}
+
+/// 报告用的高斯等效参数：EMG峰换算为矩相同的高斯峰，其他峰型原样返回
+#[tauri::command]
+pub async fn get_gaussian_equivalents(
+    peaks: Vec<crate::core::data::Peak>,
+    state: State<'_, AppStateManager>
+) -> Result<Vec<crate::core::data::Peak>, String> {
+    let equivalents: Vec<crate::core::data::Peak> = peaks.iter()
+        .map(|peak| peak.to_gaussian_equivalent())
+        .collect();
+    let converted = peaks.iter()
+        .filter(|peak| peak.peak_type == crate::core::data::PeakType::EMG)
+        .count();
+    
+    {
+        let mut app_state = state.lock();
+        app_state.add_message("info", "高斯等效参数", &format!("已换算 {} 个EMG峰（共 {} 个峰）", converted, peaks.len()));
+    }
+    
+    Ok(equivalents)
+}
+
+/// 约化迁移率：以峰中心为漂移时间 (ms)，按仪器参数为每个峰标注 K0
+#[tauri::command]
+pub async fn calculate_k0(
+    peaks: Vec<crate::core::data::Peak>,
+    params: K0Params,
+    state: State<'_, AppStateManager>
+) -> Result<Vec<crate::core::data::Peak>, String> {
+    match crate::core::processors::k0::annotate_peaks_with_k0(&peaks, &params) {
+        Ok(annotated) => {
+            let mut app_state = state.lock();
+            app_state.add_message("success", "K0计算完成", &format!("已为 {} 个峰计算约化迁移率", annotated.len()));
+            Ok(annotated)
+        }
+        Err(e) => {
+            let mut app_state = state.lock();
+            app_state.add_message("error", "K0计算失败", &format!("错误: {}", e));
+            Err(format!("K0计算失败: {}", e))
+        }
+    }
+}
+
+/// 重叠程度预估：在执行耗时的解卷积之前给出重叠峰对比例、推荐策略和逐对重叠情况
+#[tauri::command]
+pub async fn estimate_overlap(
+    peaks: Vec<crate::core::data::Peak>,
+    curve: crate::core::data::Curve,
+    strategy_thresholds: Option<crate::core::processors::overlapping_peaks::StrategyThresholds>,
+    state: State<'_, AppStateManager>
+) -> Result<OverlapEstimate, String> {
+    let thresholds = strategy_thresholds.unwrap_or_default();
+    thresholds.validate().map_err(|e| e.to_string())?;
+    let estimate = crate::core::processors::overlapping_peaks::estimate_overlap(&peaks, &curve, &thresholds);
+    
+    {
+        let mut app_state = state.lock();
+        app_state.add_message("info", "重叠程度预估", &format!("重叠峰对比例 {:.2}，推荐策略: {}",
+            estimate.overlap_ratio, estimate.recommended_strategy));
+    }
+    
+    Ok(estimate)
+}
+
+/// 区域统计：对容器中每条曲线计算 [x_min, x_max] 内的面积、最大值、质心、均值、标准差和面积占比，区间截断到曲线X范围
+#[tauri::command]
+pub async fn region_stats(
+    container: crate::core::data::container::SerializableDataContainer,
+    x_min: f64,
+    x_max: f64,
+    state: State<'_, AppStateManager>
+) -> Result<Vec<RegionStats>, String> {
+    if container.curves.is_empty() {
+        return Err("容器中没有曲线".to_string());
+    }
+    let result: Result<Vec<RegionStats>, _> = container.curves.iter()
+        .map(|curve| crate::core::processors::region_stats::region_stats(curve, x_min, x_max))
+        .collect();
+    
+    let mut app_state = state.lock();
+    match result {
+        Ok(stats) => {
+            app_state.add_message("info", "区域统计", &format!("{} 条曲线，区间 {} - {}", stats.len(), x_min, x_max));
+            Ok(stats)
+        },
+        Err(e) => {
+            app_state.add_message("error", "区域统计失败", &e.to_string());
+            Err(e.to_string())
+        }
+    }
+}
+
+/// 同位素分组：按 1.00335/z 间距把峰归入同位素包络，标注包络ID、电荷和单同位素峰
+#[tauri::command]
+pub async fn group_isotopes(
+    peaks: Vec<crate::core::data::Peak>,
+    charge_states: Vec<u8>,
+    mz_tolerance: f64,
+    state: State<'_, AppStateManager>
+) -> Result<Vec<crate::core::data::Peak>, String> {
+    match crate::core::processors::isotope::group_isotopes(&peaks, &charge_states, mz_tolerance) {
+        Ok(grouped) => {
+            let envelopes: std::collections::HashSet<&str> = grouped.iter()
+                .filter_map(|peak| peak.get_metadata("isotope_envelope_id").and_then(|v| v.as_str()))
+                .collect();
+            let mut app_state = state.lock();
+            app_state.add_message("success", "同位素分组完成", &format!("{} 个峰归入 {} 个同位素包络", peaks.len(), envelopes.len()));
+            Ok(grouped)
+        },
+        Err(e) => {
+            let mut app_state = state.lock();
+            app_state.add_message("error", "同位素分组失败", &e.to_string());
+            Err(e.to_string())
+        }
+    }
+}
+
+/// 曲线健康诊断：分析前逐条检查信噪比、基线漂移、饱和、点密度和缺失点
+#[tauri::command]
+pub async fn diagnose_curve(
+    container: crate::core::data::container::SerializableDataContainer,
+    state: State<'_, AppStateManager>
+) -> Result<Vec<CurveHealthReport>, String> {
+    if container.curves.is_empty() {
+        return Err("容器中没有曲线".to_string());
+    }
+    let reports: Vec<CurveHealthReport> = container.curves.iter()
+        .map(crate::core::processors::curve_health::diagnose_curve)
+        .collect();
+    
+    {
+        let failed = reports.iter().filter(|r| r.verdict == HealthVerdict::Fail).count();
+        let warned = reports.iter().filter(|r| r.verdict == HealthVerdict::Warn).count();
+        let mut app_state = state.lock();
+        app_state.add_message("info", "曲线健康诊断", &format!("{} 条曲线：{} 条不通过，{} 条有警告",
+            reports.len(), failed, warned));
+    }
+    
+    Ok(reports)
+}
+
+/// 谱库检索：`library_json` 为条目数组（name/mz_values/intensities），以余弦相似度对每个条目打分，按分数从高到低返回
+#[tauri::command]
+pub async fn search_library(
+    spectrum: SpectrumData,
+    library_json: String,
+    mz_tolerance: Option<f64>,
+    state: State<'_, AppStateManager>
+) -> Result<Vec<LibraryMatch>, String> {
+    let query = PeakList { mz_values: spectrum.mz_values, intensities: spectrum.intensities };
+    let tolerance = mz_tolerance.unwrap_or(crate::core::processors::library_search::DEFAULT_MZ_TOLERANCE);
+
+    let result = crate::core::processors::library_search::parse_library(&library_json)
+        .and_then(|library| crate::core::processors::library_search::search_library(&query, &library, tolerance));
+    match result {
+        Ok(matches) => {
+            let mut app_state = state.lock();
+            match matches.first() {
+                Some(best) => app_state.add_message("success", "谱库检索完成", &format!(
+                    "已比对 {} 个条目，最佳匹配 {}（{:.3}）", matches.len(), best.name, best.score)),
+                None => app_state.add_message("info", "谱库检索完成", "谱库为空"),
+            }
+            Ok(matches)
+        },
+        Err(e) => {
+            let mut app_state = state.lock();
+            app_state.add_message("error", "谱库检索失败", &e.to_string());
+            Err(e.to_string())
+        }
+    }
+}
+
+/// 单峰拆分：在给定峰周围以 `n_components` 个组分重新拟合，AIC更优时用组分替换原峰
+#[tauri::command]
+pub async fn split_peak(
+    container: crate::core::data::container::SerializableDataContainer,
+    peak_id: String,
+    n_components: usize,
+    state: State<'_, AppStateManager>
+) -> Result<PeakSplitResult, String> {
+    let mut container = container;
+    let curve = container.curves.iter_mut()
+        .find(|c| c.peaks.iter().any(|p| p.id == peak_id))
+        .ok_or_else(|| format!("未找到峰: {}", peak_id))?;
+    let index = curve.peaks.iter().position(|p| p.id == peak_id).unwrap_or_default();
+    let peak = curve.peaks[index].clone();
+    
+    let fitter = crate::core::processors::peak_fitting::multi_peak_fitter::MultiPeakFitter::new();
+    match fitter.split_peak(&peak, curve, n_components, &serde_json::json!({})) {
+        Ok(split) => {
+            if split.accepted {
+                curve.peaks.splice(index..=index, split.components.iter().cloned());
+            }
+            let mut app_state = state.lock();
+            app_state.add_message(
+                if split.accepted { "success" } else { "info" },
+                "峰拆分",
+                &format!("{} 拆分为 {} 个组分{}（AIC {:.2} → {:.2}）",
+                    peak_id, n_components, if split.accepted { "已接受" } else { "未接受" }, split.single_aic, split.split_aic),
+            );
+            Ok(PeakSplitResult {
+                accepted: split.accepted,
+                split,
+                container,
+            })
+        }
+        Err(e) => {
+            let mut app_state = state.lock();
+            app_state.add_message("error", "峰拆分失败", &format!("错误: {}", e));
+            Err(format!("峰拆分失败: {}", e))
+        }
+    }
+}
+
+/// 批量重处理：把同一分析配方（检测+拟合+重叠处理）并行应用到多个已加载的容器
+#[tauri::command]
+pub async fn process_many(
+    containers: Vec<crate::core::data::container::SerializableDataContainer>,
+    recipe: AnalysisRecipe,
+    state: State<'_, AppStateManager>
+) -> Result<Vec<RecipeRunResult>, String> {
+    {
+        let mut app_state = state.lock();
+        app_state.set_processing_status(ProcessingStatus::Analyzing);
+        app_state.add_message("info", "批量重处理", &format!("按配方 {} 处理 {} 个容器...", recipe.name, containers.len()));
+    }
+    
+    let containers = containers.into_iter().map(Into::into).collect();
+    let runs = crate::core::processors::peak_analysis::PeakAnalyzer::process_many(containers, &recipe).await;
+    
+    {
+        let mut app_state = state.lock();
+        app_state.set_processing_status(ProcessingStatus::Idle);
+        let failed: Vec<String> = runs.iter()
+            .filter_map(|run| run.error.as_ref().map(|e| format!("#{}: {}", run.index, e)))
+            .collect();
+        if !failed.is_empty() {
+            app_state.add_message("error", "部分容器处理失败", &failed.join("; "));
+        }
+        let total_time: u64 = runs.iter().map(|run| run.processing_time_ms).sum();
+        app_state.add_message("success", "批量重处理完成", &format!("{} 个容器完成，{} 个失败，累计耗时 {}ms",
+            runs.len() - failed.len(), failed.len(), total_time));
+    }
+    
+    Ok(runs)
+}
+
+/// 统一峰面积：用同一种方法（analytic / trapezoid / local_baseline）重新计算所有峰的面积与面积百分比
+#[tauri::command]
+pub async fn normalize_peak_areas(
+    container: crate::core::data::container::SerializableDataContainer,
+    method: String,
+    state: State<'_, AppStateManager>
+) -> Result<crate::core::data::container::SerializableDataContainer, String> {
+    let method = crate::core::data::AreaMethod::from_name(&method).map_err(|e| e.to_string())?;
+    
+    let spectra = container.spectra.clone();
+    let mut data = container.to_data_container();
+    let peak_count = data.normalize_peak_areas(method);
+    
+    {
+        let mut app_state = state.lock();
+        app_state.add_message("success", "峰面积统一", &format!("已用 {} 方法重新计算 {} 个峰的面积", method.name(), peak_count));
+    }
+    
+    let mut result = crate::core::data::container::SerializableDataContainer::from(data);
+    result.spectra = spectra;
+    Ok(result)
+}
+
+/// 一键分析：自动估计噪声和峰宽、选择平滑窗口与检测敏感度后检测并拟合峰
+///
+/// 返回的容器中曲线为平滑后的曲线（峰嵌套其中），元数据 `quick_analysis_parameters` 记录自动选出的参数
+#[tauri::command]
+pub async fn quick_analyze(
+    container: crate::core::data::container::SerializableDataContainer,
+    state: State<'_, AppStateManager>
+) -> Result<crate::core::data::container::SerializableDataContainer, String> {
+    {
+        let mut app_state = state.lock();
+        app_state.set_processing_status(ProcessingStatus::Analyzing);
+        app_state.add_message("info", "一键分析", &format!("自动分析 {} 条曲线...", container.curves.len()));
+    }
+    
+    let result = run_quick_analysis(container).await;
+    
+    let mut app_state = state.lock();
+    app_state.set_processing_status(ProcessingStatus::Idle);
+    match result {
+        Ok(container) => {
+            let peak_count: usize = container.curves.iter().map(|c| c.peaks.len()).sum();
+            app_state.add_message("success", "一键分析完成", &format!("{} 条曲线，检测到 {} 个峰", container.curves.len(), peak_count));
+            Ok(container)
+        },
+        Err(e) => {
+            app_state.add_message("error", "一键分析失败", &e.to_string());
+            Err(format!("一键分析失败: {}", e))
+        }
+    }
+}
+
+/// 一键分析并把检测到的峰按 `curve_id` 嵌入对应的平滑曲线，光谱原样保留
+async fn run_quick_analysis(
+    container: crate::core::data::container::SerializableDataContainer,
+) -> Result<crate::core::data::container::SerializableDataContainer, crate::core::data::ProcessingError> {
+    let spectra = container.spectra.clone();
+    let result = crate::core::processors::quick_analysis::quick_analyze(container.to_data_container()).await?;
+    
+    let mut data = crate::core::data::DataContainer::new();
+    data.curves = result.curves;
+    data.metadata = result.metadata;
+    for curve in &mut data.curves {
+        curve.peaks = result.peaks.iter().filter(|p| p.curve_id == curve.id).cloned().collect();
+    }
+    
+    let mut container = crate::core::data::container::SerializableDataContainer::from(data);
+    container.spectra = spectra;
+    Ok(container)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::data::{Curve, DataContainer};
+    use crate::core::data::container::SerializableDataContainer;
+    use rand::{Rng, SeedableRng};
+
+    #[tokio::test]
+    async fn quick_analysis_keeps_detected_peaks_on_their_curves() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let x_values: Vec<f64> = (0..600).map(|i| i as f64 * 0.02).collect();
+        let y_values: Vec<f64> = x_values
+            .iter()
+            .map(|&x| 5.0 + rng.gen_range(-2.0..2.0) + [(3.0, 100.0), (8.0, 70.0)].iter()
+                .map(|&(center, amplitude)| amplitude * (-(x - center).powi(2) / (2.0 * 0.2 * 0.2)).exp())
+                .sum::<f64>())
+            .collect();
+        let mut data = DataContainer::new();
+        data.curves.push(Curve::new(
+            "dt".to_string(),
+            "DT".to_string(),
+            x_values,
+            y_values,
+            "Drift Time".to_string(),
+            "Intensity".to_string(),
+            "ms".to_string(),
+            "counts".to_string(),
+        ));
+
+        let result = run_quick_analysis(SerializableDataContainer::from(data)).await.unwrap();
+
+        assert_eq!(result.curves.len(), 1);
+        let peaks = &result.curves[0].peaks;
+        assert_eq!(peaks.len(), 2, "{:?}", peaks.iter().map(|p| p.center).collect::<Vec<_>>());
+        assert!(peaks.iter().all(|p| p.curve_id == "dt"));
+        for expected in [3.0, 8.0] {
+            assert!(peaks.iter().any(|p| (p.center - expected).abs() < 0.1), "missing peak at {}", expected);
+        }
+        assert!(result.metadata.contains_key("quick_analysis_parameters"));
+    }
+}
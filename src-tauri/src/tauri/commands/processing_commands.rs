@@ -16,6 +16,7 @@ pub struct BaselineCorrectionParams {
     pub lambda: Option<f64>, // 非对称最小二乘参数
     pub p: Option<f64>, // 非对称最小二乘参数
     pub max_iterations: Option<usize>, // 最大迭代次数
+    pub negative_handling: Option<String>, // 负强度处理: "clamp"（默认）, "keep", "shift"
 }
 
 // 基线校正结果结构
@@ -58,6 +59,8 @@ pub struct SmoothDataParams {
     pub polynomial_order: Option<u32>, // Savitzky-Golay多项式阶数
     pub sigma: Option<f64>, // 高斯平滑参数
     pub span: Option<f64>, // LOWESS平滑参数
+    #[serde(default)]
+    pub result_id: Option<String>, // 处理溯源链ID，不设置时使用文件当前的工作结果
 }
 
 // 数据平滑结果结构
@@ -68,6 +71,8 @@ pub struct SmoothDataResult {
     pub smoothing_method: String,
     pub processing_time: u64,
     pub message: String,
+    #[serde(default)]
+    pub result_id: Option<String>,
 }
 
 // 噪声降低参数结构
@@ -78,7 +83,15 @@ pub struct NoiseReductionParams {
     pub threshold: Option<f64>,
     pub wavelet_type: Option<String>, // "daubechies", "coiflets", "biorthogonal"
     pub decomposition_level: Option<u32>,
-    pub cutoff_frequency: Option<f64>, // 傅里叶滤波截止频率
+    pub cutoff_frequency: Option<f64>, // 傅里叶滤波截止频率（相对奈奎斯特频率的比例，0-1）
+    #[serde(default)]
+    pub window: Option<String>, // FFT前的窗函数: "hann", "hamming", "blackman", "tukey", "rectangular"
+    #[serde(default)]
+    pub tukey_alpha: Option<f64>, // Tukey窗锥形比例
+    #[serde(default)]
+    pub window_size: Option<usize>, // 中值滤波/维纳滤波窗口大小（奇数）
+    #[serde(default)]
+    pub noise_region: Option<(f64, f64)>, // 维纳滤波估计噪声用的纯噪声区间
 }
 
 // 噪声降低结果结构
@@ -92,6 +105,26 @@ pub struct NoiseReductionResult {
     pub message: String,
 }
 
+// 二阶导数参数结构
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SecondDerivativeParams {
+    pub curve: CurveData,
+    pub window_size: Option<usize>, // Savitzky-Golay窗口大小（奇数）
+    pub polynomial_order: Option<usize>, // Savitzky-Golay多项式阶数
+}
+
+// 二阶导数结果结构
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SecondDerivativeResult {
+    pub success: bool,
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    pub d2: Vec<f64>,
+    pub tsv: String, // X, Y, D2 三列TSV
+    pub processing_time: u64,
+    pub message: String,
+}
+
 /// 基线校正处理
 #[tauri::command]
 pub async fn baseline_correction(params: BaselineCorrectionParams, _app: tauri::AppHandle, state: State<'_, AppStateManager>) -> Result<BaselineCorrectionResult, String> {
@@ -123,6 +156,9 @@ pub async fn baseline_correction(params: BaselineCorrectionParams, _app: tauri::
         "preserve_original": true,
         "output_baseline": true
     });
+    if let Some(negative_handling) = &params.negative_handling {
+        config["negative_handling"] = serde_json::json!(negative_handling);
+    }
     
     // 添加方法特定的参数
     match params.method.as_str() {
@@ -303,7 +339,7 @@ pub async fn smooth_data(params: SmoothDataParams, _app: tauri::AppHandle, state
     
     // 加载原始数据
     log::info!("🔄 加载原始数据...");
-    let _container = match DataLoader::load_from_file(&params.file_path) {
+    let container = match DataLoader::load_from_file(&params.file_path) {
         Ok(container) => {
             log::info!("✅ 数据加载成功: {} 条曲线", container.curves.len());
             container
@@ -333,8 +369,12 @@ pub async fn smooth_data(params: SmoothDataParams, _app: tauri::AppHandle, state
             log::info!("📊 使用Savitzky-Golay方法");
             if let Some(polynomial_order) = params.polynomial_order {
                 log::info!("📊 多项式阶数: {}", polynomial_order);
-                // 这里应该调用真实的Savitzky-Golay处理器
-                Err("Savitzky-Golay处理器尚未实现".to_string())
+                // 未指定窗口时按最窄峰宽自动选择
+                let config = serde_json::json!({
+                    "window_size": params.window_size.map(|w| serde_json::json!(w)).unwrap_or_else(|| serde_json::json!("auto")),
+                    "polynomial_order": polynomial_order,
+                });
+                smooth_tic_savitzky_golay(&container, &params.file_path, &config).map_err(|e| e.to_string())
             } else {
                 Err("Savitzky-Golay方法需要指定多项式阶数".to_string())
             }
@@ -371,6 +411,11 @@ pub async fn smooth_data(params: SmoothDataParams, _app: tauri::AppHandle, state
         Ok((smoothed_curve, _smoothing_factor)) => {
             log::info!("✅ 数据平滑成功: {} 个数据点", smoothed_curve.metadata.total_points);
             app_state.add_message("success", "数据平滑完成", &format!("使用 {} 方法完成数据平滑", params.method));
+            
+            let stage_config = serde_json::to_value(&params).unwrap_or_default();
+            let result_id = state.record_provenance(params.result_id.as_deref(), Some(&params.file_path), "smooth", stage_config)
+                .map_err(|e| log::warn!("⚠️ 溯源记录失败: {}", e))
+                .ok();
     
             Ok(SmoothDataResult {
                 success: true,
@@ -378,6 +423,7 @@ pub async fn smooth_data(params: SmoothDataParams, _app: tauri::AppHandle, state
                 smoothing_method: params.method,
                 processing_time,
                 message: "数据平滑成功".to_string(),
+                result_id,
             })
         }
         Err(e) => {
@@ -393,22 +439,26 @@ pub async fn smooth_data(params: SmoothDataParams, _app: tauri::AppHandle, state
 pub async fn noise_reduction(params: NoiseReductionParams, _app: tauri::AppHandle, state: State<'_, AppStateManager>) -> Result<NoiseReductionResult, String> {
     log::info!("🔇 开始噪声降低: {} - {}", params.file_path, params.method);
     
-    let mut app_state = state.lock();
-    
-    app_state.add_message("info", "噪声降低", &format!("开始噪声降低: {} - {}", params.file_path, params.method));
+    {
+        let mut app_state = state.lock();
+        app_state.add_message("info", "噪声降低", &format!("开始噪声降低: {} - {}", params.file_path, params.method));
+    }
     
     let start_time = std::time::Instant::now();
     
     // 加载原始数据
     log::info!("🔄 加载原始数据...");
-    let _container = match DataLoader::load_from_file(&params.file_path) {
+    let container = match DataLoader::load_from_file(&params.file_path) {
         Ok(container) => {
             log::info!("✅ 数据加载成功: {} 条曲线", container.curves.len());
             container
         }
         Err(e) => {
             log::error!("❌ 数据加载失败: {}", e);
-            app_state.add_message("error", "噪声降低失败", &format!("无法加载文件: {}", e));
+            {
+                let mut app_state = state.lock();
+                app_state.add_message("error", "噪声降低失败", &format!("无法加载文件: {}", e));
+            }
             return Err(format!("无法加载文件: {}", e));
         }
     };
@@ -416,50 +466,26 @@ pub async fn noise_reduction(params: NoiseReductionParams, _app: tauri::AppHandl
     // 使用真实的噪声降低算法
     log::info!("🔄 使用 {} 方法进行噪声降低", params.method);
     
-    let result = match params.method.as_str() {
-        "wavelet" => {
-            log::info!("📊 使用小波变换方法");
-            if let Some(wavelet_type) = &params.wavelet_type {
-                log::info!("📊 小波类型: {}", wavelet_type);
-            }
-            if let Some(decomposition_level) = params.decomposition_level {
-                log::info!("📊 分解层数: {}", decomposition_level);
-            }
-            if let Some(threshold) = params.threshold {
-                log::info!("📊 阈值: {}", threshold);
-            }
-            // 这里应该调用真实的小波变换处理器
-            Err::<(CurveData, f64), String>("小波变换处理器尚未实现".to_string())
-        }
-        "fourier" => {
-            log::info!("📊 使用傅里叶变换方法");
-            if let Some(cutoff_frequency) = params.cutoff_frequency {
-                log::info!("📊 截止频率: {}", cutoff_frequency);
-            }
-            // 这里应该调用真实的傅里叶变换处理器
-            Err("傅里叶变换处理器尚未实现".to_string())
-        }
-        "median_filter" => {
-            log::info!("📊 使用中值滤波方法");
-            if let Some(threshold) = params.threshold {
-                log::info!("📊 阈值: {}", threshold);
-            }
-            // 这里应该调用真实的中值滤波处理器
-            Err("中值滤波处理器尚未实现".to_string())
-        }
-        "wiener_filter" => {
-            log::info!("📊 使用维纳滤波方法");
-            if let Some(threshold) = params.threshold {
-                log::info!("📊 阈值: {}", threshold);
-            }
-            // 这里应该调用真实的维纳滤波处理器
-            Err("维纳滤波处理器尚未实现".to_string())
-        }
-        _ => {
-            log::error!("❌ 未知的噪声降低方法: {}", params.method);
-            Err(format!("未知的噪声降低方法: {}", params.method))
-        }
-    };
+    if let Some(wavelet_type) = &params.wavelet_type {
+        log::info!("📊 小波类型: {}", wavelet_type);
+    }
+    if let Some(threshold) = params.threshold {
+        log::info!("📊 阈值: {}", threshold);
+    }
+    let config = serde_json::json!({
+        "method": params.method,
+        "threshold": params.threshold,
+        "wavelet_type": params.wavelet_type,
+        "decomposition_level": params.decomposition_level,
+        "cutoff_frequency": params.cutoff_frequency,
+        "window": params.window,
+        "tukey_alpha": params.tukey_alpha,
+        "window_size": params.window_size,
+        "noise_region": params.noise_region,
+    });
+    let result = denoise_tic(&container, &params.file_path, &params.method, config)
+        .await
+        .map_err(|e| e.to_string());
     
     let processing_time = start_time.elapsed().as_millis() as u64;
     
@@ -467,7 +493,10 @@ pub async fn noise_reduction(params: NoiseReductionParams, _app: tauri::AppHandl
         Ok((denoised_curve, snr_improvement)) => {
             log::info!("✅ 噪声降低成功: {} 个数据点, SNR提升: {:.2}", 
                 denoised_curve.metadata.total_points, snr_improvement);
-            app_state.add_message("success", "噪声降低完成", &format!("使用 {} 方法完成噪声降低", params.method));
+            {
+                let mut app_state = state.lock();
+                app_state.add_message("success", "噪声降低完成", &format!("使用 {} 方法完成噪声降低", params.method));
+            }
     
             Ok(NoiseReductionResult {
                 success: true,
@@ -480,8 +509,135 @@ pub async fn noise_reduction(params: NoiseReductionParams, _app: tauri::AppHandl
         }
         Err(e) => {
             log::error!("❌ 噪声降低失败: {}", e);
-            app_state.add_message("error", "噪声降低失败", &e);
+            {
+                let mut app_state = state.lock();
+                app_state.add_message("error", "噪声降低失败", &e);
+            }
             Err(e)
         }
     }
 }
+
+/// 用噪声降低处理器对文件的MS1 TIC降噪，返回降噪后的曲线及噪声降低倍数
+async fn denoise_tic(
+    container: &crate::core::data::DataContainer,
+    file_path: &str,
+    method: &str,
+    config: serde_json::Value,
+) -> Result<(CurveData, f64), crate::core::data::ProcessingError> {
+    let processor = crate::core::processors::noise_reduction::NoiseReductionProcessor::create(method)?;
+    let tic = container.compute_tic(Some(1))
+        .ok_or_else(|| crate::core::data::ProcessingError::DataError("文件中没有MS1光谱，无法计算TIC".to_string()))?;
+    let input = crate::core::data::DataContainer {
+        metadata: container.metadata.clone(),
+        spectra: Vec::new(),
+        curves: vec![tic],
+    };
+    
+    let result = processor.process(input, config).await?;
+    let curve = result.curves.into_iter().next()
+        .ok_or_else(|| crate::core::data::ProcessingError::ProcessError("噪声降低未返回曲线".to_string()))?;
+    let snr_improvement = result.metadata.get("snr_improvements")
+        .and_then(|v| v.get(0))
+        .and_then(|v| v["snr_improvement"].as_f64())
+        .unwrap_or(1.0);
+    
+    Ok((to_curve_data(&curve, format!("{}_{}_denoised", file_path, method)), snr_improvement))
+}
+
+/// 用 Savitzky-Golay 平滑文件的MS1 TIC，返回平滑后的曲线及使用的窗口大小
+fn smooth_tic_savitzky_golay(
+    container: &crate::core::data::DataContainer,
+    file_path: &str,
+    config: &serde_json::Value,
+) -> Result<(CurveData, f64), crate::core::data::ProcessingError> {
+    let tic = container.compute_tic(Some(1))
+        .ok_or_else(|| crate::core::data::ProcessingError::DataError("文件中没有MS1光谱，无法计算TIC".to_string()))?;
+    let processor = crate::core::processors::noise_reduction::NoiseReductionProcessor::new("savitzky_golay");
+    let smoothed = processor.denoise(&tic, config)?;
+    let curve = crate::core::data::Curve::new(
+        tic.id.clone(),
+        tic.curve_type.clone(),
+        tic.x_values.clone(),
+        smoothed,
+        tic.x_label.clone(),
+        tic.y_label.clone(),
+        tic.x_unit.clone(),
+        tic.y_unit.clone(),
+    );
+    let window_size = config["window_size"].as_f64()
+        .unwrap_or_else(|| crate::core::processors::noise_reduction::auto_savitzky_golay_window(&tic, config["polynomial_order"].as_u64().unwrap_or(2) as usize) as f64);
+    
+    Ok((to_curve_data(&curve, format!("{}_savitzky_golay_smoothed", file_path)), window_size))
+}
+
+/// 转换为前端使用的曲线数据
+fn to_curve_data(curve: &crate::core::data::Curve, file_name: String) -> CurveData {
+    let data_points: Vec<DTCurvePoint> = curve.x_values.iter()
+        .zip(curve.y_values.iter())
+        .map(|(&x, &y)| DTCurvePoint { drift_time: x, intensity: y })
+        .collect();
+    
+    CurveData {
+        file_name,
+        curve_type: curve.curve_type.clone(),
+        data_points,
+        metadata: CurveMetadata {
+            total_points: curve.point_count,
+            rt_range: (curve.x_min, curve.x_max),
+            intensity_range: (curve.y_min, curve.y_max),
+            max_intensity: curve.y_max,
+            max_intensity_rt: curve.x_values[curve.y_values.iter().position(|&y| y == curve.y_max).unwrap_or(0)],
+        },
+    }
+}
+
+/// 计算Savitzky-Golay二阶导数，用于发现隐藏的肩峰
+#[tauri::command]
+pub async fn compute_second_derivative(params: SecondDerivativeParams, _app: tauri::AppHandle, state: State<'_, AppStateManager>) -> Result<SecondDerivativeResult, String> {
+    let window_size = params.window_size.unwrap_or(11);
+    let polynomial_order = params.polynomial_order.unwrap_or(3);
+    log::info!("📉 开始计算二阶导数: 窗口={}, 阶数={}", window_size, polynomial_order);
+    
+    let start_time = std::time::Instant::now();
+    
+    let x: Vec<f64> = params.curve.data_points.iter().map(|p| p.drift_time).collect();
+    let y: Vec<f64> = params.curve.data_points.iter().map(|p| p.intensity).collect();
+    
+    let d2 = match crate::core::utils::signal::savitzky_golay(
+        &y,
+        window_size,
+        polynomial_order,
+        2,
+        crate::core::utils::signal::mean_spacing(&x),
+    ) {
+        Ok(d2) => d2,
+        Err(e) => {
+            let mut app_state = state.lock();
+            app_state.add_message("error", "二阶导数计算失败", &format!("错误: {}", e));
+            return Err(format!("二阶导数计算失败: {}", e));
+        }
+    };
+    
+    let mut tsv = String::from("X\tY\tD2\n");
+    for ((x, y), d2) in x.iter().zip(y.iter()).zip(d2.iter()) {
+        tsv.push_str(&format!("{:.6}\t{:.6}\t{:.6}\n", x, y, d2));
+    }
+    
+    let processing_time = start_time.elapsed().as_millis() as u64;
+    
+    {
+        let mut app_state = state.lock();
+        app_state.add_message("success", "二阶导数计算完成", &format!("{} 个数据点，耗时 {}ms", d2.len(), processing_time));
+    }
+    
+    Ok(SecondDerivativeResult {
+        success: true,
+        x,
+        y,
+        d2,
+        tsv,
+        processing_time,
+        message: "二阶导数计算成功".to_string(),
+    })
+}
@@ -16,7 +16,23 @@ pub struct PlotGenerationParams {
     pub show_peaks: bool,
     pub show_baseline: bool,
     pub color_scheme: String,
+    pub show_grid: Option<bool>,
+    pub show_legend: Option<bool>,
     pub title: Option<String>,
+    #[serde(default)]
+    pub heatmap_interpolation: Option<String>, // "none" | "nearest" | "linear"
+    #[serde(default)]
+    pub log_intensity: Option<bool>,
+    #[serde(default)]
+    pub annotate_peaks: Option<bool>,
+    #[serde(default)]
+    pub annotation_template: Option<String>, // "center" | "area" | "label"
+    #[serde(default)]
+    pub show_noise_band: Option<bool>,
+    #[serde(default)]
+    pub noise_band_sigma: Option<f64>, // 噪声带半宽（σ 的倍数），默认 3
+    #[serde(default)]
+    pub max_points_per_trace: Option<u64>, // 每条曲线最多绘制的点数，默认 5000
 }
 
 // 可视化结果结构
@@ -59,20 +75,8 @@ pub async fn generate_plot(params: PlotGenerationParams, _app: tauri::AppHandle,
     // 生成唯一的图表ID
     let plot_id = format!("plot_{}", Uuid::new_v4());
     
-    // 使用真实的ExportManager生成Plotly数据
-    let export_manager = crate::core::exporters::export_manager::ExportManager::new();
-    
-    // 加载数据
-    let container = match DataLoader::load_from_file(&params.file_path) {
-        Ok(container) => container,
-        Err(e) => {
-            {
-                let mut app_state = state.lock();
-                app_state.add_message("error", "图表生成失败", &format!("无法加载文件: {}", e));
-            }
-            return Err(format!("无法加载文件: {}", e));
-        }
-    };
+    // 使用共享的ExportManager生成Plotly数据；文件与配置未变时直接命中导出缓存，不重新加载文件
+    let export_manager = state.export_manager();
     
     // 准备Plotly导出配置
     let export_config = serde_json::json!({
@@ -82,15 +86,25 @@ pub async fn generate_plot(params: PlotGenerationParams, _app: tauri::AppHandle,
         "chart_type": params.plot_type,
         "show_peaks": params.show_peaks,
         "show_fit": false,
+        "color_scheme": params.color_scheme,
+        "show_grid": params.show_grid.unwrap_or(true),
+        "show_legend": params.show_legend.unwrap_or(true),
         "title": params.title.clone().unwrap_or_else(|| "IMS Data Visualization".to_string()),
         "x_axis_title": "Drift Time (ms)",
         "y_axis_title": "Intensity",
         "width": 1000,
-        "height": 600
+        "height": 600,
+        "heatmap_interpolation": params.heatmap_interpolation.clone().unwrap_or_else(|| "none".to_string()),
+        "log_intensity": params.log_intensity.unwrap_or(false),
+        "annotate_peaks": params.annotate_peaks.unwrap_or(false),
+        "annotation_template": params.annotation_template.clone().unwrap_or_else(|| "center".to_string()),
+        "show_noise_band": params.show_noise_band.unwrap_or(false),
+        "noise_band_sigma": params.noise_band_sigma.unwrap_or(3.0),
+        "max_points_per_trace": params.max_points_per_trace.unwrap_or(5000)
     });
     
     // 生成Plotly数据
-    match export_manager.export("plotly", &container, export_config).await {
+    match export_manager.export_file("plotly", &params.file_path, export_config).await {
         Ok(result) => {
             // 解析Plotly JSON数据
             let plotly_json: serde_json::Value = match serde_json::from_slice(&result.data) {
@@ -114,7 +128,7 @@ pub async fn generate_plot(params: PlotGenerationParams, _app: tauri::AppHandle,
                     title: params.title.clone().unwrap_or_else(|| "IMS Data Visualization".to_string()),
                     x_axis_label: "Drift Time (ms)".to_string(),
                     y_axis_label: "Intensity".to_string(),
-                    data_points: container.curves.iter().map(|c| c.point_count).sum(),
+                    data_points: result.metadata.get("source_point_count").and_then(|v| v.as_u64()).unwrap_or(0) as usize,
                     generated_at: chrono::Utc::now().to_rfc3339(),
                     file_path: params.file_path.clone(),
                 },
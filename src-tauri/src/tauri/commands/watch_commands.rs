@@ -0,0 +1,190 @@
+//! 文件夹自动处理相关命令
+
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, State};
+use crate::tauri::state::AppStateManager;
+use crate::core::data::{AxisLabels, DataContainer, ProcessingError};
+use crate::core::loaders::mzdata_loader::DataLoader;
+use crate::core::processors::peak_analysis::{AnalysisRecipe, PeakAnalyzer, RecipeRunResult};
+use crate::core::utils::folder_watcher::{FolderWatcher, DEFAULT_STABILITY_INTERVAL};
+
+/// 监视文件夹中的文件处理完成后发送的事件名
+pub const FILE_PROCESSED_EVENT: &str = "file-processed";
+
+/// `file-processed` 事件内容；加载或提取失败时 `run` 为空，`error` 给出原因
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileProcessedEvent {
+    pub file_path: String,
+    pub recipe: String,
+    pub run: Option<RecipeRunResult>,
+    pub error: Option<String>,
+}
+
+/// 元数据中的数值范围，格式为 "min-max"
+fn metadata_range(container: &DataContainer, min_key: &str, max_key: &str) -> Result<String, ProcessingError> {
+    let value = |key: &str| container.metadata.get(key)
+        .and_then(|v| v.as_f64())
+        .ok_or_else(|| ProcessingError::DataError(format!("文件元数据缺少 {}", key)));
+    Ok(format!("{}-{}", value(min_key)?, value(max_key)?))
+}
+
+/// 加载文件，在完整 RT / m/z 范围内提取指定 MS 级别的曲线后按配方分析
+///
+/// 文件加载是阻塞读取，放到阻塞线程池中执行，不占用异步运行时的工作线程
+async fn process_watched_file(path: &Path, recipe: &AnalysisRecipe, curve_type: &str, ms_level: u8) -> Result<RecipeRunResult, ProcessingError> {
+    let file_path = path.to_string_lossy().to_string();
+    let container = tauri::async_runtime::spawn_blocking(move || DataLoader::load_from_file(&file_path))
+        .await
+        .map_err(|e| ProcessingError::ProcessError(format!("文件加载任务异常终止: {}", e)))??;
+    let mz_range = metadata_range(&container, "mz_min", "mz_max")?;
+    let rt_range = metadata_range(&container, "rt_min", "rt_max")?;
+    let extracted = crate::core::processors::overlay_extractor::extract_by_type(
+        container,
+        curve_type,
+        &mz_range,
+        &rt_range,
+        ms_level,
+        &AxisLabels::default(),
+    ).await?;
+    let container = DataContainer {
+        metadata: extracted.metadata,
+        spectra: Vec::new(),
+        curves: extracted.curves,
+    };
+
+    PeakAnalyzer::process_many(vec![container], recipe).await
+        .pop()
+        .ok_or_else(|| ProcessingError::ProcessError("峰分析未返回结果".to_string()))
+}
+
+/// 开始监视文件夹，每个写入完成的 mzML 文件处理后把 `file-processed` 事件内容交给 `emit`
+fn start_auto_processing<E>(
+    dir: &Path,
+    recipe: AnalysisRecipe,
+    curve_type: String,
+    ms_level: u8,
+    stability_interval: std::time::Duration,
+    emit: E,
+) -> Result<FolderWatcher, ProcessingError>
+where
+    E: Fn(FileProcessedEvent) + Clone + Send + 'static,
+{
+    FolderWatcher::start(dir, stability_interval, move |file| {
+        let recipe = recipe.clone();
+        let curve_type = curve_type.clone();
+        let emit = emit.clone();
+        tauri::async_runtime::spawn(async move {
+            let file_path = file.to_string_lossy().to_string();
+            log::info!("🔄 自动处理文件: {}", file_path);
+            let event = match process_watched_file(&file, &recipe, &curve_type, ms_level).await {
+                Ok(run) => FileProcessedEvent {
+                    file_path,
+                    recipe: recipe.name.clone(),
+                    error: run.error.clone(),
+                    run: Some(run),
+                },
+                Err(e) => {
+                    log::warn!("⚠️ 自动处理失败 {}: {}", file_path, e);
+                    FileProcessedEvent {
+                        file_path,
+                        recipe: recipe.name.clone(),
+                        run: None,
+                        error: Some(e.to_string()),
+                    }
+                }
+            };
+            emit(event);
+        });
+    })
+}
+
+/// 自动处理模式：监视文件夹，新写入完成的 mzML 文件按配方处理并发送 `file-processed` 事件
+///
+/// 同一时间只监视一个文件夹，再次调用会替换原有的监视。`curve_type` 默认为 "dt"，`ms_level` 默认为 1，
+/// `stability_interval_ms` 为判断文件写入完成的大小稳定检查间隔
+#[tauri::command]
+pub async fn watch_folder(
+    path: String,
+    recipe: AnalysisRecipe,
+    curve_type: Option<String>,
+    ms_level: Option<u8>,
+    stability_interval_ms: Option<u64>,
+    app: tauri::AppHandle,
+    state: State<'_, AppStateManager>
+) -> Result<(), String> {
+    let curve_type = curve_type.unwrap_or_else(|| "dt".to_string());
+    let stability_interval = stability_interval_ms
+        .map(std::time::Duration::from_millis)
+        .unwrap_or(DEFAULT_STABILITY_INTERVAL);
+    let recipe_name = recipe.name.clone();
+
+    let watcher = start_auto_processing(
+        Path::new(&path),
+        recipe,
+        curve_type,
+        ms_level.unwrap_or(1),
+        stability_interval,
+        move |event| {
+            let _ = app.emit(FILE_PROCESSED_EVENT, &event);
+        },
+    ).map_err(|e| format!("无法监视文件夹: {}", e))?;
+
+    state.set_folder_watcher(watcher).map_err(|e| format!("无法监视文件夹: {}", e))?;
+    state.lock().add_message("info", "自动处理", &format!("开始监视 {}，配方 {}", path, recipe_name));
+    Ok(())
+}
+
+/// 停止自动处理模式，返回被停止监视的文件夹；未在监视时返回 None
+#[tauri::command]
+pub async fn stop_watching(
+    state: State<'_, AppStateManager>
+) -> Result<Option<String>, String> {
+    let dir = state.stop_folder_watcher();
+    if let Some(dir) = &dir {
+        state.lock().add_message("info", "自动处理", &format!("停止监视 {}", dir));
+    }
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+    use crate::core::utils::test_fixtures::write_ms1_run;
+
+    /// 在保留时间上呈高斯分布的 MS1 运行
+    fn write_run(path: &Path) {
+        write_ms1_run(path, 40, 0.05, |i| {
+            let rt = i as f64 * 0.05;
+            vec![(500.0, (10.0 + 1000.0 * (-(rt - 1.0).powi(2) / (2.0 * 0.1 * 0.1)).exp()) as f32)]
+        });
+    }
+
+    #[test]
+    fn new_file_emits_one_file_processed_event() {
+        let dir = std::env::temp_dir().join(format!("mz_curve_auto_process_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        let recipe = AnalysisRecipe { name: "watch".to_string(), ..AnalysisRecipe::default() };
+        let mut watcher = start_auto_processing(&dir, recipe, "tic".to_string(), 1, Duration::from_millis(200), move |event| {
+            let _ = tx.send(event);
+        }).unwrap();
+
+        let path = dir.join("run_1.mzML");
+        write_run(&path);
+
+        let event: FileProcessedEvent = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(event.file_path, path.to_string_lossy());
+        assert_eq!(event.recipe, "watch");
+        assert_eq!(event.error, None);
+        assert!(!event.run.unwrap().curves.is_empty());
+        assert!(rx.recv_timeout(Duration::from_millis(800)).is_err());
+
+        watcher.stop();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,309 @@
+//! 结构化日志
+//!
+//! `tracing` 事件与 `log` 宏记录统一转换为 `LogEvent`，作为 `log-event` Tauri 事件发送到前端，
+//! 前端按 `level` 过滤显示。只有本 crate 的记录会发送到前端，依赖库（tauri、wry 等）的日志不进入界面；
+//! 调试构建下同时回显到标准输出。`AppState::add_message` 的用户消息使用 `USER_MESSAGE_TARGET`，
+//! 不受级别过滤，与原来的 `log-message` 事件一样总会送达界面
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::Emitter;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+
+/// 前端监听的日志事件名
+pub const LOG_EVENT: &str = "log-event";
+
+/// 设置日志级别的环境变量（error / warn / info / debug / trace），默认 info
+pub const LOG_LEVEL_ENV: &str = "MZ_CURVE_LOG";
+
+/// 用户消息（`AppState::add_message`）的日志目标，不受级别过滤
+pub const USER_MESSAGE_TARGET: &str = "mz_curve_gui_lib::user_message";
+
+/// 发送到前端的日志目标前缀
+const EMITTED_TARGET_PREFIX: &str = "mz_curve_gui_lib";
+
+/// 发送到前端的结构化日志
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEvent {
+    /// "error" | "warn" | "info" | "debug" | "trace"
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    pub fields: HashMap<String, Value>,
+    pub timestamp: String,
+}
+
+/// 日志级别的详细程度，数值越大越详细
+fn verbosity(level: &str) -> u8 {
+    match level {
+        "error" => 0,
+        "warn" => 1,
+        "info" => 2,
+        "debug" => 3,
+        _ => 4,
+    }
+}
+
+fn tracing_level_name(level: &Level) -> &'static str {
+    match *level {
+        Level::ERROR => "error",
+        Level::WARN => "warn",
+        Level::INFO => "info",
+        Level::DEBUG => "debug",
+        Level::TRACE => "trace",
+    }
+}
+
+fn log_level_name(level: log::Level) -> &'static str {
+    match level {
+        log::Level::Error => "error",
+        log::Level::Warn => "warn",
+        log::Level::Info => "info",
+        log::Level::Debug => "debug",
+        log::Level::Trace => "trace",
+    }
+}
+
+/// 收集事件字段，`message` 字段单独保存
+#[derive(Default)]
+struct FieldCollector {
+    message: String,
+    fields: HashMap<String, Value>,
+}
+
+impl FieldCollector {
+    fn insert(&mut self, field: &Field, value: Value) {
+        if field.name() == "message" {
+            self.message = match value {
+                Value::String(s) => s,
+                other => other.to_string(),
+            };
+        } else {
+            self.fields.insert(field.name().to_string(), value);
+        }
+    }
+}
+
+impl Visit for FieldCollector {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, serde_json::json!(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, serde_json::json!(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, serde_json::json!(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, Value::Bool(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, Value::String(value.to_string()));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.insert(field, Value::String(format!("{:?}", value)));
+    }
+}
+
+/// 结构化日志器：同时实现 `tracing::Subscriber` 与 `log::Log`，把目标匹配的记录交给 sink
+#[derive(Clone)]
+pub struct StructuredLogger {
+    max_verbosity: u8,
+    sink: Arc<dyn Fn(&LogEvent) + Send + Sync>,
+    next_span_id: Arc<AtomicU64>,
+    /// 只有目标以这些前缀开头的记录交给 sink，为空时不限制
+    target_prefixes: Vec<String>,
+    /// 是否同时回显到标准输出
+    echo_stdout: bool,
+}
+
+impl StructuredLogger {
+    /// `max_level` 为最详细的输出级别，如 "info"
+    pub fn new(max_level: &str, sink: impl Fn(&LogEvent) + Send + Sync + 'static) -> Self {
+        Self {
+            max_verbosity: verbosity(max_level),
+            sink: Arc::new(sink),
+            next_span_id: Arc::new(AtomicU64::new(1)),
+            target_prefixes: Vec::new(),
+            echo_stdout: false,
+        }
+    }
+
+    /// 只把目标以 `prefixes` 之一开头的记录交给 sink
+    pub fn with_target_prefixes(mut self, prefixes: &[&str]) -> Self {
+        self.target_prefixes = prefixes.iter().map(|p| p.to_string()).collect();
+        self
+    }
+
+    /// 同时把记录回显到标准输出
+    pub fn with_stdout_echo(mut self, echo: bool) -> Self {
+        self.echo_stdout = echo;
+        self
+    }
+
+    fn is_enabled(&self, level: &str, target: &str) -> bool {
+        target == USER_MESSAGE_TARGET || verbosity(level) <= self.max_verbosity
+    }
+
+    fn emits_target(&self, target: &str) -> bool {
+        self.target_prefixes.is_empty() || self.target_prefixes.iter().any(|p| target.starts_with(p.as_str()))
+    }
+
+    fn dispatch(&self, event: LogEvent) {
+        if self.echo_stdout {
+            let fields = if event.fields.is_empty() {
+                String::new()
+            } else {
+                format!(" {}", serde_json::to_string(&event.fields).unwrap_or_default())
+            };
+            println!("{} {:>5} {}: {}{}", event.timestamp, event.level.to_uppercase(), event.target, event.message, fields);
+        }
+        if self.emits_target(&event.target) {
+            (self.sink)(&event);
+        }
+    }
+}
+
+impl Subscriber for StructuredLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.is_enabled(tracing_level_name(metadata.level()), metadata.target())
+    }
+
+    // 只记录事件，span 仅分配 ID 以满足接口
+    fn new_span(&self, _span: &Attributes<'_>) -> Id {
+        Id::from_u64(self.next_span_id.fetch_add(1, Ordering::Relaxed))
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let metadata = event.metadata();
+        let mut collector = FieldCollector::default();
+        event.record(&mut collector);
+        self.dispatch(LogEvent {
+            level: tracing_level_name(metadata.level()).to_string(),
+            target: metadata.target().to_string(),
+            message: collector.message,
+            fields: collector.fields,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        });
+    }
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+impl log::Log for StructuredLogger {
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        self.is_enabled(log_level_name(metadata.level()), metadata.target())
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        if !log::Log::enabled(self, record.metadata()) {
+            return;
+        }
+        self.dispatch(LogEvent {
+            level: log_level_name(record.level()).to_string(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+            fields: HashMap::new(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        });
+    }
+
+    fn flush(&self) {}
+}
+
+/// 安装全局日志器，本 crate 的日志以 `log-event` 事件发送到前端，调试构建下回显到标准输出
+pub fn init(app_handle: tauri::AppHandle) {
+    let max_level = std::env::var(LOG_LEVEL_ENV).unwrap_or_else(|_| "info".to_string());
+    let logger = StructuredLogger::new(&max_level, move |event| {
+        let _ = app_handle.emit(LOG_EVENT, event);
+    })
+    .with_target_prefixes(&[EMITTED_TARGET_PREFIX])
+    .with_stdout_echo(cfg!(debug_assertions));
+
+    log::set_max_level(match verbosity(&max_level) {
+        0 => log::LevelFilter::Error,
+        1 => log::LevelFilter::Warn,
+        2 => log::LevelFilter::Info,
+        3 => log::LevelFilter::Debug,
+        _ => log::LevelFilter::Trace,
+    });
+    if log::set_boxed_logger(Box::new(logger.clone())).is_err() {
+        eprintln!("log 日志器已被设置，跳过");
+    }
+    if tracing::subscriber::set_global_default(logger).is_err() {
+        eprintln!("tracing 订阅器已被设置，跳过");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn error_event_reaches_sink_with_level_and_message() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let captured = events.clone();
+        let logger = StructuredLogger::new("info", move |event| captured.lock().unwrap().push(event.clone()));
+
+        tracing::subscriber::with_default(logger.clone(), || {
+            tracing::error!(file = "run_1.mzML", spectra = 42_u64, "文件加载失败");
+            tracing::debug!("低于 info，不输出");
+        });
+        log::Log::log(&logger, &log::Record::builder()
+            .level(log::Level::Warn)
+            .target("mz_curve_gui::core")
+            .args(format_args!("旧接口警告"))
+            .build());
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].level, "error");
+        assert_eq!(events[0].message, "文件加载失败");
+        assert_eq!(events[0].fields["file"], "run_1.mzML");
+        assert_eq!(events[0].fields["spectra"], 42);
+        assert_eq!(events[1].level, "warn");
+        assert_eq!(events[1].message, "旧接口警告");
+    }
+
+    #[test]
+    fn dependency_targets_are_not_emitted_and_user_messages_bypass_level() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let captured = events.clone();
+        let logger = StructuredLogger::new("warn", move |event| captured.lock().unwrap().push(event.clone()))
+            .with_target_prefixes(&[EMITTED_TARGET_PREFIX]);
+
+        tracing::subscriber::with_default(logger, || {
+            tracing::error!(target: "wry::webview", "依赖库错误");
+            tracing::info!("低于 warn，不输出");
+            tracing::warn!("本 crate 警告");
+            tracing::info!(target: USER_MESSAGE_TARGET, title = "文件加载成功", kind = "success", "成功加载 3 个光谱");
+        });
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].message, "本 crate 警告");
+        assert_eq!(events[1].target, USER_MESSAGE_TARGET);
+        assert_eq!(events[1].level, "info");
+        assert_eq!(events[1].fields["kind"], "success");
+        assert_eq!(events[1].fields["title"], "文件加载成功");
+    }
+}
@@ -2,6 +2,7 @@
 //! 包含Tauri相关的状态管理和命令
 
 pub mod commands;
+pub mod logging;
 pub mod state;
 
 // 重新导出 - 避免重复导出
@@ -9,7 +10,7 @@ pub use commands::{
     FileInfo, ValidationResult, DataRanges, CurveExtractionParams,
     PeakAnalysisParams, PeakAnalysisResult, BatchProcessingResult, ProgressUpdate,
     ExportResultInfo, ExportParams, CurveDisplayData,
-    load_file, validate_file, clear_file_cache, extract_curve, analyze_peaks, batch_process_files,
+    load_file, validate_file, quick_validate, clear_file_cache, extract_curve, analyze_peaks, batch_process_files,
     get_app_state, update_processing_params, get_processing_status,
     export_curves_to_folder, export_tsv, export_json, export_plot, export_spectro_tsv,
     get_curve_data_for_display, baseline_correction, overlapping_peaks, smooth_data, noise_reduction,
@@ -3,8 +3,15 @@
 
 use serde::{Deserialize, Serialize};
 use std::sync::{Mutex, Arc};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use tauri::Emitter;
 use crate::core::processors::peak_fitting::controllers::PeakProcessingController;
+use crate::core::loaders::indexed_reader::IndexedSpectrumReader;
+use crate::core::utils::folder_watcher::FolderWatcher;
+use crate::core::data::provenance::{Provenance, ProvenanceRegistry};
+use crate::core::exporters::ExportManager;
+use crate::core::processors::calibration::CalibrationCurve;
+use crate::tauri::logging::USER_MESSAGE_TARGET;
 
 /// 应用状态
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,6 +30,9 @@ pub struct AppState {
     pub data_ranges: Option<DataRanges>,
     /// 日志消息
     pub messages: Vec<LogMessage>,
+    /// 最近一次拟合的定量校准曲线
+    #[serde(default)]
+    pub calibration: Option<CalibrationCurve>,
 }
 
 /// 处理状态
@@ -178,11 +188,131 @@ pub struct ProgressUpdate {
     pub percentage: f64,
 }
 
+impl ProgressUpdate {
+    /// 创建进度更新，百分比限制在 0-100 之间
+    pub fn new(current: usize, total: usize, message: &str) -> Self {
+        let current = current.min(total);
+        Self {
+            current,
+            total,
+            message: message.to_string(),
+            percentage: if total > 0 { (current as f64 / total as f64) * 100.0 } else { 0.0 },
+        }
+    }
+}
+
+/// 默认每秒最多发送的进度事件数
+pub const DEFAULT_PROGRESS_EVENTS_PER_SECOND: f64 = 20.0;
+
+/// 进度事件节流：逐点/逐光谱的进度更新合并为限定频率的事件，避免淹没 IPC 通道
+///
+/// 节流状态按任务键分别记录，并发任务交错上报时互不影响。同一任务距上次发送超过最小间隔、
+/// 或百分比前进达到 `min_percent_step` 时发送；100% 与进度回退（该任务重新开始）总是发送。
+/// 被合并掉的最后一次更新保留下来，任务结束时由 `finish` 取出补发
+#[derive(Debug)]
+pub struct ProgressThrottle {
+    state: Mutex<ThrottleState>,
+}
+
+#[derive(Debug)]
+struct ThrottleState {
+    min_interval: std::time::Duration,
+    min_percent_step: Option<f64>,
+    tasks: std::collections::HashMap<String, TaskThrottle>,
+}
+
+/// 单个任务的节流状态
+#[derive(Debug, Default)]
+struct TaskThrottle {
+    last_emit: Option<std::time::Instant>,
+    last_percentage: f64,
+    /// 最近一次未发送的更新
+    held_back: Option<ProgressUpdate>,
+}
+
+impl ProgressThrottle {
+    pub fn new(max_events_per_second: f64, min_percent_step: Option<f64>) -> Self {
+        Self {
+            state: Mutex::new(ThrottleState {
+                min_interval: Self::interval(max_events_per_second),
+                min_percent_step,
+                tasks: std::collections::HashMap::new(),
+            }),
+        }
+    }
+    
+    /// 每秒最多 n 个事件对应的最小间隔，n 非正时不限频率
+    fn interval(max_events_per_second: f64) -> std::time::Duration {
+        if max_events_per_second > 0.0 && max_events_per_second.is_finite() {
+            std::time::Duration::from_secs_f64(1.0 / max_events_per_second)
+        } else {
+            std::time::Duration::ZERO
+        }
+    }
+    
+    /// 修改节流频率与百分比步长
+    pub fn configure(&self, max_events_per_second: f64, min_percent_step: Option<f64>) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.min_interval = Self::interval(max_events_per_second);
+        state.min_percent_step = min_percent_step.filter(|step| *step > 0.0);
+    }
+    
+    /// 判断任务 `task` 的这次更新是否需要发送；发送时记录发送时间与百分比，否则保留为待补发的更新
+    pub fn should_emit(&self, task: &str, progress: &ProgressUpdate) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let (min_interval, min_percent_step) = (state.min_interval, state.min_percent_step);
+        let throttle = state.tasks.entry(task.to_string()).or_default();
+        let now = std::time::Instant::now();
+        let emit = progress.percentage >= 100.0
+            || progress.percentage < throttle.last_percentage
+            || throttle.last_emit.is_none_or(|last| now.duration_since(last) >= min_interval)
+            || min_percent_step.is_some_and(|step| progress.percentage - throttle.last_percentage >= step);
+        if emit {
+            throttle.last_emit = Some(now);
+            throttle.last_percentage = progress.percentage;
+            throttle.held_back = None;
+        } else {
+            throttle.held_back = Some(progress.clone());
+        }
+        emit
+    }
+    
+    /// 结束任务 `task`：清除其节流状态，返回最后一次被合并掉、尚未发送的更新
+    pub fn finish(&self, task: &str) -> Option<ProgressUpdate> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.tasks.remove(task).and_then(|throttle| throttle.held_back)
+    }
+}
+
+impl Default for ProgressThrottle {
+    fn default() -> Self {
+        Self::new(DEFAULT_PROGRESS_EVENTS_PER_SECOND, None)
+    }
+}
+
 /// 状态管理器
 pub struct AppStateManager {
     state: Mutex<AppState>,
     file_cache: Mutex<std::collections::HashMap<String, crate::core::data::container::DataContainer>>,
+    /// 索引读取器缓存，用于对同一文件的重复随机访问
+    indexed_readers: Mutex<std::collections::HashMap<String, Arc<Mutex<IndexedSpectrumReader>>>>,
     peak_processing_controller: Arc<Mutex<Option<PeakProcessingController>>>,
+    /// 批量任务完成计数（并发任务共享）
+    batch_completed: AtomicUsize,
+    /// 批量任务总数
+    batch_total: AtomicUsize,
+    /// 批量任务取消请求
+    batch_cancelled: AtomicBool,
+    /// 曲线导出取消请求，与批量处理互不影响
+    export_cancelled: AtomicBool,
+    /// 自动处理模式的文件夹监视器
+    folder_watcher: Mutex<Option<FolderWatcher>>,
+    /// 处理溯源记录
+    provenance: Mutex<ProvenanceRegistry>,
+    /// 共享的导出管理器，重复导出相同内容时命中其结果缓存
+    export_manager: ExportManager,
+    /// 进度事件节流
+    progress_throttle: ProgressThrottle,
 }
 
 impl AppStateManager {
@@ -190,7 +320,16 @@ impl AppStateManager {
         Self {
             state: Mutex::new(state),
             file_cache: Mutex::new(std::collections::HashMap::new()),
+            indexed_readers: Mutex::new(std::collections::HashMap::new()),
             peak_processing_controller: Arc::new(Mutex::new(None)),
+            batch_completed: AtomicUsize::new(0),
+            batch_total: AtomicUsize::new(0),
+            batch_cancelled: AtomicBool::new(false),
+            export_cancelled: AtomicBool::new(false),
+            folder_watcher: Mutex::new(None),
+            provenance: Mutex::new(ProvenanceRegistry::new()),
+            export_manager: ExportManager::new(),
+            progress_throttle: ProgressThrottle::default(),
         }
     }
     
@@ -198,6 +337,10 @@ impl AppStateManager {
         self.state.lock().unwrap()
     }
     
+    pub fn export_manager(&self) -> &ExportManager {
+        &self.export_manager
+    }
+    
     pub fn try_lock(&self) -> Result<std::sync::MutexGuard<'_, AppState>, std::sync::TryLockError<std::sync::MutexGuard<'_, AppState>>> {
         self.state.try_lock()
     }
@@ -207,22 +350,149 @@ impl AppStateManager {
         let _ = app_handle.emit("status-updated", status);
     }
     
-    /// 发送日志消息事件到前端
-    pub fn emit_log_message(&self, app_handle: &tauri::AppHandle, message: &LogMessage) {
-        let _ = app_handle.emit("log-message", message);
+    /// 发送任务 `task` 的进度更新事件到前端，经节流合并（见 `ProgressThrottle`）
+    pub fn emit_progress_update(&self, app_handle: &tauri::AppHandle, task: &str, current: usize, total: usize, message: &str) {
+        let progress = ProgressUpdate::new(current, total, message);
+        if self.progress_throttle.should_emit(task, &progress) {
+            let _ = app_handle.emit("progress-updated", &progress);
+        }
     }
     
-    /// 发送进度更新事件到前端
-    pub fn emit_progress_update(&self, app_handle: &tauri::AppHandle, current: usize, total: usize, message: &str) {
-        let progress = ProgressUpdate {
-            current,
-            total,
-            message: message.to_string(),
-            percentage: if total > 0 { (current as f64 / total as f64) * 100.0 } else { 0.0 },
-        };
+    /// 任务 `task` 的一个阶段结束：补发被节流合并掉的最后一次更新，使前端停在实际的最终进度
+    pub fn finish_progress(&self, app_handle: &tauri::AppHandle, task: &str) {
+        if let Some(progress) = self.progress_throttle.finish(task) {
+            let _ = app_handle.emit("progress-updated", &progress);
+        }
+    }
+    
+    /// 设置进度事件的最大频率（每秒事件数，非正数不限）和可选的百分比步长
+    pub fn set_progress_throttle(&self, max_events_per_second: f64, min_percent_step: Option<f64>) {
+        self.progress_throttle.configure(max_events_per_second, min_percent_step);
+    }
+    
+    /// 开始新的批量进度统计
+    pub fn start_batch_progress(&self, total: usize) {
+        self.batch_completed.store(0, Ordering::SeqCst);
+        self.batch_total.store(total, Ordering::SeqCst);
+        self.batch_cancelled.store(false, Ordering::SeqCst);
+    }
+    
+    /// 请求取消当前批量任务（当前文件处理完成后停止）
+    pub fn cancel_batch(&self) {
+        self.batch_cancelled.store(true, Ordering::SeqCst);
+    }
+    
+    /// 是否已请求取消批量任务
+    pub fn is_batch_cancelled(&self) -> bool {
+        self.batch_cancelled.load(Ordering::SeqCst)
+    }
+    
+    /// 开始新的曲线导出，清除上一次导出的取消请求
+    pub fn start_export(&self) {
+        self.export_cancelled.store(false, Ordering::SeqCst);
+    }
+    
+    /// 请求取消当前曲线导出（当前文件写完后停止）
+    pub fn cancel_export(&self) {
+        self.export_cancelled.store(true, Ordering::SeqCst);
+    }
+    
+    /// 是否已请求取消曲线导出
+    pub fn is_export_cancelled(&self) -> bool {
+        self.export_cancelled.load(Ordering::SeqCst)
+    }
+    
+    /// 标记一个批量任务完成并返回最新进度
+    /// 
+    /// 计数基于原子自增，多个并发任务同时完成时进度依然单调且不会超过100%
+    pub fn complete_batch_task(&self, message: &str) -> ProgressUpdate {
+        let completed = self.batch_completed.fetch_add(1, Ordering::SeqCst) + 1;
+        ProgressUpdate::new(completed, self.batch_total.load(Ordering::SeqCst), message)
+    }
+    
+    /// 标记一个批量任务完成并发送进度更新事件
+    pub fn emit_batch_task_completed(&self, app_handle: &tauri::AppHandle, message: &str) {
+        let progress = self.complete_batch_task(message);
         let _ = app_handle.emit("progress-updated", &progress);
     }
     
+    /// 获取当前批量进度
+    pub fn batch_progress(&self, message: &str) -> ProgressUpdate {
+        ProgressUpdate::new(
+            self.batch_completed.load(Ordering::SeqCst),
+            self.batch_total.load(Ordering::SeqCst),
+            message,
+        )
+    }
+    
+    /// 替换文件夹监视器，原有的监视器被停止；锁定失败时新的监视器随之停止并返回错误
+    pub fn set_folder_watcher(&self, watcher: FolderWatcher) -> Result<(), String> {
+        let mut current = self.folder_watcher.lock().map_err(|_| "文件夹监视器锁定失败".to_string())?;
+        *current = Some(watcher);
+        Ok(())
+    }
+    
+    /// 停止文件夹监视，返回被停止监视的文件夹
+    pub fn stop_folder_watcher(&self) -> Option<String> {
+        let mut watcher = self.folder_watcher.lock().ok()?.take()?;
+        watcher.stop();
+        Some(watcher.dir().to_string_lossy().to_string())
+    }
+    
+    /// 为文件开始新的处理链并记录加载阶段，返回 result_id
+    pub fn start_provenance(&self, file_path: &str, load_config: serde_json::Value) -> Result<String, String> {
+        let provenance = {
+            let mut registry = self.provenance.lock().map_err(|_| "溯源记录锁定失败".to_string())?;
+            let result_id = registry.start(file_path, load_config).map_err(|e| e.to_string())?;
+            registry.get(&result_id).cloned()
+        };
+        let provenance = provenance.ok_or_else(|| "溯源记录创建失败".to_string())?;
+        self.attach_provenance(&provenance);
+        Ok(provenance.result_id)
+    }
+    
+    /// 在处理链上追加阶段；未给出 result_id 时使用文件当前的工作结果，文件尚无记录时先补记加载阶段
+    pub fn record_provenance(&self, result_id: Option<&str>, file_path: Option<&str>, stage: &str, config: serde_json::Value) -> Result<String, String> {
+        let result_id = match (result_id, file_path) {
+            (Some(result_id), _) => result_id.to_string(),
+            (None, Some(file_path)) => {
+                let current = self.provenance.lock()
+                    .map_err(|_| "溯源记录锁定失败".to_string())?
+                    .result_for_file(file_path)
+                    .map(str::to_string);
+                match current {
+                    Some(result_id) => result_id,
+                    None => self.start_provenance(file_path, serde_json::json!({"file_path": file_path}))?,
+                }
+            }
+            (None, None) => return Err("记录处理阶段需要结果ID或文件路径".to_string()),
+        };
+        
+        let provenance = self.provenance.lock()
+            .map_err(|_| "溯源记录锁定失败".to_string())?
+            .record(&result_id, stage, config)
+            .map_err(|e| e.to_string())?
+            .clone();
+        self.attach_provenance(&provenance);
+        Ok(result_id)
+    }
+    
+    /// 获取结果的完整处理链
+    pub fn get_provenance(&self, result_id: &str) -> Option<Provenance> {
+        self.provenance.lock().ok()?.get(result_id).cloned()
+    }
+    
+    /// 把溯源记录写入已缓存的工作容器
+    fn attach_provenance(&self, provenance: &Provenance) {
+        if let Ok(mut cache) = self.file_cache.lock() {
+            if let Some(container) = cache.get_mut(&provenance.source_file) {
+                if let Err(e) = provenance.attach(container) {
+                    log::warn!("⚠️ 溯源记录写入容器失败: {}", e);
+                }
+            }
+        }
+    }
+    
     /// 缓存文件数据
     pub fn cache_file(&self, file_path: &str, container: crate::core::data::container::DataContainer) {
         if let Ok(mut cache) = self.file_cache.lock() {
@@ -248,6 +518,27 @@ impl AppStateManager {
             cache.clear();
             log::info!("🗑️ 文件缓存已清除");
         }
+        if let Ok(mut readers) = self.indexed_readers.lock() {
+            readers.clear();
+        }
+    }
+    
+    /// 获取缓存的索引读取器，不存在时打开文件并建立索引
+    pub fn get_indexed_reader(&self, file_path: &str) -> Result<Arc<Mutex<IndexedSpectrumReader>>, String> {
+        let mut readers = self.indexed_readers.lock()
+            .map_err(|_| "索引读取器缓存锁定失败".to_string())?;
+        
+        if let Some(reader) = readers.get(file_path) {
+            return Ok(reader.clone());
+        }
+        
+        let reader = crate::core::loaders::mzdata_loader::DataLoader::open_indexed(file_path)
+            .map_err(|e| format!("无法建立光谱索引: {}", e))?;
+        let reader = Arc::new(Mutex::new(reader));
+        readers.insert(file_path.to_string(), reader.clone());
+        log::info!("📦 索引读取器已缓存: {}", file_path);
+        
+        Ok(reader)
     }
     
     /// 初始化峰处理控制器
@@ -293,13 +584,21 @@ impl Default for AppState {
             multi_curve_data: None,
             data_ranges: None,
             messages: Vec::new(),
+            calibration: None,
         }
     }
 }
 
 impl AppState {
-    /// 添加日志消息
+    /// 添加日志消息，同时作为结构化日志事件发送到前端
+    ///
+    /// 使用 `USER_MESSAGE_TARGET`，不受日志级别过滤，总会显示在界面上
     pub fn add_message(&mut self, level: &str, title: &str, content: &str) {
+        match level {
+            "error" => tracing::error!(target: USER_MESSAGE_TARGET, title, kind = level, "{}", content),
+            "warning" => tracing::warn!(target: USER_MESSAGE_TARGET, title, kind = level, "{}", content),
+            _ => tracing::info!(target: USER_MESSAGE_TARGET, title, kind = level, "{}", content),
+        }
         let message = LogMessage {
             id: uuid::Uuid::new_v4().to_string(),
             level: level.to_string(),
@@ -355,3 +654,101 @@ impl AppState {
         self.messages.clear();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_throttle_coalesces_rapid_updates_and_keeps_final() {
+        let throttle = ProgressThrottle::default();
+        let total = 10000;
+        let emitted: Vec<ProgressUpdate> = (1..=total)
+            .map(|current| ProgressUpdate::new(current, total, "processing"))
+            .filter(|progress| throttle.should_emit("load", progress))
+            .collect();
+
+        assert!(emitted.len() < 100, "{} events emitted", emitted.len());
+        assert_eq!(emitted.last().unwrap().percentage, 100.0);
+
+        // 按百分比步长发送时每 10% 至少一个事件
+        throttle.configure(0.001, Some(10.0));
+        let emitted = (0..=total)
+            .map(|current| ProgressUpdate::new(current, total, "processing"))
+            .filter(|progress| throttle.should_emit("load", progress))
+            .count();
+        assert_eq!(emitted, 11);
+    }
+
+    #[test]
+    fn progress_throttle_keeps_interleaved_tasks_apart_and_flushes_held_back_update() {
+        let throttle = ProgressThrottle::new(0.001, None);
+        let total = 1000;
+        // 两个任务交错上报，进度互相“回退”也不触发发送
+        let mut emitted = Vec::new();
+        for current in 1..=600 {
+            for (task, offset) in [("export", 0), ("batch", 300)] {
+                let progress = ProgressUpdate::new(current + offset, total, task);
+                if throttle.should_emit(task, &progress) {
+                    emitted.push((task, progress.current));
+                }
+            }
+        }
+        assert_eq!(emitted, vec![("export", 1), ("batch", 301)]);
+
+        // 任务在 100% 之前结束时补发最后一次被合并的更新，之后没有待发送的更新
+        assert_eq!(throttle.finish("export").map(|progress| progress.current), Some(600));
+        assert!(throttle.finish("export").is_none());
+        assert!(throttle.should_emit("batch", &ProgressUpdate::new(total, total, "batch")));
+        assert!(throttle.finish("batch").is_none());
+    }
+
+    #[tokio::test]
+    async fn parallel_batch_progress_is_exact_and_never_exceeds_100() {
+        use crate::core::utils::batch_checkpoint::{run_batch, BatchCheckpoint};
+
+        let manager = Arc::new(AppStateManager::new(AppState::default()));
+        let files: Vec<String> = (1..=20).map(|i| format!("/data/run_{}.mzML", i)).collect();
+        let path = std::env::temp_dir().join(format!("mz_curve_progress_checkpoint_{}.json", std::process::id()));
+        let observed = Arc::new(Mutex::new(Vec::new()));
+
+        manager.start_batch_progress(files.len());
+        let mut checkpoint = BatchCheckpoint::new(&path);
+        let run = run_batch(&files, &mut checkpoint, 8, || false, |file| {
+            let (manager, observed) = (manager.clone(), observed.clone());
+            async move {
+                tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+                let progress = manager.complete_batch_task(&file);
+                observed.lock().unwrap().push((progress.current, progress.percentage));
+                Ok::<_, String>(())
+            }
+        }).await;
+        assert_eq!(run.processed.len(), 20);
+
+        let mut observed = observed.lock().unwrap().clone();
+        assert!(observed.iter().all(|&(_, percentage)| percentage <= 100.0));
+        observed.sort_by_key(|&(current, _)| current);
+        assert_eq!(observed.iter().map(|&(current, _)| current).collect::<Vec<_>>(), (1..=20).collect::<Vec<_>>());
+        assert_eq!(manager.batch_progress("done").percentage, 100.0);
+    }
+
+    #[test]
+    fn export_cancellation_is_independent_of_batch_processing() {
+        let manager = AppStateManager::new(AppState::default());
+        manager.start_batch_progress(4);
+        manager.complete_batch_task("run_1");
+
+        // 批量处理进行中开始并取消导出，批量计数与取消标志都不受影响
+        manager.start_export();
+        manager.cancel_export();
+        assert!(manager.is_export_cancelled());
+        assert!(!manager.is_batch_cancelled());
+        assert_eq!(manager.batch_progress("running").current, 1);
+        assert_eq!(manager.batch_progress("running").total, 4);
+
+        manager.cancel_batch();
+        manager.start_export();
+        assert!(!manager.is_export_cancelled());
+        assert!(manager.is_batch_cancelled());
+    }
+}
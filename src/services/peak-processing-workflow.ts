@@ -306,7 +306,7 @@ export class PeakProcessingWorkflowManager implements PeakProcessingWorkflow {
       console.log('状态更新事件:', event.payload);
     });
     
-    listen('log-message', (event) => {
+    listen('log-event', (event) => {
       console.log('日志消息事件:', event.payload);
     });
     
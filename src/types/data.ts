@@ -47,6 +47,8 @@ export interface Peak {
   area: number
   rsquared: number
   fit_parameters: Record<string, any>
+  // 重叠峰处理后同一拟合区域内的峰共享簇ID
+  cluster_id?: string
   metadata: Record<string, any>
 }
 
@@ -68,6 +70,7 @@ export interface FileInfo {
   is_valid: boolean
   spectra_count?: number
   data_ranges?: DataRanges
+  result_id?: string
 }
 
 // 日志消息类型
@@ -79,6 +82,26 @@ export interface LogMessage {
   timestamp: string
 }
 
+// 后端结构化日志事件（log-event）
+export type LogLevel = 'error' | 'warn' | 'info' | 'debug' | 'trace'
+
+export interface LogEvent {
+  level: LogLevel
+  target: string
+  message: string
+  fields: Record<string, unknown>
+  timestamp: string
+}
+
+// 日志级别的详细程度，数值越大越详细
+export const LOG_LEVEL_VERBOSITY: Record<LogLevel, number> = {
+  error: 0,
+  warn: 1,
+  info: 2,
+  debug: 3,
+  trace: 4
+}
+
 export interface CurveDisplayData {
   id: string
   curve_type: string